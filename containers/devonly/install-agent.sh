@@ -86,9 +86,16 @@ nodeagent:
   node_ip: "${NODE_IP}"
   grpc_port: 47004
   log_level: "info"
+  heartbeat_interval: 3
   metrics:
     collection_interval: 5
     batch_size: 50
+  container_poll:
+    interval: 1
+    max_backoff: 30
+    # filter:
+    #   names: ["helloworld-*"]
+    #   labels: ["piccolo=true"]
   system:
     hostname: "${NODE_NAME}"
     platform: "$(uname -s)"
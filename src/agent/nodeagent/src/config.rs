@@ -4,14 +4,22 @@
 */
 use if_addrs::{get_if_addrs, Interface};
 use serde::Deserialize;
+use std::collections::HashMap;
 use std::fs::File;
 use std::io::Read;
-use std::path::Path;
-use std::sync::OnceLock;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
 use thiserror::Error;
+use tracing::{info, warn};
 
 // Global config instance
-static NODEAGENT_CONFIG: OnceLock<Config> = OnceLock::new();
+static NODEAGENT_CONFIG: SharedConfig = SharedConfig::new();
+
+/// Heartbeat interval used when the config does not set one, in seconds
+const DEFAULT_HEARTBEAT_INTERVAL: u64 = 3;
+/// Log levels accepted in `log_level`
+const LOG_LEVELS: &[&str] = &["trace", "debug", "info", "warn", "error"];
 
 #[derive(Debug, Error)]
 pub enum ConfigError {
@@ -20,6 +28,9 @@ pub enum ConfigError {
 
     #[error("Failed to parse YAML: {0}")]
     YamlError(#[from] serde_yaml::Error),
+
+    #[error("Invalid config: {0}")]
+    Invalid(String),
 }
 
 #[derive(Debug, Deserialize, Clone, Default, PartialEq)]
@@ -48,10 +59,280 @@ pub struct NodeAgentConfig {
     pub node_ip: String,
     pub grpc_port: u16,
     pub log_level: String,
+    /// Seconds between heartbeats sent to the API server
+    #[serde(default = "default_heartbeat_interval")]
+    pub heartbeat_interval: u64,
     pub metrics: MetricsConfig,
+    #[serde(default)]
+    pub container_poll: ContainerPollConfig,
     pub system: SystemConfig,
     #[serde(default = "default_yaml_storage")]
     pub yaml_storage: String,
+    #[serde(default)]
+    pub volumes: VolumeConfig,
+    #[serde(default)]
+    pub image_pull: ImagePullConfig,
+    #[serde(default)]
+    pub log_tail: LogTailConfig,
+    #[serde(default)]
+    pub bluechi: BluechiConfig,
+}
+
+/// How often the containers of the node are inspected
+#[derive(Debug, Deserialize, Clone, PartialEq)]
+pub struct ContainerPollConfig {
+    /// Seconds between two inspections
+    #[serde(default = "default_container_poll_interval")]
+    pub interval: u64,
+    /// Longest wait in seconds after repeated failed or slow inspections
+    #[serde(default = "default_container_poll_max_backoff")]
+    pub max_backoff: u64,
+    /// Containers to inspect; every container of the node when empty
+    #[serde(default)]
+    pub filter: ContainerFilter,
+}
+
+impl Default for ContainerPollConfig {
+    fn default() -> Self {
+        Self {
+            interval: default_container_poll_interval(),
+            max_backoff: default_container_poll_max_backoff(),
+            filter: ContainerFilter::default(),
+        }
+    }
+}
+
+impl ContainerPollConfig {
+    /// Time between two inspections when the runtime responds normally
+    pub fn interval(&self) -> Duration {
+        Duration::from_secs(self.interval.max(1))
+    }
+
+    /// Wait before the next inspection
+    ///
+    /// The interval doubles with each consecutive failed or slow inspection,
+    /// up to `max_backoff`, so an unavailable runtime is not polled in a
+    /// tight loop.
+    ///
+    /// # Arguments
+    ///
+    /// * `failures` - Consecutive failed or slow inspections, 0 after a
+    ///   normal one
+    pub fn delay(&self, failures: u32) -> Duration {
+        let interval = self.interval();
+        let max = Duration::from_secs(self.max_backoff).max(interval);
+        interval
+            .checked_mul(2_u32.saturating_pow(failures))
+            .map_or(max, |delay| delay.min(max))
+    }
+}
+
+/// Selects the containers inspected on each poll, e.g. only the ones
+/// Pullpiri deployed
+#[derive(Debug, Deserialize, Clone, PartialEq, Default)]
+pub struct ContainerFilter {
+    /// Name patterns, where `*` matches any run of characters and `?` any
+    /// one character, e.g. `helloworld-*`
+    #[serde(default)]
+    pub names: Vec<String>,
+    /// Labels as `key` or `key=pattern`
+    #[serde(default)]
+    pub labels: Vec<String>,
+}
+
+impl ContainerFilter {
+    /// Whether a container passes the filter
+    ///
+    /// A container must match one of the name patterns, if any are set,
+    /// and carry one of the labels, if any are set.
+    ///
+    /// # Arguments
+    ///
+    /// * `names` - Names of the container
+    /// * `labels` - Labels of the container
+    pub fn matches(&self, names: &[String], labels: &HashMap<String, String>) -> bool {
+        let name_matches = self.names.is_empty()
+            || self.names.iter().any(|pattern| {
+                names
+                    .iter()
+                    .any(|name| glob_match(pattern, name.trim_start_matches('/')))
+            });
+        let label_matches = self.labels.is_empty()
+            || self.labels.iter().any(|label| match label.split_once('=') {
+                Some((key, pattern)) => labels
+                    .get(key)
+                    .is_some_and(|value| glob_match(pattern, value)),
+                None => labels.contains_key(label),
+            });
+        name_matches && label_matches
+    }
+}
+
+/// Match `text` against `pattern`, where `*` matches any run of characters
+/// and `?` any one character
+fn glob_match(pattern: &str, text: &str) -> bool {
+    let (pattern, text): (Vec<char>, Vec<char>) =
+        (pattern.chars().collect(), text.chars().collect());
+    let (mut p, mut t) = (0, 0);
+    // Position of the last `*` and of the text it currently covers up to
+    let mut backtrack = None;
+    while t < text.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                backtrack = Some((p, t));
+                p += 1;
+            }
+            Some(&c) if c == '?' || c == text[t] => {
+                p += 1;
+                t += 1;
+            }
+            _ => match backtrack {
+                Some((star, covered)) => {
+                    p = star + 1;
+                    t = covered + 1;
+                    backtrack = Some((star, covered + 1));
+                }
+                None => return false,
+            },
+        }
+    }
+    pattern[p..].iter().all(|&c| c == '*')
+}
+
+fn default_container_poll_interval() -> u64 {
+    1
+}
+
+fn default_container_poll_max_backoff() -> u64 {
+    30
+}
+
+/// Where hostPath volumes are created on this node
+#[derive(Debug, Deserialize, Clone, PartialEq)]
+pub struct VolumeConfig {
+    /// Directory that relative hostPath volume paths are resolved against
+    #[serde(default = "default_volume_base_path")]
+    pub base_path: String,
+    /// Permissions of the directories created for hostPath volumes, e.g. `0o755`
+    #[serde(default = "default_volume_permissions")]
+    pub permissions: u32,
+}
+
+impl Default for VolumeConfig {
+    fn default() -> Self {
+        Self {
+            base_path: default_volume_base_path(),
+            permissions: default_volume_permissions(),
+        }
+    }
+}
+
+/// How the images of an artifact are pulled before it is applied
+#[derive(Debug, Deserialize, Clone, PartialEq)]
+pub struct ImagePullConfig {
+    /// Seconds allowed for pulling all the images of an artifact
+    #[serde(default = "default_image_pull_timeout")]
+    pub timeout: u64,
+}
+
+impl Default for ImagePullConfig {
+    fn default() -> Self {
+        Self {
+            timeout: default_image_pull_timeout(),
+        }
+    }
+}
+
+impl ImagePullConfig {
+    /// Time allowed for pulling all the images of an artifact
+    pub fn timeout(&self) -> Duration {
+        Duration::from_secs(self.timeout.max(1))
+    }
+}
+
+/// How the logs of the managed containers are sent to the monitoring server
+#[derive(Debug, Deserialize, Clone, PartialEq)]
+pub struct LogTailConfig {
+    /// Seconds between two reads of the logs, 0 to send no logs
+    #[serde(default = "default_log_tail_interval")]
+    pub interval: u64,
+}
+
+impl Default for LogTailConfig {
+    fn default() -> Self {
+        Self {
+            interval: default_log_tail_interval(),
+        }
+    }
+}
+
+/// How this node is enrolled with the bluechi controller
+#[derive(Debug, Deserialize, Clone, PartialEq)]
+pub struct BluechiConfig {
+    /// Host of the bluechi controller, `master_ip` when empty
+    #[serde(default)]
+    pub controller_host: String,
+    /// Port the bluechi controller listens on for agents
+    #[serde(default = "default_bluechi_controller_port")]
+    pub controller_port: u16,
+    /// Directory of the bluechi-agent `agent.conf`
+    #[serde(default = "default_bluechi_config_dir")]
+    pub config_dir: String,
+    /// D-Bus address the controller API is reached on, the system bus when
+    /// empty, which only holds the controller on the master node
+    #[serde(default)]
+    pub controller_bus: String,
+    /// Seconds to wait for the controller to list the node online after
+    /// enrollment, 0 to not check
+    #[serde(default = "default_bluechi_verify_timeout")]
+    pub verify_timeout: u64,
+}
+
+impl Default for BluechiConfig {
+    fn default() -> Self {
+        Self {
+            controller_host: String::new(),
+            controller_port: default_bluechi_controller_port(),
+            config_dir: default_bluechi_config_dir(),
+            controller_bus: String::new(),
+            verify_timeout: default_bluechi_verify_timeout(),
+        }
+    }
+}
+
+impl BluechiConfig {
+    /// Time the controller gets to list the node online after enrollment
+    pub fn verify_timeout(&self) -> Duration {
+        Duration::from_secs(self.verify_timeout)
+    }
+}
+
+fn default_bluechi_controller_port() -> u16 {
+    842
+}
+
+fn default_bluechi_config_dir() -> String {
+    "/etc/bluechi".to_string()
+}
+
+fn default_bluechi_verify_timeout() -> u64 {
+    30
+}
+
+fn default_log_tail_interval() -> u64 {
+    5
+}
+
+fn default_image_pull_timeout() -> u64 {
+    300
+}
+
+fn default_volume_base_path() -> String {
+    "/var/lib/piccolo/volumes".to_string()
+}
+
+fn default_volume_permissions() -> u32 {
+    0o755
 }
 
 fn default_node_name() -> String {
@@ -73,6 +354,10 @@ fn default_yaml_storage() -> String {
     "/etc/piccolo/yaml".to_string()
 }
 
+fn default_heartbeat_interval() -> u64 {
+    DEFAULT_HEARTBEAT_INTERVAL
+}
+
 #[derive(Debug, Deserialize, Clone, Default, PartialEq)]
 pub struct Config {
     pub nodeagent: NodeAgentConfig,
@@ -121,21 +406,187 @@ impl Config {
         self.nodeagent.yaml_storage.clone()
     }
 
+    /// Time between heartbeats sent to the API server
+    pub fn heartbeat_interval(&self) -> Duration {
+        match self.nodeagent.heartbeat_interval {
+            0 => Duration::from_secs(DEFAULT_HEARTBEAT_INTERVAL),
+            secs => Duration::from_secs(secs),
+        }
+    }
+
+    /// Time between node resource reports to the monitoring server
+    pub fn collection_interval(&self) -> Duration {
+        Duration::from_secs(self.nodeagent.metrics.collection_interval.max(1))
+    }
+
+    /// Check the values that cannot be rejected by parsing alone
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        let nodeagent = &self.nodeagent;
+        if nodeagent.heartbeat_interval == 0 {
+            return Err(ConfigError::Invalid(
+                "heartbeat_interval must be at least 1 second".to_string(),
+            ));
+        }
+        if nodeagent.metrics.collection_interval == 0 {
+            return Err(ConfigError::Invalid(
+                "metrics.collection_interval must be at least 1 second".to_string(),
+            ));
+        }
+        if nodeagent.container_poll.interval == 0 {
+            return Err(ConfigError::Invalid(
+                "container_poll.interval must be at least 1 second".to_string(),
+            ));
+        }
+        if nodeagent.image_pull.timeout == 0 {
+            return Err(ConfigError::Invalid(
+                "image_pull.timeout must be at least 1 second".to_string(),
+            ));
+        }
+        if !LOG_LEVELS.contains(&nodeagent.log_level.as_str()) {
+            return Err(ConfigError::Invalid(format!(
+                "unknown log_level '{}', expected one of {}",
+                nodeagent.log_level,
+                LOG_LEVELS.join(", ")
+            )));
+        }
+        if nodeagent.grpc_port == 0 {
+            return Err(ConfigError::Invalid("grpc_port must not be 0".to_string()));
+        }
+        Ok(())
+    }
+
+    /// This config with the runtime-changeable fields of `new` applied
+    ///
+    /// # Returns
+    ///
+    /// * `(Config, Vec<&'static str>)` - Updated config, and the fields
+    ///   that differ in `new` but only take effect after a restart
+    pub fn reloaded(&self, new: &Config) -> (Config, Vec<&'static str>) {
+        let mut updated = self.clone();
+        updated.nodeagent.heartbeat_interval = new.nodeagent.heartbeat_interval;
+        updated.nodeagent.log_level = new.nodeagent.log_level.clone();
+        updated.nodeagent.metrics = new.nodeagent.metrics.clone();
+        updated.nodeagent.container_poll = new.nodeagent.container_poll.clone();
+        updated.nodeagent.image_pull = new.nodeagent.image_pull.clone();
+        updated.nodeagent.log_tail = new.nodeagent.log_tail.clone();
+        updated.nodeagent.bluechi = new.nodeagent.bluechi.clone();
+
+        let (old, new) = (&self.nodeagent, &new.nodeagent);
+        let restart_fields = [
+            ("node_name", old.node_name != new.node_name),
+            ("node_type", old.node_type != new.node_type),
+            ("node_role", old.node_role != new.node_role),
+            ("master_ip", old.master_ip != new.master_ip),
+            ("node_ip", old.node_ip != new.node_ip),
+            ("grpc_port", old.grpc_port != new.grpc_port),
+            ("system", old.system != new.system),
+            ("yaml_storage", old.yaml_storage != new.yaml_storage),
+            ("volumes", old.volumes != new.volumes),
+        ];
+        let ignored = restart_fields
+            .into_iter()
+            .filter(|(_, changed)| *changed)
+            .map(|(field, _)| field)
+            .collect();
+        (updated, ignored)
+    }
+
     // Get or initialize the global config
-    pub fn get() -> &'static Config {
-        NODEAGENT_CONFIG.get().unwrap_or_else(|| {
-            let default_config = Config::default();
-            NODEAGENT_CONFIG.set(default_config.clone()).unwrap_or(());
-            NODEAGENT_CONFIG.get().unwrap()
-        })
+    pub fn get() -> Arc<Config> {
+        NODEAGENT_CONFIG.get()
     }
 
-    // Set the global config
+    // Replace the global config; readers see either the old or the new one
     pub fn set_global(config: Config) {
-        let _ = NODEAGENT_CONFIG.set(config);
+        NODEAGENT_CONFIG.set(config);
     }
 }
 
+/// Config shared by every task, replaced as a whole on reload
+pub struct SharedConfig(RwLock<Option<Arc<Config>>>);
+
+impl SharedConfig {
+    pub const fn new() -> Self {
+        Self(RwLock::new(None))
+    }
+
+    pub fn get(&self) -> Arc<Config> {
+        if let Some(config) = self.0.read().unwrap_or_else(|e| e.into_inner()).as_ref() {
+            return Arc::clone(config);
+        }
+        let mut slot = self.0.write().unwrap_or_else(|e| e.into_inner());
+        Arc::clone(slot.get_or_insert_with(|| Arc::new(Config::default())))
+    }
+
+    pub fn set(&self, config: Config) {
+        *self.0.write().unwrap_or_else(|e| e.into_inner()) = Some(Arc::new(config));
+    }
+
+    /// Re-read the config file at `path` and apply its runtime-changeable fields
+    ///
+    /// The current config stays active if the file cannot be read, parsed
+    /// or validated.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<Vec<&'static str>, ConfigError>` - Changed fields that were
+    ///   ignored because they need a restart
+    pub fn reload(&self, path: &Path) -> Result<Vec<&'static str>, ConfigError> {
+        let new = Config::load(path)?;
+        new.validate()?;
+        let (updated, ignored) = self.get().reloaded(&new);
+        self.set(updated);
+        Ok(ignored)
+    }
+
+    /// Reload the config whenever the content of the file at `path` changes
+    ///
+    /// The file is checked every `period`. Polling the content rather than
+    /// waiting for file events also catches editors that replace the file.
+    pub fn watch(&'static self, path: PathBuf, period: Duration) -> tokio::task::JoinHandle<()> {
+        tokio::spawn(async move {
+            let mut last = std::fs::read(&path).ok();
+            let mut ticker = tokio::time::interval(period);
+            loop {
+                ticker.tick().await;
+                let current = std::fs::read(&path).ok();
+                if current.is_none() || current == last {
+                    continue;
+                }
+                last = current;
+
+                match self.reload(&path) {
+                    Ok(ignored) => {
+                        info!("Reloaded configuration from {}", path.display());
+                        for field in ignored {
+                            warn!(
+                                "Config field '{}' changed, restart NodeAgent to apply it",
+                                field
+                            );
+                        }
+                    }
+                    Err(e) => warn!(
+                        "Ignoring new configuration in {}, keeping the current one: {}",
+                        path.display(),
+                        e
+                    ),
+                }
+            }
+        })
+    }
+}
+
+impl Default for SharedConfig {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Reload the global config when the file at `path` changes
+pub fn watch(path: PathBuf, period: Duration) -> tokio::task::JoinHandle<()> {
+    NODEAGENT_CONFIG.watch(path, period)
+}
+
 // Helper function to get network interfaces
 fn get_network_interfaces() -> Result<Vec<Interface>, std::io::Error> {
     get_if_addrs()
@@ -185,6 +636,34 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_volume_config_defaults_and_overrides() {
+        let yaml = r#"
+nodeagent:
+  master_ip: 10.0.0.1
+  grpc_port: 47004
+  log_level: info
+  metrics:
+    collection_interval: 5
+    batch_size: 50
+  system:
+    hostname: node
+    platform: linux
+    architecture: x86_64
+"#;
+        let config: Config = serde_yaml::from_str(yaml).unwrap();
+        assert_eq!(config.nodeagent.volumes, VolumeConfig::default());
+        assert_eq!(config.nodeagent.volumes.permissions, 0o755);
+
+        let yaml = format!(
+            "{}  volumes:\n    base_path: /data/volumes\n    permissions: 0o700\n",
+            yaml
+        );
+        let config: Config = serde_yaml::from_str(&yaml).unwrap();
+        assert_eq!(config.nodeagent.volumes.base_path, "/data/volumes");
+        assert_eq!(config.nodeagent.volumes.permissions, 0o700);
+    }
+
     #[test]
     fn test_config_clone_and_eq() {
         let config1 = Config::default();
@@ -192,6 +671,269 @@ mod tests {
         assert_eq!(config1, config2);
     }
 
+    const RELOAD_YAML: &str = r#"
+nodeagent:
+  node_name: vehicle-1
+  master_ip: 10.0.0.1
+  grpc_port: 47004
+  log_level: info
+  heartbeat_interval: 3
+  metrics:
+    collection_interval: 5
+    batch_size: 50
+  system:
+    hostname: node
+    platform: linux
+    architecture: x86_64
+"#;
+
+    fn reload_config() -> Config {
+        serde_yaml::from_str(RELOAD_YAML).unwrap()
+    }
+
+    #[test]
+    fn test_heartbeat_interval_defaults() {
+        let yaml = RELOAD_YAML.replace("  heartbeat_interval: 3\n", "");
+        let config: Config = serde_yaml::from_str(&yaml).unwrap();
+        assert_eq!(config.nodeagent.heartbeat_interval, 3);
+        // A config built in code has no interval set
+        assert_eq!(
+            Config::default().heartbeat_interval(),
+            Duration::from_secs(3)
+        );
+        assert_eq!(
+            Config::default().collection_interval(),
+            Duration::from_secs(1)
+        );
+    }
+
+    #[test]
+    fn test_validate_rejects_bad_values() {
+        assert!(reload_config().validate().is_ok());
+
+        let mut config = reload_config();
+        config.nodeagent.heartbeat_interval = 0;
+        assert!(matches!(config.validate(), Err(ConfigError::Invalid(_))));
+
+        let mut config = reload_config();
+        config.nodeagent.metrics.collection_interval = 0;
+        assert!(config.validate().is_err());
+
+        let mut config = reload_config();
+        config.nodeagent.log_level = "verbose".to_string();
+        let err = config.validate().unwrap_err().to_string();
+        assert!(err.contains("unknown log_level 'verbose'"));
+
+        let mut config = reload_config();
+        config.nodeagent.grpc_port = 0;
+        assert!(config.validate().is_err());
+
+        let mut config = reload_config();
+        config.nodeagent.container_poll.interval = 0;
+        assert!(config.validate().is_err());
+
+        let mut config = reload_config();
+        config.nodeagent.image_pull.timeout = 0;
+        assert!(config.validate().is_err());
+    }
+
+    #[test]
+    fn test_image_pull_defaults_and_overrides() {
+        let config = reload_config();
+        assert_eq!(config.nodeagent.image_pull, ImagePullConfig::default());
+        assert_eq!(
+            config.nodeagent.image_pull.timeout(),
+            Duration::from_secs(300)
+        );
+
+        let yaml = format!("{}  image_pull:\n    timeout: 60\n", RELOAD_YAML);
+        let config: Config = serde_yaml::from_str(&yaml).unwrap();
+        assert_eq!(config.nodeagent.image_pull.timeout, 60);
+    }
+
+    #[test]
+    fn test_log_tail_defaults_and_overrides() {
+        let config = reload_config();
+        assert_eq!(config.nodeagent.log_tail.interval, 5);
+
+        let yaml = format!("{}  log_tail:\n    interval: 0\n", RELOAD_YAML);
+        let config: Config = serde_yaml::from_str(&yaml).unwrap();
+        assert_eq!(config.nodeagent.log_tail.interval, 0);
+    }
+
+    #[test]
+    fn test_bluechi_defaults_and_overrides() {
+        let config = reload_config();
+        assert_eq!(config.nodeagent.bluechi, BluechiConfig::default());
+        assert_eq!(config.nodeagent.bluechi.controller_port, 842);
+        assert_eq!(config.nodeagent.bluechi.config_dir, "/etc/bluechi");
+
+        let yaml = format!(
+            "{}  bluechi:\n    controller_host: 10.0.0.2\n    verify_timeout: 0\n",
+            RELOAD_YAML
+        );
+        let config: Config = serde_yaml::from_str(&yaml).unwrap();
+        assert_eq!(config.nodeagent.bluechi.controller_host, "10.0.0.2");
+        assert_eq!(config.nodeagent.bluechi.verify_timeout(), Duration::ZERO);
+    }
+
+    #[test]
+    fn test_container_poll_defaults_and_overrides() {
+        let config = reload_config();
+        assert_eq!(
+            config.nodeagent.container_poll,
+            ContainerPollConfig::default()
+        );
+        assert_eq!(
+            config.nodeagent.container_poll.interval(),
+            Duration::from_secs(1)
+        );
+
+        let yaml = format!("{}  container_poll:\n    interval: 5\n", RELOAD_YAML);
+        let config: Config = serde_yaml::from_str(&yaml).unwrap();
+        assert_eq!(config.nodeagent.container_poll.interval, 5);
+        assert_eq!(config.nodeagent.container_poll.max_backoff, 30);
+    }
+
+    #[test]
+    fn test_container_filter_matches_names_and_labels() {
+        let yaml = format!(
+            "{}  container_poll:\n    filter:\n      names: [\"helloworld-*\", \"bms?\"]\n      labels: [\"piccolo\", \"app=pull*\"]\n",
+            RELOAD_YAML
+        );
+        let config: Config = serde_yaml::from_str(&yaml).unwrap();
+        let filter = &config.nodeagent.container_poll.filter;
+        let names = |name: &str| vec![format!("/{}", name)];
+        let labels = |pairs: &[(&str, &str)]| -> HashMap<String, String> {
+            pairs
+                .iter()
+                .map(|(k, v)| (k.to_string(), v.to_string()))
+                .collect()
+        };
+        let piccolo = labels(&[("piccolo", "true")]);
+
+        assert!(filter.matches(&names("helloworld-backend"), &piccolo));
+        assert!(filter.matches(&names("bms1"), &labels(&[("app", "pullpiri")])));
+        assert!(!filter.matches(&names("bms10"), &piccolo));
+        assert!(!filter.matches(&names("infra"), &piccolo));
+        assert!(!filter.matches(&names("helloworld-backend"), &labels(&[("app", "other")])));
+        assert!(!filter.matches(&names("helloworld-backend"), &HashMap::new()));
+
+        // The default filter lets every container through
+        assert!(ContainerFilter::default().matches(&names("infra"), &HashMap::new()));
+    }
+
+    #[test]
+    fn test_glob_match() {
+        assert!(glob_match("*", ""));
+        assert!(glob_match("a*b*c", "aXXbYYbc"));
+        assert!(glob_match("a?c", "abc"));
+        assert!(!glob_match("a?c", "ac"));
+        assert!(!glob_match("a*b", "aXXbc"));
+        assert!(glob_match("abc", "abc"));
+        assert!(!glob_match("abc", "abcd"));
+    }
+
+    #[test]
+    fn test_container_poll_delay_backs_off_on_repeated_failures() {
+        let poll = ContainerPollConfig {
+            interval: 2,
+            max_backoff: 30,
+            ..Default::default()
+        };
+
+        let delays: Vec<u64> = (0..7).map(|n| poll.delay(n).as_secs()).collect();
+        assert_eq!(delays, vec![2, 4, 8, 16, 30, 30, 30]);
+        // Very long failure streaks stay capped instead of overflowing
+        assert_eq!(poll.delay(u32::MAX), Duration::from_secs(30));
+
+        // A maximum below the interval never shortens the interval
+        let poll = ContainerPollConfig {
+            interval: 10,
+            max_backoff: 5,
+            ..Default::default()
+        };
+        assert_eq!(poll.delay(0), Duration::from_secs(10));
+        assert_eq!(poll.delay(3), Duration::from_secs(10));
+    }
+
+    #[test]
+    fn test_reloaded_applies_runtime_fields_only() {
+        let current = reload_config();
+        let mut new = reload_config();
+        new.nodeagent.heartbeat_interval = 10;
+        new.nodeagent.log_level = "debug".to_string();
+        new.nodeagent.metrics.collection_interval = 30;
+        new.nodeagent.container_poll.interval = 5;
+        new.nodeagent.grpc_port = 48004;
+        new.nodeagent.node_name = "vehicle-2".to_string();
+
+        let (updated, ignored) = current.reloaded(&new);
+
+        assert_eq!(updated.nodeagent.heartbeat_interval, 10);
+        assert_eq!(updated.nodeagent.log_level, "debug");
+        assert_eq!(updated.nodeagent.metrics.collection_interval, 30);
+        assert_eq!(updated.nodeagent.container_poll.interval, 5);
+        assert_eq!(updated.nodeagent.grpc_port, 47004);
+        assert_eq!(updated.nodeagent.node_name, "vehicle-1");
+        assert_eq!(ignored, vec!["node_name", "grpc_port"]);
+
+        let (unchanged, ignored) = current.reloaded(&current);
+        assert_eq!(unchanged, current);
+        assert!(ignored.is_empty());
+    }
+
+    /// Poll `config` until `check` holds, for at most one second
+    async fn wait_for(config: &SharedConfig, check: impl Fn(&Config) -> bool) -> bool {
+        for _ in 0..50 {
+            if check(&config.get()) {
+                return true;
+            }
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+        false
+    }
+
+    #[tokio::test]
+    async fn test_watch_applies_rewritten_config() {
+        static CONFIG: SharedConfig = SharedConfig::new();
+        let path = std::env::temp_dir().join("nodeagent-config-reload.yaml");
+        std::fs::write(&path, RELOAD_YAML).unwrap();
+        CONFIG.set(Config::load(&path).unwrap());
+        let watcher = CONFIG.watch(path.clone(), Duration::from_millis(10));
+        // Let the watcher record the initial content
+        tokio::time::sleep(Duration::from_millis(30)).await;
+
+        let rewritten = RELOAD_YAML
+            .replace("heartbeat_interval: 3", "heartbeat_interval: 7")
+            .replace("grpc_port: 47004", "grpc_port: 48004");
+        std::fs::write(&path, rewritten).unwrap();
+
+        assert!(
+            wait_for(&CONFIG, |c| c.heartbeat_interval()
+                == Duration::from_secs(7))
+            .await
+        );
+        assert_eq!(CONFIG.get().nodeagent.grpc_port, 47004);
+
+        // A malformed file keeps the last good config
+        std::fs::write(&path, "nodeagent: [unclosed").unwrap();
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        assert_eq!(CONFIG.get().heartbeat_interval(), Duration::from_secs(7));
+
+        // So does a file that parses but fails validation
+        std::fs::write(
+            &path,
+            RELOAD_YAML.replace("heartbeat_interval: 3", "heartbeat_interval: 0"),
+        )
+        .unwrap();
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        assert_eq!(CONFIG.get().heartbeat_interval(), Duration::from_secs(7));
+
+        watcher.abort();
+        let _ = std::fs::remove_file(&path);
+    }
+
     #[test]
     fn test_config_load_from_file_fallback() {
         let path = PathBuf::from("/nonexistent/path/to/config.yaml");
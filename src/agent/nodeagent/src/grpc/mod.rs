@@ -2,5 +2,6 @@
 * SPDX-FileCopyrightText: Copyright 2024 LG Electronics Inc.
 * SPDX-License-Identifier: Apache-2.0
 */
+pub mod queue;
 pub mod receiver;
 pub mod sender;
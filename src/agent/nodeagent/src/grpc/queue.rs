@@ -0,0 +1,161 @@
+/*
+* SPDX-FileCopyrightText: Copyright 2024 LG Electronics Inc.
+* SPDX-License-Identifier: Apache-2.0
+*/
+//! Container lists waiting to be sent to the monitoring server
+//!
+//! The collection loop pushes without waiting, so a slow monitoring server
+//! cannot stall it. While the queue is full, new lists are dropped and
+//! counted; the next list carries the same containers with newer stats.
+
+use common::monitoringserver::ContainerList;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::mpsc::{self, Receiver, Sender};
+use tracing::warn;
+
+/// Interval of the warning about dropped container lists
+pub const DROP_REPORT_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Container lists dropped because the queue was full
+#[derive(Default)]
+struct Dropped {
+    /// Since the start of NodeAgent
+    total: AtomicU64,
+    /// Since the last warning
+    unreported: AtomicU64,
+}
+
+/// Sending side of the container list queue
+#[derive(Clone)]
+pub struct ContainerQueue {
+    tx: Sender<ContainerList>,
+    dropped: Arc<Dropped>,
+}
+
+impl ContainerQueue {
+    /// Queue of `capacity` container lists and the receiver to send them from
+    pub fn new(capacity: usize) -> (Self, Receiver<ContainerList>) {
+        let (tx, rx) = mpsc::channel(capacity.max(1));
+        let queue = Self {
+            tx,
+            dropped: Arc::new(Dropped::default()),
+        };
+        (queue, rx)
+    }
+
+    /// Queue `list` without waiting
+    ///
+    /// Returns false if the list was dropped because the queue is full or
+    /// its receiver is gone.
+    pub fn push(&self, list: ContainerList) -> bool {
+        if self.tx.try_send(list).is_ok() {
+            return true;
+        }
+        self.dropped.total.fetch_add(1, Ordering::Relaxed);
+        self.dropped.unreported.fetch_add(1, Ordering::Relaxed);
+        false
+    }
+
+    /// Container lists dropped since the start of NodeAgent
+    pub fn dropped(&self) -> u64 {
+        self.dropped.total.load(Ordering::Relaxed)
+    }
+
+    /// Container lists dropped since the last call, resetting the count
+    fn take_unreported(&self) -> u64 {
+        self.dropped.unreported.swap(0, Ordering::Relaxed)
+    }
+
+    /// Warn every `interval` about the container lists dropped meanwhile
+    ///
+    /// Nothing is logged for an interval without drops.
+    pub async fn report_dropped(&self, interval: Duration) {
+        let mut ticker = tokio::time::interval(interval);
+        ticker.tick().await;
+        loop {
+            ticker.tick().await;
+            let dropped = self.take_unreported();
+            if dropped > 0 {
+                warn!(
+                    "Dropped {} container list updates in the last {:?}, \
+                     the monitoring server is not keeping up ({} in total)",
+                    dropped,
+                    interval,
+                    self.dropped()
+                );
+            }
+        }
+    }
+}
+
+//UNIT TEST CASES
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn list(node: &str) -> ContainerList {
+        ContainerList {
+            node_name: node.to_string(),
+            containers: Vec::new(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_push_drops_and_counts_when_full() {
+        let (queue, mut rx) = ContainerQueue::new(2);
+        assert!(queue.push(list("a")));
+        assert!(queue.push(list("b")));
+
+        // Full: the pushes return at once instead of waiting for the receiver
+        let pushes = async {
+            for _ in 0..5 {
+                assert!(!queue.push(list("c")));
+            }
+        };
+        tokio::time::timeout(Duration::from_millis(100), pushes)
+            .await
+            .expect("push blocked on a full queue");
+        assert_eq!(queue.dropped(), 5);
+
+        // The queued lists are kept and room is made again by receiving
+        assert_eq!(rx.recv().await.unwrap().node_name, "a");
+        assert!(queue.push(list("d")));
+        assert_eq!(rx.recv().await.unwrap().node_name, "b");
+        assert_eq!(rx.recv().await.unwrap().node_name, "d");
+        assert_eq!(queue.dropped(), 5);
+    }
+
+    #[tokio::test]
+    async fn test_unreported_count_resets_but_total_does_not() {
+        let (queue, _rx) = ContainerQueue::new(1);
+        queue.push(list("a"));
+        queue.push(list("b"));
+        queue.push(list("c"));
+
+        assert_eq!(queue.take_unreported(), 2);
+        assert_eq!(queue.take_unreported(), 0);
+        queue.push(list("d"));
+        assert_eq!(queue.take_unreported(), 1);
+        assert_eq!(queue.dropped(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_push_counts_when_receiver_is_gone() {
+        let (queue, rx) = ContainerQueue::new(4);
+        drop(rx);
+        assert!(!queue.push(list("a")));
+        assert_eq!(queue.dropped(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_clones_share_the_count() {
+        let (queue, _rx) = ContainerQueue::new(1);
+        let clone = queue.clone();
+        queue.push(list("a"));
+        clone.push(list("b"));
+        assert_eq!(queue.dropped(), 1);
+        assert_eq!(clone.dropped(), 1);
+    }
+}
@@ -2,23 +2,51 @@
 * SPDX-FileCopyrightText: Copyright 2024 LG Electronics Inc.
 * SPDX-License-Identifier: Apache-2.0
 */
+use crate::config::Config;
+use crate::desired_state::DesiredState;
+use crate::resource::logs;
+use crate::runtime::bluechi::agent::{self, AgentConf, Controller, Systemd};
+use crate::runtime::podman::image::{self, ImageClient, ImageRef};
 use common::nodeagent::fromapiserver::{
-    ConfigRequest, ConfigResponse, HandleYamlRequest, HandleYamlResponse, HeartbeatRequest,
-    HeartbeatResponse, NodeRegistrationRequest, NodeRegistrationResponse, StatusAck, StatusReport,
+    ConfigRequest, ConfigResponse, EnrollBluechiRequest, EnrollBluechiResponse, HandleYamlRequest,
+    HandleYamlResponse, HeartbeatRequest, HeartbeatResponse, NodeRegistrationRequest,
+    NodeRegistrationResponse, PrefetchImagesRequest, PrefetchImagesResponse, StatusAck,
+    StatusReport, WorkloadLogLine, WorkloadLogsRequest,
 };
-use tokio::sync::mpsc;
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::{mpsc, Mutex};
+use tokio_stream::wrappers::ReceiverStream;
 use tonic::{Request, Response, Status};
 
+/// Interval between two reads of the logs of a followed workload
+const FOLLOW_INTERVAL: Duration = Duration::from_secs(1);
+/// Lines buffered for a client reading the logs slower than they come
+const LOG_BUFFER: usize = 256;
+
+/// Stream of the log lines of a workload
+pub type WorkloadLogStream = ReceiverStream<Result<WorkloadLogLine, Status>>;
+
 /// Handle a yaml request from API-Server
 ///
-/// Receives a yaml from API-Server and forwards it to the NodeAgent manager for processing.
+/// Receives a yaml from API-Server, pulls the images of its models and
+/// forwards it to the NodeAgent manager for processing. The artifact is not
+/// forwarded if an image cannot be pulled in time or has another digest
+/// than its model pins.
 pub async fn handle_yaml(
     tx: mpsc::Sender<HandleYamlRequest>,
+    images: &dyn ImageClient,
     request: Request<HandleYamlRequest>,
 ) -> Result<Response<HandleYamlResponse>, Status> {
     println!("Got a Yamlrequest from api-server");
     let req: HandleYamlRequest = request.into_inner();
 
+    let artifact_images = image::artifact_images(&req.yaml)?;
+    let timeout = Config::get().nodeagent.image_pull.timeout();
+    image::prefetch(images, &artifact_images, timeout).await?;
+
     match tx.send(req).await {
         Ok(_) => Ok(tonic::Response::new(HandleYamlResponse {
             status: true,
@@ -31,6 +59,156 @@ pub async fn handle_yaml(
     }
 }
 
+/// Pull images ahead of scenario activation
+///
+/// Lets the API server warm the node, so a later artifact using the images
+/// is applied without waiting on the registry.
+pub async fn prefetch_images(
+    images: &dyn ImageClient,
+    request: Request<PrefetchImagesRequest>,
+) -> Result<Response<PrefetchImagesResponse>, Status> {
+    let req = request.into_inner();
+    let requested: Vec<ImageRef> = req.images.iter().map(|i| ImageRef::new(i)).collect();
+    let timeout = Config::get().nodeagent.image_pull.timeout();
+    image::prefetch(images, &requested, timeout).await?;
+
+    Ok(Response::new(PrefetchImagesResponse {
+        status: true,
+        desc: format!("Prefetched {} images", requested.len()),
+    }))
+}
+
+/// Enroll this node with the bluechi controller
+///
+/// Installs the `agent.conf` rendered from the node config, restarts
+/// bluechi-agent if it changed and waits for the controller to list the
+/// node online, so the master can bring a new node in remotely.
+pub async fn enroll_bluechi(
+    systemd: &dyn Systemd,
+    controller: &dyn Controller,
+    request: Request<EnrollBluechiRequest>,
+) -> Result<Response<EnrollBluechiResponse>, Status> {
+    let _req = request.into_inner();
+    let config = Config::get();
+    let bluechi = &config.nodeagent.bluechi;
+    let conf = AgentConf::from_config(&config);
+
+    let changed = agent::enroll(
+        &conf,
+        std::path::Path::new(&bluechi.config_dir),
+        systemd,
+        controller,
+        bluechi.verify_timeout(),
+    )
+    .await?;
+
+    Ok(Response::new(EnrollBluechiResponse {
+        status: true,
+        desc: format!(
+            "Node {} enrolled with bluechi controller {}:{}",
+            conf.node_name, conf.controller_host, conf.controller_port
+        ),
+        changed,
+    }))
+}
+
+/// Stream the logs of the container backing a workload
+///
+/// The workload is looked up by the name of its model among the workloads
+/// started on this node. With `follow`, the container is read again every
+/// `FOLLOW_INTERVAL` until the client goes away, so a container recreated
+/// by self-healing keeps being followed.
+pub async fn get_workload_logs(
+    desired_states_cache: Arc<Mutex<HashMap<String, DesiredState>>>,
+    request: Request<WorkloadLogsRequest>,
+) -> Result<Response<WorkloadLogStream>, Status> {
+    let req = request.into_inner();
+    let workload = req.workload;
+    // Fails early for a workload this node does not run
+    container_of(&desired_states_cache, &workload).await?;
+
+    let (tx, rx) = mpsc::channel(LOG_BUFFER);
+    let read = move |after| {
+        let cache = Arc::clone(&desired_states_cache);
+        let workload = workload.clone();
+        async move {
+            let id = container_of(&cache, &workload).await?;
+            logs::read(&id, req.tail, after).await.map_err(|e| {
+                Status::unavailable(format!("cannot read logs of {}: {}", workload, e))
+            })
+        }
+    };
+    tokio::spawn(pump(read, req.follow, FOLLOW_INTERVAL, tx));
+
+    Ok(Response::new(ReceiverStream::new(rx)))
+}
+
+/// ID of the container backing `workload`
+async fn container_of(
+    desired_states_cache: &Mutex<HashMap<String, DesiredState>>,
+    workload: &str,
+) -> Result<String, Status> {
+    let cache = desired_states_cache.lock().await;
+    match cache.get(workload) {
+        Some(desired) if !desired.container_id.is_empty() => Ok(desired.container_id.clone()),
+        Some(_) => Err(Status::unavailable(format!(
+            "workload {} has no container yet",
+            workload
+        ))),
+        None => Err(Status::not_found(format!(
+            "workload {} is not running on this node",
+            workload
+        ))),
+    }
+}
+
+/// Send the lines returned by `read` to `tx`
+///
+/// `read` is given the timestamp of the last line sent, `None` on the
+/// first read. With `follow`, reads again every `interval` until `tx`
+/// closes, which happens when the client disconnects. A failed read is
+/// sent to the client and ends the stream.
+async fn pump<R, Fut>(
+    mut read: R,
+    follow: bool,
+    interval: Duration,
+    tx: mpsc::Sender<Result<WorkloadLogLine, Status>>,
+) where
+    R: FnMut(Option<i64>) -> Fut,
+    Fut: Future<Output = Result<Vec<(i64, String)>, Status>>,
+{
+    // Following a workload whose first read returned nothing starts here
+    let started = chrono::Utc::now().timestamp_nanos_opt();
+    let mut last = None;
+    loop {
+        let lines = match read(last).await {
+            Ok(lines) => lines,
+            Err(status) => {
+                let _ = tx.send(Err(status)).await;
+                return;
+            }
+        };
+        for (timestamp, line) in lines {
+            last = Some(timestamp);
+            if tx
+                .send(Ok(WorkloadLogLine { timestamp, line }))
+                .await
+                .is_err()
+            {
+                return;
+            }
+        }
+        if !follow {
+            return;
+        }
+        last = last.or(started);
+        tokio::select! {
+            _ = tx.closed() => return,
+            _ = tokio::time::sleep(interval) => {}
+        }
+    }
+}
+
 /// Register this node with the API server
 pub async fn register_node(
     request: Request<NodeRegistrationRequest>,
@@ -118,13 +296,17 @@ pub async fn receive_config(
 
 #[cfg(test)]
 mod tests {
+    use super::{get_workload_logs, pump};
+    use crate::desired_state::DesiredState;
     use crate::grpc::receiver::{NodeAgentConnection, NodeAgentReceiver};
+    use crate::runtime::podman::image::tests::MockImages;
     use common::nodeagent::fromapiserver::{
         ClusterConfig, ConfigRequest, ConfigResponse, HandleYamlRequest, HandleYamlResponse,
         HeartbeatRequest, HeartbeatResponse, NodeRegistrationRequest, NodeRegistrationResponse,
-        StatusAck, StatusReport,
+        PrefetchImagesRequest, StatusAck, StatusReport, WorkloadLogsRequest,
     };
     use std::sync::Arc;
+    use std::time::Duration;
     use tokio::sync::mpsc;
     use tokio::sync::Mutex;
     use tonic::{Request, Status};
@@ -172,16 +354,25 @@ spec:
   terminationGracePeriodSeconds: 0
 "#;
 
-    #[tokio::test]
-    async fn test_handle_yaml_with_valid_artifact_yaml() {
-        let (tx, mut rx) = mpsc::channel(1);
-        let receiver = NodeAgentReceiver::new(
+    fn receiver_with_images(
+        tx: mpsc::Sender<HandleYamlRequest>,
+        images: Arc<MockImages>,
+    ) -> NodeAgentReceiver {
+        NodeAgentReceiver::new(
             tx,
             "test-node".to_string(),
             "test-host".to_string(),
             "192.168.1.100".to_string(),
             Arc::new(Mutex::new(std::collections::HashMap::new())),
-        );
+        )
+        .with_images(images)
+    }
+
+    #[tokio::test]
+    async fn test_handle_yaml_with_valid_artifact_yaml() {
+        let (tx, mut rx) = mpsc::channel(1);
+        let images = Arc::new(MockImages::default());
+        let receiver = receiver_with_images(tx, images.clone());
 
         let request = HandleYamlRequest {
             yaml: VALID_ARTIFACT_YAML.to_string(),
@@ -197,19 +388,56 @@ spec:
 
         let received = rx.recv().await.unwrap();
         assert_eq!(received.yaml, request.yaml);
+        assert_eq!(images.pulls(), vec!["hellow"]);
+    }
+
+    #[tokio::test]
+    async fn test_handle_yaml_digest_mismatch_is_not_applied() {
+        let (tx, mut rx) = mpsc::channel(1);
+        let images = Arc::new(MockImages::with_registry(&[("hellow", "sha256:aaa")]));
+        let receiver = receiver_with_images(tx, images);
+
+        let yaml = VALID_ARTIFACT_YAML.replace(
+            "  labels:\n    app: hellow-core",
+            "    io.piccolo.annotations.image-digest.hellow: sha256:bbb\n  labels:\n    app: hellow-core",
+        );
+        let status = receiver
+            .handle_yaml(Request::new(HandleYamlRequest { yaml }))
+            .await
+            .unwrap_err();
+
+        assert_eq!(status.code(), tonic::Code::FailedPrecondition);
+        assert_eq!(
+            status.message(),
+            "image hellow has digest sha256:aaa, expected sha256:bbb"
+        );
+        assert!(rx.try_recv().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_prefetch_images() {
+        let (tx, _rx) = mpsc::channel(1);
+        let images = Arc::new(MockImages::default());
+        let receiver = receiver_with_images(tx, images.clone());
+
+        let response = receiver
+            .prefetch_images(Request::new(PrefetchImagesRequest {
+                images: vec!["hellow".to_string(), "busybox:1.36".to_string()],
+            }))
+            .await
+            .unwrap()
+            .into_inner();
+
+        assert!(response.status);
+        assert_eq!(response.desc, "Prefetched 2 images");
+        assert_eq!(images.pulls(), vec!["hellow", "busybox:1.36"]);
     }
 
     #[tokio::test]
     async fn test_handle_yaml_send_error() {
         let (tx, rx) = mpsc::channel(1);
         drop(rx);
-        let receiver = NodeAgentReceiver::new(
-            tx,
-            "test-node".to_string(),
-            "test-host".to_string(),
-            "192.168.1.100".to_string(),
-            Arc::new(Mutex::new(std::collections::HashMap::new())),
-        );
+        let receiver = receiver_with_images(tx, Arc::new(MockImages::default()));
 
         let request = HandleYamlRequest {
             yaml: VALID_ARTIFACT_YAML.to_string(),
@@ -343,4 +571,95 @@ spec:
         assert!(response.applied);
         assert_eq!(response.message, "Configuration applied successfully");
     }
+
+    type LogRead = std::future::Ready<Result<Vec<(i64, String)>, Status>>;
+
+    /// Log reader returning `batches` one after another, then nothing,
+    /// and recording the timestamp it was given at each read
+    fn scripted(
+        batches: Vec<Vec<(i64, &'static str)>>,
+        seen: Arc<std::sync::Mutex<Vec<Option<i64>>>>,
+    ) -> impl FnMut(Option<i64>) -> LogRead {
+        let mut batches = batches.into_iter();
+        move |after| {
+            seen.lock().unwrap().push(after);
+            let lines = batches.next().unwrap_or_default();
+            std::future::ready(Ok(lines
+                .into_iter()
+                .map(|(timestamp, line)| (timestamp, line.to_string()))
+                .collect()))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_pump_without_follow_sends_lines_once() {
+        let seen = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let read = scripted(vec![vec![(1, "a"), (2, "b")]], seen.clone());
+        let (tx, mut rx) = mpsc::channel(8);
+
+        pump(read, false, Duration::from_millis(1), tx).await;
+
+        assert_eq!(rx.recv().await.unwrap().unwrap().line, "a");
+        assert_eq!(rx.recv().await.unwrap().unwrap().timestamp, 2);
+        assert!(rx.recv().await.is_none());
+        assert_eq!(seen.lock().unwrap().clone(), vec![None]);
+    }
+
+    #[tokio::test]
+    async fn test_pump_follow_reads_after_last_line_until_client_leaves() {
+        let seen = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let read = scripted(vec![vec![(1, "a")], vec![(5, "b")]], seen.clone());
+        let (tx, mut rx) = mpsc::channel(8);
+        let pumping = tokio::spawn(pump(read, true, Duration::from_millis(5), tx));
+
+        assert_eq!(rx.recv().await.unwrap().unwrap().line, "a");
+        assert_eq!(rx.recv().await.unwrap().unwrap().line, "b");
+        drop(rx);
+
+        // The tail stops once the client is gone
+        tokio::time::timeout(Duration::from_secs(1), pumping)
+            .await
+            .expect("pump kept running after the client left")
+            .unwrap();
+        let seen = seen.lock().unwrap().clone();
+        assert_eq!(&seen[..2], &[None, Some(1)]);
+        assert!(seen[2..].iter().all(|after| *after == Some(5)));
+    }
+
+    #[tokio::test]
+    async fn test_pump_ends_with_read_error() {
+        let (tx, mut rx) = mpsc::channel(8);
+        let read = |_| std::future::ready(Err(Status::unavailable("podman is down")));
+
+        pump(read, true, Duration::from_millis(1), tx).await;
+
+        let status = rx.recv().await.unwrap().unwrap_err();
+        assert_eq!(status.code(), tonic::Code::Unavailable);
+        assert!(rx.recv().await.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_get_workload_logs_of_unknown_workload() {
+        // Started, but its container is not created yet
+        let starting = DesiredState::new("starting".to_string());
+        let cache = Arc::new(Mutex::new(std::collections::HashMap::from([(
+            "starting".to_string(),
+            starting,
+        )])));
+        let request = |workload: &str| {
+            Request::new(WorkloadLogsRequest {
+                workload: workload.to_string(),
+                ..Default::default()
+            })
+        };
+
+        let status = get_workload_logs(cache.clone(), request("unknown"))
+            .await
+            .unwrap_err();
+        assert_eq!(status.code(), tonic::Code::NotFound);
+        let status = get_workload_logs(cache, request("starting"))
+            .await
+            .unwrap_err();
+        assert_eq!(status.code(), tonic::Code::Unavailable);
+    }
 }
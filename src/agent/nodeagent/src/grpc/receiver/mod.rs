@@ -7,13 +7,16 @@ pub mod actioncontroller;
 pub mod apiserver;
 
 use crate::desired_state::DesiredState;
+use crate::runtime::bluechi::agent::{Controller, ControllerBus, Systemd, SystemdBus};
+use crate::runtime::podman::image::{ImageClient, PodmanImages};
 use common::nodeagent::node_agent_connection_server::NodeAgentConnection;
 use common::nodeagent::{
     fromactioncontroller::{HandleWorkloadRequest, HandleWorkloadResponse},
     fromapiserver::{
-        ConfigRequest, ConfigResponse, HandleYamlRequest, HandleYamlResponse, HeartbeatRequest,
-        HeartbeatResponse, NodeRegistrationRequest, NodeRegistrationResponse, StatusAck,
-        StatusReport,
+        ConfigRequest, ConfigResponse, EnrollBluechiRequest, EnrollBluechiResponse,
+        HandleYamlRequest, HandleYamlResponse, HeartbeatRequest, HeartbeatResponse,
+        NodeRegistrationRequest, NodeRegistrationResponse, PrefetchImagesRequest,
+        PrefetchImagesResponse, StatusAck, StatusReport, WorkloadLogsRequest,
     },
 };
 use std::collections::HashMap;
@@ -31,6 +34,12 @@ pub struct NodeAgentReceiver {
     pub ip_address: String,
     /// In-memory cache of desired states for self-healing
     pub desired_states_cache: Arc<Mutex<HashMap<String, DesiredState>>>,
+    /// Image API used to pull the images of applied artifacts
+    pub images: Arc<dyn ImageClient>,
+    /// Systemd of the node, restarting bluechi-agent on enrollment
+    pub systemd: Arc<dyn Systemd>,
+    /// Bluechi controller checked for the node on enrollment
+    pub controller: Arc<dyn Controller>,
 }
 
 impl NodeAgentReceiver {
@@ -47,20 +56,55 @@ impl NodeAgentReceiver {
             hostname,
             ip_address,
             desired_states_cache,
+            images: Arc::new(PodmanImages),
+            systemd: Arc::new(SystemdBus),
+            controller: Arc::new(ControllerBus),
         }
     }
+
+    /// Pull images through `images` instead of Podman
+    pub fn with_images(mut self, images: Arc<dyn ImageClient>) -> Self {
+        self.images = images;
+        self
+    }
 }
 
 #[tonic::async_trait]
 impl NodeAgentConnection for NodeAgentReceiver {
     /// Handle a yaml request from API-Server
     ///
-    /// Receives a yaml from API-Server and forwards it to the NodeAgent manager for processing.
+    /// Pulls the images of the artifact, then forwards it to the NodeAgent manager for processing.
     async fn handle_yaml(
         &self,
         request: Request<HandleYamlRequest>,
     ) -> Result<Response<HandleYamlResponse>, Status> {
-        apiserver::handle_yaml(self.tx.clone(), request).await
+        apiserver::handle_yaml(self.tx.clone(), self.images.as_ref(), request).await
+    }
+
+    /// Pull images ahead of scenario activation
+    async fn prefetch_images(
+        &self,
+        request: Request<PrefetchImagesRequest>,
+    ) -> Result<Response<PrefetchImagesResponse>, Status> {
+        apiserver::prefetch_images(self.images.as_ref(), request).await
+    }
+
+    /// Enroll this node with the bluechi controller
+    async fn enroll_bluechi(
+        &self,
+        request: Request<EnrollBluechiRequest>,
+    ) -> Result<Response<EnrollBluechiResponse>, Status> {
+        apiserver::enroll_bluechi(self.systemd.as_ref(), self.controller.as_ref(), request).await
+    }
+
+    type GetWorkloadLogsStream = apiserver::WorkloadLogStream;
+
+    /// Stream the logs of a workload running on this node
+    async fn get_workload_logs(
+        &self,
+        request: Request<WorkloadLogsRequest>,
+    ) -> Result<Response<Self::GetWorkloadLogsStream>, Status> {
+        apiserver::get_workload_logs(Arc::clone(&self.desired_states_cache), request).await
     }
 
     /// Register this node with the API server
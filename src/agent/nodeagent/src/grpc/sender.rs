@@ -4,7 +4,9 @@
  */
 
 use common::apiserver::api_server_connection_client::ApiServerConnectionClient;
-use common::monitoringserver::{ContainerList, SendContainerListResponse};
+use common::monitoringserver::{
+    ContainerList, LogEntry, SendContainerListResponse, SendLogsResponse,
+};
 use common::nodeagent::fromapiserver::{
     HeartbeatRequest, HeartbeatResponse, NodeRegistrationRequest, NodeRegistrationResponse,
     StatusAck, StatusReport,
@@ -14,24 +16,71 @@ use common::statemanager::{
 };
 
 use common::monitoringserver::monitoring_server_connection_client::MonitoringServerConnectionClient;
-use tonic::{Request, Status};
+use common::rpc;
+use std::collections::HashMap;
+use std::sync::Arc;
+use tokio::sync::Mutex;
+use tonic::transport::Channel;
+use tonic::{Code, Status};
 
 /// Sender for making gRPC requests to Monitoring Server
+///
+/// Connections are cached per server address and shared between clones, so
+/// periodic calls (heartbeat, container list) reuse one HTTP/2 connection.
+/// Every call has the default deadline of `common::rpc`. A cached channel is
+/// dropped when a call fails with `UNAVAILABLE` or `DEADLINE_EXCEEDED` and
+/// the next call reconnects.
 #[derive(Clone, Default)]
-pub struct NodeAgentSender {}
+pub struct NodeAgentSender {
+    channels: Arc<Mutex<HashMap<String, Channel>>>,
+}
 
 impl NodeAgentSender {
+    /// Return the cached channel for `addr`, connecting on first use
+    ///
+    /// The lock is not held while connecting, so a slow server does not stall
+    /// calls to other addresses. If two calls race, the first channel cached wins.
+    async fn channel(&self, addr: &str) -> Result<Channel, tonic::transport::Error> {
+        if let Some(channel) = self.channels.lock().await.get(addr) {
+            return Ok(channel.clone());
+        }
+
+        let channel = rpc::connect(addr).await?;
+        Ok(self
+            .channels
+            .lock()
+            .await
+            .entry(addr.to_string())
+            .or_insert(channel)
+            .clone())
+    }
+
+    /// Drop the cached channel for `addr` if the call failed at transport level or timed out
+    async fn check<T>(
+        &self,
+        addr: &str,
+        result: Result<tonic::Response<T>, Status>,
+    ) -> Result<tonic::Response<T>, Status> {
+        if let Err(status) = &result {
+            if matches!(status.code(), Code::Unavailable | Code::DeadlineExceeded) {
+                self.channels.lock().await.remove(addr);
+            }
+        }
+        result
+    }
+
     /// Trigger an action for a scenario
     pub async fn trigger_action(
         &mut self,
         action: Action,
     ) -> Result<tonic::Response<Response>, Status> {
         let addr = common::statemanager::connect_server();
-        let client = StateManagerConnectionClient::connect(addr).await;
-        match client {
-            Ok(mut client) => {
+        match self.channel(&addr).await {
+            Ok(channel) => {
                 // Send the action
-                client.send_action(Request::new(action)).await
+                let mut client = StateManagerConnectionClient::new(channel);
+                let result = rpc::call(action, |request| client.send_action(request)).await;
+                self.check(&addr, result).await
             }
             Err(e) => {
                 // Handle connection error
@@ -52,14 +101,15 @@ impl NodeAgentSender {
         let master_ip = config.nodeagent.master_ip.clone();
         let addr = format!("http://{}:47003", master_ip);
 
-        let client = MonitoringServerConnectionClient::connect(addr).await;
-
-        match client {
-            Ok(mut client) => {
+        match self.channel(&addr).await {
+            Ok(channel) => {
                 // Send the container list
-                client
-                    .send_container_list(Request::new(container_list))
-                    .await
+                let mut client = MonitoringServerConnectionClient::new(channel);
+                let result = rpc::call(container_list, |request| {
+                    client.send_container_list(request)
+                })
+                .await;
+                self.check(&addr, result).await
             }
             Err(e) => {
                 // Handle connection error
@@ -77,10 +127,34 @@ impl NodeAgentSender {
         let master_ip = config.nodeagent.master_ip.clone();
         let addr = format!("http://{}:47003", master_ip);
 
-        let client = MonitoringServerConnectionClient::connect(addr).await;
+        match self.channel(&addr).await {
+            Ok(channel) => {
+                let mut client = MonitoringServerConnectionClient::new(channel);
+                let result = rpc::call(node_info, |request| client.send_node_info(request)).await;
+                self.check(&addr, result).await
+            }
+            Err(e) => Err(Status::unknown(format!("Failed to connect: {}", e))),
+        }
+    }
 
-        match client {
-            Ok(mut client) => client.send_node_info(Request::new(node_info)).await,
+    /// Stream container log lines to the monitoring server
+    pub async fn send_logs(
+        &mut self,
+        entries: Vec<LogEntry>,
+    ) -> Result<tonic::Response<SendLogsResponse>, Status> {
+        let config = crate::config::Config::get();
+        let master_ip = config.nodeagent.master_ip.clone();
+        let addr = format!("http://{}:47003", master_ip);
+
+        match self.channel(&addr).await {
+            Ok(channel) => {
+                let mut client = MonitoringServerConnectionClient::new(channel);
+                let result = rpc::call(futures::stream::iter(entries), |request| {
+                    client.send_logs(request)
+                })
+                .await;
+                self.check(&addr, result).await
+            }
             Err(e) => Err(Status::unknown(format!("Failed to connect: {}", e))),
         }
     }
@@ -94,14 +168,15 @@ impl NodeAgentSender {
         let master_ip = config.nodeagent.master_ip.clone();
         let addr = format!("http://{}:47006", master_ip);
 
-        let client = StateManagerConnectionClient::connect(addr).await;
-
-        match client {
-            Ok(mut client) => {
+        match self.channel(&addr).await {
+            Ok(channel) => {
                 // Send the changed container list
-                client
-                    .send_changed_container_list(Request::new(container_list))
-                    .await
+                let mut client = StateManagerConnectionClient::new(channel);
+                let result = rpc::call(container_list, |request| {
+                    client.send_changed_container_list(request)
+                })
+                .await;
+                self.check(&addr, result).await
             }
             Err(e) => {
                 // Handle connection error
@@ -119,13 +194,14 @@ impl NodeAgentSender {
         let master_ip = config.nodeagent.master_ip.clone();
         let addr = format!("http://{}:47098", master_ip);
 
-        let client = ApiServerConnectionClient::connect(addr).await;
-
-        match client {
-            Ok(mut client) => {
-                client
-                    .register_node(Request::new(registration_request))
-                    .await
+        match self.channel(&addr).await {
+            Ok(channel) => {
+                let mut client = ApiServerConnectionClient::new(channel);
+                let result = rpc::call(registration_request, |request| {
+                    client.register_node(request)
+                })
+                .await;
+                self.check(&addr, result).await
             }
             Err(e) => Err(Status::unknown(format!(
                 "Failed to connect to API server: {}",
@@ -135,30 +211,29 @@ impl NodeAgentSender {
     }
 
     /// Send heartbeat to the API server
+    ///
+    /// `ack` is false in the response if the API server does not know this
+    /// node, e.g. because it was removed while unreachable.
     pub async fn send_heartbeat(
         &mut self,
-        _heartbeat_request: HeartbeatRequest,
+        heartbeat_request: HeartbeatRequest,
     ) -> Result<tonic::Response<HeartbeatResponse>, Status> {
-        // For NodeAgent sender, we use the NodeAgent service to send heartbeat
-        // This is a local operation that would be handled by the node's own receiver
-        // In practice, heartbeats are typically sent from NodeAgent to API server
-        // but this implementation allows for local heartbeat processing
-
-        // TODO: Implement heartbeat sending to API server if needed
-        // For now, return a success response
-        // Use master_ip from config
         let config = crate::config::Config::get();
         let master_ip = config.nodeagent.master_ip.clone();
-        let master_endpoint = format!("http://{}:47098", master_ip);
-
-        Ok(tonic::Response::new(HeartbeatResponse {
-            ack: true,
-            updated_config: Some(common::nodeagent::fromapiserver::ClusterConfig {
-                master_endpoint,
-                heartbeat_interval: 30,
-                settings: std::collections::HashMap::new(),
-            }),
-        }))
+        let addr = format!("http://{}:47098", master_ip);
+
+        match self.channel(&addr).await {
+            Ok(channel) => {
+                let mut client = ApiServerConnectionClient::new(channel);
+                let result =
+                    rpc::call(heartbeat_request, |request| client.heartbeat(request)).await;
+                self.check(&addr, result).await
+            }
+            Err(e) => Err(Status::unknown(format!(
+                "Failed to connect to API server: {}",
+                e
+            ))),
+        }
     }
 
     /// Send status report to the API server
@@ -265,15 +340,12 @@ mod tests {
     }
 
     #[tokio::test]
-    async fn test_send_heartbeat_returns_success() {
+    async fn test_send_heartbeat_without_api_server_fails() {
         let mut sender = NodeAgentSender::default();
 
         let req = HeartbeatRequest::default();
         let result = sender.send_heartbeat(req).await;
-        assert!(result.is_ok());
-        let resp = result.unwrap().into_inner();
-        assert!(resp.ack);
-        assert_eq!(resp.updated_config.as_ref().unwrap().heartbeat_interval, 30);
+        assert!(result.is_err());
     }
 
     #[tokio::test]
@@ -337,8 +409,8 @@ mod tests {
         let req = HeartbeatRequest::default();
         let result1 = sender.send_heartbeat(req.clone()).await;
         let result2 = sender.send_heartbeat(req).await;
-        assert!(result1.is_ok());
-        assert!(result2.is_ok());
+        assert!(result1.is_err());
+        assert!(result2.is_err());
     }
 
     #[tokio::test]
@@ -351,4 +423,149 @@ mod tests {
         assert!(result1.is_ok());
         assert!(result2.is_ok());
     }
+
+    mod channel_reuse {
+        use crate::grpc::sender::NodeAgentSender;
+        use common::monitoringserver::monitoring_server_connection_client::MonitoringServerConnectionClient;
+        use common::monitoringserver::monitoring_server_connection_server::{
+            MonitoringServerConnection, MonitoringServerConnectionServer,
+        };
+        use common::monitoringserver::{
+            ContainerList, GetLogsRequest, GetLogsResponse, LogEntry, NodeInfo,
+            SendContainerListResponse, SendLogsResponse, SendNodeInfoResponse, StorageStatsRequest,
+            StorageStatsResponse, StressMonitoringMetric, StressMonitoringMetricResponse,
+        };
+        use std::net::SocketAddr;
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        use std::sync::Arc;
+        use tokio_stream::StreamExt;
+        use tonic::{Request, Response, Status};
+
+        struct MockMonitoringServer;
+
+        #[tonic::async_trait]
+        impl MonitoringServerConnection for MockMonitoringServer {
+            async fn send_container_list(
+                &self,
+                _request: Request<ContainerList>,
+            ) -> Result<Response<SendContainerListResponse>, Status> {
+                Ok(Response::new(SendContainerListResponse {
+                    resp: "ok".to_string(),
+                }))
+            }
+
+            async fn send_node_info(
+                &self,
+                _request: Request<NodeInfo>,
+            ) -> Result<Response<SendNodeInfoResponse>, Status> {
+                Ok(Response::new(SendNodeInfoResponse {
+                    resp: "ok".to_string(),
+                }))
+            }
+
+            async fn send_stress_monitoring_metric(
+                &self,
+                _request: Request<StressMonitoringMetric>,
+            ) -> Result<Response<StressMonitoringMetricResponse>, Status> {
+                Ok(Response::new(StressMonitoringMetricResponse {
+                    resp: "ok".to_string(),
+                }))
+            }
+
+            async fn get_storage_stats(
+                &self,
+                _request: Request<StorageStatsRequest>,
+            ) -> Result<Response<StorageStatsResponse>, Status> {
+                Ok(Response::new(StorageStatsResponse::default()))
+            }
+
+            async fn send_logs(
+                &self,
+                _request: Request<tonic::Streaming<LogEntry>>,
+            ) -> Result<Response<SendLogsResponse>, Status> {
+                Ok(Response::new(SendLogsResponse::default()))
+            }
+
+            async fn get_logs(
+                &self,
+                _request: Request<GetLogsRequest>,
+            ) -> Result<Response<GetLogsResponse>, Status> {
+                Ok(Response::new(GetLogsResponse::default()))
+            }
+        }
+
+        /// Start a mock server that counts accepted TCP connections
+        async fn start_counting_server() -> (SocketAddr, Arc<AtomicUsize>) {
+            let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+            let addr = listener.local_addr().unwrap();
+            let connections = Arc::new(AtomicUsize::new(0));
+            let counter = connections.clone();
+            let incoming =
+                tokio_stream::wrappers::TcpListenerStream::new(listener).map(move |stream| {
+                    counter.fetch_add(1, Ordering::SeqCst);
+                    stream
+                });
+            tokio::spawn(
+                tonic::transport::Server::builder()
+                    .add_service(MonitoringServerConnectionServer::new(MockMonitoringServer))
+                    .serve_with_incoming(incoming),
+            );
+            (addr, connections)
+        }
+
+        async fn send(sender: &NodeAgentSender, addr: &str) -> Result<(), Status> {
+            let channel = sender
+                .channel(addr)
+                .await
+                .map_err(|e| Status::unavailable(e.to_string()))?;
+            let mut client = MonitoringServerConnectionClient::new(channel);
+            let result = client
+                .send_node_info(Request::new(NodeInfo::default()))
+                .await;
+            sender.check(addr, result).await.map(|_| ())
+        }
+
+        #[tokio::test]
+        async fn test_multiple_sends_reuse_single_connection() {
+            let (addr, connections) = start_counting_server().await;
+            let addr = format!("http://{}", addr);
+            let sender = NodeAgentSender::default();
+            let cloned = sender.clone();
+
+            for _ in 0..5 {
+                send(&sender, &addr).await.unwrap();
+            }
+            send(&cloned, &addr).await.unwrap();
+
+            assert_eq!(connections.load(Ordering::SeqCst), 1);
+            assert_eq!(sender.channels.lock().await.len(), 1);
+        }
+
+        #[tokio::test]
+        async fn test_unavailable_drops_cached_channel() {
+            let (addr, connections) = start_counting_server().await;
+            let addr = format!("http://{}", addr);
+            let sender = NodeAgentSender::default();
+            send(&sender, &addr).await.unwrap();
+
+            let failed: Result<Response<()>, Status> = Err(Status::unavailable("connection lost"));
+            assert!(sender.check(&addr, failed).await.is_err());
+            assert!(sender.channels.lock().await.is_empty());
+
+            send(&sender, &addr).await.unwrap();
+            assert_eq!(connections.load(Ordering::SeqCst), 2);
+        }
+
+        #[tokio::test]
+        async fn test_application_error_keeps_cached_channel() {
+            let (addr, _connections) = start_counting_server().await;
+            let addr = format!("http://{}", addr);
+            let sender = NodeAgentSender::default();
+            send(&sender, &addr).await.unwrap();
+
+            let failed: Result<Response<()>, Status> = Err(Status::invalid_argument("bad"));
+            assert!(sender.check(&addr, failed).await.is_err());
+            assert_eq!(sender.channels.lock().await.len(), 1);
+        }
+    }
 }
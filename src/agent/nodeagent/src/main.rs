@@ -12,6 +12,7 @@ use common::nodeagent::fromapiserver::{HandleYamlRequest, NodeRegistrationReques
 use std::collections::HashMap;
 use std::path::PathBuf;
 use std::sync::Arc;
+use tracing::{error, info, warn};
 pub mod config;
 pub mod desired_state;
 pub mod grpc;
@@ -21,8 +22,9 @@ pub mod resource;
 pub mod runtime;
 
 use crate::desired_state::DesiredState;
+use common::channel::bounded;
 use common::nodeagent::node_agent_connection_server::NodeAgentConnectionServer;
-use tokio::sync::mpsc::{channel, Receiver, Sender};
+use tokio::sync::mpsc::{Receiver, Sender};
 use tokio::sync::Mutex;
 
 /// Launches the NodeAgentManager in an asynchronous task.
@@ -40,7 +42,7 @@ async fn launch_manager(
 
     match manager.initialize().await {
         Ok(_) => {
-            println!("NodeAgentManager successfully initialized");
+            info!("NodeAgentManager successfully initialized");
             // Add registration with API server
             let mut sender = grpc::sender::NodeAgentSender::default();
 
@@ -55,6 +57,7 @@ async fn launch_manager(
                 hostname: hostname.clone(),
                 ip_address: host_ip.clone(),
                 metadata: std::collections::HashMap::new(),
+                version: Some(common::version::info("nodeagent")),
                 resources: None,
                 node_type: match config.nodeagent.node_type.as_str() {
                     "cloud" => 1,   // NodeType::Cloud as i32
@@ -70,18 +73,21 @@ async fn launch_manager(
             };
 
             // Register with API server
-            match sender.register_with_api_server(registration_request).await {
-                Ok(_) => println!("Successfully registered with API server"),
-                Err(e) => eprintln!("Failed to register with API server: {:?}", e),
+            match sender
+                .register_with_api_server(registration_request.clone())
+                .await
+            {
+                Ok(_) => info!("Successfully registered with API server"),
+                Err(e) => error!("Failed to register with API server: {:?}", e),
             }
 
             // Start heartbeat task
             let mut sender_clone = sender.clone();
             let node_id_clone = node_id.clone();
             tokio::spawn(async move {
-                let mut interval = tokio::time::interval(tokio::time::Duration::from_secs(3));
                 loop {
-                    interval.tick().await;
+                    // Read on every beat so a reloaded interval applies right away
+                    tokio::time::sleep(config::Config::get().heartbeat_interval()).await;
                     let heartbeat_request = common::nodeagent::fromapiserver::HeartbeatRequest {
                         node_id: node_id_clone.clone(),
                         timestamp: std::time::SystemTime::now()
@@ -89,20 +95,30 @@ async fn launch_manager(
                             .unwrap()
                             .as_secs() as i64, // Cast to i64
                     };
-                    // Fix: call on instance, not static method
-                    if let Err(e) = sender_clone.send_heartbeat(heartbeat_request).await {
-                        eprintln!("Failed to send heartbeat: {:?}", e);
+                    match sender_clone.send_heartbeat(heartbeat_request).await {
+                        Ok(response) if !response.get_ref().ack => {
+                            // Removed while unreachable, register again
+                            warn!("API server does not know this node, registering again");
+                            if let Err(e) = sender_clone
+                                .register_with_api_server(registration_request.clone())
+                                .await
+                            {
+                                warn!("Failed to register with API server: {:?}", e);
+                            }
+                        }
+                        Ok(_) => {}
+                        Err(e) => warn!("Failed to send heartbeat: {:?}", e),
                     }
                 }
             });
 
             // Run the manager
             if let Err(e) = manager.run().await {
-                eprintln!("Error running NodeAgentManager: {:?}", e);
+                error!("Error running NodeAgentManager: {:?}", e);
             }
         }
         Err(e) => {
-            eprintln!("Failed to initialize NodeAgentManager: {:?}", e);
+            error!("Failed to initialize NodeAgentManager: {:?}", e);
         }
     }
 }
@@ -110,14 +126,25 @@ async fn launch_manager(
 /// Initializes the NodeAgent gRPC server.
 ///
 /// Sets up the gRPC service and starts listening for incoming requests.
+/// The `grpc.health.v1.Health` service reports `SERVING` while the manager
+/// channel is open and `NOT_SERVING` once shutdown starts.
 async fn initialize(
     tx_grpc: Sender<HandleYamlRequest>,
     hostname: String,
     config: config::Config,
     desired_states_cache: Arc<Mutex<HashMap<String, DesiredState>>>,
 ) {
+    use common::health::{ServerHealth, PROBE_INTERVAL};
     use tonic::transport::Server;
 
+    let (health, health_service) =
+        ServerHealth::new::<NodeAgentConnectionServer<grpc::receiver::NodeAgentReceiver>>();
+    let probe_tx = tx_grpc.clone();
+    health.spawn_probe(PROBE_INTERVAL, move || {
+        let channel_open = !probe_tx.is_closed();
+        async move { channel_open }
+    });
+
     // Use IP address from config file
     let host_ip = config.get_host_ip();
     let node_name = config.get_node_name();
@@ -145,18 +172,24 @@ async fn initialize(
     let addr = format!("{}:{}", host_ip, config.nodeagent.grpc_port)
         .parse()
         .expect("nodeagent address parsing error");
-    println!("NodeAgent listening on {}", addr);
-    println!(
+    info!("NodeAgent listening on {}", addr);
+    info!(
         "NodeAgent config - master_ip: {}, grpc_port: {}",
         config.nodeagent.master_ip, config.nodeagent.grpc_port
     );
 
     let _ = Server::builder()
+        .layer(common::trace::TraceLayer)
+        .add_service(health_service)
+        .add_service(common::version::service("nodeagent"))
         .add_service(NodeAgentConnectionServer::new(server))
-        .serve(addr)
+        .serve_with_shutdown(addr, health.shutdown_signal())
         .await;
 }
 
+/// How often the config file is checked for changes
+const CONFIG_WATCH_PERIOD: std::time::Duration = std::time::Duration::from_secs(2);
+
 /// Main entry point for the NodeAgent binary.
 ///
 /// Sets up the async runtime, creates the communication channel, and launches
@@ -174,26 +207,29 @@ struct Args {
 async fn main() {
     // Parse command line arguments
     let args = Args::parse();
+    common::logging::init("nodeagent");
 
     // Load configuration file
     let app_config = match config::Config::load(&args.config) {
         Ok(config) => {
-            println!("Loaded configuration from {}", args.config.display());
+            info!("Loaded configuration from {}", args.config.display());
             config
         }
         Err(err) => {
-            eprintln!(
+            error!(
                 "Error loading configuration from {}: {}",
                 args.config.display(),
                 err
             );
-            eprintln!("Falling back to default configuration");
+            warn!("Falling back to default configuration");
             config::Config::default()
         }
     };
 
     // Set global config for other parts of the application
     config::Config::set_global(app_config.clone());
+    // Apply edits of the heartbeat interval, log level and monitoring interval without a restart
+    config::watch(args.config.clone(), CONFIG_WATCH_PERIOD);
 
     let mut hostname = app_config.get_hostname();
     if hostname.is_empty() || hostname == "$(hostname)" {
@@ -207,13 +243,15 @@ async fn main() {
         .trim()
         .to_string();
     }
-    println!("Starting NodeAgent on host: {}", hostname);
+    info!("Starting NodeAgent on host: {}", hostname);
 
     // Create the shared desired states cache - used by both manager and gRPC receiver
     let desired_states_cache: Arc<Mutex<HashMap<String, DesiredState>>> =
         Arc::new(Mutex::new(HashMap::new()));
 
-    let (tx_grpc, rx_grpc) = channel::<HandleYamlRequest>(100);
+    let channels = &common::setting::get_config().channels;
+    let (tx_grpc, rx_grpc) =
+        bounded::<HandleYamlRequest>("nodeagent_yaml", channels.nodeagent_yaml);
     let mgr = launch_manager(
         rx_grpc,
         hostname.clone(),
@@ -222,7 +260,11 @@ async fn main() {
     );
     let grpc = initialize(tx_grpc, hostname, app_config, desired_states_cache);
 
-    tokio::join!(mgr, grpc);
+    // Stop the process once the gRPC server has shut down
+    tokio::select! {
+        _ = mgr => {}
+        _ = grpc => {}
+    }
 }
 
 #[cfg(feature = "tarpaulin_include")]
@@ -284,6 +326,7 @@ mod tests {
             hostname: hostname.clone(),
             ip_address: host_ip.clone(),
             metadata: HashMap::new(),
+            version: None,
             resources: None,
             node_type: match config.nodeagent.node_type.as_str() {
                 "cloud" => 1,
@@ -312,7 +355,7 @@ mod tests {
         .trim()
         .to_string();
 
-        let r = crate::resource::container::inspect(hostname).await;
+        let r = crate::resource::container::inspect(hostname, &Default::default()).await;
         println!("{:#?}", r);
     }
     #[tokio::test]
@@ -8,6 +8,7 @@
 //! a gRPC sender for communicating with the monitoring server or other services.
 //! It is designed to be thread-safe and run in an async context.
 use crate::desired_state::DesiredState;
+use crate::grpc::queue::{ContainerQueue, DROP_REPORT_INTERVAL};
 use crate::grpc::sender::NodeAgentSender;
 use common::monitoringserver::{ContainerInfo, ContainerList};
 use common::nodeagent::fromapiserver::HandleYamlRequest;
@@ -15,6 +16,7 @@ use common::Result;
 use std::collections::HashMap;
 use std::sync::Arc;
 use tokio::sync::{mpsc, Mutex};
+use tracing::{debug, error, info, warn};
 
 /// Main manager struct for NodeAgent.
 ///
@@ -25,6 +27,10 @@ pub struct NodeAgentManager {
     rx_grpc: Arc<Mutex<mpsc::Receiver<HandleYamlRequest>>>,
     /// gRPC sender for monitoring server
     sender: Arc<Mutex<NodeAgentSender>>,
+    /// Container lists waiting to be sent to the monitoring server
+    containers: ContainerQueue,
+    /// Receiving side of `containers`, drained by the forwarding task
+    rx_containers: Arc<Mutex<mpsc::Receiver<ContainerList>>>,
     // Add other shared state as needed
     hostname: String,
     /// In-memory cache of desired states for self-healing.
@@ -44,9 +50,13 @@ impl NodeAgentManager {
         hostname: String,
         desired_states_cache: Arc<Mutex<HashMap<String, DesiredState>>>,
     ) -> Self {
+        let capacity = common::setting::get_config().channels.nodeagent_container;
+        let (containers, rx_containers) = ContainerQueue::new(capacity);
         Self {
             rx_grpc: Arc::new(Mutex::new(rx)),
             sender: Arc::new(Mutex::new(NodeAgentSender::default())),
+            containers,
+            rx_containers: Arc::new(Mutex::new(rx_containers)),
             hostname,
             desired_states_cache,
         }
@@ -54,7 +64,7 @@ impl NodeAgentManager {
 
     /// Initializes the NodeAgentManager (e.g., loads scenarios, prepares state).
     pub async fn initialize(&mut self) -> Result<()> {
-        println!("NodeAgentManager init");
+        info!("NodeAgentManager init");
         // Add initialization logic here (e.g., read scenarios, subscribe, etc.)
         Ok(())
     }
@@ -81,31 +91,54 @@ impl NodeAgentManager {
 
     /// Background task: Periodically gathers container info using inspect().
     ///
-    /// This runs in an infinite loop and logs or processes container info as needed.
+    /// The containers are inspected every `container_poll.interval` seconds
+    /// and each successful inspection is queued for the monitoring server.
+    /// Queuing never waits: while the monitoring server lags behind and the
+    /// queue is full, inspections are dropped and counted instead.
+    /// While inspections fail or take longer than the interval, the wait
+    /// between them grows up to `container_poll.max_backoff`.
     async fn gather_container_info_loop(&self) {
         use crate::resource::container::inspect;
-        use tokio::time::{sleep, Duration};
+        use tokio::time::{sleep, Instant};
 
         // This is the previous container list for comparison
         let mut previous_container_list = Vec::new();
+        // Consecutive inspections that failed or were slower than the interval
+        let mut failures: u32 = 0;
 
         loop {
-            let container_list = inspect(self.hostname.clone()).await.unwrap_or_default();
+            let poll = crate::config::Config::get()
+                .nodeagent
+                .container_poll
+                .clone();
+            let started = Instant::now();
+            let container_list = match inspect(self.hostname.clone(), &poll.filter).await {
+                Ok(container_list) => {
+                    failures = if started.elapsed() > poll.interval() {
+                        failures.saturating_add(1)
+                    } else {
+                        0
+                    };
+                    container_list
+                }
+                Err(e) => {
+                    failures = failures.saturating_add(1);
+                    let delay = poll.delay(failures);
+                    warn!(
+                        "Failed to inspect containers: {}. Retrying in {:?}",
+                        e, delay
+                    );
+                    sleep(delay).await;
+                    continue;
+                }
+            };
             let node = self.hostname.clone();
 
-            // Send the container info to the monitoring server
-            {
-                let mut sender = self.sender.lock().await;
-                if let Err(e) = sender
-                    .send_container_list(ContainerList {
-                        node_name: node.clone(),
-                        containers: container_list.clone(),
-                    })
-                    .await
-                {
-                    eprintln!("[NodeAgent] Error sending container info: {}", e);
-                }
-            }
+            // Queue the container info for the monitoring server
+            self.containers.push(ContainerList {
+                node_name: node.clone(),
+                containers: container_list.clone(),
+            });
 
             // Check if the container list is changed from the previous one except for ContainerList.stats
             // (which is not included in the comparison)
@@ -127,11 +160,25 @@ impl NodeAgentManager {
                     })
                     .await
                 {
-                    eprintln!("[NodeAgent] Error sending changed container list: {}", e);
+                    error!("Error sending changed container list: {}", e);
                 }
             }
 
-            sleep(Duration::from_secs(1)).await;
+            sleep(poll.delay(failures)).await;
+        }
+    }
+
+    /// Background task: Sends the queued container lists to the monitoring server.
+    ///
+    /// Runs apart from the collection loop with its own clone of the sender,
+    /// so only this task waits on a slow monitoring server.
+    async fn forward_container_lists_loop(&self) {
+        let mut sender = self.sender.lock().await.clone();
+        let mut rx_containers = self.rx_containers.lock().await;
+        while let Some(container_list) = rx_containers.recv().await {
+            if let Err(e) = sender.send_container_list(container_list).await {
+                error!("Error sending container info: {}", e);
+            }
         }
     }
 
@@ -141,7 +188,7 @@ impl NodeAgentManager {
     async fn gather_node_info_loop(&self) {
         use crate::resource::nodeinfo::extract_node_info_delta;
         use common::monitoringserver::NodeInfo;
-        use tokio::time::{sleep, Duration};
+        use tokio::time::sleep;
 
         loop {
             let node_info_data = extract_node_info_delta();
@@ -162,17 +209,18 @@ impl NodeAgentManager {
                 os: node_info_data.os,
                 arch: node_info_data.arch,
                 ip: node_info_data.ip,
+                dropped_container_updates: self.containers.dropped(),
             };
 
             // Send NodeInfo to monitoring server
             {
                 let mut sender = self.sender.lock().await;
                 if let Err(e) = sender.send_node_info(node_info.clone()).await {
-                    eprintln!("[NodeAgent] Error sending node info: {}", e);
+                    error!("Error sending node info: {}", e);
                 }
             }
 
-            println!(
+            debug!(
                 "[NodeInfo] CPU: {:.2}%, CPU Count: {}, GPU Count: {}, Mem: {}/{} KB ({:.2}%), Net RX: {} B, Net TX: {} B, Disk Read: {} B, Disk Write: {} B, OS: {}, Arch: {}, IP: {}",
                 node_info.cpu_usage,
                 node_info.cpu_count,
@@ -188,7 +236,69 @@ impl NodeAgentManager {
                 node_info.arch,
                 node_info.ip
             );
-            sleep(Duration::from_secs(1)).await;
+            sleep(crate::config::Config::get().collection_interval()).await;
+        }
+    }
+
+    /// Background task: Periodically sends the new log lines of the managed
+    /// containers to the monitoring server.
+    ///
+    /// Lines are read every `log_tail.interval` seconds and labelled with the
+    /// pod name. The first read of a container only sends its last lines.
+    /// Nothing is sent while the interval is 0.
+    async fn tail_logs_loop(&self) {
+        use crate::resource::logs::tail;
+        use common::monitoringserver::LogEntry;
+        use tokio::time::{sleep, Duration};
+
+        // Timestamp of the last line sent, by container ID
+        let mut last_sent: HashMap<String, i64> = HashMap::new();
+
+        loop {
+            let interval = crate::config::Config::get().nodeagent.log_tail.interval;
+            if interval == 0 {
+                last_sent.clear();
+                sleep(Duration::from_secs(1)).await;
+                continue;
+            }
+
+            let containers: Vec<(String, String)> = {
+                let cache = self.desired_states_cache.lock().await;
+                cache
+                    .values()
+                    .filter(|desired| !desired.container_id.is_empty())
+                    .map(|desired| (desired.pod_name.clone(), desired.container_id.clone()))
+                    .collect()
+            };
+            last_sent.retain(|id, _| containers.iter().any(|(_, c)| c == id));
+
+            let mut entries = Vec::new();
+            for (pod_name, id) in containers {
+                let lines = match tail(&id, last_sent.get(&id).copied()).await {
+                    Ok(lines) => lines,
+                    Err(e) => {
+                        debug!("Failed to read logs of {}: {}", pod_name, e);
+                        continue;
+                    }
+                };
+                if let Some((timestamp, _)) = lines.last() {
+                    last_sent.insert(id, *timestamp);
+                }
+                entries.extend(lines.into_iter().map(|(timestamp, line)| LogEntry {
+                    node: self.hostname.clone(),
+                    container: pod_name.clone(),
+                    timestamp,
+                    line,
+                }));
+            }
+
+            if !entries.is_empty() {
+                let mut sender = self.sender.lock().await;
+                if let Err(e) = sender.send_logs(entries).await {
+                    error!("Error sending container logs: {}", e);
+                }
+            }
+            sleep(Duration::from_secs(interval)).await;
         }
     }
 
@@ -200,7 +310,7 @@ impl NodeAgentManager {
         let grpc_manager = Arc::clone(&arc_self);
         let grpc_processor = tokio::spawn(async move {
             if let Err(e) = grpc_manager.process_grpc_requests().await {
-                eprintln!("Error in gRPC processor: {:?}", e);
+                error!("Error in gRPC processor: {:?}", e);
             }
         });
         let container_manager = Arc::clone(&arc_self);
@@ -208,12 +318,28 @@ impl NodeAgentManager {
             container_manager.gather_container_info_loop().await;
         });
 
+        // Spawn the tasks sending the container lists and reporting the dropped ones
+        let forward_manager = Arc::clone(&arc_self);
+        let container_forwarder = tokio::spawn(async move {
+            forward_manager.forward_container_lists_loop().await;
+        });
+        let containers = arc_self.containers.clone();
+        let drop_reporter = tokio::spawn(async move {
+            containers.report_dropped(DROP_REPORT_INTERVAL).await;
+        });
+
         // Spawn a background task to periodically extract and print system info
         let nodeinfo_manager = Arc::clone(&arc_self);
         let nodeinfo_task = tokio::spawn(async move {
             nodeinfo_manager.gather_node_info_loop().await;
         });
 
+        // Spawn a background task to send the container logs
+        let logs_manager = Arc::clone(&arc_self);
+        let logs_task = tokio::spawn(async move {
+            logs_manager.tail_logs_loop().await;
+        });
+
         // Spawn the reconciliation loop to detect and recover exited containers
         let reconcile_cache = Arc::clone(&arc_self.desired_states_cache);
         let reconciler = tokio::spawn(async move {
@@ -229,11 +355,14 @@ impl NodeAgentManager {
         let _ = tokio::try_join!(
             grpc_processor,
             container_gatherer,
+            container_forwarder,
+            drop_reporter,
             nodeinfo_task,
+            logs_task,
             reconciler,
             probe_task
         );
-        println!("NodeAgentManager stopped");
+        info!("NodeAgentManager stopped");
         Ok(())
     }
 }
@@ -284,7 +413,7 @@ pub async fn reconciliation_loop(desired_states_cache: Arc<Mutex<HashMap<String,
         let actual_containers = match get_list().await {
             Ok(containers) => containers,
             Err(e) => {
-                eprintln!("[Reconciliation] Failed to list containers: {:?}", e);
+                error!("[Reconciliation] Failed to list containers: {:?}", e);
                 sleep(Duration::from_secs(1)).await;
                 continue;
             }
@@ -304,7 +433,7 @@ pub async fn reconciliation_loop(desired_states_cache: Arc<Mutex<HashMap<String,
             match actual {
                 None => {
                     // Container does not exist in Podman at all (completely removed).
-                    eprintln!(
+                    warn!(
                         "[Reconciliation] Container '{}' not found for pod '{}'",
                         desired.container_id, pod_name
                     );
@@ -314,7 +443,7 @@ pub async fn reconciliation_loop(desired_states_cache: Arc<Mutex<HashMap<String,
                     if let Some(new_id) = handle_missing_container(desired).await {
                         let mut cache = desired_states_cache.lock().await;
                         if let Some(state) = cache.get_mut(pod_name) {
-                            eprintln!(
+                            info!(
                                 "[Reconciliation] Updating container_id for pod '{}': {} → {}",
                                 pod_name, state.container_id, new_id
                             );
@@ -339,14 +468,14 @@ pub async fn reconciliation_loop(desired_states_cache: Arc<Mutex<HashMap<String,
                     let exit_code = match get_inspect(&desired.container_id).await {
                         Ok(inspect) => inspect.State.ExitCode,
                         Err(e) => {
-                            eprintln!(
+                            warn!(
                                 "[Reconciliation] Failed to inspect container '{}': {:?}; using exit code 1",
                                 desired.container_id, e
                             );
                             1
                         }
                     };
-                    eprintln!(
+                    warn!(
                         "[Reconciliation] Container '{}' in state '{}' for pod '{}' (exit code {})",
                         desired.container_id, container.State, pod_name, exit_code
                     );
@@ -383,7 +512,7 @@ async fn handle_missing_container(desired: &DesiredState) -> Option<String> {
         RestartPolicy::Never => false,
     };
 
-    eprintln!(
+    warn!(
         "[Reconciliation] Pod '{}': container '{}' missing, policy={:?}, restart={}",
         desired.pod_name, desired.container_id, desired.restart_policy, should_restart
     );
@@ -393,7 +522,7 @@ async fn handle_missing_container(desired: &DesiredState) -> Option<String> {
     }
 
     if desired.pod_yaml.is_empty() {
-        eprintln!(
+        error!(
             "[Reconciliation] Pod '{}': pod YAML not available, cannot recreate container",
             desired.pod_name
         );
@@ -404,12 +533,12 @@ async fn handle_missing_container(desired: &DesiredState) -> Option<String> {
         Ok(ids) => {
             let new_id = ids.into_iter().next();
             if let Some(ref id) = new_id {
-                eprintln!(
+                info!(
                     "[Reconciliation] Pod '{}': recreated container with new ID '{}'",
                     desired.pod_name, id
                 );
             } else {
-                eprintln!(
+                error!(
                     "[Reconciliation] Pod '{}': start() returned no container IDs",
                     desired.pod_name
                 );
@@ -417,7 +546,7 @@ async fn handle_missing_container(desired: &DesiredState) -> Option<String> {
             new_id
         }
         Err(e) => {
-            eprintln!(
+            error!(
                 "[Reconciliation] Pod '{}': failed to recreate container: {:?}",
                 desired.pod_name, e
             );
@@ -452,7 +581,7 @@ async fn handle_exited_container(
         RestartPolicy::Never => false,
     };
 
-    eprintln!(
+    warn!(
         "[Reconciliation] Pod '{}': container '{}' exited (code {}), policy={:?}, restart={}",
         desired.pod_name, desired.container_id, exit_code, desired.restart_policy, should_restart
     );
@@ -481,7 +610,7 @@ async fn handle_exited_container(
 
         // Safety rule: stop restarting after the 5-minute window has elapsed.
         if elapsed.as_secs() >= 300 {
-            eprintln!(
+            error!(
                 "[Reconciliation] Container '{}': backoff period expired (5 min), \
                  stop restarting for vehicle safety",
                 desired.container_id
@@ -492,7 +621,7 @@ async fn handle_exited_container(
         // Not yet past the required backoff delay — come back next loop iteration.
         let required_backoff = calculate_backoff(backoff_state.restart_count);
         if elapsed < required_backoff {
-            eprintln!(
+            info!(
                 "[Reconciliation] Container '{}': waiting backoff ({:.0}s remaining)",
                 desired.container_id,
                 (required_backoff - elapsed).as_secs_f64()
@@ -502,7 +631,7 @@ async fn handle_exited_container(
     }
 
     // Attempt restart via Podman.
-    eprintln!(
+    info!(
         "[Reconciliation] Container '{}': restarting (attempt #{})",
         desired.container_id,
         backoff_state.restart_count + 1
@@ -511,7 +640,7 @@ async fn handle_exited_container(
     let restart_path = format!("/v4.0.0/libpod/containers/{}/restart", desired.container_id);
     match crate::runtime::podman::post(&restart_path, Body::empty()).await {
         Ok(_) => {
-            eprintln!(
+            info!(
                 "[Reconciliation] Container '{}' restarted successfully",
                 desired.container_id
             );
@@ -526,7 +655,7 @@ async fn handle_exited_container(
             );
         }
         Err(e) => {
-            eprintln!(
+            error!(
                 "[Reconciliation] Failed to restart container '{}': {:?}",
                 desired.container_id, e
             );
@@ -546,7 +675,7 @@ fn containers_equal_except_stats<'a>(a: &'a [ContainerInfo], b: &'a [ContainerIn
             && c1.state == c2.state
             && c1.config == c2.config
             && c1.annotation == c2.annotation
-        // do NOT compare c1.stats/c2.stats
+        // do NOT compare c1.stats/c2.stats or c1.usage/c2.usage
     })
 }
 
@@ -619,6 +748,7 @@ spec:
             config: HashMap::new(),
             annotation: HashMap::new(),
             stats: HashMap::new(),
+            usage: None,
         };
         let c2 = ContainerInfo {
             id: "id1".to_string(),
@@ -628,6 +758,7 @@ spec:
             config: HashMap::new(),
             annotation: HashMap::new(),
             stats: HashMap::new(),
+            usage: None,
         };
         let c3 = ContainerInfo {
             id: "id2".to_string(),
@@ -637,6 +768,7 @@ spec:
             config: HashMap::new(),
             annotation: HashMap::new(),
             stats: HashMap::new(),
+            usage: None,
         };
 
         // True: stats ignored, all else equal
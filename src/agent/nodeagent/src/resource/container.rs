@@ -2,25 +2,39 @@
 * SPDX-FileCopyrightText: Copyright 2024 LG Electronics Inc.
 * SPDX-License-Identifier: Apache-2.0
 */
+use super::stats::{usage_from_cgroup, usage_from_stats};
 use super::{Container, ContainerError, ContainerInspect, ContainerStats};
+use crate::config::ContainerFilter;
 use crate::runtime::podman::get;
 use common::monitoringserver::ContainerInfo;
-use futures::future::try_join_all;
+use futures::future::join_all;
 use std::collections::HashMap;
+use std::path::Path;
+use tracing::{debug, warn};
 
 pub type Result<T> = core::result::Result<T, Box<dyn std::error::Error + Send + Sync>>;
 
-pub async fn inspect(hostname: String) -> std::result::Result<Vec<ContainerInfo>, ContainerError> {
+/// Inspects the containers of the node selected by `filter`
+///
+/// Containers the filter rejects are not inspected at all. Containers
+/// removed while they are being inspected are left out instead of failing
+/// the whole list.
+pub async fn inspect(
+    hostname: String,
+    filter: &ContainerFilter,
+) -> std::result::Result<Vec<ContainerInfo>, ContainerError> {
     let list = get_list().await?;
-    let infos: Vec<ContainerInfo> = try_join_all(list.iter().map(|container| {
+    let results = join_all(select(&list, filter).into_iter().map(|container| {
         let id = container.Id.clone();
         let host_name = hostname.clone();
         async move {
             let inspect = get_inspect(&id).await?;
             let mut stats_map = HashMap::new();
+            let mut usage = None;
             if inspect.State.Status == "running" {
                 match get_stats(&id).await {
                     Ok(stats) => {
+                        usage = Some(usage_from_stats(&stats));
                         stats_map.insert(
                             "CpuTotalUsage".to_string(),
                             stats.cpu_stats.cpu_usage.total_usage.to_string(),
@@ -57,7 +71,24 @@ pub async fn inspect(hostname: String) -> std::result::Result<Vec<ContainerInfo>
                     }
                     Err(e) => {
                         println!("Failed to get stats for {}: {:?}", id, e);
-                        stats_map.insert("Status".to_string(), "StatsUnavailable".to_string());
+                        match usage_from_cgroup(
+                            Path::new("/proc"),
+                            Path::new("/sys/fs/cgroup"),
+                            inspect.State.Pid,
+                        )
+                        .await
+                        {
+                            Ok(cgroup_usage) => usage = Some(cgroup_usage),
+                            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                                debug!("Container {} is gone, skipping it.", id);
+                                return Ok(None);
+                            }
+                            Err(e) => {
+                                warn!("Failed to read cgroup of {}: {:?}", id, e);
+                                stats_map
+                                    .insert("Status".to_string(), "StatsUnavailable".to_string());
+                            }
+                        }
                     }
                 }
             } else {
@@ -113,7 +144,7 @@ pub async fn inspect(hostname: String) -> std::result::Result<Vec<ContainerInfo>
             } else {
                 HashMap::new()
             };
-            Ok::<ContainerInfo, ContainerError>(ContainerInfo {
+            Ok::<Option<ContainerInfo>, ContainerError>(Some(ContainerInfo {
                 id: inspect.Id,
                 names: vec![inspect.Name],
                 image: inspect.Config.Image.clone(),
@@ -121,15 +152,43 @@ pub async fn inspect(hostname: String) -> std::result::Result<Vec<ContainerInfo>
                 config: config_map,
                 annotation: annotation_map,
                 stats: stats_map,
-            })
+                usage,
+            }))
         }
     }))
-    .await
-    .map_err(|e| ContainerError::PodmanApi(Box::new(e)))?
-    .into_iter()
-    .collect();
+    .await;
 
-    Ok(infos)
+    Ok(keep_available(results))
+}
+
+/// Containers of `list` that pass `filter`
+fn select<'a>(list: &'a [Container], filter: &ContainerFilter) -> Vec<&'a Container> {
+    let no_labels = HashMap::new();
+    list.iter()
+        .filter(|container| {
+            filter.matches(
+                &container.Names,
+                container.Labels.as_ref().unwrap_or(&no_labels),
+            )
+        })
+        .collect()
+}
+
+/// Drops containers that could not be inspected, typically because they
+/// were removed after being listed
+fn keep_available(
+    results: Vec<std::result::Result<Option<ContainerInfo>, ContainerError>>,
+) -> Vec<ContainerInfo> {
+    results
+        .into_iter()
+        .filter_map(|result| match result {
+            Ok(info) => info,
+            Err(e) => {
+                warn!("Skipping container that could not be inspected: {}", e);
+                None
+            }
+        })
+        .collect()
 }
 
 pub async fn get_list() -> Result<Vec<Container>> {
@@ -167,7 +226,10 @@ pub async fn get_stats(
 //Unit Test Cases
 #[cfg(test)]
 mod tests {
-    use super::{get_inspect, get_list, inspect, Container, ContainerError, ContainerInspect};
+    use super::{
+        get_inspect, get_list, inspect, keep_available, select, Container, ContainerError,
+        ContainerFilter, ContainerInspect,
+    };
     use common::monitoringserver::ContainerInfo;
     use std::collections::HashMap;
     use tokio;
@@ -218,7 +280,7 @@ mod tests {
         .trim()
         .to_string();
 
-        let result = inspect(hostname).await;
+        let result = inspect(hostname, &ContainerFilter::default()).await;
         assert!(result.is_ok());
         let infos = result.unwrap();
         for info in infos {
@@ -230,4 +292,71 @@ mod tests {
             assert!(info.config.contains_key("Hostname"));
         }
     }
+
+    #[test]
+    fn test_keep_available_skips_removed_containers() {
+        let info = |id: &str| ContainerInfo {
+            id: id.to_string(),
+            names: vec![],
+            image: String::new(),
+            state: HashMap::new(),
+            config: HashMap::new(),
+            annotation: HashMap::new(),
+            stats: HashMap::new(),
+            usage: None,
+        };
+        let removed = ContainerError::PodmanApi("no such container".into());
+
+        let infos = keep_available(vec![
+            Ok(Some(info("a"))),
+            Err(removed),
+            Ok(None),
+            Ok(Some(info("b"))),
+        ]);
+        let ids: Vec<&str> = infos.iter().map(|i| i.id.as_str()).collect();
+        assert_eq!(ids, vec!["a", "b"]);
+    }
+
+    #[test]
+    fn test_select_skips_unmatched_containers() {
+        let container = |id: &str, name: &str, labels: &[(&str, &str)]| Container {
+            Id: id.to_string(),
+            Names: vec![name.to_string()],
+            Image: "image".to_string(),
+            State: "running".to_string(),
+            Status: String::new(),
+            Labels: Some(
+                labels
+                    .iter()
+                    .map(|(k, v)| (k.to_string(), v.to_string()))
+                    .collect(),
+            ),
+        };
+        let list = vec![
+            container("1", "helloworld-backend", &[("piccolo", "true")]),
+            container("2", "systemd-journald", &[]),
+            container("3", "helloworld-frontend", &[]),
+            container("4", "bms", &[("piccolo", "true")]),
+            Container {
+                Labels: None,
+                ..container("5", "helloworld-db", &[])
+            },
+        ];
+
+        let ids = |filter: &ContainerFilter| -> Vec<String> {
+            select(&list, filter).iter().map(|c| c.Id.clone()).collect()
+        };
+
+        assert_eq!(ids(&ContainerFilter::default()).len(), list.len());
+        let by_name = ContainerFilter {
+            names: vec!["helloworld-*".to_string()],
+            ..Default::default()
+        };
+        assert_eq!(ids(&by_name), vec!["1", "3", "5"]);
+        let by_name_and_label = ContainerFilter {
+            labels: vec!["piccolo=true".to_string()],
+            ..by_name
+        };
+        assert_eq!(ids(&by_name_and_label), vec!["1"]);
+    }
 }
@@ -0,0 +1,151 @@
+/*
+* SPDX-FileCopyrightText: Copyright 2024 LG Electronics Inc.
+* SPDX-License-Identifier: Apache-2.0
+*/
+//! Tails the logs of the containers through the podman API
+//!
+//! Podman returns the lines with their RFC3339 timestamp. Containers
+//! started without a TTY multiplex stdout and stderr in frames of an 8-byte
+//! header (stream, 3 zero bytes, big-endian length) followed by the data.
+
+use super::container::Result;
+use crate::runtime::podman::get;
+use chrono::DateTime;
+
+/// Lines requested for a container seen for the first time
+pub const INITIAL_TAIL: u32 = 100;
+
+/// Lines of a container logged after `after`, in unix nanoseconds
+///
+/// # Arguments
+///
+/// * `id` - Container ID
+/// * `after` - Timestamp of the last line already read, `None` for the
+///   last `INITIAL_TAIL` lines
+///
+/// # Returns
+///
+/// * `Vec<(i64, String)>` - Timestamp and text of each line, oldest first
+pub async fn tail(id: &str, after: Option<i64>) -> Result<Vec<(i64, String)>> {
+    read(id, Some(INITIAL_TAIL), after).await
+}
+
+/// Lines of a container
+///
+/// # Arguments
+///
+/// * `id` - Container ID
+/// * `tail` - Only the last lines, `None` for all of them
+/// * `after` - Timestamp of the last line already read, overriding `tail`
+///
+/// # Returns
+///
+/// * `Vec<(i64, String)>` - Timestamp and text of each line, oldest first
+pub async fn read(id: &str, tail: Option<u32>, after: Option<i64>) -> Result<Vec<(i64, String)>> {
+    let body = get(&logs_path(id, tail, after)).await?;
+
+    Ok(demux(&body)
+        .lines()
+        .filter_map(parse_line)
+        .filter(|(timestamp, _)| after.is_none_or(|after| *timestamp > after))
+        .collect())
+}
+
+/// Podman API path of the logs of a container
+fn logs_path(id: &str, tail: Option<u32>, after: Option<i64>) -> String {
+    let path = format!(
+        "/v4.0.0/libpod/containers/{}/logs?stdout=true&stderr=true&timestamps=true",
+        id
+    );
+    match (after, tail) {
+        // `since` has a one second resolution, older lines are filtered by `read`
+        (Some(after), _) => format!("{}&since={}", path, after.div_euclid(1_000_000_000)),
+        (None, Some(tail)) => format!("{}&tail={}", path, tail),
+        (None, None) => path,
+    }
+}
+
+/// Text of a log response, with the stream frames removed if there are any
+fn demux(body: &[u8]) -> String {
+    let mut text = Vec::with_capacity(body.len());
+    let mut rest = body;
+    while !rest.is_empty() {
+        let framed = rest.len() >= 8 && rest[0] <= 2 && rest[1..4] == [0, 0, 0];
+        if !framed {
+            // Not multiplexed, e.g. a container with a TTY
+            return String::from_utf8_lossy(body).into_owned();
+        }
+        let len = u32::from_be_bytes([rest[4], rest[5], rest[6], rest[7]]) as usize;
+        let end = (8 + len).min(rest.len());
+        text.extend_from_slice(&rest[8..end]);
+        rest = &rest[end..];
+    }
+    String::from_utf8_lossy(&text).into_owned()
+}
+
+/// Split a `<RFC3339 timestamp> <text>` line
+fn parse_line(line: &str) -> Option<(i64, String)> {
+    let (timestamp, text) = line.split_once(' ')?;
+    let timestamp = DateTime::parse_from_rfc3339(timestamp)
+        .ok()?
+        .timestamp_nanos_opt()?;
+    Some((timestamp, text.to_string()))
+}
+
+//Unit Test Cases
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn frame(stream: u8, data: &str) -> Vec<u8> {
+        let mut frame = vec![stream, 0, 0, 0];
+        frame.extend_from_slice(&(data.len() as u32).to_be_bytes());
+        frame.extend_from_slice(data.as_bytes());
+        frame
+    }
+
+    #[test]
+    fn test_logs_path() {
+        let base = "/v4.0.0/libpod/containers/abc/logs?stdout=true&stderr=true&timestamps=true";
+        assert_eq!(logs_path("abc", None, None), base);
+        assert_eq!(
+            logs_path("abc", Some(10), None),
+            format!("{}&tail=10", base)
+        );
+        assert_eq!(
+            logs_path("abc", Some(10), Some(1_704_067_201_500_000_000)),
+            format!("{}&since=1704067201", base)
+        );
+    }
+
+    #[test]
+    fn test_demux_frames() {
+        let mut body = frame(1, "2024-01-01T00:00:00Z out\n");
+        body.extend(frame(2, "2024-01-01T00:00:01Z err\n"));
+
+        assert_eq!(
+            demux(&body),
+            "2024-01-01T00:00:00Z out\n2024-01-01T00:00:01Z err\n"
+        );
+    }
+
+    #[test]
+    fn test_demux_plain_text() {
+        let body = b"2024-01-01T00:00:00Z hello\n";
+        assert_eq!(demux(body), "2024-01-01T00:00:00Z hello\n");
+    }
+
+    #[test]
+    fn test_parse_line() {
+        assert_eq!(
+            parse_line("2024-01-01T00:00:01.5+00:00 hello world"),
+            Some((1_704_067_201_500_000_000, "hello world".to_string()))
+        );
+        assert_eq!(
+            parse_line("2024-01-01T00:00:01Z "),
+            Some((1_704_067_201_000_000_000, String::new()))
+        );
+        assert_eq!(parse_line("no timestamp"), None);
+        assert_eq!(parse_line(""), None);
+    }
+}
@@ -3,7 +3,9 @@
 * SPDX-License-Identifier: Apache-2.0
 */
 pub mod container;
+pub mod logs;
 pub mod nodeinfo;
+pub mod stats;
 
 use serde::Deserialize;
 use std::collections::HashMap;
@@ -56,6 +58,8 @@ pub struct Container {
     pub Image: String,
     pub State: String,
     pub Status: String,
+    #[serde(default)]
+    pub Labels: Option<HashMap<String, String>>,
 }
 
 #[allow(non_snake_case, unused)]
@@ -128,6 +132,7 @@ pub struct ContainerStats {
     pub Id: String,
     pub name: String,
     pub cpu_stats: ContainerCpuStats,
+    pub precpu_stats: Option<ContainerCpuStats>,
     pub memory_stats: ContainerMemoryStats,
     pub networks: Option<HashMap<String, ContainerNetworkStats>>,
 }
@@ -136,6 +141,7 @@ pub struct ContainerStats {
 #[derive(Deserialize, Debug)]
 pub struct ContainerCpuStats {
     pub cpu_usage: ContainerCpuUsage,
+    pub system_cpu_usage: Option<u64>,
     pub online_cpus: Option<u64>,
 }
 
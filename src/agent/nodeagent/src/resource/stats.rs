@@ -0,0 +1,292 @@
+/*
+* SPDX-FileCopyrightText: Copyright 2024 LG Electronics Inc.
+* SPDX-License-Identifier: Apache-2.0
+*/
+//! Resource usage of a single container
+//!
+//! Usage comes from the podman stats endpoint. When podman cannot answer,
+//! it is read from the container's cgroup v2 files and `/proc` instead.
+
+use super::{ContainerCpuStats, ContainerStats};
+use common::monitoringserver::ContainerUsage;
+use std::io;
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+/// Time between the two CPU samples of the cgroup fallback
+const CPU_SAMPLE_INTERVAL: Duration = Duration::from_millis(200);
+
+/// Converts a podman stats response
+///
+/// CPU usage is relative to one CPU, like `podman stats`, so a container
+/// using two CPUs fully reports 200%.
+pub fn usage_from_stats(stats: &ContainerStats) -> ContainerUsage {
+    let (net_rx_bytes, net_tx_bytes) = stats
+        .networks
+        .as_ref()
+        .map(|nets| {
+            nets.values().fold((0, 0), |(rx, tx), net| {
+                (rx + net.rx_bytes, tx + net.tx_bytes)
+            })
+        })
+        .unwrap_or_default();
+
+    ContainerUsage {
+        cpu_percent: cpu_percent(&stats.cpu_stats, stats.precpu_stats.as_ref()),
+        mem_usage_bytes: stats.memory_stats.usage,
+        mem_limit_bytes: stats.memory_stats.limit,
+        net_rx_bytes,
+        net_tx_bytes,
+    }
+}
+
+fn cpu_percent(cpu: &ContainerCpuStats, precpu: Option<&ContainerCpuStats>) -> f64 {
+    let Some(precpu) = precpu else {
+        return 0.0;
+    };
+    let cpu_delta = cpu
+        .cpu_usage
+        .total_usage
+        .saturating_sub(precpu.cpu_usage.total_usage);
+    let system_delta = cpu
+        .system_cpu_usage
+        .unwrap_or_default()
+        .saturating_sub(precpu.system_cpu_usage.unwrap_or_default());
+    if cpu_delta == 0 || system_delta == 0 {
+        return 0.0;
+    }
+    let online_cpus = cpu.online_cpus.unwrap_or(1).max(1);
+    cpu_delta as f64 / system_delta as f64 * online_cpus as f64 * 100.0
+}
+
+/// Reads the usage of the container whose main process is `pid`
+///
+/// # Arguments
+///
+/// * `proc_root` - Mount point of procfs, normally `/proc`
+/// * `cgroup_root` - Mount point of the cgroup v2 hierarchy, normally `/sys/fs/cgroup`
+/// * `pid` - Main process of the container
+///
+/// # Returns
+///
+/// * `io::Result<ContainerUsage>` - Usage, with a memory limit of 0 when the
+///   cgroup has none. `ErrorKind::NotFound` means the container is gone.
+pub async fn usage_from_cgroup(
+    proc_root: &Path,
+    cgroup_root: &Path,
+    pid: i32,
+) -> io::Result<ContainerUsage> {
+    let process = proc_root.join(pid.to_string());
+    let cgroups = std::fs::read_to_string(process.join("cgroup"))?;
+    let cgroup = cgroup_root.join(cgroup_dir(&cgroups)?.trim_start_matches('/'));
+
+    let first_usage = cpu_usage_usec(&cgroup)?;
+    let started = Instant::now();
+    tokio::time::sleep(CPU_SAMPLE_INTERVAL).await;
+    let cpu_delta = cpu_usage_usec(&cgroup)?.saturating_sub(first_usage);
+    let elapsed = started.elapsed().as_micros().max(1) as f64;
+
+    let mem_limit_bytes = match read_trimmed(&cgroup.join("memory.max"))?.as_str() {
+        "max" => 0,
+        limit => parse_u64(limit)?,
+    };
+    let (net_rx_bytes, net_tx_bytes) =
+        net_bytes(&std::fs::read_to_string(process.join("net/dev"))?);
+
+    Ok(ContainerUsage {
+        cpu_percent: cpu_delta as f64 / elapsed * 100.0,
+        mem_usage_bytes: parse_u64(&read_trimmed(&cgroup.join("memory.current"))?)?,
+        mem_limit_bytes,
+        net_rx_bytes,
+        net_tx_bytes,
+    })
+}
+
+/// Cgroup v2 path in the contents of `/proc/<pid>/cgroup`
+fn cgroup_dir(contents: &str) -> io::Result<&str> {
+    contents
+        .lines()
+        .find_map(|line| line.strip_prefix("0::"))
+        .ok_or_else(|| io::Error::new(io::ErrorKind::Unsupported, "no cgroup v2 entry"))
+}
+
+/// Total CPU time of a cgroup, from `usage_usec` in `cpu.stat`
+fn cpu_usage_usec(cgroup: &Path) -> io::Result<u64> {
+    let stat = std::fs::read_to_string(cgroup.join("cpu.stat"))?;
+    stat.lines()
+        .find_map(|line| line.strip_prefix("usage_usec "))
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "no usage_usec in cpu.stat"))
+        .and_then(parse_u64)
+}
+
+/// Received and transmitted bytes of every interface but loopback in `/proc/<pid>/net/dev`
+fn net_bytes(net_dev: &str) -> (u64, u64) {
+    net_dev
+        .lines()
+        .skip(2)
+        .filter_map(|line| line.split_once(':'))
+        .filter(|(name, _)| name.trim() != "lo")
+        .fold((0, 0), |(rx, tx), (_, counters)| {
+            let counters: Vec<u64> = counters
+                .split_whitespace()
+                .map(|c| c.parse().unwrap_or_default())
+                .collect();
+            (
+                rx + counters.first().copied().unwrap_or_default(),
+                tx + counters.get(8).copied().unwrap_or_default(),
+            )
+        })
+}
+
+fn read_trimmed(path: &Path) -> io::Result<String> {
+    Ok(std::fs::read_to_string(path)?.trim().to_string())
+}
+
+fn parse_u64(value: &str) -> io::Result<u64> {
+    value
+        .trim()
+        .parse()
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+}
+
+//Unit Test Cases
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use std::path::PathBuf;
+
+    /// Response of `GET /v4.0.0/libpod/containers/{id}/stats?stream=false`
+    /// recorded from podman 4.9
+    const RECORDED_STATS: &str = r#"{
+        "read": "2024-11-05T09:12:44.409384027Z",
+        "preread": "2024-11-05T09:12:43.408517652Z",
+        "pids_stats": {"current": 3},
+        "blkio_stats": {"io_service_bytes_recursive": null},
+        "num_procs": 0,
+        "storage_stats": {},
+        "cpu_stats": {
+            "cpu_usage": {
+                "total_usage": 2215862000,
+                "percpu_usage": [1107931000, 1107931000],
+                "usage_in_kernelmode": 503512000,
+                "usage_in_usermode": 1712350000
+            },
+            "system_cpu_usage": 8203476930000000,
+            "online_cpus": 4,
+            "throttling_data": {"periods": 0, "throttled_periods": 0, "throttled_time": 0}
+        },
+        "precpu_stats": {
+            "cpu_usage": {
+                "total_usage": 1715862000,
+                "usage_in_kernelmode": 403512000,
+                "usage_in_usermode": 1312350000
+            },
+            "system_cpu_usage": 8203472930000000,
+            "online_cpus": 4,
+            "throttling_data": {"periods": 0, "throttled_periods": 0, "throttled_time": 0}
+        },
+        "memory_stats": {"usage": 14376960, "max_usage": 16252928, "limit": 536870912},
+        "name": "helloworld-core",
+        "Id": "3f1b2c4d5e6f",
+        "networks": {
+            "eth0": {
+                "rx_bytes": 2048, "rx_packets": 20, "rx_errors": 0, "rx_dropped": 0,
+                "tx_bytes": 1024, "tx_packets": 10, "tx_errors": 0, "tx_dropped": 0
+            },
+            "eth1": {
+                "rx_bytes": 100, "rx_packets": 1, "rx_errors": 0, "rx_dropped": 0,
+                "tx_bytes": 50, "tx_packets": 1, "tx_errors": 0, "tx_dropped": 0
+            }
+        }
+    }"#;
+
+    #[test]
+    fn test_usage_from_recorded_stats() {
+        let stats: ContainerStats = serde_json::from_str(RECORDED_STATS).unwrap();
+        let usage = usage_from_stats(&stats);
+
+        // 0.5s of CPU time over 4s of system time on 4 CPUs
+        assert!((usage.cpu_percent - 50.0).abs() < 1e-9);
+        assert_eq!(usage.mem_usage_bytes, 14376960);
+        assert_eq!(usage.mem_limit_bytes, 536870912);
+        assert_eq!(usage.net_rx_bytes, 2148);
+        assert_eq!(usage.net_tx_bytes, 1074);
+    }
+
+    #[test]
+    fn test_usage_from_stats_without_previous_sample() {
+        let mut stats: serde_json::Value = serde_json::from_str(RECORDED_STATS).unwrap();
+        stats.as_object_mut().unwrap().remove("precpu_stats");
+        stats.as_object_mut().unwrap().remove("networks");
+        let stats: ContainerStats = serde_json::from_value(stats).unwrap();
+
+        let usage = usage_from_stats(&stats);
+        assert_eq!(usage.cpu_percent, 0.0);
+        assert_eq!(usage.net_rx_bytes, 0);
+        assert_eq!(usage.mem_usage_bytes, 14376960);
+    }
+
+    /// Fake `/proc` and `/sys/fs/cgroup` trees for one container process
+    fn fake_roots(name: &str, memory_max: &str) -> (PathBuf, PathBuf) {
+        let root = std::env::temp_dir().join(format!("nodeagent-stats-{}", name));
+        let _ = fs::remove_dir_all(&root);
+        let proc_root = root.join("proc");
+        let cgroup_root = root.join("cgroup");
+        let cgroup = cgroup_root.join("machine.slice/libpod-3f1b.scope");
+
+        fs::create_dir_all(proc_root.join("42/net")).unwrap();
+        fs::create_dir_all(&cgroup).unwrap();
+        fs::write(
+            proc_root.join("42/cgroup"),
+            "0::/machine.slice/libpod-3f1b.scope\n",
+        )
+        .unwrap();
+        fs::write(
+            proc_root.join("42/net/dev"),
+            "Inter-|   Receive                                                |  Transmit\n \
+             face |bytes    packets errs drop fifo frame compressed multicast|bytes    packets errs drop fifo colls carrier compressed\n    \
+             lo:     500       5    0    0    0     0          0         0      500       5    0    0    0     0       0          0\n  \
+             eth0:    2048      20    0    0    0     0          0         0     1024      10    0    0    0     0       0          0\n",
+        )
+        .unwrap();
+        fs::write(cgroup.join("cpu.stat"), "usage_usec 1000\nuser_usec 800\n").unwrap();
+        fs::write(cgroup.join("memory.current"), "14376960\n").unwrap();
+        fs::write(cgroup.join("memory.max"), memory_max).unwrap();
+        (proc_root, cgroup_root)
+    }
+
+    #[tokio::test]
+    async fn test_usage_from_cgroup() {
+        let (proc_root, cgroup_root) = fake_roots("limited", "536870912\n");
+
+        let usage = usage_from_cgroup(&proc_root, &cgroup_root, 42)
+            .await
+            .unwrap();
+        assert_eq!(usage.cpu_percent, 0.0);
+        assert_eq!(usage.mem_usage_bytes, 14376960);
+        assert_eq!(usage.mem_limit_bytes, 536870912);
+        assert_eq!(usage.net_rx_bytes, 2048);
+        assert_eq!(usage.net_tx_bytes, 1024);
+    }
+
+    #[tokio::test]
+    async fn test_usage_from_cgroup_without_limit() {
+        let (proc_root, cgroup_root) = fake_roots("unlimited", "max\n");
+
+        let usage = usage_from_cgroup(&proc_root, &cgroup_root, 42)
+            .await
+            .unwrap();
+        assert_eq!(usage.mem_limit_bytes, 0);
+    }
+
+    #[tokio::test]
+    async fn test_usage_from_cgroup_of_gone_process() {
+        let (proc_root, cgroup_root) = fake_roots("gone", "max\n");
+
+        let err = usage_from_cgroup(&proc_root, &cgroup_root, 7)
+            .await
+            .unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::NotFound);
+    }
+}
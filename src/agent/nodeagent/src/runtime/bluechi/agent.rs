@@ -0,0 +1,548 @@
+/*
+ * SPDX-FileCopyrightText: Copyright 2024 LG Electronics Inc.
+ * SPDX-License-Identifier: Apache-2.0
+ */
+
+//! Enrollment of the node with the bluechi controller
+//!
+//! `agent.conf` of bluechi-agent is rendered from the NodeAgent config and
+//! only written when its content changes, in which case bluechi-agent is
+//! enabled and restarted through the systemd D-Bus API. Enrollment then
+//! waits for the controller to list the node online.
+
+use crate::config::Config;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use thiserror::Error;
+use tonic::Status;
+use tracing::{info, warn};
+use zbus::zvariant::OwnedObjectPath;
+use zbus::{Connection, Proxy};
+
+/// Systemd unit of bluechi-agent
+pub const AGENT_UNIT: &str = "bluechi-agent.service";
+/// Name of the config file in the bluechi config directory
+pub const AGENT_CONF: &str = "agent.conf";
+/// Interval between two checks of the controller node list
+const VERIFY_POLL_INTERVAL: Duration = Duration::from_millis(200);
+
+/// Content of `agent.conf`
+#[derive(Debug, Clone, PartialEq)]
+pub struct AgentConf {
+    /// Name the node is known by in the controller
+    pub node_name: String,
+    pub controller_host: String,
+    pub controller_port: u16,
+}
+
+impl AgentConf {
+    /// Agent config of this node, from `node_name` and the `bluechi` section
+    pub fn from_config(config: &Config) -> Self {
+        let bluechi = &config.nodeagent.bluechi;
+        let controller_host = if bluechi.controller_host.is_empty() {
+            config.nodeagent.master_ip.clone()
+        } else {
+            bluechi.controller_host.clone()
+        };
+        Self {
+            node_name: config.get_node_name(),
+            controller_host,
+            controller_port: bluechi.controller_port,
+        }
+    }
+
+    /// `agent.conf` as written on the node
+    pub fn render(&self) -> String {
+        format!(
+            "# Written by the Pullpiri NodeAgent, local changes are overwritten\n\
+             [bluechi-agent]\n\
+             NodeName={}\n\
+             ControllerHost={}\n\
+             ControllerPort={}\n",
+            self.node_name, self.controller_host, self.controller_port
+        )
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum EnrollError {
+    #[error("cannot write {path:?}: {reason}")]
+    Install { path: PathBuf, reason: String },
+
+    #[error("cannot restart {AGENT_UNIT}: {0}")]
+    Systemd(String),
+
+    #[error("node {node} not online in the bluechi controller within {timeout:?}: {last}")]
+    NotOnline {
+        node: String,
+        timeout: Duration,
+        last: String,
+    },
+}
+
+impl From<EnrollError> for Status {
+    fn from(e: EnrollError) -> Self {
+        match e {
+            EnrollError::Install { .. } => Status::internal(e.to_string()),
+            EnrollError::Systemd(_) => Status::unavailable(e.to_string()),
+            EnrollError::NotOnline { .. } => Status::deadline_exceeded(e.to_string()),
+        }
+    }
+}
+
+/// Unit management of systemd
+#[tonic::async_trait]
+pub trait Systemd: Send + Sync {
+    /// Enable `unit` so it starts on boot
+    async fn enable(&self, unit: &str) -> Result<(), String>;
+
+    /// Restart `unit`, starting it if it is stopped
+    async fn restart(&self, unit: &str) -> Result<(), String>;
+}
+
+/// Node list of the bluechi controller
+#[tonic::async_trait]
+pub trait Controller: Send + Sync {
+    /// Names and statuses of the nodes, e.g. `("vehicle-1", "online")`
+    async fn list_nodes(&self) -> Result<Vec<(String, String)>, String>;
+}
+
+/// Systemd of this node, through the system bus
+pub struct SystemdBus;
+
+impl SystemdBus {
+    async fn manager() -> zbus::Result<Proxy<'static>> {
+        let connection = Connection::system().await?;
+        Proxy::new(
+            &connection,
+            "org.freedesktop.systemd1",
+            "/org/freedesktop/systemd1",
+            "org.freedesktop.systemd1.Manager",
+        )
+        .await
+    }
+}
+
+#[tonic::async_trait]
+impl Systemd for SystemdBus {
+    async fn enable(&self, unit: &str) -> Result<(), String> {
+        let manager = Self::manager().await.map_err(|e| e.to_string())?;
+        manager
+            .call_method("EnableUnitFiles", &(vec![unit], false, true))
+            .await
+            .map_err(|e| e.to_string())?;
+        manager
+            .call_method("Reload", &())
+            .await
+            .map_err(|e| e.to_string())?;
+        Ok(())
+    }
+
+    async fn restart(&self, unit: &str) -> Result<(), String> {
+        let manager = Self::manager().await.map_err(|e| e.to_string())?;
+        manager
+            .call::<_, _, OwnedObjectPath>("RestartUnit", &(unit, "replace"))
+            .await
+            .map_err(|e| e.to_string())?;
+        Ok(())
+    }
+}
+
+/// Bluechi controller API, on the D-Bus address set in `bluechi.controller_bus`
+pub struct ControllerBus;
+
+#[tonic::async_trait]
+impl Controller for ControllerBus {
+    async fn list_nodes(&self) -> Result<Vec<(String, String)>, String> {
+        let address = Config::get().nodeagent.bluechi.controller_bus.clone();
+        let connection = if address.is_empty() {
+            Connection::system().await
+        } else {
+            match zbus::connection::Builder::address(address.as_str()) {
+                Ok(builder) => builder.build().await,
+                Err(e) => Err(e),
+            }
+        }
+        .map_err(|e| e.to_string())?;
+        let controller = Proxy::new(
+            &connection,
+            "org.eclipse.bluechi",
+            "/org/eclipse/bluechi",
+            "org.eclipse.bluechi.Controller",
+        )
+        .await
+        .map_err(|e| e.to_string())?;
+
+        // Name, object path, status and peer IP of each node
+        let nodes: Vec<(String, OwnedObjectPath, String, String)> = controller
+            .call("ListNodes", &())
+            .await
+            .map_err(|e| e.to_string())?;
+        Ok(nodes
+            .into_iter()
+            .map(|(name, _, status, _)| (name, status))
+            .collect())
+    }
+}
+
+/// Content of the `agent.conf` in `dir`, `None` if there is none
+fn read(dir: &Path) -> Result<Option<String>, EnrollError> {
+    let path = dir.join(AGENT_CONF);
+    match std::fs::read_to_string(&path) {
+        Ok(content) => Ok(Some(content)),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+        Err(e) => Err(EnrollError::Install {
+            path,
+            reason: e.to_string(),
+        }),
+    }
+}
+
+/// Write `content` as the `agent.conf` in `dir`
+fn install(dir: &Path, content: &str) -> Result<(), EnrollError> {
+    let path = dir.join(AGENT_CONF);
+    std::fs::create_dir_all(dir)
+        .and_then(|_| write(&path, content))
+        .map_err(|e| EnrollError::Install {
+            path,
+            reason: e.to_string(),
+        })
+}
+
+/// Put `content` in place of `path` at once, so bluechi-agent never reads
+/// a partial file
+fn write(path: &Path, content: &str) -> std::io::Result<()> {
+    let tmp = path.with_extension("conf.tmp");
+    std::fs::write(&tmp, content)?;
+    std::fs::rename(&tmp, path)
+}
+
+/// Put back the `agent.conf` replaced on enrollment, so the next
+/// enrollment writes it and restarts bluechi-agent again
+fn restore(dir: &Path, previous: Option<String>) {
+    let path = dir.join(AGENT_CONF);
+    let restored = match previous {
+        Some(previous) => write(&path, &previous),
+        None => std::fs::remove_file(&path),
+    };
+    if let Err(e) = restored {
+        warn!("Cannot restore {:?}: {}", path, e);
+    }
+}
+
+/// Enroll this node with the bluechi controller
+///
+/// # Arguments
+///
+/// * `conf` - Agent config to install
+/// * `dir` - Bluechi config directory
+/// * `systemd` - Systemd of this node
+/// * `controller` - Bluechi controller
+/// * `verify_timeout` - Time the controller gets to list the node online,
+///   zero to not check
+///
+/// # Returns
+///
+/// * `Result<bool, EnrollError>` - Whether `agent.conf` changed and
+///   bluechi-agent was restarted
+pub async fn enroll(
+    conf: &AgentConf,
+    dir: &Path,
+    systemd: &dyn Systemd,
+    controller: &dyn Controller,
+    verify_timeout: Duration,
+) -> Result<bool, EnrollError> {
+    let content = conf.render();
+    let previous = read(dir)?;
+    let changed = previous.as_deref() != Some(content.as_str());
+    if changed {
+        install(dir, &content)?;
+        let restarted = match systemd.enable(AGENT_UNIT).await {
+            Ok(()) => systemd.restart(AGENT_UNIT).await,
+            Err(e) => Err(e),
+        };
+        if let Err(e) = restarted {
+            restore(dir, previous);
+            return Err(EnrollError::Systemd(e));
+        }
+        info!("Installed {} and restarted {}", AGENT_CONF, AGENT_UNIT);
+    }
+
+    if !verify_timeout.is_zero() {
+        wait_until_online(controller, &conf.node_name, verify_timeout).await?;
+    }
+    Ok(changed)
+}
+
+/// Wait for the controller to list `node` online
+async fn wait_until_online(
+    controller: &dyn Controller,
+    node: &str,
+    timeout: Duration,
+) -> Result<(), EnrollError> {
+    let deadline = tokio::time::Instant::now() + timeout;
+    loop {
+        let last = match controller.list_nodes().await {
+            Ok(nodes) => match nodes.into_iter().find(|(name, _)| name == node) {
+                Some((_, status)) if status == "online" => return Ok(()),
+                Some((_, status)) => status,
+                None => "not listed".to_string(),
+            },
+            Err(e) => e,
+        };
+        if tokio::time::Instant::now() + VERIFY_POLL_INTERVAL > deadline {
+            return Err(EnrollError::NotOnline {
+                node: node.to_string(),
+                timeout,
+                last,
+            });
+        }
+        tokio::time::sleep(VERIFY_POLL_INTERVAL).await;
+    }
+}
+
+//UNIT TEST CASES
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Mutex;
+
+    /// Systemd recording the calls it gets
+    #[derive(Default)]
+    struct MockSystemd {
+        calls: Mutex<Vec<String>>,
+        fail_restart: bool,
+    }
+
+    impl MockSystemd {
+        fn calls(&self) -> Vec<String> {
+            std::mem::take(&mut self.calls.lock().unwrap())
+        }
+    }
+
+    #[tonic::async_trait]
+    impl Systemd for MockSystemd {
+        async fn enable(&self, unit: &str) -> Result<(), String> {
+            self.calls.lock().unwrap().push(format!("enable {}", unit));
+            Ok(())
+        }
+
+        async fn restart(&self, unit: &str) -> Result<(), String> {
+            self.calls.lock().unwrap().push(format!("restart {}", unit));
+            if self.fail_restart {
+                return Err("Unit bluechi-agent.service not found.".to_string());
+            }
+            Ok(())
+        }
+    }
+
+    /// Controller listing the node online from its `online_after`-th call
+    struct MockController {
+        node: String,
+        online_after: usize,
+        calls: AtomicUsize,
+    }
+
+    impl MockController {
+        fn new(node: &str, online_after: usize) -> Self {
+            Self {
+                node: node.to_string(),
+                online_after,
+                calls: AtomicUsize::new(0),
+            }
+        }
+    }
+
+    #[tonic::async_trait]
+    impl Controller for MockController {
+        async fn list_nodes(&self) -> Result<Vec<(String, String)>, String> {
+            let call = self.calls.fetch_add(1, Ordering::SeqCst) + 1;
+            let status = if call >= self.online_after {
+                "online"
+            } else {
+                "offline"
+            };
+            Ok(vec![
+                ("master".to_string(), "online".to_string()),
+                (self.node.clone(), status.to_string()),
+            ])
+        }
+    }
+
+    /// Empty scratch directory for one test
+    fn scratch(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("nodeagent-bluechi-{}", name));
+        let _ = std::fs::remove_dir_all(&dir);
+        dir
+    }
+
+    fn conf(controller_host: &str) -> AgentConf {
+        AgentConf {
+            node_name: "vehicle-1".to_string(),
+            controller_host: controller_host.to_string(),
+            controller_port: 842,
+        }
+    }
+
+    fn read_conf(dir: &Path) -> String {
+        std::fs::read_to_string(dir.join(AGENT_CONF)).unwrap()
+    }
+
+    #[test]
+    fn test_from_config_defaults_to_master_ip() {
+        let mut config = Config::default();
+        config.nodeagent.node_name = "vehicle-1".to_string();
+        config.nodeagent.master_ip = "10.0.0.1".to_string();
+        assert_eq!(AgentConf::from_config(&config), conf("10.0.0.1"));
+
+        config.nodeagent.bluechi.controller_host = "10.0.0.2".to_string();
+        assert_eq!(AgentConf::from_config(&config), conf("10.0.0.2"));
+    }
+
+    #[test]
+    fn test_render() {
+        let rendered = conf("10.0.0.1").render();
+
+        assert!(rendered.contains(
+            "[bluechi-agent]\nNodeName=vehicle-1\nControllerHost=10.0.0.1\nControllerPort=842\n"
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_enroll_restarts_agent_only_when_conf_changes() {
+        let dir = scratch("changes");
+        let systemd = MockSystemd::default();
+        let controller = MockController::new("vehicle-1", 1);
+
+        let changed = enroll(
+            &conf("10.0.0.1"),
+            &dir,
+            &systemd,
+            &controller,
+            Duration::ZERO,
+        )
+        .await
+        .unwrap();
+        assert!(changed);
+        assert_eq!(read_conf(&dir), conf("10.0.0.1").render());
+        assert_eq!(
+            systemd.calls(),
+            vec![
+                "enable bluechi-agent.service",
+                "restart bluechi-agent.service"
+            ]
+        );
+
+        // Same content, nothing to do
+        let changed = enroll(
+            &conf("10.0.0.1"),
+            &dir,
+            &systemd,
+            &controller,
+            Duration::ZERO,
+        )
+        .await
+        .unwrap();
+        assert!(!changed);
+        assert!(systemd.calls().is_empty());
+
+        // New controller address
+        let changed = enroll(
+            &conf("10.0.0.2"),
+            &dir,
+            &systemd,
+            &controller,
+            Duration::ZERO,
+        )
+        .await
+        .unwrap();
+        assert!(changed);
+        assert_eq!(read_conf(&dir), conf("10.0.0.2").render());
+        assert_eq!(systemd.calls().len(), 2);
+        assert!(!dir.join("agent.conf.tmp").exists());
+    }
+
+    #[tokio::test]
+    async fn test_failed_restart_restores_previous_conf() {
+        let dir = scratch("restore");
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join(AGENT_CONF), "[bluechi-agent]\nNodeName=old\n").unwrap();
+        let systemd = MockSystemd {
+            fail_restart: true,
+            ..Default::default()
+        };
+        let controller = MockController::new("vehicle-1", 1);
+
+        let err = enroll(
+            &conf("10.0.0.1"),
+            &dir,
+            &systemd,
+            &controller,
+            Duration::ZERO,
+        )
+        .await
+        .unwrap_err();
+        assert!(matches!(err, EnrollError::Systemd(_)));
+        assert_eq!(Status::from(err).code(), tonic::Code::Unavailable);
+        assert_eq!(read_conf(&dir), "[bluechi-agent]\nNodeName=old\n");
+
+        // A file that did not exist is removed again
+        let dir = scratch("restore-new");
+        assert!(enroll(
+            &conf("10.0.0.1"),
+            &dir,
+            &systemd,
+            &controller,
+            Duration::ZERO
+        )
+        .await
+        .is_err());
+        assert!(!dir.join(AGENT_CONF).exists());
+    }
+
+    #[tokio::test]
+    async fn test_enroll_waits_for_node_online() {
+        let dir = scratch("online");
+        let systemd = MockSystemd::default();
+        let controller = MockController::new("vehicle-1", 3);
+
+        let changed = enroll(
+            &conf("10.0.0.1"),
+            &dir,
+            &systemd,
+            &controller,
+            Duration::from_secs(5),
+        )
+        .await
+        .unwrap();
+
+        assert!(changed);
+        assert_eq!(controller.calls.load(Ordering::SeqCst), 3);
+    }
+
+    #[tokio::test]
+    async fn test_enroll_fails_when_node_stays_offline() {
+        let dir = scratch("offline");
+        let systemd = MockSystemd::default();
+        let controller = MockController::new("vehicle-1", usize::MAX);
+
+        let err = enroll(
+            &conf("10.0.0.1"),
+            &dir,
+            &systemd,
+            &controller,
+            Duration::from_millis(500),
+        )
+        .await
+        .unwrap_err();
+
+        assert_eq!(
+            err.to_string(),
+            "node vehicle-1 not online in the bluechi controller within 500ms: offline"
+        );
+        assert_eq!(Status::from(err).code(), tonic::Code::DeadlineExceeded);
+        // The agent config stays installed for the next attempt
+        assert_eq!(read_conf(&dir), conf("10.0.0.1").render());
+    }
+}
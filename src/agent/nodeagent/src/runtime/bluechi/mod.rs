@@ -5,6 +5,7 @@
 
 //! Performs tasks required for Bluechi integration
 
+pub mod agent;
 mod filemaker;
 mod parser;
 
@@ -23,6 +24,7 @@ use common::spec::{
 /// Convert `Model` to `Pod`
 /// Make `.kube`, `.yaml` files for bluechi
 /// Copy files to the guest node running Bluechi
+#[allow(dead_code)] // Not called until the manager handles YAML requests with bluechi
 pub async fn parse(yaml_str: String, nodename: String) -> common::Result<()> {
     let (package_str, models_str) = parser::yaml_split(&yaml_str).await?;
     let package: Package = serde_yaml::from_str(&package_str)?;
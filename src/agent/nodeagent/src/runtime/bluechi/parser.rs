@@ -72,20 +72,18 @@ pub async fn get_complete_model(
             let model_name = mi.get_name();
             for model in models.iter() {
                 if model.get_name() == model_name {
+                    let mut model = model.clone();
                     if let Some(volume_name) = mi.get_resources().get_volume() {
-                        let key = format!("Volume/{}", volume_name);
+                        let key = common::keys::volume(&volume_name);
                         let volume_str: String = common::etcd::get(&key).await?;
                         let volume: Volume = serde_yaml::from_str(&volume_str)?;
 
-                        if let Some(volume_spec) = volume.get_spec() {
-                            model
-                                .get_podspec()
-                                .volumes
-                                .clone_from(volume_spec.get_volume());
+                        if let Some(volumes) = volume.get_pod_volumes() {
+                            model.get_podspec_mut().volumes = Some(volumes);
                         }
                     }
                     if let Some(network_name) = mi.get_resources().get_network() {
-                        let key = format!("Network/{}", network_name);
+                        let key = common::keys::network(&network_name);
                         let network_str = common::etcd::get(&key).await?;
                         let network: Network = serde_yaml::from_str(&network_str)?;
 
@@ -93,7 +91,7 @@ pub async fn get_complete_model(
                             // TODO
                         }
                     }
-                    base_models.push(model.clone());
+                    base_models.push(model);
                 } else {
                     println!("Model {} is not for this node {}", model.get_name(), node);
                     continue;
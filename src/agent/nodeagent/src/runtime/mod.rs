@@ -1,2 +1,2 @@
-//pub mod bluechi;
+pub mod bluechi;
 pub mod podman;
@@ -22,9 +22,11 @@
 //! - Image management (existence check, pull)
 
 use super::{get, post};
+use common::spec::k8s::quantity::Quantity;
 use hyper::Body;
 use serde_json::json;
 use std::path::Path;
+use tracing::{info, warn};
 
 //const PODMAN_API_VERSION: &str = "/v4.0.0/libpod";
 const PODMAN_API_VERSION: &str = "/v4.0.0"; // docker-compatible API
@@ -99,13 +101,13 @@ fn get_container_names(
 fn build_host_config(
     container: &serde_json::Value,
     spec: &serde_json::Value,
-    host_network: bool,
+    network_mode: Option<&str>,
 ) -> serde_json::Value {
     let mut host_config = serde_json::Map::new();
 
-    // Network configuration
-    if host_network {
-        host_config.insert("NetworkMode".to_string(), json!("host"));
+    // Network configuration: "host" or the podman network of the pod
+    if let Some(mode) = network_mode {
+        host_config.insert("NetworkMode".to_string(), json!(mode));
     }
 
     // Security context (capabilities, privileged, user/group)
@@ -179,7 +181,7 @@ fn apply_security_config(
     }
 }
 
-/// Apply resource limits (CPU, Memory, GPU) to HostConfig
+/// Apply resource limits (CPU, Memory, GPU) and the memory request to HostConfig
 fn apply_resource_limits(
     host_config: &mut serde_json::Map<String, serde_json::Value>,
     container: &serde_json::Value,
@@ -189,18 +191,14 @@ fn apply_resource_limits(
         .and_then(|l| l.as_object())
     {
         // CPU limit (NanoCpus)
-        if let Some(cpu) = limits.get("cpu").and_then(|c| c.as_str()) {
-            if let Ok(cpu_num) = cpu.parse::<f64>() {
-                let nano_cpus = (cpu_num * 1_000_000_000.0) as i64;
-                host_config.insert("NanoCpus".to_string(), json!(nano_cpus));
-            }
+        if let Some(cpu) = limits.get("cpu").and_then(parse_quantity) {
+            let nano_cpus = (cpu * 1_000_000_000.0) as i64;
+            host_config.insert("NanoCpus".to_string(), json!(nano_cpus));
         }
 
         // Memory limit
-        if let Some(memory) = limits.get("memory").and_then(|m| m.as_str()) {
-            if let Some(memory_bytes) = parse_memory(memory) {
-                host_config.insert("Memory".to_string(), json!(memory_bytes));
-            }
+        if let Some(memory) = limits.get("memory").and_then(parse_quantity) {
+            host_config.insert("Memory".to_string(), json!(memory as i64));
         }
 
         // GPU devices (nvidia.com/gpu)
@@ -209,6 +207,15 @@ fn apply_resource_limits(
             apply_nvidia_libraries(host_config);
         }
     }
+
+    // Memory request (soft limit)
+    if let Some(memory) = container["resources"]
+        .get("requests")
+        .and_then(|r| r.get("memory"))
+        .and_then(parse_quantity)
+    {
+        host_config.insert("MemoryReservation".to_string(), json!(memory as i64));
+    }
 }
 
 /// Detect available NVIDIA GPU devices on the host
@@ -425,6 +432,22 @@ fn apply_volume_mounts(
                                 "Source": host_path,
                                 "Target": mount_path
                             }));
+                        } else if volume["emptyDir"].is_object() {
+                            let mut tmpfs = json!({
+                                "Type": "tmpfs",
+                                "Target": mount_path
+                            });
+                            if let Some(size) = parse_quantity(&volume["emptyDir"]["sizeLimit"]) {
+                                tmpfs["TmpfsOptions"] = json!({"SizeBytes": size as i64});
+                            }
+                            mounts.push(tmpfs);
+                        } else if let Some(reference) = volume["image"]["reference"].as_str() {
+                            mounts.push(json!({
+                                "Type": "image",
+                                "Source": reference,
+                                "Target": mount_path,
+                                "ReadOnly": true
+                            }));
                         }
                         break;
                     }
@@ -437,36 +460,13 @@ fn apply_volume_mounts(
     }
 }
 
-/// Parse memory string (e.g., "512Mi", "1Gi", "1024") to bytes
-fn parse_memory(memory: &str) -> Option<i64> {
-    if memory.is_empty() {
-        return None;
-    }
-
-    // Try direct number parse first
-    if let Ok(bytes) = memory.parse::<i64>() {
-        return Some(bytes);
-    }
-
-    // Parse with suffix (Ki, Mi, Gi, K, M, G)
-    let memory = memory.trim();
-    let (value_str, multiplier) = if memory.ends_with("Gi") {
-        (memory.trim_end_matches("Gi"), 1024 * 1024 * 1024)
-    } else if memory.ends_with("Mi") {
-        (memory.trim_end_matches("Mi"), 1024 * 1024)
-    } else if memory.ends_with("Ki") {
-        (memory.trim_end_matches("Ki"), 1024)
-    } else if memory.ends_with('G') {
-        (memory.trim_end_matches('G'), 1000 * 1000 * 1000)
-    } else if memory.ends_with('M') {
-        (memory.trim_end_matches('M'), 1000 * 1000)
-    } else if memory.ends_with('K') {
-        (memory.trim_end_matches('K'), 1000)
-    } else {
-        return None;
-    };
-
-    value_str.parse::<i64>().ok().map(|v| v * multiplier)
+/// Parse a resource quantity (e.g., "500m", "512Mi", 2) into base units
+///
+/// The pod spec is validated when parsed, so an invalid quantity is ignored.
+fn parse_quantity(value: &serde_json::Value) -> Option<f64> {
+    serde_json::from_value::<Quantity>(value.clone())
+        .ok()
+        .map(|q| q.value())
 }
 
 /// Build environment variables array
@@ -591,7 +591,7 @@ async fn create_container(
     pod_name: &str,
     container: &serde_json::Value,
     spec: &serde_json::Value,
-    host_network: bool,
+    network_mode: Option<&str>,
 ) -> Result<String, Box<dyn std::error::Error>> {
     let image = container["image"]
         .as_str()
@@ -606,7 +606,7 @@ async fn create_container(
     let name = format!("{}_{}", pod_name, container_name);
 
     // Build the complete container creation request
-    let create_body = build_container_spec(&name, image, container, spec, host_network);
+    let create_body = build_container_spec(&name, image, container, spec, network_mode);
 
     println!("{}", create_body);
 
@@ -630,7 +630,7 @@ fn build_container_spec(
     image: &str,
     container: &serde_json::Value,
     spec: &serde_json::Value,
-    host_network: bool,
+    network_mode: Option<&str>,
 ) -> serde_json::Value {
     let mut create_body = json!({
         "Image": image,
@@ -661,7 +661,7 @@ fn build_container_spec(
     }
 
     // Host configuration (resources, security, networking, etc.)
-    let host_config = build_host_config(container, spec, host_network);
+    let host_config = build_host_config(container, spec, network_mode);
     if !host_config.as_object().unwrap().is_empty() {
         create_body["HostConfig"] = host_config;
     }
@@ -716,14 +716,26 @@ async fn create_container_via_api(
 }
 
 pub async fn start(pod_yaml: &str) -> Result<Vec<String>, Box<dyn std::error::Error>> {
-    let (pod_name, spec) = parse_pod(pod_yaml)?;
-    let host_network = spec["hostNetwork"].as_bool().unwrap_or(false);
+    let (pod_name, mut spec) = parse_pod(pod_yaml)?;
+    super::volume::materialize(&mut spec, &crate::config::Config::get().nodeagent.volumes)?;
+
+    let network = super::network::pod_network(pod_yaml);
+    let network_mode = if spec["hostNetwork"].as_bool().unwrap_or(false) {
+        Some("host")
+    } else if let Some(network) = &network {
+        super::network::attach_pod(network, &pod_name)
+            .await
+            .map_err(|e| e.to_string())?;
+        Some(network.as_str())
+    } else {
+        None
+    };
 
     let mut container_ids = Vec::new();
 
     if let Some(containers) = spec["containers"].as_array() {
         for container in containers.iter() {
-            let container_id = create_container(&pod_name, container, &spec, host_network).await?;
+            let container_id = create_container(&pod_name, container, &spec, network_mode).await?;
 
             // Start the container
             println!("Starting container: {}", container_id);
@@ -772,6 +784,16 @@ pub async fn stop(pod_yaml: &str) -> Result<(), Box<dyn std::error::Error>> {
         }
     }
 
+    // Remove the pod's network once no other pod uses it
+    let host_network = spec["hostNetwork"].as_bool().unwrap_or(false);
+    if let (false, Some(network)) = (host_network, super::network::pod_network(pod_yaml)) {
+        match super::network::detach_pod(&network, &pod_name).await {
+            Ok(true) => info!("Network {} is no longer used", network),
+            Ok(false) => {}
+            Err(e) => warn!("Failed to release network {}: {}", network, e),
+        }
+    }
+
     Ok(())
 }
 
@@ -824,9 +846,23 @@ pub async fn image_exists(image_name: &str) -> Result<bool, Box<dyn std::error::
 }
 
 /// Pull an image from a registry
+///
+/// Podman reports the progress of the pull as JSON lines, ending with an
+/// `error` line if the pull failed.
 pub async fn pull_image(image_name: &str) -> Result<(), Box<dyn std::error::Error>> {
     let path = format!("/v4.0.0/libpod/images/pull?reference={}", image_name);
-    post(&path, Body::empty()).await?;
+    let body = post(&path, Body::empty()).await?;
+    for line in body.split(|byte| *byte == b'\n') {
+        let Ok(report) = serde_json::from_slice::<serde_json::Value>(line) else {
+            continue;
+        };
+        if let Some(error) = report["error"].as_str().or(report["message"].as_str()) {
+            return Err(error.into());
+        }
+        if let Some(progress) = report["stream"].as_str() {
+            tracing::debug!("Pulling {}: {}", image_name, progress.trim());
+        }
+    }
     Ok(())
 }
 
@@ -835,15 +871,40 @@ mod tests {
     use super::*;
 
     #[test]
-    fn test_parse_memory_with_suffixes() {
-        assert_eq!(parse_memory("1024"), Some(1024));
-        assert_eq!(parse_memory("512Mi"), Some(512 * 1024 * 1024));
-        assert_eq!(parse_memory("1Gi"), Some(1024 * 1024 * 1024));
-        assert_eq!(parse_memory("100Ki"), Some(100 * 1024));
-        assert_eq!(parse_memory("500M"), Some(500 * 1000 * 1000));
-        assert_eq!(parse_memory("2G"), Some(2 * 1000 * 1000 * 1000));
-        assert_eq!(parse_memory(""), None);
-        assert_eq!(parse_memory("invalid"), None);
+    fn test_parse_quantity_with_suffixes() {
+        assert_eq!(parse_quantity(&json!("1024")), Some(1024.0));
+        assert_eq!(parse_quantity(&json!(1024)), Some(1024.0));
+        assert_eq!(
+            parse_quantity(&json!("512Mi")),
+            Some(512.0 * 1024.0 * 1024.0)
+        );
+        assert_eq!(
+            parse_quantity(&json!("1Gi")),
+            Some(1024.0 * 1024.0 * 1024.0)
+        );
+        assert_eq!(parse_quantity(&json!("100Ki")), Some(100.0 * 1024.0));
+        assert_eq!(parse_quantity(&json!("500M")), Some(500e6));
+        assert_eq!(parse_quantity(&json!("2G")), Some(2e9));
+        assert_eq!(parse_quantity(&json!("500m")), Some(0.5));
+        assert_eq!(parse_quantity(&json!("")), None);
+        assert_eq!(parse_quantity(&json!("invalid")), None);
+        assert_eq!(parse_quantity(&json!("2Gib")), None);
+    }
+
+    #[test]
+    fn test_apply_resource_limits_and_requests() {
+        let container = json!({
+            "resources": {
+                "limits": {"cpu": "500m", "memory": "256Mi"},
+                "requests": {"memory": "128Mi"}
+            }
+        });
+        let mut host_config = serde_json::Map::new();
+        apply_resource_limits(&mut host_config, &container);
+
+        assert_eq!(host_config["NanoCpus"], json!(500_000_000));
+        assert_eq!(host_config["Memory"], json!(256 * 1024 * 1024));
+        assert_eq!(host_config["MemoryReservation"], json!(128 * 1024 * 1024));
     }
 
     #[test]
@@ -1024,4 +1085,34 @@ mod tests {
             assert!(has_nvidia_mount, "Should have NVIDIA library mount");
         }
     }
+
+    #[test]
+    fn test_apply_volume_mounts_for_each_volume_kind() {
+        let container = json!({
+            "volumeMounts": [
+                {"name": "shared", "mountPath": "/data"},
+                {"name": "scratch", "mountPath": "/tmp/scratch"},
+                {"name": "maps", "mountPath": "/maps"}
+            ]
+        });
+        let spec = json!({
+            "volumes": [
+                {"name": "shared", "hostPath": {"path": "/var/lib/piccolo/volumes/shared"}},
+                {"name": "scratch", "emptyDir": {"sizeLimit": "64Mi"}},
+                {"name": "maps", "image": {"reference": "maps:v2"}}
+            ]
+        });
+        let mut host_config = serde_json::Map::new();
+
+        apply_volume_mounts(&mut host_config, &container, &spec);
+
+        assert_eq!(
+            host_config["Mounts"],
+            json!([
+                {"Type": "bind", "Source": "/var/lib/piccolo/volumes/shared", "Target": "/data"},
+                {"Type": "tmpfs", "Target": "/tmp/scratch", "TmpfsOptions": {"SizeBytes": 67108864}},
+                {"Type": "image", "Source": "maps:v2", "Target": "/maps", "ReadOnly": true}
+            ])
+        );
+    }
 }
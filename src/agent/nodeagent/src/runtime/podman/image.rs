@@ -0,0 +1,450 @@
+/*
+* SPDX-FileCopyrightText: Copyright 2024 LG Electronics Inc.
+* SPDX-License-Identifier: Apache-2.0
+*/
+
+//! Container image prefetch
+//!
+//! The images of the models in an artifact are pulled before the NodeAgent
+//! reports the artifact as applied, so activating a scenario does not wait
+//! on the registry. Images already on the node are not pulled again.
+//!
+//! A model pins the digest of a container image with the annotation
+//! `io.piccolo.annotations.image-digest.<container name>`, or by
+//! referencing the image as `name@sha256:...`. Applying the artifact fails
+//! when the image on the node has another digest.
+
+use super::container::{image_exists, pull_image};
+use super::get;
+use serde::Deserialize;
+use serde_yaml::Value;
+use std::time::{Duration, Instant};
+use thiserror::Error;
+use tonic::Status;
+use tracing::{debug, info};
+
+/// Prefix of the annotation pinning the digest of a container image, the
+/// container name follows it
+pub const DIGEST_ANNOTATION_PREFIX: &str = "io.piccolo.annotations.image-digest.";
+
+/// Image of a model container
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ImageRef {
+    /// Reference the image is pulled with, e.g. `nginx:latest`
+    pub reference: String,
+    /// Digest the image must have, if pinned
+    pub digest: Option<String>,
+}
+
+impl ImageRef {
+    /// Image pulled with `reference`, pinned to the digest it contains if any
+    pub fn new(reference: &str) -> Self {
+        Self {
+            reference: reference.to_string(),
+            digest: reference
+                .split_once('@')
+                .map(|(_, digest)| digest.to_string()),
+        }
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum PrefetchError {
+    #[error("invalid artifact YAML: {0}")]
+    InvalidYaml(#[from] serde_yaml::Error),
+
+    #[error("cannot pull image {image}: {reason}")]
+    Pull { image: String, reason: String },
+
+    #[error("image {image} has digest {actual}, expected {expected}")]
+    DigestMismatch {
+        image: String,
+        expected: String,
+        actual: String,
+    },
+
+    #[error("images not pulled within {0:?}")]
+    Timeout(Duration),
+}
+
+impl From<PrefetchError> for Status {
+    fn from(e: PrefetchError) -> Self {
+        match e {
+            PrefetchError::InvalidYaml(_) => Status::invalid_argument(e.to_string()),
+            PrefetchError::Pull { .. } => Status::unavailable(e.to_string()),
+            PrefetchError::DigestMismatch { .. } => Status::failed_precondition(e.to_string()),
+            PrefetchError::Timeout(_) => Status::deadline_exceeded(e.to_string()),
+        }
+    }
+}
+
+/// Image API of the container runtime
+#[tonic::async_trait]
+pub trait ImageClient: Send + Sync {
+    /// Whether `image` is on the node
+    async fn exists(&self, image: &str) -> Result<bool, String>;
+
+    /// Pull `image` from its registry
+    async fn pull(&self, image: &str) -> Result<(), String>;
+
+    /// Digests of `image` on the node
+    async fn digests(&self, image: &str) -> Result<Vec<String>, String>;
+}
+
+/// Image API of Podman
+pub struct PodmanImages;
+
+#[tonic::async_trait]
+impl ImageClient for PodmanImages {
+    async fn exists(&self, image: &str) -> Result<bool, String> {
+        image_exists(image).await.map_err(|e| e.to_string())
+    }
+
+    async fn pull(&self, image: &str) -> Result<(), String> {
+        pull_image(image).await.map_err(|e| e.to_string())
+    }
+
+    async fn digests(&self, image: &str) -> Result<Vec<String>, String> {
+        let path = format!("/v4.0.0/libpod/images/{}/json", image);
+        let body = get(&path).await.map_err(|e| e.to_string())?;
+        let inspect: serde_json::Value =
+            serde_json::from_slice(&body).map_err(|e| e.to_string())?;
+        if let Some(message) = inspect["message"].as_str() {
+            return Err(message.to_string());
+        }
+
+        let repo_digests = inspect["RepoDigests"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .filter_map(|d| d.as_str()?.split_once('@').map(|(_, digest)| digest));
+        let mut digests: Vec<String> = inspect["Digest"]
+            .as_str()
+            .into_iter()
+            .chain(repo_digests)
+            .map(str::to_string)
+            .collect();
+        digests.dedup();
+        Ok(digests)
+    }
+}
+
+/// Images of the models and pods in an artifact
+///
+/// # Arguments
+/// * `yaml` - Artifact, as one or more YAML documents
+///
+/// # Returns
+/// * `Result<Vec<ImageRef>, PrefetchError>` - Images in order of first
+///   use, each listed once per pinned digest
+pub fn artifact_images(yaml: &str) -> Result<Vec<ImageRef>, PrefetchError> {
+    let mut images: Vec<ImageRef> = Vec::new();
+    for document in serde_yaml::Deserializer::from_str(yaml) {
+        let document = Value::deserialize(document)?;
+        if !matches!(document["kind"].as_str(), Some("Model" | "Pod")) {
+            continue;
+        }
+
+        let annotations = &document["metadata"]["annotations"];
+        let spec = &document["spec"];
+        let containers = ["initContainers", "containers"]
+            .iter()
+            .filter_map(|field| spec[*field].as_sequence())
+            .flatten();
+        for container in containers {
+            let Some(reference) = container["image"].as_str() else {
+                continue;
+            };
+            let mut image = ImageRef::new(reference);
+            if let Some(name) = container["name"].as_str() {
+                let key = format!("{}{}", DIGEST_ANNOTATION_PREFIX, name);
+                if let Some(digest) = annotations[key.as_str()].as_str() {
+                    image.digest = Some(digest.to_string());
+                }
+            }
+            if !images.contains(&image) {
+                images.push(image);
+            }
+        }
+    }
+    Ok(images)
+}
+
+/// Pull the images missing on the node and check their digests
+///
+/// # Arguments
+/// * `client` - Image API of the runtime
+/// * `images` - Images to prefetch
+/// * `timeout` - Time allowed for all the images together
+///
+/// # Returns
+/// * `Result<(), PrefetchError>` - Error of the first image that cannot be
+///   pulled or has another digest than pinned, or `Timeout`
+pub async fn prefetch(
+    client: &dyn ImageClient,
+    images: &[ImageRef],
+    timeout: Duration,
+) -> Result<(), PrefetchError> {
+    tokio::time::timeout(timeout, prefetch_all(client, images))
+        .await
+        .map_err(|_| PrefetchError::Timeout(timeout))?
+}
+
+async fn prefetch_all(client: &dyn ImageClient, images: &[ImageRef]) -> Result<(), PrefetchError> {
+    for (index, image) in images.iter().enumerate() {
+        let progress = format!("{}/{}", index + 1, images.len());
+        let pull_error = |reason| PrefetchError::Pull {
+            image: image.reference.clone(),
+            reason,
+        };
+
+        if client.exists(&image.reference).await.map_err(pull_error)? {
+            debug!("Image {} already present ({})", image.reference, progress);
+        } else {
+            info!("Pulling image {} ({})", image.reference, progress);
+            let started = Instant::now();
+            client.pull(&image.reference).await.map_err(pull_error)?;
+            info!(
+                "Pulled image {} in {:?} ({})",
+                image.reference,
+                started.elapsed(),
+                progress
+            );
+        }
+
+        if let Some(expected) = &image.digest {
+            verify_digest(client, &image.reference, expected).await?;
+        }
+    }
+    Ok(())
+}
+
+/// Check that the image pulled with `reference` has the digest `expected`
+async fn verify_digest(
+    client: &dyn ImageClient,
+    reference: &str,
+    expected: &str,
+) -> Result<(), PrefetchError> {
+    let digests = client
+        .digests(reference)
+        .await
+        .map_err(|reason| PrefetchError::Pull {
+            image: reference.to_string(),
+            reason,
+        })?;
+    if digests.iter().any(|digest| digest == expected) {
+        return Ok(());
+    }
+    Err(PrefetchError::DigestMismatch {
+        image: reference.to_string(),
+        expected: expected.to_string(),
+        actual: if digests.is_empty() {
+            "none".to_string()
+        } else {
+            digests.join(", ")
+        },
+    })
+}
+
+//Unit Test Cases
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use std::collections::{HashMap, HashSet};
+    use std::sync::Mutex;
+
+    /// Stands in for Podman, pulling images into memory
+    #[derive(Default)]
+    pub(crate) struct MockImages {
+        present: Mutex<HashSet<String>>,
+        digests: HashMap<String, String>,
+        pulls: Mutex<Vec<String>>,
+        pull_delay: Duration,
+        unavailable: bool,
+    }
+
+    impl MockImages {
+        /// Registry serving `images` with the given digests
+        pub(crate) fn with_registry(images: &[(&str, &str)]) -> Self {
+            Self {
+                digests: images
+                    .iter()
+                    .map(|(image, digest)| (image.to_string(), digest.to_string()))
+                    .collect(),
+                ..Default::default()
+            }
+        }
+
+        pub(crate) fn pulls(&self) -> Vec<String> {
+            self.pulls.lock().unwrap().clone()
+        }
+    }
+
+    #[tonic::async_trait]
+    impl ImageClient for MockImages {
+        async fn exists(&self, image: &str) -> Result<bool, String> {
+            Ok(self.present.lock().unwrap().contains(image))
+        }
+
+        async fn pull(&self, image: &str) -> Result<(), String> {
+            if self.unavailable {
+                return Err("registry unavailable".to_string());
+            }
+            tokio::time::sleep(self.pull_delay).await;
+            self.pulls.lock().unwrap().push(image.to_string());
+            self.present.lock().unwrap().insert(image.to_string());
+            Ok(())
+        }
+
+        async fn digests(&self, image: &str) -> Result<Vec<String>, String> {
+            Ok(self.digests.get(image).cloned().into_iter().collect())
+        }
+    }
+
+    const ARTIFACT: &str = r#"
+apiVersion: v1
+kind: Scenario
+metadata:
+  name: bms
+spec:
+  action: update
+  target: bms
+---
+apiVersion: v1
+kind: Model
+metadata:
+  name: bms-core
+  annotations:
+    io.piccolo.annotations.image-digest.core: sha256:aaa
+spec:
+  initContainers:
+    - name: init
+      image: busybox:1.36
+  containers:
+    - name: core
+      image: bms-core:1.0
+    - name: logger
+      image: logger@sha256:bbb
+---
+apiVersion: v1
+kind: Model
+metadata:
+  name: bms-ui
+spec:
+  containers:
+    - name: ui
+      image: busybox:1.36
+"#;
+
+    fn artifact_with_digest(digest: &str) -> String {
+        ARTIFACT.replace("sha256:aaa", digest)
+    }
+
+    #[test]
+    fn test_artifact_images_from_models() {
+        let images = artifact_images(ARTIFACT).unwrap();
+
+        assert_eq!(
+            images,
+            vec![
+                ImageRef::new("busybox:1.36"),
+                ImageRef {
+                    reference: "bms-core:1.0".to_string(),
+                    digest: Some("sha256:aaa".to_string()),
+                },
+                ImageRef {
+                    reference: "logger@sha256:bbb".to_string(),
+                    digest: Some("sha256:bbb".to_string()),
+                },
+            ]
+        );
+        assert!(artifact_images("test: value").unwrap().is_empty());
+        assert!(matches!(
+            artifact_images("kind: [Model"),
+            Err(PrefetchError::InvalidYaml(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_prefetch_pulls_missing_images() {
+        let images = MockImages::with_registry(&[
+            ("bms-core:1.0", "sha256:aaa"),
+            ("logger@sha256:bbb", "sha256:bbb"),
+        ]);
+        images
+            .present
+            .lock()
+            .unwrap()
+            .insert("busybox:1.36".to_string());
+
+        let artifact = artifact_images(ARTIFACT).unwrap();
+        prefetch(&images, &artifact, Duration::from_secs(5))
+            .await
+            .unwrap();
+
+        assert_eq!(images.pulls(), vec!["bms-core:1.0", "logger@sha256:bbb"]);
+
+        // Nothing left to pull the second time
+        prefetch(&images, &artifact, Duration::from_secs(5))
+            .await
+            .unwrap();
+        assert_eq!(images.pulls().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_prefetch_digest_mismatch() {
+        let images = MockImages::with_registry(&[
+            ("bms-core:1.0", "sha256:aaa"),
+            ("logger@sha256:bbb", "sha256:bbb"),
+        ]);
+        let artifact = artifact_images(&artifact_with_digest("sha256:ccc")).unwrap();
+
+        let err = prefetch(&images, &artifact, Duration::from_secs(5))
+            .await
+            .unwrap_err();
+
+        assert_eq!(
+            err.to_string(),
+            "image bms-core:1.0 has digest sha256:aaa, expected sha256:ccc"
+        );
+        assert_eq!(Status::from(err).code(), tonic::Code::FailedPrecondition);
+    }
+
+    #[tokio::test]
+    async fn test_prefetch_timeout() {
+        let images = MockImages {
+            pull_delay: Duration::from_millis(200),
+            ..Default::default()
+        };
+        let artifact = artifact_images(ARTIFACT).unwrap();
+
+        let err = prefetch(&images, &artifact, Duration::from_millis(50))
+            .await
+            .unwrap_err();
+
+        assert!(matches!(err, PrefetchError::Timeout(_)));
+        assert!(images.pulls().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_prefetch_pull_failure() {
+        let images = MockImages {
+            unavailable: true,
+            ..Default::default()
+        };
+
+        let err = prefetch(
+            &images,
+            &[ImageRef::new("bms-core:1.0")],
+            Duration::from_secs(5),
+        )
+        .await
+        .unwrap_err();
+
+        assert_eq!(
+            err.to_string(),
+            "cannot pull image bms-core:1.0: registry unavailable"
+        );
+        assert_eq!(Status::from(err).code(), tonic::Code::Unavailable);
+    }
+}
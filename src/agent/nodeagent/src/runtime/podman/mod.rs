@@ -4,6 +4,9 @@
 */
 
 pub mod container;
+pub mod image;
+pub mod network;
+pub mod volume;
 
 use common::nodeagent::fromactioncontroller::WorkloadCommand;
 use hyper::{Body, Client, Method, Request, Uri};
@@ -0,0 +1,66 @@
+/*
+* SPDX-FileCopyrightText: Copyright 2024 LG Electronics Inc.
+* SPDX-License-Identifier: Apache-2.0
+*/
+
+//! Podman networks of the pods started by NodeAgent
+//!
+//! Networks are created and removed through the libpod API as described
+//! in [`common::network`], with the pods using them recorded for this node.
+
+use super::{delete, get, post};
+use common::network::{attach, create_body, detach, Error, EtcdRefs, NetworkApi};
+use common::spec::artifact::Network;
+use hyper::Body;
+
+pub use common::network::pod_network;
+
+const LIBPOD_API_VERSION: &str = "/v4.0.0/libpod";
+
+/// Networks managed through the libpod REST API
+pub struct PodmanNetworks;
+
+#[tonic::async_trait]
+impl NetworkApi for PodmanNetworks {
+    async fn exists(&self, name: &str) -> Result<bool, Error> {
+        let path = format!("{}/networks/{}/json", LIBPOD_API_VERSION, name);
+        let response = get(&path).await?;
+        let inspect: serde_json::Value = serde_json::from_slice(&response)?;
+        Ok(inspect["name"].as_str() == Some(name))
+    }
+
+    async fn create(&self, network: &Network) -> Result<(), Error> {
+        let path = format!("{}/networks/create", LIBPOD_API_VERSION);
+        let body = create_body(network);
+        let response = post(&path, Body::from(body.to_string())).await?;
+        let result: serde_json::Value = serde_json::from_slice(&response)?;
+        match result["message"].as_str() {
+            Some(message) if result["name"].is_null() => Err(message.to_string().into()),
+            _ => Ok(()),
+        }
+    }
+
+    async fn remove(&self, name: &str) -> Result<(), Error> {
+        let path = format!("{}/networks/{}", LIBPOD_API_VERSION, name);
+        delete(&path).await?;
+        Ok(())
+    }
+}
+
+fn node_refs() -> EtcdRefs {
+    EtcdRefs {
+        node: crate::config::Config::get().get_node_name(),
+    }
+}
+
+/// Create the network named `network` from its stored artifact if needed
+/// and record that `pod` uses it
+pub async fn attach_pod(network: &str, pod: &str) -> Result<(), Error> {
+    let artifact = common::network::load(network).await?;
+    attach(&PodmanNetworks, &node_refs(), &artifact, pod).await
+}
+
+/// Record that `pod` stopped using `network`
+pub async fn detach_pod(network: &str, pod: &str) -> Result<bool, Error> {
+    detach(&PodmanNetworks, &node_refs(), network, pod).await
+}
@@ -0,0 +1,167 @@
+/*
+* SPDX-FileCopyrightText: Copyright 2024 LG Electronics Inc.
+* SPDX-License-Identifier: Apache-2.0
+*/
+
+//! Preparation of pod volumes before their containers are created
+//!
+//! A hostPath volume is bound to a directory of the node. Its path is
+//! resolved against the configured base path when it is relative, and the
+//! directory is created with the configured permissions when missing, so
+//! a Volume artifact works without preparing the node by hand.
+
+use crate::config::VolumeConfig;
+use std::os::unix::fs::PermissionsExt;
+use std::path::{Path, PathBuf};
+use tracing::info;
+
+/// Resolve a hostPath volume path on this node
+///
+/// # Arguments
+///
+/// * `path` - Path from the pod spec
+/// * `config` - Volume settings of the node
+fn resolve(path: &str, config: &VolumeConfig) -> PathBuf {
+    let path = Path::new(path);
+    if path.is_absolute() {
+        path.to_path_buf()
+    } else {
+        Path::new(&config.base_path).join(path)
+    }
+}
+
+/// Create the directories of the hostPath volumes in `spec`
+///
+/// Every hostPath path in `spec` is replaced by its resolved absolute path,
+/// so the container mounts the directory that was created. Directories
+/// that already exist are used as they are and keep their permissions.
+///
+/// # Arguments
+///
+/// * `spec` - Pod spec as JSON, updated in place
+/// * `config` - Volume settings of the node
+pub fn materialize(
+    spec: &mut serde_json::Value,
+    config: &VolumeConfig,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let Some(volumes) = spec.get_mut("volumes").and_then(|v| v.as_array_mut()) else {
+        return Ok(());
+    };
+
+    for volume in volumes {
+        let Some(path) = volume["hostPath"]["path"].as_str() else {
+            continue;
+        };
+        let name = volume["name"].as_str().unwrap_or_default().to_string();
+        let dir = resolve(path, config);
+
+        if !dir.exists() {
+            create_dir(&dir, config.permissions)
+                .map_err(|e| format!("volume '{}': cannot create {:?}: {}", name, dir, e))?;
+            info!("Created directory {:?} for volume {}", dir, name);
+        }
+        volume["hostPath"]["path"] = serde_json::json!(dir.to_string_lossy());
+    }
+    Ok(())
+}
+
+fn create_dir(dir: &Path, permissions: u32) -> std::io::Result<()> {
+    std::fs::create_dir_all(dir)?;
+    // Set explicitly, the mode of a new directory is reduced by the umask
+    std::fs::set_permissions(dir, std::fs::Permissions::from_mode(permissions))
+}
+
+//Unit Test Cases
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    /// Empty scratch directory for one test
+    fn scratch(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("nodeagent-volume-{}", name));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn config(base: &Path, permissions: u32) -> VolumeConfig {
+        VolumeConfig {
+            base_path: base.to_string_lossy().to_string(),
+            permissions,
+        }
+    }
+
+    #[test]
+    fn test_relative_path_is_created_under_base_path() {
+        let base = scratch("relative");
+        let mut spec = json!({
+            "volumes": [
+                {"name": "shared", "hostPath": {"path": "shared/data"}},
+                {"name": "scratch", "emptyDir": {"sizeLimit": "64Mi"}}
+            ]
+        });
+
+        materialize(&mut spec, &config(&base, 0o750)).unwrap();
+
+        let dir = base.join("shared/data");
+        assert!(dir.is_dir());
+        assert_eq!(
+            std::fs::metadata(&dir).unwrap().permissions().mode() & 0o777,
+            0o750
+        );
+        assert_eq!(
+            spec["volumes"][0]["hostPath"]["path"],
+            json!(dir.to_string_lossy())
+        );
+        // Other volume kinds are left alone
+        assert_eq!(
+            spec["volumes"][1],
+            json!({"name": "scratch", "emptyDir": {"sizeLimit": "64Mi"}})
+        );
+    }
+
+    #[test]
+    fn test_existing_directory_keeps_its_permissions() {
+        let base = scratch("existing");
+        let existing = base.join("existing");
+        std::fs::create_dir(&existing).unwrap();
+        std::fs::set_permissions(&existing, std::fs::Permissions::from_mode(0o700)).unwrap();
+        let mut spec = json!({
+            "volumes": [{"name": "x11", "hostPath": {"path": existing.to_string_lossy()}}]
+        });
+
+        materialize(&mut spec, &config(Path::new("/unused"), 0o777)).unwrap();
+
+        assert_eq!(
+            std::fs::metadata(&existing).unwrap().permissions().mode() & 0o777,
+            0o700
+        );
+        assert_eq!(
+            spec["volumes"][0]["hostPath"]["path"],
+            json!(existing.to_string_lossy())
+        );
+    }
+
+    #[test]
+    fn test_uncreatable_directory_is_an_error() {
+        let base = scratch("uncreatable");
+        let file = base.join("file");
+        std::fs::write(&file, "").unwrap();
+        let mut spec = json!({
+            "volumes": [{"name": "broken", "hostPath": {"path": "file/data"}}]
+        });
+
+        let err = materialize(&mut spec, &config(&base, 0o755)).unwrap_err();
+        assert!(err
+            .to_string()
+            .starts_with("volume 'broken': cannot create"));
+    }
+
+    #[test]
+    fn test_spec_without_volumes() {
+        let mut spec = json!({"containers": []});
+        assert!(materialize(&mut spec, &VolumeConfig::default()).is_ok());
+        assert_eq!(spec, json!({"containers": []}));
+    }
+}
@@ -9,8 +9,24 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
         std::fs::create_dir_all(out_dir)?;
     }
 
+    // Commit reported by the version service of every component
+    if let Ok(output) = std::process::Command::new("git")
+        .args(["rev-parse", "--short", "HEAD"])
+        .output()
+    {
+        if output.status.success() {
+            let commit = String::from_utf8_lossy(&output.stdout);
+            println!("cargo:rustc-env=PULLPIRI_GIT_COMMIT={}", commit.trim());
+        }
+    }
+
     tonic_build::configure()
         .type_attribute(".", "#[derive(serde::Serialize, serde::Deserialize)]")
+        // Node metrics stored before the field existed have none
+        .field_attribute(
+            "monitoringserver.NodeInfo.dropped_container_updates",
+            "#[serde(default)]",
+        )
         .protoc_arg("--experimental_allow_proto3_optional")
         .out_dir(out_dir)
         .compile_protos(
@@ -26,6 +42,7 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
                 "proto/external/pharos/pharos_service.proto",
                 "proto/external/timpani/schedinfo.proto",
                 "proto/rocksdbservice.proto", // Add RocksDB service proto
+                "proto/version.proto",
             ],
             &["proto"],
         )?;
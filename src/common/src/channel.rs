@@ -0,0 +1,162 @@
+/*
+ * SPDX-FileCopyrightText: Copyright 2024 LG Electronics Inc.
+ * SPDX-License-Identifier: Apache-2.0
+ */
+
+//! Bounded channels between the tasks of a component
+//!
+//! Their capacities come from the `channels` settings. A channel that stays
+//! full blocks its senders, so each one is watched and a warning naming its
+//! setting is logged once it has been full for `channels.full_warning_secs`.
+
+use crate::logd;
+use crate::setting::get_config;
+use std::time::Duration;
+use tokio::sync::mpsc::{self, Receiver, Sender, WeakSender};
+
+/// Interval of checking whether a channel is full
+const CHECK_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Bounded channel of `capacity` messages, watched for saturation
+///
+/// `name` is the key of the capacity in the `channels` settings, e.g.
+/// `statemanager_container`. Must be called within a Tokio runtime.
+pub fn bounded<T: Send + 'static>(name: &'static str, capacity: usize) -> (Sender<T>, Receiver<T>) {
+    let full_checks = get_config().channels.full_warning_secs;
+    watched(name, capacity, CHECK_INTERVAL, full_checks, |message| {
+        logd!(4, "{}", message)
+    })
+}
+
+/// Bounded channel warning through `warn` once it has been full for
+/// `full_checks` checks in a row; 0 disables the watch
+fn watched<T, W>(
+    name: &'static str,
+    capacity: usize,
+    interval: Duration,
+    full_checks: u64,
+    warn: W,
+) -> (Sender<T>, Receiver<T>)
+where
+    T: Send + 'static,
+    W: Fn(String) + Send + 'static,
+{
+    if capacity == 0 {
+        logd!(4, "Capacity of channel {} cannot be 0, using 1", name);
+    }
+    let (tx, rx) = mpsc::channel(capacity.max(1));
+    if full_checks > 0 {
+        tokio::spawn(watch(name, tx.downgrade(), interval, full_checks, warn));
+    }
+    (tx, rx)
+}
+
+/// Check the free capacity of a channel until its senders or its receiver
+/// are dropped
+///
+/// Only a weak sender is kept, so the watch does not keep the channel open.
+/// The warning is given once per stretch of time the channel stays full.
+async fn watch<T, W>(
+    name: &'static str,
+    weak: WeakSender<T>,
+    interval: Duration,
+    full_checks: u64,
+    warn: W,
+) where
+    W: Fn(String),
+{
+    let mut ticker = tokio::time::interval(interval);
+    let mut full_for = 0;
+    loop {
+        ticker.tick().await;
+        let (full, capacity) = match weak.upgrade() {
+            Some(tx) if !tx.is_closed() => (tx.capacity() == 0, tx.max_capacity()),
+            _ => return,
+        };
+        if !full {
+            full_for = 0;
+            continue;
+        }
+        full_for += 1;
+        if full_for == full_checks {
+            warn(format!(
+                "Channel {} has been full ({} messages) for {:?}, its senders are blocked; \
+                 raise channels.{} if this persists",
+                name,
+                capacity,
+                interval * full_checks as u32,
+                name
+            ));
+        }
+    }
+}
+
+//Unit Test Cases
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::{Arc, Mutex};
+
+    const INTERVAL: Duration = Duration::from_millis(10);
+
+    /// Channel watched every `INTERVAL`, and the warnings it gave
+    fn recorded(capacity: usize) -> (Sender<u32>, Receiver<u32>, Arc<Mutex<Vec<String>>>) {
+        let warnings = Arc::new(Mutex::new(Vec::new()));
+        let sink = Arc::clone(&warnings);
+        let (tx, rx) = watched("statemanager_ack", capacity, INTERVAL, 3, move |message| {
+            sink.lock().unwrap().push(message)
+        });
+        (tx, rx, warnings)
+    }
+
+    #[tokio::test]
+    async fn test_configured_capacity_is_applied() {
+        let (tx, _rx, _) = recorded(7);
+        assert_eq!(tx.max_capacity(), 7);
+        for i in 0..7 {
+            tx.try_send(i).unwrap();
+        }
+        assert!(tx.try_send(7).is_err());
+
+        let (tx, _rx, _) = recorded(0);
+        assert_eq!(tx.max_capacity(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_warns_once_when_saturated() {
+        let (tx, mut rx, warnings) = recorded(2);
+        tx.try_send(1).unwrap();
+        tx.try_send(2).unwrap();
+
+        tokio::time::sleep(INTERVAL * 10).await;
+        {
+            let warnings = warnings.lock().unwrap();
+            assert_eq!(warnings.len(), 1);
+            assert!(warnings[0].contains("statemanager_ack"));
+            assert!(warnings[0].contains("2 messages"));
+        }
+
+        // Draining ends the stretch, filling it again warns again
+        rx.recv().await.unwrap();
+        tokio::time::sleep(INTERVAL * 5).await;
+        tx.try_send(3).unwrap();
+        tokio::time::sleep(INTERVAL * 10).await;
+        assert_eq!(warnings.lock().unwrap().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_no_warning_while_not_full() {
+        let (tx, _rx, warnings) = recorded(2);
+        tx.try_send(1).unwrap();
+
+        tokio::time::sleep(INTERVAL * 10).await;
+        assert!(warnings.lock().unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_watch_does_not_keep_channel_open() {
+        let (tx, mut rx, _) = recorded(2);
+        drop(tx);
+        assert_eq!(rx.recv().await, None);
+    }
+}
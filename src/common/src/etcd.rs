@@ -8,303 +8,783 @@ use crate::rocksdbservice::{
     rocks_db_service_client::RocksDbServiceClient, BatchPutRequest, DeleteRequest,
     GetByPrefixRequest, GetRequest, HealthRequest, KeyValue, PutRequest,
 };
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Duration;
+use tokio::sync::Mutex;
+use tonic::transport::{Channel, Endpoint};
 
 lazy_static::lazy_static! {
+    /// Comma-separated list of RocksDB service endpoints
     static ref ROCKSDB_SERVICE_URL: String = {
         std::env::var("ROCKSDB_SERVICE_URL")
             .unwrap_or_else(|_| "http://localhost:47007".to_string())
     };
+    static ref POOL: EndpointPool = {
+        let settings = &crate::setting::get_config().etcd;
+        EndpointPool::from_list(&ROCKSDB_SERVICE_URL)
+            .with_namespace(&settings.prefix, settings.legacy_fallback)
+    };
 }
 
 const DEV: bool = false;
 
-/// Put a key-value pair into the gRPC RocksDB service
-pub async fn put(key: &str, value: &str) -> Result<(), String> {
-    if DEV {
-        logd!(
-            1,
-            "[RocksDB] Putting key '{}' to service: {}",
-            key,
-            *ROCKSDB_SERVICE_URL
-        );
+/// Time allowed to open a connection before trying the next endpoint
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(3);
+
+/// Connections to the members of a RocksDB service cluster
+///
+/// Each operation is sent to the endpoint that answered last. When that
+/// endpoint is unreachable, the operation is retried on the next one, so a
+/// single member going down does not fail the caller.
+///
+/// Keys may be isolated in a namespace, so several Pullpiri clusters can
+/// share one store. The namespace is prepended to every key sent and
+/// stripped from every key returned, so callers never see it.
+pub struct EndpointPool {
+    /// Endpoint URLs in configuration order
+    endpoints: Vec<String>,
+    /// Open connections, keyed by endpoint URL
+    channels: Mutex<HashMap<String, Channel>>,
+    /// Index of the endpoint to try first
+    preferred: AtomicUsize,
+    /// Prefix of every key, empty or ending with `/`
+    namespace: String,
+    /// Whether keys missing from the namespace are read without it
+    legacy_fallback: bool,
+}
+
+impl EndpointPool {
+    /// Create a pool over `endpoints`
+    pub fn new(endpoints: Vec<String>) -> Self {
+        Self {
+            endpoints,
+            channels: Mutex::new(HashMap::new()),
+            preferred: AtomicUsize::new(0),
+            namespace: String::new(),
+            legacy_fallback: false,
+        }
     }
 
-    match RocksDbServiceClient::connect(ROCKSDB_SERVICE_URL.clone()).await {
-        Ok(mut client) => {
-            let request = tonic::Request::new(PutRequest {
-                key: key.to_string(),
-                value: value.to_string(),
-            });
-
-            match client.put(request).await {
-                Ok(response) => {
-                    let put_response = response.into_inner();
-                    if put_response.success {
-                        Ok(())
-                    } else {
-                        let error_msg = put_response.error;
-                        logd!(5, "[RocksDB] Put failed: {}", error_msg);
-                        Err(error_msg)
-                    }
-                }
-                Err(e) => {
-                    let error_msg = format!("gRPC request failed: {}", e);
-                    logd!(5, "[RocksDB] {}", error_msg);
-                    Err(error_msg)
+    /// Isolate the keys of this pool under `prefix`, e.g. `bench` stores
+    /// `Scenario/a` as `bench/Scenario/a`
+    ///
+    /// With `legacy_fallback`, keys written before the namespace was set
+    /// stay readable while data is migrated: a missing key is read without
+    /// the namespace, and deleting a key also deletes its legacy copy.
+    pub fn with_namespace(mut self, prefix: &str, legacy_fallback: bool) -> Self {
+        let prefix = prefix.trim_end_matches('/');
+        self.namespace = if prefix.is_empty() {
+            String::new()
+        } else {
+            format!("{}/", prefix)
+        };
+        self.legacy_fallback = legacy_fallback;
+        self
+    }
+
+    /// Key as stored, inside the namespace
+    fn namespaced(&self, key: &str) -> String {
+        format!("{}{}", self.namespace, key)
+    }
+
+    /// Whether reads fall back to keys outside the namespace
+    fn falls_back(&self) -> bool {
+        self.legacy_fallback && !self.namespace.is_empty()
+    }
+
+    /// Create a pool from a comma-separated endpoint list
+    ///
+    /// Endpoints without a scheme are assumed to be `http://`.
+    pub fn from_list(list: &str) -> Self {
+        let endpoints = list
+            .split(',')
+            .map(str::trim)
+            .filter(|e| !e.is_empty())
+            .map(|e| {
+                if e.contains("://") {
+                    e.to_string()
+                } else {
+                    format!("http://{}", e)
                 }
-            }
-        }
-        Err(e) => {
-            let error_msg = format!("Failed to create client: {}", e);
-            logd!(5, "[RocksDB] {}", error_msg);
-            Err(error_msg)
+            })
+            .collect();
+        Self::new(endpoints)
+    }
+
+    /// Endpoint URLs in configuration order
+    pub fn endpoints(&self) -> &[String] {
+        &self.endpoints
+    }
+
+    /// Return the open connection to `endpoint`, connecting if needed
+    ///
+    /// The flag is `true` when the connection was reused from the pool.
+    async fn channel(&self, endpoint: &str) -> Result<(Channel, bool), String> {
+        if let Some(channel) = self.channels.lock().await.get(endpoint) {
+            return Ok((channel.clone(), true));
         }
+
+        let channel = Endpoint::from_shared(endpoint.to_string())
+            .map_err(|e| format!("{}: {}", endpoint, e))?
+            .connect_timeout(CONNECT_TIMEOUT)
+            .connect()
+            .await
+            .map_err(|e| format!("{}: {}", endpoint, e))?;
+        self.channels
+            .lock()
+            .await
+            .insert(endpoint.to_string(), channel.clone());
+        Ok((channel, false))
     }
-}
 
-/// Get a value by key from the gRPC RocksDB service
-pub async fn get(key: &str) -> Result<String, String> {
-    if DEV {
-        logd!(
-            1,
-            "[RocksDB] Getting key '{}' from service: {}",
-            key,
-            *ROCKSDB_SERVICE_URL
-        );
+    async fn forget(&self, endpoint: &str) {
+        self.channels.lock().await.remove(endpoint);
     }
 
-    match RocksDbServiceClient::connect(ROCKSDB_SERVICE_URL.clone()).await {
-        Ok(mut client) => {
-            let request = tonic::Request::new(GetRequest {
-                key: key.to_string(),
-            });
-
-            match client.get(request).await {
-                Ok(response) => {
-                    let get_response = response.into_inner();
-                    if get_response.success {
-                        if DEV {
-                            logd!(
-                                1,
-                                "[RocksDB] Successfully retrieved key: {} (value length: {})",
-                                key,
-                                get_response.value.len()
-                            );
+    /// Run `op` against the first reachable endpoint
+    ///
+    /// Connection failures and `Unavailable` responses move on to the next
+    /// endpoint; any other gRPC error is returned as is. `op` may run more
+    /// than once, so it must be idempotent.
+    pub async fn call<T, F, Fut>(&self, op: F) -> Result<T, String>
+    where
+        F: Fn(RocksDbServiceClient<Channel>) -> Fut,
+        Fut: Future<Output = Result<T, tonic::Status>>,
+    {
+        if self.endpoints.is_empty() {
+            return Err("Failed to create client: no RocksDB service endpoint".to_string());
+        }
+
+        let start = self.preferred.load(Ordering::Relaxed);
+        let mut failures = Vec::new();
+
+        for offset in 0..self.endpoints.len() {
+            let index = (start + offset) % self.endpoints.len();
+            let endpoint = &self.endpoints[index];
+
+            // A pooled connection may have gone stale; retry once on a fresh one
+            for _ in 0..2 {
+                let (channel, reused) = match self.channel(endpoint).await {
+                    Ok(channel) => channel,
+                    Err(e) => {
+                        failures.push(e);
+                        break;
+                    }
+                };
+
+                match op(RocksDbServiceClient::new(channel)).await {
+                    Ok(value) => {
+                        if index != start {
+                            logd!(4, "[RocksDB] Failed over to {}", endpoint);
                         }
-                        Ok(get_response.value)
-                    } else {
-                        logd!(5, "[RocksDB] Key not found: {}", key);
-                        Err("Key not found".to_string())
+                        self.preferred.store(index, Ordering::Relaxed);
+                        return Ok(value);
                     }
-                }
-                Err(e) => {
-                    let error_msg = format!("gRPC request failed: {}", e);
-                    logd!(5, "[RocksDB] {}", error_msg);
-                    Err(error_msg)
+                    // Every operation is idempotent, so a failure on a pooled
+                    // connection is retried on a fresh one
+                    Err(_) if reused => self.forget(endpoint).await,
+                    Err(status) if status.code() == tonic::Code::Unavailable => {
+                        self.forget(endpoint).await;
+                        failures.push(format!("{}: {}", endpoint, status));
+                        break;
+                    }
+                    Err(status) => return Err(format!("gRPC request failed: {}", status)),
                 }
             }
         }
-        Err(e) => {
-            let error_msg = format!("Failed to create client: {}", e);
-            logd!(5, "[RocksDB] {}", error_msg);
+
+        let error_msg = format!("Failed to create client: {}", failures.join("; "));
+        logd!(5, "[RocksDB] {}", error_msg);
+        Err(error_msg)
+    }
+
+    /// Put a key-value pair into the gRPC RocksDB service
+    pub async fn put(&self, key: &str, value: &str) -> Result<(), String> {
+        let key = &self.namespaced(key);
+        if DEV {
+            logd!(
+                1,
+                "[RocksDB] Putting key '{}' to service: {:?}",
+                key,
+                self.endpoints
+            );
+        }
+
+        let put_response = self
+            .call(|mut client| async move {
+                client
+                    .put(tonic::Request::new(PutRequest {
+                        key: key.to_string(),
+                        value: value.to_string(),
+                    }))
+                    .await
+                    .map(|response| response.into_inner())
+            })
+            .await?;
+
+        if put_response.success {
+            Ok(())
+        } else {
+            let error_msg = put_response.error;
+            logd!(5, "[RocksDB] Put failed: {}", error_msg);
             Err(error_msg)
         }
     }
-}
 
-/// Get all key-value pairs with the specified prefix using gRPC RocksDB service
-pub async fn get_all_with_prefix(prefix: &str) -> Result<Vec<(String, String)>, String> {
-    if DEV {
-        logd!(
-            1,
-            "[RocksDB] Getting all keys with prefix '{}' from service: {}",
-            prefix,
-            *ROCKSDB_SERVICE_URL
-        );
+    /// Get a value by key from the gRPC RocksDB service
+    pub async fn get(&self, key: &str) -> Result<String, String> {
+        match self.get_raw(&self.namespaced(key)).await {
+            Err(e) if self.falls_back() && e == "Key not found" => {
+                logd!(1, "[RocksDB] Reading legacy key: {}", key);
+                self.get_raw(key).await
+            }
+            result => result,
+        }
     }
 
-    match RocksDbServiceClient::connect(ROCKSDB_SERVICE_URL.clone()).await {
-        Ok(mut client) => {
-            let request = tonic::Request::new(GetByPrefixRequest {
-                prefix: prefix.to_string(),
-                limit: 0, // 0 means no limit
-            });
-
-            match client.get_by_prefix(request).await {
-                Ok(response) => {
-                    let get_response = response.into_inner();
-                    if get_response.error.is_empty() {
-                        let result: Vec<(String, String)> = get_response
-                            .pairs
-                            .into_iter()
-                            .map(|kv| (kv.key, kv.value))
-                            .collect();
-                        if DEV {
-                            logd!(
-                                1,
-                                "[RocksDB] Successfully retrieved {} keys with prefix '{}'",
-                                result.len(),
-                                prefix
-                            );
-                        }
-                        Ok(result)
-                    } else {
-                        logd!(5, "[RocksDB] Error from service: {}", get_response.error);
-                        Err(get_response.error)
-                    }
-                }
-                Err(e) => {
-                    let error_msg = format!("gRPC request failed: {}", e);
-                    logd!(5, "[RocksDB] {}", error_msg);
-                    Err(error_msg)
+    /// Get a value by its key as stored
+    async fn get_raw(&self, key: &str) -> Result<String, String> {
+        if DEV {
+            logd!(
+                1,
+                "[RocksDB] Getting key '{}' from service: {:?}",
+                key,
+                self.endpoints
+            );
+        }
+
+        let get_response = self
+            .call(|mut client| async move {
+                client
+                    .get(tonic::Request::new(GetRequest {
+                        key: key.to_string(),
+                    }))
+                    .await
+                    .map(|response| response.into_inner())
+            })
+            .await?;
+
+        if get_response.success {
+            if DEV {
+                logd!(
+                    1,
+                    "[RocksDB] Successfully retrieved key: {} (value length: {})",
+                    key,
+                    get_response.value.len()
+                );
+            }
+            Ok(get_response.value)
+        } else {
+            logd!(5, "[RocksDB] Key not found: {}", key);
+            Err("Key not found".to_string())
+        }
+    }
+
+    /// Get all key-value pairs with the specified prefix
+    ///
+    /// With the legacy fallback, pairs stored without the namespace are
+    /// included unless the namespace holds the same key.
+    pub async fn get_all_with_prefix(&self, prefix: &str) -> Result<Vec<(String, String)>, String> {
+        let mut pairs: Vec<(String, String)> = self
+            .get_all_with_raw_prefix(&self.namespaced(prefix))
+            .await?
+            .into_iter()
+            .filter_map(|(key, value)| {
+                key.strip_prefix(&self.namespace)
+                    .map(|key| (key.to_string(), value))
+            })
+            .collect();
+
+        if self.falls_back() {
+            let legacy = self.get_all_with_raw_prefix(prefix).await?;
+            for (key, value) in legacy {
+                // An empty prefix also matches the namespaced keys
+                if key.starts_with(&self.namespace) || pairs.iter().any(|(k, _)| *k == key) {
+                    continue;
                 }
+                pairs.push((key, value));
             }
         }
-        Err(e) => {
-            let error_msg = format!("Failed to create client: {}", e);
-            logd!(5, "[RocksDB] {}", error_msg);
-            Err(error_msg)
+        Ok(pairs)
+    }
+
+    /// Get all key-value pairs whose key as stored starts with `prefix`
+    async fn get_all_with_raw_prefix(&self, prefix: &str) -> Result<Vec<(String, String)>, String> {
+        if DEV {
+            logd!(
+                1,
+                "[RocksDB] Getting all keys with prefix '{}' from service: {:?}",
+                prefix,
+                self.endpoints
+            );
+        }
+
+        let get_response = self
+            .call(|mut client| async move {
+                client
+                    .get_by_prefix(tonic::Request::new(GetByPrefixRequest {
+                        prefix: prefix.to_string(),
+                        limit: 0, // 0 means no limit
+                    }))
+                    .await
+                    .map(|response| response.into_inner())
+            })
+            .await?;
+
+        if get_response.error.is_empty() {
+            let result: Vec<(String, String)> = get_response
+                .pairs
+                .into_iter()
+                .map(|kv| (kv.key, kv.value))
+                .collect();
+            if DEV {
+                logd!(
+                    1,
+                    "[RocksDB] Successfully retrieved {} keys with prefix '{}'",
+                    result.len(),
+                    prefix
+                );
+            }
+            Ok(result)
+        } else {
+            logd!(5, "[RocksDB] Error from service: {}", get_response.error);
+            Err(get_response.error)
         }
     }
-}
 
-/// Delete a key from the gRPC RocksDB service
-pub async fn delete(key: &str) -> Result<(), String> {
-    if DEV {
-        logd!(
-            1,
-            "[RocksDB] Deleting key '{}' from service: {}",
-            key,
-            *ROCKSDB_SERVICE_URL
-        );
+    /// Delete a key from the gRPC RocksDB service
+    pub async fn delete(&self, key: &str) -> Result<(), String> {
+        self.delete_raw(&self.namespaced(key)).await?;
+        if self.falls_back() {
+            // Otherwise the legacy copy would be read back
+            self.delete_raw(key).await?;
+        }
+        Ok(())
     }
 
-    match RocksDbServiceClient::connect(ROCKSDB_SERVICE_URL.clone()).await {
-        Ok(mut client) => {
-            let request = tonic::Request::new(DeleteRequest {
-                key: key.to_string(),
-            });
-
-            match client.delete(request).await {
-                Ok(response) => {
-                    let delete_response = response.into_inner();
-                    if delete_response.success {
-                        if DEV {
-                            logd!(1, "[RocksDB] Successfully deleted key: {}", key);
-                        }
-                        Ok(())
-                    } else {
-                        let error_msg = delete_response.error;
-                        logd!(5, "[RocksDB] Delete failed: {}", error_msg);
-                        Err(error_msg)
-                    }
-                }
-                Err(e) => {
-                    let error_msg = format!("gRPC request failed: {}", e);
-                    logd!(5, "[RocksDB] {}", error_msg);
-                    Err(error_msg)
-                }
-            }
+    /// Delete a key as stored
+    async fn delete_raw(&self, key: &str) -> Result<(), String> {
+        if DEV {
+            logd!(
+                1,
+                "[RocksDB] Deleting key '{}' from service: {:?}",
+                key,
+                self.endpoints
+            );
         }
-        Err(e) => {
-            let error_msg = format!("Failed to create client: {}", e);
-            logd!(5, "[RocksDB] {}", error_msg);
+
+        let delete_response = self
+            .call(|mut client| async move {
+                client
+                    .delete(tonic::Request::new(DeleteRequest {
+                        key: key.to_string(),
+                    }))
+                    .await
+                    .map(|response| response.into_inner())
+            })
+            .await?;
+
+        if delete_response.success {
+            if DEV {
+                logd!(1, "[RocksDB] Successfully deleted key: {}", key);
+            }
+            Ok(())
+        } else {
+            let error_msg = delete_response.error;
+            logd!(5, "[RocksDB] Delete failed: {}", error_msg);
             Err(error_msg)
         }
     }
-}
 
-/// Batch put operation to store multiple key-value pairs using gRPC RocksDB service
-pub async fn batch_put(items: Vec<(String, String)>) -> Result<(), String> {
-    if DEV {
-        logd!(
-            1,
-            "[RocksDB] Batch putting {} items to service: {}",
-            items.len(),
-            *ROCKSDB_SERVICE_URL
-        );
-    }
+    /// Store multiple key-value pairs in one request
+    pub async fn batch_put(&self, items: Vec<(String, String)>) -> Result<(), String> {
+        if DEV {
+            logd!(
+                1,
+                "[RocksDB] Batch putting {} items to service: {:?}",
+                items.len(),
+                self.endpoints
+            );
+        }
 
-    match RocksDbServiceClient::connect(ROCKSDB_SERVICE_URL.clone()).await {
-        Ok(mut client) => {
-            let pairs: Vec<KeyValue> = items
-                .into_iter()
-                .map(|(key, value)| KeyValue { key, value })
-                .collect();
+        let pairs: Vec<KeyValue> = items
+            .into_iter()
+            .map(|(key, value)| KeyValue {
+                key: self.namespaced(&key),
+                value,
+            })
+            .collect();
 
-            let request = tonic::Request::new(BatchPutRequest { pairs });
-
-            match client.batch_put(request).await {
-                Ok(response) => {
-                    let batch_response = response.into_inner();
-                    if batch_response.success {
-                        if DEV {
-                            logd!(
-                                1,
-                                "[RocksDB] Successfully stored {} items in batch",
-                                batch_response.processed_count
-                            );
-                        }
-                        Ok(())
-                    } else {
-                        let error_msg = batch_response.error;
-                        logd!(5, "[RocksDB] Batch put failed: {}", error_msg);
-                        Err(error_msg)
-                    }
-                }
-                Err(e) => {
-                    let error_msg = format!("gRPC request failed: {}", e);
-                    logd!(5, "[RocksDB] {}", error_msg);
-                    Err(error_msg)
+        let batch_response = self
+            .call(|mut client| {
+                let pairs = pairs.clone();
+                async move {
+                    client
+                        .batch_put(tonic::Request::new(BatchPutRequest { pairs }))
+                        .await
+                        .map(|response| response.into_inner())
                 }
+            })
+            .await?;
+
+        if batch_response.success {
+            if DEV {
+                logd!(
+                    1,
+                    "[RocksDB] Successfully stored {} items in batch",
+                    batch_response.processed_count
+                );
             }
-        }
-        Err(e) => {
-            let error_msg = format!("Failed to create client: {}", e);
-            logd!(5, "[RocksDB] {}", error_msg);
+            Ok(())
+        } else {
+            let error_msg = batch_response.error;
+            logd!(5, "[RocksDB] Batch put failed: {}", error_msg);
             Err(error_msg)
         }
     }
+
+    /// Health check for the gRPC RocksDB service
+    pub async fn health_check(&self) -> Result<bool, String> {
+        if DEV {
+            logd!(
+                1,
+                "[RocksDB] Health check for service: {:?}",
+                self.endpoints
+            );
+        }
+
+        let health_response = self
+            .call(|mut client| async move {
+                client
+                    .health(tonic::Request::new(HealthRequest {}))
+                    .await
+                    .map(|response| response.into_inner())
+            })
+            .await
+            .map_err(|e| format!("Health check failed: {}", e))?;
+
+        if DEV {
+            logd!(
+                1,
+                "[RocksDB] Health check result: {}",
+                health_response.status
+            );
+        }
+        Ok(health_response.status == "healthy")
+    }
+}
+
+/// Endpoints the module-level functions use, from `ROCKSDB_SERVICE_URL`
+pub fn pool() -> &'static EndpointPool {
+    &POOL
+}
+
+/// Put a key-value pair into the gRPC RocksDB service
+pub async fn put(key: &str, value: &str) -> Result<(), String> {
+    POOL.put(key, value).await
+}
+
+/// Get a value by key from the gRPC RocksDB service
+pub async fn get(key: &str) -> Result<String, String> {
+    POOL.get(key).await
+}
+
+/// Get all key-value pairs with the specified prefix using gRPC RocksDB service
+pub async fn get_all_with_prefix(prefix: &str) -> Result<Vec<(String, String)>, String> {
+    POOL.get_all_with_prefix(prefix).await
+}
+
+/// Delete a key from the gRPC RocksDB service
+pub async fn delete(key: &str) -> Result<(), String> {
+    POOL.delete(key).await
+}
+
+/// Batch put operation to store multiple key-value pairs using gRPC RocksDB service
+pub async fn batch_put(items: Vec<(String, String)>) -> Result<(), String> {
+    POOL.batch_put(items).await
 }
 
 /// Health check for the gRPC RocksDB service
 pub async fn health_check() -> Result<bool, String> {
-    if DEV {
-        logd!(
-            1,
-            "[RocksDB] Health check for service: {}",
-            *ROCKSDB_SERVICE_URL
-        );
+    POOL.health_check().await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rocksdbservice::rocks_db_service_server::{RocksDbService, RocksDbServiceServer};
+    use crate::rocksdbservice::{
+        BatchPutResponse, DeleteResponse, GetByPrefixResponse, GetResponse, HealthResponse,
+        ListKeysRequest, ListKeysResponse, PutResponse,
+    };
+    use std::collections::BTreeMap;
+    use std::sync::Arc;
+    use tokio_stream::wrappers::TcpListenerStream;
+    use tonic::{Request, Response, Status};
+
+    /// In-memory RocksDB service
+    #[derive(Default)]
+    struct MockRocksDb {
+        data: Arc<std::sync::Mutex<BTreeMap<String, String>>>,
     }
 
-    match RocksDbServiceClient::connect(ROCKSDB_SERVICE_URL.clone()).await {
-        Ok(mut client) => {
-            let request = tonic::Request::new(HealthRequest {});
-
-            match client.health(request).await {
-                Ok(response) => {
-                    let health_response = response.into_inner();
-                    let is_healthy = health_response.status == "healthy";
-                    if DEV {
-                        logd!(
-                            1,
-                            "[RocksDB] Health check result: {}",
-                            health_response.status
-                        );
-                    }
-                    Ok(is_healthy)
-                }
-                Err(e) => {
-                    let error_msg = format!("Health check failed: {}", e);
-                    logd!(5, "[RocksDB] {}", error_msg);
-                    Err(error_msg)
-                }
+    #[tonic::async_trait]
+    impl RocksDbService for MockRocksDb {
+        async fn health(
+            &self,
+            _request: Request<HealthRequest>,
+        ) -> Result<Response<HealthResponse>, Status> {
+            Ok(Response::new(HealthResponse {
+                status: "healthy".to_string(),
+                ..Default::default()
+            }))
+        }
+
+        async fn put(&self, request: Request<PutRequest>) -> Result<Response<PutResponse>, Status> {
+            let req = request.into_inner();
+            self.data.lock().unwrap().insert(req.key, req.value);
+            Ok(Response::new(PutResponse {
+                success: true,
+                error: String::new(),
+            }))
+        }
+
+        async fn get(&self, request: Request<GetRequest>) -> Result<Response<GetResponse>, Status> {
+            let value = self
+                .data
+                .lock()
+                .unwrap()
+                .get(&request.into_inner().key)
+                .cloned();
+            Ok(Response::new(GetResponse {
+                success: value.is_some(),
+                value: value.unwrap_or_default(),
+                message: String::new(),
+            }))
+        }
+
+        async fn delete(
+            &self,
+            request: Request<DeleteRequest>,
+        ) -> Result<Response<DeleteResponse>, Status> {
+            self.data.lock().unwrap().remove(&request.into_inner().key);
+            Ok(Response::new(DeleteResponse {
+                success: true,
+                error: String::new(),
+            }))
+        }
+
+        async fn batch_put(
+            &self,
+            request: Request<BatchPutRequest>,
+        ) -> Result<Response<BatchPutResponse>, Status> {
+            let pairs = request.into_inner().pairs;
+            let count = pairs.len() as i32;
+            let mut data = self.data.lock().unwrap();
+            for kv in pairs {
+                data.insert(kv.key, kv.value);
             }
+            Ok(Response::new(BatchPutResponse {
+                success: true,
+                processed_count: count,
+                error: String::new(),
+            }))
         }
-        Err(e) => {
-            let error_msg = format!("Failed to create client: {}", e);
-            logd!(5, "[RocksDB] {}", error_msg);
-            Err(error_msg)
+
+        async fn get_by_prefix(
+            &self,
+            request: Request<GetByPrefixRequest>,
+        ) -> Result<Response<GetByPrefixResponse>, Status> {
+            let prefix = request.into_inner().prefix;
+            let pairs: Vec<KeyValue> = self
+                .data
+                .lock()
+                .unwrap()
+                .iter()
+                .filter(|(k, _)| k.starts_with(&prefix))
+                .map(|(k, v)| KeyValue {
+                    key: k.clone(),
+                    value: v.clone(),
+                })
+                .collect();
+            Ok(Response::new(GetByPrefixResponse {
+                total_count: pairs.len() as i32,
+                pairs,
+                error: String::new(),
+            }))
+        }
+
+        async fn list_keys(
+            &self,
+            _request: Request<ListKeysRequest>,
+        ) -> Result<Response<ListKeysResponse>, Status> {
+            Err(Status::unimplemented("list_keys"))
         }
     }
+
+    /// Start a mock service and return its endpoint URL
+    async fn start_mock() -> String {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(
+            tonic::transport::Server::builder()
+                .add_service(RocksDbServiceServer::new(MockRocksDb::default()))
+                .serve_with_incoming(TcpListenerStream::new(listener)),
+        );
+        format!("http://{}", addr)
+    }
+
+    /// Endpoint URL with nothing listening on it
+    async fn down_endpoint() -> String {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        drop(listener);
+        format!("http://{}", addr)
+    }
+
+    #[test]
+    fn test_from_list_parses_endpoints() {
+        let pool = EndpointPool::from_list(" a:1, http://b:2 ,,https://c:3");
+        assert_eq!(
+            pool.endpoints(),
+            &["http://a:1", "http://b:2", "https://c:3"]
+        );
+        assert!(EndpointPool::from_list("").endpoints().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_failover_when_first_endpoint_is_down() {
+        let down = down_endpoint().await;
+        let up = start_mock().await;
+        let pool = EndpointPool::new(vec![down, up]);
+
+        pool.put("/piccolo/a", "1").await.unwrap();
+        assert_eq!(pool.preferred.load(Ordering::Relaxed), 1);
+        pool.batch_put(vec![("/piccolo/b".to_string(), "2".to_string())])
+            .await
+            .unwrap();
+        assert_eq!(pool.get("/piccolo/a").await.unwrap(), "1");
+        assert_eq!(
+            pool.get_all_with_prefix("/piccolo/").await.unwrap().len(),
+            2
+        );
+        pool.delete("/piccolo/a").await.unwrap();
+        assert_eq!(pool.get("/piccolo/a").await.unwrap_err(), "Key not found");
+        assert!(pool.health_check().await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_failover_when_member_goes_down() {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let first = format!("http://{}", listener.local_addr().unwrap());
+        let (stop_tx, stop_rx) = tokio::sync::oneshot::channel::<()>();
+        let server = tokio::spawn(
+            tonic::transport::Server::builder()
+                .add_service(RocksDbServiceServer::new(MockRocksDb::default()))
+                .serve_with_incoming_shutdown(TcpListenerStream::new(listener), async {
+                    let _ = stop_rx.await;
+                }),
+        );
+        let second = start_mock().await;
+        let pool = EndpointPool::new(vec![first, second]);
+
+        pool.put("key", "first").await.unwrap();
+        assert_eq!(pool.preferred.load(Ordering::Relaxed), 0);
+
+        let _ = stop_tx.send(());
+        let _ = server.await;
+
+        // The pooled connection to the first member is dead now
+        pool.put("key", "second").await.unwrap();
+        assert_eq!(pool.preferred.load(Ordering::Relaxed), 1);
+        assert_eq!(pool.get("key").await.unwrap(), "second");
+    }
+
+    #[tokio::test]
+    async fn test_all_endpoints_down() {
+        let pool = EndpointPool::new(vec![down_endpoint().await, down_endpoint().await]);
+
+        let err = pool.put("key", "value").await.unwrap_err();
+        assert!(err.starts_with("Failed to create client"));
+        assert!(pool.health_check().await.is_err());
+        assert!(EndpointPool::new(vec![]).get("key").await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_namespaces_are_isolated() {
+        let url = start_mock().await;
+        let bench = EndpointPool::new(vec![url.clone()]).with_namespace("bench", false);
+        let hil = EndpointPool::new(vec![url.clone()]).with_namespace("hil/", false);
+        let raw = EndpointPool::new(vec![url]);
+
+        bench.put("Scenario/a", "bench").await.unwrap();
+        hil.put("Scenario/a", "hil").await.unwrap();
+        hil.batch_put(vec![("Scenario/b".to_string(), "hil".to_string())])
+            .await
+            .unwrap();
+
+        assert_eq!(bench.get("Scenario/a").await.unwrap(), "bench");
+        assert_eq!(hil.get("Scenario/a").await.unwrap(), "hil");
+        assert_eq!(bench.get("Scenario/b").await.unwrap_err(), "Key not found");
+        assert_eq!(
+            bench.get_all_with_prefix("Scenario/").await.unwrap(),
+            vec![("Scenario/a".to_string(), "bench".to_string())]
+        );
+        assert_eq!(hil.get_all_with_prefix("Scenario/").await.unwrap().len(), 2);
+        assert_eq!(raw.get("bench/Scenario/a").await.unwrap(), "bench");
+        assert!(raw.get("Scenario/a").await.is_err());
+
+        bench.delete("Scenario/a").await.unwrap();
+        assert!(bench.get("Scenario/a").await.is_err());
+        assert_eq!(hil.get("Scenario/a").await.unwrap(), "hil");
+    }
+
+    #[tokio::test]
+    async fn test_namespace_legacy_fallback() {
+        let url = start_mock().await;
+        let raw = EndpointPool::new(vec![url.clone()]);
+        let strict = EndpointPool::new(vec![url.clone()]).with_namespace("bench", false);
+        let migrating = EndpointPool::new(vec![url]).with_namespace("bench", true);
+
+        raw.put("Scenario/old", "legacy").await.unwrap();
+        raw.put("Scenario/both", "legacy").await.unwrap();
+        migrating.put("Scenario/both", "new").await.unwrap();
+
+        assert!(strict.get("Scenario/old").await.is_err());
+        assert_eq!(migrating.get("Scenario/old").await.unwrap(), "legacy");
+        assert_eq!(migrating.get("Scenario/both").await.unwrap(), "new");
+
+        let mut pairs = migrating.get_all_with_prefix("Scenario/").await.unwrap();
+        pairs.sort();
+        assert_eq!(
+            pairs,
+            vec![
+                ("Scenario/both".to_string(), "new".to_string()),
+                ("Scenario/old".to_string(), "legacy".to_string()),
+            ]
+        );
+        assert_eq!(migrating.get_all_with_prefix("").await.unwrap().len(), 2);
+
+        migrating.delete("Scenario/old").await.unwrap();
+        assert!(migrating.get("Scenario/old").await.is_err());
+        assert!(raw.get("Scenario/old").await.is_err());
+    }
+
+    #[test]
+    fn test_empty_namespace_keeps_keys() {
+        let pool = EndpointPool::new(vec![]).with_namespace("", true);
+        assert_eq!(pool.namespaced("Scenario/a"), "Scenario/a");
+        assert!(!pool.falls_back());
+    }
+
+    #[tokio::test]
+    async fn test_connections_are_reused() {
+        let pool = EndpointPool::new(vec![start_mock().await]);
+
+        pool.put("key", "value").await.unwrap();
+        pool.get("key").await.unwrap();
+        assert_eq!(pool.channels.lock().await.len(), 1);
+    }
 }
@@ -0,0 +1,291 @@
+/*
+ * SPDX-FileCopyrightText: Copyright 2024 LG Electronics Inc.
+ * SPDX-License-Identifier: Apache-2.0
+ */
+
+//! Standard `grpc.health.v1.Health` service shared by Pullpiri gRPC servers.
+//!
+//! Each server adds the health service next to its own service, runs a
+//! dependency probe that flips the reported status between `SERVING` and
+//! `NOT_SERVING`, and reports `NOT_SERVING` for good once shutdown begins.
+
+use crate::logd;
+use std::future::Future;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tonic::server::NamedService;
+
+pub use tonic_health::pb::health_server::{Health, HealthServer};
+pub use tonic_health::server::HealthReporter;
+pub use tonic_health::ServingStatus;
+
+/// Default interval between dependency probes
+pub const PROBE_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Health state of one gRPC server.
+///
+/// Status is published both for the named service and for the empty
+/// service name, which the health protocol defines as the overall server.
+#[derive(Clone)]
+pub struct ServerHealth {
+    reporter: HealthReporter,
+    service_name: &'static str,
+    shutting_down: Arc<AtomicBool>,
+}
+
+impl ServerHealth {
+    /// Create the health state for service `S` and its linked health service.
+    pub fn new<S: NamedService>() -> (Self, HealthServer<impl Health>) {
+        let (reporter, server) = tonic_health::server::health_reporter();
+        let health = Self {
+            reporter,
+            service_name: S::NAME,
+            shutting_down: Arc::new(AtomicBool::new(false)),
+        };
+        (health, server)
+    }
+
+    /// Publish `SERVING` or `NOT_SERVING`.
+    ///
+    /// Once shutdown has started the status stays `NOT_SERVING`.
+    pub async fn set_serving(&self, serving: bool) {
+        let status = if serving && !self.shutting_down.load(Ordering::SeqCst) {
+            ServingStatus::Serving
+        } else {
+            ServingStatus::NotServing
+        };
+
+        let mut reporter = self.reporter.clone();
+        reporter.set_service_status(self.service_name, status).await;
+        reporter.set_service_status("", status).await;
+    }
+
+    /// Spawn a task that runs `probe` every `interval` and publishes its result.
+    ///
+    /// The probe should return `true` when every dependency the server needs
+    /// (etcd, downstream channels, ...) is reachable.
+    pub fn spawn_probe<F, Fut>(&self, interval: Duration, probe: F) -> tokio::task::JoinHandle<()>
+    where
+        F: Fn() -> Fut + Send + 'static,
+        Fut: Future<Output = bool> + Send,
+    {
+        let health = self.clone();
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            let mut last = None;
+            loop {
+                ticker.tick().await;
+                if health.shutting_down.load(Ordering::SeqCst) {
+                    break;
+                }
+                let serving = probe().await;
+                if last != Some(serving) {
+                    logd!(
+                        if serving { 2 } else { 4 },
+                        "[Health] {} is {}",
+                        health.service_name,
+                        if serving { "SERVING" } else { "NOT_SERVING" }
+                    );
+                    last = Some(serving);
+                }
+                health.set_serving(serving).await;
+            }
+        })
+    }
+
+    /// Mark the server as shutting down and report `NOT_SERVING`.
+    pub async fn shutdown(&self) {
+        self.shutting_down.store(true, Ordering::SeqCst);
+        self.set_serving(false).await;
+    }
+
+    /// Wait for Ctrl-C or SIGTERM, then switch to `NOT_SERVING`.
+    ///
+    /// Intended to be passed to `Server::serve_with_shutdown`.
+    pub async fn shutdown_signal(self) {
+        wait_for_signal().await;
+        logd!(4, "[Health] {} shutting down", self.service_name);
+        self.shutdown().await;
+    }
+}
+
+/// Ask the gRPC server at `url` for its overall health status
+///
+/// Returns the status name reported by the server (`SERVING`,
+/// `NOT_SERVING`, ...), or why it could not be queried.
+pub async fn check(url: &str) -> Result<String, String> {
+    use tonic_health::pb::health_client::HealthClient;
+    use tonic_health::pb::HealthCheckRequest;
+
+    let channel = crate::rpc::connect(url.to_string())
+        .await
+        .map_err(|e| format!("cannot connect to {}: {}", url, e))?;
+    let mut client = HealthClient::new(channel);
+    let request = HealthCheckRequest {
+        service: String::new(),
+    };
+    let response = crate::rpc::call(request, |request| client.check(request))
+        .await
+        .map_err(|status| status.message().to_string())?;
+
+    let status = response.into_inner().status();
+    Ok(status.as_str_name().to_string())
+}
+
+/// Returns `true` when the RocksDB service behind `common::etcd` answers healthy.
+pub async fn etcd_reachable() -> bool {
+    matches!(crate::etcd::health_check().await, Ok(true))
+}
+
+#[cfg(unix)]
+async fn wait_for_signal() {
+    use tokio::signal::unix::{signal, SignalKind};
+
+    match signal(SignalKind::terminate()) {
+        Ok(mut sigterm) => {
+            tokio::select! {
+                _ = tokio::signal::ctrl_c() => {}
+                _ = sigterm.recv() => {}
+            }
+        }
+        Err(_) => {
+            let _ = tokio::signal::ctrl_c().await;
+        }
+    }
+}
+
+#[cfg(not(unix))]
+async fn wait_for_signal() {
+    let _ = tokio::signal::ctrl_c().await;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio_stream::wrappers::TcpListenerStream;
+    use tonic_health::pb::health_client::HealthClient;
+    use tonic_health::pb::HealthCheckRequest;
+
+    struct TestService;
+
+    impl NamedService for TestService {
+        const NAME: &'static str = "pullpiri.test.TestService";
+    }
+
+    async fn query(client: &mut HealthClient<tonic::transport::Channel>, service: &str) -> i32 {
+        match client
+            .check(HealthCheckRequest {
+                service: service.to_string(),
+            })
+            .await
+        {
+            Ok(response) => response.into_inner().status,
+            // The probe may not have published a status yet
+            Err(status) if status.code() == tonic::Code::NotFound => -1,
+            Err(status) => panic!("health check failed: {}", status),
+        }
+    }
+
+    async fn wait_for(
+        client: &mut HealthClient<tonic::transport::Channel>,
+        expected: ServingStatus,
+    ) -> bool {
+        let expected = match expected {
+            ServingStatus::Serving => 1,
+            ServingStatus::NotServing => 2,
+            ServingStatus::Unknown => 0,
+        };
+        for _ in 0..50 {
+            if query(client, TestService::NAME).await == expected
+                && query(client, "").await == expected
+            {
+                return true;
+            }
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+        false
+    }
+
+    #[tokio::test]
+    async fn test_health_status_transitions() {
+        let (health, health_server) = ServerHealth::new::<TestService>();
+        let dependency_up = Arc::new(AtomicBool::new(true));
+        let probe_flag = dependency_up.clone();
+        let probe = health.spawn_probe(Duration::from_millis(10), move || {
+            let up = probe_flag.load(Ordering::SeqCst);
+            async move { up }
+        });
+
+        let listener = match tokio::net::TcpListener::bind("127.0.0.1:0").await {
+            Ok(listener) => listener,
+            Err(e) => panic!("failed to bind test listener: {}", e),
+        };
+        let addr = match listener.local_addr() {
+            Ok(addr) => addr,
+            Err(e) => panic!("failed to read listener address: {}", e),
+        };
+        let server = tokio::spawn(
+            tonic::transport::Server::builder()
+                .add_service(health_server)
+                .serve_with_incoming(TcpListenerStream::new(listener)),
+        );
+
+        let channel = match tonic::transport::Endpoint::from_shared(format!("http://{}", addr)) {
+            Ok(endpoint) => match endpoint.connect().await {
+                Ok(channel) => channel,
+                Err(e) => panic!("failed to connect health client: {}", e),
+            },
+            Err(e) => panic!("invalid endpoint: {}", e),
+        };
+        let mut client = HealthClient::new(channel);
+
+        assert!(wait_for(&mut client, ServingStatus::Serving).await);
+
+        dependency_up.store(false, Ordering::SeqCst);
+        assert!(wait_for(&mut client, ServingStatus::NotServing).await);
+
+        dependency_up.store(true, Ordering::SeqCst);
+        assert!(wait_for(&mut client, ServingStatus::Serving).await);
+
+        health.shutdown().await;
+        assert!(wait_for(&mut client, ServingStatus::NotServing).await);
+
+        // The probe must not flip the status back once shutdown started
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        assert_eq!(query(&mut client, TestService::NAME).await, 2);
+        assert!(probe.is_finished());
+
+        server.abort();
+    }
+
+    #[tokio::test]
+    async fn test_check_reports_server_status() {
+        let (health, health_server) = ServerHealth::new::<TestService>();
+        health.set_serving(true).await;
+
+        let listener = match tokio::net::TcpListener::bind("127.0.0.1:0").await {
+            Ok(listener) => listener,
+            Err(e) => panic!("failed to bind test listener: {}", e),
+        };
+        let addr = match listener.local_addr() {
+            Ok(addr) => addr,
+            Err(e) => panic!("failed to read listener address: {}", e),
+        };
+        let server = tokio::spawn(
+            tonic::transport::Server::builder()
+                .add_service(health_server)
+                .serve_with_incoming(TcpListenerStream::new(listener)),
+        );
+
+        let url = format!("http://{}", addr);
+        assert_eq!(check(&url).await, Ok("SERVING".to_string()));
+
+        health.shutdown().await;
+        assert_eq!(check(&url).await, Ok("NOT_SERVING".to_string()));
+
+        server.abort();
+        let _ = server.await;
+        assert!(check(&url).await.is_err());
+    }
+}
@@ -0,0 +1,348 @@
+/*
+ * SPDX-FileCopyrightText: Copyright 2024 LG Electronics Inc.
+ * SPDX-License-Identifier: Apache-2.0
+ */
+
+//! Canonical keys of the key-value store behind `common::etcd`.
+//!
+//! Every component reads what another one wrote, so keys must be built
+//! here instead of with ad-hoc `format!` calls.
+
+/// Prefix of stored scenario artifacts
+pub const SCENARIO_PREFIX: &str = "Scenario/";
+/// Prefix of stored package artifacts
+pub const PACKAGE_PREFIX: &str = "Package/";
+/// Prefix of stored model artifacts
+pub const MODEL_PREFIX: &str = "Model/";
+/// Prefix of stored policy artifacts
+pub const POLICY_PREFIX: &str = "Policy/";
+/// Prefix of stored network artifacts
+pub const NETWORK_PREFIX: &str = "Network/";
+/// Prefix of stored volume artifacts
+pub const VOLUME_PREFIX: &str = "Volume/";
+/// Prefix of stored schedule artifacts
+pub const SCHEDULE_PREFIX: &str = "Schedule/";
+/// Prefix of cluster-wide data such as registered nodes and topology
+pub const CLUSTER_PREFIX: &str = "cluster/";
+/// Prefix of registered node information
+pub const CLUSTER_NODES_PREFIX: &str = "cluster/nodes/";
+/// Prefix of the nodes cordoned for maintenance, where nothing is launched
+pub const CORDONED_NODES_PREFIX: &str = "cluster/cordoned/";
+/// Prefix of the nodes marked offline after missing their heartbeats
+pub const OFFLINE_NODES_PREFIX: &str = "cluster/offline/";
+/// Prefix of the versions reported by the NodeAgents when registering
+pub const NODE_VERSIONS_PREFIX: &str = "cluster/versions/";
+/// Prefix of the hostname <-> IP address mappings
+pub const NODE_ADDRESS_PREFIX: &str = "nodes/";
+/// Key of the cluster topology
+pub const CLUSTER_TOPOLOGY: &str = "cluster/topology";
+/// Prefix of monitoring metrics
+pub const METRICS_PREFIX: &str = "/piccolo/metrics/";
+/// Prefix of monitoring logs
+pub const LOGS_PREFIX: &str = "/piccolo/logs/";
+/// Prefix of monitoring metadata
+pub const METADATA_PREFIX: &str = "/piccolo/metadata/";
+/// Prefix of the scenario states saved by StateManager
+pub const SCENARIO_STATE_PREFIX: &str = "/scenario/";
+/// Prefix of the package states saved by StateManager
+pub const PACKAGE_STATE_PREFIX: &str = "/package/";
+/// Prefix of the model states saved by StateManager
+pub const MODEL_STATE_PREFIX: &str = "/model/";
+/// Prefix of recorded preemption decisions
+pub const DECISIONS_PREFIX: &str = "/piccolo/decisions/";
+/// Prefix of the requests StateManager sent and awaits an acknowledgement for
+pub const PENDING_PREFIX: &str = "StateManager/pending/";
+/// Prefix of the pods using each network created by a NodeAgent
+pub const NETWORK_REFS_PREFIX: &str = "NodeAgent/networks/";
+/// Prefix of the scenario notifications ApiServer could not deliver to FilterGateway
+pub const FILTERGATEWAY_DEADLETTER_PREFIX: &str = "ApiServer/deadletter/filtergateway/";
+/// Prefix of the git sources artifacts were applied from
+pub const ARTIFACT_SOURCE_PREFIX: &str = "ApiServer/sources/";
+/// Prefix of the import status of artifacts
+pub const ARTIFACT_STATUS_PREFIX: &str = "ApiServer/status/";
+/// Prefix of the delayed actions ActionController has yet to run
+pub const SCHEDULED_ACTIONS_PREFIX: &str = "ActionController/scheduled/";
+
+/// Key of an artifact of any `kind`, e.g. `Scenario/helloworld`
+pub fn artifact(kind: &str, name: &str) -> String {
+    format!("{}/{}", kind, name)
+}
+
+/// Key of a scenario artifact
+pub fn scenario(name: &str) -> String {
+    artifact("Scenario", name)
+}
+
+/// Key of a package artifact
+pub fn package(name: &str) -> String {
+    artifact("Package", name)
+}
+
+/// Key of a model artifact
+pub fn model(name: &str) -> String {
+    artifact("Model", name)
+}
+
+/// Key of a volume artifact
+pub fn volume(name: &str) -> String {
+    artifact("Volume", name)
+}
+
+/// Key of a network artifact
+pub fn network(name: &str) -> String {
+    artifact("Network", name)
+}
+
+/// Key of a schedule artifact
+pub fn schedule(name: &str) -> String {
+    artifact("Schedule", name)
+}
+
+/// Key of a policy artifact
+pub fn policy(name: &str) -> String {
+    artifact("Policy", name)
+}
+
+/// Key of the pod generated for a model
+pub fn pod(name: &str) -> String {
+    artifact("Pod", name)
+}
+
+/// Key of a registered node, e.g. `cluster/nodes/HPC`
+pub fn node(name: &str) -> String {
+    format!("{}{}", CLUSTER_NODES_PREFIX, name)
+}
+
+/// Key marking a node unschedulable, e.g. `cluster/cordoned/HPC`
+pub fn cordoned_node(name: &str) -> String {
+    format!("{}{}", CORDONED_NODES_PREFIX, name)
+}
+
+/// Key marking a node offline, e.g. `cluster/offline/HPC`
+pub fn offline_node(name: &str) -> String {
+    format!("{}{}", OFFLINE_NODES_PREFIX, name)
+}
+
+/// Key of the version a node reported, e.g. `cluster/versions/HPC`
+pub fn node_version(name: &str) -> String {
+    format!("{}{}", NODE_VERSIONS_PREFIX, name)
+}
+
+/// Key mapping a hostname to its IP address, or an IP address to its hostname
+pub fn node_address(hostname_or_ip: &str) -> String {
+    format!("{}{}", NODE_ADDRESS_PREFIX, hostname_or_ip)
+}
+
+/// Key of the state of a resource, e.g. `/scenario/helloworld/state`
+///
+/// `resource_type` is the lowercase resource kind (`scenario`, `package`, `model`).
+pub fn state(resource_type: &str, name: &str) -> String {
+    format!("/{}/{}/state", resource_type, name)
+}
+
+/// Key of the state of a scenario
+pub fn scenario_state(name: &str) -> String {
+    state("scenario", name)
+}
+
+/// Key of the state of a package
+pub fn package_state(name: &str) -> String {
+    state("package", name)
+}
+
+/// Key of the state of a model
+pub fn model_state(name: &str) -> String {
+    state("model", name)
+}
+
+/// Prefix of the metrics of one resource type, e.g. `/piccolo/metrics/nodes/`
+pub fn metrics_prefix(resource_type: &str) -> String {
+    format!("{}{}/", METRICS_PREFIX, resource_type)
+}
+
+/// Key of the metrics of one resource
+pub fn metrics(resource_type: &str, resource_id: &str) -> String {
+    format!("{}{}", metrics_prefix(resource_type), resource_id)
+}
+
+/// Prefix of the logs of one resource
+pub fn logs_prefix(resource_type: &str, resource_id: &str) -> String {
+    format!("{}{}/{}", LOGS_PREFIX, resource_type, resource_id)
+}
+
+/// Key of the recent log lines of a container,
+/// e.g. `/piccolo/logs/containers/HPC/helloworld`
+pub fn container_logs(node: &str, container: &str) -> String {
+    logs_prefix("containers", &format!("{}/{}", node, container))
+}
+
+/// Key of the metadata of one resource
+pub fn metadata(resource_type: &str, resource_id: &str) -> String {
+    format!("{}{}/{}", METADATA_PREFIX, resource_type, resource_id)
+}
+
+/// Key of a preemption decision taken for a scenario, e.g.
+/// `/piccolo/decisions/parking-assist/1700000000000000000`
+pub fn decision(scenario_name: &str, timestamp_ns: i64) -> String {
+    format!("{}{}/{}", DECISIONS_PREFIX, scenario_name, timestamp_ns)
+}
+
+/// Key of a request awaiting acknowledgement, e.g. `StateManager/pending/<id>`
+pub fn pending(transaction_id: &str) -> String {
+    format!("{}{}", PENDING_PREFIX, transaction_id)
+}
+
+/// Key of an undelivered scenario notification,
+/// e.g. `ApiServer/deadletter/filtergateway/helloworld`
+pub fn filtergateway_deadletter(scenario_name: &str) -> String {
+    format!("{}{}", FILTERGATEWAY_DEADLETTER_PREFIX, scenario_name)
+}
+
+/// Key of the pods using a network on one node, e.g. `NodeAgent/networks/HPC/bms-net`
+pub fn network_refs(node: &str, network: &str) -> String {
+    format!("{}{}/{}", NETWORK_REFS_PREFIX, node, network)
+}
+
+/// Key of the delayed action of a scenario, e.g. `ActionController/scheduled/helloworld`
+pub fn scheduled_action(scenario: &str) -> String {
+    format!("{}{}", SCHEDULED_ACTIONS_PREFIX, scenario)
+}
+
+/// Key of the git source an artifact was applied from,
+/// e.g. `ApiServer/sources/Scenario/helloworld`
+pub fn artifact_source(kind: &str, name: &str) -> String {
+    format!("{}{}", ARTIFACT_SOURCE_PREFIX, artifact(kind, name))
+}
+
+/// Key of the import status of an artifact, by the name of its scenario,
+/// e.g. `ApiServer/status/helloworld`
+pub fn artifact_status(scenario_name: &str) -> String {
+    format!("{}{}", ARTIFACT_STATUS_PREFIX, scenario_name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_artifact_keys() {
+        assert_eq!(scenario("helloworld"), "Scenario/helloworld");
+        assert_eq!(package("helloworld"), "Package/helloworld");
+        assert_eq!(model("helloworld-core"), "Model/helloworld-core");
+        assert_eq!(volume("data"), "Volume/data");
+        assert_eq!(network("bridge"), "Network/bridge");
+        assert_eq!(schedule("nightly"), "Schedule/nightly");
+        assert_eq!(policy("night-limit"), "Policy/night-limit");
+        assert_eq!(pod("helloworld-core"), "Pod/helloworld-core");
+        assert_eq!(artifact("Node", "HPC"), "Node/HPC");
+    }
+
+    #[test]
+    fn test_artifact_keys_match_prefixes() {
+        assert!(scenario("a").starts_with(SCENARIO_PREFIX));
+        assert!(package("a").starts_with(PACKAGE_PREFIX));
+        assert!(model("a").starts_with(MODEL_PREFIX));
+        assert!(policy("a").starts_with(POLICY_PREFIX));
+        assert!(network("a").starts_with(NETWORK_PREFIX));
+        assert!(volume("a").starts_with(VOLUME_PREFIX));
+        assert!(schedule("a").starts_with(SCHEDULE_PREFIX));
+        // Reference lists must not show up in a scan of the network artifacts
+        assert!(!network_refs("HPC", "a").starts_with(NETWORK_PREFIX));
+    }
+
+    #[test]
+    fn test_node_keys() {
+        assert_eq!(node("HPC"), "cluster/nodes/HPC");
+        assert_eq!(node_address("HPC"), "nodes/HPC");
+        assert_eq!(node_address("192.168.0.10"), "nodes/192.168.0.10");
+        assert!(node("HPC").starts_with(CLUSTER_PREFIX));
+        assert!(CLUSTER_TOPOLOGY.starts_with(CLUSTER_PREFIX));
+        assert_eq!(cordoned_node("HPC"), "cluster/cordoned/HPC");
+        // Cordon marks must not show up in a scan of the registered nodes
+        assert!(!cordoned_node("HPC").starts_with(CLUSTER_NODES_PREFIX));
+        assert_eq!(offline_node("HPC"), "cluster/offline/HPC");
+        assert!(!offline_node("HPC").starts_with(CLUSTER_NODES_PREFIX));
+        assert_eq!(node_version("HPC"), "cluster/versions/HPC");
+        assert!(!node_version("HPC").starts_with(CLUSTER_NODES_PREFIX));
+    }
+
+    #[test]
+    fn test_state_keys() {
+        assert_eq!(scenario_state("helloworld"), "/scenario/helloworld/state");
+        assert_eq!(package_state("helloworld"), "/package/helloworld/state");
+        assert_eq!(
+            model_state("helloworld-core"),
+            "/model/helloworld-core/state"
+        );
+        assert_eq!(
+            state("scenario", "helloworld"),
+            scenario_state("helloworld")
+        );
+        assert!(scenario_state("helloworld").starts_with(SCENARIO_STATE_PREFIX));
+        assert!(package_state("helloworld").starts_with(PACKAGE_STATE_PREFIX));
+        assert!(model_state("helloworld-core").starts_with(MODEL_STATE_PREFIX));
+    }
+
+    #[test]
+    fn test_monitoring_keys() {
+        assert_eq!(metrics_prefix("nodes"), "/piccolo/metrics/nodes/");
+        assert_eq!(metrics("nodes", "HPC"), "/piccolo/metrics/nodes/HPC");
+        assert_eq!(
+            logs_prefix("containers", "c1"),
+            "/piccolo/logs/containers/c1"
+        );
+        assert_eq!(
+            container_logs("HPC", "c1"),
+            "/piccolo/logs/containers/HPC/c1"
+        );
+        assert_eq!(
+            metadata("containers", "c1"),
+            "/piccolo/metadata/containers/c1"
+        );
+        assert_eq!(decision("p", 42), "/piccolo/decisions/p/42");
+    }
+
+    #[test]
+    fn test_pending_keys() {
+        assert_eq!(pending("tx-1"), "StateManager/pending/tx-1");
+        assert!(pending("tx-1").starts_with(PENDING_PREFIX));
+    }
+
+    #[test]
+    fn test_deadletter_keys() {
+        assert_eq!(
+            filtergateway_deadletter("helloworld"),
+            "ApiServer/deadletter/filtergateway/helloworld"
+        );
+        assert!(filtergateway_deadletter("a").starts_with(FILTERGATEWAY_DEADLETTER_PREFIX));
+    }
+
+    #[test]
+    fn test_artifact_source_keys() {
+        assert_eq!(
+            artifact_source("Scenario", "helloworld"),
+            "ApiServer/sources/Scenario/helloworld"
+        );
+        // Sources must not show up in a scan of the artifacts
+        assert!(!artifact_source("Scenario", "a").starts_with(SCENARIO_PREFIX));
+        assert_eq!(artifact_status("helloworld"), "ApiServer/status/helloworld");
+        assert!(!artifact_status("a").starts_with(ARTIFACT_SOURCE_PREFIX));
+    }
+
+    #[test]
+    fn test_network_refs_keys() {
+        assert_eq!(
+            network_refs("HPC", "bms-net"),
+            "NodeAgent/networks/HPC/bms-net"
+        );
+    }
+
+    #[test]
+    fn test_scheduled_action_keys() {
+        assert_eq!(
+            scheduled_action("helloworld"),
+            "ActionController/scheduled/helloworld"
+        );
+        assert!(scheduled_action("a").starts_with(SCHEDULED_ACTIONS_PREFIX));
+    }
+}
@@ -4,10 +4,19 @@
  */
 pub use crate::error::Result;
 
+pub mod channel;
 pub mod error;
 pub mod etcd;
+pub mod health;
+pub mod keys;
+pub mod logging;
+pub mod network;
+pub mod recover;
+pub mod rpc;
 pub mod setting;
 pub mod spec;
+pub mod trace;
+pub mod version;
 
 // gRPC protobuf module for RocksDB service
 pub mod rocksdbservice {
@@ -142,6 +142,14 @@ pub async fn log(level: i32, message: String) {
     }
 }
 
+/// Whether `log_nowait` can deliver a message from the calling thread
+///
+/// # Returns
+/// * `bool` - The logger is initialized and a Tokio runtime is running.
+pub fn is_running() -> bool {
+    LOGGER.get().is_some() && Handle::try_current().is_ok()
+}
+
 /// Fire-and-forget API for synchronous call sites. Spawns a task on the
 /// current Tokio runtime (if any) to enqueue the log message.
 ///
@@ -149,6 +157,11 @@ pub async fn log(level: i32, message: String) {
 /// * `level` - Severity level code.
 /// * `message` - Formatted log message.
 pub fn log_nowait(level: i32, message: String) {
+    // Read here, the trace of the caller is not visible in the spawned task
+    let message = match crate::trace::current() {
+        Some(trace_id) => format!("[trace_id={}] {}", trace_id, message),
+        None => message,
+    };
     match Handle::try_current() {
         Ok(handle) => {
             handle.spawn(async move {
@@ -0,0 +1,378 @@
+/*
+ * SPDX-FileCopyrightText: Copyright 2024 LG Electronics Inc.
+ * SPDX-License-Identifier: Apache-2.0
+ */
+
+//! Structured logging shared by the Pullpiri daemons
+//!
+//! `init` installs a `tracing` subscriber writing to stdout, so the daemons
+//! log through the `tracing` macros instead of `println!`. Every line
+//! carries the name of the daemon in a `component` field, letting journald
+//! or a log shipper tell the daemons apart.
+//!
+//! The level filter comes from the `PICCOLO_LOG` environment variable, in
+//! `RUST_LOG` syntax. Without it, the daemon logs at the level set for it in
+//! `logging.levels` of `settings.yaml`, `info` by default, and other crates
+//! only log warnings.
+//!
+//! Once the daemon has started the logd logger, the events that pass the
+//! filter are also sent to the logd aggregator.
+
+use crate::logd::logger;
+use crate::setting::{LogFormat, LoggingSettings};
+use std::fmt;
+use tracing::field::{Field, Visit};
+use tracing::{Event, Level, Subscriber};
+use tracing_subscriber::fmt::format::{Format, JsonFields, Writer};
+use tracing_subscriber::fmt::{FmtContext, FormatEvent, FormatFields, MakeWriter};
+use tracing_subscriber::layer::{Context, SubscriberExt};
+use tracing_subscriber::registry::LookupSpan;
+use tracing_subscriber::util::SubscriberInitExt;
+use tracing_subscriber::{EnvFilter, Layer};
+
+/// Environment variable overriding the level filter
+pub const LOG_ENV: &str = "PICCOLO_LOG";
+
+const DEFAULT_LEVEL: &str = "info";
+
+const TRACE_SPAN_DIRECTIVE: &str = "common::trace=info";
+
+/// Install the logging subscriber of the daemon `component`
+///
+/// Does nothing if a subscriber is already installed.
+///
+/// # Arguments
+/// * `component` - Name of the daemon, e.g. `nodeagent`
+pub fn init(component: &'static str) {
+    let settings = &crate::setting::get_config().logging;
+    let env = std::env::var(LOG_ENV).ok();
+
+    let installed = tracing_subscriber::registry()
+        .with(filter(component, settings, env.as_deref()))
+        .with(layer(component, settings.format, std::io::stdout))
+        .with(LogdLayer)
+        .try_init()
+        .is_ok();
+
+    if let Some(env) = env.filter(|env| installed && EnvFilter::try_new(env).is_err()) {
+        tracing::warn!("Ignoring invalid {}: {}", LOG_ENV, env);
+    }
+}
+
+/// Level filter of `component`
+///
+/// # Arguments
+/// * `component` - Name of the daemon
+/// * `settings` - `logging` section of `settings.yaml`
+/// * `env` - Value of `PICCOLO_LOG`, if set
+///
+/// # Returns
+/// * `EnvFilter` - `env` if it is a valid filter, otherwise the default
+///   level of `component` for itself and `common`, and `warn` for the rest.
+///   The span of [`crate::trace::scope`] is always enabled.
+pub fn filter(component: &str, settings: &LoggingSettings, env: Option<&str>) -> EnvFilter {
+    let filter = env
+        .and_then(|env| EnvFilter::try_new(env).ok())
+        .unwrap_or_else(|| {
+            let level = settings
+                .levels
+                .get(component)
+                .map(String::as_str)
+                .unwrap_or(DEFAULT_LEVEL);
+            EnvFilter::try_new(format!("warn,{}={},common={}", component, level, level))
+                .unwrap_or_else(|_| EnvFilter::new(format!("warn,{}={}", component, DEFAULT_LEVEL)))
+        });
+    // Keep the span carrying the trace ID whatever the levels
+    match TRACE_SPAN_DIRECTIVE.parse() {
+        Ok(directive) => filter.add_directive(directive),
+        Err(_) => filter,
+    }
+}
+
+/// Layer formatting events of `component` to `writer`
+pub fn layer<S, W>(
+    component: &'static str,
+    format: LogFormat,
+    writer: W,
+) -> Box<dyn Layer<S> + Send + Sync>
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+    W: for<'w> MakeWriter<'w> + Send + Sync + 'static,
+{
+    let layer = tracing_subscriber::fmt::layer()
+        .with_ansi(false)
+        .with_writer(writer);
+    match format {
+        LogFormat::Text => Box::new(layer.event_format(ComponentFormat {
+            component,
+            json: false,
+            inner: Format::default(),
+        })),
+        // Span fields, such as the trace ID, must be recorded as JSON too
+        LogFormat::Json => Box::new(layer.fmt_fields(JsonFields::new()).event_format(
+            ComponentFormat {
+                component,
+                json: true,
+                inner: Format::default().json(),
+            },
+        )),
+    }
+}
+
+/// Event format adding a `component` field to every line of `inner`
+struct ComponentFormat<F> {
+    component: &'static str,
+    json: bool,
+    inner: F,
+}
+
+impl<S, N, F> FormatEvent<S, N> for ComponentFormat<F>
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+    N: for<'a> FormatFields<'a> + 'static,
+    F: FormatEvent<S, N>,
+{
+    fn format_event(
+        &self,
+        ctx: &FmtContext<'_, S, N>,
+        mut writer: Writer<'_>,
+        event: &Event<'_>,
+    ) -> fmt::Result {
+        let mut line = String::new();
+        self.inner
+            .format_event(ctx, Writer::new(&mut line), event)?;
+        if !self.json {
+            return write!(writer, "component={} {}", self.component, line);
+        }
+        let fields = line.strip_prefix('{').unwrap_or(&line);
+        let separator = if fields.starts_with('}') { "" } else { "," };
+        write!(
+            writer,
+            "{{\"component\":\"{}\"{}{}",
+            self.component, separator, fields
+        )
+    }
+}
+
+/// Layer forwarding events to the logd aggregator
+///
+/// Events are dropped until the daemon starts the logd logger, and when
+/// logged outside of the Tokio runtime.
+struct LogdLayer;
+
+impl<S: Subscriber> Layer<S> for LogdLayer {
+    fn on_event(&self, event: &Event<'_>, _ctx: Context<'_, S>) {
+        if logger::is_running() {
+            logger::log_nowait(logd_level(event.metadata().level()), logd_message(event));
+        }
+    }
+}
+
+/// Severity code logd uses for `level`
+fn logd_level(level: &Level) -> i32 {
+    match *level {
+        Level::TRACE => 1,
+        Level::DEBUG => 2,
+        Level::INFO => 3,
+        Level::WARN => 4,
+        Level::ERROR => 5,
+    }
+}
+
+/// Message of `event`, followed by its other fields as `name=value`
+fn logd_message(event: &Event<'_>) -> String {
+    struct Message(String);
+
+    impl Visit for Message {
+        fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+            let separator = if self.0.is_empty() { "" } else { " " };
+            if field.name() == "message" {
+                self.0 = format!("{:?}{}{}", value, separator, self.0);
+            } else {
+                self.0 = format!("{}{}{}={:?}", self.0, separator, field.name(), value);
+            }
+        }
+    }
+
+    let mut message = Message(String::new());
+    event.record(&mut message);
+    message.0
+}
+
+//Unit Test Cases
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+    use std::io;
+    use std::sync::{Arc, Mutex};
+
+    #[derive(Clone, Default)]
+    struct Buffer(Arc<Mutex<Vec<u8>>>);
+
+    impl io::Write for Buffer {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.lock().unwrap().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    impl Buffer {
+        fn lines(&self) -> Vec<String> {
+            String::from_utf8(self.0.lock().unwrap().clone())
+                .unwrap()
+                .lines()
+                .map(str::to_string)
+                .collect()
+        }
+    }
+
+    /// Lines logged while running `f` with the subscriber of `component`
+    fn capture(
+        component: &'static str,
+        settings: &LoggingSettings,
+        env: Option<&str>,
+        f: impl FnOnce(),
+    ) -> Vec<String> {
+        let buffer = Buffer::default();
+        let writer = buffer.clone();
+        let subscriber = tracing_subscriber::registry()
+            .with(filter(component, settings, env))
+            .with(layer(component, settings.format, move || writer.clone()));
+        tracing::subscriber::with_default(subscriber, f);
+        buffer.lines()
+    }
+
+    fn settings(format: LogFormat, levels: &[(&str, &str)]) -> LoggingSettings {
+        LoggingSettings {
+            format,
+            levels: levels
+                .iter()
+                .map(|(k, v)| (k.to_string(), v.to_string()))
+                .collect::<HashMap<_, _>>(),
+        }
+    }
+
+    #[test]
+    fn test_env_filter_overrides_default_level() {
+        let settings = settings(LogFormat::Text, &[]);
+        let log = || {
+            tracing::debug!(target: "nodeagent", "debug line");
+            tracing::info!(target: "nodeagent", "info line");
+            tracing::info!(target: "tonic", "noisy line");
+        };
+
+        let default = capture("nodeagent", &settings, None, log);
+        assert_eq!(default.len(), 1);
+        assert!(default[0].contains("info line"));
+
+        let verbose = capture("nodeagent", &settings, Some("nodeagent=debug"), log);
+        assert_eq!(verbose.len(), 2);
+        assert!(verbose[0].contains("debug line"));
+
+        let quiet = capture("nodeagent", &settings, Some("error"), log);
+        assert!(quiet.is_empty());
+
+        // An invalid filter falls back to the default level
+        let invalid = capture("nodeagent", &settings, Some("nodeagent=loud"), log);
+        assert_eq!(invalid.len(), 1);
+    }
+
+    #[test]
+    fn test_component_level_from_settings() {
+        let settings = settings(LogFormat::Text, &[("statemanager", "debug")]);
+
+        let lines = capture("statemanager", &settings, None, || {
+            tracing::debug!(target: "statemanager", "debug line");
+            tracing::debug!(target: "common", "common debug line");
+            tracing::debug!(target: "h2", "dependency debug line");
+        });
+
+        assert_eq!(lines.len(), 2);
+        assert!(lines[1].contains("common debug line"));
+    }
+
+    #[test]
+    fn test_text_line_has_component() {
+        let settings = settings(LogFormat::Text, &[]);
+
+        let lines = capture("apiserver", &settings, None, || {
+            tracing::warn!(target: "apiserver", "hello");
+        });
+
+        assert_eq!(lines.len(), 1);
+        assert!(lines[0].starts_with("component=apiserver "));
+        assert!(lines[0].contains("WARN"));
+        assert!(lines[0].ends_with("hello"));
+    }
+
+    #[test]
+    fn test_json_line_has_component() {
+        let settings = settings(LogFormat::Json, &[]);
+
+        let lines = capture("monitoringserver", &settings, None, || {
+            tracing::info!(target: "monitoringserver", node = "HPC", "stored");
+        });
+
+        assert_eq!(lines.len(), 1);
+        let line: serde_json::Value = serde_json::from_str(&lines[0]).unwrap();
+        assert_eq!(line["component"], "monitoringserver");
+        assert_eq!(line["level"], "INFO");
+        assert_eq!(line["fields"]["message"], "stored");
+        assert_eq!(line["fields"]["node"], "HPC");
+    }
+
+    #[test]
+    fn test_trace_id_in_every_format() {
+        let log = || {
+            crate::trace::span("trace-1")
+                .in_scope(|| tracing::info!(target: "filtergateway", "in trace"));
+        };
+
+        let text = capture("filtergateway", &settings(LogFormat::Text, &[]), None, log);
+        assert!(text[0].contains("trace{trace_id=trace-1}"));
+
+        // Also kept when PICCOLO_LOG leaves out `common`
+        let filtered = settings(LogFormat::Text, &[]);
+        let text = capture("filtergateway", &filtered, Some("filtergateway=info"), log);
+        assert!(text[0].contains("trace{trace_id=trace-1}"));
+
+        let json = capture("filtergateway", &settings(LogFormat::Json, &[]), None, log);
+        let line: serde_json::Value = serde_json::from_str(&json[0]).unwrap();
+        assert_eq!(line["span"]["trace_id"], "trace-1");
+    }
+
+    #[test]
+    fn test_logd_message_and_level() {
+        #[derive(Clone, Default)]
+        struct Forwarded(Arc<Mutex<Vec<(i32, String)>>>);
+
+        impl<S: Subscriber> Layer<S> for Forwarded {
+            fn on_event(&self, event: &Event<'_>, _ctx: Context<'_, S>) {
+                self.0
+                    .lock()
+                    .unwrap()
+                    .push((logd_level(event.metadata().level()), logd_message(event)));
+            }
+        }
+
+        let forwarded = Forwarded::default();
+        let subscriber = tracing_subscriber::registry().with(forwarded.clone());
+        tracing::subscriber::with_default(subscriber, || {
+            tracing::warn!(node = "HPC", attempt = 2, "node {} is late", "HPC");
+            tracing::debug!(done = true);
+        });
+
+        assert_eq!(
+            *forwarded.0.lock().unwrap(),
+            vec![
+                (4, "node HPC is late node=\"HPC\" attempt=2".to_string()),
+                (2, "done=true".to_string()),
+            ]
+        );
+    }
+}
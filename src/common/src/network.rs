@@ -0,0 +1,333 @@
+/*
+ * SPDX-FileCopyrightText: Copyright 2024 LG Electronics Inc.
+ * SPDX-License-Identifier: Apache-2.0
+ */
+
+//! Podman networks declared by Network artifacts
+//!
+//! A pod annotated with a network joins it. The network is created before
+//! the first pod using it starts, and removed when the last one stops. The
+//! pods using a network are kept in etcd by node, so the count survives a
+//! restart of whichever component drives podman on that node: NodeAgent, or
+//! ActionController for nodes of type `podman`.
+
+use crate::logd;
+use crate::spec::artifact::network::NETWORK_ANNOTATION;
+use crate::spec::artifact::{Artifact, Network};
+use serde_json::json;
+
+pub type Error = Box<dyn std::error::Error + Send + Sync>;
+
+/// Network operations of the container runtime
+#[tonic::async_trait]
+pub trait NetworkApi: Send + Sync {
+    async fn exists(&self, name: &str) -> Result<bool, Error>;
+    async fn create(&self, network: &Network) -> Result<(), Error>;
+    async fn remove(&self, name: &str) -> Result<(), Error>;
+}
+
+/// Storage of the pods using each network
+#[tonic::async_trait]
+pub trait RefStore: Send + Sync {
+    async fn load(&self, network: &str) -> Result<Vec<String>, Error>;
+    /// Save the pods using `network`, forgetting it when there are none
+    async fn save(&self, network: &str, pods: &[String]) -> Result<(), Error>;
+}
+
+/// Request body creating `network` with the libpod API
+pub fn create_body(network: &Network) -> serde_json::Value {
+    let mut body = json!({ "name": network.get_name() });
+    let Some(spec) = network.get_spec() else {
+        return body;
+    };
+
+    body["driver"] = json!(spec.get_driver().as_str());
+    if let Some(subnet) = spec.get_subnet() {
+        let mut entry = json!({ "subnet": subnet });
+        if let Some(gateway) = spec.get_gateway() {
+            entry["gateway"] = json!(gateway);
+        }
+        body["subnets"] = json!([entry]);
+    }
+    if let Some(dns) = spec.get_dns() {
+        body["dns_enabled"] = json!(true);
+        if !dns.servers.is_empty() {
+            body["network_dns_servers"] = json!(dns.servers);
+        }
+    }
+    body
+}
+
+/// Pods using each network of one node, stored in etcd
+pub struct EtcdRefs {
+    pub node: String,
+}
+
+#[tonic::async_trait]
+impl RefStore for EtcdRefs {
+    async fn load(&self, network: &str) -> Result<Vec<String>, Error> {
+        match crate::etcd::get(&crate::keys::network_refs(&self.node, network)).await {
+            Ok(value) => Ok(serde_json::from_str(&value)?),
+            Err(_) => Ok(Vec::new()),
+        }
+    }
+
+    async fn save(&self, network: &str, pods: &[String]) -> Result<(), Error> {
+        let key = crate::keys::network_refs(&self.node, network);
+        if pods.is_empty() {
+            crate::etcd::delete(&key).await?;
+        } else {
+            crate::etcd::put(&key, &serde_json::to_string(pods)?).await?;
+        }
+        Ok(())
+    }
+}
+
+/// Make sure `network` exists and record that `pod` uses it
+///
+/// # Arguments
+///
+/// * `api` - Container runtime creating the network
+/// * `refs` - Pods using each network
+/// * `network` - Network artifact the pod references
+/// * `pod` - Name of the pod about to start
+pub async fn attach(
+    api: &impl NetworkApi,
+    refs: &impl RefStore,
+    network: &Network,
+    pod: &str,
+) -> Result<(), Error> {
+    let name = network.get_name();
+    if !api.exists(&name).await? {
+        api.create(network).await?;
+        logd!(3, "Created network {}", name);
+    }
+
+    let mut pods = refs.load(&name).await?;
+    if !pods.iter().any(|p| p == pod) {
+        pods.push(pod.to_string());
+        refs.save(&name, &pods).await?;
+    }
+    Ok(())
+}
+
+/// Record that `pod` stopped using `network`, removing the network if it
+/// was the last pod using it
+///
+/// # Returns
+///
+/// * `Result<bool, Error>` - Whether the network was removed
+pub async fn detach(
+    api: &impl NetworkApi,
+    refs: &impl RefStore,
+    network: &str,
+    pod: &str,
+) -> Result<bool, Error> {
+    let mut pods = refs.load(network).await?;
+    pods.retain(|p| p != pod);
+    refs.save(network, &pods).await?;
+
+    if !pods.is_empty() {
+        return Ok(false);
+    }
+    if api.exists(network).await? {
+        api.remove(network).await?;
+        logd!(3, "Removed network {}", network);
+    }
+    Ok(true)
+}
+
+/// Network the pod in `pod_yaml` joins, if it is annotated with one
+pub fn pod_network(pod_yaml: &str) -> Option<String> {
+    let pod = serde_yaml::from_str::<crate::spec::k8s::Pod>(pod_yaml).ok()?;
+    pod.get_annotation(NETWORK_ANNOTATION).map(str::to_string)
+}
+
+/// Network artifact named `network`, as stored in etcd
+pub async fn load(network: &str) -> Result<Network, Error> {
+    let artifact = crate::etcd::get(&crate::keys::network(network))
+        .await
+        .map_err(|e| format!("network '{}' is not registered: {}", network, e))?;
+    Ok(serde_yaml::from_str(&artifact)?)
+}
+
+//Unit Test Cases
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+    use std::sync::Mutex;
+
+    /// Podman networks kept in memory, counting the calls made
+    #[derive(Default)]
+    struct MockPodman {
+        networks: Mutex<Vec<String>>,
+        created: Mutex<u32>,
+        removed: Mutex<u32>,
+    }
+
+    #[tonic::async_trait]
+    impl NetworkApi for MockPodman {
+        async fn exists(&self, name: &str) -> Result<bool, Error> {
+            Ok(self.networks.lock().unwrap().iter().any(|n| n == name))
+        }
+
+        async fn create(&self, network: &Network) -> Result<(), Error> {
+            self.networks.lock().unwrap().push(network.get_name());
+            *self.created.lock().unwrap() += 1;
+            Ok(())
+        }
+
+        async fn remove(&self, name: &str) -> Result<(), Error> {
+            self.networks.lock().unwrap().retain(|n| n != name);
+            *self.removed.lock().unwrap() += 1;
+            Ok(())
+        }
+    }
+
+    #[derive(Default)]
+    struct MemoryRefs(Mutex<HashMap<String, Vec<String>>>);
+
+    #[tonic::async_trait]
+    impl RefStore for MemoryRefs {
+        async fn load(&self, network: &str) -> Result<Vec<String>, Error> {
+            Ok(self
+                .0
+                .lock()
+                .unwrap()
+                .get(network)
+                .cloned()
+                .unwrap_or_default())
+        }
+
+        async fn save(&self, network: &str, pods: &[String]) -> Result<(), Error> {
+            let mut refs = self.0.lock().unwrap();
+            if pods.is_empty() {
+                refs.remove(network);
+            } else {
+                refs.insert(network.to_string(), pods.to_vec());
+            }
+            Ok(())
+        }
+    }
+
+    const NETWORK: &str = r#"
+apiVersion: v1
+kind: Network
+metadata:
+  name: bms-net
+spec:
+  driver: bridge
+  subnet: 10.89.1.0/24
+  gateway: 10.89.1.1
+  dns:
+    servers: [10.89.1.53]
+"#;
+
+    fn network() -> Network {
+        serde_yaml::from_str(NETWORK).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_first_pod_creates_network() {
+        let podman = MockPodman::default();
+        let refs = MemoryRefs::default();
+
+        attach(&podman, &refs, &network(), "bms-blis")
+            .await
+            .unwrap();
+
+        assert_eq!(*podman.created.lock().unwrap(), 1);
+        assert!(podman.exists("bms-net").await.unwrap());
+        assert_eq!(refs.load("bms-net").await.unwrap(), vec!["bms-blis"]);
+    }
+
+    #[tokio::test]
+    async fn test_existing_network_is_reused() {
+        let podman = MockPodman::default();
+        let refs = MemoryRefs::default();
+
+        attach(&podman, &refs, &network(), "bms-blis")
+            .await
+            .unwrap();
+        attach(&podman, &refs, &network(), "bms-mavd")
+            .await
+            .unwrap();
+        // Starting the same pod again does not count it twice
+        attach(&podman, &refs, &network(), "bms-mavd")
+            .await
+            .unwrap();
+
+        assert_eq!(*podman.created.lock().unwrap(), 1);
+        assert_eq!(
+            refs.load("bms-net").await.unwrap(),
+            vec!["bms-blis", "bms-mavd"]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_last_pod_removes_network() {
+        let podman = MockPodman::default();
+        let refs = MemoryRefs::default();
+        attach(&podman, &refs, &network(), "bms-blis")
+            .await
+            .unwrap();
+        attach(&podman, &refs, &network(), "bms-mavd")
+            .await
+            .unwrap();
+
+        assert!(!detach(&podman, &refs, "bms-net", "bms-blis").await.unwrap());
+        assert!(podman.exists("bms-net").await.unwrap());
+        assert_eq!(*podman.removed.lock().unwrap(), 0);
+
+        assert!(detach(&podman, &refs, "bms-net", "bms-mavd").await.unwrap());
+        assert!(!podman.exists("bms-net").await.unwrap());
+        assert_eq!(*podman.removed.lock().unwrap(), 1);
+        assert!(refs.0.lock().unwrap().is_empty());
+
+        // Stopping a pod again leaves the removed network alone
+        assert!(detach(&podman, &refs, "bms-net", "bms-mavd").await.unwrap());
+        assert_eq!(*podman.removed.lock().unwrap(), 1);
+    }
+
+    #[test]
+    fn test_create_body() {
+        assert_eq!(
+            create_body(&network()),
+            json!({
+                "name": "bms-net",
+                "driver": "bridge",
+                "subnets": [{"subnet": "10.89.1.0/24", "gateway": "10.89.1.1"}],
+                "dns_enabled": true,
+                "network_dns_servers": ["10.89.1.53"]
+            })
+        );
+
+        let bare: Network = serde_yaml::from_str(
+            "apiVersion: v1\nkind: Network\nmetadata:\n  name: plain\nspec: null\n",
+        )
+        .unwrap();
+        assert_eq!(create_body(&bare), json!({"name": "plain"}));
+    }
+
+    #[test]
+    fn test_pod_network() {
+        let pod = r#"
+apiVersion: v1
+kind: Pod
+metadata:
+  name: bms-blis
+  annotations:
+    io.piccolo.annotations.network: bms-net
+spec:
+  containers:
+    - name: blis
+      image: blis:latest
+"#;
+        assert_eq!(pod_network(pod), Some("bms-net".to_string()));
+        assert_eq!(
+            pod_network(&pod.replace("io.piccolo.annotations.network", "other")),
+            None
+        );
+    }
+}
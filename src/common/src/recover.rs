@@ -0,0 +1,185 @@
+/*
+ * SPDX-FileCopyrightText: Copyright 2024 LG Electronics Inc.
+ * SPDX-License-Identifier: Apache-2.0
+ */
+
+//! Recovery from panics in gRPC handlers
+//!
+//! Without it, a handler that panics (e.g. an `.unwrap()` on malformed
+//! input) aborts its stream and the client only sees a reset. A server
+//! built with [`RecoverLayer`] answers such a request with `INTERNAL`
+//! instead, and logs the panic. The panic message is never sent to the
+//! client.
+
+use crate::logd;
+use std::any::Any;
+use std::future::Future;
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::pin::{pin, Pin};
+use std::task::{Context, Poll};
+use tonic::body::BoxBody;
+use tonic::Status;
+use tower_layer::Layer;
+use tower_service::Service;
+
+/// Message of the status returned for a request whose handler panicked
+pub const PANIC_MESSAGE: &str = "internal error while handling the request";
+
+/// Server layer turning handler panics into `INTERNAL` statuses
+///
+/// ```ignore
+/// Server::builder()
+///     .layer(common::trace::TraceLayer)
+///     .layer(common::recover::RecoverLayer)
+///     .add_service(...)
+/// ```
+#[derive(Debug, Clone, Copy, Default)]
+pub struct RecoverLayer;
+
+impl<S> Layer<S> for RecoverLayer {
+    type Service = RecoverService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        RecoverService { inner }
+    }
+}
+
+/// Service wrapped by [`RecoverLayer`]
+#[derive(Debug, Clone)]
+pub struct RecoverService<S> {
+    inner: S,
+}
+
+impl<S, B> Service<http::Request<B>> for RecoverService<S>
+where
+    S: Service<http::Request<B>, Response = http::Response<BoxBody>>,
+    S::Future: Send + 'static,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = Pin<Box<dyn Future<Output = Result<S::Response, S::Error>> + Send>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, request: http::Request<B>) -> Self::Future {
+        let path = request.uri().path().to_string();
+        let future = self.inner.call(request);
+        Box::pin(async move {
+            let mut future = pin!(future);
+            let polled = std::future::poll_fn(|cx| {
+                match catch_unwind(AssertUnwindSafe(|| future.as_mut().poll(cx))) {
+                    Ok(Poll::Pending) => Poll::Pending,
+                    Ok(Poll::Ready(result)) => Poll::Ready(Ok(result)),
+                    Err(panic) => Poll::Ready(Err(panic)),
+                }
+            })
+            .await;
+            match polled {
+                Ok(result) => result,
+                Err(panic) => {
+                    logd!(
+                        5,
+                        "Handler of {} panicked: {}",
+                        path,
+                        panic_message(&*panic)
+                    );
+                    Ok(Status::internal(PANIC_MESSAGE).into_http())
+                }
+            }
+        })
+    }
+}
+
+/// Message a panic was raised with
+fn panic_message(panic: &(dyn Any + Send)) -> &str {
+    if let Some(message) = panic.downcast_ref::<&str>() {
+        message
+    } else if let Some(message) = panic.downcast_ref::<String>() {
+        message
+    } else {
+        "unknown panic payload"
+    }
+}
+
+//Unit Test Cases
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::policymanager::policy_manager_connection_client::PolicyManagerConnectionClient;
+    use crate::policymanager::policy_manager_connection_server::{
+        PolicyManagerConnection, PolicyManagerConnectionServer,
+    };
+    use crate::policymanager::{
+        CheckPolicyRequest, CheckPolicyResponse, DecideRequest, DecideResponse,
+    };
+    use tokio_stream::wrappers::TcpListenerStream;
+    use tonic::{Code, Request, Response};
+
+    /// Policy manager panicking on a request without scenario name
+    struct Fragile;
+
+    #[tonic::async_trait]
+    impl PolicyManagerConnection for Fragile {
+        async fn check_policy(
+            &self,
+            request: Request<CheckPolicyRequest>,
+        ) -> Result<Response<CheckPolicyResponse>, Status> {
+            let name = request.into_inner().scenario_name;
+            let first = name.chars().next().unwrap();
+            Ok(Response::new(CheckPolicyResponse {
+                status: 0,
+                desc: first.to_string(),
+            }))
+        }
+
+        async fn decide(
+            &self,
+            _request: Request<DecideRequest>,
+        ) -> Result<Response<DecideResponse>, Status> {
+            Err(Status::unimplemented("not used by these tests"))
+        }
+    }
+
+    fn check(scenario_name: &str) -> CheckPolicyRequest {
+        CheckPolicyRequest {
+            scenario_name: scenario_name.to_string(),
+            context: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_handler_panic_returns_internal() {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(
+            tonic::transport::Server::builder()
+                .layer(RecoverLayer)
+                .add_service(PolicyManagerConnectionServer::new(Fragile))
+                .serve_with_incoming(TcpListenerStream::new(listener)),
+        );
+        let channel = crate::rpc::connect(format!("http://{}", addr))
+            .await
+            .unwrap();
+        let mut client = PolicyManagerConnectionClient::new(channel);
+
+        let status = client.check_policy(check("")).await.unwrap_err();
+        assert_eq!(status.code(), Code::Internal);
+        assert_eq!(status.message(), PANIC_MESSAGE);
+
+        // The connection survives the panic
+        let response = client.check_policy(check("helloworld")).await.unwrap();
+        assert_eq!(response.into_inner().desc, "h");
+    }
+
+    #[test]
+    fn test_panic_message() {
+        let panic = catch_unwind(|| panic!("boom")).unwrap_err();
+        assert_eq!(panic_message(&*panic), "boom");
+        let panic = catch_unwind(|| panic!("code {}", 7)).unwrap_err();
+        assert_eq!(panic_message(&*panic), "code 7");
+        let panic = catch_unwind(|| std::panic::panic_any(7)).unwrap_err();
+        assert_eq!(panic_message(&*panic), "unknown panic payload");
+    }
+}
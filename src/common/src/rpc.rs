@@ -0,0 +1,182 @@
+/*
+ * SPDX-FileCopyrightText: Copyright 2024 LG Electronics Inc.
+ * SPDX-License-Identifier: Apache-2.0
+ */
+
+//! Client-side helpers shared by inter-service gRPC calls.
+//!
+//! Every call made through [`call`] carries a deadline, so a hung server
+//! fails the call with `DEADLINE_EXCEEDED` instead of blocking the caller.
+//! The default deadline is `grpc.timeout_ms` in `settings.yaml`. Calls
+//! also carry the trace ID of the request being served, see [`crate::trace`].
+//! Connecting negotiates the version of the peer, see [`crate::version`].
+
+use crate::setting;
+use std::future::Future;
+use std::time::Duration;
+use tonic::transport::{Channel, Endpoint};
+use tonic::{Code, Request, Response, Status};
+
+/// Default deadline of inter-service gRPC calls
+pub fn default_deadline() -> Duration {
+    Duration::from_millis(setting::get_config().grpc.timeout_ms)
+}
+
+/// Builds an endpoint whose connection attempts are bounded by the default deadline
+pub fn endpoint(url: impl Into<String>) -> Result<Endpoint, tonic::transport::Error> {
+    Ok(Endpoint::from_shared(url.into())?.connect_timeout(default_deadline()))
+}
+
+/// Connects to `url`, giving up after the default deadline
+///
+/// The version of the peer is asked on the first connection to `url`.
+pub async fn connect(url: impl Into<String>) -> Result<Channel, tonic::transport::Error> {
+    let url = url.into();
+    let channel = endpoint(url.clone())?.connect().await?;
+    crate::version::negotiate(&url, channel.clone()).await;
+    Ok(channel)
+}
+
+/// Sends `message` with the default deadline
+///
+/// ```ignore
+/// let response = rpc::call(action, |request| client.send_action(request)).await?;
+/// ```
+pub async fn call<M, T, F, Fut>(message: M, send: F) -> Result<Response<T>, Status>
+where
+    F: FnOnce(Request<M>) -> Fut,
+    Fut: Future<Output = Result<Response<T>, Status>>,
+{
+    call_with_deadline(message, default_deadline(), send).await
+}
+
+/// Sends `message` with an explicit deadline, overriding the default
+///
+/// The deadline is also sent to the server as `grpc-timeout`. When it
+/// expires the call fails with `Code::DeadlineExceeded`.
+pub async fn call_with_deadline<M, T, F, Fut>(
+    message: M,
+    deadline: Duration,
+    send: F,
+) -> Result<Response<T>, Status>
+where
+    F: FnOnce(Request<M>) -> Fut,
+    Fut: Future<Output = Result<Response<T>, Status>>,
+{
+    let mut request = crate::trace::request(message);
+    request.set_timeout(deadline);
+
+    match tokio::time::timeout(deadline, send(request)).await {
+        // The channel enforces `grpc-timeout` itself and reports it as CANCELLED
+        Ok(Err(status)) if is_client_timeout(&status) => Err(deadline_exceeded(deadline)),
+        Ok(result) => result,
+        Err(_) => Err(deadline_exceeded(deadline)),
+    }
+}
+
+fn is_client_timeout(status: &Status) -> bool {
+    status.code() == Code::Cancelled && status.message() == tonic::TimeoutExpired(()).to_string()
+}
+
+fn deadline_exceeded(deadline: Duration) -> Status {
+    Status::deadline_exceeded(format!("no response within {:?}", deadline))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::policymanager::policy_manager_connection_client::PolicyManagerConnectionClient;
+    use crate::policymanager::policy_manager_connection_server::{
+        PolicyManagerConnection, PolicyManagerConnectionServer,
+    };
+    use crate::policymanager::{
+        CheckPolicyRequest, CheckPolicyResponse, DecideRequest, DecideResponse,
+    };
+    use std::time::Instant;
+    use tokio_stream::wrappers::TcpListenerStream;
+
+    /// Policy manager that answers after `delay`
+    struct SlowPolicyManager {
+        delay: Duration,
+    }
+
+    #[tonic::async_trait]
+    impl PolicyManagerConnection for SlowPolicyManager {
+        async fn check_policy(
+            &self,
+            request: Request<CheckPolicyRequest>,
+        ) -> Result<Response<CheckPolicyResponse>, Status> {
+            tokio::time::sleep(self.delay).await;
+            Ok(Response::new(CheckPolicyResponse {
+                status: 0,
+                desc: request.into_inner().scenario_name,
+            }))
+        }
+
+        async fn decide(
+            &self,
+            _request: Request<DecideRequest>,
+        ) -> Result<Response<DecideResponse>, Status> {
+            Err(Status::unimplemented("not used by these tests"))
+        }
+    }
+
+    async fn start_server(delay: Duration) -> PolicyManagerConnectionClient<Channel> {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(
+            tonic::transport::Server::builder()
+                .add_service(PolicyManagerConnectionServer::new(SlowPolicyManager {
+                    delay,
+                }))
+                .serve_with_incoming(TcpListenerStream::new(listener)),
+        );
+        let channel = connect(format!("http://{}", addr)).await.unwrap();
+        PolicyManagerConnectionClient::new(channel)
+    }
+
+    fn check(name: &str) -> CheckPolicyRequest {
+        CheckPolicyRequest {
+            scenario_name: name.to_string(),
+            context: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_slow_server_fails_within_deadline() {
+        let mut client = start_server(Duration::from_secs(30)).await;
+
+        let start = Instant::now();
+        let status = call_with_deadline(check("slow"), Duration::from_millis(200), |request| {
+            client.check_policy(request)
+        })
+        .await
+        .unwrap_err();
+
+        assert_eq!(status.code(), Code::DeadlineExceeded);
+        assert!(start.elapsed() < Duration::from_secs(2));
+    }
+
+    #[tokio::test]
+    async fn test_fast_server_answers_within_deadline() {
+        let mut client = start_server(Duration::ZERO).await;
+
+        let response = call(check("fast"), |request| client.check_policy(request))
+            .await
+            .unwrap();
+        assert_eq!(response.into_inner().desc, "fast");
+    }
+
+    #[tokio::test]
+    async fn test_connect_to_unreachable_endpoint_fails() {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        drop(listener);
+        assert!(connect(format!("http://{}", addr)).await.is_err());
+    }
+
+    #[test]
+    fn test_default_deadline_from_settings() {
+        assert_eq!(default_deadline(), Duration::from_secs(10));
+    }
+}
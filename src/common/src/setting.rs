@@ -3,12 +3,39 @@
 * SPDX-License-Identifier: Apache-2.0
 */
 use serde::Deserialize;
+use std::collections::HashMap;
 use std::sync::OnceLock;
 static SETTINGS: OnceLock<Settings> = OnceLock::new();
 
 #[derive(Deserialize)]
 pub struct Settings {
     pub host: HostSettings,
+    #[serde(default)]
+    pub grpc: GrpcSettings,
+    #[serde(default)]
+    pub diagnostics: DiagnosticsSettings,
+    #[serde(default)]
+    pub monitoring: MonitoringSettings,
+    #[serde(default)]
+    pub dds: DdsSettings,
+    #[serde(default)]
+    pub logging: LoggingSettings,
+    #[serde(default)]
+    pub actioncontroller: ActionControllerSettings,
+    #[serde(default)]
+    pub timpani: TimpaniSettings,
+    #[serde(default)]
+    pub git: GitSettings,
+    #[serde(default)]
+    pub filtergateway: FilterGatewaySettings,
+    #[serde(default)]
+    pub etcd: EtcdSettings,
+    #[serde(default)]
+    pub heartbeat: HeartbeatSettings,
+    #[serde(default)]
+    pub statemanager: StateManagerSettings,
+    #[serde(default)]
+    pub channels: ChannelSettings,
 }
 
 #[derive(Deserialize)]
@@ -19,6 +46,481 @@ pub struct HostSettings {
     pub role: String,
 }
 
+#[derive(Deserialize)]
+pub struct GrpcSettings {
+    /// Default deadline of inter-service gRPC calls, in milliseconds
+    #[serde(default = "default_grpc_timeout_ms")]
+    pub timeout_ms: u64,
+}
+
+fn default_grpc_timeout_ms() -> u64 {
+    10_000
+}
+
+impl Default for GrpcSettings {
+    fn default() -> Self {
+        Self {
+            timeout_ms: default_grpc_timeout_ms(),
+        }
+    }
+}
+
+#[derive(Deserialize)]
+pub struct DiagnosticsSettings {
+    /// Largest size of one section of a diagnostics bundle, in bytes
+    #[serde(default = "default_section_limit_bytes")]
+    pub section_limit_bytes: usize,
+    /// Number of most recent preemption decisions put in a bundle
+    #[serde(default = "default_history_limit")]
+    pub history_limit: usize,
+}
+
+fn default_section_limit_bytes() -> usize {
+    1024 * 1024
+}
+
+fn default_history_limit() -> usize {
+    100
+}
+
+impl Default for DiagnosticsSettings {
+    fn default() -> Self {
+        Self {
+            section_limit_bytes: default_section_limit_bytes(),
+            history_limit: default_history_limit(),
+        }
+    }
+}
+
+#[derive(Deserialize)]
+pub struct MonitoringSettings {
+    /// A container restarting more than this many times within the window is flapping
+    #[serde(default = "default_flapping_restarts")]
+    pub flapping_restarts: usize,
+    /// Length of the sliding window counting restarts, in seconds
+    #[serde(default = "default_flapping_window_secs")]
+    pub flapping_window_secs: u64,
+    /// Log lines kept in memory per container
+    #[serde(default = "default_log_buffer_lines")]
+    pub log_buffer_lines: usize,
+    /// Log lines per container saved to etcd, 0 to keep them in memory only
+    #[serde(default)]
+    pub log_persist_lines: usize,
+}
+
+fn default_flapping_restarts() -> usize {
+    3
+}
+
+fn default_flapping_window_secs() -> u64 {
+    300
+}
+
+fn default_log_buffer_lines() -> usize {
+    1000
+}
+
+impl Default for MonitoringSettings {
+    fn default() -> Self {
+        Self {
+            flapping_restarts: default_flapping_restarts(),
+            flapping_window_secs: default_flapping_window_secs(),
+            log_buffer_lines: default_log_buffer_lines(),
+            log_persist_lines: 0,
+        }
+    }
+}
+
+#[derive(Deserialize)]
+pub struct DdsSettings {
+    /// DDS domain of the topics Piccolo reads and writes
+    #[serde(default)]
+    pub domain_id: i32,
+    /// JSON Lines file FilterGateway appends every received DDS sample to
+    #[serde(default)]
+    pub record_path: Option<String>,
+    /// The recording is rotated once it would grow beyond this size, in bytes
+    #[serde(default = "default_record_max_bytes")]
+    pub record_max_bytes: u64,
+    /// The recording is rotated once it is this old, in seconds; 0 disables
+    #[serde(default = "default_record_max_age_secs")]
+    pub record_max_age_secs: u64,
+    /// Number of rotated recordings kept next to the current one
+    #[serde(default = "default_record_max_files")]
+    pub record_max_files: usize,
+}
+
+fn default_record_max_bytes() -> u64 {
+    64 * 1024 * 1024
+}
+
+fn default_record_max_age_secs() -> u64 {
+    3600
+}
+
+fn default_record_max_files() -> usize {
+    5
+}
+
+impl Default for DdsSettings {
+    fn default() -> Self {
+        Self {
+            domain_id: 0,
+            record_path: None,
+            record_max_bytes: default_record_max_bytes(),
+            record_max_age_secs: default_record_max_age_secs(),
+            record_max_files: default_record_max_files(),
+        }
+    }
+}
+
+#[derive(Deserialize, Default)]
+pub struct LoggingSettings {
+    /// Output format of the daemon logs
+    #[serde(default)]
+    pub format: LogFormat,
+    /// Default level of each component, e.g. `nodeagent: debug`; components
+    /// not listed log at `info`. `PICCOLO_LOG` overrides these.
+    #[serde(default)]
+    pub levels: HashMap<String, String>,
+}
+
+#[derive(Deserialize, Default, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum LogFormat {
+    /// One human-readable line per event
+    #[default]
+    Text,
+    /// One JSON object per event, for journald or log shippers
+    Json,
+}
+
+#[derive(Deserialize)]
+pub struct ActionControllerSettings {
+    /// Time during which a repeated idempotency key gets the first outcome, in seconds
+    #[serde(default = "default_idempotency_window_secs")]
+    pub idempotency_window_secs: u64,
+    /// Number of idempotency keys remembered
+    #[serde(default = "default_idempotency_cache_size")]
+    pub idempotency_cache_size: usize,
+    /// URL of the podman REST API used for nodes of type `podman`, either
+    /// `unix:///path/to/podman.sock` or `http://host:port`
+    #[serde(default = "default_podman_url")]
+    pub podman_url: String,
+    /// Directory of the podman host that relative hostPath volume paths are
+    /// resolved against
+    #[serde(default = "default_podman_volume_base_path")]
+    pub podman_volume_base_path: String,
+    /// Runtime type of nodes not registered in etcd, e.g. `bench: podman`
+    #[serde(default)]
+    pub node_types: HashMap<String, String>,
+    /// Delay before the second restart in a row of a stopped workload, in
+    /// seconds, doubled for each next restart
+    #[serde(default = "default_restart_backoff_secs")]
+    pub restart_backoff_secs: u64,
+    /// Longest delay between two restarts of a workload, in seconds
+    #[serde(default = "default_restart_backoff_max_secs")]
+    pub restart_backoff_max_secs: u64,
+    /// Time a workload must stay up for its restarts to be forgotten, in seconds
+    #[serde(default = "default_restart_reset_secs")]
+    pub restart_reset_secs: u64,
+    /// Time past its due time a delayed action is still run after a restart
+    /// of ActionController, in seconds; 0 runs it however late it is
+    #[serde(default = "default_scheduled_expiry_secs")]
+    pub scheduled_expiry_secs: u64,
+}
+
+fn default_idempotency_window_secs() -> u64 {
+    60
+}
+
+fn default_idempotency_cache_size() -> usize {
+    1024
+}
+
+fn default_podman_url() -> String {
+    "unix:///var/run/podman/podman.sock".to_string()
+}
+
+fn default_podman_volume_base_path() -> String {
+    "/var/lib/piccolo/volumes".to_string()
+}
+
+fn default_restart_backoff_secs() -> u64 {
+    10
+}
+
+fn default_restart_backoff_max_secs() -> u64 {
+    300
+}
+
+fn default_restart_reset_secs() -> u64 {
+    600
+}
+
+fn default_scheduled_expiry_secs() -> u64 {
+    3600
+}
+
+impl Default for ActionControllerSettings {
+    fn default() -> Self {
+        Self {
+            idempotency_window_secs: default_idempotency_window_secs(),
+            idempotency_cache_size: default_idempotency_cache_size(),
+            podman_url: default_podman_url(),
+            podman_volume_base_path: default_podman_volume_base_path(),
+            node_types: HashMap::new(),
+            restart_backoff_secs: default_restart_backoff_secs(),
+            restart_backoff_max_secs: default_restart_backoff_max_secs(),
+            restart_reset_secs: default_restart_reset_secs(),
+            scheduled_expiry_secs: default_scheduled_expiry_secs(),
+        }
+    }
+}
+
+#[derive(Deserialize)]
+pub struct TimpaniSettings {
+    /// Address StateManager serves the Timpani FaultService on
+    #[serde(default = "default_timpani_address")]
+    pub address: String,
+    /// Whether faults with a duration are injected into the state machine
+    #[serde(default)]
+    pub fault_injection: bool,
+}
+
+fn default_timpani_address() -> String {
+    String::from("127.0.0.1:50053")
+}
+
+impl Default for TimpaniSettings {
+    fn default() -> Self {
+        Self {
+            address: default_timpani_address(),
+            fault_injection: false,
+        }
+    }
+}
+
+#[derive(Deserialize)]
+pub struct GitSettings {
+    /// Token sent as a bearer token when ApiServer fetches artifacts over HTTPS
+    #[serde(default)]
+    pub token: Option<String>,
+    /// Time allowed to fetch a repository, in seconds
+    #[serde(default = "default_git_timeout_secs")]
+    pub timeout_secs: u64,
+}
+
+fn default_git_timeout_secs() -> u64 {
+    120
+}
+
+impl Default for GitSettings {
+    fn default() -> Self {
+        Self {
+            token: None,
+            timeout_secs: default_git_timeout_secs(),
+        }
+    }
+}
+
+#[derive(Deserialize)]
+pub struct FilterGatewaySettings {
+    /// Interval of logging the scenario metrics, in seconds; 0 disables it
+    #[serde(default)]
+    pub metrics_log_secs: u64,
+    /// IANA timezone of the time windows of scenario conditions, e.g. `Europe/Berlin`
+    #[serde(default = "default_timezone")]
+    pub timezone: String,
+    /// How the samples of each DDS topic reach the scenario filters, by
+    /// topic name; topics left out are queued
+    #[serde(default)]
+    pub ingestion: HashMap<String, Ingestion>,
+}
+
+/// How the samples of a DDS topic reach the scenario filters
+#[derive(Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Ingestion {
+    /// Every sample is evaluated; the listener waits while the queue is full
+    #[default]
+    Queue,
+    /// Only the newest sample is evaluated; older ones waiting are dropped
+    Latest,
+}
+
+fn default_timezone() -> String {
+    "UTC".to_string()
+}
+
+impl Default for FilterGatewaySettings {
+    fn default() -> Self {
+        Self {
+            metrics_log_secs: 0,
+            timezone: default_timezone(),
+            ingestion: HashMap::new(),
+        }
+    }
+}
+
+#[derive(Deserialize, Default)]
+pub struct EtcdSettings {
+    /// Namespace of every key, so several clusters can share one store;
+    /// empty keeps the keys unprefixed
+    #[serde(default)]
+    pub prefix: String,
+    /// Whether keys missing from the namespace are read without it, while
+    /// the data of a cluster is migrated into its namespace
+    #[serde(default)]
+    pub legacy_fallback: bool,
+}
+
+#[derive(Deserialize)]
+pub struct HeartbeatSettings {
+    /// Age of the last heartbeat after which a node is marked offline, in seconds
+    #[serde(default = "default_offline_after_secs")]
+    pub offline_after_secs: u64,
+    /// Age of the last heartbeat after which a node is removed, in seconds
+    #[serde(default = "default_remove_after_secs")]
+    pub remove_after_secs: u64,
+    /// Interval of the check for stale nodes, in seconds; 0 disables it
+    #[serde(default)]
+    pub check_interval_secs: u64,
+}
+
+fn default_offline_after_secs() -> u64 {
+    90
+}
+
+fn default_remove_after_secs() -> u64 {
+    24 * 60 * 60
+}
+
+impl Default for HeartbeatSettings {
+    fn default() -> Self {
+        Self {
+            offline_after_secs: default_offline_after_secs(),
+            remove_after_secs: default_remove_after_secs(),
+            check_interval_secs: 0,
+        }
+    }
+}
+
+#[derive(Deserialize)]
+pub struct StateManagerSettings {
+    /// Whether committed state transitions are published on DDS
+    #[serde(default)]
+    pub dds_events: bool,
+    /// DDS topic the state transition events are published on
+    #[serde(default = "default_dds_events_topic")]
+    pub dds_events_topic: String,
+    /// Whether the events are sent reliably rather than best effort
+    #[serde(default = "default_dds_events_reliable")]
+    pub dds_events_reliable: bool,
+    /// Events kept by the writer for late or slow readers
+    #[serde(default = "default_dds_events_history_depth")]
+    pub dds_events_history_depth: u32,
+}
+
+fn default_dds_events_topic() -> String {
+    "PiccoloStateEvent".to_string()
+}
+
+fn default_dds_events_reliable() -> bool {
+    true
+}
+
+fn default_dds_events_history_depth() -> u32 {
+    16
+}
+
+impl Default for StateManagerSettings {
+    fn default() -> Self {
+        Self {
+            dds_events: false,
+            dds_events_topic: default_dds_events_topic(),
+            dds_events_reliable: default_dds_events_reliable(),
+            dds_events_history_depth: default_dds_events_history_depth(),
+        }
+    }
+}
+
+#[derive(Deserialize)]
+pub struct ChannelSettings {
+    /// YAML requests from the gRPC server to the manager of NodeAgent
+    #[serde(default = "default_channel_capacity")]
+    pub nodeagent_yaml: usize,
+    /// Container lists from the collection loop of NodeAgent to the task
+    /// sending them to MonitoringServer; lists are dropped while it is full
+    #[serde(default = "default_container_queue_capacity")]
+    pub nodeagent_container: usize,
+    /// Scenarios from the gRPC server to the manager of FilterGateway
+    #[serde(default = "default_channel_capacity")]
+    pub filtergateway_scenario: usize,
+    /// DDS samples from the listeners to the manager of FilterGateway
+    #[serde(default = "default_dds_channel_capacity")]
+    pub filtergateway_dds: usize,
+    /// Container lists from the gRPC server to the manager of StateManager
+    #[serde(default = "default_channel_capacity")]
+    pub statemanager_container: usize,
+    /// State change requests to the manager of StateManager
+    #[serde(default = "default_channel_capacity")]
+    pub statemanager_state_change: usize,
+    /// Acknowledgements to the manager of StateManager
+    #[serde(default = "default_channel_capacity")]
+    pub statemanager_ack: usize,
+    /// Container lists from the gRPC server to the manager of MonitoringServer
+    #[serde(default = "default_channel_capacity")]
+    pub monitoringserver_container: usize,
+    /// Node information from the gRPC server to the manager of MonitoringServer
+    #[serde(default = "default_channel_capacity")]
+    pub monitoringserver_node: usize,
+    /// Stress metrics from the gRPC server to the manager of MonitoringServer
+    #[serde(default = "default_stress_channel_capacity")]
+    pub monitoringserver_stress: usize,
+    /// Time a channel stays full before a warning is logged, in seconds; 0 disables it
+    #[serde(default = "default_full_warning_secs")]
+    pub full_warning_secs: u64,
+}
+
+fn default_channel_capacity() -> usize {
+    100
+}
+
+fn default_dds_channel_capacity() -> usize {
+    10
+}
+
+fn default_stress_channel_capacity() -> usize {
+    16
+}
+
+fn default_container_queue_capacity() -> usize {
+    10
+}
+
+fn default_full_warning_secs() -> u64 {
+    5
+}
+
+impl Default for ChannelSettings {
+    fn default() -> Self {
+        Self {
+            nodeagent_yaml: default_channel_capacity(),
+            nodeagent_container: default_container_queue_capacity(),
+            filtergateway_scenario: default_channel_capacity(),
+            filtergateway_dds: default_dds_channel_capacity(),
+            statemanager_container: default_channel_capacity(),
+            statemanager_state_change: default_channel_capacity(),
+            statemanager_ack: default_channel_capacity(),
+            monitoringserver_container: default_channel_capacity(),
+            monitoringserver_node: default_channel_capacity(),
+            monitoringserver_stress: default_stress_channel_capacity(),
+            full_warning_secs: default_full_warning_secs(),
+        }
+    }
+}
+
 fn parse_settings_yaml() -> Settings {
     let default_settings: Settings = Settings {
         host: HostSettings {
@@ -27,6 +529,19 @@ fn parse_settings_yaml() -> Settings {
             r#type: String::from("nodeagent"),
             role: String::from("master"),
         },
+        grpc: GrpcSettings::default(),
+        diagnostics: DiagnosticsSettings::default(),
+        monitoring: MonitoringSettings::default(),
+        dds: DdsSettings::default(),
+        logging: LoggingSettings::default(),
+        actioncontroller: ActionControllerSettings::default(),
+        timpani: TimpaniSettings::default(),
+        git: GitSettings::default(),
+        filtergateway: FilterGatewaySettings::default(),
+        etcd: EtcdSettings::default(),
+        heartbeat: HeartbeatSettings::default(),
+        statemanager: StateManagerSettings::default(),
+        channels: ChannelSettings::default(),
     };
 
     let settings = config::Config::builder()
@@ -58,6 +573,87 @@ mod tests {
         assert_eq!(settings.host.name, "HPC");
         assert_eq!(settings.host.ip, "0.0.0.0");
         assert_eq!(settings.host.r#type, "nodeagent");
+        assert_eq!(settings.grpc.timeout_ms, 10_000);
+        assert_eq!(settings.diagnostics.section_limit_bytes, 1024 * 1024);
+        assert_eq!(settings.diagnostics.history_limit, 100);
+        assert_eq!(settings.monitoring.flapping_restarts, 3);
+        assert_eq!(settings.monitoring.flapping_window_secs, 300);
+        assert_eq!(settings.monitoring.log_buffer_lines, 1000);
+        assert_eq!(settings.monitoring.log_persist_lines, 0);
+        assert_eq!(settings.dds.domain_id, 0);
+        assert_eq!(settings.dds.record_path, None);
+        assert_eq!(settings.dds.record_max_bytes, 64 * 1024 * 1024);
+        assert_eq!(settings.dds.record_max_age_secs, 3600);
+        assert_eq!(settings.dds.record_max_files, 5);
+        assert_eq!(settings.logging.format, LogFormat::Text);
+        assert!(settings.logging.levels.is_empty());
+        assert_eq!(settings.actioncontroller.idempotency_window_secs, 60);
+        assert_eq!(settings.actioncontroller.idempotency_cache_size, 1024);
+        assert_eq!(
+            settings.actioncontroller.podman_url,
+            "unix:///var/run/podman/podman.sock"
+        );
+        assert_eq!(
+            settings.actioncontroller.podman_volume_base_path,
+            "/var/lib/piccolo/volumes"
+        );
+        assert!(settings.actioncontroller.node_types.is_empty());
+        assert_eq!(settings.actioncontroller.restart_backoff_secs, 10);
+        assert_eq!(settings.actioncontroller.restart_backoff_max_secs, 300);
+        assert_eq!(settings.actioncontroller.restart_reset_secs, 600);
+        assert_eq!(settings.actioncontroller.scheduled_expiry_secs, 3600);
+        assert_eq!(settings.timpani.address, "127.0.0.1:50053");
+        assert!(!settings.timpani.fault_injection);
+        assert_eq!(settings.git.token, None);
+        assert_eq!(settings.git.timeout_secs, 120);
+        assert_eq!(settings.filtergateway.metrics_log_secs, 0);
+        assert_eq!(settings.filtergateway.timezone, "UTC");
+        assert!(settings.filtergateway.ingestion.is_empty());
+        assert_eq!(settings.etcd.prefix, "");
+        assert!(!settings.etcd.legacy_fallback);
+        assert_eq!(settings.heartbeat.offline_after_secs, 90);
+        assert_eq!(settings.heartbeat.remove_after_secs, 86400);
+        assert_eq!(settings.heartbeat.check_interval_secs, 0);
+        assert!(!settings.statemanager.dds_events);
+        assert_eq!(settings.statemanager.dds_events_topic, "PiccoloStateEvent");
+        assert!(settings.statemanager.dds_events_reliable);
+        assert_eq!(settings.statemanager.dds_events_history_depth, 16);
+        assert_eq!(settings.channels.nodeagent_yaml, 100);
+        assert_eq!(settings.channels.nodeagent_container, 10);
+        assert_eq!(settings.channels.filtergateway_scenario, 100);
+        assert_eq!(settings.channels.filtergateway_dds, 10);
+        assert_eq!(settings.channels.statemanager_container, 100);
+        assert_eq!(settings.channels.statemanager_state_change, 100);
+        assert_eq!(settings.channels.statemanager_ack, 100);
+        assert_eq!(settings.channels.monitoringserver_container, 100);
+        assert_eq!(settings.channels.monitoringserver_node, 100);
+        assert_eq!(settings.channels.monitoringserver_stress, 16);
+        assert_eq!(settings.channels.full_warning_secs, 5);
+    }
+
+    // Test that a channel left out of the settings keeps its default capacity
+    #[test]
+    fn test_channel_settings_partial_override() {
+        let channels: ChannelSettings =
+            serde_yaml::from_str("statemanager_ack: 512\nfull_warning_secs: 0\n").unwrap();
+        assert_eq!(channels.statemanager_ack, 512);
+        assert_eq!(channels.full_warning_secs, 0);
+        assert_eq!(channels.statemanager_container, 100);
+        assert_eq!(channels.monitoringserver_stress, 16);
+    }
+
+    // Test the ingestion policy of DDS topics
+    #[test]
+    fn test_filtergateway_ingestion() {
+        let filtergateway: FilterGatewaySettings =
+            serde_yaml::from_str("ingestion:\n  VehicleSpeed: latest\n  DoorState: queue\n")
+                .unwrap();
+        assert_eq!(filtergateway.ingestion["VehicleSpeed"], Ingestion::Latest);
+        assert_eq!(filtergateway.ingestion["DoorState"], Ingestion::Queue);
+        assert_eq!(filtergateway.timezone, "UTC");
+        assert!(
+            serde_yaml::from_str::<FilterGatewaySettings>("ingestion:\n  A: newest\n").is_err()
+        );
     }
 
     // Guest 설정 테스트 제거
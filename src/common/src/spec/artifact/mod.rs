@@ -6,6 +6,7 @@ pub mod model;
 pub mod network;
 pub mod node;
 pub mod package;
+pub mod policy;
 pub mod scenario;
 pub mod schedule;
 pub mod volume;
@@ -75,6 +76,14 @@ pub struct Schedule {
     spec: Option<Vec<schedule::ScheduleSpec>>,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Policy {
+    apiVersion: String,
+    kind: String,
+    metadata: MetaData,
+    spec: policy::PolicySpec,
+}
+
 //Unit Test Cases
 #[cfg(test)]
 mod tests {
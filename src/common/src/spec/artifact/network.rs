@@ -4,6 +4,9 @@
 */
 use super::Artifact;
 use super::Network;
+use std::fmt;
+use std::net::IpAddr;
+use std::str::FromStr;
 
 impl Artifact for Network {
     fn get_name(&self) -> String {
@@ -11,21 +14,173 @@ impl Artifact for Network {
     }
 }
 
+/// Model annotation naming the Network its pod joins
+pub const NETWORK_ANNOTATION: &str = "io.piccolo.annotations.network";
+
 impl Network {
     pub fn get_spec(&self) -> &Option<NetworkSpec> {
         &self.spec
     }
+
+    /// Check that the spec describes a network podman can create
+    pub fn validate(&self) -> Result<(), String> {
+        match &self.spec {
+            Some(spec) => spec
+                .validate()
+                .map_err(|e| format!("network '{}': {}", self.metadata.name, e)),
+            None => Ok(()),
+        }
+    }
+
+    /// Subnet of the network, if it sets a valid one
+    pub fn get_subnet(&self) -> Option<Subnet> {
+        self.spec.as_ref()?.subnet.as_ref()?.parse().ok()
+    }
 }
 
-#[derive(Debug, Clone, serde::Serialize, serde::Deserialize, PartialEq)]
+/// Podman network shared by the pods of a package
+#[derive(Debug, Clone, Default, serde::Serialize, serde::Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
 pub struct NetworkSpec {
     dummy: Option<String>,
+    #[serde(default)]
+    driver: NetworkDriver,
+    /// CIDR of the network, e.g. `10.89.1.0/24`. Podman picks one if unset.
+    subnet: Option<String>,
+    gateway: Option<String>,
+    dns: Option<DnsConfig>,
+}
+
+#[derive(Debug, Clone, Copy, Default, serde::Serialize, serde::Deserialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum NetworkDriver {
+    #[default]
+    Bridge,
+    Macvlan,
+    Ipvlan,
+}
+
+impl NetworkDriver {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            NetworkDriver::Bridge => "bridge",
+            NetworkDriver::Macvlan => "macvlan",
+            NetworkDriver::Ipvlan => "ipvlan",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, serde::Serialize, serde::Deserialize, PartialEq)]
+pub struct DnsConfig {
+    /// Name servers the network's resolver forwards to
+    #[serde(default)]
+    pub servers: Vec<String>,
 }
 
 impl NetworkSpec {
     pub fn get_network(&self) -> &Option<String> {
         &self.dummy
     }
+
+    pub fn get_driver(&self) -> NetworkDriver {
+        self.driver
+    }
+
+    pub fn get_subnet(&self) -> Option<&str> {
+        self.subnet.as_deref()
+    }
+
+    pub fn get_gateway(&self) -> Option<&str> {
+        self.gateway.as_deref()
+    }
+
+    pub fn get_dns(&self) -> Option<&DnsConfig> {
+        self.dns.as_ref()
+    }
+
+    /// Check the subnet, gateway and DNS addresses
+    pub fn validate(&self) -> Result<(), String> {
+        let subnet = self
+            .subnet
+            .as_deref()
+            .map(str::parse::<Subnet>)
+            .transpose()?;
+
+        if let Some(gateway) = &self.gateway {
+            let Some(subnet) = &subnet else {
+                return Err("`gateway` requires a `subnet`".to_string());
+            };
+            let address: IpAddr = gateway
+                .parse()
+                .map_err(|_| format!("invalid gateway '{}'", gateway))?;
+            if !subnet.contains(address) {
+                return Err(format!("gateway {} is outside {}", gateway, subnet));
+            }
+        }
+
+        for server in self.dns.iter().flat_map(|dns| &dns.servers) {
+            server
+                .parse::<IpAddr>()
+                .map_err(|_| format!("invalid DNS server '{}'", server))?;
+        }
+        Ok(())
+    }
+}
+
+/// IPv4 or IPv6 network in CIDR notation
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Subnet {
+    address: IpAddr,
+    prefix: u8,
+}
+
+impl Subnet {
+    fn bits(address: IpAddr) -> (u128, u8) {
+        match address {
+            IpAddr::V4(v4) => (u32::from(v4) as u128, 32),
+            IpAddr::V6(v6) => (u128::from(v6), 128),
+        }
+    }
+
+    /// Network part of `address` for a prefix of `prefix` bits
+    fn network(address: IpAddr, prefix: u8) -> u128 {
+        let (bits, width) = Self::bits(address);
+        let host_bits = u32::from(width - prefix);
+        bits.checked_shr(host_bits).unwrap_or(0)
+    }
+
+    pub fn contains(&self, address: IpAddr) -> bool {
+        self.address.is_ipv4() == address.is_ipv4()
+            && Self::network(self.address, self.prefix) == Self::network(address, self.prefix)
+    }
+
+    /// Whether the two subnets share at least one address
+    pub fn overlaps(&self, other: &Subnet) -> bool {
+        let prefix = self.prefix.min(other.prefix);
+        self.address.is_ipv4() == other.address.is_ipv4()
+            && Self::network(self.address, prefix) == Self::network(other.address, prefix)
+    }
+}
+
+impl FromStr for Subnet {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || format!("invalid subnet '{}'", s);
+        let (address, prefix) = s.trim().split_once('/').ok_or_else(invalid)?;
+        let address: IpAddr = address.parse().map_err(|_| invalid())?;
+        let prefix: u8 = prefix.parse().map_err(|_| invalid())?;
+        if prefix > Self::bits(address).1 {
+            return Err(invalid());
+        }
+        Ok(Subnet { address, prefix })
+    }
+}
+
+impl fmt::Display for Subnet {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}/{}", self.address, self.prefix)
+    }
 }
 
 //Unit Test Cases
@@ -46,6 +201,7 @@ mod tests {
             },
             spec: dummy_value.map(|v| NetworkSpec {
                 dummy: Some(v.to_string()),
+                ..Default::default()
             }),
         }
     }
@@ -84,6 +240,7 @@ mod tests {
         let dummy_value = "test-dummy-value";
         let network_spec = NetworkSpec {
             dummy: Some(dummy_value.to_string()),
+            ..Default::default()
         };
 
         // Test NetworkSpec's get_network method
@@ -92,7 +249,7 @@ mod tests {
 
     #[test]
     fn test_network_spec_get_network_none() {
-        let network_spec = NetworkSpec { dummy: None };
+        let network_spec = NetworkSpec::default();
 
         // Test NetworkSpec's get_network when dummy is None
         assert_eq!(network_spec.get_network(), &None);
@@ -135,4 +292,78 @@ mod tests {
         assert!(debug_output.contains("debug-network"));
         assert!(debug_output.contains("debug-value"));
     }
+
+    fn parse_network(yaml_spec: &str) -> Network {
+        let yaml = format!(
+            "apiVersion: v1\nkind: Network\nmetadata:\n  name: bms-net\nspec:\n{}",
+            yaml_spec
+        );
+        serde_yaml::from_str(&yaml).unwrap()
+    }
+
+    #[test]
+    fn test_network_spec_from_yaml() {
+        let network = parse_network(
+            "  driver: macvlan\n  subnet: 10.89.1.0/24\n  gateway: 10.89.1.1\n  dns:\n    servers: [10.89.1.53]\n",
+        );
+        assert!(network.validate().is_ok());
+
+        let spec = network.get_spec().as_ref().unwrap();
+        assert_eq!(spec.get_driver(), NetworkDriver::Macvlan);
+        assert_eq!(spec.get_subnet(), Some("10.89.1.0/24"));
+        assert_eq!(spec.get_gateway(), Some("10.89.1.1"));
+        assert_eq!(spec.get_dns().unwrap().servers, vec!["10.89.1.53"]);
+        assert_eq!(network.get_subnet(), Some("10.89.1.0/24".parse().unwrap()));
+
+        // The placeholder spec of older artifacts still parses
+        let network = parse_network("  dummy: network123\n");
+        assert!(network.validate().is_ok());
+        assert_eq!(
+            network.get_spec().as_ref().unwrap().get_driver(),
+            NetworkDriver::Bridge
+        );
+        assert_eq!(network.get_subnet(), None);
+    }
+
+    #[test]
+    fn test_invalid_network_specs() {
+        for (spec, error) in [
+            ("  subnet: 10.89.1.0\n", "invalid subnet '10.89.1.0'"),
+            ("  subnet: 10.89.1.0/33\n", "invalid subnet"),
+            ("  gateway: 10.89.1.1\n", "`gateway` requires a `subnet`"),
+            (
+                "  subnet: 10.89.1.0/24\n  gateway: 10.89.2.1\n",
+                "gateway 10.89.2.1 is outside 10.89.1.0/24",
+            ),
+            (
+                "  dns:\n    servers: [resolver]\n",
+                "invalid DNS server 'resolver'",
+            ),
+        ] {
+            let err = parse_network(spec).validate().unwrap_err();
+            assert!(err.starts_with("network 'bms-net': "), "{}", err);
+            assert!(err.contains(error), "{}: {}", spec, err);
+        }
+
+        let yaml =
+            "apiVersion: v1\nkind: Network\nmetadata:\n  name: n\nspec:\n  driver: overlay\n";
+        assert!(serde_yaml::from_str::<Network>(yaml).is_err());
+    }
+
+    #[test]
+    fn test_subnet_overlaps() {
+        let subnet = |s: &str| s.parse::<Subnet>().unwrap();
+
+        assert!(subnet("10.89.0.0/16").overlaps(&subnet("10.89.1.0/24")));
+        assert!(subnet("10.89.1.0/24").overlaps(&subnet("10.89.0.0/16")));
+        assert!(subnet("10.89.1.128/25").overlaps(&subnet("10.89.1.0/24")));
+        assert!(!subnet("10.89.1.0/24").overlaps(&subnet("10.89.2.0/24")));
+        assert!(subnet("0.0.0.0/0").overlaps(&subnet("192.168.0.0/16")));
+        assert!(subnet("fd00::/64").overlaps(&subnet("fd00::/48")));
+        assert!(!subnet("fd00::/64").overlaps(&subnet("10.89.1.0/24")));
+
+        assert!(subnet("10.89.1.0/24").contains("10.89.1.254".parse().unwrap()));
+        assert!(!subnet("10.89.1.0/24").contains("fd00::1".parse().unwrap()));
+        assert_eq!(subnet(" 10.89.1.0/24 ").to_string(), "10.89.1.0/24");
+    }
 }
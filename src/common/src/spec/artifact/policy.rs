@@ -0,0 +1,185 @@
+/*
+* SPDX-FileCopyrightText: Copyright 2024 LG Electronics Inc.
+* SPDX-License-Identifier: Apache-2.0
+*/
+use super::Artifact;
+use super::Policy;
+
+/// Scenario annotation holding its ASIL level (e.g. "B" or "ASIL-B")
+pub const ASIL_ANNOTATION: &str = "io.piccolo.annotations.asil-level";
+
+impl Artifact for Policy {
+    fn get_name(&self) -> String {
+        self.metadata.name.clone()
+    }
+}
+
+impl Policy {
+    pub fn get_spec(&self) -> &PolicySpec {
+        &self.spec
+    }
+}
+
+/// Admission rule for scenarios
+///
+/// A rule applies to a scenario when every condition it sets matches.
+/// Among the rules that apply, the one with the highest priority decides.
+#[derive(Debug, Clone, Default, serde::Serialize, serde::Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct PolicySpec {
+    #[serde(default)]
+    pub priority: i32,
+    #[serde(default)]
+    pub action: PolicyAction,
+    /// Scenario names, a trailing `*` matches any suffix. Empty matches all.
+    #[serde(default)]
+    pub scenarios: Vec<String>,
+    /// Node types the scenario deploys to. Empty matches all.
+    #[serde(default)]
+    pub node_types: Vec<String>,
+    pub time_window: Option<TimeWindow>,
+    /// Scenarios allowed to run at once, counting the one being admitted
+    pub max_concurrent: Option<u32>,
+    /// Lowest ASIL level a scenario needs to be allowed
+    pub required_asil: Option<AsilLevel>,
+}
+
+#[derive(Debug, Clone, Copy, Default, serde::Serialize, serde::Deserialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum PolicyAction {
+    #[default]
+    Allow,
+    Deny,
+}
+
+/// Time of day in UTC, "HH:MM". The window may cross midnight.
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize, PartialEq)]
+pub struct TimeWindow {
+    pub start: String,
+    pub end: String,
+}
+
+impl TimeWindow {
+    /// Whether `minute` (minutes since midnight) falls in the window
+    ///
+    /// Returns `None` when start or end is not a valid "HH:MM" time.
+    pub fn contains(&self, minute: u32) -> Option<bool> {
+        let start = parse_minute(&self.start)?;
+        let end = parse_minute(&self.end)?;
+        Some(if start <= end {
+            start <= minute && minute < end
+        } else {
+            minute >= start || minute < end
+        })
+    }
+}
+
+fn parse_minute(time: &str) -> Option<u32> {
+    let (hour, minute) = time.trim().split_once(':')?;
+    let (hour, minute): (u32, u32) = (hour.parse().ok()?, minute.parse().ok()?);
+    (hour < 24 && minute < 60).then_some(hour * 60 + minute)
+}
+
+#[derive(
+    Debug, Clone, Copy, serde::Serialize, serde::Deserialize, PartialEq, Eq, PartialOrd, Ord,
+)]
+pub enum AsilLevel {
+    QM,
+    A,
+    B,
+    C,
+    D,
+}
+
+impl std::str::FromStr for AsilLevel {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let level = s.trim().to_uppercase();
+        match level.strip_prefix("ASIL-").unwrap_or(&level) {
+            "QM" => Ok(AsilLevel::QM),
+            "A" => Ok(AsilLevel::A),
+            "B" => Ok(AsilLevel::B),
+            "C" => Ok(AsilLevel::C),
+            "D" => Ok(AsilLevel::D),
+            _ => Err(format!("unknown ASIL level '{}'", s)),
+        }
+    }
+}
+
+//Unit Test Cases
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_policy_from_yaml() {
+        let yaml = r#"
+apiVersion: v1
+kind: Policy
+metadata:
+  name: night-limit
+spec:
+  priority: 10
+  action: deny
+  scenarios: ["infotainment-*"]
+  nodeTypes: ["nodeagent"]
+  timeWindow:
+    start: "22:00"
+    end: "06:00"
+  maxConcurrent: 2
+  requiredAsil: B
+"#;
+        let policy: Policy = serde_yaml::from_str(yaml).unwrap();
+        let spec = policy.get_spec();
+        assert_eq!(policy.get_name(), "night-limit");
+        assert_eq!(spec.priority, 10);
+        assert_eq!(spec.action, PolicyAction::Deny);
+        assert_eq!(spec.scenarios, vec!["infotainment-*"]);
+        assert_eq!(spec.node_types, vec!["nodeagent"]);
+        assert_eq!(spec.max_concurrent, Some(2));
+        assert_eq!(spec.required_asil, Some(AsilLevel::B));
+    }
+
+    #[test]
+    fn test_policy_defaults() {
+        let yaml = "apiVersion: v1\nkind: Policy\nmetadata:\n  name: p\nspec: {}\n";
+        let policy: Policy = serde_yaml::from_str(yaml).unwrap();
+        assert_eq!(policy.get_spec(), &PolicySpec::default());
+    }
+
+    #[test]
+    fn test_time_window_contains() {
+        let day = TimeWindow {
+            start: "08:00".to_string(),
+            end: "18:30".to_string(),
+        };
+        assert_eq!(day.contains(8 * 60), Some(true));
+        assert_eq!(day.contains(18 * 60 + 30), Some(false));
+        assert_eq!(day.contains(60), Some(false));
+
+        let night = TimeWindow {
+            start: "22:00".to_string(),
+            end: "06:00".to_string(),
+        };
+        assert_eq!(night.contains(23 * 60), Some(true));
+        assert_eq!(night.contains(5 * 60), Some(true));
+        assert_eq!(night.contains(12 * 60), Some(false));
+
+        let invalid = TimeWindow {
+            start: "25:00".to_string(),
+            end: "06:00".to_string(),
+        };
+        assert_eq!(invalid.contains(0), None);
+    }
+
+    #[test]
+    fn test_asil_level_parse_and_order() {
+        assert_eq!("b".parse::<AsilLevel>(), Ok(AsilLevel::B));
+        assert_eq!("ASIL-D".parse::<AsilLevel>(), Ok(AsilLevel::D));
+        assert_eq!("QM".parse::<AsilLevel>(), Ok(AsilLevel::QM));
+        assert!("E".parse::<AsilLevel>().is_err());
+        assert!(AsilLevel::QM < AsilLevel::A);
+        assert!(AsilLevel::C < AsilLevel::D);
+    }
+}
@@ -4,6 +4,13 @@
 */
 use super::Artifact;
 use super::Scenario;
+use std::collections::{HashMap, HashSet};
+
+/// Scenario annotation naming the node type its models need (`cloud` or `vehicle`)
+pub const NODE_TYPE_ANNOTATION: &str = "io.piccolo.annotations.node-type";
+/// Scenario annotation naming the node role its models need
+/// (`master`, `nodeagent` or `bluechi`)
+pub const NODE_ROLE_ANNOTATION: &str = "io.piccolo.annotations.node-role";
 
 impl Artifact for Scenario {
     fn get_name(&self) -> String {
@@ -23,6 +30,77 @@ impl Scenario {
     pub fn get_targets(&self) -> String {
         self.spec.target.clone()
     }
+
+    /// Priority used to arbitrate between competing scenarios, higher wins
+    ///
+    /// Scenarios without a priority never preempt and are never preempted.
+    pub fn get_priority(&self) -> Option<i32> {
+        self.spec.priority
+    }
+
+    pub fn get_annotation(&self, key: &str) -> Option<String> {
+        self.metadata.annotations.as_ref()?.get(key).cloned()
+    }
+
+    /// Scenarios that must be activated before this one
+    pub fn get_depends_on(&self) -> &Vec<String> {
+        &self.spec.depends_on
+    }
+
+    /// Time the action waits once the scenario is triggered, if it is delayed
+    pub fn get_delay(&self) -> Option<std::time::Duration> {
+        self.spec.delay_ms.map(std::time::Duration::from_millis)
+    }
+}
+
+/// Scenarios in the order they are activated, dependencies first
+///
+/// # Arguments
+///
+/// * `dependencies` - Scenarios each scenario depends on, by scenario name
+/// * `roots` - Scenarios to activate
+///
+/// # Returns
+///
+/// * `Ok(Vec<String>)` - `roots` and all their dependencies, each listed
+///   once and after the scenarios it depends on. Scenarios missing from
+///   `dependencies` are taken as depending on nothing.
+/// * `Err(String)` - The scenarios along a dependency cycle
+pub fn dependency_order(
+    dependencies: &HashMap<String, Vec<String>>,
+    roots: &[String],
+) -> Result<Vec<String>, String> {
+    fn visit(
+        scenario: &str,
+        dependencies: &HashMap<String, Vec<String>>,
+        path: &mut Vec<String>,
+        done: &mut HashSet<String>,
+        order: &mut Vec<String>,
+    ) -> Result<(), String> {
+        if done.contains(scenario) {
+            return Ok(());
+        }
+        if let Some(start) = path.iter().position(|s| s == scenario) {
+            let mut cycle = path[start..].to_vec();
+            cycle.push(scenario.to_string());
+            return Err(format!("dependency cycle: {}", cycle.join(" -> ")));
+        }
+        path.push(scenario.to_string());
+        for dependency in dependencies.get(scenario).into_iter().flatten() {
+            visit(dependency, dependencies, path, done, order)?;
+        }
+        path.pop();
+        done.insert(scenario.to_string());
+        order.push(scenario.to_string());
+        Ok(())
+    }
+
+    let mut done = HashSet::new();
+    let mut order = Vec::new();
+    for root in roots {
+        visit(root, dependencies, &mut Vec::new(), &mut done, &mut order)?;
+    }
+    Ok(order)
 }
 
 #[derive(Debug, serde::Serialize, serde::Deserialize, PartialEq)]
@@ -30,6 +108,13 @@ pub struct ScenarioSpec {
     condition: Option<Condition>,
     action: String,
     target: String,
+    priority: Option<i32>,
+    /// Scenarios activated before this one
+    #[serde(default, rename = "dependsOn")]
+    depends_on: Vec<String>,
+    /// Time between the scenario being triggered and its action being run
+    #[serde(default, rename = "delayMs", skip_serializing_if = "Option::is_none")]
+    delay_ms: Option<u64>,
 }
 
 #[derive(Debug, serde::Serialize, serde::Deserialize, PartialEq)]
@@ -49,10 +134,18 @@ enum ScenarioState {
 }
 
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
 pub struct Condition {
     express: String,
     value: String,
     operands: Operand,
+    /// Time the condition must hold before it is considered met
+    debounce_ms: Option<u64>,
+    /// Threshold the value must cross back over before the condition is
+    /// considered no longer met
+    exit_value: Option<String>,
+    /// Local time window outside of which the condition is never met
+    window: Option<TimeWindow>,
 }
 
 impl Condition {
@@ -71,6 +164,44 @@ impl Condition {
     pub fn get_operand_name(&self) -> String {
         self.operands.name.clone()
     }
+
+    pub fn get_debounce_ms(&self) -> Option<u64> {
+        self.debounce_ms
+    }
+
+    pub fn get_exit_value(&self) -> Option<String> {
+        self.exit_value.clone()
+    }
+
+    pub fn get_window(&self) -> Option<TimeWindow> {
+        self.window.clone()
+    }
+}
+
+/// Daily time window, in the timezone set in FilterGateway settings
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize, PartialEq)]
+pub struct TimeWindow {
+    /// Local time the window opens, `HH:MM`
+    start: String,
+    /// Local time the window closes, `HH:MM`; earlier than `start` when
+    /// the window spans midnight
+    end: String,
+    /// Days the window opens on, e.g. `[Mon, Fri]`; every day when unset
+    days: Option<Vec<String>>,
+}
+
+impl TimeWindow {
+    pub fn get_start(&self) -> String {
+        self.start.clone()
+    }
+
+    pub fn get_end(&self) -> String {
+        self.end.clone()
+    }
+
+    pub fn get_days(&self) -> Vec<String> {
+        self.days.clone().unwrap_or_default()
+    }
 }
 
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize, PartialEq)]
@@ -104,9 +235,15 @@ mod tests {
                         name: "test-pod".to_string(),
                         value: "status".to_string(),
                     },
+                    debounce_ms: None,
+                    exit_value: None,
+                    window: None,
                 }),
                 action: "start".to_string(),
                 target: "model-1".to_string(),
+                priority: None,
+                depends_on: vec![],
+                delay_ms: None,
             },
             status: Some(ScenarioStatus {
                 state: ScenarioState::None,
@@ -143,6 +280,81 @@ mod tests {
         assert_eq!(scenario.get_targets(), "model-1");
     }
 
+    #[test]
+    fn test_get_priority() {
+        assert_eq!(create_test_scenario().get_priority(), None);
+
+        let yaml = r#"
+apiVersion: v1
+kind: Scenario
+metadata:
+  name: parking-assist
+spec:
+  condition:
+  action: launch
+  target: parking-assist
+  priority: 10
+"#;
+        let scenario: Scenario = serde_yaml::from_str(yaml).unwrap();
+        assert_eq!(scenario.get_priority(), Some(10));
+    }
+
+    #[test]
+    fn test_condition_stability_settings() {
+        let condition = create_test_scenario().get_conditions().unwrap();
+        assert_eq!(condition.get_debounce_ms(), None);
+        assert_eq!(condition.get_exit_value(), None);
+
+        let yaml = r#"
+express: gt
+value: "80"
+operands:
+  type: DDS
+  name: speed
+  value: VehicleSpeed
+debounceMs: 300
+exitValue: "75"
+"#;
+        let condition: Condition = serde_yaml::from_str(yaml).unwrap();
+        assert_eq!(condition.get_debounce_ms(), Some(300));
+        assert_eq!(condition.get_exit_value(), Some("75".to_string()));
+        assert_eq!(condition.get_window(), None);
+    }
+
+    #[test]
+    fn test_condition_window() {
+        let yaml = r#"
+express: eq
+value: open
+operands:
+  type: DDS
+  name: status
+  value: ChargingFlapStatus
+window:
+  start: "22:00"
+  end: "06:00"
+  days: [Mon, Tue]
+"#;
+        let condition: Condition = serde_yaml::from_str(yaml).unwrap();
+        let window = condition.get_window().unwrap();
+        assert_eq!(window.get_start(), "22:00");
+        assert_eq!(window.get_end(), "06:00");
+        assert_eq!(window.get_days(), vec!["Mon", "Tue"]);
+    }
+
+    #[test]
+    fn test_get_annotation() {
+        let mut scenario = create_test_scenario();
+        assert_eq!(scenario.get_annotation("asil"), None);
+
+        scenario.metadata.annotations = Some(std::collections::HashMap::from([(
+            "asil".to_string(),
+            "B".to_string(),
+        )]));
+        assert_eq!(scenario.get_annotation("asil"), Some("B".to_string()));
+        assert_eq!(scenario.get_annotation("other"), None);
+    }
+
     #[test]
     fn test_scenario_without_conditions() {
         let scenario = Scenario {
@@ -157,6 +369,9 @@ mod tests {
                 condition: None,
                 action: "stop".to_string(),
                 target: "model-2".to_string(),
+                priority: None,
+                depends_on: vec![],
+                delay_ms: None,
             },
             status: None,
         };
@@ -222,9 +437,19 @@ mod tests {
                     name: "cpu_usage".to_string(),
                     value: "value".to_string(),
                 },
+                debounce_ms: Some(500),
+                exit_value: Some("3".to_string()),
+                window: Some(TimeWindow {
+                    start: "22:00".to_string(),
+                    end: "06:00".to_string(),
+                    days: None,
+                }),
             }),
             action: "scale".to_string(),
             target: "deployment".to_string(),
+            priority: Some(3),
+            depends_on: vec!["base".to_string()],
+            delay_ms: Some(30_000),
         };
 
         let serialized = serde_json::to_string(&spec).unwrap();
@@ -233,6 +458,96 @@ mod tests {
         assert_eq!(spec, deserialized);
     }
 
+    #[test]
+    fn test_get_depends_on() {
+        assert!(create_test_scenario().get_depends_on().is_empty());
+
+        let yaml = r#"
+apiVersion: v1
+kind: Scenario
+metadata:
+  name: parking-assist
+spec:
+  condition:
+  action: launch
+  target: parking-assist
+  dependsOn: [camera, radar]
+"#;
+        let scenario: Scenario = serde_yaml::from_str(yaml).unwrap();
+        assert_eq!(scenario.get_depends_on(), &vec!["camera", "radar"]);
+    }
+
+    #[test]
+    fn test_get_delay() {
+        assert!(create_test_scenario().get_delay().is_none());
+
+        let yaml = r#"
+apiVersion: v1
+kind: Scenario
+metadata:
+  name: cabin-heating
+spec:
+  condition:
+  action: launch
+  target: cabin-heating
+  delayMs: 30000
+"#;
+        let scenario: Scenario = serde_yaml::from_str(yaml).unwrap();
+        assert_eq!(
+            scenario.get_delay(),
+            Some(std::time::Duration::from_secs(30))
+        );
+    }
+
+    fn graph(edges: &[(&str, &[&str])]) -> HashMap<String, Vec<String>> {
+        edges
+            .iter()
+            .map(|(name, deps)| {
+                (
+                    name.to_string(),
+                    deps.iter().map(|d| d.to_string()).collect(),
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_dependency_order_chain() {
+        let dependencies = graph(&[("c", &["b"]), ("b", &["a"]), ("a", &[])]);
+
+        assert_eq!(
+            dependency_order(&dependencies, &["c".to_string()]).unwrap(),
+            vec!["a", "b", "c"]
+        );
+        assert_eq!(
+            dependency_order(&dependencies, &["b".to_string()]).unwrap(),
+            vec!["a", "b"]
+        );
+    }
+
+    #[test]
+    fn test_dependency_order_diamond() {
+        let dependencies = graph(&[
+            ("top", &["left", "right"]),
+            ("left", &["base"]),
+            ("right", &["base"]),
+        ]);
+
+        let order = dependency_order(&dependencies, &["top".to_string()]).unwrap();
+
+        // The shared dependency is activated once, first
+        assert_eq!(order, vec!["base", "left", "right", "top"]);
+    }
+
+    #[test]
+    fn test_dependency_order_detects_cycle() {
+        let dependencies = graph(&[("a", &["b"]), ("b", &["c"]), ("c", &["a"]), ("d", &[])]);
+
+        let err = dependency_order(&dependencies, &["d".to_string(), "a".to_string()]).unwrap_err();
+
+        assert_eq!(err, "dependency cycle: a -> b -> c -> a");
+    }
+
     #[test]
     fn test_condition_cloning() {
         let condition = Condition {
@@ -243,6 +558,9 @@ mod tests {
                 name: "memory_usage".to_string(),
                 value: "value".to_string(),
             },
+            debounce_ms: None,
+            exit_value: None,
+            window: None,
         };
 
         let cloned = condition.clone();
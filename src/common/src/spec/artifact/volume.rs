@@ -4,6 +4,8 @@
 */
 use super::Artifact;
 use super::Volume;
+use crate::spec::k8s::pod::Volume as PodVolume;
+use crate::spec::k8s::quantity::Quantity;
 
 impl Artifact for Volume {
     fn get_name(&self) -> String {
@@ -15,17 +17,124 @@ impl Volume {
     pub fn get_spec(&self) -> &Option<VolumeSpec> {
         &self.spec
     }
+
+    /// Check that the spec describes a usable volume
+    pub fn validate(&self) -> Result<(), String> {
+        match &self.spec {
+            Some(spec) => spec
+                .validate()
+                .map_err(|e| format!("volume '{}': {}", self.metadata.name, e)),
+            None => Ok(()),
+        }
+    }
+
+    /// Pod volumes to add to the models using this volume
+    pub fn get_pod_volumes(&self) -> Option<Vec<PodVolume>> {
+        self.spec
+            .as_ref()
+            .and_then(|spec| spec.to_pod_volumes(&self.metadata.name))
+    }
 }
 
-#[derive(Debug, Clone, serde::Serialize, serde::Deserialize, PartialEq)]
+/// Storage shared between models
+///
+/// Either lists raw pod `volumes`, or describes a single volume with `kind`.
+/// The pod volume of a described volume is named after the Volume artifact,
+/// so containers mount it with `volumeMounts: [{name: <volume name>, ...}]`.
+#[derive(Debug, Clone, Default, serde::Serialize, serde::Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
 pub struct VolumeSpec {
-    volumes: Option<Vec<crate::spec::k8s::pod::Volume>>,
+    volumes: Option<Vec<PodVolume>>,
+    kind: Option<VolumeKind>,
+    /// Directory of a `hostPath` volume. A relative path, or none for the
+    /// volume name, is resolved under the base path of the node.
+    path: Option<String>,
+    /// Image reference of an `image` volume
+    image: Option<String>,
+    /// Size limit of an `emptyDir` volume
+    size: Option<Quantity>,
+    #[serde(default)]
+    access_modes: Vec<AccessMode>,
+}
+
+#[derive(Debug, Clone, Copy, serde::Serialize, serde::Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub enum VolumeKind {
+    HostPath,
+    EmptyDir,
+    Image,
+}
+
+#[derive(Debug, Clone, Copy, serde::Serialize, serde::Deserialize, PartialEq)]
+pub enum AccessMode {
+    ReadWriteOnce,
+    ReadOnlyMany,
+    ReadWriteMany,
 }
 
 impl VolumeSpec {
-    pub fn get_volume(&self) -> &Option<Vec<crate::spec::k8s::pod::Volume>> {
+    pub fn get_volume(&self) -> &Option<Vec<PodVolume>> {
         &self.volumes
     }
+
+    pub fn get_kind(&self) -> Option<VolumeKind> {
+        self.kind
+    }
+
+    pub fn get_access_modes(&self) -> &Vec<AccessMode> {
+        &self.access_modes
+    }
+
+    /// Check that the fields set match the volume kind
+    pub fn validate(&self) -> Result<(), String> {
+        let Some(kind) = self.kind else {
+            return Ok(());
+        };
+        if self.volumes.is_some() {
+            return Err("`volumes` and `kind` cannot be used together".to_string());
+        }
+        if self.path.is_some() && kind != VolumeKind::HostPath {
+            return Err("`path` is only valid for hostPath volumes".to_string());
+        }
+        if self.size.is_some() && kind != VolumeKind::EmptyDir {
+            return Err("`size` is only valid for emptyDir volumes".to_string());
+        }
+        match (kind, &self.image) {
+            (VolumeKind::Image, None) => Err("image volume without `image`".to_string()),
+            (VolumeKind::Image, Some(_)) => {
+                if self
+                    .access_modes
+                    .iter()
+                    .any(|m| *m != AccessMode::ReadOnlyMany)
+                {
+                    Err("image volumes only support ReadOnlyMany".to_string())
+                } else {
+                    Ok(())
+                }
+            }
+            (_, Some(_)) => Err("`image` is only valid for image volumes".to_string()),
+            (_, None) => Ok(()),
+        }
+    }
+
+    /// Pod volumes described by the spec, raw `volumes` as they are
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - Name of the Volume artifact, used for a volume described by `kind`
+    pub fn to_pod_volumes(&self, name: &str) -> Option<Vec<PodVolume>> {
+        let Some(kind) = self.kind else {
+            return self.volumes.clone();
+        };
+        let volume = match kind {
+            VolumeKind::HostPath => {
+                PodVolume::host_path(name, self.path.as_deref().unwrap_or(name))
+            }
+            VolumeKind::EmptyDir => PodVolume::empty_dir(name, self.size.clone()),
+            VolumeKind::Image => PodVolume::image(name, self.image.as_deref()?),
+        };
+        Some(vec![volume])
+    }
 }
 
 //Unit Test Cases
@@ -86,6 +195,7 @@ mod tests {
     async fn test_get_spec_some() {
         let volume_spec = VolumeSpec {
             volumes: Some(vec![]), // Empty volumes list
+            ..Default::default()
         };
         let volume = Volume {
             apiVersion: String::from("v1"), // Required field for Volume struct
@@ -103,7 +213,7 @@ mod tests {
     // Test case to verify the `get_volume` function returns `None` when no volumes are provided.
     #[tokio::test]
     async fn test_get_volume_none() {
-        let volume_spec = VolumeSpec { volumes: None }; // No volumes provided
+        let volume_spec = VolumeSpec::default(); // No volumes provided
         assert_eq!(volume_spec.get_volume(), &None);
     }
 
@@ -113,6 +223,7 @@ mod tests {
         let volumes = vec![]; // Empty list of volumes
         let volume_spec = VolumeSpec {
             volumes: Some(volumes.clone()), // Volumes provided
+            ..Default::default()
         };
         assert_eq!(volume_spec.get_volume(), &Some(volumes));
     }
@@ -120,7 +231,7 @@ mod tests {
     // Negative test case to verify the `get_volume` function does not return incorrect values.
     #[tokio::test]
     async fn test_get_volume_invalid() {
-        let volume_spec = VolumeSpec { volumes: None }; // No volumes provided
+        let volume_spec = VolumeSpec::default(); // No volumes provided
         assert_ne!(volume_spec.get_volume(), &Some(vec![])); // Should not match an empty list
     }
 
@@ -129,6 +240,7 @@ mod tests {
     async fn test_get_spec_invalid() {
         let volume_spec = VolumeSpec {
             volumes: Some(vec![]), // Empty volumes list
+            ..Default::default()
         };
         let volume = Volume {
             apiVersion: String::from("v1"), // Required field for Volume struct
@@ -142,4 +254,93 @@ mod tests {
         };
         assert_ne!(volume.get_spec(), &Some(volume_spec)); // Should not match the provided spec
     }
+
+    fn parse_volume(yaml_spec: &str) -> Volume {
+        let yaml = format!(
+            "apiVersion: v1\nkind: Volume\nmetadata:\n  name: shared\nspec:\n{}",
+            yaml_spec
+        );
+        serde_yaml::from_str(&yaml).unwrap()
+    }
+
+    #[test]
+    fn test_host_path_volume() {
+        let volume =
+            parse_volume("  kind: hostPath\n  path: data\n  accessModes: [ReadWriteMany]\n");
+        assert!(volume.validate().is_ok());
+        let spec = volume.get_spec().as_ref().unwrap();
+        assert_eq!(spec.get_kind(), Some(VolumeKind::HostPath));
+        assert_eq!(spec.get_access_modes(), &vec![AccessMode::ReadWriteMany]);
+        assert_eq!(
+            volume.get_pod_volumes(),
+            Some(vec![PodVolume::host_path("shared", "data")])
+        );
+
+        // Without a path the directory is named after the volume
+        let volume = parse_volume("  kind: hostPath\n");
+        assert_eq!(
+            volume.get_pod_volumes(),
+            Some(vec![PodVolume::host_path("shared", "shared")])
+        );
+    }
+
+    #[test]
+    fn test_empty_dir_and_image_volumes() {
+        let volume = parse_volume("  kind: emptyDir\n  size: 64Mi\n");
+        assert!(volume.validate().is_ok());
+        assert_eq!(
+            volume.get_pod_volumes(),
+            Some(vec![PodVolume::empty_dir(
+                "shared",
+                Some("64Mi".parse().unwrap())
+            )])
+        );
+
+        let volume =
+            parse_volume("  kind: image\n  image: maps:v2\n  accessModes: [ReadOnlyMany]\n");
+        assert!(volume.validate().is_ok());
+        assert_eq!(
+            volume.get_pod_volumes(),
+            Some(vec![PodVolume::image("shared", "maps:v2")])
+        );
+    }
+
+    #[test]
+    fn test_raw_volumes_are_kept() {
+        let volume = parse_volume(
+            "  volumes:\n    - name: x11\n      hostPath:\n        path: /tmp/.X11-unix\n",
+        );
+        assert!(volume.validate().is_ok());
+        assert_eq!(
+            volume.get_pod_volumes(),
+            Some(vec![PodVolume::host_path("x11", "/tmp/.X11-unix")])
+        );
+    }
+
+    #[test]
+    fn test_invalid_volume_specs() {
+        for (spec, error) in [
+            ("  kind: image\n", "image volume without `image`"),
+            (
+                "  kind: image\n  image: maps\n  accessModes: [ReadWriteOnce]\n",
+                "image volumes only support ReadOnlyMany",
+            ),
+            ("  kind: emptyDir\n  path: /data\n", "`path` is only valid"),
+            ("  kind: hostPath\n  size: 1Gi\n", "`size` is only valid"),
+            ("  kind: hostPath\n  image: maps\n", "`image` is only valid"),
+            (
+                "  kind: hostPath\n  volumes: []\n",
+                "`volumes` and `kind` cannot be used together",
+            ),
+        ] {
+            let err = parse_volume(spec).validate().unwrap_err();
+            assert!(err.starts_with("volume 'shared': "), "{}", err);
+            assert!(err.contains(error), "{}: {}", spec, err);
+        }
+
+        let yaml = "apiVersion: v1\nkind: Volume\nmetadata:\n  name: v\nspec:\n  kind: nfs\n";
+        assert!(serde_yaml::from_str::<Volume>(yaml).is_err());
+        let yaml = "apiVersion: v1\nkind: Volume\nmetadata:\n  name: v\nspec:\n  kind: emptyDir\n  size: lots\n";
+        assert!(serde_yaml::from_str::<Volume>(yaml).is_err());
+    }
 }
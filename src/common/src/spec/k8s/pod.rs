@@ -2,6 +2,7 @@
 
 use std::collections::HashMap;
 
+use super::quantity::Quantity;
 use super::Pod;
 use crate::spec::artifact::Model;
 use crate::spec::MetaData;
@@ -24,9 +25,28 @@ impl Pod {
         self.metadata.name.clone()
     }
 
+    pub fn get_spec(&self) -> &PodSpec {
+        &self.spec
+    }
+
+    pub fn get_annotation(&self, key: &str) -> Option<&str> {
+        self.metadata
+            .annotations
+            .as_ref()?
+            .get(key)
+            .map(String::as_str)
+    }
+
+    pub fn set_annotation(&mut self, key: &str, value: &str) {
+        self.metadata
+            .annotations
+            .get_or_insert_with(HashMap::new)
+            .insert(key.to_string(), value.to_string());
+    }
+
     /// Returns the restart policy of the pod spec, if set.
     pub fn get_restart_policy(&self) -> Option<&str> {
-        self.spec.restartPolicy.as_deref()
+        self.spec.restartPolicy.map(RestartPolicy::as_str)
     }
 
     /// Returns the probe configuration of the pod spec, if set.
@@ -47,7 +67,7 @@ pub struct PodSpec {
     pub containers: Vec<Container>,
     pub volumes: Option<Vec<Volume>>,
     initContainers: Option<Vec<Container>>,
-    restartPolicy: Option<String>,
+    restartPolicy: Option<RestartPolicy>,
     terminationGracePeriodSeconds: Option<i32>,
     hostIPC: Option<bool>,
     runtimeClassName: Option<String>,
@@ -55,6 +75,24 @@ pub struct PodSpec {
     pub probeConfig: Option<ProbeConfig>,
 }
 
+/// What to do when a container of the pod exits
+#[derive(Debug, Clone, Copy, serde::Serialize, serde::Deserialize, PartialEq, Eq)]
+pub enum RestartPolicy {
+    Always,
+    OnFailure,
+    Never,
+}
+
+impl RestartPolicy {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            RestartPolicy::Always => "Always",
+            RestartPolicy::OnFailure => "OnFailure",
+            RestartPolicy::Never => "Never",
+        }
+    }
+}
+
 /// Configuration for health probes in the Pod YAML spec.
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize, PartialEq)]
 pub struct ProbeConfig {
@@ -137,10 +175,13 @@ pub struct PodSecurityContext {
     runAsGroup: Option<i64>,
 }
 
+/// Pod volume, backed by exactly one of the sources below
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize, PartialEq)]
 pub struct Volume {
     name: String,
-    hostPath: HostPath,
+    hostPath: Option<HostPath>,
+    emptyDir: Option<EmptyDir>,
+    image: Option<ImageSource>,
 }
 
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize, PartialEq)]
@@ -148,6 +189,62 @@ pub struct HostPath {
     path: String,
 }
 
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize, PartialEq)]
+pub struct EmptyDir {
+    sizeLimit: Option<Quantity>,
+}
+
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize, PartialEq)]
+pub struct ImageSource {
+    reference: String,
+}
+
+impl Volume {
+    /// Volume bound to a directory of the node
+    pub fn host_path(name: &str, path: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            hostPath: Some(HostPath {
+                path: path.to_string(),
+            }),
+            emptyDir: None,
+            image: None,
+        }
+    }
+
+    /// Scratch volume living as long as the pod
+    pub fn empty_dir(name: &str, size_limit: Option<Quantity>) -> Self {
+        Self {
+            name: name.to_string(),
+            hostPath: None,
+            emptyDir: Some(EmptyDir {
+                sizeLimit: size_limit,
+            }),
+            image: None,
+        }
+    }
+
+    /// Read-only volume with the content of a container image
+    pub fn image(name: &str, reference: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            hostPath: None,
+            emptyDir: None,
+            image: Some(ImageSource {
+                reference: reference.to_string(),
+            }),
+        }
+    }
+
+    pub fn get_name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn get_host_path(&self) -> Option<&str> {
+        self.hostPath.as_ref().map(|h| h.path.as_str())
+    }
+}
+
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize, PartialEq)]
 pub struct VolumeMount {
     name: String,
@@ -172,7 +269,28 @@ pub struct ResourceRequirements {
     requests: Option<ResourceList>,
 }
 
-type ResourceList = HashMap<String, String>;
+/// Quantities by resource name, e.g. `cpu: 500m`
+pub type ResourceList = HashMap<String, Quantity>;
+
+impl ResourceRequirements {
+    pub fn get_limits(&self) -> Option<&ResourceList> {
+        self.limits.as_ref()
+    }
+
+    pub fn get_requests(&self) -> Option<&ResourceList> {
+        self.requests.as_ref()
+    }
+
+    /// Requested amount of `resource`, falling back to its limit as
+    /// Kubernetes does when only a limit is given
+    pub fn requested(&self, resource: &str) -> Option<f64> {
+        self.requests
+            .as_ref()
+            .and_then(|r| r.get(resource))
+            .or_else(|| self.limits.as_ref().and_then(|l| l.get(resource)))
+            .map(Quantity::value)
+    }
+}
 
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize, PartialEq)]
 pub struct SecurityContext {
@@ -188,6 +306,12 @@ pub struct Capabilities {
     drop: Option<Vec<String>>,
 }
 
+impl Container {
+    pub fn get_resources(&self) -> Option<&ResourceRequirements> {
+        self.resources.as_ref()
+    }
+}
+
 impl PodSpec {
     /// Returns the image of the first container in the PodSpec.
     /// If no containers are present, returns `None`.
@@ -200,6 +324,15 @@ impl PodSpec {
     pub fn get_volume(&mut self) -> &Option<Vec<Volume>> {
         &self.volumes
     }
+
+    /// Total amount of `resource` requested by the containers of the pod,
+    /// in base units (cores for `cpu`, bytes for `memory`)
+    pub fn requested(&self, resource: &str) -> f64 {
+        self.containers
+            .iter()
+            .filter_map(|c| c.resources.as_ref()?.requested(resource))
+            .sum()
+    }
 }
 
 //Unit Test Cases
@@ -309,18 +442,8 @@ mod tests {
     // multiple volumes are present in the PodSpec.
     #[tokio::test]
     async fn test_get_volume_with_multiple_volumes() {
-        let volume1 = Volume {
-            name: String::from("volume-1"),
-            hostPath: HostPath {
-                path: String::from("/path/1"),
-            },
-        };
-        let volume2 = Volume {
-            name: String::from("volume-2"),
-            hostPath: HostPath {
-                path: String::from("/path/2"),
-            },
-        };
+        let volume1 = Volume::host_path("volume-1", "/path/1");
+        let volume2 = Volume::host_path("volume-2", "/path/2");
         let mut podspec = PodSpec {
             hostNetwork: None,
             containers: vec![],
@@ -336,18 +459,8 @@ mod tests {
         assert_eq!(
             podspec.get_volume(),
             &Some(vec![
-                Volume {
-                    name: String::from("volume-1"),
-                    hostPath: HostPath {
-                        path: String::from("/path/1"),
-                    },
-                },
-                Volume {
-                    name: String::from("volume-2"),
-                    hostPath: HostPath {
-                        path: String::from("/path/2"),
-                    },
-                },
+                Volume::host_path("volume-1", "/path/1"),
+                Volume::host_path("volume-2", "/path/2"),
             ])
         );
     }
@@ -391,12 +504,7 @@ mod tests {
     // Negative Test: Validate that `get_volume` correctly handles invalid volume data.
     #[tokio::test]
     async fn test_get_volume_with_invalid_volume() {
-        let volume = Volume {
-            name: String::from(""),
-            hostPath: HostPath {
-                path: String::from(""),
-            },
-        };
+        let volume = Volume::host_path("", "");
         let mut podspec = PodSpec {
             hostNetwork: None,
             containers: vec![],
@@ -409,15 +517,7 @@ mod tests {
             securityContext: None,
             probeConfig: None,
         };
-        assert_eq!(
-            podspec.get_volume(),
-            &Some(vec![Volume {
-                name: String::from(""),
-                hostPath: HostPath {
-                    path: String::from(""),
-                },
-            }])
-        );
+        assert_eq!(podspec.get_volume(), &Some(vec![Volume::host_path("", "")]));
     }
 
     // Positive Test: Validate that `get_image` correctly handles container image names
@@ -515,4 +615,101 @@ spec:
         assert!(liveness.tcp.is_some());
         assert_eq!(liveness.tcp.as_ref().unwrap().port, 8080);
     }
+
+    const RESOURCE_POD: &str = r#"
+apiVersion: v1
+kind: Pod
+metadata:
+  name: resource-pod
+spec:
+  containers:
+    - name: app
+      image: myapp:latest
+      resources:
+        requests:
+          cpu: 250m
+          memory: 64Mi
+        limits:
+          cpu: 1
+          memory: 128Mi
+    - name: sidecar
+      image: sidecar:latest
+      resources:
+        limits:
+          cpu: "0.5"
+          memory: 32Mi
+  restartPolicy: OnFailure
+"#;
+
+    // Test: resources and restartPolicy survive a parse/serialize/parse round trip.
+    #[test]
+    fn test_pod_resources_and_restart_policy_round_trip() {
+        let pod = serde_yaml::from_str::<crate::spec::k8s::Pod>(RESOURCE_POD).unwrap();
+        assert_eq!(pod.get_restart_policy(), Some("OnFailure"));
+
+        let yaml = serde_yaml::to_string(&pod).unwrap();
+        assert!(yaml.contains("memory: 64Mi"));
+        assert!(yaml.contains("restartPolicy: OnFailure"));
+        assert_eq!(
+            serde_yaml::from_str::<crate::spec::k8s::Pod>(&yaml).unwrap(),
+            pod
+        );
+    }
+
+    // Test: requests are summed over containers, using limits where no request is given.
+    #[test]
+    fn test_podspec_requested_resources() {
+        let pod = serde_yaml::from_str::<crate::spec::k8s::Pod>(RESOURCE_POD).unwrap();
+        let resources = pod.spec.containers[0].get_resources().unwrap();
+        assert_eq!(resources.get_limits().unwrap()["memory"].as_str(), "128Mi");
+
+        assert_eq!(pod.spec.requested("cpu"), 0.75);
+        assert_eq!(pod.spec.requested("memory"), 96.0 * 1024.0 * 1024.0);
+        assert_eq!(pod.spec.requested("nvidia.com/gpu"), 0.0);
+    }
+
+    // Test: a pod without resources or restartPolicy still parses and requests nothing.
+    #[test]
+    fn test_pod_without_resources_requests_nothing() {
+        let yaml = r#"
+apiVersion: v1
+kind: Pod
+metadata:
+  name: plain-pod
+spec:
+  containers:
+    - name: app
+      image: myapp:latest
+"#;
+        let pod = serde_yaml::from_str::<crate::spec::k8s::Pod>(yaml).unwrap();
+        assert_eq!(pod.get_restart_policy(), None);
+        assert_eq!(pod.spec.requested("cpu"), 0.0);
+    }
+
+    // Test: invalid quantities and restart policies are rejected when the spec is parsed.
+    #[test]
+    fn test_pod_rejects_invalid_resources_and_restart_policy() {
+        let bad_quantity = RESOURCE_POD.replace("memory: 128Mi", "memory: 2Gib");
+        let err = serde_yaml::from_str::<crate::spec::k8s::Pod>(&bad_quantity).unwrap_err();
+        assert!(err.to_string().contains("invalid quantity '2Gib'"));
+
+        let bad_policy =
+            RESOURCE_POD.replace("restartPolicy: OnFailure", "restartPolicy: Sometimes");
+        assert!(serde_yaml::from_str::<crate::spec::k8s::Pod>(&bad_policy).is_err());
+    }
+
+    // Test: annotations set on a pod survive serialization.
+    #[test]
+    fn test_pod_annotations() {
+        let mut pod = serde_yaml::from_str::<crate::spec::k8s::Pod>(RESOURCE_POD).unwrap();
+        assert_eq!(pod.get_annotation("io.piccolo.annotations.network"), None);
+
+        pod.set_annotation("io.piccolo.annotations.network", "bms-net");
+        let yaml = serde_yaml::to_string(&pod).unwrap();
+        let pod = serde_yaml::from_str::<crate::spec::k8s::Pod>(&yaml).unwrap();
+        assert_eq!(
+            pod.get_annotation("io.piccolo.annotations.network"),
+            Some("bms-net")
+        );
+    }
 }
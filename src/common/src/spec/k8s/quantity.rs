@@ -0,0 +1,177 @@
+// SPDX-License-Identifier: Apache-2.0
+
+//! Resource quantities such as `500m` CPU or `512Mi` memory
+//!
+//! Follows the Kubernetes quantity format: a non-negative decimal number
+//! followed by a binary suffix (`Ki`, `Mi`, ...), a decimal suffix (`m`,
+//! `k`, `M`, ...) or an exponent (`1e3`). Anything else is rejected when
+//! the spec is parsed.
+
+use std::fmt;
+use std::str::FromStr;
+
+/// Binary suffixes and their multipliers
+const BINARY_SUFFIXES: [(&str, f64); 6] = [
+    ("Ki", 1024.0),
+    ("Mi", 1_048_576.0),
+    ("Gi", 1_073_741_824.0),
+    ("Ti", 1_099_511_627_776.0),
+    ("Pi", 1_125_899_906_842_624.0),
+    ("Ei", 1_152_921_504_606_846_976.0),
+];
+
+/// Decimal suffixes and their multipliers
+const DECIMAL_SUFFIXES: [(&str, f64); 10] = [
+    ("n", 1e-9),
+    ("u", 1e-6),
+    ("m", 1e-3),
+    ("k", 1e3),
+    ("M", 1e6),
+    ("G", 1e9),
+    ("T", 1e12),
+    ("P", 1e15),
+    ("E", 1e18),
+    ("", 1.0),
+];
+
+/// A resource quantity, kept as written so it serializes unchanged
+#[derive(Debug, Clone, PartialEq)]
+pub struct Quantity {
+    raw: String,
+    value: f64,
+}
+
+impl Quantity {
+    /// Value in base units: cores for CPU, bytes for memory
+    pub fn value(&self) -> f64 {
+        self.value
+    }
+
+    /// The quantity as written in the spec
+    pub fn as_str(&self) -> &str {
+        &self.raw
+    }
+}
+
+impl FromStr for Quantity {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let raw = s.trim();
+        let invalid = || format!("invalid quantity '{}'", s);
+
+        let split = raw
+            .find(|c: char| !c.is_ascii_digit() && c != '.')
+            .unwrap_or(raw.len());
+        let (number, suffix) = raw.split_at(split);
+        if number.is_empty() || number == "." || number.matches('.').count() > 1 {
+            return Err(invalid());
+        }
+        let number: f64 = number.parse().map_err(|_| invalid())?;
+
+        let multiplier = if let Some((_, m)) = BINARY_SUFFIXES.iter().find(|(n, _)| *n == suffix) {
+            *m
+        } else if let Some((_, m)) = DECIMAL_SUFFIXES.iter().find(|(n, _)| *n == suffix) {
+            *m
+        } else {
+            let exponent = suffix
+                .strip_prefix(['e', 'E'])
+                .and_then(|e| e.parse::<i32>().ok())
+                .ok_or_else(invalid)?;
+            10f64.powi(exponent)
+        };
+
+        Ok(Quantity {
+            raw: raw.to_string(),
+            value: number * multiplier,
+        })
+    }
+}
+
+impl fmt::Display for Quantity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.raw)
+    }
+}
+
+impl serde::Serialize for Quantity {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.raw)
+    }
+}
+
+impl<'de> serde::Deserialize<'de> for Quantity {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        // `cpu: 1` and `cpu: "1"` are both valid in a spec
+        #[derive(serde::Deserialize)]
+        #[serde(untagged)]
+        enum Raw {
+            Text(String),
+            Integer(u64),
+            Float(f64),
+        }
+
+        let raw = match Raw::deserialize(deserializer)? {
+            Raw::Text(s) => s,
+            Raw::Integer(n) => n.to_string(),
+            Raw::Float(n) => n.to_string(),
+        };
+        raw.parse().map_err(serde::de::Error::custom)
+    }
+}
+
+//Unit Test Cases
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn value(s: &str) -> f64 {
+        s.parse::<Quantity>().unwrap().value()
+    }
+
+    #[test]
+    fn test_parse_cpu_quantities() {
+        assert_eq!(value("2"), 2.0);
+        assert_eq!(value("0.5"), 0.5);
+        assert_eq!(value("500m"), 0.5);
+        assert_eq!(value("250000u"), 0.25);
+    }
+
+    #[test]
+    fn test_parse_memory_quantities() {
+        assert_eq!(value("512Mi"), 512.0 * 1024.0 * 1024.0);
+        assert_eq!(value("1.5Gi"), 1.5 * 1024.0 * 1024.0 * 1024.0);
+        assert_eq!(value("100k"), 100_000.0);
+        assert_eq!(value("2G"), 2e9);
+        assert_eq!(value("128e6"), 128e6);
+        assert_eq!(value("1E"), 1e18);
+    }
+
+    #[test]
+    fn test_reject_invalid_quantities() {
+        for invalid in [
+            "", "2Gib", "Mi", "-1", "1.2.3", ".", "1 Gi", "1K", "one", "1e",
+        ] {
+            assert!(
+                invalid.parse::<Quantity>().is_err(),
+                "'{}' should be rejected",
+                invalid
+            );
+        }
+    }
+
+    #[test]
+    fn test_quantity_serde_keeps_original_text() {
+        let quantity: Quantity = serde_yaml::from_str("\"512Mi\"").unwrap();
+        assert_eq!(quantity.as_str(), "512Mi");
+        assert_eq!(serde_yaml::to_string(&quantity).unwrap().trim(), "512Mi");
+
+        let quantity: Quantity = serde_yaml::from_str("1").unwrap();
+        assert_eq!(quantity.value(), 1.0);
+        let quantity: Quantity = serde_yaml::from_str("0.5").unwrap();
+        assert_eq!(quantity.value(), 0.5);
+
+        let err = serde_yaml::from_str::<Quantity>("2Gib").unwrap_err();
+        assert!(err.to_string().contains("invalid quantity '2Gib'"));
+    }
+}
@@ -0,0 +1,298 @@
+/*
+ * SPDX-FileCopyrightText: Copyright 2024 LG Electronics Inc.
+ * SPDX-License-Identifier: Apache-2.0
+ */
+
+//! Correlation of requests across the Pullpiri daemons
+//!
+//! Inter-service gRPC requests carry a trace ID in the `x-piccolo-trace-id`
+//! metadata header. A server built with [`TraceLayer`] runs every request
+//! inside a scope holding its trace ID and a `tracing` span carrying it, so
+//! the logs of the request share the ID and the requests it sends carry it
+//! on. A request arriving without the header starts a new trace.
+//!
+//! Requests sent through [`crate::rpc::call`] get the header
+//! automatically; other calls wrap their message with [`request`], or build
+//! their client with [`inject`] as interceptor.
+
+use std::future::Future;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use tonic::metadata::MetadataValue;
+use tonic::Request;
+use tower_layer::Layer;
+use tower_service::Service;
+use tracing::Instrument;
+
+/// Metadata header carrying the trace ID
+pub const TRACE_ID_HEADER: &str = "x-piccolo-trace-id";
+
+/// Longest trace ID accepted from a caller
+const MAX_TRACE_ID_LEN: usize = 128;
+
+tokio::task_local! {
+    static TRACE_ID: String;
+}
+
+/// Fresh trace ID, for a request that starts a trace
+pub fn new_trace_id() -> String {
+    uuid::Uuid::new_v4().to_string()
+}
+
+/// Trace ID of the request being served, `None` outside of any trace
+pub fn current() -> Option<String> {
+    TRACE_ID.try_with(Clone::clone).ok()
+}
+
+/// Run `future` as part of the trace `trace_id`
+///
+/// Within `future`, [`current`] returns `trace_id` and `tracing` events are
+/// recorded in a span carrying it.
+pub async fn scope<F: Future>(trace_id: String, future: F) -> F::Output {
+    let span = span(&trace_id);
+    TRACE_ID.scope(trace_id, future.instrument(span)).await
+}
+
+/// Span the events of the trace `trace_id` are recorded in
+pub(crate) fn span(trace_id: &str) -> tracing::Span {
+    tracing::info_span!("trace", trace_id = %trace_id)
+}
+
+/// Add the trace ID to `request`
+///
+/// A request that already carries one is left as it is. Outside of any
+/// trace, the request starts a new one. Used as a client interceptor, it
+/// adds the trace ID to every request of the client:
+///
+/// ```ignore
+/// let client = NodeAgentConnectionClient::with_interceptor(channel, |request| {
+///     Ok(trace::inject(request))
+/// });
+/// ```
+pub fn inject<T>(mut request: Request<T>) -> Request<T> {
+    if !request.metadata().contains_key(TRACE_ID_HEADER) {
+        let trace_id = current().unwrap_or_else(new_trace_id);
+        if let Ok(value) = MetadataValue::try_from(trace_id) {
+            request.metadata_mut().insert(TRACE_ID_HEADER, value);
+        }
+    }
+    request
+}
+
+/// Wrap `message` in a request carrying the trace ID
+pub fn request<T>(message: T) -> Request<T> {
+    inject(Request::new(message))
+}
+
+/// Trace ID sent by the caller, or a new one if it sent none or an
+/// unusable one
+pub fn extract(headers: &http::HeaderMap) -> String {
+    headers
+        .get(TRACE_ID_HEADER)
+        .and_then(|value| value.to_str().ok())
+        .filter(|value| !value.is_empty() && value.len() <= MAX_TRACE_ID_LEN)
+        .map(str::to_string)
+        .unwrap_or_else(new_trace_id)
+}
+
+/// Server layer running every request in the scope of its trace
+///
+/// ```ignore
+/// Server::builder()
+///     .layer(common::trace::TraceLayer)
+///     .add_service(...)
+/// ```
+#[derive(Debug, Clone, Copy, Default)]
+pub struct TraceLayer;
+
+impl<S> Layer<S> for TraceLayer {
+    type Service = TraceService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        TraceService { inner }
+    }
+}
+
+/// Service wrapped by [`TraceLayer`]
+#[derive(Debug, Clone)]
+pub struct TraceService<S> {
+    inner: S,
+}
+
+impl<S, B> Service<http::Request<B>> for TraceService<S>
+where
+    S: Service<http::Request<B>>,
+    S::Future: Send + 'static,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = Pin<Box<dyn Future<Output = Result<S::Response, S::Error>> + Send>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, request: http::Request<B>) -> Self::Future {
+        let trace_id = extract(request.headers());
+        Box::pin(scope(trace_id, self.inner.call(request)))
+    }
+}
+
+//Unit Test Cases
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::policymanager::policy_manager_connection_client::PolicyManagerConnectionClient;
+    use crate::policymanager::policy_manager_connection_server::{
+        PolicyManagerConnection, PolicyManagerConnectionServer,
+    };
+    use crate::policymanager::{
+        CheckPolicyRequest, CheckPolicyResponse, DecideRequest, DecideResponse,
+    };
+    use std::sync::{Arc, Mutex};
+    use tokio_stream::wrappers::TcpListenerStream;
+    use tonic::{Response, Status};
+
+    /// Policy manager recording the trace ID of every request, and
+    /// forwarding it to the server at `next` if set
+    struct Relay {
+        seen: Arc<Mutex<Vec<Option<String>>>>,
+        next: Option<String>,
+    }
+
+    #[tonic::async_trait]
+    impl PolicyManagerConnection for Relay {
+        async fn check_policy(
+            &self,
+            request: Request<CheckPolicyRequest>,
+        ) -> Result<Response<CheckPolicyResponse>, Status> {
+            self.seen.lock().unwrap().push(current());
+            if let Some(next) = &self.next {
+                let channel = crate::rpc::connect(next.clone())
+                    .await
+                    .map_err(|e| Status::unavailable(e.to_string()))?;
+                let mut client = PolicyManagerConnectionClient::new(channel);
+                crate::rpc::call(request.into_inner(), |request| client.check_policy(request))
+                    .await?;
+            }
+            Ok(Response::new(CheckPolicyResponse {
+                status: 0,
+                desc: current().unwrap_or_default(),
+            }))
+        }
+
+        async fn decide(
+            &self,
+            _request: Request<DecideRequest>,
+        ) -> Result<Response<DecideResponse>, Status> {
+            Err(Status::unimplemented("not used by these tests"))
+        }
+    }
+
+    /// Start a relay to `next` and return its URL and the trace IDs it saw
+    async fn start_relay(next: Option<String>) -> (String, Arc<Mutex<Vec<Option<String>>>>) {
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(
+            tonic::transport::Server::builder()
+                .layer(TraceLayer)
+                .add_service(PolicyManagerConnectionServer::new(Relay {
+                    seen: seen.clone(),
+                    next,
+                }))
+                .serve_with_incoming(TcpListenerStream::new(listener)),
+        );
+        (format!("http://{}", addr), seen)
+    }
+
+    /// Two relays in a row, returning the URL of the first and the trace
+    /// IDs seen by each
+    async fn start_chain() -> (
+        String,
+        Arc<Mutex<Vec<Option<String>>>>,
+        Arc<Mutex<Vec<Option<String>>>>,
+    ) {
+        let (second, second_seen) = start_relay(None).await;
+        let (first, first_seen) = start_relay(Some(second)).await;
+        (first, first_seen, second_seen)
+    }
+
+    fn check() -> CheckPolicyRequest {
+        CheckPolicyRequest {
+            scenario_name: "helloworld".to_string(),
+            context: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_first_hop_starts_trace_and_propagates_it() {
+        let (first, first_seen, second_seen) = start_chain().await;
+        let channel = crate::rpc::connect(first).await.unwrap();
+
+        // A client that sends no trace ID
+        let response = PolicyManagerConnectionClient::new(channel)
+            .check_policy(Request::new(check()))
+            .await
+            .unwrap()
+            .into_inner();
+
+        let trace_id = first_seen.lock().unwrap()[0].clone().unwrap();
+        assert!(uuid::Uuid::parse_str(&trace_id).is_ok());
+        assert_eq!(
+            second_seen.lock().unwrap().clone(),
+            vec![Some(trace_id.clone())]
+        );
+        assert_eq!(response.desc, trace_id);
+    }
+
+    #[tokio::test]
+    async fn test_caller_trace_id_is_kept() {
+        let (first, first_seen, second_seen) = start_chain().await;
+        let channel = crate::rpc::connect(first).await.unwrap();
+
+        scope("trace-1".to_string(), async {
+            PolicyManagerConnectionClient::new(channel)
+                .check_policy(request(check()))
+                .await
+                .unwrap();
+        })
+        .await;
+
+        let expected = vec![Some("trace-1".to_string())];
+        assert_eq!(first_seen.lock().unwrap().clone(), expected);
+        assert_eq!(second_seen.lock().unwrap().clone(), expected);
+    }
+
+    #[tokio::test]
+    async fn test_inject() {
+        assert_eq!(current(), None);
+
+        // Outside of a trace every request starts a new one
+        let first = request(());
+        let second = request(());
+        let id = |r: &Request<()>| r.metadata().get(TRACE_ID_HEADER).cloned().unwrap();
+        assert_ne!(id(&first), id(&second));
+
+        // Within a trace, requests carry its ID
+        let inner = scope("outer".to_string(), async { request(()) }).await;
+        assert_eq!(id(&inner), "outer");
+
+        // A request that already has a trace ID keeps it
+        let before = id(&first);
+        let kept = scope("outer".to_string(), async { inject(first) }).await;
+        assert_eq!(id(&kept), before);
+    }
+
+    #[test]
+    fn test_extract() {
+        let mut headers = http::HeaderMap::new();
+        assert!(uuid::Uuid::parse_str(&extract(&headers)).is_ok());
+
+        headers.insert(TRACE_ID_HEADER, "abc".parse().unwrap());
+        assert_eq!(extract(&headers), "abc");
+
+        headers.insert(TRACE_ID_HEADER, "x".repeat(200).parse().unwrap());
+        assert_ne!(extract(&headers), "x".repeat(200));
+    }
+}
@@ -0,0 +1,368 @@
+/*
+ * SPDX-FileCopyrightText: Copyright 2024 LG Electronics Inc.
+ * SPDX-License-Identifier: Apache-2.0
+ */
+
+//! Version and capability negotiation between components
+//!
+//! Every component serves `VersionService` next to its own service. When
+//! [`crate::rpc::connect`] opens a channel, it asks the peer for its version
+//! and capabilities, logs how they differ from this build, and remembers
+//! them. Optional features are then gated on what the peer advertised, see
+//! [`require`]. A peer predating the negotiation answers `UNIMPLEMENTED` and
+//! is taken to support none of the [`capability`]s.
+
+include!("generated/version.rs");
+
+use crate::logd;
+use std::collections::HashMap;
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, Instant};
+use tonic::transport::Channel;
+use tonic::{Code, Request, Response, Status};
+use version_service_client::VersionServiceClient;
+use version_service_server::{VersionService, VersionServiceServer};
+
+/// Version of this build of the components
+pub const VERSION: &str = env!("CARGO_PKG_VERSION");
+
+/// Optional protocol features, named as advertised in [`VersionInfo`]
+pub mod capability {
+    /// NodeAgent pulls images ahead of a rollout (`PrefetchImages`)
+    pub const PREFETCH_IMAGES: &str = "prefetch-images";
+    /// NodeAgent enrolls with the bluechi controller on request (`EnrollBluechi`)
+    pub const ENROLL_BLUECHI: &str = "enroll-bluechi";
+    /// NodeAgent and ApiServer stream the logs of a workload (`GetWorkloadLogs`)
+    pub const WORKLOAD_LOGS: &str = "workload-logs";
+    /// MonitoringServer keeps the container logs streamed to it (`SendLogs`)
+    pub const CONTAINER_LOGS: &str = "container-logs";
+    /// PolicyManager decides between conflicting scenarios (`Decide`)
+    pub const POLICY_DECISIONS: &str = "policy-decisions";
+}
+
+/// Capabilities of this build, shared by all components built from it
+pub const CAPABILITIES: &[&str] = &[
+    capability::PREFETCH_IMAGES,
+    capability::ENROLL_BLUECHI,
+    capability::WORKLOAD_LOGS,
+    capability::CONTAINER_LOGS,
+    capability::POLICY_DECISIONS,
+];
+
+/// Time a negotiated peer version is trusted before it is asked again
+const PEER_TTL: Duration = Duration::from_secs(300);
+
+type Peers = Mutex<HashMap<String, (Instant, VersionInfo)>>;
+
+static PEERS: OnceLock<Peers> = OnceLock::new();
+
+fn peers() -> &'static Peers {
+    PEERS.get_or_init(Default::default)
+}
+
+/// Version of this build of `component`
+pub fn info(component: &str) -> VersionInfo {
+    VersionInfo {
+        component: component.to_string(),
+        version: VERSION.to_string(),
+        capabilities: CAPABILITIES.iter().map(|c| c.to_string()).collect(),
+        git_commit: option_env!("PULLPIRI_GIT_COMMIT")
+            .unwrap_or("unknown")
+            .to_string(),
+        build_profile: if cfg!(debug_assertions) {
+            "debug"
+        } else {
+            "release"
+        }
+        .to_string(),
+    }
+}
+
+/// Version service of `component`, to add to its gRPC server
+///
+/// ```ignore
+/// Server::builder()
+///     .add_service(health_service)
+///     .add_service(common::version::service("nodeagent"))
+///     .add_service(...)
+/// ```
+pub fn service(component: &'static str) -> VersionServiceServer<impl VersionService> {
+    VersionServiceServer::new(Version { component })
+}
+
+struct Version {
+    component: &'static str,
+}
+
+#[tonic::async_trait]
+impl VersionService for Version {
+    async fn get_version(
+        &self,
+        _request: Request<GetVersionRequest>,
+    ) -> Result<Response<VersionInfo>, Status> {
+        Ok(Response::new(info(self.component)))
+    }
+}
+
+/// Ask the peer at `url` for its version over `channel`, unless it is known
+///
+/// Differences to this build are logged. A peer that cannot be asked is not
+/// remembered, so the next connection asks again.
+pub async fn negotiate(url: &str, channel: Channel) -> Option<VersionInfo> {
+    if let Some(peer) = peer(url) {
+        return Some(peer);
+    }
+    let mut client = VersionServiceClient::new(channel);
+    let peer =
+        match crate::rpc::call(GetVersionRequest {}, |request| client.get_version(request)).await {
+            Ok(response) => response.into_inner(),
+            Err(status) if status.code() == Code::Unimplemented => {
+                logd!(
+                    4,
+                    "Peer at {} predates version negotiation, its optional features are disabled",
+                    url
+                );
+                VersionInfo::default()
+            }
+            Err(status) => {
+                logd!(2, "Cannot ask {} for its version: {}", url, status);
+                return None;
+            }
+        };
+    for mismatch in mismatches(&peer) {
+        logd!(4, "Peer at {}: {}", url, mismatch);
+    }
+    peers()
+        .lock()
+        .unwrap()
+        .insert(url.to_string(), (Instant::now(), peer.clone()));
+    Some(peer)
+}
+
+/// Negotiated version of the peer at `url`, if it is known and recent
+pub fn peer(url: &str) -> Option<VersionInfo> {
+    let mut peers = peers().lock().unwrap();
+    match peers.get(url) {
+        Some((at, peer)) if at.elapsed() < PEER_TTL => Some(peer.clone()),
+        Some(_) => {
+            peers.remove(url);
+            None
+        }
+        None => None,
+    }
+}
+
+/// A peer lacking a capability, answered as `UNIMPLEMENTED`
+#[derive(Debug, Clone, PartialEq)]
+pub struct Unsupported(String);
+
+impl std::fmt::Display for Unsupported {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl std::error::Error for Unsupported {}
+
+impl From<Unsupported> for Status {
+    fn from(unsupported: Unsupported) -> Self {
+        Status::unimplemented(unsupported.0)
+    }
+}
+
+/// Fails if the peer at `url` is known to lack `capability`
+///
+/// A peer whose version is unknown is given the benefit of the doubt, so a
+/// failed negotiation does not disable features.
+pub fn require(url: &str, capability: &str) -> Result<(), Unsupported> {
+    match peer(url) {
+        Some(peer) if !supports(&peer, capability) => Err(Unsupported(format!(
+            "{} at {} does not support {}",
+            describe(&peer),
+            url,
+            capability
+        ))),
+        _ => Ok(()),
+    }
+}
+
+/// Whether `peer` advertised `capability`
+pub fn supports(peer: &VersionInfo, capability: &str) -> bool {
+    peer.capabilities.iter().any(|c| c == capability)
+}
+
+/// How `peer` differs from this build
+fn mismatches(peer: &VersionInfo) -> Vec<String> {
+    let mut mismatches = Vec::new();
+    if peer.version != VERSION {
+        mismatches.push(format!(
+            "{} runs version {}, this build is {}",
+            describe(peer),
+            if peer.version.is_empty() {
+                "unknown"
+            } else {
+                &peer.version
+            },
+            VERSION
+        ));
+    }
+    let missing: Vec<&str> = CAPABILITIES
+        .iter()
+        .copied()
+        .filter(|c| !supports(peer, c))
+        .collect();
+    if !missing.is_empty() {
+        mismatches.push(format!("{} lacks {}", describe(peer), missing.join(", ")));
+    }
+    mismatches
+}
+
+/// Name of the component of `peer`, for messages
+fn describe(peer: &VersionInfo) -> &str {
+    if peer.component.is_empty() {
+        "peer"
+    } else {
+        &peer.component
+    }
+}
+
+//Unit Test Cases
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::policymanager::policy_manager_connection_server::{
+        PolicyManagerConnection, PolicyManagerConnectionServer,
+    };
+    use crate::policymanager::{
+        CheckPolicyRequest, CheckPolicyResponse, DecideRequest, DecideResponse,
+    };
+    use tokio_stream::wrappers::TcpListenerStream;
+
+    /// Policy manager of a build predating `VersionService`
+    struct OldPolicyManager;
+
+    #[tonic::async_trait]
+    impl PolicyManagerConnection for OldPolicyManager {
+        async fn check_policy(
+            &self,
+            _request: Request<CheckPolicyRequest>,
+        ) -> Result<Response<CheckPolicyResponse>, Status> {
+            Err(Status::unimplemented("not used by these tests"))
+        }
+
+        async fn decide(
+            &self,
+            _request: Request<DecideRequest>,
+        ) -> Result<Response<DecideResponse>, Status> {
+            Err(Status::unimplemented("not used by these tests"))
+        }
+    }
+
+    /// Peer of an older build, advertising only `capabilities`
+    struct OldVersion {
+        capabilities: Vec<&'static str>,
+    }
+
+    #[tonic::async_trait]
+    impl VersionService for OldVersion {
+        async fn get_version(
+            &self,
+            _request: Request<GetVersionRequest>,
+        ) -> Result<Response<VersionInfo>, Status> {
+            Ok(Response::new(VersionInfo {
+                component: "nodeagent".to_string(),
+                version: "0.0.9".to_string(),
+                capabilities: self.capabilities.iter().map(|c| c.to_string()).collect(),
+                git_commit: "unknown".to_string(),
+                build_profile: "release".to_string(),
+            }))
+        }
+    }
+
+    /// URL of a server answering with `version`, or as this build when `None`
+    async fn start(version: Option<OldVersion>) -> (String, Channel) {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let router = tonic::transport::Server::builder()
+            .add_service(PolicyManagerConnectionServer::new(OldPolicyManager));
+        let router = match version {
+            Some(version) => router.add_service(VersionServiceServer::new(version)),
+            None => router.add_service(service("policymanager")),
+        };
+        tokio::spawn(router.serve_with_incoming(TcpListenerStream::new(listener)));
+        let channel = crate::rpc::endpoint(url.clone())
+            .unwrap()
+            .connect()
+            .await
+            .unwrap();
+        (url, channel)
+    }
+
+    #[tokio::test]
+    async fn test_current_peer_supports_everything() {
+        let (url, channel) = start(None).await;
+
+        let peer = negotiate(&url, channel).await.unwrap();
+
+        assert_eq!(peer, info("policymanager"));
+        assert!(mismatches(&peer).is_empty());
+        assert!(require(&url, capability::WORKLOAD_LOGS).is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_old_peer_lacking_a_capability() {
+        let (url, channel) = start(Some(OldVersion {
+            capabilities: vec![capability::PREFETCH_IMAGES],
+        }))
+        .await;
+
+        let peer = negotiate(&url, channel).await.unwrap();
+
+        assert_eq!(peer.version, "0.0.9");
+        assert!(supports(&peer, capability::PREFETCH_IMAGES));
+        assert!(require(&url, capability::PREFETCH_IMAGES).is_ok());
+        let status = Status::from(require(&url, capability::WORKLOAD_LOGS).unwrap_err());
+        assert_eq!(status.code(), Code::Unimplemented);
+        assert!(status.message().contains("nodeagent"));
+        assert!(status.message().contains(capability::WORKLOAD_LOGS));
+
+        let mismatches = mismatches(&peer);
+        assert_eq!(mismatches.len(), 2);
+        assert!(mismatches[0].contains("0.0.9"));
+        assert!(mismatches[1].contains(capability::WORKLOAD_LOGS));
+        assert!(!mismatches[1].contains(capability::PREFETCH_IMAGES));
+    }
+
+    #[tokio::test]
+    async fn test_peer_predating_negotiation() {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(
+            tonic::transport::Server::builder()
+                .add_service(PolicyManagerConnectionServer::new(OldPolicyManager))
+                .serve_with_incoming(TcpListenerStream::new(listener)),
+        );
+
+        // Connecting negotiates, and remembers the peer
+        crate::rpc::connect(url.clone()).await.unwrap();
+
+        let peer = peer(&url).unwrap();
+        assert_eq!(peer, VersionInfo::default());
+        assert!(CAPABILITIES.iter().all(|c| require(&url, c).is_err()));
+    }
+
+    #[tokio::test]
+    async fn test_unknown_peer_is_not_gated() {
+        assert_eq!(peer("http://127.0.0.1:1"), None);
+        assert!(require("http://127.0.0.1:1", capability::WORKLOAD_LOGS).is_ok());
+    }
+
+    #[test]
+    fn test_info_of_this_build() {
+        let info = info("apiserver");
+        assert_eq!(info.component, "apiserver");
+        assert_eq!(info.version, VERSION);
+        assert_eq!(info.capabilities.len(), CAPABILITIES.len());
+        assert!(!info.git_commit.is_empty());
+    }
+}
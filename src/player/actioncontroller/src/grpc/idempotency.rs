@@ -0,0 +1,226 @@
+/*
+* SPDX-FileCopyrightText: Copyright 2024 LG Electronics Inc.
+* SPDX-License-Identifier: Apache-2.0
+*/
+//! Deduplication of retried trigger requests
+//!
+//! FilterGateway sends every trigger with an idempotency key and repeats
+//! the key when it retries the trigger. The first request with a key runs
+//! the scenario actions; requests repeating it within the window get the
+//! outcome of that run, waiting for it if it is still in progress.
+//!
+//! The cache is a bounded LRU, so a key can be forgotten before its window
+//! ends when many triggers arrive. Evictions are logged.
+
+use common::actioncontroller::TriggerActionResponse;
+use common::logd;
+use lru::LruCache;
+use std::future::Future;
+use std::num::NonZeroUsize;
+use std::sync::{Arc, Mutex, PoisonError};
+use std::time::{Duration, Instant};
+use tokio::sync::OnceCell;
+use tonic::Status;
+
+/// Outcome of a trigger, as returned to every request sharing its key
+pub type TriggerOutcome = Result<TriggerActionResponse, Status>;
+
+struct Entry {
+    first_seen: Instant,
+    outcome: Arc<OnceCell<TriggerOutcome>>,
+}
+
+/// Outcomes of recent triggers, by idempotency key
+pub struct IdempotencyCache {
+    entries: Mutex<LruCache<String, Entry>>,
+    window: Duration,
+}
+
+impl IdempotencyCache {
+    /// Create a cache remembering at most `capacity` keys for `window`
+    pub fn new(capacity: usize, window: Duration) -> Self {
+        let capacity = NonZeroUsize::new(capacity).unwrap_or(NonZeroUsize::MIN);
+        Self {
+            entries: Mutex::new(LruCache::new(capacity)),
+            window,
+        }
+    }
+
+    /// Cache using the limits of `settings.yaml`
+    pub fn from_settings() -> Self {
+        let settings = &common::setting::get_config().actioncontroller;
+        Self::new(
+            settings.idempotency_cache_size,
+            Duration::from_secs(settings.idempotency_window_secs),
+        )
+    }
+
+    /// Run `execute` unless a trigger with `key` already ran within the window
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - Idempotency key of the trigger
+    /// * `execute` - Runs the scenario actions
+    ///
+    /// # Returns
+    ///
+    /// * `TriggerOutcome` - Outcome of `execute`, or of the first trigger
+    ///   with `key` if there was one
+    pub async fn run<F, Fut>(&self, key: &str, execute: F) -> TriggerOutcome
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = TriggerOutcome>,
+    {
+        let outcome = self.outcome_cell(key);
+        outcome.get_or_init(execute).await.clone()
+    }
+
+    /// Cell holding the outcome of `key`, a new one if the key is unknown
+    /// or its window has ended
+    fn outcome_cell(&self, key: &str) -> Arc<OnceCell<TriggerOutcome>> {
+        let mut entries = self.entries.lock().unwrap_or_else(PoisonError::into_inner);
+        if let Some(entry) = entries.get(key) {
+            if entry.first_seen.elapsed() < self.window {
+                logd!(2, "Duplicate trigger with idempotency key {}", key);
+                return entry.outcome.clone();
+            }
+        }
+
+        let outcome = Arc::new(OnceCell::new());
+        let entry = Entry {
+            first_seen: Instant::now(),
+            outcome: outcome.clone(),
+        };
+        if let Some((evicted, _)) = entries.push(key.to_string(), entry) {
+            if evicted != key {
+                logd!(
+                    3,
+                    "Idempotency cache full, forgetting key {} before its window ends",
+                    evicted
+                );
+            }
+        }
+        outcome
+    }
+}
+
+//Unit Test Cases
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    /// Stands in for the manager, counting how often the actions run
+    #[derive(Default)]
+    struct MockManager {
+        executions: AtomicUsize,
+    }
+
+    impl MockManager {
+        async fn trigger(&self, scenario_name: &str) -> TriggerOutcome {
+            let run = self.executions.fetch_add(1, Ordering::SeqCst) + 1;
+            tokio::time::sleep(Duration::from_millis(20)).await;
+            Ok(TriggerActionResponse {
+                status: 0,
+                desc: format!("{} run {}", scenario_name, run),
+            })
+        }
+
+        fn executions(&self) -> usize {
+            self.executions.load(Ordering::SeqCst)
+        }
+    }
+
+    async fn trigger(cache: &IdempotencyCache, manager: &MockManager, key: &str) -> String {
+        cache
+            .run(key, || manager.trigger("helloworld"))
+            .await
+            .unwrap()
+            .desc
+    }
+
+    #[tokio::test]
+    async fn test_duplicate_key_runs_once() {
+        let cache = IdempotencyCache::new(16, Duration::from_secs(60));
+        let manager = MockManager::default();
+
+        let first = trigger(&cache, &manager, "key-1").await;
+        let retry = trigger(&cache, &manager, "key-1").await;
+
+        assert_eq!(manager.executions(), 1);
+        assert_eq!(first, "helloworld run 1");
+        assert_eq!(retry, first);
+    }
+
+    #[tokio::test]
+    async fn test_distinct_keys_run_each() {
+        let cache = IdempotencyCache::new(16, Duration::from_secs(60));
+        let manager = MockManager::default();
+
+        trigger(&cache, &manager, "key-1").await;
+        let second = trigger(&cache, &manager, "key-2").await;
+
+        assert_eq!(manager.executions(), 2);
+        assert_eq!(second, "helloworld run 2");
+    }
+
+    #[tokio::test]
+    async fn test_concurrent_duplicates_wait_for_first_run() {
+        let cache = IdempotencyCache::new(16, Duration::from_secs(60));
+        let manager = MockManager::default();
+
+        let (first, retry) = tokio::join!(
+            trigger(&cache, &manager, "key-1"),
+            trigger(&cache, &manager, "key-1")
+        );
+
+        assert_eq!(manager.executions(), 1);
+        assert_eq!(first, retry);
+    }
+
+    #[tokio::test]
+    async fn test_failure_is_cached() {
+        let cache = IdempotencyCache::new(16, Duration::from_secs(60));
+        let manager = MockManager::default();
+        let fail = || async {
+            manager.executions.fetch_add(1, Ordering::SeqCst);
+            Err(Status::not_found("Scenario 'helloworld' not found"))
+        };
+
+        cache.run("key-1", fail).await.unwrap_err();
+        let retry = cache.run("key-1", fail).await.unwrap_err();
+
+        assert_eq!(manager.executions(), 1);
+        assert_eq!(retry.code(), tonic::Code::NotFound);
+    }
+
+    #[tokio::test]
+    async fn test_key_runs_again_after_window() {
+        let cache = IdempotencyCache::new(16, Duration::from_millis(50));
+        let manager = MockManager::default();
+
+        trigger(&cache, &manager, "key-1").await;
+        tokio::time::sleep(Duration::from_millis(60)).await;
+        trigger(&cache, &manager, "key-1").await;
+
+        assert_eq!(manager.executions(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_least_recently_used_key_is_evicted() {
+        let cache = IdempotencyCache::new(2, Duration::from_secs(60));
+        let manager = MockManager::default();
+
+        trigger(&cache, &manager, "key-1").await;
+        trigger(&cache, &manager, "key-2").await;
+        // Using key-1 again makes key-2 the least recently used
+        trigger(&cache, &manager, "key-1").await;
+        trigger(&cache, &manager, "key-3").await;
+        assert_eq!(manager.executions(), 3);
+
+        trigger(&cache, &manager, "key-1").await;
+        assert_eq!(manager.executions(), 3);
+        trigger(&cache, &manager, "key-2").await;
+        assert_eq!(manager.executions(), 4);
+    }
+}
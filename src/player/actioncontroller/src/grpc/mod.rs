@@ -2,9 +2,12 @@
 * SPDX-FileCopyrightText: Copyright 2024 LG Electronics Inc.
 * SPDX-License-Identifier: Apache-2.0
 */
+pub mod idempotency;
 pub mod receiver;
 pub mod sender;
 
+use common::actioncontroller::action_controller_connection_server::ActionControllerConnectionServer;
+use common::health::{etcd_reachable, ServerHealth, PROBE_INTERVAL};
 use common::logd;
 use std::sync::Arc;
 use tonic::transport::Server;
@@ -13,6 +16,7 @@ use tonic::transport::Server;
 ///
 /// Sets up the gRPC server to receive requests from FilterGateway and StateManager,
 /// and establishes client connections to communicate with PolicyManager and NodeAgent.
+/// The server also exposes `grpc.health.v1.Health`, driven by etcd reachability.
 ///
 /// # Returns
 ///
@@ -26,15 +30,23 @@ use tonic::transport::Server;
 /// - Client connection establishment fails
 pub async fn init(manager: crate::manager::ActionControllerManager) -> common::Result<()> {
     let arc_manager = Arc::new(manager);
+    tokio::spawn(arc_manager.clone().run_scheduled_actions());
     let grpc_server = receiver::ActionControllerReceiver::new(arc_manager.clone());
 
     let addr = common::actioncontroller::open_server().parse()?;
     logd!(1, "Starting gRPC server on {}", addr);
 
+    let (health, health_service) =
+        ServerHealth::new::<ActionControllerConnectionServer<receiver::ActionControllerReceiver>>();
+    health.spawn_probe(PROBE_INTERVAL, etcd_reachable);
+
     tokio::spawn(async move {
         if let Err(e) = Server::builder()
+            .layer(common::trace::TraceLayer)
+            .add_service(health_service)
+            .add_service(common::version::service("actioncontroller"))
             .add_service(grpc_server.into_service())
-            .serve(addr)
+            .serve_with_shutdown(addr, health.shutdown_signal())
             .await
         {
             logd!(5, "gRPC server error: {}", e);
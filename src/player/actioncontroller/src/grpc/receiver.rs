@@ -6,13 +6,16 @@ use std::sync::Arc;
 use tonic::{Request, Response, Status};
 
 // Import the generated protobuf code
+use crate::grpc::idempotency::{IdempotencyCache, TriggerOutcome};
 use crate::grpc::sender::statemanager::StateManagerSender;
 use common::actioncontroller::{
     action_controller_connection_server::{
         ActionControllerConnection, ActionControllerConnectionServer,
     },
-    CompleteNetworkSettingRequest, CompleteNetworkSettingResponse, PodStatus as ActionStatus,
+    CancelScheduledRequest, CancelScheduledResponse, CompleteNetworkSettingRequest,
+    CompleteNetworkSettingResponse, DrainNodeRequest, DrainNodeResponse, PodStatus as ActionStatus,
     ReconcileRequest, ReconcileResponse, TriggerActionRequest, TriggerActionResponse,
+    UncordonNodeRequest, UncordonNodeResponse,
 };
 use common::logd;
 
@@ -22,12 +25,16 @@ use common::logd;
 /// the protobuf specification. Handles incoming requests from:
 /// - FilterGateway (trigger_action)
 /// - StateManager (reconcile)
+/// - Operators preparing node maintenance (drain_node, uncordon_node)
+/// - FilterGateway withdrawing a scenario with a delayed action (cancel_scheduled)
 #[allow(dead_code)]
 pub struct ActionControllerReceiver {
     /// Reference to the ActionController manager
     manager: Arc<crate::manager::ActionControllerManager>,
     /// StateManager sender for scenario state changes
     state_sender: StateManagerSender,
+    /// Outcomes of recent triggers, by idempotency key
+    triggers: IdempotencyCache,
 }
 
 impl ActionControllerReceiver {
@@ -44,43 +51,48 @@ impl ActionControllerReceiver {
         Self {
             manager,
             state_sender: StateManagerSender::new(),
+            triggers: IdempotencyCache::from_settings(),
         }
     }
 
-    /// Get a gRPC server for this receiver
+    /// Acknowledge a tracked reconcile request to the StateManager
     ///
-    /// # Returns
-    ///
-    /// A configured ActionControllerConnectionServer
-    pub fn into_service(self) -> ActionControllerConnectionServer<Self> {
-        ActionControllerConnectionServer::new(self)
-    }
-}
-
-#[tonic::async_trait]
-impl ActionControllerConnection for ActionControllerReceiver {
-    /// Handle trigger action requests from FilterGateway
+    /// The ack is sent in the background so the reconcile response is not
+    /// delayed. Requests without a transaction ID are not tracked and are
+    /// not acknowledged.
     ///
     /// # Arguments
     ///
-    /// * `request` - gRPC request containing scenario name to trigger
+    /// * `transaction_id` - Transaction ID of the reconcile request
+    /// * `success` - Whether the reconciliation succeeded
+    /// * `details` - Outcome description or error details
+    fn acknowledge(&self, transaction_id: &str, success: bool, details: &str) {
+        if transaction_id.is_empty() {
+            return;
+        }
+
+        let mut sender = self.state_sender.clone();
+        let transaction_id = transaction_id.to_string();
+        let details = details.to_string();
+        tokio::spawn(async move {
+            if let Err(e) = sender.send_ack(&transaction_id, success, &details).await {
+                logd!(
+                    4,
+                    "Failed to acknowledge reconcile {}: {:?}",
+                    transaction_id,
+                    e
+                );
+            }
+        });
+    }
+
+    /// Run the actions of `scenario_name`
     ///
     /// # Returns
     ///
-    /// * `Response<TriggerActionResponse>` - gRPC response with status and description
-    /// * `Status` - gRPC status error if the request fails
-    async fn trigger_action(
-        &self,
-        request: Request<TriggerActionRequest>,
-    ) -> Result<Response<TriggerActionResponse>, Status> {
-        use std::time::Instant;
-        let start = Instant::now();
-
-        logd!(1, "trigger_action in grpc receiver");
-
-        let scenario_name = request.into_inner().scenario_name;
-        logd!(2, "trigger_action scenario: {}", scenario_name);
-
+    /// * `TriggerOutcome` - Response, or the gRPC status matching the
+    ///   failure of the manager
+    async fn execute_trigger(&self, scenario_name: &str) -> TriggerOutcome {
         logd!(
             1,
             "🔄 SCENARIO STATE TRANSITION: ActionController Processing"
@@ -100,15 +112,21 @@ impl ActionControllerConnection for ActionControllerReceiver {
         );
 
         logd!(1, "   🎯 Processing scenario actions...");
-        let result = match self.manager.trigger_manager_action(&scenario_name).await {
-            Ok(_) => Ok(Response::new(TriggerActionResponse {
+        match self.manager.trigger_manager_action(scenario_name).await {
+            Ok(_) => Ok(TriggerActionResponse {
                 status: 0,
                 desc: "Action triggered successfully".to_string(),
-            })),
+            }),
             Err(e) => {
                 let err_msg = e.to_string();
                 let grpc_status = if err_msg.contains("Invalid scenario name") {
                     Status::invalid_argument(err_msg)
+                } else if err_msg.contains("denied by policy") {
+                    Status::permission_denied(err_msg)
+                } else if err_msg.contains("Cannot place model") {
+                    Status::failed_precondition(err_msg)
+                } else if err_msg.contains("queued behind") {
+                    Status::resource_exhausted(err_msg)
                 } else if err_msg.contains("not found") {
                     Status::not_found(err_msg)
                 } else if err_msg.contains("Failed to parse") {
@@ -122,12 +140,59 @@ impl ActionControllerConnection for ActionControllerReceiver {
                 };
                 Err(grpc_status)
             }
+        }
+    }
+
+    /// Get a gRPC server for this receiver
+    ///
+    /// # Returns
+    ///
+    /// A configured ActionControllerConnectionServer
+    pub fn into_service(self) -> ActionControllerConnectionServer<Self> {
+        ActionControllerConnectionServer::new(self)
+    }
+}
+
+#[tonic::async_trait]
+impl ActionControllerConnection for ActionControllerReceiver {
+    /// Handle trigger action requests from FilterGateway
+    ///
+    /// # Arguments
+    ///
+    /// * `request` - gRPC request containing scenario name to trigger
+    ///
+    /// # Returns
+    ///
+    /// * `Response<TriggerActionResponse>` - gRPC response with status and description
+    /// * `Status` - gRPC status error if the request fails
+    async fn trigger_action(
+        &self,
+        request: Request<TriggerActionRequest>,
+    ) -> Result<Response<TriggerActionResponse>, Status> {
+        use std::time::Instant;
+        let start = Instant::now();
+
+        logd!(1, "trigger_action in grpc receiver");
+
+        let req = request.into_inner();
+        let scenario_name = req.scenario_name;
+        logd!(2, "trigger_action scenario: {}", scenario_name);
+        validate_scenario(&scenario_name).await?;
+
+        let result = if req.idempotency_key.is_empty() {
+            self.execute_trigger(&scenario_name).await
+        } else {
+            // Keys are only unique per scenario
+            let key = format!("{}/{}", scenario_name, req.idempotency_key);
+            self.triggers
+                .run(&key, || self.execute_trigger(&scenario_name))
+                .await
         };
 
         let elapsed = start.elapsed();
         logd!(1, "trigger_action: elapsed = {:?}", elapsed);
 
-        result
+        result.map(Response::new)
     }
 
     /// Handle reconcile requests from StateManager
@@ -147,14 +212,17 @@ impl ActionControllerConnection for ActionControllerReceiver {
         // TODO: Implementation
         let req = request.into_inner();
         let scenario_name = req.scenario_name;
+        let transaction_id = req.transaction_id;
 
         let current = i32_to_status(req.current);
         let desired = i32_to_status(req.desired);
 
         if current == desired {
+            let desc = "Current and desired states are equal".to_string();
+            self.acknowledge(&transaction_id, true, &desc);
             return Ok(Response::new(ReconcileResponse {
                 status: 0, // Success
-                desc: "Current and desired states are equal".to_string(),
+                desc,
             }));
         }
 
@@ -163,15 +231,21 @@ impl ActionControllerConnection for ActionControllerReceiver {
             .reconcile_do(scenario_name, current, desired)
             .await
         {
-            Ok(_) => Ok(Response::new(ReconcileResponse {
-                status: 0, // Success
-                desc: "Reconciliation completed successfully".to_string(),
-            })),
+            Ok(_) => {
+                let desc = "Reconciliation completed successfully".to_string();
+                self.acknowledge(&transaction_id, true, &desc);
+                Ok(Response::new(ReconcileResponse {
+                    status: 0, // Success
+                    desc,
+                }))
+            }
             // If reconcile_do returns an error, convert it into a gRPC Status::internal error
             // and propagate it. This allows gRPC clients to receive a proper error status.
             Err(e) => {
                 logd!(5, "Reconciliation failed: {:?}", e); // Log the error for debugging
-                Err(Status::internal(format!("Failed to reconcile: {}", e)))
+                let desc = format!("Failed to reconcile: {}", e);
+                self.acknowledge(&transaction_id, false, &desc);
+                Err(Status::internal(desc))
             }
         }
     }
@@ -189,6 +263,114 @@ impl ActionControllerConnection for ActionControllerReceiver {
         let response = CompleteNetworkSettingResponse { acknowledged: true };
         Ok(Response::new(response))
     }
+
+    /// Handle drain requests before the maintenance of a node
+    ///
+    /// # Arguments
+    ///
+    /// * `request` - gRPC request containing the node to drain
+    ///
+    /// # Returns
+    ///
+    /// * `Response<DrainNodeResponse>` - Models stopped and models left running
+    /// * `Status` - gRPC status error if the node name is empty or the drain
+    ///   could not start
+    async fn drain_node(
+        &self,
+        request: Request<DrainNodeRequest>,
+    ) -> Result<Response<DrainNodeResponse>, Status> {
+        let node_name = request.into_inner().node_name;
+        if node_name.trim().is_empty() {
+            return Err(Status::invalid_argument(
+                "Invalid node name: cannot be empty",
+            ));
+        }
+        logd!(3, "drain_node: {}", node_name);
+
+        match self.manager.drain_node(&node_name).await {
+            Ok(report) => Ok(Response::new(DrainNodeResponse {
+                stopped: report.stopped,
+                failed: report
+                    .failed
+                    .into_iter()
+                    .map(|(model, e)| format!("{}: {}", model, e))
+                    .collect(),
+            })),
+            Err(e) => Err(Status::internal(format!(
+                "Failed to drain node '{}': {}",
+                node_name, e
+            ))),
+        }
+    }
+
+    /// Handle requests to make a drained node schedulable again
+    ///
+    /// # Arguments
+    ///
+    /// * `request` - gRPC request containing the node to uncordon
+    ///
+    /// # Returns
+    ///
+    /// * `Response<UncordonNodeResponse>` - gRPC response with status and description
+    /// * `Status` - gRPC status error if the node name is empty
+    async fn uncordon_node(
+        &self,
+        request: Request<UncordonNodeRequest>,
+    ) -> Result<Response<UncordonNodeResponse>, Status> {
+        let node_name = request.into_inner().node_name;
+        if node_name.trim().is_empty() {
+            return Err(Status::invalid_argument(
+                "Invalid node name: cannot be empty",
+            ));
+        }
+
+        self.manager.uncordon_node(&node_name).await;
+        Ok(Response::new(UncordonNodeResponse {
+            status: 0,
+            desc: format!("Node '{}' is schedulable", node_name),
+        }))
+    }
+
+    /// Handle the cancel of a delayed action when its scenario is withdrawn
+    ///
+    /// # Arguments
+    ///
+    /// * `request` - gRPC request containing the scenario name
+    ///
+    /// # Returns
+    ///
+    /// * `Response<CancelScheduledResponse>` - Whether an action was pending
+    /// * `Status` - gRPC status error if the scenario name is empty
+    async fn cancel_scheduled(
+        &self,
+        request: Request<CancelScheduledRequest>,
+    ) -> Result<Response<CancelScheduledResponse>, Status> {
+        let scenario_name = request.into_inner().scenario_name;
+        if scenario_name.trim().is_empty() {
+            return Err(Status::invalid_argument(
+                "Invalid scenario name: cannot be empty",
+            ));
+        }
+
+        let cancelled = self.manager.cancel_scheduled(&scenario_name).await;
+        Ok(Response::new(CancelScheduledResponse { cancelled }))
+    }
+}
+
+/// Reject triggers for an empty scenario name or one unknown to etcd
+async fn validate_scenario(scenario_name: &str) -> Result<(), Status> {
+    if scenario_name.trim().is_empty() {
+        return Err(Status::invalid_argument(
+            "Invalid scenario name: cannot be empty",
+        ));
+    }
+    match common::etcd::get(&common::keys::scenario(scenario_name)).await {
+        Ok(_) => Ok(()),
+        Err(e) => Err(Status::not_found(format!(
+            "Scenario '{}' not found: {}",
+            scenario_name, e
+        ))),
+    }
 }
 
 fn i32_to_status(value: i32) -> ActionStatus {
@@ -289,12 +471,28 @@ mod tests {
 
         let request = Request::new(TriggerActionRequest {
             scenario_name: "invalid_scenario".to_string(),
+            idempotency_key: "key-1".to_string(),
         });
 
         let response = receiver.trigger_action(request).await.unwrap_err();
+        assert_eq!(response.code(), tonic::Code::NotFound);
         assert!(response.message().contains("not found"));
     }
 
+    #[tokio::test]
+    async fn test_trigger_action_empty_scenario() {
+        let manager = Arc::new(ActionControllerManager::new());
+        let receiver = ActionControllerReceiver::new(manager);
+
+        let request = Request::new(TriggerActionRequest {
+            scenario_name: " ".to_string(),
+            idempotency_key: String::new(),
+        });
+
+        let response = receiver.trigger_action(request).await.unwrap_err();
+        assert_eq!(response.code(), tonic::Code::InvalidArgument);
+    }
+
     #[tokio::test]
     async fn test_reconcile_when_states_equal() {
         let manager = Arc::new(ActionControllerManager::new());
@@ -304,6 +502,7 @@ mod tests {
             scenario_name: "test_scenario".to_string(),
             current: 3, // RUNNING
             desired: 3, // RUNNING
+            transaction_id: "reconcile_test_scenario_1".to_string(),
         });
 
         let response = receiver.reconcile(request).await.unwrap();
@@ -368,6 +567,7 @@ mod tests {
             scenario_name: "invalid_scenario".to_string(),
             current: 0,
             desired: 3,
+            transaction_id: String::new(),
         });
 
         let response = receiver.reconcile(request).await.unwrap_err();
@@ -440,6 +640,47 @@ mod tests {
         assert_eq!(i32_to_status(-1), ActionStatus::Unknown);
     }
 
+    #[tokio::test]
+    async fn test_drain_node_empty_name() {
+        let manager = Arc::new(ActionControllerManager::new());
+        let receiver = ActionControllerReceiver::new(manager);
+
+        let request = Request::new(DrainNodeRequest {
+            node_name: String::new(),
+        });
+
+        let response = receiver.drain_node(request).await.unwrap_err();
+        assert_eq!(response.code(), tonic::Code::InvalidArgument);
+    }
+
+    #[tokio::test]
+    async fn test_uncordon_node_makes_node_schedulable() {
+        let manager = Arc::new(ActionControllerManager::new());
+        manager.cordon_node("HPC").await;
+        let receiver = ActionControllerReceiver::new(manager.clone());
+
+        let request = Request::new(UncordonNodeRequest {
+            node_name: "HPC".to_string(),
+        });
+
+        let response = receiver.uncordon_node(request).await.unwrap();
+        assert_eq!(response.get_ref().status, 0);
+        assert!(manager.is_schedulable("HPC"));
+    }
+
+    #[tokio::test]
+    async fn test_cancel_scheduled_empty_name() {
+        let manager = Arc::new(ActionControllerManager::new());
+        let receiver = ActionControllerReceiver::new(manager);
+
+        let request = Request::new(CancelScheduledRequest {
+            scenario_name: String::new(),
+        });
+
+        let response = receiver.cancel_scheduled(request).await.unwrap_err();
+        assert_eq!(response.code(), tonic::Code::InvalidArgument);
+    }
+
     #[test]
     fn test_receiver_new_and_into_service() {
         let manager = Arc::new(ActionControllerManager::new());
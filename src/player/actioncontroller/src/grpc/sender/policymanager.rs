@@ -5,7 +5,9 @@
 use common::logd;
 use common::policymanager::{
     policy_manager_connection_client::PolicyManagerConnectionClient, CheckPolicyRequest,
+    DecideRequest, DecideResponse, PolicyContext,
 };
+use common::rpc;
 use common::Result;
 
 /// Check if a scenario is allowed by policy
@@ -16,6 +18,7 @@ use common::Result;
 /// # Arguments
 ///
 /// * `scenario_name` - The name of the scenario to check
+/// * `context` - Node types, running scenarios and ASIL level of the scenario
 ///
 /// # Returns
 ///
@@ -27,54 +30,69 @@ use common::Result;
 /// Returns an error if:
 /// - The connection to PolicyManager is not established
 /// - The gRPC request fails (e.g., PolicyManager returns a gRPC Status error)
-/// - The policy check fails (application-level failure indicated by gRPC Status)
-#[allow(dead_code)]
-pub async fn check_policy(scenario_name: String) -> Result<()> {
-    // Change return type
+/// - A policy denies the scenario; the message contains "denied by policy"
+pub async fn check_policy(scenario_name: String, context: PolicyContext) -> Result<()> {
     if scenario_name.trim().is_empty() {
         return Err("Invalid scenario name: cannot be empty".into());
     }
 
-    let addr = common::policymanager::connect_server();
-    let mut client = PolicyManagerConnectionClient::connect(addr)
+    let channel = rpc::connect(common::policymanager::connect_server())
         .await
         .map_err(|e| format!("Failed to connect to PolicyManager: {}", e))?;
+    let mut client = PolicyManagerConnectionClient::new(channel);
 
-    let request = tonic::Request::new(CheckPolicyRequest {
+    let request = CheckPolicyRequest {
         scenario_name: scenario_name.clone(),
-    }); // Clone scenario_name if needed later for error messages
-    let response = client.check_policy(request).await?;
-    let response_inner = response.into_inner();
+        context: Some(context),
+    };
+    let response = rpc::call(request, |req| client.check_policy(req))
+        .await?
+        .into_inner();
 
-    // Check application-level status from the response payload *only if* the gRPC call was successful
-    if response_inner.status == 0 {
+    if response.status == 0 {
         logd!(
             2,
             "Policy check successful for '{}': {}",
             scenario_name,
-            response_inner.desc
+            response.desc
         );
-        Ok(()) // Policy passed
+        Ok(())
     } else {
-        // This block would only be reached if the server sent a successful gRPC status (OK)
-        // but included an application-level error code (non-0 status) in the payload.
-        // Given our recommended `receiver.rs`, this path should ideally not be taken for errors.
-        // It's more robust to rely on the gRPC `Status` for errors.
         logd!(
-            5,
+            4,
             "Policy check failed for '{}' (Application Status: {}): {}",
             scenario_name,
-            response_inner.status,
-            response_inner.desc
+            response.status,
+            response.desc
         );
         Err(format!(
-            "Policy check failed for scenario '{}' with status {}: {}",
-            scenario_name, response_inner.status, response_inner.desc
+            "Scenario '{}' denied by policy: {}",
+            scenario_name, response.desc
         )
         .into())
     }
 }
 
+/// Ask PolicyManager how a scenario competes with the running ones
+///
+/// # Arguments
+///
+/// * `request` - Scenario about to start, with its priority and workloads
+///
+/// # Returns
+///
+/// * `Ok(DecideResponse)` - Allow, Queue, or Preempt with the workloads to stop first
+/// * `Err(...)` if PolicyManager cannot be reached or rejects the request
+pub async fn decide(request: DecideRequest) -> Result<DecideResponse> {
+    let channel = rpc::connect(common::policymanager::connect_server())
+        .await
+        .map_err(|e| format!("Failed to connect to PolicyManager: {}", e))?;
+    let mut client = PolicyManagerConnectionClient::new(channel);
+
+    let response = rpc::call(request, |req| client.decide(req)).await?;
+    Ok(response.into_inner())
+}
+
 // ===========================
 // UNIT TESTS
 // ===========================
@@ -87,7 +105,7 @@ mod tests {
     // async fn test_check_policy_success() {
     //     let scenario_name = "antipinch-enable".to_string();
 
-    //     let result = check_policy(scenario_name).await;
+    //     let result = check_policy(scenario_name, PolicyContext::default()).await;
     //     if let Err(ref e) = result {
     //         logd!(5, "Error in test_check_policy_success: {:?}", e);
     //     } else {
@@ -101,7 +119,7 @@ mod tests {
         // Sending invalid scenario_name to simulate policy check failure
         let scenario_name = "".to_string(); // Empty string is invalid
 
-        let result = check_policy(scenario_name).await;
+        let result = check_policy(scenario_name, PolicyContext::default()).await;
         assert!(result.is_err());
     }
 }
@@ -13,11 +13,12 @@
 //! confirmations, and error conditions back to the StateManager for proper resource
 //! state tracking and recovery management.
 
+use common::rpc;
 use common::statemanager::{
-    connect_server, state_manager_connection_client::StateManagerConnectionClient, ResourceType,
-    StateChange, StateChangeResponse,
+    connect_server, state_manager_connection_client::StateManagerConnectionClient, Ack,
+    AckResponse, ResourceType, StateChange, StateChangeResponse,
 };
-use tonic::{Request, Status};
+use tonic::Status;
 
 /// StateManager gRPC client for ActionController component.
 ///
@@ -100,9 +101,9 @@ impl StateManagerSender {
     /// - Add connection pooling for high-throughput scenarios
     async fn ensure_connected(&mut self) -> Result<(), Status> {
         if self.client.is_none() {
-            match StateManagerConnectionClient::connect(connect_server()).await {
-                Ok(client) => {
-                    self.client = Some(client);
+            match rpc::connect(connect_server()).await {
+                Ok(channel) => {
+                    self.client = Some(StateManagerConnectionClient::new(channel));
                     Ok(())
                 }
                 Err(e) => Err(Status::unknown(format!(
@@ -202,7 +203,7 @@ impl StateManagerSender {
 
         if let Some(client) = &mut self.client {
             // Send the state change message via gRPC
-            client.send_state_change(Request::new(state_change)).await
+            rpc::call(state_change, |request| client.send_state_change(request)).await
         } else {
             // This should never happen due to ensure_connected, but provide safety fallback
             Err(Status::unknown("Client not connected"))
@@ -256,6 +257,7 @@ impl StateManagerSender {
             transition_id: transition_id.to_string(),
             timestamp_ns: timestamp,
             source: "actioncontroller".to_string(),
+            trace_id: String::new(),
         };
 
         self.send_state_change(state_change).await
@@ -308,6 +310,7 @@ impl StateManagerSender {
             transition_id: format!("error-{}", transition_id), // Unique ID for error transition
             timestamp_ns: timestamp,
             source: "actioncontroller".to_string(),
+            trace_id: String::new(),
         };
 
         self.send_state_change(state_change).await
@@ -360,10 +363,46 @@ impl StateManagerSender {
             transition_id: format!("recovery-{}", recovery_id),
             timestamp_ns: timestamp,
             source: "actioncontroller".to_string(),
+            trace_id: String::new(),
         };
 
         self.send_state_change(state_change).await
     }
+
+    /// Acknowledges a reconcile request of the StateManager with its outcome.
+    ///
+    /// The StateManager sends a reconcile request again until it is
+    /// acknowledged, so every request carrying a transaction ID must be
+    /// acknowledged once it was handled.
+    ///
+    /// # Arguments
+    /// * `transaction_id` - Transaction ID of the reconcile request
+    /// * `success` - Whether the reconciliation succeeded
+    /// * `details` - Outcome description or error details
+    ///
+    /// # Returns
+    /// * `Result<tonic::Response<AckResponse>, Status>` - StateManager response
+    pub async fn send_ack(
+        &mut self,
+        transaction_id: &str,
+        success: bool,
+        details: &str,
+    ) -> Result<tonic::Response<AckResponse>, Status> {
+        self.ensure_connected().await?;
+
+        let ack = Ack {
+            transaction_id: transaction_id.to_string(),
+            success,
+            details: details.to_string(),
+            source: "actioncontroller".to_string(),
+        };
+
+        if let Some(client) = &mut self.client {
+            rpc::call(ack, |request| client.send_ack(request)).await
+        } else {
+            Err(Status::unknown("Client not connected"))
+        }
+    }
 }
 
 // ========================================
@@ -411,6 +450,7 @@ mod tests {
             transition_id: format!("update-complete-{}", timestamp),
             timestamp_ns: timestamp,
             source: "actioncontroller".to_string(),
+            trace_id: String::new(),
         };
 
         // Send the message and verify successful response
@@ -2,13 +2,16 @@
 * SPDX-FileCopyrightText: Copyright 2024 LG Electronics Inc.
 * SPDX-License-Identifier: Apache-2.0
 */
-use common::logd;
 use common::logd::logger;
 use std::error::Error;
+use tracing::{debug, error, info, trace};
 
 mod grpc;
 mod manager;
+mod placement;
+mod restart;
 mod runtime;
+mod scheduler;
 
 /// Initialize the ActionController component
 ///
@@ -32,24 +35,23 @@ async fn initialize(skip_grpc: bool) -> Result<(), Box<dyn Error>> {
     let node_type = &config.host.r#type;
 
     if node_type == "bluechi" {
-        logd!(
-            5,
+        error!(
             "{} is set bluechi_nodes. Bluechi is not supported.",
             hostname
         );
-        //logd!(2, "Adding {} to bluechi_nodes from settings.yaml", hostname);
+        //debug!("Adding {} to bluechi_nodes from settings.yaml", hostname);
         //manager.bluechi_nodes.push(hostname.clone());
+    } else if node_type == "podman" {
+        debug!("Adding {} to podman_nodes from settings.yaml", hostname);
+        manager.podman_nodes.push(hostname.clone());
     } else {
-        logd!(
-            2,
-            "Adding {} to nodeagent_nodes from settings.yaml",
-            hostname
-        );
+        debug!("Adding {} to nodeagent_nodes from settings.yaml", hostname);
         manager.nodeagent_nodes.push(hostname.clone());
     }
 
     // gRPC 서버 초기화 (테스트 모드가 아닌 경우)
     if !skip_grpc {
+        manager.load_cordoned_nodes().await;
         grpc::init(manager).await?;
     }
 
@@ -69,8 +71,9 @@ async fn initialize(skip_grpc: bool) -> Result<(), Box<dyn Error>> {
 /// critical error during operation.
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn Error>> {
+    common::logging::init("actioncontroller");
     let _ = logger::init_async_logger("actioncontroller").await;
-    logd!(1, "initiailize action controller");
+    trace!("initiailize action controller");
 
     // Initialize the controller
     initialize(false).await?;
@@ -79,7 +82,7 @@ async fn main() -> Result<(), Box<dyn Error>> {
 
     // Keep the application running
     tokio::signal::ctrl_c().await?;
-    logd!(3, "Shutting down ActionController...");
+    info!("Shutting down ActionController...");
 
     Ok(())
 }
@@ -0,0 +1,253 @@
+/*
+* SPDX-FileCopyrightText: Copyright 2024 LG Electronics Inc.
+* SPDX-License-Identifier: Apache-2.0
+*/
+//! Placement of scenario workloads on nodes of the required type and role
+//!
+//! A scenario may require the nodes running its models to be of a given
+//! type (`cloud` or `vehicle`) and role (`master`, `nodeagent` or
+//! `bluechi`) through its annotations. Each model names its node, so
+//! placement checks that node against the requirement before launching.
+use common::apiserver::NodeInfo;
+use common::nodeagent::fromapiserver::{NodeRole, NodeType};
+use common::spec::artifact::scenario::{NODE_ROLE_ANNOTATION, NODE_TYPE_ANNOTATION};
+use common::spec::artifact::Scenario;
+
+/// Node type and role a scenario requires, `None` when any is accepted
+#[derive(Debug, Default, PartialEq)]
+pub struct Placement {
+    node_type: Option<NodeType>,
+    node_role: Option<NodeRole>,
+}
+
+impl Placement {
+    /// Requirement set by the annotations of a scenario
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if an annotation names an unknown type or role
+    pub fn from_scenario(scenario: &Scenario) -> Result<Self, String> {
+        let node_type = scenario
+            .get_annotation(NODE_TYPE_ANNOTATION)
+            .map(|value| {
+                NodeType::from_str_name(&format!("NODE_TYPE_{}", value.to_uppercase()))
+                    .filter(|t| *t != NodeType::Unspecified)
+                    .ok_or_else(|| {
+                        format!("Unknown node type '{}' in {}", value, NODE_TYPE_ANNOTATION)
+                    })
+            })
+            .transpose()?;
+        let node_role = scenario
+            .get_annotation(NODE_ROLE_ANNOTATION)
+            .map(|value| {
+                NodeRole::from_str_name(&format!("NODE_ROLE_{}", value.to_uppercase()))
+                    .filter(|r| *r != NodeRole::Unspecified)
+                    .ok_or_else(|| {
+                        format!("Unknown node role '{}' in {}", value, NODE_ROLE_ANNOTATION)
+                    })
+            })
+            .transpose()?;
+        Ok(Self {
+            node_type,
+            node_role,
+        })
+    }
+
+    /// Whether the scenario runs on any node
+    pub fn is_unconstrained(&self) -> bool {
+        self.node_type.is_none() && self.node_role.is_none()
+    }
+
+    /// Whether a registered node meets the requirement
+    pub fn accepts(&self, node: &NodeInfo) -> bool {
+        self.node_type.is_none_or(|t| node.node_type == t as i32)
+            && self.node_role.is_none_or(|r| node.node_role == r as i32)
+    }
+
+    /// Checks that a model may run on `node_name`
+    ///
+    /// # Arguments
+    ///
+    /// * `model_name` - Model to place
+    /// * `node_name` - Node the model names
+    /// * `nodes` - Registered nodes
+    ///
+    /// # Errors
+    ///
+    /// Returns which requirement the node misses, and the registered nodes
+    /// that would meet it
+    pub fn check(
+        &self,
+        model_name: &str,
+        node_name: &str,
+        nodes: &[NodeInfo],
+    ) -> Result<(), String> {
+        if self.is_unconstrained() {
+            return Ok(());
+        }
+        let node = nodes.iter().find(|node| node.hostname == node_name);
+        if node.is_some_and(|node| self.accepts(node)) {
+            return Ok(());
+        }
+
+        let reason = match node {
+            Some(node) => format!(
+                "node '{}' is {}/{}",
+                node_name,
+                type_name(node.node_type),
+                role_name(node.node_role)
+            ),
+            None => format!("node '{}' is not registered", node_name),
+        };
+        let matching: Vec<&str> = nodes
+            .iter()
+            .filter(|node| self.accepts(node))
+            .map(|node| node.hostname.as_str())
+            .collect();
+        let candidates = if matching.is_empty() {
+            "no registered node matches".to_string()
+        } else {
+            format!("matching nodes: {}", matching.join(", "))
+        };
+        Err(format!(
+            "Cannot place model '{}': requires {} but {}; {}",
+            model_name, self, reason, candidates
+        ))
+    }
+}
+
+impl std::fmt::Display for Placement {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let node_type = self.node_type.map_or("any", |t| type_name(t as i32));
+        let node_role = self.node_role.map_or("any", |r| role_name(r as i32));
+        write!(f, "{}/{}", node_type, node_role)
+    }
+}
+
+/// Lowercase name of a node type, as written in annotations
+fn type_name(node_type: i32) -> &'static str {
+    match NodeType::try_from(node_type) {
+        Ok(NodeType::Cloud) => "cloud",
+        Ok(NodeType::Vehicle) => "vehicle",
+        _ => "unspecified",
+    }
+}
+
+/// Lowercase name of a node role, as written in annotations
+fn role_name(node_role: i32) -> &'static str {
+    match NodeRole::try_from(node_role) {
+        Ok(NodeRole::Master) => "master",
+        Ok(NodeRole::Nodeagent) => "nodeagent",
+        Ok(NodeRole::Bluechi) => "bluechi",
+        _ => "unspecified",
+    }
+}
+
+//UNIT TEST
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn scenario(annotations: &str) -> Scenario {
+        serde_yaml::from_str(&format!(
+            r#"
+apiVersion: v1
+kind: Scenario
+metadata:
+  name: parking
+  annotations: {}
+spec:
+  action: launch
+  target: parking
+"#,
+            annotations
+        ))
+        .unwrap()
+    }
+
+    fn node(hostname: &str, node_type: NodeType, node_role: NodeRole) -> NodeInfo {
+        NodeInfo {
+            hostname: hostname.to_string(),
+            node_type: node_type as i32,
+            node_role: node_role as i32,
+            ..Default::default()
+        }
+    }
+
+    fn mixed_nodes() -> Vec<NodeInfo> {
+        vec![
+            node("HPC", NodeType::Vehicle, NodeRole::Nodeagent),
+            node("ZONE", NodeType::Vehicle, NodeRole::Bluechi),
+            node("cloud", NodeType::Cloud, NodeRole::Nodeagent),
+        ]
+    }
+
+    #[test]
+    fn test_from_scenario_parses_annotations() {
+        let placement = Placement::from_scenario(&scenario(
+            r#"{"io.piccolo.annotations.node-type": "Vehicle", "io.piccolo.annotations.node-role": "nodeagent"}"#,
+        ))
+        .unwrap();
+
+        assert_eq!(placement.node_type, Some(NodeType::Vehicle));
+        assert_eq!(placement.node_role, Some(NodeRole::Nodeagent));
+        assert!(Placement::from_scenario(&scenario("{}"))
+            .unwrap()
+            .is_unconstrained());
+    }
+
+    #[test]
+    fn test_from_scenario_rejects_unknown_values() {
+        let err =
+            Placement::from_scenario(&scenario(r#"{"io.piccolo.annotations.node-type": "edge"}"#))
+                .unwrap_err();
+
+        assert!(err.contains("Unknown node type 'edge'"));
+        assert!(Placement::from_scenario(&scenario(
+            r#"{"io.piccolo.annotations.node-role": "unspecified"}"#
+        ))
+        .is_err());
+    }
+
+    #[test]
+    fn test_vehicle_workload_not_placed_on_cloud_node() {
+        let placement = Placement::from_scenario(&scenario(
+            r#"{"io.piccolo.annotations.node-type": "vehicle"}"#,
+        ))
+        .unwrap();
+        let nodes = mixed_nodes();
+
+        assert!(placement.check("parking-core", "HPC", &nodes).is_ok());
+        assert!(placement.check("parking-core", "ZONE", &nodes).is_ok());
+        let err = placement
+            .check("parking-core", "cloud", &nodes)
+            .unwrap_err();
+        assert_eq!(
+            err,
+            "Cannot place model 'parking-core': requires vehicle/any but node 'cloud' is \
+             cloud/nodeagent; matching nodes: HPC, ZONE"
+        );
+    }
+
+    #[test]
+    fn test_check_without_matching_node() {
+        let placement = Placement::from_scenario(&scenario(
+            r#"{"io.piccolo.annotations.node-type": "cloud", "io.piccolo.annotations.node-role": "bluechi"}"#,
+        ))
+        .unwrap();
+
+        let err = placement
+            .check("parking-core", "unknown", &mixed_nodes())
+            .unwrap_err();
+
+        assert!(err.contains("node 'unknown' is not registered"));
+        assert!(err.contains("no registered node matches"));
+    }
+
+    #[test]
+    fn test_unconstrained_placement_accepts_unregistered_nodes() {
+        let placement = Placement::default();
+
+        assert!(placement.check("parking-core", "HPC", &[]).is_ok());
+    }
+}
@@ -0,0 +1,297 @@
+/*
+* SPDX-FileCopyrightText: Copyright 2024 LG Electronics Inc.
+* SPDX-License-Identifier: Apache-2.0
+*/
+//! Restart policy of the workloads started again on reconcile
+//!
+//! A workload reported stopped is started again according to the
+//! `restartPolicy` of its pod. Restarts in a row are spaced by an
+//! exponential backoff, so a crash-looping workload does not hammer its
+//! node; a workload that stayed up long enough starts over from no delay.
+use common::spec::k8s::pod::RestartPolicy;
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// What to do with a workload reported stopped
+#[derive(Debug, PartialEq)]
+pub enum Decision {
+    /// Start it now
+    Restart,
+    /// Start it once the delay has passed
+    Delay(Duration),
+    /// A delayed restart is already scheduled
+    Scheduled,
+    /// Leave it stopped, as its restart policy says
+    Skip,
+}
+
+/// Restarts of one workload since it was last stable
+struct Restarts {
+    /// Restarts in a row
+    count: u32,
+    /// Time of the last restart, in the future while one is scheduled
+    last: Instant,
+}
+
+/// Restart counts of the workloads, by model name
+pub struct RestartTracker {
+    workloads: Mutex<HashMap<String, Restarts>>,
+    /// Delay before the second restart in a row, doubled for each next one
+    base: Duration,
+    /// Longest delay between two restarts
+    cap: Duration,
+    /// Time a workload must stay up for its restart count to be reset
+    reset_after: Duration,
+}
+
+impl RestartTracker {
+    pub fn new(base: Duration, cap: Duration, reset_after: Duration) -> Self {
+        Self {
+            workloads: Mutex::new(HashMap::new()),
+            base,
+            cap,
+            reset_after,
+        }
+    }
+
+    /// Tracker with the delays set in `actioncontroller` settings
+    pub fn from_settings() -> Self {
+        let settings = &common::setting::get_config().actioncontroller;
+        Self::new(
+            Duration::from_secs(settings.restart_backoff_secs),
+            Duration::from_secs(settings.restart_backoff_max_secs),
+            Duration::from_secs(settings.restart_reset_secs),
+        )
+    }
+
+    /// Delay after `count` restarts in a row before the next one
+    ///
+    /// The first restart is immediate, then the delay doubles from `base`
+    /// up to `cap`.
+    pub fn backoff(&self, count: u32) -> Duration {
+        if count == 0 {
+            return Duration::ZERO;
+        }
+        let factor = 2u32.checked_pow(count - 1).unwrap_or(u32::MAX);
+        self.base
+            .checked_mul(factor)
+            .unwrap_or(self.cap)
+            .min(self.cap)
+    }
+
+    /// Decides whether a stopped workload is started again
+    ///
+    /// # Arguments
+    ///
+    /// * `model` - Name of the workload
+    /// * `policy` - Restart policy of its pod
+    /// * `failed` - Whether it stopped on a failure
+    /// * `now` - Current time
+    pub fn decide(
+        &self,
+        model: &str,
+        policy: RestartPolicy,
+        failed: bool,
+        now: Instant,
+    ) -> Decision {
+        let restart = match policy {
+            RestartPolicy::Always => true,
+            RestartPolicy::OnFailure => failed,
+            RestartPolicy::Never => false,
+        };
+        if !restart {
+            return Decision::Skip;
+        }
+
+        let mut workloads = self.workloads.lock().unwrap();
+        if workloads
+            .get(model)
+            .is_some_and(|r| now.saturating_duration_since(r.last) >= self.reset_after)
+        {
+            // Stayed up since its last restart
+            workloads.remove(model);
+        }
+        let Some(restarts) = workloads.get(model) else {
+            return Decision::Restart;
+        };
+        if restarts.last > now {
+            return Decision::Scheduled;
+        }
+        let due = restarts.last + self.backoff(restarts.count);
+        if due <= now {
+            Decision::Restart
+        } else {
+            Decision::Delay(due - now)
+        }
+    }
+
+    /// Counts a restart of `model` done, or scheduled, at `at`
+    pub fn record(&self, model: &str, at: Instant) {
+        let mut workloads = self.workloads.lock().unwrap();
+        let restarts = workloads
+            .entry(model.to_string())
+            .or_insert(Restarts { count: 0, last: at });
+        restarts.count += 1;
+        restarts.last = at;
+    }
+
+    /// Restarts of `model` in a row
+    pub fn count(&self, model: &str) -> u32 {
+        self.workloads
+            .lock()
+            .unwrap()
+            .get(model)
+            .map_or(0, |r| r.count)
+    }
+
+    /// Drops the restart count of a workload stopped on purpose
+    pub fn forget(&self, model: &str) {
+        self.workloads.lock().unwrap().remove(model);
+    }
+}
+
+/// Restart policy named by a pod, `OnFailure` when it sets none
+pub fn parse_policy(policy: Option<&str>) -> RestartPolicy {
+    match policy {
+        Some("Always") => RestartPolicy::Always,
+        Some("Never") => RestartPolicy::Never,
+        _ => RestartPolicy::OnFailure,
+    }
+}
+
+//UNIT TEST
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tracker() -> RestartTracker {
+        RestartTracker::new(
+            Duration::from_secs(10),
+            Duration::from_secs(60),
+            Duration::from_secs(600),
+        )
+    }
+
+    #[test]
+    fn test_backoff_doubles_up_to_cap() {
+        let tracker = tracker();
+
+        assert_eq!(tracker.backoff(0), Duration::ZERO);
+        assert_eq!(tracker.backoff(1), Duration::from_secs(10));
+        assert_eq!(tracker.backoff(2), Duration::from_secs(20));
+        assert_eq!(tracker.backoff(3), Duration::from_secs(40));
+        assert_eq!(tracker.backoff(4), Duration::from_secs(60));
+        assert_eq!(tracker.backoff(64), Duration::from_secs(60));
+    }
+
+    #[test]
+    fn test_policy_decides_restart() {
+        let tracker = tracker();
+        let now = Instant::now();
+
+        assert_eq!(
+            tracker.decide("a", RestartPolicy::Never, true, now),
+            Decision::Skip
+        );
+        assert_eq!(
+            tracker.decide("a", RestartPolicy::OnFailure, false, now),
+            Decision::Skip
+        );
+        assert_eq!(
+            tracker.decide("a", RestartPolicy::OnFailure, true, now),
+            Decision::Restart
+        );
+        assert_eq!(
+            tracker.decide("a", RestartPolicy::Always, false, now),
+            Decision::Restart
+        );
+    }
+
+    #[test]
+    fn test_backoff_grows_on_repeated_failures() {
+        let tracker = tracker();
+        let start = Instant::now();
+
+        assert_eq!(
+            tracker.decide("a", RestartPolicy::Always, true, start),
+            Decision::Restart
+        );
+        tracker.record("a", start);
+
+        // Crashes again right away
+        let now = start + Duration::from_secs(1);
+        assert_eq!(
+            tracker.decide("a", RestartPolicy::Always, true, now),
+            Decision::Delay(Duration::from_secs(9))
+        );
+        let second = start + Duration::from_secs(10);
+        tracker.record("a", second);
+        assert_eq!(
+            tracker.decide("a", RestartPolicy::Always, true, now),
+            Decision::Scheduled
+        );
+
+        let now = second + Duration::from_secs(1);
+        assert_eq!(
+            tracker.decide("a", RestartPolicy::Always, true, now),
+            Decision::Delay(Duration::from_secs(19))
+        );
+        tracker.record("a", second + Duration::from_secs(20));
+        assert_eq!(tracker.count("a"), 3);
+
+        // Other workloads are not delayed
+        assert_eq!(
+            tracker.decide("b", RestartPolicy::Always, true, now),
+            Decision::Restart
+        );
+    }
+
+    #[test]
+    fn test_restart_count_resets_after_stability() {
+        let tracker = tracker();
+        let start = Instant::now();
+        for i in 0..4 {
+            tracker.record("a", start + Duration::from_secs(i));
+        }
+        let last = start + Duration::from_secs(3);
+
+        assert!(matches!(
+            tracker.decide(
+                "a",
+                RestartPolicy::Always,
+                true,
+                last + Duration::from_secs(1)
+            ),
+            Decision::Delay(_)
+        ));
+        assert_eq!(
+            tracker.decide(
+                "a",
+                RestartPolicy::Always,
+                true,
+                last + Duration::from_secs(600)
+            ),
+            Decision::Restart
+        );
+        assert_eq!(tracker.count("a"), 0);
+    }
+
+    #[test]
+    fn test_forget_drops_count() {
+        let tracker = tracker();
+        tracker.record("a", Instant::now());
+
+        tracker.forget("a");
+
+        assert_eq!(tracker.count("a"), 0);
+    }
+
+    #[test]
+    fn test_parse_policy() {
+        assert_eq!(parse_policy(Some("Always")), RestartPolicy::Always);
+        assert_eq!(parse_policy(Some("Never")), RestartPolicy::Never);
+        assert_eq!(parse_policy(Some("OnFailure")), RestartPolicy::OnFailure);
+        assert_eq!(parse_policy(None), RestartPolicy::OnFailure);
+    }
+}
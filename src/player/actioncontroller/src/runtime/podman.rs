@@ -0,0 +1,699 @@
+/*
+* SPDX-FileCopyrightText: Copyright 2024 LG Electronics Inc.
+* SPDX-License-Identifier: Apache-2.0
+*/
+//! Runtime implementation driving the local podman service directly
+//!
+//! Used on hosts running neither Bluechi nor NodeAgent, e.g. small test
+//! benches. Workloads are started from their generated kube YAML with the
+//! libpod REST API (`play kube`), and stopped by stopping and removing the
+//! pod. A pod annotated with a network joins it, the network being created
+//! and removed with the pods using it as NodeAgent does on its nodes.
+//! hostPath volumes are resolved and created on the podman host; image
+//! volumes cannot be played and are rejected.
+use common::actioncontroller::PodStatus;
+use common::logd;
+use common::network::{self, NetworkApi, RefStore};
+use common::spec::artifact::{Artifact, Network};
+use common::spec::k8s::Pod;
+use common::Result;
+use hyper::body::Bytes;
+use hyper::{Body, Client, Method, Request, StatusCode, Uri};
+use std::path::Path;
+
+/// Version prefix of the libpod REST API
+const LIBPOD: &str = "/v4.0.0/libpod";
+
+/// Where the podman REST API is served
+#[derive(Debug, Clone, PartialEq)]
+enum Endpoint {
+    /// Path of a unix socket
+    Unix(String),
+    /// Base URL of a TCP listener, e.g. `http://127.0.0.1:8080`
+    Http(String),
+}
+
+/// Client of the libpod REST API
+#[derive(Debug, Clone)]
+pub struct PodmanClient {
+    endpoint: Endpoint,
+}
+
+impl PodmanClient {
+    /// Client of the service at `url`, either `unix:///path/to/podman.sock`
+    /// or an `http://` URL
+    pub fn new(url: &str) -> Self {
+        let endpoint = match url.strip_prefix("unix://") {
+            Some(socket) => Endpoint::Unix(socket.to_string()),
+            None => Endpoint::Http(url.trim_end_matches('/').to_string()),
+        };
+        Self { endpoint }
+    }
+
+    /// Client of the service set in `actioncontroller.podman_url`
+    pub fn from_settings() -> Self {
+        Self::new(&common::setting::get_config().actioncontroller.podman_url)
+    }
+
+    /// Send a request to the libpod API
+    ///
+    /// # Returns
+    ///
+    /// * `(StatusCode, Bytes)` - Status and body of the response
+    async fn request(&self, method: Method, path: &str, body: Body) -> Result<(StatusCode, Bytes)> {
+        let path = format!("{}{}", LIBPOD, path);
+        let response = match &self.endpoint {
+            Endpoint::Unix(socket) => {
+                let uri: Uri = hyperlocal::Uri::new(socket, &path).into();
+                let request = Request::builder().method(method).uri(uri).body(body)?;
+                Client::builder()
+                    .build::<_, Body>(hyperlocal::UnixConnector)
+                    .request(request)
+                    .await?
+            }
+            Endpoint::Http(base) => {
+                let uri: Uri = format!("{}{}", base, path).parse()?;
+                let request = Request::builder().method(method).uri(uri).body(body)?;
+                Client::new().request(request).await?
+            }
+        };
+        let status = response.status();
+        let body = hyper::body::to_bytes(response.into_body()).await?;
+        Ok((status, body))
+    }
+
+    /// Create and start the pod of a kube YAML, joining `network` if set
+    pub async fn play_kube(&self, pod_yaml: &str, network: Option<&str>) -> Result<()> {
+        let path = match network {
+            Some(network) => format!("/play/kube?network={}", urlencoding::encode(network)),
+            None => "/play/kube".to_string(),
+        };
+        let (status, body) = self
+            .request(Method::POST, &path, Body::from(pod_yaml.to_string()))
+            .await?;
+        if !status.is_success() {
+            return Err(api_error("play kube", status, &body).into());
+        }
+        Ok(())
+    }
+
+    /// Stop a pod; a pod already stopped or missing is not an error
+    pub async fn stop_pod(&self, name: &str) -> Result<()> {
+        let path = format!("/pods/{}/stop", name);
+        let (status, body) = self.request(Method::POST, &path, Body::empty()).await?;
+        match status {
+            s if s.is_success() || s == StatusCode::NOT_MODIFIED || s == StatusCode::NOT_FOUND => {
+                Ok(())
+            }
+            _ => Err(api_error("stop pod", status, &body).into()),
+        }
+    }
+
+    /// Remove a pod and its containers; a missing pod is not an error
+    pub async fn remove_pod(&self, name: &str) -> Result<()> {
+        let path = format!("/pods/{}?force=true", name);
+        let (status, body) = self.request(Method::DELETE, &path, Body::empty()).await?;
+        match status {
+            s if s.is_success() || s == StatusCode::NOT_FOUND => Ok(()),
+            _ => Err(api_error("remove pod", status, &body).into()),
+        }
+    }
+
+    /// State of a pod, `PodStatus::None` when it does not exist
+    pub async fn pod_status(&self, name: &str) -> Result<PodStatus> {
+        let path = format!("/pods/{}/json", name);
+        let (status, body) = self.request(Method::GET, &path, Body::empty()).await?;
+        if status == StatusCode::NOT_FOUND {
+            return Ok(PodStatus::None);
+        }
+        if !status.is_success() {
+            return Err(api_error("inspect pod", status, &body).into());
+        }
+        let inspect: serde_json::Value = serde_json::from_slice(&body)?;
+        let state = inspect["State"].as_str().unwrap_or_default();
+        Ok(pod_state_to_status(state))
+    }
+}
+
+#[tonic::async_trait]
+impl NetworkApi for PodmanClient {
+    async fn exists(&self, name: &str) -> std::result::Result<bool, network::Error> {
+        let path = format!("/networks/{}/json", name);
+        let (status, body) = self
+            .request(Method::GET, &path, Body::empty())
+            .await
+            .map_err(|e| e.to_string())?;
+        if status == StatusCode::NOT_FOUND {
+            return Ok(false);
+        }
+        if !status.is_success() {
+            return Err(api_error("inspect network", status, &body).into());
+        }
+        let inspect: serde_json::Value = serde_json::from_slice(&body)?;
+        Ok(inspect["name"].as_str() == Some(name))
+    }
+
+    async fn create(&self, network: &Network) -> std::result::Result<(), network::Error> {
+        let body = network::create_body(network).to_string();
+        let (status, body) = self
+            .request(Method::POST, "/networks/create", Body::from(body))
+            .await
+            .map_err(|e| e.to_string())?;
+        if !status.is_success() {
+            return Err(api_error("create network", status, &body).into());
+        }
+        Ok(())
+    }
+
+    async fn remove(&self, name: &str) -> std::result::Result<(), network::Error> {
+        let path = format!("/networks/{}", name);
+        let (status, body) = self
+            .request(Method::DELETE, &path, Body::empty())
+            .await
+            .map_err(|e| e.to_string())?;
+        match status {
+            s if s.is_success() || s == StatusCode::NOT_FOUND => Ok(()),
+            _ => Err(api_error("remove network", status, &body).into()),
+        }
+    }
+}
+
+/// Map the state of a podman pod to the common workload state
+fn pod_state_to_status(state: &str) -> PodStatus {
+    match state {
+        "Created" => PodStatus::Init,
+        "Paused" => PodStatus::Ready,
+        "Running" => PodStatus::Running,
+        "Exited" | "Stopped" => PodStatus::Done,
+        "Degraded" | "Dead" | "Error" => PodStatus::Failed,
+        _ => PodStatus::Unknown,
+    }
+}
+
+/// Error of a libpod call, with the message podman answered
+fn api_error(operation: &str, status: StatusCode, body: &[u8]) -> String {
+    let message = serde_json::from_slice::<serde_json::Value>(body)
+        .ok()
+        .and_then(|v| v["message"].as_str().map(str::to_string))
+        .unwrap_or_else(|| String::from_utf8_lossy(body).into_owned());
+    format!("podman {} failed with {}: {}", operation, status, message)
+}
+
+/// Name of the pod described by a kube YAML
+fn pod_name(pod: &str) -> Result<String> {
+    let pod: Pod = serde_yaml::from_str(pod)?;
+    Ok(pod.get_name())
+}
+
+/// Prepare the volumes of a kube YAML for the podman host
+///
+/// A relative hostPath, as Volume artifacts give by default, is resolved
+/// under `base_path`, and a hostPath without `type` gets `DirectoryOrCreate`
+/// so that podman creates the directory as NodeAgent does on its nodes. The
+/// directories are kept when the pod is removed.
+///
+/// # Errors
+///
+/// Returns an error if the pod uses an image volume, which `play kube`
+/// cannot mount
+fn prepare_volumes(pod: &str, base_path: &str) -> Result<String> {
+    let mut value: serde_yaml::Value = serde_yaml::from_str(pod)?;
+    let Some(volumes) = value
+        .get_mut("spec")
+        .and_then(|spec| spec.get_mut("volumes"))
+        .and_then(|volumes| volumes.as_sequence_mut())
+    else {
+        return Ok(pod.to_string());
+    };
+
+    for volume in volumes.iter_mut() {
+        let name = volume["name"].as_str().unwrap_or_default().to_string();
+        if volume.get("image").is_some() {
+            return Err(format!(
+                "volume '{}': image volumes are not supported on podman nodes",
+                name
+            )
+            .into());
+        }
+        let Some(host_path) = volume
+            .get_mut("hostPath")
+            .and_then(|host_path| host_path.as_mapping_mut())
+        else {
+            continue;
+        };
+        let path = host_path
+            .get("path")
+            .and_then(|path| path.as_str())
+            .unwrap_or(&name)
+            .to_string();
+        if !Path::new(&path).is_absolute() {
+            let resolved = Path::new(base_path).join(&path);
+            host_path.insert("path".into(), resolved.to_string_lossy().into());
+        }
+        if !host_path.contains_key("type") {
+            host_path.insert("type".into(), "DirectoryOrCreate".into());
+        }
+    }
+    Ok(serde_yaml::to_string(&value)?)
+}
+
+/// Release the network `pod` joined, logging a failure
+async fn release_network(client: &PodmanClient, refs: &impl RefStore, network: &str, pod: &str) {
+    if let Err(e) = network::detach(client, refs, network, pod).await {
+        logd!(
+            4,
+            "Failed to release network '{}' of {}: {}",
+            network,
+            pod,
+            e
+        );
+    }
+}
+
+/// Start the pod of a kube YAML and check that it did not fail right away
+///
+/// # Arguments
+///
+/// * `client` - podman service of the node
+/// * `refs` - Pods using each network of the node
+/// * `pod` - Kube YAML of the pod
+pub async fn start_workload(client: &PodmanClient, refs: &impl RefStore, pod: &str) -> Result<()> {
+    let joined = match network::pod_network(pod) {
+        Some(joined) => Some(network::load(&joined).await.map_err(|e| e.to_string())?),
+        None => None,
+    };
+    start_pod(client, refs, pod, joined.as_ref()).await
+}
+
+/// Start the pod of a kube YAML joining `joined`, if set
+///
+/// The network is released again if the pod cannot be played.
+async fn start_pod(
+    client: &PodmanClient,
+    refs: &impl RefStore,
+    pod: &str,
+    joined: Option<&Network>,
+) -> Result<()> {
+    let name = pod_name(pod)?;
+    let base_path = &common::setting::get_config()
+        .actioncontroller
+        .podman_volume_base_path;
+    let pod = prepare_volumes(pod, base_path)?;
+    if let Some(network) = joined {
+        network::attach(client, refs, network, &name)
+            .await
+            .map_err(|e| format!("Cannot set up network '{}': {}", network.get_name(), e))?;
+    }
+    let network_name = joined.map(|network| network.get_name());
+    let played = client
+        .play_kube(&pod, network_name.as_deref())
+        .await
+        .map_err(|e| e.to_string());
+    if let Err(e) = played {
+        if let Some(network_name) = &network_name {
+            release_network(client, refs, network_name, &name).await;
+        }
+        return Err(e.into());
+    }
+
+    let status = client.pod_status(&name).await?;
+    logd!(2, "podman pod {} is {:?}", name, status);
+    if status == PodStatus::Failed {
+        return Err(format!("podman pod {} failed to start", name).into());
+    }
+    Ok(())
+}
+
+/// Stop and remove the pod of a kube YAML, and the network it joined if no
+/// other pod uses it
+pub async fn stop_workload(client: &PodmanClient, refs: &impl RefStore, pod: &str) -> Result<()> {
+    let name = pod_name(pod)?;
+    client.stop_pod(&name).await?;
+    client.remove_pod(&name).await?;
+
+    if let Some(joined) = network::pod_network(pod) {
+        release_network(client, refs, &joined, &name).await;
+    }
+    Ok(())
+}
+
+/// Recreate the pod of a kube YAML, so changes to the YAML are applied
+pub async fn restart_workload(
+    client: &PodmanClient,
+    refs: &impl RefStore,
+    pod: &str,
+) -> Result<()> {
+    stop_workload(client, refs, pod).await?;
+    start_workload(client, refs, pod).await
+}
+
+//UNIT TEST
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hyper::service::{make_service_fn, service_fn};
+    use hyper::{Response, Server};
+    use std::collections::HashMap;
+    use std::convert::Infallible;
+    use std::sync::{Arc, Mutex};
+
+    const POD_YAML: &str = r#"
+apiVersion: v1
+kind: Pod
+metadata:
+  name: helloworld
+spec:
+  containers:
+    - name: helloworld
+      image: quay.io/podman/hello:latest
+"#;
+
+    type Calls = Arc<Mutex<Vec<String>>>;
+
+    /// Pod state making the fake podman service refuse to play kube YAMLs
+    const PLAY_FAILS: &str = "PlayFails";
+
+    /// Start a fake podman service recording `METHOD path` of each call
+    ///
+    /// The pod is reported with `state`, or missing when `state` is empty.
+    async fn mock_podman(state: &'static str) -> (PodmanClient, Calls) {
+        let calls: Calls = Arc::new(Mutex::new(Vec::new()));
+        let recorded = calls.clone();
+        let make_service = make_service_fn(move |_| {
+            let recorded = recorded.clone();
+            async move {
+                Ok::<_, Infallible>(service_fn(move |request: Request<Body>| {
+                    let recorded = recorded.clone();
+                    async move {
+                        let path = request
+                            .uri()
+                            .path_and_query()
+                            .map(|p| p.to_string())
+                            .unwrap_or_default();
+                        recorded
+                            .lock()
+                            .unwrap()
+                            .push(format!("{} {}", request.method(), path));
+                        let response = if path.starts_with("/v4.0.0/libpod/play/kube")
+                            && state == PLAY_FAILS
+                        {
+                            Response::builder()
+                                .status(500)
+                                .body(Body::from(r#"{"message":"image not found"}"#))
+                        } else if path.starts_with("/v4.0.0/libpod/networks/")
+                            && path.ends_with("/json")
+                        {
+                            Response::builder().body(Body::from(r#"{"name":"bms-net"}"#))
+                        } else if path.ends_with("/json") && state.is_empty() {
+                            Response::builder()
+                                .status(404)
+                                .body(Body::from(r#"{"message":"no such pod"}"#))
+                        } else if path.ends_with("/json") {
+                            Response::builder()
+                                .body(Body::from(format!(r#"{{"State":"{}"}}"#, state)))
+                        } else {
+                            Response::builder().body(Body::from("{}"))
+                        };
+                        Ok::<_, Infallible>(response.unwrap())
+                    }
+                }))
+            }
+        });
+        let server = Server::bind(&([127, 0, 0, 1], 0).into()).serve(make_service);
+        let url = format!("http://{}", server.local_addr());
+        tokio::spawn(server);
+        (PodmanClient::new(&url), calls)
+    }
+
+    fn calls(calls: &Calls) -> Vec<String> {
+        calls.lock().unwrap().clone()
+    }
+
+    /// Pods using each network, kept in memory
+    #[derive(Default)]
+    struct MemoryRefs(Mutex<HashMap<String, Vec<String>>>);
+
+    impl MemoryRefs {
+        fn with(network: &str, pods: &[&str]) -> Self {
+            let pods = pods.iter().map(|p| p.to_string()).collect();
+            Self(Mutex::new(HashMap::from([(network.to_string(), pods)])))
+        }
+
+        fn pods(&self, network: &str) -> Vec<String> {
+            self.0
+                .lock()
+                .unwrap()
+                .get(network)
+                .cloned()
+                .unwrap_or_default()
+        }
+    }
+
+    #[tonic::async_trait]
+    impl RefStore for MemoryRefs {
+        async fn load(&self, network: &str) -> std::result::Result<Vec<String>, network::Error> {
+            Ok(self.pods(network))
+        }
+
+        async fn save(
+            &self,
+            network: &str,
+            pods: &[String],
+        ) -> std::result::Result<(), network::Error> {
+            let mut refs = self.0.lock().unwrap();
+            if pods.is_empty() {
+                refs.remove(network);
+            } else {
+                refs.insert(network.to_string(), pods.to_vec());
+            }
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_new_parses_endpoint() {
+        assert_eq!(
+            PodmanClient::new("unix:///run/podman/podman.sock").endpoint,
+            Endpoint::Unix("/run/podman/podman.sock".to_string())
+        );
+        assert_eq!(
+            PodmanClient::new("http://127.0.0.1:8080/").endpoint,
+            Endpoint::Http("http://127.0.0.1:8080".to_string())
+        );
+    }
+
+    #[test]
+    fn test_pod_state_to_status() {
+        assert_eq!(pod_state_to_status("Created"), PodStatus::Init);
+        assert_eq!(pod_state_to_status("Running"), PodStatus::Running);
+        assert_eq!(pod_state_to_status("Exited"), PodStatus::Done);
+        assert_eq!(pod_state_to_status("Degraded"), PodStatus::Failed);
+        assert_eq!(pod_state_to_status("Whatever"), PodStatus::Unknown);
+    }
+
+    #[tokio::test]
+    async fn test_start_workload_plays_kube() {
+        let (client, recorded) = mock_podman("Running").await;
+
+        start_workload(&client, &MemoryRefs::default(), POD_YAML)
+            .await
+            .unwrap();
+
+        assert_eq!(
+            calls(&recorded),
+            vec![
+                "POST /v4.0.0/libpod/play/kube",
+                "GET /v4.0.0/libpod/pods/helloworld/json",
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_start_workload_fails_on_degraded_pod() {
+        let (client, _) = mock_podman("Degraded").await;
+
+        let err = start_workload(&client, &MemoryRefs::default(), POD_YAML)
+            .await
+            .unwrap_err();
+
+        assert!(err.to_string().contains("failed to start"));
+    }
+
+    #[tokio::test]
+    async fn test_stop_workload_stops_and_removes() {
+        let (client, recorded) = mock_podman("Running").await;
+
+        stop_workload(&client, &MemoryRefs::default(), POD_YAML)
+            .await
+            .unwrap();
+
+        assert_eq!(
+            calls(&recorded),
+            vec![
+                "POST /v4.0.0/libpod/pods/helloworld/stop",
+                "DELETE /v4.0.0/libpod/pods/helloworld?force=true",
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_pod_status_of_missing_pod() {
+        let (client, _) = mock_podman("").await;
+
+        assert_eq!(
+            client.pod_status("helloworld").await.unwrap(),
+            PodStatus::None
+        );
+    }
+
+    #[tokio::test]
+    async fn test_start_workload_rejects_invalid_yaml() {
+        let (client, recorded) = mock_podman("Running").await;
+
+        let refs = MemoryRefs::default();
+        assert!(start_workload(&client, &refs, "not: [a pod").await.is_err());
+        assert!(calls(&recorded).is_empty());
+    }
+
+    /// `POD_YAML` annotated to join `bms-net`
+    fn networked_pod() -> String {
+        POD_YAML.replace(
+            "  name: helloworld\n",
+            "  name: helloworld\n  annotations:\n    io.piccolo.annotations.network: bms-net\n",
+        )
+    }
+
+    #[tokio::test]
+    async fn test_play_kube_joins_network() {
+        let (client, recorded) = mock_podman("Running").await;
+
+        client.play_kube(POD_YAML, Some("bms-net")).await.unwrap();
+
+        assert_eq!(
+            calls(&recorded),
+            vec!["POST /v4.0.0/libpod/play/kube?network=bms-net"]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_play_kube_encodes_network() {
+        let (client, recorded) = mock_podman("Running").await;
+
+        client.play_kube(POD_YAML, Some("bms net&x")).await.unwrap();
+
+        assert_eq!(
+            calls(&recorded),
+            vec!["POST /v4.0.0/libpod/play/kube?network=bms%20net%26x"]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_start_pod_releases_network_when_play_fails() {
+        let (client, recorded) = mock_podman(PLAY_FAILS).await;
+        let refs = MemoryRefs::default();
+        let network: Network = serde_yaml::from_str(
+            "apiVersion: v1\nkind: Network\nmetadata:\n  name: bms-net\nspec:\n  subnet: 10.89.1.0/24\n",
+        )
+        .unwrap();
+
+        let err = start_pod(&client, &refs, &networked_pod(), Some(&network))
+            .await
+            .unwrap_err();
+
+        assert!(err.to_string().contains("image not found"));
+        assert!(refs.pods("bms-net").is_empty());
+        assert_eq!(
+            calls(&recorded).last().unwrap(),
+            "DELETE /v4.0.0/libpod/networks/bms-net"
+        );
+    }
+
+    #[test]
+    fn test_prepare_volumes() {
+        let pod = POD_YAML.replace(
+            "spec:\n",
+            "spec:\n  volumes:\n    - name: data\n      hostPath:\n        path: vd/data\n    - name: logs\n      hostPath:\n        path: /var/log/app\n        type: Directory\n    - name: scratch\n      emptyDir: {}\n",
+        );
+
+        let prepared: serde_yaml::Value =
+            serde_yaml::from_str(&prepare_volumes(&pod, "/srv/volumes").unwrap()).unwrap();
+        let volumes = &prepared["spec"]["volumes"];
+
+        assert_eq!(volumes[0]["hostPath"]["path"], "/srv/volumes/vd/data");
+        assert_eq!(volumes[0]["hostPath"]["type"], "DirectoryOrCreate");
+        assert_eq!(volumes[1]["hostPath"]["path"], "/var/log/app");
+        assert_eq!(volumes[1]["hostPath"]["type"], "Directory");
+        assert!(volumes[2]["hostPath"].is_null());
+        assert_eq!(prepare_volumes(POD_YAML, "/srv/volumes").unwrap(), POD_YAML);
+    }
+
+    #[test]
+    fn test_prepare_volumes_rejects_image_volume() {
+        let pod = POD_YAML.replace(
+            "spec:\n",
+            "spec:\n  volumes:\n    - name: maps\n      image:\n        reference: maps:latest\n",
+        );
+
+        let err = prepare_volumes(&pod, "/srv/volumes").unwrap_err();
+
+        assert!(err.to_string().contains("image volumes are not supported"));
+    }
+
+    #[tokio::test]
+    async fn test_network_api() {
+        let (client, recorded) = mock_podman("Running").await;
+        let network: Network = serde_yaml::from_str(
+            "apiVersion: v1\nkind: Network\nmetadata:\n  name: bms-net\nspec:\n  subnet: 10.89.1.0/24\n",
+        )
+        .unwrap();
+
+        assert!(client.exists("bms-net").await.unwrap());
+        assert!(!client.exists("other-net").await.unwrap());
+        client.create(&network).await.unwrap();
+        client.remove("bms-net").await.unwrap();
+
+        assert_eq!(
+            calls(&recorded),
+            vec![
+                "GET /v4.0.0/libpod/networks/bms-net/json",
+                "GET /v4.0.0/libpod/networks/other-net/json",
+                "POST /v4.0.0/libpod/networks/create",
+                "DELETE /v4.0.0/libpod/networks/bms-net",
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_stop_workload_removes_network_of_last_pod() {
+        let (client, recorded) = mock_podman("Running").await;
+        let refs = MemoryRefs::with("bms-net", &["helloworld"]);
+
+        stop_workload(&client, &refs, &networked_pod())
+            .await
+            .unwrap();
+
+        assert_eq!(
+            calls(&recorded),
+            vec![
+                "POST /v4.0.0/libpod/pods/helloworld/stop",
+                "DELETE /v4.0.0/libpod/pods/helloworld?force=true",
+                "GET /v4.0.0/libpod/networks/bms-net/json",
+                "DELETE /v4.0.0/libpod/networks/bms-net",
+            ]
+        );
+        assert!(refs.pods("bms-net").is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_stop_workload_keeps_shared_network() {
+        let (client, recorded) = mock_podman("Running").await;
+        let refs = MemoryRefs::with("bms-net", &["helloworld", "telemetry"]);
+
+        stop_workload(&client, &refs, &networked_pod())
+            .await
+            .unwrap();
+
+        assert_eq!(calls(&recorded).len(), 2);
+        assert_eq!(refs.pods("bms-net"), vec!["telemetry"]);
+    }
+}
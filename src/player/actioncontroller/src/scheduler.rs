@@ -0,0 +1,477 @@
+/*
+* SPDX-FileCopyrightText: Copyright 2024 LG Electronics Inc.
+* SPDX-License-Identifier: Apache-2.0
+*/
+//! Delayed actions of scenarios
+//!
+//! The action of a scenario with `delayMs` runs once the delay has passed
+//! since the scenario was triggered. Until then it is kept in etcd with the
+//! time it is due, so a restart of ActionController does not lose it: an
+//! action that fell due meanwhile runs at startup, unless it is later than
+//! `actioncontroller.scheduled_expiry_secs`.
+//!
+//! Delays are waited on the monotonic clock, so a jump of the wall clock
+//! neither runs a pending action early nor holds it back. The wall clock
+//! only dates the stored due times, read again after a restart.
+
+use common::keys;
+use common::logd;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tokio::sync::mpsc::{self, UnboundedReceiver, UnboundedSender};
+use tokio::task::AbortHandle;
+
+/// Action of a scenario waiting for its delay, as stored in etcd
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ScheduledAction {
+    /// Name of the scenario
+    pub scenario: String,
+    /// Wall clock time the action is due, in milliseconds since the Unix epoch
+    pub due_ms: i64,
+    /// Delay the scenario was scheduled with, in milliseconds
+    pub delay_ms: u64,
+}
+
+/// Source of the wall clock time
+pub trait Clock: Send + Sync {
+    /// Milliseconds since the Unix epoch
+    fn now_ms(&self) -> i64;
+}
+
+/// Wall clock of the system
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now_ms(&self) -> i64 {
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|since| since.as_millis() as i64)
+            .unwrap_or(0)
+    }
+}
+
+/// Persistent record of the pending actions
+#[tonic::async_trait]
+pub trait ScheduleStore: Send + Sync {
+    async fn put(&self, action: &ScheduledAction) -> Result<(), String>;
+    async fn delete(&self, scenario: &str) -> Result<(), String>;
+    async fn list(&self) -> Result<Vec<ScheduledAction>, String>;
+}
+
+/// Pending actions stored under `ActionController/scheduled/`
+pub struct EtcdStore;
+
+#[tonic::async_trait]
+impl ScheduleStore for EtcdStore {
+    async fn put(&self, action: &ScheduledAction) -> Result<(), String> {
+        let value = serde_json::to_string(action).map_err(|e| e.to_string())?;
+        common::etcd::put(&keys::scheduled_action(&action.scenario), &value).await
+    }
+
+    async fn delete(&self, scenario: &str) -> Result<(), String> {
+        common::etcd::delete(&keys::scheduled_action(scenario)).await
+    }
+
+    async fn list(&self) -> Result<Vec<ScheduledAction>, String> {
+        let entries = common::etcd::get_all_with_prefix(keys::SCHEDULED_ACTIONS_PREFIX).await?;
+        Ok(entries
+            .into_iter()
+            .filter_map(|(key, value)| match serde_json::from_str(&value) {
+                Ok(action) => Some(action),
+                Err(e) => {
+                    logd!(4, "Ignoring malformed scheduled action {}: {}", key, e);
+                    None
+                }
+            })
+            .collect())
+    }
+}
+
+/// Timers of the pending actions, each sending its scenario once due
+pub struct Scheduler {
+    clock: Arc<dyn Clock>,
+    store: Arc<dyn ScheduleStore>,
+    /// Pending actions by scenario name, without a timer while being stored
+    pending: Mutex<HashMap<String, Option<AbortHandle>>>,
+    due_tx: UnboundedSender<String>,
+}
+
+impl Scheduler {
+    /// Scheduler and the receiver of the scenarios whose action is due
+    pub fn new(
+        clock: Arc<dyn Clock>,
+        store: Arc<dyn ScheduleStore>,
+    ) -> (Self, UnboundedReceiver<String>) {
+        let (due_tx, due_rx) = mpsc::unbounded_channel();
+        let scheduler = Self {
+            clock,
+            store,
+            pending: Mutex::new(HashMap::new()),
+            due_tx,
+        };
+        (scheduler, due_rx)
+    }
+
+    /// Scheduler on the system clock, storing its actions in etcd
+    pub fn from_etcd() -> (Self, UnboundedReceiver<String>) {
+        Self::new(Arc::new(SystemClock), Arc::new(EtcdStore))
+    }
+
+    /// Timer sending `scenario` once `wait` has passed
+    fn start(&self, scenario: &str, wait: Duration) -> AbortHandle {
+        let due_tx = self.due_tx.clone();
+        let scenario = scenario.to_string();
+        tokio::spawn(async move {
+            tokio::time::sleep(wait).await;
+            let _ = due_tx.send(scenario);
+        })
+        .abort_handle()
+    }
+
+    /// Runs the action of `scenario` once `delay` has passed
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(true)` - The action is stored and its timer started
+    /// * `Ok(false)` - An action of the scenario is already pending, and is
+    ///   left due at its own time rather than pushed back
+    /// * `Err(String)` - The action could not be stored
+    pub async fn schedule(&self, scenario: &str, delay: Duration) -> Result<bool, String> {
+        {
+            let mut pending = self.pending.lock().unwrap();
+            if pending.contains_key(scenario) {
+                return Ok(false);
+            }
+            pending.insert(scenario.to_string(), None);
+        }
+
+        let action = ScheduledAction {
+            scenario: scenario.to_string(),
+            due_ms: self.clock.now_ms() + delay.as_millis() as i64,
+            delay_ms: delay.as_millis() as u64,
+        };
+        if let Err(e) = self.store.put(&action).await {
+            self.pending.lock().unwrap().remove(scenario);
+            return Err(format!(
+                "Failed to store delayed action of scenario '{}': {}",
+                scenario, e
+            ));
+        }
+
+        let cancelled = {
+            let mut pending = self.pending.lock().unwrap();
+            match pending.get_mut(scenario) {
+                Some(timer) => {
+                    *timer = Some(self.start(scenario, delay));
+                    false
+                }
+                None => true,
+            }
+        };
+        if cancelled {
+            // Cancelled while being stored, the delete may have come first
+            self.forget(scenario).await;
+        }
+        Ok(true)
+    }
+
+    /// Cancels the pending action of `scenario`
+    ///
+    /// Returns false if no action of the scenario was pending.
+    pub async fn cancel(&self, scenario: &str) -> bool {
+        let removed = self.pending.lock().unwrap().remove(scenario);
+        if let Some(Some(timer)) = &removed {
+            timer.abort();
+        }
+        self.forget(scenario).await;
+        removed.is_some()
+    }
+
+    /// Takes the action of `scenario` its timer reported due
+    ///
+    /// Returns false if the action was cancelled since.
+    pub fn take(&self, scenario: &str) -> bool {
+        self.pending.lock().unwrap().remove(scenario).is_some()
+    }
+
+    /// Removes the stored action of `scenario` once it has run
+    ///
+    /// A new action of the scenario scheduled meanwhile is kept.
+    pub async fn complete(&self, scenario: &str) {
+        if !self.pending.lock().unwrap().contains_key(scenario) {
+            self.forget(scenario).await;
+        }
+    }
+
+    /// Deletes the stored action of `scenario`
+    async fn forget(&self, scenario: &str) {
+        if let Err(e) = self.store.delete(scenario).await {
+            logd!(
+                4,
+                "Failed to delete delayed action of scenario '{}': {}",
+                scenario,
+                e
+            );
+        }
+    }
+
+    /// Starts the timers of the actions stored before a restart
+    ///
+    /// An action already due is reported due at once, unless it is more than
+    /// `expiry` late, in which case it is dropped. The wait of an action is
+    /// never longer than its delay, even when the wall clock was set back
+    /// since it was scheduled.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(usize)` - Number of actions pending again
+    /// * `Err(String)` - The stored actions could not be read
+    pub async fn restore(&self, expiry: Option<Duration>) -> Result<usize, String> {
+        let now = self.clock.now_ms();
+        let mut restored = 0;
+        for action in self.store.list().await? {
+            let late = now - action.due_ms;
+            if expiry.is_some_and(|expiry| late > expiry.as_millis() as i64) {
+                logd!(
+                    4,
+                    "Dropping delayed action of scenario '{}', due {} ms ago",
+                    action.scenario,
+                    late
+                );
+                self.forget(&action.scenario).await;
+                continue;
+            }
+
+            let wait = Duration::from_millis(late.min(0).unsigned_abs())
+                .min(Duration::from_millis(action.delay_ms));
+            let mut pending = self.pending.lock().unwrap();
+            if pending.contains_key(&action.scenario) {
+                continue;
+            }
+            logd!(
+                2,
+                "Delayed action of scenario '{}' is due in {:?}",
+                action.scenario,
+                wait
+            );
+            pending.insert(
+                action.scenario.clone(),
+                Some(self.start(&action.scenario, wait)),
+            );
+            restored += 1;
+        }
+        Ok(restored)
+    }
+}
+
+//UNIT TEST
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicI64, Ordering};
+
+    const DELAY: Duration = Duration::from_millis(50);
+
+    /// Wall clock set by the test
+    #[derive(Default)]
+    struct MockClock(AtomicI64);
+
+    impl MockClock {
+        fn set(&self, now_ms: i64) {
+            self.0.store(now_ms, Ordering::SeqCst);
+        }
+    }
+
+    impl Clock for MockClock {
+        fn now_ms(&self) -> i64 {
+            self.0.load(Ordering::SeqCst)
+        }
+    }
+
+    /// Store kept in memory, in place of etcd
+    #[derive(Default)]
+    struct MemoryStore(Mutex<HashMap<String, ScheduledAction>>);
+
+    impl MemoryStore {
+        fn get(&self, scenario: &str) -> Option<ScheduledAction> {
+            self.0.lock().unwrap().get(scenario).cloned()
+        }
+    }
+
+    #[tonic::async_trait]
+    impl ScheduleStore for MemoryStore {
+        async fn put(&self, action: &ScheduledAction) -> Result<(), String> {
+            self.0
+                .lock()
+                .unwrap()
+                .insert(action.scenario.clone(), action.clone());
+            Ok(())
+        }
+
+        async fn delete(&self, scenario: &str) -> Result<(), String> {
+            self.0.lock().unwrap().remove(scenario);
+            Ok(())
+        }
+
+        async fn list(&self) -> Result<Vec<ScheduledAction>, String> {
+            Ok(self.0.lock().unwrap().values().cloned().collect())
+        }
+    }
+
+    fn scheduler(
+        now_ms: i64,
+    ) -> (
+        Scheduler,
+        UnboundedReceiver<String>,
+        Arc<MockClock>,
+        Arc<MemoryStore>,
+    ) {
+        let clock = Arc::new(MockClock::default());
+        clock.set(now_ms);
+        let store = Arc::new(MemoryStore::default());
+        let (scheduler, due) = Scheduler::new(clock.clone(), store.clone());
+        (scheduler, due, clock, store)
+    }
+
+    fn action(scenario: &str, due_ms: i64, delay: Duration) -> ScheduledAction {
+        ScheduledAction {
+            scenario: scenario.to_string(),
+            due_ms,
+            delay_ms: delay.as_millis() as u64,
+        }
+    }
+
+    /// Next scenario reported due within `within`
+    async fn due_within(due: &mut UnboundedReceiver<String>, within: Duration) -> Option<String> {
+        tokio::time::timeout(within, due.recv())
+            .await
+            .ok()
+            .flatten()
+    }
+
+    #[tokio::test]
+    async fn test_action_is_due_after_its_delay() {
+        let (scheduler, mut due, _, store) = scheduler(1_000);
+        assert_eq!(scheduler.schedule("heating", DELAY).await, Ok(true));
+        assert_eq!(store.get("heating"), Some(action("heating", 1_050, DELAY)));
+
+        // Triggering again does not push the action back
+        assert_eq!(scheduler.schedule("heating", DELAY * 10).await, Ok(false));
+
+        assert_eq!(due_within(&mut due, DELAY / 2).await, None);
+        assert_eq!(
+            due_within(&mut due, DELAY).await.as_deref(),
+            Some("heating")
+        );
+
+        // Stored until it has run
+        assert!(scheduler.take("heating"));
+        assert!(store.get("heating").is_some());
+        scheduler.complete("heating").await;
+        assert!(store.get("heating").is_none());
+    }
+
+    #[tokio::test]
+    async fn test_cancelled_action_never_runs() {
+        let (scheduler, mut due, _, store) = scheduler(1_000);
+        scheduler.schedule("heating", DELAY).await.unwrap();
+
+        assert!(scheduler.cancel("heating").await);
+        assert!(store.get("heating").is_none());
+        assert_eq!(due_within(&mut due, DELAY * 2).await, None);
+        assert!(!scheduler.cancel("heating").await);
+
+        // Scheduling again after a cancel starts a new action
+        assert_eq!(scheduler.schedule("heating", DELAY).await, Ok(true));
+    }
+
+    #[tokio::test]
+    async fn test_cancel_after_due_wins_over_take() {
+        let (scheduler, mut due, _, _) = scheduler(1_000);
+        scheduler.schedule("heating", DELAY).await.unwrap();
+        let scenario = due_within(&mut due, DELAY * 2).await.unwrap();
+
+        assert!(scheduler.cancel(&scenario).await);
+        assert!(!scheduler.take(&scenario));
+    }
+
+    #[tokio::test]
+    async fn test_wall_clock_jump_does_not_move_pending_action() {
+        let (scheduler, mut due, clock, _) = scheduler(1_000);
+        scheduler.schedule("heating", DELAY).await.unwrap();
+
+        clock.set(1_000 + 3_600_000);
+        assert_eq!(due_within(&mut due, DELAY / 2).await, None);
+        clock.set(0);
+        assert_eq!(
+            due_within(&mut due, DELAY).await.as_deref(),
+            Some("heating")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_restore_runs_overdue_action_at_once() {
+        let (scheduler, mut due, _, store) = scheduler(10_000);
+        store.put(&action("heating", 9_000, DELAY)).await.unwrap();
+
+        let restored = scheduler.restore(Some(Duration::from_secs(60))).await;
+        assert_eq!(restored, Ok(1));
+        assert_eq!(
+            due_within(&mut due, DELAY / 2).await.as_deref(),
+            Some("heating")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_restore_drops_expired_action() {
+        let (scheduler, mut due, _, store) = scheduler(100_000);
+        store.put(&action("heating", 9_000, DELAY)).await.unwrap();
+
+        let restored = scheduler.restore(Some(Duration::from_secs(60))).await;
+        assert_eq!(restored, Ok(0));
+        assert!(store.get("heating").is_none());
+        assert_eq!(due_within(&mut due, DELAY).await, None);
+
+        // Without an expiry, however late it is
+        store.put(&action("heating", 9_000, DELAY)).await.unwrap();
+        assert_eq!(scheduler.restore(None).await, Ok(1));
+        assert_eq!(
+            due_within(&mut due, DELAY / 2).await.as_deref(),
+            Some("heating")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_restore_waits_for_the_rest_of_the_delay() {
+        let (scheduler, mut due, _, store) = scheduler(10_000);
+        store
+            .put(&action("heating", 10_000 + 50, DELAY * 2))
+            .await
+            .unwrap();
+
+        assert_eq!(scheduler.restore(None).await, Ok(1));
+        assert_eq!(due_within(&mut due, Duration::from_millis(25)).await, None);
+        assert_eq!(
+            due_within(&mut due, DELAY).await.as_deref(),
+            Some("heating")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_restore_after_clock_set_back_waits_at_most_the_delay() {
+        // Scheduled for an hour from now, with a delay of 50 ms
+        let (scheduler, mut due, _, store) = scheduler(10_000);
+        store
+            .put(&action("heating", 10_000 + 3_600_000, DELAY))
+            .await
+            .unwrap();
+
+        assert_eq!(scheduler.restore(None).await, Ok(1));
+        assert_eq!(
+            due_within(&mut due, DELAY * 2).await.as_deref(),
+            Some("heating")
+        );
+    }
+}
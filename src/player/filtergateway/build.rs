@@ -20,6 +20,7 @@ use build_scripts::settings::load_dds_settings;
 
 fn main() -> Result<(), Box<dyn std::error::Error>> {
     println!("cargo:rerun-if-changed=src/vehicle/dds/idl");
+    println!("cargo:rerun-if-changed=build_scripts");
     // Ensure build script reruns if settings.yaml changes (relative to project root)
     let manifest_dir = env::var("CARGO_MANIFEST_DIR").expect("CARGO_MANIFEST_DIR not set");
     let settings_path = PathBuf::from(&manifest_dir)
@@ -107,6 +107,100 @@ pub fn generate_type_registry(
     }
 
     // 기본 매핑 종료
+    writeln!(registry_file, "        _ => None,")?;
+    writeln!(registry_file, "    }}")?;
+    writeln!(registry_file, "}}")?;
+    writeln!(registry_file)?;
+
+    // 토픽 레지스트리 등록 함수
+    writeln!(
+        registry_file,
+        "pub fn register_topics(registry: &mut crate::vehicle::dds::registry::TopicRegistry) {{"
+    )?;
+
+    for idl_file in idl_files {
+        if let Some(file_stem) = idl_file.file_stem() {
+            let module_name = file_stem.to_string_lossy();
+
+            if let Ok(dds_data) = IdlParser::parse_idl_file(idl_file) {
+                let struct_name = &dds_data.name;
+
+                writeln!(
+                    registry_file,
+                    "    registry.register(\"{}\", |topic_name, tx, domain_id| {{",
+                    struct_name
+                )?;
+                writeln!(
+                    registry_file,
+                    "        Box::new(GenericTopicListener::<{}::{}>::new(topic_name, \"{}\".to_string(), tx, domain_id))",
+                    module_name, struct_name, struct_name
+                )?;
+                writeln!(registry_file, "    }});")?;
+            }
+        }
+    }
+
+    writeln!(registry_file, "}}")?;
+    writeln!(registry_file)?;
+
+    // 타입별 JSON 퍼블리시 함수
+    writeln!(registry_file, "pub async fn publish_raw(topic_name: &str, value: serde_json::Value, domain_id: i32) -> Result<()> {{")?;
+    writeln!(registry_file, "    match topic_name {{")?;
+
+    for idl_file in idl_files {
+        if let Some(file_stem) = idl_file.file_stem() {
+            let module_name = file_stem.to_string_lossy();
+
+            if let Ok(dds_data) = IdlParser::parse_idl_file(idl_file) {
+                let struct_name = &dds_data.name;
+
+                writeln!(registry_file, "        \"{}\" => {{", struct_name)?;
+                writeln!(
+                    registry_file,
+                    "            let data: {}::{} = serde_json::from_value(value)?;",
+                    module_name, struct_name
+                )?;
+                writeln!(
+                    registry_file,
+                    "            crate::vehicle::dds::publisher::send_dds(topic_name, data, domain_id).await"
+                )?;
+                writeln!(registry_file, "        }},")?;
+            }
+        }
+    }
+
+    writeln!(
+        registry_file,
+        "        _ => Err(format!(\"Unknown DDS type for topic '{{}}'\", topic_name).into()),"
+    )?;
+    writeln!(registry_file, "    }}")?;
+    writeln!(registry_file, "}}")?;
+    writeln!(registry_file)?;
+
+    // 타입별 기본 샘플 변환 함수 (테스트용)
+    writeln!(registry_file, "#[cfg(test)]")?;
+    writeln!(
+        registry_file,
+        "pub fn default_sample_data(type_name: &str) -> Option<Result<DdsData>> {{"
+    )?;
+    writeln!(registry_file, "    match type_name {{")?;
+
+    for idl_file in idl_files {
+        if let Some(file_stem) = idl_file.file_stem() {
+            let module_name = file_stem.to_string_lossy();
+
+            if let Ok(dds_data) = IdlParser::parse_idl_file(idl_file) {
+                let struct_name = &dds_data.name;
+
+                writeln!(
+                    registry_file,
+                    "        \"{}\" => Some(DdsData::from_sample(type_name, type_name, &{}::{}::default())),",
+                    struct_name, module_name, struct_name
+                )?;
+            }
+        }
+    }
+
     writeln!(registry_file, "        _ => None,")?;
     writeln!(registry_file, "    }}")?;
     writeln!(registry_file, "}}")?;
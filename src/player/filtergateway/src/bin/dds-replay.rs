@@ -0,0 +1,45 @@
+/*
+* SPDX-FileCopyrightText: Copyright 2024 LG Electronics Inc.
+* SPDX-License-Identifier: Apache-2.0
+*/
+//! Publishes a trace of vehicle signals on the DDS bus
+//!
+//! Used for regression tests of scenarios: a running FilterGateway receives
+//! the trace through its listeners like live vehicle data.
+
+use clap::Parser;
+use filtergateway::vehicle::dds::trace;
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+#[derive(Parser)]
+#[command(name = "dds-replay")]
+#[command(about = "Publish a recorded vehicle signal trace over DDS")]
+struct Args {
+    /// Trace file: `.csv` (topic,value,offset_ms), `.json` or a `.jsonl` recording
+    trace: PathBuf,
+
+    /// Speed factor of the replay, 1.0 for real time
+    #[arg(long, default_value = "1.0")]
+    speed: f64,
+
+    /// DDS domain ID to publish on
+    #[arg(long, default_value = "100")]
+    domain_id: i32,
+}
+
+#[tokio::main]
+async fn main() -> ExitCode {
+    let args = Args::parse();
+
+    match trace::publish_trace(&args.trace, args.speed, args.domain_id).await {
+        Ok(count) => {
+            println!("Published {} samples from {}", count, args.trace.display());
+            ExitCode::SUCCESS
+        }
+        Err(e) => {
+            eprintln!("Failed to replay {}: {}", args.trace.display(), e);
+            ExitCode::FAILURE
+        }
+    }
+}
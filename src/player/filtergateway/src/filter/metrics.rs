@@ -0,0 +1,192 @@
+/*
+* SPDX-FileCopyrightText: Copyright 2024 LG Electronics Inc.
+* SPDX-License-Identifier: Apache-2.0
+*/
+//! Per-scenario evaluation counters and trigger latency
+//!
+//! Every filter owns a `FilterMetrics` updated with atomics on the DDS
+//! path, so counting a sample neither locks nor allocates. The filters also
+//! register their metrics here, where the gRPC service reads them.
+
+use common::filtergateway::{LatencyBucket, ScenarioMetrics};
+use common::logd;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::Duration;
+
+/// Upper bounds of the latency buckets, in milliseconds
+pub const LATENCY_BUCKETS_MS: [u64; 10] = [1, 5, 10, 25, 50, 100, 250, 500, 1000, 5000];
+
+/// Counters of one scenario filter
+#[derive(Debug, Default)]
+pub struct FilterMetrics {
+    /// Samples on a topic the filter watches
+    pub samples_received: AtomicU64,
+    /// Samples the condition was evaluated against
+    pub evaluations: AtomicU64,
+    /// Evaluations that met the condition
+    pub matches: AtomicU64,
+    /// Actions triggered through ActionController
+    pub actions_sent: AtomicU64,
+    /// Actions ActionController could not be reached for
+    pub action_send_failures: AtomicU64,
+    /// Triggers per latency bucket, the last one for the slower ones
+    latency: [AtomicU64; LATENCY_BUCKETS_MS.len() + 1],
+    /// Sum of the trigger latencies, in microseconds
+    latency_sum_us: AtomicU64,
+}
+
+impl FilterMetrics {
+    /// Adds one to `counter`
+    pub fn incr(counter: &AtomicU64) {
+        counter.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Records the time from receiving a sample to the end of its trigger
+    pub fn observe_latency(&self, latency: Duration) {
+        let millis = latency.as_millis();
+        let bucket = LATENCY_BUCKETS_MS
+            .iter()
+            .position(|le| millis <= *le as u128)
+            .unwrap_or(LATENCY_BUCKETS_MS.len());
+        self.latency[bucket].fetch_add(1, Ordering::Relaxed);
+        self.latency_sum_us
+            .fetch_add(latency.as_micros() as u64, Ordering::Relaxed);
+    }
+
+    /// Current values as the `GetMetrics` message of `scenario`
+    pub fn snapshot(&self, scenario: &str) -> ScenarioMetrics {
+        let latency: Vec<LatencyBucket> = self
+            .latency
+            .iter()
+            .enumerate()
+            .map(|(i, count)| LatencyBucket {
+                le_ms: LATENCY_BUCKETS_MS.get(i).copied().unwrap_or(u64::MAX),
+                count: count.load(Ordering::Relaxed),
+            })
+            .collect();
+        ScenarioMetrics {
+            scenario: scenario.to_string(),
+            samples_received: self.samples_received.load(Ordering::Relaxed),
+            evaluations: self.evaluations.load(Ordering::Relaxed),
+            matches: self.matches.load(Ordering::Relaxed),
+            actions_sent: self.actions_sent.load(Ordering::Relaxed),
+            action_send_failures: self.action_send_failures.load(Ordering::Relaxed),
+            latency_count: latency.iter().map(|b| b.count).sum(),
+            latency,
+            latency_sum_us: self.latency_sum_us.load(Ordering::Relaxed),
+        }
+    }
+}
+
+/// Metrics of the running filters, by scenario name
+fn registry() -> &'static Mutex<HashMap<String, Arc<FilterMetrics>>> {
+    static REGISTRY: OnceLock<Mutex<HashMap<String, Arc<FilterMetrics>>>> = OnceLock::new();
+    REGISTRY.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+/// Metrics of a new filter, shared with a running filter of the same
+/// scenario so a duplicate filter does not reset them
+pub fn register(scenario: &str) -> Arc<FilterMetrics> {
+    registry()
+        .lock()
+        .unwrap()
+        .entry(scenario.to_string())
+        .or_default()
+        .clone()
+}
+
+/// Removes the metrics of a dropped filter once no other filter uses them
+pub fn unregister(scenario: &str, metrics: &Arc<FilterMetrics>) {
+    let mut registry = registry().lock().unwrap();
+    // Held by the registry and the dropped filter only
+    if registry
+        .get(scenario)
+        .is_some_and(|current| Arc::ptr_eq(current, metrics) && Arc::strong_count(current) <= 2)
+    {
+        registry.remove(scenario);
+    }
+}
+
+/// Metrics of the running filters
+///
+/// # Arguments
+///
+/// * `scenario` - Name of one scenario, or empty for all of them
+///
+/// # Returns
+///
+/// * `Vec<ScenarioMetrics>` - Metrics sorted by scenario name
+pub fn snapshot(scenario: &str) -> Vec<ScenarioMetrics> {
+    let registry = registry().lock().unwrap();
+    let mut metrics: Vec<ScenarioMetrics> = registry
+        .iter()
+        .filter(|(name, _)| scenario.is_empty() || name.as_str() == scenario)
+        .map(|(name, metrics)| metrics.snapshot(name))
+        .collect();
+    metrics.sort_by(|a, b| a.scenario.cmp(&b.scenario));
+    metrics
+}
+
+/// Logs the metrics of every running filter each `interval`
+pub async fn log_periodically(interval: Duration) {
+    let mut ticker = tokio::time::interval(interval);
+    ticker.tick().await;
+    loop {
+        ticker.tick().await;
+        for m in snapshot("") {
+            logd!(
+                3,
+                "Scenario metrics {}: samples={} evaluations={} matches={} actions={} failures={} latency_avg_us={}",
+                m.scenario,
+                m.samples_received,
+                m.evaluations,
+                m.matches,
+                m.actions_sent,
+                m.action_send_failures,
+                m.latency_sum_us.checked_div(m.latency_count).unwrap_or(0)
+            );
+        }
+    }
+}
+
+//Unit Test Cases
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_latency_buckets() {
+        let metrics = FilterMetrics::default();
+        metrics.observe_latency(Duration::from_micros(300));
+        metrics.observe_latency(Duration::from_millis(5));
+        metrics.observe_latency(Duration::from_secs(60));
+
+        let snapshot = metrics.snapshot("latency");
+        assert_eq!(snapshot.latency_count, 3);
+        assert_eq!(snapshot.latency[0].count, 1);
+        assert_eq!(snapshot.latency[1].le_ms, 5);
+        assert_eq!(snapshot.latency[1].count, 1);
+        let overflow = snapshot.latency.last().unwrap();
+        assert_eq!((overflow.le_ms, overflow.count), (u64::MAX, 1));
+        assert_eq!(snapshot.latency_sum_us, 60_005_300);
+    }
+
+    #[test]
+    fn test_registry_outlives_duplicate_filters() {
+        let first = register("metrics-registry-test");
+        FilterMetrics::incr(&first.samples_received);
+        let duplicate = register("metrics-registry-test");
+
+        // Dropping the duplicate keeps the counters of the running filter
+        unregister("metrics-registry-test", &duplicate);
+        drop(duplicate);
+        let metrics = snapshot("metrics-registry-test");
+        assert_eq!(metrics.len(), 1);
+        assert_eq!(metrics[0].samples_received, 1);
+
+        unregister("metrics-registry-test", &first);
+        assert!(snapshot("metrics-registry-test").is_empty());
+    }
+}
@@ -2,17 +2,39 @@
 * SPDX-FileCopyrightText: Copyright 2024 LG Electronics Inc.
 * SPDX-License-Identifier: Apache-2.0
 */
+#[allow(dead_code)]
+pub mod metrics;
+pub mod stability;
+pub mod window;
+
 use crate::grpc::sender::actioncontroller::FilterGatewaySender;
 use crate::grpc::sender::statemanager::StateManagerSender;
 use crate::vehicle::dds::DdsData;
+use chrono::{DateTime, Utc};
 use common::logd;
-use common::spec::artifact::Scenario;
+use common::spec::artifact::{Artifact, Scenario};
 use common::statemanager::{ResourceType, StateChange};
 use common::Result;
+use metrics::FilterMetrics;
+use stability::StabilityTracker;
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
+use window::Window;
 // use dust_dds::infrastructure::wait_set::Condition;
 // use std::sync::Arc;
 // use tokio::sync::{mpsc, Mutex};
 
+/// DDS message published after a scenario action is triggered
+#[derive(Debug, Clone, PartialEq)]
+pub struct Acknowledgement {
+    /// Topic to publish on, which also selects the DDS type
+    pub topic_name: String,
+    /// Message fields as JSON
+    pub value: serde_json::Value,
+    /// DDS domain ID
+    pub domain_id: i32,
+}
+
 #[allow(dead_code)]
 /// Filter for evaluating scenario conditions
 pub struct Filter {
@@ -26,6 +48,17 @@ pub struct Filter {
     sender: FilterGatewaySender,
     /// gRPC sender for state manager
     state_sender: StateManagerSender,
+    /// Optional DDS message published once the action is triggered
+    acknowledgement: Option<Acknowledgement>,
+    /// Debounce and hysteresis state, when the condition configures either
+    stability: Option<StabilityTracker>,
+    /// Evaluation counters and trigger latency
+    metrics: Arc<FilterMetrics>,
+    /// Time window the condition is limited to, if any
+    window: Option<Window>,
+    /// Whether the vehicle data currently meets the condition, in or out
+    /// of the window
+    data_met: bool,
 }
 
 #[allow(dead_code)]
@@ -48,15 +81,52 @@ impl Filter {
         is_active: bool,
         sender: FilterGatewaySender,
     ) -> Self {
+        let stability = scenario
+            .get_conditions()
+            .filter(|c| c.get_debounce_ms().is_some() || c.get_exit_value().is_some())
+            .map(|c| {
+                StabilityTracker::new(Duration::from_millis(c.get_debounce_ms().unwrap_or(0)))
+            });
+        let metrics = metrics::register(&scenario_name);
+        // A scenario with an invalid window is never armed rather than armed all day
+        let (window, is_active) = match scenario.get_conditions().and_then(|c| c.get_window()) {
+            Some(spec) => match Window::from_settings(&spec) {
+                Ok(window) => (Some(window), is_active),
+                Err(e) => {
+                    logd!(5, "Scenario {} is not armed: {}", scenario_name, e);
+                    (None, false)
+                }
+            },
+            None => (None, is_active),
+        };
         Self {
             scenario_name,
             scenario,
             is_active,
             sender,
             state_sender: StateManagerSender::new(),
+            acknowledgement: None,
+            stability,
+            metrics,
+            window,
+            data_met: false,
         }
     }
 
+    /// Evaluation counters and trigger latency of the filter
+    pub fn metrics(&self) -> &FilterMetrics {
+        &self.metrics
+    }
+
+    /// Publish `acknowledgement` on DDS after each triggered action
+    ///
+    /// # Arguments
+    ///
+    /// * `acknowledgement` - Topic, message and domain to publish
+    pub fn set_acknowledgement(&mut self, acknowledgement: Acknowledgement) {
+        self.acknowledgement = Some(acknowledgement);
+    }
+
     /// Check if scenario conditions are met
     ///
     /// Evaluates if the received vehicle data meets the scenario conditions.
@@ -70,6 +140,29 @@ impl Filter {
     ///
     /// * `Result<()>` - Success or error result
     pub async fn meet_scenario_condition(&mut self, data: &DdsData) -> Result<()> {
+        if self.condition_met(data)? {
+            self.run_action().await
+        } else {
+            Err("cannot meet condition".into())
+        }
+    }
+
+    /// Evaluate the scenario condition against vehicle data
+    ///
+    /// When the condition configures a debounce or an exit value, it is only
+    /// reported as met on the sample where it stably becomes met, not on
+    /// every sample while it stays met. When it configures a time window, it
+    /// is only reported as met for samples received within the window.
+    ///
+    /// # Arguments
+    ///
+    /// * `data` - Vehicle message data
+    ///
+    /// # Returns
+    ///
+    /// * `Result<bool>` - Whether the condition holds, or an error if the
+    ///   data does not carry the field the condition refers to
+    pub fn condition_met(&mut self, data: &DdsData) -> Result<bool> {
         use std::time::Instant;
         let start = Instant::now();
 
@@ -99,118 +192,141 @@ impl Filter {
             }
         };
 
-        let check: bool = match express.as_str() {
-            "eq" => target_value.to_lowercase() == field_value.to_lowercase(),
-            "lt" => {
-                let target_v = target_value
-                    .parse::<f32>()
-                    .map_err(|_| "target_value parse error")?;
-                let current_v = field_value
-                    .parse::<f32>()
-                    .map_err(|_| "field_value parse error")?;
-                current_v < target_v
-            }
-            "le" => {
-                let target_v = target_value
-                    .parse::<f32>()
-                    .map_err(|_| "target_value parse error")?;
-                let current_v = field_value
-                    .parse::<f32>()
-                    .map_err(|_| "field_value parse error")?;
-                current_v <= target_v
-            }
-            "ge" => {
-                let target_v = target_value
-                    .parse::<f32>()
-                    .map_err(|_| "target_value parse error")?;
-                let current_v = field_value
-                    .parse::<f32>()
-                    .map_err(|_| "field_value parse error")?;
-                current_v >= target_v
-            }
-            "gt" => {
-                let target_v = target_value
-                    .parse::<f32>()
-                    .map_err(|_| "target_value parse error")?;
-                let current_v = field_value
-                    .parse::<f32>()
-                    .map_err(|_| "field_value parse error")?;
-                current_v > target_v
-            }
-            _ => {
+        let check = match field_value.compare(&express, &target_value) {
+            Ok(check) => check,
+            Err(e) => {
                 let elapsed = start.elapsed();
                 logd!(3, "meet_scenario_condition: elapsed = {:?}", elapsed);
-                return Err("wrong expression in condition".into());
+                return Err(e.into());
             }
         };
 
+        let stays = match condition.get_exit_value() {
+            Some(exit_value) => field_value.compare(&express, &exit_value)?,
+            None => check,
+        };
+
         let elapsed = start.elapsed();
         logd!(1, "meet_scenario_condition: elapsed = {:?}", elapsed);
 
-        if check {
-            logd!(1, "Condition met for scenario: {}", self.scenario_name);
-            logd!(1, "🔄 SCENARIO STATE TRANSITION: FilterGateway Processing");
-            logd!(1, "   📋 Scenario: {}", self.scenario_name);
-            logd!(1, "   🔄 State Change: idle → waiting");
-            logd!(1, "   🔍 Reason: Scenario condition satisfied");
-
-            // 🔍 COMMENT 1: FilterGateway condition registration
-            // When scenario condition is met, FilterGateway triggers ActionController
-            // via gRPC call. This initiates the scenario processing workflow.
-            // The ActionController will then handle state changes with StateManager.
-
-            // Send state change to StateManager: waiting -> satisfied
-            let timestamp = std::time::SystemTime::now()
-                .duration_since(std::time::UNIX_EPOCH)
-                .unwrap()
-                .as_nanos() as i64;
-
-            let state_change = StateChange {
-                resource_type: ResourceType::Scenario as i32,
-                resource_name: self.scenario_name.clone(),
-                current_state: "waiting".to_string(),
-                target_state: "satisfied".to_string(),
-                transition_id: format!("filtergateway-condition-satisfied-{}", timestamp),
-                timestamp_ns: timestamp,
-                source: "filtergateway".to_string(),
-            };
-
-            logd!(1, "   📤 Sending StateChange to StateManager:");
-            logd!(1, "      • Resource Type: SCENARIO");
-            logd!(1, "      • Resource Name: {}", state_change.resource_name);
-            logd!(1, "      • Current State: {}", state_change.current_state);
-            logd!(1, "      • Target State: {}", state_change.target_state);
-            logd!(1, "      • Transition ID: {}", state_change.transition_id);
-            logd!(1, "      • Source: {}", state_change.source);
-
-            if let Err(e) = self
-                .state_sender
-                .clone()
-                .send_state_change(state_change)
-                .await
-            {
-                logd!(
-                    5,
-                    "   ❌ Failed to send state change to StateManager: {:?}",
-                    e
-                );
-            } else {
-                logd!(
-                    1,
-                    "   ✅ Successfully notified StateManager: scenario {} waiting → satisfied",
-                    self.scenario_name
-                );
+        let met = match self.stability.as_mut() {
+            Some(tracker) => {
+                let met = tracker.update(check, stays, data.received_at);
+                self.data_met = tracker.is_met();
+                met
+            }
+            None => {
+                self.data_met = check;
+                check
             }
+        };
+        Ok(met && self.in_window(data.received_at))
+    }
+
+    /// Whether `at` is within the time window of the condition, if it has one
+    fn in_window(&self, at: SystemTime) -> bool {
+        self.window
+            .as_ref()
+            .is_none_or(|window| window.contains(at.into()))
+    }
 
-            logd!(1, "   📤 Triggering ActionController via gRPC...");
-            self.sender
-                .trigger_action(self.scenario_name.clone())
-                .await?;
-            logd!(2, "   ✅ ActionController triggered successfully");
-            Ok(())
+    /// First time the window of the condition opens strictly after `after`
+    pub fn next_window_start(&self, after: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        self.window.as_ref()?.next_start(after)
+    }
+
+    /// Whether the window opened in `(since, now]` while the vehicle data
+    /// already met the condition, so the action must run without a new sample
+    pub fn window_opened(&self, since: DateTime<Utc>, now: DateTime<Utc>) -> bool {
+        self.is_active
+            && self.data_met
+            && self
+                .next_window_start(since)
+                .is_some_and(|start| start <= now)
+    }
+
+    /// Run the scenario action
+    ///
+    /// Notifies StateManager that the scenario is satisfied, triggers the
+    /// action through ActionController and publishes the acknowledgement.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<()>` - Success or error result
+    pub async fn run_action(&mut self) -> Result<()> {
+        logd!(1, "Condition met for scenario: {}", self.scenario_name);
+        logd!(1, "🔄 SCENARIO STATE TRANSITION: FilterGateway Processing");
+        logd!(1, "   📋 Scenario: {}", self.scenario_name);
+        logd!(1, "   🔄 State Change: idle → waiting");
+        logd!(1, "   🔍 Reason: Scenario condition satisfied");
+
+        // 🔍 COMMENT 1: FilterGateway condition registration
+        // When scenario condition is met, FilterGateway triggers ActionController
+        // via gRPC call. This initiates the scenario processing workflow.
+        // The ActionController will then handle state changes with StateManager.
+
+        // Send state change to StateManager: waiting -> satisfied
+        let timestamp = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap()
+            .as_nanos() as i64;
+
+        let state_change = StateChange {
+            resource_type: ResourceType::Scenario as i32,
+            resource_name: self.scenario_name.clone(),
+            current_state: "waiting".to_string(),
+            target_state: "satisfied".to_string(),
+            transition_id: format!("filtergateway-condition-satisfied-{}", timestamp),
+            timestamp_ns: timestamp,
+            source: "filtergateway".to_string(),
+            trace_id: String::new(),
+        };
+
+        logd!(1, "   📤 Sending StateChange to StateManager:");
+        logd!(1, "      • Resource Type: SCENARIO");
+        logd!(1, "      • Resource Name: {}", state_change.resource_name);
+        logd!(1, "      • Current State: {}", state_change.current_state);
+        logd!(1, "      • Target State: {}", state_change.target_state);
+        logd!(1, "      • Transition ID: {}", state_change.transition_id);
+        logd!(1, "      • Source: {}", state_change.source);
+
+        if let Err(e) = self
+            .state_sender
+            .clone()
+            .send_state_change(state_change)
+            .await
+        {
+            logd!(
+                5,
+                "   ❌ Failed to send state change to StateManager: {:?}",
+                e
+            );
         } else {
-            Err("cannot meet condition".into())
+            logd!(
+                1,
+                "   ✅ Successfully notified StateManager: scenario {} waiting → satisfied",
+                self.scenario_name
+            );
         }
+
+        logd!(1, "   📤 Triggering ActionController via gRPC...");
+        self.sender
+            .trigger_action(self.scenario_name.clone())
+            .await?;
+        logd!(2, "   ✅ ActionController triggered successfully");
+
+        if let Some(ack) = &self.acknowledgement {
+            if let Err(e) = crate::vehicle::dds::publisher::publish_raw(
+                &ack.topic_name,
+                ack.value.clone(),
+                ack.domain_id,
+            )
+            .await
+            {
+                logd!(4, "   ⚠️ Failed to publish acknowledgement: {:?}", e);
+            }
+        }
+        Ok(())
     }
 
     /// Pause the filter processing
@@ -256,6 +372,21 @@ impl Filter {
         self.is_active
     }
 
+    /// Check if the scenario condition refers to the topic of `data`
+    ///
+    /// # Arguments
+    ///
+    /// * `data` - Received DDS data
+    ///
+    /// # Returns
+    ///
+    /// * `bool` - False for other topics and for scenarios without conditions
+    pub fn watches(&self, data: &DdsData) -> bool {
+        self.scenario
+            .get_conditions()
+            .is_some_and(|condition| data.name == condition.get_operand_value())
+    }
+
     /// Process DDS data and check conditions
     ///
     /// Processes received DDS data and checks scenario conditions.
@@ -276,22 +407,14 @@ impl Filter {
 
         logd!(
             1,
-            "process data for scenario: {}\nTopic: {:?}\nTarget Name: {:?}\nTarget Value: {:?}\n",
+            "process data for scenario: {}\nData: {}\n",
             self.scenario_name,
-            data.name,
-            data.value,
-            data.fields
+            data
         );
 
-        // Check if topic matches filter condition
-        let condition = match self.scenario.get_conditions() {
-            Some(c) => c,
-            None => return Ok(()), // No conditions case (already handled)
-        };
-
-        let topic = condition.get_operand_value();
-        if !data.name.eq(&topic) {
-            return Ok(()); // Ignore unrelated topics
+        // Ignore unrelated topics and scenarios without conditions (already handled)
+        if !self.watches(data) {
+            return Ok(());
         }
 
         // Perform condition check
@@ -312,6 +435,53 @@ impl Filter {
         Ok(())
     }
 }
+
+impl Drop for Filter {
+    fn drop(&mut self) {
+        metrics::unregister(&self.scenario_name, &self.metrics);
+    }
+}
+
+/// Resolve conflicts between scenarios matched by the same DDS data
+///
+/// Scenarios conflict when they act on the same target with different
+/// actions. Within a target, the scenario with the highest priority wins,
+/// with ties going to the first name in alphabetical order, and every
+/// scenario whose action differs from the winner's is suppressed.
+/// Scenarios without a priority count as priority 0.
+///
+/// # Arguments
+///
+/// * `scenarios` - Scenarios whose conditions were met
+///
+/// # Returns
+///
+/// * `Vec<Option<usize>>` - For each scenario, `None` if its action runs or
+///   the index of the scenario that suppresses it
+pub fn resolve_conflicts(scenarios: &[&Scenario]) -> Vec<Option<usize>> {
+    let rank = |i: usize| {
+        (
+            std::cmp::Reverse(scenarios[i].get_priority().unwrap_or_default()),
+            scenarios[i].get_name(),
+        )
+    };
+
+    (0..scenarios.len())
+        .map(|i| {
+            let target = scenarios[i].get_targets();
+            let winner = (0..scenarios.len())
+                .filter(|j| scenarios[*j].get_targets() == target)
+                .min_by_key(|j| rank(*j))
+                .unwrap_or(i);
+            if scenarios[winner].get_actions() == scenarios[i].get_actions() {
+                None
+            } else {
+                Some(winner)
+            }
+        })
+        .collect()
+}
+
 //Unit Test Cases
 #[cfg(test)]
 mod tests {
@@ -717,4 +887,168 @@ mod tests {
 
         assert!(result.is_ok());
     }
+
+    /// Scenario with the given target, action and optional priority
+    fn artifact_scenario(
+        name: &str,
+        target: &str,
+        action: &str,
+        priority: Option<i32>,
+    ) -> common::spec::artifact::Scenario {
+        let priority = priority
+            .map(|p| format!("  priority: {}\n", p))
+            .unwrap_or_default();
+        serde_yaml::from_str(&format!(
+            "apiVersion: v1\nkind: Scenario\nmetadata:\n  name: {}\nspec:\n  condition:\n    express: eq\n    value: \"true\"\n    operands:\n      type: DDS\n      name: value\n      value: BodyLightsHeadLampStatus\n  action: {}\n  target: {}\n{}",
+            name, action, target, priority
+        ))
+        .unwrap()
+    }
+
+    // Test: Higher-priority action wins over a conflicting one
+    #[test]
+    fn test_resolve_conflicts_higher_priority_wins() {
+        let light_on = artifact_scenario("light-on", "headlamp", "launch", Some(10));
+        let light_off = artifact_scenario("light-off", "headlamp", "terminate", Some(1));
+
+        let resolution = super::resolve_conflicts(&[&light_off, &light_on]);
+
+        assert_eq!(resolution, vec![Some(1), None]);
+    }
+
+    // Test: Equal priorities are resolved by scenario name
+    #[test]
+    fn test_resolve_conflicts_tie_broken_by_name() {
+        let b = artifact_scenario("b-light-off", "headlamp", "terminate", None);
+        let a = artifact_scenario("a-light-on", "headlamp", "launch", Some(0));
+
+        assert_eq!(super::resolve_conflicts(&[&b, &a]), vec![Some(1), None]);
+        assert_eq!(super::resolve_conflicts(&[&a, &b]), vec![None, Some(0)]);
+    }
+
+    // Test: Scenarios with the same action or different targets do not conflict
+    #[test]
+    fn test_resolve_conflicts_without_conflict() {
+        let on_high = artifact_scenario("on-high", "headlamp", "launch", Some(5));
+        let on_low = artifact_scenario("on-low", "headlamp", "launch", Some(1));
+        let wiper_off = artifact_scenario("wiper-off", "wiper", "terminate", None);
+
+        assert_eq!(
+            super::resolve_conflicts(&[&on_high, &on_low, &wiper_off]),
+            vec![None, None, None]
+        );
+        assert!(super::resolve_conflicts(&[]).is_empty());
+    }
+
+    /// Feeds `speeds`, one sample every 50ms, to a filter on `speed gt 80`
+    /// and returns the samples at which the condition was reported met
+    fn fire_on_speeds(stability: &str, speeds: &[i64]) -> Vec<usize> {
+        use crate::vehicle::dds::{DdsData, DdsValue};
+        use std::time::{Duration, SystemTime};
+
+        let scenario: common::spec::artifact::Scenario = serde_yaml::from_str(&format!(
+            "apiVersion: v1\nkind: Scenario\nmetadata:\n  name: overspeed\nspec:\n  condition:\n    express: gt\n    value: \"80\"\n    operands:\n      type: DDS\n      name: speed\n      value: VehicleSpeed\n{}  action: launch\n  target: overspeed-warning\n",
+            stability
+        ))
+        .unwrap();
+        let mut filter = super::Filter::new(
+            "overspeed".into(),
+            scenario,
+            true,
+            crate::grpc::sender::actioncontroller::FilterGatewaySender::new(),
+        );
+
+        speeds
+            .iter()
+            .enumerate()
+            .filter(|(i, speed)| {
+                let mut data = DdsData::new("VehicleSpeed", "");
+                data.fields
+                    .insert("speed".to_string(), DdsValue::Int64(**speed));
+                data.received_at = SystemTime::UNIX_EPOCH + Duration::from_millis(*i as u64 * 50);
+                filter.condition_met(&data).unwrap()
+            })
+            .map(|(i, _)| i)
+            .collect()
+    }
+
+    const OSCILLATING_SPEED: [i64; 12] = [79, 81, 79, 81, 82, 83, 84, 79, 81, 78, 76, 74];
+
+    // Test: Without debounce or hysteresis every sample above the threshold fires
+    #[tokio::test]
+    async fn test_oscillating_signal_without_stability() {
+        assert_eq!(
+            fire_on_speeds("", &OSCILLATING_SPEED),
+            vec![1, 3, 4, 5, 6, 8]
+        );
+    }
+
+    // Test: Debounce ignores excursions shorter than the debounce time
+    #[tokio::test]
+    async fn test_oscillating_signal_with_debounce() {
+        assert_eq!(
+            fire_on_speeds("    debounceMs: 100\n", &OSCILLATING_SPEED),
+            vec![5]
+        );
+    }
+
+    // Test: Hysteresis keeps the condition met until the exit threshold is crossed
+    #[tokio::test]
+    async fn test_oscillating_signal_with_hysteresis() {
+        assert_eq!(
+            fire_on_speeds("    exitValue: \"75\"\n", &OSCILLATING_SPEED),
+            vec![1]
+        );
+        assert_eq!(
+            fire_on_speeds("    exitValue: \"75\"\n", &[81, 74, 81, 76, 81]),
+            vec![0, 2]
+        );
+    }
+
+    // Test: A condition limited to a time window is only met within it, and
+    // the window opening fires it when the data already meets it
+    #[tokio::test]
+    async fn test_condition_limited_to_window() {
+        use crate::vehicle::dds::DdsData;
+        use chrono::{DateTime, Utc};
+
+        let scenario: common::spec::artifact::Scenario = serde_yaml::from_str(
+            "apiVersion: v1\nkind: Scenario\nmetadata:\n  name: night-charging\nspec:\n  condition:\n    express: eq\n    value: open\n    operands:\n      type: DDS\n      name: status\n      value: ChargingFlapStatus\n    window:\n      start: \"22:00\"\n      end: \"06:00\"\n  action: launch\n  target: night-charging\n",
+        )
+        .unwrap();
+        let mut filter = super::Filter::new(
+            "night-charging".into(),
+            scenario,
+            true,
+            crate::grpc::sender::actioncontroller::FilterGatewaySender::new(),
+        );
+        let at = |rfc3339: &str| {
+            DateTime::parse_from_rfc3339(rfc3339)
+                .unwrap()
+                .with_timezone(&Utc)
+        };
+        let sample = |status: &str, received_at: &str| {
+            let mut data = DdsData::new("ChargingFlapStatus", "").with_field("status", status);
+            data.received_at = at(received_at).into();
+            data
+        };
+
+        // Flap opened in the afternoon, before the window
+        assert!(!filter
+            .condition_met(&sample("open", "2026-06-05T15:00:00Z"))
+            .unwrap());
+        assert!(!filter.window_opened(at("2026-06-05T15:00:00Z"), at("2026-06-05T21:59:00Z")));
+        assert!(filter.window_opened(at("2026-06-05T21:59:00Z"), at("2026-06-05T22:00:00Z")));
+
+        // Within the window, after midnight
+        assert!(filter
+            .condition_met(&sample("open", "2026-06-06T01:00:00Z"))
+            .unwrap());
+
+        // Flap closed before the next window opens
+        assert!(!filter
+            .condition_met(&sample("closed", "2026-06-06T15:00:00Z"))
+            .unwrap());
+        assert!(!filter.window_opened(at("2026-06-06T21:59:00Z"), at("2026-06-06T22:00:00Z")));
+    }
 }
@@ -0,0 +1,125 @@
+/*
+* SPDX-FileCopyrightText: Copyright 2024 LG Electronics Inc.
+* SPDX-License-Identifier: Apache-2.0
+*/
+//! Debounce and hysteresis of scenario conditions
+//!
+//! A noisy signal close to a threshold makes a condition flip on almost
+//! every sample. The tracker turns those raw evaluations into a stable
+//! state and reports only the moments the condition becomes met.
+
+use std::time::{Duration, SystemTime};
+
+/// Stable state of one scenario condition
+#[derive(Debug, Default)]
+pub struct StabilityTracker {
+    /// Time the condition must hold before it is met
+    debounce: Duration,
+    /// Whether the condition is currently met
+    met: bool,
+    /// Receive time of the first sample of the current run meeting the
+    /// entry threshold
+    pending_since: Option<SystemTime>,
+}
+
+impl StabilityTracker {
+    /// Create a tracker
+    ///
+    /// # Arguments
+    ///
+    /// * `debounce` - Time the condition must hold before it is met
+    pub fn new(debounce: Duration) -> Self {
+        Self {
+            debounce,
+            ..Default::default()
+        }
+    }
+
+    /// Whether the condition is currently met
+    pub fn is_met(&self) -> bool {
+        self.met
+    }
+
+    /// Feed the evaluation of one sample
+    ///
+    /// Debounce is measured between sample receive times, so a condition
+    /// becomes met on the first sample received at least `debounce` after
+    /// the run started. Leaving the met state is not debounced, as the exit
+    /// threshold already keeps it from flapping.
+    ///
+    /// # Arguments
+    ///
+    /// * `enters` - Whether the sample meets the entry threshold
+    /// * `stays` - Whether the sample is still within the exit threshold
+    /// * `received_at` - Time the sample was received
+    ///
+    /// # Returns
+    ///
+    /// * `bool` - True only when the condition has just become met
+    pub fn update(&mut self, enters: bool, stays: bool, received_at: SystemTime) -> bool {
+        if self.met {
+            self.met = stays;
+            return false;
+        }
+        if !enters {
+            self.pending_since = None;
+            return false;
+        }
+
+        let since = *self.pending_since.get_or_insert(received_at);
+        let held = received_at.duration_since(since).unwrap_or_default();
+        if held >= self.debounce {
+            self.met = true;
+            self.pending_since = None;
+        }
+        self.met
+    }
+}
+
+//Unit Test Cases
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(ms: u64) -> SystemTime {
+        SystemTime::UNIX_EPOCH + Duration::from_millis(ms)
+    }
+
+    #[test]
+    fn test_without_debounce_fires_on_entry_only() {
+        let mut tracker = StabilityTracker::new(Duration::ZERO);
+
+        assert!(tracker.update(true, true, at(0)));
+        assert!(!tracker.update(true, true, at(10)));
+        assert!(tracker.met);
+        assert!(!tracker.update(false, false, at(20)));
+        assert!(!tracker.met);
+        assert!(tracker.update(true, true, at(30)));
+    }
+
+    #[test]
+    fn test_debounce_requires_condition_to_hold() {
+        let mut tracker = StabilityTracker::new(Duration::from_millis(100));
+
+        assert!(!tracker.update(true, true, at(0)));
+        assert!(!tracker.update(true, true, at(50)));
+        // The run is broken, so the debounce starts again
+        assert!(!tracker.update(false, false, at(60)));
+        assert!(!tracker.update(true, true, at(70)));
+        assert!(!tracker.update(true, true, at(160)));
+        assert!(tracker.update(true, true, at(170)));
+        assert!(!tracker.update(true, true, at(300)));
+    }
+
+    #[test]
+    fn test_met_state_ends_at_exit_threshold() {
+        let mut tracker = StabilityTracker::new(Duration::ZERO);
+
+        assert!(tracker.update(true, true, at(0)));
+        // Below the entry threshold but within the exit threshold
+        assert!(!tracker.update(false, true, at(10)));
+        assert!(tracker.met);
+        assert!(!tracker.update(false, false, at(20)));
+        assert!(!tracker.met);
+    }
+}
@@ -0,0 +1,256 @@
+/*
+* SPDX-FileCopyrightText: Copyright 2024 LG Electronics Inc.
+* SPDX-License-Identifier: Apache-2.0
+*/
+//! Time windows of scenario conditions
+//!
+//! A window is a daily range of local wall-clock times, possibly spanning
+//! midnight and limited to some days of the week. Times are converted in
+//! the timezone set in FilterGateway settings, so a window keeps its wall
+//! clock times across DST shifts.
+
+use chrono::{
+    DateTime, Datelike, Duration, LocalResult, NaiveDate, NaiveTime, TimeZone, Utc, Weekday,
+};
+use chrono_tz::Tz;
+use common::spec::artifact::scenario::TimeWindow;
+use common::Result;
+
+/// Source of the current time, replaced in tests
+pub trait Clock: Send + Sync {
+    fn now(&self) -> DateTime<Utc>;
+}
+
+/// Clock of the system
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> DateTime<Utc> {
+        Utc::now()
+    }
+}
+
+/// Daily window of local time
+#[derive(Debug, Clone, PartialEq)]
+pub struct Window {
+    start: NaiveTime,
+    end: NaiveTime,
+    /// Days the window opens on, every day when empty
+    days: Vec<Weekday>,
+    tz: Tz,
+}
+
+impl Window {
+    /// Parse the window of a scenario condition
+    ///
+    /// # Arguments
+    ///
+    /// * `spec` - Window of the condition
+    /// * `tz` - Timezone of its times
+    ///
+    /// # Returns
+    ///
+    /// * `Result<Self>` - The window, or an error naming the invalid time or day
+    pub fn parse(spec: &TimeWindow, tz: Tz) -> Result<Self> {
+        let time = |value: String| {
+            NaiveTime::parse_from_str(&value, "%H:%M")
+                .map_err(|_| format!("invalid time '{}' in window, expected HH:MM", value))
+        };
+        let days = spec
+            .get_days()
+            .iter()
+            .map(|day| {
+                day.parse::<Weekday>()
+                    .map_err(|_| format!("invalid day '{}' in window", day))
+            })
+            .collect::<std::result::Result<_, _>>()?;
+        Ok(Self {
+            start: time(spec.get_start())?,
+            end: time(spec.get_end())?,
+            days,
+            tz,
+        })
+    }
+
+    /// Parse the window of a scenario condition in the timezone of the settings
+    pub fn from_settings(spec: &TimeWindow) -> Result<Self> {
+        let name = &common::setting::get_config().filtergateway.timezone;
+        let tz = name
+            .parse::<Tz>()
+            .map_err(|_| format!("invalid timezone '{}' in settings", name))?;
+        Self::parse(spec, tz)
+    }
+
+    /// Whether the window opens on `date`
+    fn opens_on(&self, date: NaiveDate) -> bool {
+        self.days.is_empty() || self.days.contains(&date.weekday())
+    }
+
+    /// Whether `at` falls within the window
+    ///
+    /// A window spanning midnight belongs to the day it opens on, so with
+    /// `days: [Fri]` it also covers the early hours of Saturday.
+    pub fn contains(&self, at: DateTime<Utc>) -> bool {
+        let local = at.with_timezone(&self.tz).naive_local();
+        let (date, time) = (local.date(), local.time());
+        if self.start < self.end {
+            self.opens_on(date) && self.start <= time && time < self.end
+        } else {
+            (time >= self.start && self.opens_on(date))
+                || (time < self.end && date.pred_opt().is_some_and(|d| self.opens_on(d)))
+        }
+    }
+
+    /// First time the window opens strictly after `after`
+    pub fn next_start(&self, after: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        let today = after.with_timezone(&self.tz).date_naive();
+        (-1..=7)
+            .filter_map(|offset| today.checked_add_signed(Duration::days(offset)))
+            .filter(|date| self.opens_on(*date))
+            .filter_map(|date| self.opening(date))
+            .find(|start| *start > after)
+    }
+
+    /// Time the window opens on `date`
+    ///
+    /// When the clocks go back the earlier of the two matching times is
+    /// used; when they go forward over the start, the window opens at the
+    /// shift.
+    fn opening(&self, date: NaiveDate) -> Option<DateTime<Utc>> {
+        let mut local = date.and_time(self.start);
+        // DST shifts are at most a few hours
+        for _ in 0..=24 * 60 {
+            match self.tz.from_local_datetime(&local) {
+                LocalResult::Single(start) | LocalResult::Ambiguous(start, _) => {
+                    return Some(start.with_timezone(&Utc))
+                }
+                LocalResult::None => local += Duration::minutes(1),
+            }
+        }
+        None
+    }
+}
+
+//Unit Test Cases
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn window(start: &str, end: &str, days: &str, tz: Tz) -> Window {
+        let spec: TimeWindow =
+            serde_yaml::from_str(&format!("start: \"{}\"\nend: \"{}\"\n{}", start, end, days))
+                .unwrap();
+        Window::parse(&spec, tz).unwrap()
+    }
+
+    fn utc(rfc3339: &str) -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339(rfc3339)
+            .unwrap()
+            .with_timezone(&Utc)
+    }
+
+    #[test]
+    fn test_parse_rejects_invalid_values() {
+        let spec = |yaml: &str| serde_yaml::from_str::<TimeWindow>(yaml).unwrap();
+
+        let err = Window::parse(&spec("start: \"25:00\"\nend: \"06:00\""), Tz::UTC).unwrap_err();
+        assert!(err.to_string().contains("invalid time '25:00'"));
+        let err = Window::parse(
+            &spec("start: \"22:00\"\nend: \"06:00\"\ndays: [Someday]"),
+            Tz::UTC,
+        )
+        .unwrap_err();
+        assert!(err.to_string().contains("invalid day 'Someday'"));
+    }
+
+    #[test]
+    fn test_contains_window_within_day() {
+        let window = window("08:00", "18:00", "", Tz::UTC);
+
+        assert!(!window.contains(utc("2026-06-01T07:59:59Z")));
+        assert!(window.contains(utc("2026-06-01T08:00:00Z")));
+        assert!(window.contains(utc("2026-06-01T17:59:59Z")));
+        assert!(!window.contains(utc("2026-06-01T18:00:00Z")));
+    }
+
+    #[test]
+    fn test_contains_window_spanning_midnight() {
+        // 2026-06-05 is a Friday
+        let window = window("22:00", "06:00", "days: [Fri]\n", Tz::UTC);
+
+        assert!(!window.contains(utc("2026-06-05T21:59:00Z")));
+        assert!(window.contains(utc("2026-06-05T22:00:00Z")));
+        assert!(window.contains(utc("2026-06-06T05:59:00Z")));
+        assert!(!window.contains(utc("2026-06-06T06:00:00Z")));
+        // Saturday night is not a Friday window
+        assert!(!window.contains(utc("2026-06-06T23:00:00Z")));
+        // Nor the early hours of Friday
+        assert!(!window.contains(utc("2026-06-05T01:00:00Z")));
+    }
+
+    #[test]
+    fn test_next_start_spanning_midnight() {
+        let window = window("22:00", "06:00", "", Tz::UTC);
+
+        // Inside the window, the next start is the next evening
+        assert_eq!(
+            window.next_start(utc("2026-06-06T01:00:00Z")),
+            Some(utc("2026-06-06T22:00:00Z"))
+        );
+        assert_eq!(
+            window.next_start(utc("2026-06-06T22:00:00Z")),
+            Some(utc("2026-06-07T22:00:00Z"))
+        );
+
+        let weekly = Window {
+            days: vec![Weekday::Mon],
+            ..window
+        };
+        // 2026-06-06 is a Saturday
+        assert_eq!(
+            weekly.next_start(utc("2026-06-06T12:00:00Z")),
+            Some(utc("2026-06-08T22:00:00Z"))
+        );
+    }
+
+    #[test]
+    fn test_window_keeps_local_time_across_dst() {
+        let berlin: Tz = "Europe/Berlin".parse().unwrap();
+        let window = window("22:00", "06:00", "", berlin);
+
+        // CET (UTC+1) before the shift on 2026-03-29, CEST (UTC+2) after
+        assert_eq!(
+            window.next_start(utc("2026-03-28T12:00:00Z")),
+            Some(utc("2026-03-28T21:00:00Z"))
+        );
+        assert_eq!(
+            window.next_start(utc("2026-03-29T12:00:00Z")),
+            Some(utc("2026-03-29T20:00:00Z"))
+        );
+        // The night of the shift ends at 06:00 CEST
+        assert!(window.contains(utc("2026-03-29T03:59:00Z")));
+        assert!(!window.contains(utc("2026-03-29T04:00:00Z")));
+        // The night the clocks go back ends at 06:00 CET
+        assert!(window.contains(utc("2026-10-25T04:59:00Z")));
+        assert!(!window.contains(utc("2026-10-25T05:00:00Z")));
+    }
+
+    #[test]
+    fn test_start_skipped_by_dst_opens_at_shift() {
+        let berlin: Tz = "Europe/Berlin".parse().unwrap();
+        // 02:30 does not exist on 2026-03-29: clocks jump from 02:00 to 03:00 CEST
+        let window = window("02:30", "04:00", "", berlin);
+
+        assert_eq!(
+            window.next_start(utc("2026-03-28T12:00:00Z")),
+            Some(utc("2026-03-29T01:00:00Z"))
+        );
+        assert!(window.contains(utc("2026-03-29T01:00:00Z")));
+
+        // 02:30 happens twice on 2026-10-25, the window opens at the first
+        assert_eq!(
+            window.next_start(utc("2026-10-24T12:00:00Z")),
+            Some(utc("2026-10-25T00:30:00Z"))
+        );
+    }
+}
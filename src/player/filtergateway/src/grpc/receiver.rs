@@ -17,7 +17,7 @@ use tonic::{Request, Response, Status};
 // Import the generated protobuf code from filtergateway.proto
 use common::filtergateway::{
     filter_gateway_connection_server::{FilterGatewayConnection, FilterGatewayConnectionServer},
-    HandleScenarioRequest, HandleScenarioResponse,
+    GetMetricsRequest, GetMetricsResponse, HandleScenarioRequest, HandleScenarioResponse,
 };
 
 /// FilterGateway gRPC service handler
@@ -110,6 +110,26 @@ impl FilterGatewayConnection for FilterGatewayReceiver {
             desc: "Successfully handled scenario".to_string(),
         }))
     }
+
+    async fn get_metrics(
+        &self,
+        request: Request<GetMetricsRequest>,
+    ) -> std::result::Result<Response<GetMetricsResponse>, Status> {
+        let scenario = request.into_inner().scenario;
+        let scenarios = crate::filter::metrics::snapshot(&scenario);
+        if scenarios.is_empty() && !scenario.is_empty() {
+            return Err(Status::not_found(format!(
+                "No filter running for scenario {}",
+                scenario
+            )));
+        }
+        let topics = if scenario.is_empty() {
+            crate::vehicle::dds::ingest::snapshot()
+        } else {
+            Vec::new()
+        };
+        Ok(Response::new(GetMetricsResponse { scenarios, topics }))
+    }
 }
 //Unit Test Cases
 #[cfg(test)]
@@ -255,4 +275,39 @@ mod tests {
             .await;
         assert!(result.is_ok());
     }
+
+    // GetMetrics returns the metrics of a running filter and rejects unknown scenarios
+    #[tokio::test]
+    async fn test_get_metrics() {
+        use crate::filter::metrics;
+        use common::filtergateway::{
+            filter_gateway_connection_server::FilterGatewayConnection, GetMetricsRequest,
+        };
+        use tonic::{Code, Request};
+
+        let (tx, _rx) = mpsc::channel(1);
+        let receiver = FilterGatewayReceiver::new(tx);
+        let filter_metrics = metrics::register("receiver-metrics-test");
+        metrics::FilterMetrics::incr(&filter_metrics.matches);
+
+        let response = receiver
+            .get_metrics(Request::new(GetMetricsRequest {
+                scenario: "receiver-metrics-test".to_string(),
+            }))
+            .await
+            .unwrap()
+            .into_inner();
+        assert_eq!(response.scenarios.len(), 1);
+        assert_eq!(response.scenarios[0].matches, 1);
+        assert!(response.topics.is_empty());
+
+        metrics::unregister("receiver-metrics-test", &filter_metrics);
+        let status = receiver
+            .get_metrics(Request::new(GetMetricsRequest {
+                scenario: "receiver-metrics-test".to_string(),
+            }))
+            .await
+            .unwrap_err();
+        assert_eq!(status.code(), Code::NotFound);
+    }
 }
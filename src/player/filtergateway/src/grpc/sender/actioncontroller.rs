@@ -11,6 +11,9 @@ use common::actioncontroller::connect_server;
 // Import the generated protobuf code from actioncontroller.proto
 use common::actioncontroller::action_controller_connection_client::ActionControllerConnectionClient;
 
+/// Number of times a trigger is sent while ActionController is unavailable
+const TRIGGER_ATTEMPTS: usize = 2;
+
 /// Sender for making gRPC requests to ActionController
 #[derive(Clone)]
 pub struct FilterGatewaySender {}
@@ -33,6 +36,10 @@ impl FilterGatewaySender {
 
     /// Trigger an action for a scenario
     ///
+    /// The trigger carries a fresh idempotency key and is sent once more
+    /// with the same key if ActionController is unavailable, so a trigger
+    /// that reached it before the failure does not run twice.
+    ///
     /// # Arguments
     ///
     /// * `scenario_name` - Name of the scenario
@@ -47,16 +54,54 @@ impl FilterGatewaySender {
         use common::actioncontroller::TriggerActionRequest;
         let mut client = ActionControllerConnectionClient::connect(connect_server())
             .await
-            .unwrap();
+            .map_err(|e| anyhow::anyhow!("Failed to connect to ActionController: {:?}", e))?;
+
+        let request = TriggerActionRequest {
+            scenario_name,
+            idempotency_key: uuid::Uuid::new_v4().to_string(),
+        };
 
-        let request = TriggerActionRequest { scenario_name };
+        let mut attempt = 1;
+        loop {
+            match client
+                .trigger_action(common::trace::request(request.clone()))
+                .await
+            {
+                Ok(_) => return Ok(()),
+                Err(e) if e.code() == tonic::Code::Unavailable && attempt < TRIGGER_ATTEMPTS => {
+                    common::logd!(4, "Retrying trigger of {}: {:?}", request.scenario_name, e);
+                    attempt += 1;
+                }
+                Err(e) => {
+                    common::logd!(5, "Failed to trigger action: {:?}", e);
+                    return Err(anyhow::anyhow!("Failed to trigger action: {:?}", e).into());
+                }
+            }
+        }
+    }
 
-        client.trigger_action(request).await.map_err(|e| {
-            common::logd!(5, "Failed to trigger action: {:?}", e);
-            anyhow::anyhow!("Failed to trigger action: {:?}", e)
-        })?;
+    /// Cancel the delayed action of a withdrawn scenario
+    ///
+    /// # Arguments
+    ///
+    /// * `scenario_name` - Name of the scenario
+    ///
+    /// # Returns
+    ///
+    /// * `Result<bool>` - Whether an action of the scenario was pending
+    pub async fn cancel_scheduled(&mut self, scenario_name: String) -> Result<bool> {
+        use common::actioncontroller::CancelScheduledRequest;
+        let mut client = ActionControllerConnectionClient::connect(connect_server())
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to connect to ActionController: {:?}", e))?;
 
-        Ok(())
+        let response = client
+            .cancel_scheduled(common::trace::request(CancelScheduledRequest {
+                scenario_name,
+            }))
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to cancel delayed action: {:?}", e))?;
+        Ok(response.into_inner().cancelled)
     }
 }
 
@@ -70,7 +115,9 @@ mod tests {
         action_controller_connection_server::{
             ActionControllerConnection, ActionControllerConnectionServer,
         },
+        CancelScheduledRequest, CancelScheduledResponse, DrainNodeRequest, DrainNodeResponse,
         ReconcileRequest, ReconcileResponse, TriggerActionRequest, TriggerActionResponse,
+        UncordonNodeRequest, UncordonNodeResponse,
     };
     use std::net::SocketAddr;
     use std::panic::{catch_unwind, AssertUnwindSafe};
@@ -117,6 +164,27 @@ mod tests {
                 acknowledged: true, // or false, depending on test needs
             }))
         }
+
+        async fn drain_node(
+            &self,
+            _request: Request<DrainNodeRequest>,
+        ) -> std::result::Result<Response<DrainNodeResponse>, Status> {
+            Ok(Response::new(DrainNodeResponse::default()))
+        }
+
+        async fn uncordon_node(
+            &self,
+            _request: Request<UncordonNodeRequest>,
+        ) -> std::result::Result<Response<UncordonNodeResponse>, Status> {
+            Ok(Response::new(UncordonNodeResponse::default()))
+        }
+
+        async fn cancel_scheduled(
+            &self,
+            _request: Request<CancelScheduledRequest>,
+        ) -> std::result::Result<Response<CancelScheduledResponse>, Status> {
+            Ok(Response::new(CancelScheduledResponse::default()))
+        }
     }
 
     async fn spawn_mock_server(
@@ -13,11 +13,12 @@
 //! filtering decisions, access control results, and security policy enforcement
 //! outcomes to the StateManager for proper resource state tracking.
 
+use common::rpc;
 use common::statemanager::{
     connect_server, state_manager_connection_client::StateManagerConnectionClient, ResourceType,
     StateChange, StateChangeResponse,
 };
-use tonic::{Request, Status};
+use tonic::Status;
 
 /// StateManager gRPC client for FilterGateway component.
 ///
@@ -98,9 +99,9 @@ impl StateManagerSender {
     /// * `Status::unknown` - Connection establishment failed (network, service unavailable, etc.)
     async fn ensure_connected(&mut self) -> Result<(), Status> {
         if self.client.is_none() {
-            match StateManagerConnectionClient::connect(connect_server()).await {
-                Ok(client) => {
-                    self.client = Some(client);
+            match rpc::connect(connect_server()).await {
+                Ok(channel) => {
+                    self.client = Some(StateManagerConnectionClient::new(channel));
                     Ok(())
                 }
                 Err(e) => Err(Status::unknown(format!(
@@ -160,7 +161,7 @@ impl StateManagerSender {
 
         if let Some(client) = &mut self.client {
             // Send the state change message via gRPC
-            client.send_state_change(Request::new(state_change)).await
+            rpc::call(state_change, |request| client.send_state_change(request)).await
         } else {
             // This should never happen due to ensure_connected, but provide safety fallback
             Err(Status::unknown("Client not connected"))
@@ -202,6 +203,7 @@ impl StateManagerSender {
             transition_id: format!("policy-{}", policy_id),
             timestamp_ns: timestamp,
             source: "filtergateway".to_string(),
+            trace_id: String::new(),
         };
 
         self.send_state_change(state_change).await
@@ -242,6 +244,7 @@ impl StateManagerSender {
             transition_id: format!("access-{}", access_control_id),
             timestamp_ns: timestamp,
             source: "filtergateway".to_string(),
+            trace_id: String::new(),
         };
 
         self.send_state_change(state_change).await
@@ -282,6 +285,7 @@ impl StateManagerSender {
             transition_id: format!("violation-{}", violation_id),
             timestamp_ns: timestamp,
             source: "filtergateway".to_string(),
+            trace_id: String::new(),
         };
 
         self.send_state_change(state_change).await
@@ -322,6 +326,7 @@ impl StateManagerSender {
             transition_id: format!("filter-{}", filter_id),
             timestamp_ns: timestamp,
             source: "filtergateway".to_string(),
+            trace_id: String::new(),
         };
 
         self.send_state_change(state_change).await
@@ -373,6 +378,7 @@ mod tests {
             transition_id: format!("policy-decision-{}", timestamp),
             timestamp_ns: timestamp,
             source: "filtergateway".to_string(),
+            trace_id: String::new(),
         };
 
         // Send the message and verify successful response
@@ -8,6 +8,7 @@ pub mod manager;
 pub mod vehicle;
 
 // Re-export what you need in tests:
+use common::logd;
 pub use common::spec::artifact::Scenario;
 pub use common::Result;
 pub use filter::Filter;
@@ -19,13 +20,56 @@ pub use vehicle::dds::listener;
 pub use vehicle::dds::DdsData;
 pub use vehicle::dds::DdsTopicListener;
 pub async fn launch_manager(rx_grpc: Receiver<ScenarioParameter>) {
+    launch_manager_until(rx_grpc, std::future::pending()).await
+}
+
+/// Runs the manager until `shutdown` completes, then processes what is
+/// still queued and returns
+pub async fn launch_manager_until<F>(rx_grpc: Receiver<ScenarioParameter>, shutdown: F)
+where
+    F: std::future::Future<Output = ()>,
+{
+    launch_manager_with_replay(rx_grpc, shutdown, None).await
+}
+
+/// Runs the manager until `shutdown` completes, replaying a recording of
+/// vehicle data once it is initialized
+///
+/// # Arguments
+///
+/// * `rx_grpc` - Receiver of scenario requests
+/// * `shutdown` - Completes when the manager should stop
+/// * `replay` - Recording file and replay speed factor, if any
+pub async fn launch_manager_with_replay<F>(
+    rx_grpc: Receiver<ScenarioParameter>,
+    shutdown: F,
+    replay: Option<(std::path::PathBuf, f64)>,
+) where
+    F: std::future::Future<Output = ()>,
+{
     let manager = manager::FilterGatewayManager::new(rx_grpc).await;
 
+    let metrics_log_secs = common::setting::get_config().filtergateway.metrics_log_secs;
+    if metrics_log_secs > 0 {
+        tokio::spawn(filter::metrics::log_periodically(
+            std::time::Duration::from_secs(metrics_log_secs),
+        ));
+    }
+
     match manager.initialize().await {
         Ok(_) => {
             println!("FilterGatewayManager successfully initialized");
+            if let Some((path, speed_factor)) = replay {
+                let tx = manager.dds_sender().await;
+                tokio::spawn(async move {
+                    match vehicle::replay(&path, speed_factor, tx).await {
+                        Ok(count) => logd!(3, "Replayed {} samples from {:?}", count, path),
+                        Err(e) => logd!(5, "Failed to replay {:?}: {:?}", path, e),
+                    }
+                });
+            }
             // Only proceed to run if initialization was successful
-            if let Err(e) = manager.run().await {
+            if let Err(e) = manager.run_until(shutdown).await {
                 eprintln!("Error running FilterGatewayManager: {:?}", e);
             }
         }
@@ -49,8 +93,18 @@ pub async fn initialize(tx_grpc: Sender<manager::ScenarioParameter>) {
     // manager.run().await;
 
     use common::filtergateway::filter_gateway_connection_server::FilterGatewayConnectionServer;
+    use common::health::{etcd_reachable, ServerHealth, PROBE_INTERVAL};
     use tonic::transport::Server;
 
+    let (health, health_service) = ServerHealth::new::<
+        FilterGatewayConnectionServer<crate::grpc::receiver::FilterGatewayReceiver>,
+    >();
+    let probe_tx = tx_grpc.clone();
+    health.spawn_probe(PROBE_INTERVAL, move || {
+        let channel_open = !probe_tx.is_closed();
+        async move { channel_open && etcd_reachable().await }
+    });
+
     let server = crate::grpc::receiver::FilterGatewayReceiver::new(tx_grpc);
     let addr = common::filtergateway::open_server()
         .parse()
@@ -59,7 +113,10 @@ pub async fn initialize(tx_grpc: Sender<manager::ScenarioParameter>) {
     println!("Piccolod gateway listening on {}", addr);
 
     let _ = Server::builder()
+        .layer(common::trace::TraceLayer)
+        .add_service(health_service)
+        .add_service(common::version::service("filtergateway"))
         .add_service(FilterGatewayConnectionServer::new(server))
-        .serve(addr)
+        .serve_with_shutdown(addr, health.shutdown_signal())
         .await;
 }
@@ -18,28 +18,67 @@ mod vehicle;
 //
 // Note: The `ScenarioParameter` type is re-exported from the manager module
 // via `lib.rs` to ensure a single source of truth and prevent type mismatches.
+use clap::Parser;
+use common::channel::bounded;
 use filtergateway::ScenarioParameter;
-use filtergateway::{initialize, launch_manager};
-use tokio::sync::mpsc::{channel, Receiver, Sender};
+use filtergateway::{initialize, launch_manager_with_replay};
+use std::path::PathBuf;
+use tokio::sync::mpsc::{Receiver, Sender};
+use tokio::sync::oneshot;
 
-use common::logd;
 use common::logd::logger;
+use tracing::trace;
+
+#[derive(Parser)]
+#[command(name = "filtergateway")]
+#[command(about = "Pullpiri FilterGateway")]
+struct Args {
+    /// Recording of vehicle data to feed to the scenario filters on startup
+    #[arg(long)]
+    replay: Option<PathBuf>,
+
+    /// Speed factor of the replay, 1.0 for real time
+    #[arg(long, default_value = "1.0")]
+    replay_speed: f64,
+}
 
 #[cfg(not(feature = "tarpaulin_include"))]
 #[tokio::main]
 async fn main() {
+    let args = Args::parse();
+    common::logging::init("filtergateway");
     let _ = logger::init_async_logger("filtergateway").await;
-    logd!(1, "Initializing FilterGateway");
+    trace!("Initializing FilterGateway");
 
-    // Initialize tracing subscriber for logging
-    let (tx_grpc, rx_grpc): (Sender<ScenarioParameter>, Receiver<ScenarioParameter>) = channel(100);
-    // Launch the manager thread
-    let mgr = launch_manager(rx_grpc);
+    let (tx_grpc, rx_grpc): (Sender<ScenarioParameter>, Receiver<ScenarioParameter>) = bounded(
+        "filtergateway_scenario",
+        common::setting::get_config()
+            .channels
+            .filtergateway_scenario,
+    );
+    // Launch the manager thread, stopped once the gRPC server has shut down
+    let (stop_tx, stop_rx) = oneshot::channel::<()>();
+    let replay = args.replay.map(|path| (path, args.replay_speed));
+    let mgr = launch_manager_with_replay(
+        rx_grpc,
+        async {
+            let _ = stop_rx.await;
+        },
+        replay,
+    );
+    tokio::pin!(mgr);
 
-    // Initialize the application
-    let grpc = initialize(tx_grpc);
+    // Initialize the application. The server returns on SIGTERM or Ctrl-C.
+    let grpc = async {
+        initialize(tx_grpc).await;
+        let _ = stop_tx.send(());
+    };
 
-    tokio::join!(mgr, grpc);
+    // Let the manager process queued requests before the process exits
+    tokio::select! {
+        _ = &mut mgr => {}
+        _ = grpc => mgr.await,
+    }
 }
 #[cfg(feature = "tarpaulin_include")]
 fn main() {
@@ -50,6 +89,8 @@ fn main() {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use filtergateway::launch_manager;
+    use tokio::sync::mpsc::channel;
     use tokio::task::LocalSet;
     use tokio::time::{sleep, Duration};
 
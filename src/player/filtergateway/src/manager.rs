@@ -2,18 +2,27 @@
 * SPDX-FileCopyrightText: Copyright 2024 LG Electronics Inc.
 * SPDX-License-Identifier: Apache-2.0
 */
-use crate::filter::Filter;
+use crate::filter::metrics::FilterMetrics;
+use crate::filter::window::{Clock, SystemClock};
+use crate::filter::{resolve_conflicts, Filter};
 use crate::grpc::sender::actioncontroller::FilterGatewaySender;
 use crate::grpc::sender::statemanager::StateManagerSender;
-use crate::vehicle::dds::DdsData;
+use crate::vehicle::dds::{DdsData, LastValueCache, ListenerStatus, Recorder};
 use crate::vehicle::VehicleManager;
-use common::logd;
+use common::channel::bounded;
 use common::spec::artifact::Scenario;
 use common::statemanager::{ResourceType, StateChange};
 use common::{spec::artifact::Artifact, Result};
+use tracing::{debug, error, info, trace, warn};
 // use dust_dds::infrastructure::wait_set::Condition;
+use chrono::{DateTime, Utc};
+use std::future::Future;
 use std::sync::Arc;
-use tokio::sync::{mpsc, Mutex};
+use std::time::{Duration, SystemTime};
+use tokio::sync::{mpsc, watch, Mutex};
+
+/// Longest time the window timer sleeps before looking for new windows
+const MAX_WINDOW_WAIT: Duration = Duration::from_secs(60);
 
 /// Manager for FilterGateway
 ///
@@ -42,6 +51,10 @@ pub struct FilterGatewayManager {
     pub sender: Arc<Mutex<FilterGatewaySender>>,
     /// Vehicle manager for handling vehicle data
     pub vehicle_manager: Arc<Mutex<VehicleManager>>,
+    /// Latest sample of every received data type
+    pub last_values: Arc<Mutex<LastValueCache>>,
+    /// Recording of received samples, when enabled in settings
+    pub recorder: Arc<Mutex<Option<Recorder>>>,
 }
 #[allow(dead_code)]
 impl FilterGatewayManager {
@@ -55,12 +68,15 @@ impl FilterGatewayManager {
     ///
     /// A new FilterGatewayManager instance
     pub async fn new(rx_grpc: mpsc::Receiver<ScenarioParameter>) -> Self {
-        let (tx_dds, rx_dds) = mpsc::channel::<DdsData>(10);
+        let (tx_dds, rx_dds) = bounded::<DdsData>(
+            "filtergateway_dds",
+            common::setting::get_config().channels.filtergateway_dds,
+        );
         let mut vehicle_manager = VehicleManager::new(tx_dds);
 
         // Improved error handling: explicit error handling instead of unwrap()
         if let Err(e) = vehicle_manager.init().await {
-            logd!(4, "Warning: Failed to initialize vehicle manager: {:?}. Continuing with default settings.", e);
+            warn!("Warning: Failed to initialize vehicle manager: {:?}. Continuing with default settings.", e);
             // Continue (already using default values in VehicleManager::init())
         }
 
@@ -70,6 +86,8 @@ impl FilterGatewayManager {
             filters: Arc::new(Mutex::new(Vec::new())),
             sender: Arc::new(Mutex::new(FilterGatewaySender::new())),
             vehicle_manager: Arc::new(Mutex::new(vehicle_manager)),
+            last_values: Arc::new(Mutex::new(LastValueCache::new())),
+            recorder: Arc::new(Mutex::new(Recorder::from_settings())),
         }
     }
     /// Function to initialize the FilterGatewayManager
@@ -82,46 +100,94 @@ impl FilterGatewayManager {
     ///
     /// * `Result<()>` - Success or error result
     pub async fn initialize(&self) -> Result<()> {
-        logd!(3, "FilterGatewayManager init");
+        info!("FilterGatewayManager init");
         // Initialize vehicle manager
         let etcd_scenario = Self::read_all_scenario_from_etcd()
             .await
             .unwrap_or_default();
 
+        let mut scenarios = Vec::new();
         for scenario in etcd_scenario {
             let scenario: Scenario = serde_yaml::from_str(&scenario)?;
-            logd!(3, "Scenario: {:?}", scenario);
+            info!("Scenario: {:?}", scenario);
+            scenarios.push(scenario);
+        }
+
+        // Only listen to the topics referenced by active scenarios
+        let topics = Self::scenario_topics(&scenarios);
+        {
+            let mut vehicle_manager = self.vehicle_manager.lock().await;
+            if let Err(e) = vehicle_manager.run(Some(&topics)).await {
+                error!("Error subscribing to vehicle data: {:?}", e);
+                // Fall back to per-topic subscription so known topics still start
+                for topic_name in &topics {
+                    if let Err(e) = vehicle_manager
+                        .subscribe_topic(topic_name.clone(), topic_name.clone())
+                        .await
+                    {
+                        error!("Error subscribing to vehicle data: {:?}", e);
+                    }
+                }
+            }
+        }
+
+        for scenario in scenarios {
+            self.launch_scenario_filter(scenario).await?;
+        }
+
+        Ok(())
+    }
+
+    /// Reports the health of the vehicle data listeners
+    ///
+    /// # Returns
+    ///
+    /// * `Vec<ListenerStatus>` - Listener statuses sorted by topic name
+    pub async fn listener_statuses(&self) -> Vec<ListenerStatus> {
+        self.vehicle_manager.lock().await.listener_statuses()
+    }
+
+    /// Channel the DDS listeners send received vehicle data to
+    ///
+    /// Data sent on it, e.g. a replayed recording, is evaluated by the
+    /// scenario filters like data received from the vehicle.
+    pub async fn dds_sender(&self) -> mpsc::Sender<DdsData> {
+        self.vehicle_manager.lock().await.get_sender()
+    }
+
+    /// Collects the vehicle topics referenced by scenario conditions
+    ///
+    /// # Arguments
+    ///
+    /// * `scenarios` - Scenarios to inspect
+    ///
+    /// # Returns
+    ///
+    /// * `Vec<String>` - Distinct topic names in scenario order
+    fn scenario_topics(scenarios: &[Scenario]) -> Vec<String> {
+        let mut topics: Vec<String> = Vec::new();
+        for scenario in scenarios {
             let topic_name = scenario
                 .get_conditions()
                 .as_ref()
                 .map(|cond| cond.get_operand_value())
                 .unwrap_or_default();
-            let data_type_name = scenario
-                .get_conditions()
-                .as_ref()
-                .map(|cond| cond.get_operand_value())
-                .unwrap_or_default();
-            let mut vehicle_manager = self.vehicle_manager.lock().await;
-            if let Err(e) = vehicle_manager
-                .subscribe_topic(topic_name, data_type_name)
-                .await
-            {
-                logd!(5, "Error subscribing to vehicle data: {:?}", e);
+            if !topic_name.is_empty() && !topics.contains(&topic_name) {
+                topics.push(topic_name);
             }
-            self.launch_scenario_filter(scenario).await?;
         }
-
-        Ok(())
+        topics
     }
 
     /// Function to receive subscribed DDS data and pass it to filters
     ///
     /// This function runs as a separate task to continuously receive and process DDS data.
+    /// Once `shutdown` is set, data already queued is still processed before it returns.
     ///
     /// # Returns
     ///
     /// * `Result<()>` - Success or error result
-    async fn process_dds_data(&self) -> Result<()> {
+    async fn process_dds_data(&self, mut shutdown: watch::Receiver<bool>) -> Result<()> {
         // Create clone of shared receiver
         let rx_dds = Arc::clone(&self.rx_dds);
 
@@ -130,37 +196,29 @@ impl FilterGatewayManager {
             let mut receiver = rx_dds.lock().await;
 
             // Receive DDS data
-            match receiver.recv().await {
+            match next_or_drain(&mut receiver, &mut shutdown).await {
                 Some(dds_data) => {
                     // Only print if topic or value is not empty
                     if !dds_data.name.is_empty() && !dds_data.value.is_empty() {
-                        logd!(
-                            3,
+                        info!(
                             "Received DDS data: topic={}, value={}",
-                            dds_data.name,
-                            dds_data.value
+                            dds_data.name, dds_data.value
                         );
                     }
 
-                    // Forward data to all active filters
-                    let mut filters = self.filters.lock().await;
-                    for filter in filters.iter_mut() {
-                        if filter.is_active() {
-                            // Pass DDS data to filter
-                            if let Err(e) = filter.process_data(&dds_data).await {
-                                logd!(
-                                    5,
-                                    "Error processing DDS data in filter {}: {:?}",
-                                    filter.scenario_name,
-                                    e
-                                );
-                            }
+                    self.last_values.lock().await.update(&dds_data);
+                    if let Some(recorder) = self.recorder.lock().await.as_mut() {
+                        if let Err(e) = recorder.record(&dds_data) {
+                            error!("Failed to record DDS data: {:?}", e);
                         }
                     }
+
+                    let mut filters = self.filters.lock().await;
+                    Self::trigger_matched_filters(&mut filters, &dds_data).await;
                 }
                 None => {
                     // Channel closed
-                    logd!(5, "DDS data channel closed, stopping processor");
+                    error!("DDS data channel closed, stopping processor");
                     break;
                 }
             }
@@ -169,24 +227,191 @@ impl FilterGatewayManager {
         Ok(())
     }
 
+    /// Function to run the actions of scenarios whose time window opens
+    ///
+    /// A scenario whose condition is already met when its window opens
+    /// receives no new sample to trigger it, so this task wakes up at the
+    /// next window start of any filter. It also wakes up every
+    /// `MAX_WINDOW_WAIT` to pick up filters launched in the meantime.
+    ///
+    /// # Arguments
+    ///
+    /// * `clock` - Source of the current time
+    /// * `shutdown` - Set when the manager should stop
+    async fn run_window_timer(&self, clock: Arc<dyn Clock>, mut shutdown: watch::Receiver<bool>) {
+        let mut since = clock.now();
+        loop {
+            let next = {
+                let filters = self.filters.lock().await;
+                filters
+                    .iter()
+                    .filter_map(|filter| filter.next_window_start(since))
+                    .min()
+            };
+            let wait = next
+                .and_then(|start| (start - clock.now()).to_std().ok())
+                .unwrap_or(MAX_WINDOW_WAIT)
+                .min(MAX_WINDOW_WAIT);
+            tokio::select! {
+                _ = tokio::time::sleep(wait) => {}
+                _ = shutdown.wait_for(|stop| *stop) => return,
+            }
+
+            let now = clock.now();
+            let mut filters = self.filters.lock().await;
+            Self::trigger_window_starts(&mut filters, since, now).await;
+            since = now;
+        }
+    }
+
+    /// Run the actions of the active filters whose conditions `dds_data` meets
+    ///
+    /// When matched scenarios conflict, only the action chosen by
+    /// `resolve_conflicts` runs and the others are logged as suppressed.
+    ///
+    /// # Arguments
+    ///
+    /// * `filters` - Scenario filters
+    /// * `dds_data` - Received DDS data
+    ///
+    /// # Returns
+    ///
+    /// * `Vec<String>` - Names of the scenarios whose actions were run
+    async fn trigger_matched_filters(filters: &mut [Filter], dds_data: &DdsData) -> Vec<String> {
+        let mut matched = Vec::new();
+        for (i, filter) in filters.iter_mut().enumerate() {
+            if !filter.watches(dds_data) {
+                continue;
+            }
+            FilterMetrics::incr(&filter.metrics().samples_received);
+            if !filter.is_active() {
+                continue;
+            }
+            FilterMetrics::incr(&filter.metrics().evaluations);
+            match filter.condition_met(dds_data) {
+                Ok(true) => {
+                    FilterMetrics::incr(&filter.metrics().matches);
+                    matched.push(i);
+                }
+                Ok(false) => {}
+                Err(e) => error!(
+                    "Error processing DDS data in filter {}: {:?}",
+                    filter.scenario_name, e
+                ),
+            }
+        }
+
+        Self::run_matched_actions(filters, matched, Some(dds_data.received_at)).await
+    }
+
+    /// Run the actions of the active filters whose time window just opened
+    /// while their condition was already met
+    ///
+    /// # Arguments
+    ///
+    /// * `filters` - Scenario filters
+    /// * `since` - Time of the previous check
+    /// * `now` - Current time
+    ///
+    /// # Returns
+    ///
+    /// * `Vec<String>` - Names of the scenarios whose actions were run
+    async fn trigger_window_starts(
+        filters: &mut [Filter],
+        since: DateTime<Utc>,
+        now: DateTime<Utc>,
+    ) -> Vec<String> {
+        let opened: Vec<usize> = (0..filters.len())
+            .filter(|i| filters[*i].window_opened(since, now))
+            .collect();
+        for i in &opened {
+            info!(
+                "Time window of scenario {} opened with its condition met",
+                filters[*i].scenario_name
+            );
+            FilterMetrics::incr(&filters[*i].metrics().matches);
+        }
+        Self::run_matched_actions(filters, opened, None).await
+    }
+
+    /// Run the actions of matched filters, resolving their conflicts
+    ///
+    /// When matched scenarios conflict, only the action chosen by
+    /// `resolve_conflicts` runs and the others are logged as suppressed.
+    ///
+    /// # Arguments
+    ///
+    /// * `filters` - Scenario filters
+    /// * `matched` - Indexes of the filters whose conditions were met
+    /// * `received_at` - Receive time of the sample that met them, if any
+    ///
+    /// # Returns
+    ///
+    /// * `Vec<String>` - Names of the scenarios whose actions were run
+    async fn run_matched_actions(
+        filters: &mut [Filter],
+        matched: Vec<usize>,
+        received_at: Option<SystemTime>,
+    ) -> Vec<String> {
+        let scenarios: Vec<&Scenario> = matched.iter().map(|i| &filters[*i].scenario).collect();
+        let suppressors: Vec<Option<String>> = resolve_conflicts(&scenarios)
+            .into_iter()
+            .map(|winner| winner.map(|w| scenarios[w].get_name()))
+            .collect();
+
+        let mut triggered = Vec::new();
+        for (i, suppressed_by) in matched.into_iter().zip(suppressors) {
+            if let Some(winner) = suppressed_by {
+                warn!("Suppressed action '{}' of scenario {}: conflicts with higher-priority scenario {}",
+                    filters[i].scenario.get_actions(),
+                    filters[i].scenario_name,
+                    winner
+                );
+                continue;
+            }
+            let filter = &mut filters[i];
+            let result = filter.run_action().await;
+            let metrics = filter.metrics();
+            if let Some(Ok(latency)) = received_at.map(|at| at.elapsed()) {
+                metrics.observe_latency(latency);
+            }
+            match result {
+                Ok(()) => {
+                    FilterMetrics::incr(&metrics.actions_sent);
+                    trace!("Action triggered for scenario: {}", filter.scenario_name);
+                    triggered.push(filter.scenario_name.clone());
+                }
+                Err(e) => {
+                    FilterMetrics::incr(&metrics.action_send_failures);
+                    error!(
+                        "Error processing DDS data in filter {}: {:?}",
+                        filter.scenario_name, e
+                    )
+                }
+            }
+        }
+        triggered
+    }
+
     /// Function to process gRPC requests
     ///
     /// This function processes scenario requests coming through gRPC.
+    /// Once `shutdown` is set, requests already queued are still processed before it returns.
     ///
     /// # Returns
     ///
     /// * `Result<()>` - Success or error result
-    async fn process_grpc_requests(&self) -> Result<()> {
+    async fn process_grpc_requests(&self, mut shutdown: watch::Receiver<bool>) -> Result<()> {
         loop {
             // Wait for scenario parameter from gRPC
             let scenario_parameter = {
                 let mut rx_grpc = self.rx_grpc.lock().await;
-                rx_grpc.recv().await
+                next_or_drain(&mut rx_grpc, &mut shutdown).await
             };
 
             match scenario_parameter {
                 Some(param) => {
-                    logd!(2, "Received scenario parameter: {:?}", param);
+                    debug!("Received scenario parameter: {:?}", param);
                     match param.action {
                         0 => {
                             // Allow
@@ -208,7 +433,7 @@ impl FilterGatewayManager {
                                 .subscribe_topic(topic_name, data_type_name)
                                 .await
                             {
-                                logd!(5, "Error subscribing to vehicle data: {:?}", e);
+                                error!("Error subscribing to vehicle data: {:?}", e);
                             }
                             self.launch_scenario_filter(param.scenario).await?;
                         }
@@ -220,17 +445,25 @@ impl FilterGatewayManager {
                                 .unsubscribe_topic(param.scenario.get_name().clone())
                                 .await
                             {
-                                logd!(5, "Error unsubscribing from vehicle data: {:?}", e);
+                                error!("Error unsubscribing from vehicle data: {:?}", e);
                             }
                             self.remove_scenario_filter(param.scenario.get_name().clone())
                                 .await?;
+                            // A delayed action must not run once its scenario is gone
+                            let mut sender = self.sender.lock().await;
+                            if let Err(e) = sender
+                                .cancel_scheduled(param.scenario.get_name().clone())
+                                .await
+                            {
+                                warn!("Error cancelling delayed action: {:?}", e);
+                            }
                         }
                         _ => {}
                     }
                 }
                 None => {
                     // Channel closed
-                    logd!(5, "gRPC channel closed, stopping processor");
+                    error!("gRPC channel closed, stopping processor");
                     break;
                 }
             }
@@ -242,33 +475,70 @@ impl FilterGatewayManager {
     /// Start the manager processing
     ///
     /// This function processes incoming scenario requests and
-    /// coordinates DDS data handling.
+    /// coordinates DDS data handling until both channels are closed.
     ///
     /// # Returns
     pub async fn run(self) -> Result<()> {
+        self.run_until(std::future::pending()).await
+    }
+
+    /// Start the manager processing until `shutdown` completes
+    ///
+    /// When `shutdown` completes, no new scenario requests or DDS data are
+    /// accepted. What is already queued is processed before this returns.
+    ///
+    /// # Arguments
+    ///
+    /// * `shutdown` - Completes when the manager should stop
+    ///
+    /// # Returns
+    ///
+    /// * `Result<()>` - Success or error result
+    pub async fn run_until<F: Future<Output = ()>>(self, shutdown: F) -> Result<()> {
         // 자신을 Arc로 래핑
         let arc_self = Arc::new(self);
+        let (stop_tx, stop_rx) = watch::channel(false);
 
         // DDS 데이터 처리 태스크 시작
         let gateway_dds_manager = Arc::clone(&arc_self);
+        let dds_stop = stop_rx.clone();
         let dds_processor = tokio::spawn(async move {
-            if let Err(e) = gateway_dds_manager.process_dds_data().await {
-                logd!(5, "Error in DDS processor: {:?}", e);
+            if let Err(e) = gateway_dds_manager.process_dds_data(dds_stop).await {
+                error!("Error in DDS processor: {:?}", e);
             }
         });
 
+        let window_manager = Arc::clone(&arc_self);
+        let window_stop = stop_rx.clone();
+        let window_timer = tokio::spawn(async move {
+            window_manager
+                .run_window_timer(Arc::new(SystemClock), window_stop)
+                .await;
+        });
+
         // gRPC 요청 처리를 위해 process_grpc_requests도 &self로 수정해야 함
         let gateway_grpc_manager = Arc::clone(&arc_self);
         let grpc_processor = tokio::spawn(async move {
-            if let Err(e) = gateway_grpc_manager.process_grpc_requests().await {
-                logd!(5, "Error in gRPC processor: {:?}", e);
+            if let Err(e) = gateway_grpc_manager.process_grpc_requests(stop_rx).await {
+                error!("Error in gRPC processor: {:?}", e);
             }
         });
 
         // 태스크 완료 대기
-        let _ = tokio::try_join!(dds_processor, grpc_processor);
+        let processors = async {
+            let _ = tokio::try_join!(dds_processor, grpc_processor, window_timer);
+        };
+        tokio::pin!(processors);
+        tokio::select! {
+            _ = &mut processors => {}
+            _ = shutdown => {
+                info!("FilterGatewayManager shutting down, draining queued messages");
+                let _ = stop_tx.send(true);
+                processors.await;
+            }
+        }
 
-        logd!(5, "FilterGatewayManager stopped");
+        error!("FilterGatewayManager stopped");
 
         Ok(())
     }
@@ -287,8 +557,7 @@ impl FilterGatewayManager {
         use std::time::Instant;
         let start = Instant::now();
 
-        logd!(
-            1,
+        trace!(
             "subscribe vehicle data {} - {}",
             vehicle_message.name,
             vehicle_message.value
@@ -299,7 +568,7 @@ impl FilterGatewayManager {
             .await?;
 
         let elapsed = start.elapsed();
-        logd!(1, "subscribe_vehicle_data: elapsed = {:?}", elapsed);
+        trace!("subscribe_vehicle_data: elapsed = {:?}", elapsed);
 
         Ok(())
     }
@@ -317,7 +586,7 @@ impl FilterGatewayManager {
     ///
     /// * `Result<()>` - Success or error result
     pub async fn unsubscribe_vehicle_data(&self, vehicle_message: DdsData) -> Result<()> {
-        logd!(3, "unsubscribe vehicle data {}", vehicle_message.name);
+        info!("unsubscribe vehicle data {}", vehicle_message.name);
         let mut vehicle_manager = self.vehicle_manager.lock().await;
         vehicle_manager
             .unsubscribe_topic(vehicle_message.name)
@@ -344,25 +613,19 @@ impl FilterGatewayManager {
 
         // Check if the scenario has conditions
         if scenario.get_conditions().is_none() {
-            logd!(3, "No conditions for scenario: {}", scenario.get_name());
+            info!("No conditions for scenario: {}", scenario.get_name());
             let mut sender = self.sender.lock().await;
             sender.trigger_action(scenario.get_name().clone()).await?;
             let elapsed = start.elapsed();
-            logd!(1, "launch_scenario_filter: elapsed = {:?}", elapsed);
+            trace!("launch_scenario_filter: elapsed = {:?}", elapsed);
             return Ok(());
         }
 
         // Set scenario state from idle to waiting when conditions are registered
-        logd!(
-            1,
-            "🔄 SCENARIO STATE TRANSITION: FilterGateway Condition Registration"
-        );
-        logd!(1, "   📋 Scenario: {}", scenario.get_name());
-        logd!(1, "   🔄 State Change: idle → waiting");
-        logd!(
-            1,
-            "   🔍 Reason: Scenario conditions registered in FilterGateway"
-        );
+        trace!("🔄 SCENARIO STATE TRANSITION: FilterGateway Condition Registration");
+        trace!("   📋 Scenario: {}", scenario.get_name());
+        trace!("   🔄 State Change: idle → waiting");
+        trace!("   🔍 Reason: Scenario conditions registered in FilterGateway");
 
         let timestamp = std::time::SystemTime::now()
             .duration_since(std::time::UNIX_EPOCH)
@@ -377,26 +640,22 @@ impl FilterGatewayManager {
             transition_id: format!("filtergateway-condition-registered-{}", timestamp),
             timestamp_ns: timestamp,
             source: "filtergateway".to_string(),
+            trace_id: String::new(),
         };
 
-        logd!(1, "   📤 Sending StateChange to StateManager:");
-        logd!(1, "      • Resource Type: SCENARIO");
-        logd!(1, "      • Resource Name: {}", state_change.resource_name);
-        logd!(1, "      • Current State: {}", state_change.current_state);
-        logd!(1, "      • Target State: {}", state_change.target_state);
-        logd!(1, "      • Transition ID: {}", state_change.transition_id);
-        logd!(1, "      • Source: {}", state_change.source);
+        trace!("   📤 Sending StateChange to StateManager:");
+        trace!("      • Resource Type: SCENARIO");
+        trace!("      • Resource Name: {}", state_change.resource_name);
+        trace!("      • Current State: {}", state_change.current_state);
+        trace!("      • Target State: {}", state_change.target_state);
+        trace!("      • Transition ID: {}", state_change.transition_id);
+        trace!("      • Source: {}", state_change.source);
 
         let mut state_sender = StateManagerSender::new();
         if let Err(e) = state_sender.send_state_change(state_change).await {
-            logd!(
-                5,
-                "   ❌ Failed to send state change to StateManager: {:?}",
-                e
-            );
+            error!("   ❌ Failed to send state change to StateManager: {:?}", e);
         } else {
-            logd!(
-                2,
+            debug!(
                 "   ✅ Successfully notified StateManager: scenario {} idle → waiting",
                 scenario.get_name()
             );
@@ -406,7 +665,7 @@ impl FilterGatewayManager {
             let sender_guard = self.sender.lock().await;
             sender_guard.clone()
         };
-        let filter = Filter::new(scenario.get_name().to_string(), scenario, true, sender);
+        let mut filter = Filter::new(scenario.get_name().to_string(), scenario, true, sender);
 
         // Add the filter to our managed collection
         {
@@ -416,22 +675,61 @@ impl FilterGatewayManager {
                 .iter()
                 .any(|f| f.scenario_name == filter.scenario_name)
             {
-                logd!(
-                    3,
+                info!(
                     "Filter for scenario '{}' already exists, skipping.",
                     filter.scenario_name
                 );
                 let elapsed = start.elapsed();
-                logd!(1, "launch_scenario_filter: elapsed = {:?}", elapsed);
+                trace!("launch_scenario_filter: elapsed = {:?}", elapsed);
                 return Ok(());
             }
+
+            // A condition already met by the latest sample fires right away
+            if let Some(sample) = self.match_cached_value(&mut filter).await {
+                info!(
+                    "Condition of scenario '{}' already met by cached {} sample",
+                    filter.scenario_name, sample.name
+                );
+                if let Err(e) = filter.run_action().await {
+                    error!(
+                        "Error triggering action of scenario {}: {:?}",
+                        filter.scenario_name, e
+                    );
+                }
+            }
             filters.push(filter);
         }
         let elapsed = start.elapsed();
-        logd!(1, "launch_scenario_filter: elapsed = {:?}", elapsed);
+        trace!("launch_scenario_filter: elapsed = {:?}", elapsed);
         Ok(())
     }
 
+    /// Evaluate a new filter against the latest sample of its topic
+    ///
+    /// # Arguments
+    ///
+    /// * `filter` - Filter being registered
+    ///
+    /// # Returns
+    ///
+    /// * `Option<DdsData>` - The cached sample, if one exists and meets the
+    ///   filter condition
+    async fn match_cached_value(&self, filter: &mut Filter) -> Option<DdsData> {
+        let topic = filter.scenario.get_conditions()?.get_operand_value();
+        let sample = self.last_values.lock().await.get(&topic).cloned()?;
+        match filter.condition_met(&sample) {
+            Ok(true) => Some(sample),
+            Ok(false) => None,
+            Err(e) => {
+                warn!(
+                    "Cannot evaluate scenario {} against cached {} sample: {:?}",
+                    filter.scenario_name, topic, e
+                );
+                None
+            }
+        }
+    }
+
     /// Remove a filter for a scenario
     ///
     /// Stops and removes the filter associated with a scenario.
@@ -444,7 +742,7 @@ impl FilterGatewayManager {
     ///
     /// * `Result<()>` - Success or error result
     pub async fn remove_scenario_filter(&self, scenario_name: String) -> Result<()> {
-        logd!(3, "remove filter {}\n", scenario_name);
+        info!("remove filter {}\n", scenario_name);
 
         let arc_filters = Arc::clone(&self.filters);
         let mut filters = arc_filters.lock().await;
@@ -464,12 +762,30 @@ impl FilterGatewayManager {
     /// ### Return
     /// * `Result<Vec<String>>` - `Ok(_)` contains scenario yaml string vector
     async fn read_all_scenario_from_etcd() -> common::Result<Vec<String>> {
-        let kv_scenario = common::etcd::get_all_with_prefix("Scenario").await?;
+        let kv_scenario = common::etcd::get_all_with_prefix(common::keys::SCENARIO_PREFIX).await?;
         let values = kv_scenario.into_iter().map(|kv| kv.1).collect();
 
         Ok(values)
     }
 }
+/// Receives the next message, or drains the channel once `shutdown` is set
+///
+/// After shutdown the channel is closed so senders fail, and the messages
+/// already queued are returned before `None`.
+async fn next_or_drain<T>(
+    receiver: &mut mpsc::Receiver<T>,
+    shutdown: &mut watch::Receiver<bool>,
+) -> Option<T> {
+    if !*shutdown.borrow() {
+        tokio::select! {
+            message = receiver.recv() => return message,
+            _ = shutdown.wait_for(|stop| *stop) => {}
+        }
+    }
+    receiver.close();
+    receiver.recv().await
+}
+
 //Unit Tets Cases
 #[cfg(test)]
 mod tests {
@@ -1499,6 +1815,7 @@ mod tests {
                 transition_id: format!("filtergateway-condition-registered-{}", timestamp),
                 timestamp_ns: timestamp,
                 source: "filtergateway".to_string(),
+                trace_id: String::new(),
             };
 
             if let Err(e) = state_sender.send_state_change(state_change).await {
@@ -1547,6 +1864,7 @@ mod tests {
             transition_id: "test-transition".to_string(),
             timestamp_ns: 123456789,
             source: "filtergateway".to_string(),
+            trace_id: String::new(),
         };
 
         // Test error handling path (line 264)
@@ -1658,4 +1976,354 @@ spec:
             "Vehicle manager error should be triggered"
         );
     }
+
+    #[test]
+    fn test_scenario_topics_are_distinct_and_skip_empty() {
+        use super::{FilterGatewayManager, Scenario};
+
+        let scenario = |name: &str, topic: Option<&str>| -> Scenario {
+            let condition = topic
+                .map(|topic| {
+                    format!(
+                        "
+  condition:
+    express: eq
+    value: \"true\"
+    operands:
+      type: DDS
+      name: value
+      value: {}",
+                        topic
+                    )
+                })
+                .unwrap_or_default();
+            serde_yaml::from_str(&format!(
+                "
+apiVersion: v1
+kind: Scenario
+metadata:
+  name: {}
+spec:
+  action: update
+  target: {}{}
+",
+                name, name, condition
+            ))
+            .unwrap()
+        };
+
+        let scenarios = vec![
+            scenario("a", Some("BodyTrunkStatus")),
+            scenario("b", None),
+            scenario("c", Some("BodyLightsHeadLampStatus")),
+            scenario("d", Some("BodyTrunkStatus")),
+        ];
+
+        assert_eq!(
+            FilterGatewayManager::scenario_topics(&scenarios),
+            vec!["BodyTrunkStatus", "BodyLightsHeadLampStatus"]
+        );
+    }
+
+    /// Test that queued requests and DDS data are processed before shutdown completes
+    #[tokio::test]
+    async fn test_shutdown_drains_queued_messages() {
+        use super::{FilterGatewayManager, ScenarioParameter};
+        use crate::filter::Filter;
+        use crate::grpc::sender::actioncontroller::FilterGatewaySender;
+        use crate::vehicle::dds::{DdsData, LastValueCache};
+        use crate::vehicle::VehicleManager;
+        use common::spec::artifact::Scenario;
+
+        let scenario = |name: &str| -> Scenario {
+            serde_yaml::from_str(&format!(
+                "apiVersion: v1\nkind: Scenario\nmetadata:\n  name: {}\nspec:\n  action: update\n  target: {}\n",
+                name, name
+            ))
+            .unwrap()
+        };
+        let filter = |name: &str| {
+            Filter::new(
+                name.to_string(),
+                scenario(name),
+                true,
+                FilterGatewaySender::new(),
+            )
+        };
+
+        let (tx_grpc, rx_grpc) = mpsc::channel(10);
+        let (tx_dds, rx_dds) = mpsc::channel(10);
+        let manager = FilterGatewayManager {
+            rx_grpc: Arc::new(Mutex::new(rx_grpc)),
+            rx_dds: Arc::new(Mutex::new(rx_dds)),
+            filters: Arc::new(Mutex::new(vec![filter("first"), filter("second")])),
+            sender: Arc::new(Mutex::new(FilterGatewaySender::new())),
+            vehicle_manager: Arc::new(Mutex::new(VehicleManager::new(tx_dds.clone()))),
+            last_values: Arc::new(Mutex::new(LastValueCache::new())),
+            recorder: Arc::new(Mutex::new(None)),
+        };
+        let filters = Arc::clone(&manager.filters);
+        let rx_grpc = Arc::clone(&manager.rx_grpc);
+        let rx_dds = Arc::clone(&manager.rx_dds);
+
+        for name in ["first", "second"] {
+            tx_grpc
+                .send(ScenarioParameter {
+                    action: 1,
+                    scenario: scenario(name),
+                })
+                .await
+                .unwrap();
+        }
+        tx_dds.send(DdsData::new("unused", "")).await.unwrap();
+
+        // Shutdown is requested before anything has been received, and the
+        // senders stay open, so only draining empties the queues
+        tokio::time::timeout(
+            std::time::Duration::from_secs(5),
+            manager.run_until(std::future::ready(())),
+        )
+        .await
+        .expect("manager did not stop after shutdown")
+        .unwrap();
+
+        assert!(filters.lock().await.is_empty());
+        assert!(rx_grpc.lock().await.try_recv().is_err());
+        assert!(rx_dds.lock().await.try_recv().is_err());
+        assert!(tx_grpc.is_closed());
+        assert!(tx_dds.is_closed());
+    }
+
+    /// Test that a filter registered after its topic was received is
+    /// evaluated against the cached sample right away
+    #[tokio::test]
+    async fn test_register_matches_cached_value() {
+        use super::FilterGatewayManager;
+        use crate::filter::Filter;
+        use crate::grpc::sender::actioncontroller::FilterGatewaySender;
+        use crate::vehicle::dds::{DdsData, LastValueCache};
+        use crate::vehicle::VehicleManager;
+        use common::spec::artifact::Scenario;
+
+        let filter = |name: &str, target_status: &str| {
+            let scenario: Scenario = serde_yaml::from_str(&format!(
+                "apiVersion: v1\nkind: Scenario\nmetadata:\n  name: {}\nspec:\n  condition:\n    express: eq\n    value: {}\n    operands:\n      type: DDS\n      name: status\n      value: BodyTrunkStatus\n  action: launch\n  target: {}\n",
+                name, target_status, name
+            ))
+            .unwrap();
+            Filter::new(name.to_string(), scenario, true, FilterGatewaySender::new())
+        };
+
+        let (_tx_grpc, rx_grpc) = mpsc::channel(10);
+        let (tx_dds, rx_dds) = mpsc::channel(10);
+        let manager = FilterGatewayManager {
+            rx_grpc: Arc::new(Mutex::new(rx_grpc)),
+            rx_dds: Arc::new(Mutex::new(rx_dds)),
+            filters: Arc::new(Mutex::new(Vec::new())),
+            sender: Arc::new(Mutex::new(FilterGatewaySender::new())),
+            vehicle_manager: Arc::new(Mutex::new(VehicleManager::new(tx_dds))),
+            last_values: Arc::new(Mutex::new(LastValueCache::new())),
+            recorder: Arc::new(Mutex::new(None)),
+        };
+
+        // Nothing received yet, so there is nothing to evaluate
+        assert!(manager
+            .match_cached_value(&mut filter("trunk-open", "open"))
+            .await
+            .is_none());
+
+        manager
+            .last_values
+            .lock()
+            .await
+            .update(&DdsData::new("BodyTrunkStatus", "").with_field("status", "open"));
+
+        let sample = manager
+            .match_cached_value(&mut filter("trunk-open", "open"))
+            .await
+            .expect("cached value should meet the condition");
+        assert_eq!(sample.name, "BodyTrunkStatus");
+        assert!(manager
+            .match_cached_value(&mut filter("trunk-closed", "closed"))
+            .await
+            .is_none());
+    }
+
+    /// Test that received samples are appended to the recording
+    #[tokio::test]
+    async fn test_received_data_is_recorded() {
+        use super::FilterGatewayManager;
+        use crate::grpc::sender::actioncontroller::FilterGatewaySender;
+        use crate::vehicle::dds::recorder::{read_recording, Recorder};
+        use crate::vehicle::dds::{DdsData, LastValueCache};
+        use crate::vehicle::VehicleManager;
+
+        let path = std::env::temp_dir().join("filtergateway-manager-recording.jsonl");
+        let _ = std::fs::remove_file(&path);
+
+        let (_tx_grpc, rx_grpc) = mpsc::channel(10);
+        let (tx_dds, rx_dds) = mpsc::channel(10);
+        let manager = FilterGatewayManager {
+            rx_grpc: Arc::new(Mutex::new(rx_grpc)),
+            rx_dds: Arc::new(Mutex::new(rx_dds)),
+            filters: Arc::new(Mutex::new(Vec::new())),
+            sender: Arc::new(Mutex::new(FilterGatewaySender::new())),
+            vehicle_manager: Arc::new(Mutex::new(VehicleManager::new(tx_dds))),
+            last_values: Arc::new(Mutex::new(LastValueCache::new())),
+            recorder: Arc::new(Mutex::new(Some(Recorder::open(&path).unwrap()))),
+        };
+
+        let tx = manager.dds_sender().await;
+        tx.send(DdsData::new("VehicleSpeed", "{\"speed\":81}").with_field("speed", 81i64))
+            .await
+            .unwrap();
+        tx.send(DdsData::new("BodyTrunkStatus", "").with_field("status", "open"))
+            .await
+            .unwrap();
+
+        tokio::time::timeout(
+            std::time::Duration::from_secs(5),
+            manager.run_until(std::future::ready(())),
+        )
+        .await
+        .expect("manager did not stop after shutdown")
+        .unwrap();
+
+        let recorded = read_recording(&path).unwrap();
+        let names: Vec<&str> = recorded.iter().map(|d| d.name.as_str()).collect();
+        assert_eq!(names, vec!["VehicleSpeed", "BodyTrunkStatus"]);
+    }
+
+    /// Test that a rotating recording keeps every received sample in order
+    #[tokio::test]
+    async fn test_received_data_is_recorded_across_rotations() {
+        use super::FilterGatewayManager;
+        use crate::grpc::sender::actioncontroller::FilterGatewaySender;
+        use crate::vehicle::dds::recorder::{read_recording, recording_files, Recorder, Rotation};
+        use crate::vehicle::dds::{DdsData, LastValueCache};
+        use crate::vehicle::VehicleManager;
+
+        let path = std::env::temp_dir().join("filtergateway-manager-rotation.jsonl");
+        for file in recording_files(&path) {
+            let _ = std::fs::remove_file(file);
+        }
+        // Every sample is larger than this, so each gets a file of its own
+        let rotation = Rotation {
+            max_bytes: 16,
+            max_age: None,
+            max_files: 5,
+        };
+
+        let (_tx_grpc, rx_grpc) = mpsc::channel(10);
+        let (tx_dds, rx_dds) = mpsc::channel(10);
+        let manager = FilterGatewayManager {
+            rx_grpc: Arc::new(Mutex::new(rx_grpc)),
+            rx_dds: Arc::new(Mutex::new(rx_dds)),
+            filters: Arc::new(Mutex::new(Vec::new())),
+            sender: Arc::new(Mutex::new(FilterGatewaySender::new())),
+            vehicle_manager: Arc::new(Mutex::new(VehicleManager::new(tx_dds))),
+            last_values: Arc::new(Mutex::new(LastValueCache::new())),
+            recorder: Arc::new(Mutex::new(Some(
+                Recorder::with_rotation(&path, rotation).unwrap(),
+            ))),
+        };
+
+        let tx = manager.dds_sender().await;
+        for speed in [79i64, 81, 83] {
+            tx.send(
+                DdsData::new("VehicleSpeed", format!("{{\"speed\":{}}}", speed))
+                    .with_field("speed", speed),
+            )
+            .await
+            .unwrap();
+        }
+
+        tokio::time::timeout(
+            std::time::Duration::from_secs(5),
+            manager.run_until(std::future::ready(())),
+        )
+        .await
+        .expect("manager did not stop after shutdown")
+        .unwrap();
+
+        let files = recording_files(&path);
+        assert_eq!(files.len(), 3);
+        let values: Vec<String> = files
+            .iter()
+            .flat_map(|file| read_recording(file).unwrap())
+            .map(|d| d.value)
+            .collect();
+        assert_eq!(
+            values,
+            vec!["{\"speed\":79}", "{\"speed\":81}", "{\"speed\":83}"]
+        );
+    }
+
+    /// Test that the metrics of a filter count every sample it watches
+    #[tokio::test]
+    async fn test_filter_metrics_count_samples() {
+        use super::FilterGatewayManager;
+        use crate::filter::Filter;
+        use crate::grpc::sender::actioncontroller::FilterGatewaySender;
+        use crate::vehicle::dds::{DdsData, LastValueCache};
+        use crate::vehicle::VehicleManager;
+        use common::spec::artifact::Scenario;
+
+        let scenario: Scenario = serde_yaml::from_str(
+            "apiVersion: v1\nkind: Scenario\nmetadata:\n  name: metrics-trunk-open\nspec:\n  condition:\n    express: eq\n    value: open\n    operands:\n      type: DDS\n      name: status\n      value: BodyTrunkStatus\n  action: launch\n  target: metrics-trunk-open\n",
+        )
+        .unwrap();
+        let filter = Filter::new(
+            "metrics-trunk-open".to_string(),
+            scenario,
+            true,
+            FilterGatewaySender::new(),
+        );
+
+        let (_tx_grpc, rx_grpc) = mpsc::channel(10);
+        let (tx_dds, rx_dds) = mpsc::channel(10);
+        let manager = FilterGatewayManager {
+            rx_grpc: Arc::new(Mutex::new(rx_grpc)),
+            rx_dds: Arc::new(Mutex::new(rx_dds)),
+            filters: Arc::new(Mutex::new(vec![filter])),
+            sender: Arc::new(Mutex::new(FilterGatewaySender::new())),
+            vehicle_manager: Arc::new(Mutex::new(VehicleManager::new(tx_dds))),
+            last_values: Arc::new(Mutex::new(LastValueCache::new())),
+            recorder: Arc::new(Mutex::new(None)),
+        };
+        let filters = manager.filters.clone();
+
+        let tx = manager.dds_sender().await;
+        for status in ["open", "closed", "open", "closed", "open"] {
+            tx.send(DdsData::new("BodyTrunkStatus", "").with_field("status", status))
+                .await
+                .unwrap();
+        }
+        // Not watched by the filter
+        tx.send(DdsData::new("VehicleSpeed", "").with_field("speed", 81i64))
+            .await
+            .unwrap();
+
+        tokio::time::timeout(
+            std::time::Duration::from_secs(30),
+            manager.run_until(std::future::ready(())),
+        )
+        .await
+        .expect("manager did not stop after shutdown")
+        .unwrap();
+
+        let filters = filters.lock().await;
+        let metrics = filters[0].metrics().snapshot("metrics-trunk-open");
+        assert_eq!(metrics.samples_received, 5);
+        assert_eq!(metrics.evaluations, 5);
+        assert_eq!(metrics.matches, 3);
+        // Whether ActionController is up decides which of the two counts
+        assert_eq!(metrics.actions_sent + metrics.action_send_failures, 3);
+        assert_eq!(metrics.latency_count, 3);
+        assert!(metrics.latency_sum_us > 0);
+        assert_eq!(
+            crate::filter::metrics::snapshot("metrics-trunk-open"),
+            vec![metrics]
+        );
+    }
 }
@@ -0,0 +1,62 @@
+/*
+* SPDX-FileCopyrightText: Copyright 2024 LG Electronics Inc.
+* SPDX-License-Identifier: Apache-2.0
+*/
+//! Last received sample of every vehicle data type
+//!
+//! Lets a scenario registered after a value was published be evaluated
+//! against it right away instead of waiting for the next sample.
+
+use super::DdsData;
+use std::collections::HashMap;
+
+/// Latest sample per data type name
+#[derive(Debug, Default)]
+pub struct LastValueCache {
+    samples: HashMap<String, DdsData>,
+}
+
+impl LastValueCache {
+    /// Creates an empty cache
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Records `data` as the latest sample of its type
+    ///
+    /// Samples without a type name are ignored.
+    pub fn update(&mut self, data: &DdsData) {
+        if !data.name.is_empty() {
+            self.samples.insert(data.name.clone(), data.clone());
+        }
+    }
+
+    /// Latest sample of the type `name`, if one was received
+    pub fn get(&self, name: &str) -> Option<&DdsData> {
+        self.samples.get(name)
+    }
+}
+
+//Unit Test Cases
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::vehicle::dds::DdsValue;
+
+    #[test]
+    fn test_keeps_latest_sample_per_type() {
+        let mut cache = LastValueCache::new();
+        assert!(cache.get("BodyTrunkStatus").is_none());
+
+        cache.update(&DdsData::new("BodyTrunkStatus", "").with_field("status", "closed"));
+        cache.update(&DdsData::new("BodyTrunkStatus", "").with_field("status", "open"));
+        cache.update(&DdsData::new("", ""));
+
+        let sample = cache.get("BodyTrunkStatus").unwrap();
+        assert_eq!(
+            sample.fields.get("status"),
+            Some(&DdsValue::Text("open".into()))
+        );
+        assert!(cache.get("").is_none());
+    }
+}
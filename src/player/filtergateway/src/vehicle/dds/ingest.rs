@@ -0,0 +1,260 @@
+/*
+* SPDX-FileCopyrightText: Copyright 2024 LG Electronics Inc.
+* SPDX-License-Identifier: Apache-2.0
+*/
+//! How received samples reach the scenario filters
+//!
+//! Samples of a topic are queued by default, so every one of them is
+//! evaluated and a full queue makes the listener wait. Samples of a topic
+//! set to `latest` in `filtergateway.ingestion` go through a slot holding
+//! only the newest one instead: the listener never waits on the manager, and
+//! a sample replaced before the manager took it is dropped and counted.
+
+use super::DdsData;
+use common::filtergateway::TopicMetrics;
+use common::logd;
+use common::setting::Ingestion;
+use std::collections::BTreeMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, OnceLock};
+use tokio::sync::{mpsc, watch};
+
+/// Capacity of the channel from the listener of a latest-value topic to its
+/// slot, which is emptied as fast as samples arrive
+const INLET_CAPACITY: usize = 16;
+
+/// Counters of a latest-value topic
+#[derive(Debug, Default)]
+pub struct TopicCounters {
+    /// Samples received from the listener
+    pub samples_received: AtomicU64,
+    /// Samples replaced by a newer one before the manager took them
+    pub samples_dropped: AtomicU64,
+}
+
+/// Newest sample of a topic, numbered in order of arrival
+#[derive(Debug, Clone, Default)]
+struct Slot {
+    seq: u64,
+    sample: Option<DdsData>,
+}
+
+/// Counters of the latest-value topics, by topic name
+fn registry() -> &'static Mutex<BTreeMap<String, Arc<TopicCounters>>> {
+    static REGISTRY: OnceLock<Mutex<BTreeMap<String, Arc<TopicCounters>>>> = OnceLock::new();
+    REGISTRY.get_or_init(|| Mutex::new(BTreeMap::new()))
+}
+
+/// Counters of `topic`, shared with an earlier listener of the same topic
+fn register(topic: &str) -> Arc<TopicCounters> {
+    registry()
+        .lock()
+        .unwrap()
+        .entry(topic.to_string())
+        .or_default()
+        .clone()
+}
+
+/// Metrics of the latest-value topics, sorted by topic name
+pub fn snapshot() -> Vec<TopicMetrics> {
+    registry()
+        .lock()
+        .unwrap()
+        .iter()
+        .map(|(topic, counters)| TopicMetrics {
+            topic: topic.clone(),
+            samples_received: counters.samples_received.load(Ordering::Relaxed),
+            samples_dropped: counters.samples_dropped.load(Ordering::Relaxed),
+        })
+        .collect()
+}
+
+/// Sender the listener of `topic` hands its samples to
+///
+/// # Arguments
+///
+/// * `topic` - Name of the topic
+/// * `tx` - Channel of the samples evaluated by the manager
+///
+/// # Returns
+///
+/// * `mpsc::Sender<DdsData>` - `tx` itself for a queued topic, or the inlet
+///   of the latest-value slot of the topic
+pub fn sender(topic: &str, tx: &mpsc::Sender<DdsData>) -> mpsc::Sender<DdsData> {
+    let ingestion = common::setting::get_config()
+        .filtergateway
+        .ingestion
+        .get(topic)
+        .copied()
+        .unwrap_or_default();
+    match ingestion {
+        Ingestion::Queue => tx.clone(),
+        Ingestion::Latest => {
+            logd!(
+                2,
+                "Only the newest sample of topic '{}' is evaluated",
+                topic
+            );
+            latest(register(topic), tx.clone())
+        }
+    }
+}
+
+/// Inlet of a latest-value slot forwarding to `tx`
+///
+/// One task moves the samples from the inlet to the slot without ever
+/// waiting on `tx`; another one sends the newest sample of the slot to
+/// `tx` whenever it has room. Both end once the inlet is dropped by the
+/// listener or `tx` is closed.
+fn latest(counters: Arc<TopicCounters>, tx: mpsc::Sender<DdsData>) -> mpsc::Sender<DdsData> {
+    let (inlet, mut received) = mpsc::channel::<DdsData>(INLET_CAPACITY);
+    let (slot, mut newest) = watch::channel(Slot::default());
+
+    let fill_counters = Arc::clone(&counters);
+    let fill_tx = tx.clone();
+    tokio::spawn(async move {
+        let mut seq = 0;
+        while let Some(sample) = received.recv().await {
+            if fill_tx.is_closed() {
+                break;
+            }
+            fill_counters
+                .samples_received
+                .fetch_add(1, Ordering::Relaxed);
+            seq += 1;
+            slot.send_replace(Slot {
+                seq,
+                sample: Some(sample),
+            });
+        }
+    });
+
+    tokio::spawn(async move {
+        let mut forwarded = 0;
+        while newest.changed().await.is_ok() {
+            let Slot { seq, sample } = newest.borrow_and_update().clone();
+            let Some(sample) = sample else {
+                continue;
+            };
+            counters
+                .samples_dropped
+                .fetch_add(seq - forwarded - 1, Ordering::Relaxed);
+            forwarded = seq;
+            if tx.send(sample).await.is_err() {
+                break;
+            }
+        }
+    });
+
+    inlet
+}
+
+//Unit Test Cases
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::{Duration, Instant};
+
+    fn sample(topic: &str, value: u64) -> DdsData {
+        DdsData::new(topic, value.to_string()).with_topic(topic)
+    }
+
+    #[tokio::test]
+    async fn test_queued_topic_uses_the_manager_channel() {
+        let (tx, _rx) = mpsc::channel(4);
+        assert!(sender("ingest-test-queued", &tx).same_channel(&tx));
+    }
+
+    #[tokio::test]
+    async fn test_latest_topic_drops_nothing_while_the_manager_keeps_up() {
+        let (tx, mut rx) = mpsc::channel(4);
+        let counters = Arc::new(TopicCounters::default());
+        let inlet = latest(Arc::clone(&counters), tx);
+
+        for i in 0..3 {
+            inlet.send(sample("speed", i)).await.unwrap();
+            assert_eq!(rx.recv().await.unwrap().value, i.to_string());
+        }
+        drop(inlet);
+        assert!(rx.recv().await.is_none());
+        assert_eq!(counters.samples_received.load(Ordering::Relaxed), 3);
+        assert_eq!(counters.samples_dropped.load(Ordering::Relaxed), 0);
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn test_latest_topic_at_10k_samples_per_second() {
+        const SAMPLES: u64 = 5_000;
+        let (tx, mut rx) = mpsc::channel(4);
+        let counters = Arc::new(TopicCounters::default());
+        let inlet = latest(Arc::clone(&counters), tx.clone());
+
+        // Ten samples every millisecond for half a second
+        let publisher = tokio::spawn(async move {
+            let started = Instant::now();
+            let mut ticker = tokio::time::interval(Duration::from_millis(1));
+            for i in 0..SAMPLES {
+                if i % 10 == 0 {
+                    ticker.tick().await;
+                }
+                inlet.send(sample("speed", i)).await.unwrap();
+            }
+            started.elapsed()
+        });
+
+        // A queued sample of another topic sent during the flood
+        let queued = tokio::spawn(async move {
+            tokio::time::sleep(Duration::from_millis(100)).await;
+            let sent = Instant::now();
+            tx.send(sample("door", 1)).await.unwrap();
+            sent
+        });
+
+        // A manager taking 5 ms per sample, far slower than the topic
+        let mut speeds = Vec::new();
+        let mut door_received = None;
+        while let Some(data) = rx.recv().await {
+            match data.topic.as_str() {
+                "door" => door_received = Some(Instant::now()),
+                _ => speeds.push(data.value.parse::<u64>().unwrap()),
+            }
+            tokio::time::sleep(Duration::from_millis(5)).await;
+        }
+
+        let publishing = publisher.await.unwrap();
+        assert!(
+            publishing < Duration::from_secs(2),
+            "the listener waited on the manager: {:?}",
+            publishing
+        );
+        let waited = door_received.unwrap() - queued.await.unwrap();
+        assert!(
+            waited < Duration::from_millis(500),
+            "queued sample waited {:?}",
+            waited
+        );
+
+        // Only the newest samples were evaluated, in order, ending with the last one
+        assert!(speeds.windows(2).all(|pair| pair[0] < pair[1]));
+        assert_eq!(speeds.last(), Some(&(SAMPLES - 1)));
+        let received = counters.samples_received.load(Ordering::Relaxed);
+        let dropped = counters.samples_dropped.load(Ordering::Relaxed);
+        assert_eq!(received, SAMPLES);
+        assert!(dropped > SAMPLES / 2, "only {} samples dropped", dropped);
+        assert_eq!(dropped + speeds.len() as u64, SAMPLES);
+    }
+
+    #[tokio::test]
+    async fn test_snapshot_lists_latest_topics() {
+        let counters = register("ingest-test-snapshot");
+        counters.samples_received.fetch_add(3, Ordering::Relaxed);
+        counters.samples_dropped.fetch_add(2, Ordering::Relaxed);
+        assert!(Arc::ptr_eq(&counters, &register("ingest-test-snapshot")));
+
+        let metrics = snapshot();
+        let topic = metrics
+            .iter()
+            .find(|m| m.topic == "ingest-test-snapshot")
+            .unwrap();
+        assert_eq!((topic.samples_received, topic.samples_dropped), (3, 2));
+    }
+}
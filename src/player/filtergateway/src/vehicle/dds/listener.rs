@@ -2,9 +2,12 @@
 * SPDX-FileCopyrightText: Copyright 2024 LG Electronics Inc.
 * SPDX-License-Identifier: Apache-2.0
 */
+use crate::vehicle::dds::supervisor::{
+    self, Heartbeat, ListenerHealth, ListenerStatus, SupervisorConfig,
+};
 use crate::vehicle::dds::DdsData;
 use common::Result;
-use std::collections::HashMap;
+use std::sync::Arc;
 
 #[async_trait]
 #[allow(dead_code)]
@@ -14,6 +17,16 @@ pub trait DdsTopicListener: Send + Sync {
     async fn stop(&mut self) -> Result<()>;
     fn get_topic_name(&self) -> &str;
     fn is_topic(&self, topic_name: &str) -> bool;
+
+    /// Current health of the listener, if it is supervised
+    fn status(&self) -> Option<ListenerStatus> {
+        None
+    }
+
+    /// Channel notified whenever the listener health changes, if it is supervised
+    fn subscribe_status(&self) -> Option<watch::Receiver<ListenerStatus>> {
+        None
+    }
 }
 
 #[allow(unused_variables, unused_imports)]
@@ -21,6 +34,7 @@ use dust_dds::{
     domain::domain_participant::DomainParticipant,
     domain::domain_participant_factory::DomainParticipantFactory,
     infrastructure::{
+        error::DdsError,
         qos::QosKind,
         qos_policy::{DataRepresentationQosPolicy, XCDR2_DATA_REPRESENTATION},
         status::NO_STATUS,
@@ -33,11 +47,11 @@ use dust_dds::{
 };
 
 use tokio::sync::mpsc::Sender;
+use tokio::sync::watch;
 use tokio::task::JoinHandle;
 use tokio::time;
 
 use anyhow::anyhow;
-use serde_json::Value;
 
 use async_trait::async_trait;
 // use clap::Parser;
@@ -204,11 +218,8 @@ impl TopicListener {
             interval.tick().await;
 
             // 수신된 DDS 메시지를 파싱하여 DdsData 형태로 변환
-            let dds_data = DdsData {
-                name: data_type_name.clone(),
-                value: "{}".to_string(), // 실제 값은 메시지 수신 시 채워짐
-                fields: HashMap::new(),
-            };
+            let dds_data = DdsData::new(data_type_name.clone(), "{}") // 실제 값은 메시지 수신 시 채워짐
+                .with_topic(topic_name.clone());
 
             // 데이터 전송 채널이 닫히면 루프 종료
             if tx.send(dds_data).await.is_err() {
@@ -243,10 +254,14 @@ pub struct GenericTopicListener<
     tx: Sender<DdsData>,
     /// DDS domain ID
     domain_id: i32,
-    /// Listener task handle
+    /// Supervisor task handle
     listener_task: Option<JoinHandle<()>>,
     /// Running state
     is_running: bool,
+    /// Restart policy of the supervisor
+    config: SupervisorConfig,
+    /// Health reported by the supervisor
+    status: Arc<watch::Sender<ListenerStatus>>,
     /// Type marker (for generic type specification)
     _marker: std::marker::PhantomData<T>,
 }
@@ -269,6 +284,7 @@ impl<
         tx: Sender<DdsData>,
         domain_id: i32,
     ) -> Self {
+        let (status, _) = watch::channel(ListenerStatus::stopped(&topic_name));
         Self {
             topic_name,
             data_type_name,
@@ -276,10 +292,27 @@ impl<
             domain_id,
             listener_task: None,
             is_running: false,
+            config: SupervisorConfig::default(),
+            status: Arc::new(status),
             _marker: std::marker::PhantomData,
         }
     }
 
+    /// Sets the restart policy used when the listener is started
+    ///
+    /// # Arguments
+    ///
+    /// * `config` - Restart policy of the supervisor
+    ///
+    /// # Returns
+    ///
+    /// The listener with the given policy
+    #[allow(dead_code)]
+    pub fn with_supervision(mut self, config: SupervisorConfig) -> Self {
+        self.config = config;
+        self
+    }
+
     /// 타입별 리스너 루프
     #[allow(dead_code)]
     async fn typed_listener_loop(
@@ -287,6 +320,21 @@ impl<
         data_type_name: String,
         tx: Sender<DdsData>,
         domain_id: i32,
+    ) -> Result<()> {
+        Self::supervised_listener_loop(topic_name, data_type_name, tx, domain_id, Heartbeat::new())
+            .await
+    }
+
+    /// 타입별 리스너 루프 (수신할 때마다 heartbeat 갱신)
+    ///
+    /// Returns `Ok(())` once the data channel is closed and an error when
+    /// the reader can no longer be used, so the supervisor recreates it.
+    async fn supervised_listener_loop(
+        topic_name: String,
+        data_type_name: String,
+        tx: Sender<DdsData>,
+        domain_id: i32,
+        heartbeat: Heartbeat,
     ) -> Result<()> {
         // 도메인 참여자 생성
         let domain_participant_factory = DomainParticipantFactory::get_instance();
@@ -320,35 +368,21 @@ impl<
         loop {
             interval.tick().await;
 
-            // 새 샘플 확인
-            let result = data_reader
-                .take(1, ANY_SAMPLE_STATE, ANY_VIEW_STATE, ANY_INSTANCE_STATE)
-                .map_err(|e| anyhow!("Failed to read samples: {:?}", e));
+            // 데이터 전송 채널이 닫히면 루프 종료
+            if tx.is_closed() {
+                logd!(4, "Channel closed, stopping listener for {}", topic_name);
+                return Ok(());
+            }
 
-            match result {
+            // 새 샘플 확인
+            match data_reader.take(1, ANY_SAMPLE_STATE, ANY_VIEW_STATE, ANY_INSTANCE_STATE) {
                 Ok(samples) => {
+                    heartbeat.beat();
                     for sample in samples {
                         if let Ok(data) = sample.data() {
-                            // 데이터를 JSON으로 직렬화
-                            let json_value = serde_json::to_string(&data)
-                                .map_err(|e| anyhow!("Failed to serialize data: {:?}", e))?;
-
-                            // json_value를 key, value로 파싱해서 fields에 추가
-                            let mut fields = HashMap::new();
-                            if let Ok(map) =
-                                serde_json::from_str::<serde_json::Map<String, Value>>(&json_value)
-                            {
-                                for (k, v) in map {
-                                    fields.insert(k, v.to_string());
-                                }
-                            }
-
-                            // DdsData 객체 생성 및 전송
-                            let dds_data = DdsData {
-                                name: data_type_name.clone(),
-                                value: json_value,
-                                fields,
-                            };
+                            // 샘플 필드를 타입별 값으로 변환
+                            let dds_data =
+                                DdsData::from_sample(&data_type_name, &topic_name, &data)?;
 
                             // Send data through channel
                             if tx.send(dds_data).await.is_err() {
@@ -358,8 +392,9 @@ impl<
                         }
                     }
                 }
+                Err(DdsError::NoData) => {}
                 Err(e) => {
-                    logd!(5, "No new samples available: {:?}", e);
+                    return Err(anyhow!("Failed to read samples: {:?}", e).into());
                 }
             }
         }
@@ -392,19 +427,21 @@ impl<
         let tx = self.tx.clone();
         let domain_id = self.domain_id;
 
-        // 리스너 태스크 시작
-        let task = tokio::spawn(async move {
-            if let Err(e) =
-                Self::typed_listener_loop(topic_name.clone(), data_type_name, tx, domain_id).await
-            {
-                logd!(
-                    5,
-                    "Error in typed listener loop for {}: {:?}",
-                    topic_name,
-                    e
-                );
-            }
-        });
+        // 리스너가 종료되거나 패닉하면 supervisor가 리더를 다시 생성
+        let task = tokio::spawn(supervisor::supervise(
+            self.topic_name.clone(),
+            self.config.clone(),
+            self.status.clone(),
+            move |heartbeat| {
+                Self::supervised_listener_loop(
+                    topic_name.clone(),
+                    data_type_name.clone(),
+                    tx.clone(),
+                    domain_id,
+                    heartbeat,
+                )
+            },
+        ));
 
         self.listener_task = Some(task);
         self.is_running = true;
@@ -414,8 +451,11 @@ impl<
     async fn stop(&mut self) -> Result<()> {
         if self.is_running {
             if let Some(task) = self.listener_task.take() {
+                // Dropping the supervisor also aborts the reader task it owns
                 task.abort();
             }
+            self.status
+                .send_modify(|status| status.health = ListenerHealth::Stopped);
             self.is_running = false;
         }
         Ok(())
@@ -428,16 +468,23 @@ impl<
     fn is_topic(&self, topic_name: &str) -> bool {
         self.topic_name == topic_name
     }
+
+    fn status(&self) -> Option<ListenerStatus> {
+        Some(self.status.borrow().clone())
+    }
+
+    fn subscribe_status(&self) -> Option<watch::Receiver<ListenerStatus>> {
+        Some(self.status.subscribe())
+    }
 }
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::vehicle::dds::listener::GenericTopicListener;
     use crate::vehicle::dds::listener::{DdsTopicListener, TopicListener};
-    use crate::vehicle::dds::DdsData;
+    use crate::vehicle::dds::{DdsData, DdsValue};
     use dust_dds_derive::DdsType;
     use serde::{Deserialize, Serialize};
-    use std::collections::HashMap;
     use std::time::Duration;
     use tokio::sync::mpsc;
     // Temporarily shadow the conflicting `Result` alias
@@ -472,6 +519,35 @@ mod tests {
         listener.stop().await.unwrap();
         assert!(!listener.is_running());
     }
+    #[tokio::test]
+    async fn test_generic_listener_reports_status() {
+        let (tx, _rx) = mpsc::channel::<DdsData>(1);
+        let mut listener = GenericTopicListener::<ADASObstacleDetectionIsWarning>::new(
+            "ADASObstacleDetectionIsWarning".to_string(),
+            "ADASObstacleDetectionIsWarning".to_string(),
+            tx,
+            117,
+        )
+        .with_supervision(SupervisorConfig::default());
+        let mut status = listener.subscribe_status().unwrap();
+        assert_eq!(listener.status().unwrap().health, ListenerHealth::Stopped);
+
+        listener.start().await.unwrap();
+        tokio::time::timeout(
+            Duration::from_secs(5),
+            status.wait_for(|s| s.health == ListenerHealth::Running),
+        )
+        .await
+        .unwrap()
+        .unwrap();
+
+        listener.stop().await.unwrap();
+        let current = listener.status().unwrap();
+        assert_eq!(current.health, ListenerHealth::Stopped);
+        assert_eq!(current.topic_name, "ADASObstacleDetectionIsWarning");
+        assert_eq!(current.restarts, 0);
+    }
+
     #[tokio::test]
     async fn test_topic_listener_creation() {
         let (tx, _rx) = mpsc::channel(10);
@@ -652,16 +728,18 @@ mod tests {
             label: "test_label".into(),
         };
 
-        let json_value = serde_json::to_string(&dummy).unwrap();
-        let parsed: serde_json::Map<String, Value> = serde_json::from_str(&json_value).unwrap();
-
-        let mut fields = HashMap::new();
-        for (k, v) in parsed {
-            fields.insert(k.clone(), v.to_string());
-        }
+        let data = DdsData::from_sample("DummyType", "GenericTopic", &dummy).unwrap();
 
-        assert_eq!(fields.get("id").unwrap(), "42");
-        assert_eq!(fields.get("label").unwrap(), "\"test_label\"");
+        assert_eq!(data.fields.get("id"), Some(&DdsValue::Int64(42)));
+        assert_eq!(
+            data.fields.get("label"),
+            Some(&DdsValue::from("test_label"))
+        );
+        assert_eq!(data.topic, "GenericTopic");
+        assert_eq!(
+            data.to_string(),
+            "DummyType@GenericTopic {id=42, label=test_label}"
+        );
     }
     #[tokio::test]
     async fn test_listener_loop_exits_when_channel_closed() {
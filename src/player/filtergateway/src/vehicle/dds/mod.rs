@@ -8,22 +8,129 @@ use common::Result;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::env;
+use std::fmt;
 use std::fs;
 use std::path::{Path, PathBuf};
+use std::time::SystemTime;
 use tokio::sync::mpsc::{self, Receiver, Sender};
 use tokio::sync::Mutex;
 
+pub mod cache;
+pub mod ingest;
 pub mod listener;
+pub mod publisher;
+pub mod recorder;
+pub mod registry;
+pub mod supervisor;
+// Used by the dds-replay tool, not the filtergateway binary
+#[allow(dead_code)]
+pub mod trace;
+pub mod value;
 
 // Re-export the modules
+pub use cache::LastValueCache;
 pub use listener::{create_idl_listener, DdsTopicListener};
+pub use recorder::Recorder;
+pub use registry::TopicRegistry;
+pub use supervisor::ListenerStatus;
+pub use value::DdsValue;
 
 // DdsData structure to represent parsed IDL data
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct DdsData {
+    /// Data type name
     pub name: String,
+    /// Sample serialized as JSON
     pub value: String,
-    pub fields: HashMap<String, String>,
+    /// Typed sample fields
+    pub fields: HashMap<String, DdsValue>,
+    /// Topic the sample was received on
+    pub topic: String,
+    /// Time the sample was received
+    pub received_at: SystemTime,
+}
+
+impl DdsData {
+    /// Creates data without fields, received now on a topic named like its type
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - Data type name
+    /// * `value` - Sample serialized as JSON
+    pub fn new(name: impl Into<String>, value: impl Into<String>) -> Self {
+        let name = name.into();
+        Self {
+            topic: name.clone(),
+            name,
+            value: value.into(),
+            fields: HashMap::new(),
+            received_at: SystemTime::now(),
+        }
+    }
+
+    /// Converts a received DDS sample
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - Data type name
+    /// * `topic` - Topic the sample was received on
+    /// * `sample` - Sample to convert
+    ///
+    /// # Returns
+    ///
+    /// * `Result<Self>` - Data with one typed field per top-level struct member
+    pub fn from_sample<T: Serialize>(name: &str, topic: &str, sample: &T) -> Result<Self> {
+        let json = serde_json::to_value(sample)
+            .map_err(|e| anyhow!("Failed to serialize data: {:?}", e))?;
+        let fields = match &json {
+            serde_json::Value::Object(map) => map
+                .iter()
+                .map(|(k, v)| (k.clone(), DdsValue::from_json(v)))
+                .collect(),
+            _ => HashMap::new(),
+        };
+
+        Ok(Self {
+            name: name.to_string(),
+            value: json.to_string(),
+            fields,
+            topic: topic.to_string(),
+            received_at: SystemTime::now(),
+        })
+    }
+
+    /// Sets the topic the sample was received on
+    pub fn with_topic(mut self, topic: impl Into<String>) -> Self {
+        self.topic = topic.into();
+        self
+    }
+
+    /// Adds a field
+    #[allow(dead_code)]
+    pub fn with_field(mut self, name: impl Into<String>, value: impl Into<DdsValue>) -> Self {
+        self.fields.insert(name.into(), value.into());
+        self
+    }
+}
+
+impl fmt::Display for DdsData {
+    /// Formats as `Type@Topic {field=value, ...}` with fields sorted by name
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name)?;
+        if self.topic != self.name {
+            write!(f, "@{}", self.topic)?;
+        }
+        let mut fields: Vec<_> = self.fields.iter().collect();
+        fields.sort_by(|a, b| a.0.cmp(b.0));
+        write!(f, " {{")?;
+        for (i, (name, value)) in fields.into_iter().enumerate() {
+            if i > 0 {
+                write!(f, ", ")?;
+            }
+            write!(f, "{}={}", name, value)?;
+        }
+        write!(f, "}}")
+    }
 }
 
 /// DDS Manager - Manages multiple DDS listeners
@@ -36,6 +143,8 @@ pub struct DdsManager {
     rx: Mutex<Receiver<DdsData>>,
     /// DDS domain ID
     domain_id: i32,
+    /// Topics that can be enabled with `run`
+    registry: TopicRegistry,
 }
 
 #[allow(dead_code)]
@@ -49,8 +158,80 @@ impl DdsManager {
             tx,
             rx: Mutex::new(mpsc::channel(100).1),
             domain_id: 100,
+            registry: TopicRegistry::generated(),
         }
     }
+
+    /// Replace the topic registry used by `run`
+    pub fn set_registry(&mut self, registry: TopicRegistry) {
+        self.registry = registry;
+    }
+
+    /// Topics currently being listened to
+    pub fn active_topics(&self) -> Vec<String> {
+        let mut topics: Vec<String> = self.listeners.keys().cloned().collect();
+        topics.sort();
+        topics
+    }
+
+    /// Health of every supervised listener, sorted by topic name
+    pub fn listener_statuses(&self) -> Vec<ListenerStatus> {
+        let mut statuses: Vec<ListenerStatus> = self
+            .listeners
+            .values()
+            .filter_map(|listener| listener.status())
+            .collect();
+        statuses.sort_by(|a, b| a.topic_name.cmp(&b.topic_name));
+        statuses
+    }
+
+    /// Channel notified whenever the health of `topic_name`'s listener changes
+    pub fn subscribe_status(
+        &self,
+        topic_name: &str,
+    ) -> Option<tokio::sync::watch::Receiver<ListenerStatus>> {
+        self.listeners
+            .get(topic_name)
+            .and_then(|listener| listener.subscribe_status())
+    }
+
+    /// Start listeners for registered topics
+    ///
+    /// # Arguments
+    ///
+    /// * `topics` - Topic names to enable, or `None` for every registered topic
+    ///
+    /// # Returns
+    ///
+    /// * `Result<Vec<String>>` - Topics enabled by this call. If any requested
+    ///   topic is unknown, nothing is started and the error lists the available topics.
+    pub async fn run(&mut self, topics: Option<&[String]>) -> Result<Vec<String>> {
+        let selected = self.registry.select(topics)?;
+
+        let mut started = Vec::new();
+        for topic_name in selected {
+            if self.listeners.contains_key(&topic_name) {
+                continue;
+            }
+            let Some(mut listener) = self.registry.create_listener(
+                &topic_name,
+                self.sender_for(&topic_name),
+                self.domain_id,
+            ) else {
+                continue;
+            };
+            listener
+                .start()
+                .await
+                .map_err(|e| anyhow!("Failed to start listener for {}: {:?}", topic_name, e))?;
+
+            logd!(2, "Started listener for registered topic '{}'", topic_name);
+            self.listeners.insert(topic_name.clone(), listener);
+            started.push(topic_name);
+        }
+
+        Ok(started)
+    }
     /// Scan and process IDL directory at runtime
     pub async fn scan_idl_directory(&mut self, dir: &Path) -> Result<Vec<String>> {
         logd!(3, "Scanning IDL directory at runtime: {:?}", dir);
@@ -98,7 +279,7 @@ impl DdsManager {
         if let Some(mut typed_listener) = dds_type_registry::create_typed_listener(
             &data_type_name,
             topic_name.clone(),
-            self.tx.clone(),
+            self.sender_for(&topic_name),
             self.domain_id,
         ) {
             // 리스너 시작
@@ -143,6 +324,12 @@ impl DdsManager {
         self.tx.clone()
     }
 
+    /// Sender for the listener of `topic_name`, following the ingestion
+    /// policy of the topic
+    fn sender_for(&self, topic_name: &str) -> Sender<DdsData> {
+        ingest::sender(topic_name, &self.tx)
+    }
+
     /// Get DDS data receiver
     pub async fn get_receiver(&mut self) -> &mut Mutex<Receiver<DdsData>> {
         &mut self.rx
@@ -166,7 +353,7 @@ impl DdsManager {
         let mut listener = create_idl_listener(
             topic_name.clone(),
             data_type_name,
-            self.tx.clone(),
+            self.sender_for(&topic_name),
             self.domain_id,
         );
 
@@ -314,6 +501,25 @@ pub mod dds_type_registry {
         );
         None
     }
+
+    #[cfg(not(feature = "dds_type_registry_exists"))]
+    pub fn register_topics(registry: &mut super::registry::TopicRegistry) {
+        logd!(3, "No type registry found. No topics registered");
+    }
+
+    #[cfg(not(feature = "dds_type_registry_exists"))]
+    pub async fn publish_raw(
+        topic_name: &str,
+        value: serde_json::Value,
+        domain_id: i32,
+    ) -> Result<()> {
+        Err(format!("No type registry found. Cannot publish on '{}'", topic_name).into())
+    }
+
+    #[cfg(all(test, not(feature = "dds_type_registry_exists")))]
+    pub fn default_sample_data(type_name: &str) -> Option<Result<DdsData>> {
+        None
+    }
 }
 //Unit Test Cases
 #[cfg(test)]
@@ -687,4 +893,110 @@ mod tests {
         assert!(result.is_ok());
         assert_eq!(manager.domain_id, 0); // default domain_id
     }
+
+    fn mock_factory(topic_name: String, _: Sender<DdsData>, _: i32) -> Box<dyn DdsTopicListener> {
+        Box::new(MockDdsTopicListener {
+            running: false,
+            topic_name,
+        })
+    }
+
+    fn manager_with_mock_registry() -> DdsManager {
+        let (tx, _) = mpsc::channel(100);
+        let mut manager = DdsManager::new(tx);
+        let mut registry = TopicRegistry::new();
+        for topic in ["Speed", "Gear", "Door"] {
+            registry.register(topic, mock_factory);
+        }
+        manager.set_registry(registry);
+        manager
+    }
+
+    #[tokio::test]
+    async fn test_run_starts_only_requested_topics() {
+        let mut manager = manager_with_mock_registry();
+        let topics = vec!["Gear".to_string(), "Speed".to_string()];
+
+        let started = manager.run(Some(&topics)).await.unwrap();
+        assert_eq!(started, topics);
+        assert_eq!(manager.active_topics(), vec!["Gear", "Speed"]);
+        assert!(manager.listeners.values().all(|l| l.is_running()));
+
+        // Running again does not start duplicate listeners
+        assert!(manager.run(Some(&topics)).await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_listener_statuses_skip_unsupervised_listeners() {
+        let mut manager = manager_with_mock_registry();
+        manager.run(None).await.unwrap();
+
+        assert!(manager.listener_statuses().is_empty());
+        assert!(manager.subscribe_status("Speed").is_none());
+        assert!(manager.subscribe_status("Wiper").is_none());
+    }
+
+    #[tokio::test]
+    async fn test_run_without_list_starts_all_topics() {
+        let mut manager = manager_with_mock_registry();
+
+        manager.run(None).await.unwrap();
+        assert_eq!(manager.active_topics(), vec!["Door", "Gear", "Speed"]);
+    }
+
+    #[tokio::test]
+    async fn test_run_unknown_topic_starts_nothing() {
+        let mut manager = manager_with_mock_registry();
+        let topics = vec!["Speed".to_string(), "Wiper".to_string()];
+
+        let err = manager.run(Some(&topics)).await.unwrap_err();
+        assert!(err.to_string().contains("Unknown DDS topic(s): Wiper"));
+        assert!(err
+            .to_string()
+            .contains("Available topics: Door, Gear, Speed"));
+        assert!(manager.active_topics().is_empty());
+    }
+
+    #[test]
+    fn test_every_generated_type_converts_to_typed_fields() {
+        let metadata = dds_type_metadata::generated_metadata::get_type_metadata();
+
+        for (type_name, meta) in metadata {
+            let data = super::dds_type_registry::default_sample_data(&type_name)
+                .unwrap_or_else(|| panic!("no registry entry for {}", type_name))
+                .unwrap();
+            assert_eq!(data.name, type_name);
+            assert_eq!(data.topic, type_name);
+            assert_eq!(data.fields.len(), meta.fields.len(), "{}", type_name);
+
+            for (field, rust_type) in &meta.fields {
+                let value = data
+                    .fields
+                    .get(field)
+                    .unwrap_or_else(|| panic!("{}.{} not converted", type_name, field));
+                let expected = match rust_type.as_str() {
+                    "bool" => matches!(value, DdsValue::Bool(false)),
+                    "i16" | "u16" | "i32" | "u32" | "i64" | "u64" | "u8" => {
+                        matches!(value, DdsValue::Int64(0))
+                    }
+                    "f32" | "f64" => matches!(value, DdsValue::Float64(_)),
+                    _ => matches!(value, DdsValue::Text(_)),
+                };
+                assert!(
+                    expected,
+                    "{}.{}: {} -> {:?}",
+                    type_name, field, rust_type, value
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_dds_data_display() {
+        let data = DdsData::new("BodyTrunkStatus", "{}").with_field("progress", 50);
+        assert_eq!(data.to_string(), "BodyTrunkStatus {progress=50}");
+
+        let data = data.with_topic("rt/trunk");
+        assert_eq!(data.to_string(), "BodyTrunkStatus@rt/trunk {progress=50}");
+    }
 }
@@ -0,0 +1,391 @@
+/*
+* SPDX-FileCopyrightText: Copyright 2024 LG Electronics Inc.
+* SPDX-License-Identifier: Apache-2.0
+*/
+use common::logd;
+use common::Result;
+
+use anyhow::anyhow;
+use dust_dds::{
+    domain::domain_participant_factory::DomainParticipantFactory,
+    infrastructure::{qos::QosKind, status::NO_STATUS},
+    publication::data_writer::DataWriter,
+    topic_definition::type_support::{DdsSerialize, TypeSupport},
+};
+use tokio::sync::mpsc::{self, Receiver, Sender};
+use tokio::task::JoinHandle;
+use tokio::time;
+
+/// How long a writer waits for a matching reader before writing anyway
+const MATCH_TIMEOUT: time::Duration = time::Duration::from_millis(2000);
+/// Same for the long-lived publisher task, which only waits before its first sample
+const TASK_MATCH_TIMEOUT: time::Duration = time::Duration::from_secs(10);
+/// Interval between matched-reader checks
+const MATCH_POLL_INTERVAL: time::Duration = time::Duration::from_millis(100);
+
+/// Publishes a single sample of `T` on `topic_name`
+///
+/// Creates a domain participant, publisher and data writer with the same
+/// default QoS the listeners use, so any running listener for the topic
+/// receives the sample.
+///
+/// # Arguments
+///
+/// * `topic_name` - Name of the DDS topic
+/// * `data` - Sample to publish
+/// * `domain_id` - DDS domain ID
+///
+/// # Returns
+///
+/// * `Result<()>` - Success or error result
+pub async fn send_dds<T>(topic_name: &str, data: T, domain_id: i32) -> Result<()>
+where
+    T: TypeSupport + DdsSerialize + Send + Sync + 'static,
+{
+    let writer = create_writer::<T>(topic_name, domain_id)?;
+    wait_for_reader(&writer, topic_name, MATCH_TIMEOUT).await;
+    writer
+        .write(&data, None)
+        .map_err(|e| anyhow!("Failed to write sample: {:?}", e))?;
+
+    logd!(2, "Published sample on topic '{}'", topic_name);
+    Ok(())
+}
+
+/// Publishes a JSON value on `topic_name`
+///
+/// The topic name selects the generated DDS type, as it does for listeners.
+///
+/// # Arguments
+///
+/// * `topic_name` - Name of the DDS topic, which is also its type name
+/// * `value` - Sample as JSON, using the IDL field names
+/// * `domain_id` - DDS domain ID
+///
+/// # Returns
+///
+/// * `Result<()>` - Success or error result
+pub async fn publish_raw(topic_name: &str, value: serde_json::Value, domain_id: i32) -> Result<()> {
+    super::dds_type_registry::publish_raw(topic_name, value, domain_id).await
+}
+
+/// Creates a data writer for `T` on `topic_name`
+fn create_writer<T>(topic_name: &str, domain_id: i32) -> Result<DataWriter<T>>
+where
+    T: TypeSupport + DdsSerialize + Send + Sync + 'static,
+{
+    let domain_participant_factory = DomainParticipantFactory::get_instance();
+    let participant = domain_participant_factory
+        .create_participant(domain_id, QosKind::Default, None, NO_STATUS)
+        .map_err(|e| anyhow!("Failed to create domain participant: {:?}", e))?;
+
+    let publisher = participant
+        .create_publisher(QosKind::Default, None, NO_STATUS)
+        .map_err(|e| anyhow!("Failed to create publisher: {:?}", e))?;
+
+    let topic = participant
+        .create_topic::<T>(topic_name, topic_name, QosKind::Default, None, NO_STATUS)
+        .map_err(|e| anyhow!("Failed to create topic: {:?}", e))?;
+
+    let writer = publisher
+        .create_datawriter::<T>(&topic, QosKind::Default, None, NO_STATUS)
+        .map_err(|e| anyhow!("Failed to create data writer: {:?}", e))?;
+
+    logd!(
+        3,
+        "Successfully created data writer for topic '{}'",
+        topic_name
+    );
+    Ok(writer)
+}
+
+/// Waits until discovery matches a reader, so the first sample is not lost
+async fn wait_for_reader<T>(writer: &DataWriter<T>, topic_name: &str, timeout: time::Duration) {
+    let deadline = time::Instant::now() + timeout;
+    while time::Instant::now() < deadline {
+        if writer
+            .get_matched_subscriptions()
+            .is_ok_and(|readers| !readers.is_empty())
+        {
+            return;
+        }
+        time::sleep(MATCH_POLL_INTERVAL).await;
+    }
+    logd!(4, "No reader matched topic '{}' yet", topic_name);
+}
+
+/// 타입별 DDS 토픽 퍼블리셔
+///
+/// Owns a data writer in a background task and publishes every sample sent
+/// through its channel, mirroring `GenericTopicListener`.
+#[allow(dead_code)]
+pub struct GenericTopicPublisher<T: TypeSupport + DdsSerialize + Send + Sync + 'static> {
+    /// Topic name
+    topic_name: String,
+    /// DDS domain ID
+    domain_id: i32,
+    /// Channel feeding the publisher task
+    tx: Sender<T>,
+    /// Receiving end, moved into the task on start
+    rx: Option<Receiver<T>>,
+    /// Publisher task handle
+    publisher_task: Option<JoinHandle<()>>,
+}
+
+#[allow(dead_code)]
+impl<T: TypeSupport + DdsSerialize + Send + Sync + 'static> GenericTopicPublisher<T> {
+    /// Creates a new typed publisher
+    ///
+    /// # Arguments
+    ///
+    /// * `topic_name` - Name of the DDS topic
+    /// * `domain_id` - DDS domain ID
+    pub fn new(topic_name: String, domain_id: i32) -> Self {
+        let (tx, rx) = mpsc::channel(100);
+        Self {
+            topic_name,
+            domain_id,
+            tx,
+            rx: Some(rx),
+            publisher_task: None,
+        }
+    }
+
+    /// Starts the publisher task
+    pub fn start(&mut self) -> Result<()> {
+        let Some(rx) = self.rx.take() else {
+            return Ok(());
+        };
+
+        let topic_name = self.topic_name.clone();
+        let domain_id = self.domain_id;
+        let task = tokio::spawn(async move {
+            if let Err(e) = Self::publisher_loop(topic_name.clone(), domain_id, rx).await {
+                logd!(5, "Error in publisher loop for {}: {:?}", topic_name, e);
+            }
+        });
+
+        self.publisher_task = Some(task);
+        Ok(())
+    }
+
+    /// Stops the publisher task
+    pub fn stop(&mut self) {
+        if let Some(task) = self.publisher_task.take() {
+            task.abort();
+        }
+    }
+
+    /// Returns `true` while the publisher task is running
+    pub fn is_running(&self) -> bool {
+        self.publisher_task
+            .as_ref()
+            .is_some_and(|task| !task.is_finished())
+    }
+
+    /// Gets the sender used to queue samples for publishing
+    pub fn get_sender(&self) -> Sender<T> {
+        self.tx.clone()
+    }
+
+    /// Gets the topic name
+    pub fn get_topic_name(&self) -> &str {
+        &self.topic_name
+    }
+
+    /// 퍼블리셔 루프
+    async fn publisher_loop(topic_name: String, domain_id: i32, mut rx: Receiver<T>) -> Result<()> {
+        let writer = create_writer::<T>(&topic_name, domain_id)?;
+        let mut waited = false;
+
+        while let Some(data) = rx.recv().await {
+            if !waited {
+                wait_for_reader(&writer, &topic_name, TASK_MATCH_TIMEOUT).await;
+                waited = true;
+            }
+            if let Err(e) = writer.write(&data, None) {
+                logd!(5, "Failed to publish on {}: {:?}", topic_name, e);
+            }
+        }
+
+        logd!(4, "Channel closed, stopping publisher for {}", topic_name);
+        Ok(())
+    }
+}
+
+impl<T: TypeSupport + DdsSerialize + Send + Sync + 'static> Drop for GenericTopicPublisher<T> {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::vehicle::dds::listener::{DdsTopicListener, GenericTopicListener};
+    use crate::vehicle::dds::{DdsData, DdsValue};
+    use dust_dds_derive::DdsType;
+    use serde::{Deserialize, Serialize};
+    use std::time::Duration;
+    // Temporarily shadow the conflicting `Result` alias
+    type Result<T, E> = std::result::Result<T, E>;
+
+    // Each loopback test uses its own domain so parallel tests do not
+    // interfere with each other's discovery
+    const SEND_DDS_DOMAIN: i32 = 153;
+    const PUBLISHER_TASK_DOMAIN: i32 = 156;
+    const PUBLISH_RAW_DOMAIN: i32 = 157;
+
+    #[derive(Default, Debug, Clone, Deserialize, Serialize, PartialEq, DdsType)]
+    pub struct BodyLightsHeadLampStatus {
+        pub command: i32,
+        pub status: i32,
+        pub progress: i32,
+        pub uistatus: i32,
+    }
+
+    async fn start_listener(
+        topic: &str,
+        domain_id: i32,
+    ) -> (
+        GenericTopicListener<BodyLightsHeadLampStatus>,
+        mpsc::Receiver<DdsData>,
+    ) {
+        let (tx, rx) = mpsc::channel(10);
+        let mut listener = GenericTopicListener::<BodyLightsHeadLampStatus>::new(
+            topic.to_string(),
+            "BodyLightsHeadLampStatus".to_string(),
+            tx,
+            domain_id,
+        );
+        listener.start().await.unwrap();
+        (listener, rx)
+    }
+
+    async fn receive(rx: &mut mpsc::Receiver<DdsData>) -> DdsData {
+        tokio::time::timeout(Duration::from_secs(20), rx.recv())
+            .await
+            .expect("no sample received")
+            .expect("listener channel closed")
+    }
+
+    /// One-shot writers can publish before discovery has finished under
+    /// load, so resend until the listener sees a sample
+    async fn send_until_received<F, Fut>(rx: &mut mpsc::Receiver<DdsData>, send: F) -> DdsData
+    where
+        F: Fn() -> Fut,
+        Fut: std::future::Future<Output = common::Result<()>>,
+    {
+        for _ in 0..5 {
+            send().await.unwrap();
+            if let Ok(Some(data)) = tokio::time::timeout(Duration::from_secs(4), rx.recv()).await {
+                return data;
+            }
+        }
+        panic!("no sample received");
+    }
+
+    #[tokio::test]
+    async fn test_send_dds_loopback() {
+        let topic = "LoopbackSendDds";
+        let (mut listener, mut rx) = start_listener(topic, SEND_DDS_DOMAIN).await;
+
+        let sample = BodyLightsHeadLampStatus {
+            command: 1,
+            status: 2,
+            progress: 50,
+            uistatus: 2,
+        };
+        let data =
+            send_until_received(&mut rx, || send_dds(topic, sample.clone(), SEND_DDS_DOMAIN)).await;
+        assert_eq!(data.name, "BodyLightsHeadLampStatus");
+        let received: BodyLightsHeadLampStatus = serde_json::from_str(&data.value).unwrap();
+        assert_eq!(received, sample);
+        assert_eq!(data.fields.get("progress"), Some(&DdsValue::Int64(50)));
+
+        listener.stop().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_publisher_task_loopback() {
+        let topic = "LoopbackPublisherTask";
+        let (mut listener, mut rx) = start_listener(topic, PUBLISHER_TASK_DOMAIN).await;
+
+        let mut publisher = GenericTopicPublisher::<BodyLightsHeadLampStatus>::new(
+            topic.to_string(),
+            PUBLISHER_TASK_DOMAIN,
+        );
+        assert!(!publisher.is_running());
+        publisher.start().unwrap();
+        assert!(publisher.is_running());
+        assert_eq!(publisher.get_topic_name(), topic);
+
+        // The listener keeps only the latest sample, so wait for each one
+        let sender = publisher.get_sender();
+        for progress in [0, 100] {
+            sender
+                .send(BodyLightsHeadLampStatus {
+                    command: 1,
+                    status: 3,
+                    progress,
+                    uistatus: 2,
+                })
+                .await
+                .unwrap();
+            let data = receive(&mut rx).await;
+            assert_eq!(data.fields.get("progress"), Some(&DdsValue::from(progress)));
+        }
+
+        publisher.stop();
+        assert!(!publisher.is_running());
+        listener.stop().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_publisher_start_is_idempotent() {
+        let mut publisher =
+            GenericTopicPublisher::<BodyLightsHeadLampStatus>::new("Idempotent".into(), 154);
+        publisher.start().unwrap();
+        publisher.start().unwrap();
+        assert!(publisher.is_running());
+        publisher.stop();
+        assert!(!publisher.is_running());
+    }
+
+    #[cfg(feature = "dds_type_registry_exists")]
+    #[tokio::test]
+    async fn test_publish_raw_loopback() {
+        // publish_raw looks the type up by topic name
+        // Listen with the generated type so both ends share one definition
+        let topic = "BodyLightsHeadLampStatus";
+        let (tx, mut rx) = mpsc::channel(10);
+        let mut listener = crate::vehicle::dds::dds_type_registry::create_typed_listener(
+            topic,
+            topic.to_string(),
+            tx,
+            PUBLISH_RAW_DOMAIN,
+        )
+        .unwrap();
+        listener.start().await.unwrap();
+
+        let value = serde_json::json!({
+            "command": 0,
+            "status": 3,
+            "progress": 100,
+            "uistatus": 1,
+        });
+        let data = send_until_received(&mut rx, || {
+            publish_raw(topic, value.clone(), PUBLISH_RAW_DOMAIN)
+        })
+        .await;
+        assert_eq!(data.fields.get("uistatus"), Some(&DdsValue::Int64(1)));
+
+        listener.stop().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_publish_raw_unknown_topic() {
+        let result = publish_raw("NoSuchTopic", serde_json::json!({}), 155).await;
+        assert!(result.is_err());
+    }
+}
@@ -0,0 +1,497 @@
+/*
+* SPDX-FileCopyrightText: Copyright 2024 LG Electronics Inc.
+* SPDX-License-Identifier: Apache-2.0
+*/
+//! Recording and replay of received vehicle data
+//!
+//! A recording is a JSON Lines file holding one `DdsData` per line, with
+//! the time it was received. Replaying it feeds the samples back through
+//! the channel the listeners use, so scenario filters see the same data as
+//! on the vehicle without a DDS publisher.
+//!
+//! A recording is rotated once it grows too large or too old: the current
+//! file becomes `<name>.1.jsonl`, older ones move up by one and the oldest
+//! beyond the configured count is deleted.
+
+use super::DdsData;
+use anyhow::anyhow;
+use common::Result;
+use std::fs::{File, OpenOptions};
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant, SystemTime};
+use tokio::sync::mpsc::Sender;
+
+/// When a recording is rotated
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Rotation {
+    /// Largest size of one file, in bytes
+    pub max_bytes: u64,
+    /// Longest time one file is written to, if limited
+    pub max_age: Option<Duration>,
+    /// Number of rotated files kept
+    pub max_files: usize,
+}
+
+impl Rotation {
+    /// Never rotates
+    #[cfg(test)]
+    pub fn none() -> Self {
+        Self {
+            max_bytes: u64::MAX,
+            max_age: None,
+            max_files: 0,
+        }
+    }
+}
+
+/// Appends received samples to a recording
+pub struct Recorder {
+    path: PathBuf,
+    file: File,
+    /// Size of the current file
+    size: u64,
+    /// Time the current file was opened
+    opened_at: Instant,
+    rotation: Rotation,
+}
+
+impl Recorder {
+    /// Opens `path` for appending, creating it if needed
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - Recording file
+    ///
+    /// # Returns
+    ///
+    /// * `Result<Self>` - Recorder, or an error if the file cannot be opened
+    #[cfg(test)]
+    pub fn open(path: impl AsRef<Path>) -> Result<Self> {
+        Self::with_rotation(path, Rotation::none())
+    }
+
+    /// Opens `path` for appending, rotating it as `rotation` sets
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - Recording file
+    /// * `rotation` - Limits of one file
+    ///
+    /// # Returns
+    ///
+    /// * `Result<Self>` - Recorder, or an error if the file cannot be opened
+    pub fn with_rotation(path: impl AsRef<Path>, rotation: Rotation) -> Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        let file = OpenOptions::new().create(true).append(true).open(&path)?;
+        Ok(Self {
+            size: file.metadata()?.len(),
+            path,
+            file,
+            opened_at: Instant::now(),
+            rotation,
+        })
+    }
+
+    /// Opens the recording configured by `dds.record_path` in `settings.yaml`
+    ///
+    /// # Returns
+    ///
+    /// * `Option<Self>` - Recorder, or `None` if recording is disabled or the
+    ///   file cannot be opened
+    pub fn from_settings() -> Option<Self> {
+        let dds = &common::setting::get_config().dds;
+        let path = dds.record_path.as_ref()?;
+        let rotation = Rotation {
+            max_bytes: dds.record_max_bytes,
+            max_age: (dds.record_max_age_secs > 0)
+                .then(|| Duration::from_secs(dds.record_max_age_secs)),
+            max_files: dds.record_max_files,
+        };
+        match Self::with_rotation(path, rotation) {
+            Ok(recorder) => Some(recorder),
+            Err(e) => {
+                common::logd!(5, "Failed to open DDS recording {}: {:?}", path, e);
+                None
+            }
+        }
+    }
+
+    /// Appends `data` as one line, rotating the file first if the line
+    /// would exceed its limits
+    pub fn record(&mut self, data: &DdsData) -> Result<()> {
+        let mut line = serde_json::to_string(data)?;
+        line.push('\n');
+
+        let too_large = self.size + line.len() as u64 > self.rotation.max_bytes;
+        let too_old = self
+            .rotation
+            .max_age
+            .is_some_and(|max_age| self.opened_at.elapsed() >= max_age);
+        if self.size > 0 && (too_large || too_old) {
+            self.rotate()?;
+        }
+
+        self.file.write_all(line.as_bytes())?;
+        self.size += line.len() as u64;
+        Ok(())
+    }
+
+    /// Moves the current file to the first rotated name and starts a new one
+    fn rotate(&mut self) -> Result<()> {
+        let max_files = self.rotation.max_files;
+        if max_files == 0 {
+            std::fs::remove_file(&self.path)?;
+        } else {
+            for index in (1..max_files).rev() {
+                let from = rotated_path(&self.path, index);
+                if from.exists() {
+                    std::fs::rename(&from, rotated_path(&self.path, index + 1))?;
+                }
+            }
+            std::fs::rename(&self.path, rotated_path(&self.path, 1))?;
+        }
+
+        self.file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)?;
+        self.size = 0;
+        self.opened_at = Instant::now();
+        Ok(())
+    }
+}
+
+/// Name of the `index`th most recent rotated file of the recording `path`,
+/// e.g. `dds.2.jsonl` for `dds.jsonl`
+pub fn rotated_path(path: &Path, index: usize) -> PathBuf {
+    let stem = path.file_stem().unwrap_or_default().to_string_lossy();
+    let name = match path.extension() {
+        Some(extension) => format!("{}.{}.{}", stem, index, extension.to_string_lossy()),
+        None => format!("{}.{}", stem, index),
+    };
+    path.with_file_name(name)
+}
+
+/// Files of the recording `path`, oldest first and ending with `path`
+///
+/// # Arguments
+///
+/// * `path` - Current recording file
+///
+/// # Returns
+///
+/// * `Vec<PathBuf>` - Rotated files that exist, then `path`
+#[allow(dead_code)]
+pub fn recording_files(path: impl AsRef<Path>) -> Vec<PathBuf> {
+    let path = path.as_ref();
+    let mut files: Vec<PathBuf> = (1..)
+        .map(|index| rotated_path(path, index))
+        .take_while(|rotated| rotated.exists())
+        .collect();
+    files.reverse();
+    files.push(path.to_path_buf());
+    files
+}
+
+/// Reads every sample of a recording
+///
+/// # Arguments
+///
+/// * `path` - Recording file
+///
+/// # Returns
+///
+/// * `Result<Vec<DdsData>>` - Samples in recording order, or an error
+///   naming the first line that is not a valid sample
+pub fn read_recording(path: impl AsRef<Path>) -> Result<Vec<DdsData>> {
+    let reader = BufReader::new(File::open(path)?);
+    let mut samples = Vec::new();
+    for (index, line) in reader.lines().enumerate() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let sample = serde_json::from_str(&line)
+            .map_err(|e| anyhow!("line {}: invalid sample: {}", index + 1, e))?;
+        samples.push(sample);
+    }
+    Ok(samples)
+}
+
+/// Feeds a recording to `tx`, keeping the relative timing of the samples
+///
+/// Samples are sent `speed_factor` times faster than recorded. Their
+/// receive times are shifted to start now but keep the recorded spacing
+/// whatever the speed, so debounced conditions evaluate as they did on the
+/// vehicle.
+///
+/// # Arguments
+///
+/// * `path` - Recording file
+/// * `speed_factor` - Replay speed, 1.0 for real time
+/// * `tx` - Channel the DDS listeners send to
+///
+/// # Returns
+///
+/// * `Result<usize>` - Number of samples sent
+#[allow(dead_code)]
+pub async fn replay(
+    path: impl AsRef<Path>,
+    speed_factor: f64,
+    tx: Sender<DdsData>,
+) -> Result<usize> {
+    if !(speed_factor > 0.0 && speed_factor.is_finite()) {
+        return Err(anyhow!("speed factor must be positive, got {}", speed_factor).into());
+    }
+    let samples = read_recording(path)?;
+    let Some(first) = samples.first().map(|s| s.received_at) else {
+        return Ok(0);
+    };
+
+    let start = SystemTime::now();
+    let started = tokio::time::Instant::now();
+    let mut sent = 0;
+    for mut sample in samples {
+        let offset = sample
+            .received_at
+            .duration_since(first)
+            .unwrap_or(Duration::ZERO);
+        tokio::time::sleep_until(started + offset.div_f64(speed_factor)).await;
+
+        sample.received_at = start + offset;
+        tx.send(sample)
+            .await
+            .map_err(|_| anyhow!("DDS data channel closed"))?;
+        sent += 1;
+    }
+    Ok(sent)
+}
+
+//Unit Test Cases
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::vehicle::dds::DdsValue;
+    use tokio::sync::mpsc;
+
+    fn recording_path(name: &str) -> std::path::PathBuf {
+        let path = std::env::temp_dir().join(format!("filtergateway-recording-{}.jsonl", name));
+        let _ = std::fs::remove_file(&path);
+        path
+    }
+
+    /// `VehicleSpeed` samples 50ms apart
+    fn speed_samples(speeds: &[i64]) -> Vec<DdsData> {
+        speeds
+            .iter()
+            .enumerate()
+            .map(|(i, speed)| {
+                let mut data = DdsData::new("VehicleSpeed", format!("{{\"speed\":{}}}", speed))
+                    .with_field("speed", DdsValue::Int64(*speed));
+                data.received_at = SystemTime::UNIX_EPOCH + Duration::from_millis(i as u64 * 50);
+                data
+            })
+            .collect()
+    }
+
+    fn remove_recording(path: &Path) {
+        for file in recording_files(path) {
+            let _ = std::fs::remove_file(file);
+        }
+    }
+
+    /// Samples of every file of a recording, oldest first
+    fn read_all(path: &Path) -> Vec<DdsData> {
+        recording_files(path)
+            .iter()
+            .flat_map(|file| read_recording(file).unwrap())
+            .collect()
+    }
+
+    fn speeds(samples: &[DdsData]) -> Vec<DdsValue> {
+        samples.iter().map(|s| s.fields["speed"].clone()).collect()
+    }
+
+    fn record_all(path: &Path, samples: &[DdsData]) {
+        let mut recorder = Recorder::open(path).unwrap();
+        for sample in samples {
+            recorder.record(sample).unwrap();
+        }
+    }
+
+    fn overspeed_filter() -> crate::filter::Filter {
+        let scenario: common::spec::artifact::Scenario = serde_yaml::from_str(
+            "apiVersion: v1\nkind: Scenario\nmetadata:\n  name: overspeed\nspec:\n  condition:\n    express: gt\n    value: \"80\"\n    operands:\n      type: DDS\n      name: speed\n      value: VehicleSpeed\n    debounceMs: 100\n  action: launch\n  target: overspeed-warning\n",
+        )
+        .unwrap();
+        crate::filter::Filter::new(
+            "overspeed".into(),
+            scenario,
+            true,
+            crate::grpc::sender::actioncontroller::FilterGatewaySender::new(),
+        )
+    }
+
+    /// Indices of the samples on which the overspeed condition became met
+    fn decisions(samples: &[DdsData]) -> Vec<usize> {
+        let mut filter = overspeed_filter();
+        samples
+            .iter()
+            .enumerate()
+            .filter(|(_, data)| filter.condition_met(data).unwrap())
+            .map(|(i, _)| i)
+            .collect()
+    }
+
+    #[test]
+    fn test_record_appends_lines() {
+        let path = recording_path("append");
+        let samples = speed_samples(&[79, 81]);
+        record_all(&path, &samples[..1]);
+        record_all(&path, &samples[1..]);
+
+        let recorded = read_recording(&path).unwrap();
+
+        assert_eq!(recorded.len(), 2);
+        assert_eq!(recorded[1].fields["speed"], DdsValue::Int64(81));
+        assert_eq!(recorded[1].received_at, samples[1].received_at);
+        assert_eq!(recorded[1].value, samples[1].value);
+    }
+
+    #[test]
+    fn test_rotated_path() {
+        assert_eq!(
+            rotated_path(Path::new("/var/log/dds.jsonl"), 2),
+            PathBuf::from("/var/log/dds.2.jsonl")
+        );
+        assert_eq!(
+            rotated_path(Path::new("recording"), 1),
+            PathBuf::from("recording.1")
+        );
+    }
+
+    #[test]
+    fn test_record_rotates_by_size() {
+        let path = recording_path("rotate-size");
+        remove_recording(&path);
+        let samples = speed_samples(&[1, 2, 3, 4, 5]);
+        let line_len = samples
+            .iter()
+            .map(|sample| serde_json::to_string(sample).unwrap().len() as u64 + 1)
+            .max()
+            .unwrap();
+        let rotation = Rotation {
+            max_bytes: 2 * line_len,
+            max_age: None,
+            max_files: 1,
+        };
+
+        let mut recorder = Recorder::with_rotation(&path, rotation).unwrap();
+        for sample in &samples {
+            recorder.record(sample).unwrap();
+        }
+
+        // Two samples per file, only one rotated file kept
+        assert_eq!(
+            recording_files(&path),
+            vec![rotated_path(&path, 1), path.clone()]
+        );
+        assert_eq!(
+            speeds(&read_all(&path)),
+            vec![DdsValue::Int64(3), DdsValue::Int64(4), DdsValue::Int64(5)]
+        );
+    }
+
+    #[test]
+    fn test_record_rotates_by_age() {
+        let path = recording_path("rotate-age");
+        remove_recording(&path);
+        let rotation = Rotation {
+            max_bytes: u64::MAX,
+            max_age: Some(Duration::from_millis(20)),
+            max_files: 3,
+        };
+
+        let mut recorder = Recorder::with_rotation(&path, rotation).unwrap();
+        let samples = speed_samples(&[1, 2, 3]);
+        recorder.record(&samples[0]).unwrap();
+        recorder.record(&samples[1]).unwrap();
+        std::thread::sleep(Duration::from_millis(30));
+        recorder.record(&samples[2]).unwrap();
+
+        assert_eq!(recording_files(&path).len(), 2);
+        assert_eq!(read_recording(rotated_path(&path, 1)).unwrap().len(), 2);
+        assert_eq!(
+            speeds(&read_all(&path)),
+            vec![DdsValue::Int64(1), DdsValue::Int64(2), DdsValue::Int64(3)]
+        );
+    }
+
+    #[test]
+    fn test_read_recording_rejects_invalid_line() {
+        let path = recording_path("invalid");
+        record_all(&path, &speed_samples(&[79]));
+        std::fs::OpenOptions::new()
+            .append(true)
+            .open(&path)
+            .unwrap()
+            .write_all(b"not json\n")
+            .unwrap();
+
+        let err = read_recording(&path).unwrap_err();
+        assert!(err.to_string().starts_with("line 2: invalid sample"));
+    }
+
+    #[tokio::test]
+    async fn test_replay_keeps_filter_decisions() {
+        let path = recording_path("replay");
+        let samples = speed_samples(&[79, 81, 79, 81, 82, 83, 84, 79, 81, 78]);
+        record_all(&path, &samples);
+
+        let (tx, mut rx) = mpsc::channel(4);
+        let replayer =
+            tokio::spawn(async move { replay(&path, 10.0, tx).await.map_err(|e| e.to_string()) });
+        let mut replayed = Vec::new();
+        while let Some(data) = rx.recv().await {
+            replayed.push(data);
+        }
+
+        assert_eq!(replayer.await.unwrap().unwrap(), samples.len());
+        assert_eq!(decisions(&replayed), decisions(&samples));
+        assert_eq!(decisions(&samples), vec![5]);
+
+        let spacing = replayed[3]
+            .received_at
+            .duration_since(replayed[2].received_at)
+            .unwrap();
+        assert_eq!(spacing, Duration::from_millis(50));
+    }
+
+    #[tokio::test]
+    async fn test_replay_paces_samples() {
+        let path = recording_path("pace");
+        record_all(&path, &speed_samples(&[10, 20, 30]));
+
+        let (tx, mut rx) = mpsc::channel(4);
+        let started = tokio::time::Instant::now();
+        tokio::spawn(async move { replay(&path, 2.0, tx).await.map_err(|e| e.to_string()) });
+
+        let mut arrivals = Vec::new();
+        while rx.recv().await.is_some() {
+            arrivals.push(started.elapsed());
+        }
+        // Samples recorded 50ms apart arrive 25ms apart at double speed
+        assert_eq!(arrivals.len(), 3);
+        assert!(arrivals[1] >= Duration::from_millis(25));
+        assert!(arrivals[2] >= Duration::from_millis(50));
+        assert!(arrivals[2] < Duration::from_millis(100));
+    }
+
+    #[tokio::test]
+    async fn test_replay_rejects_bad_speed() {
+        let (tx, _rx) = mpsc::channel(1);
+        assert!(replay("/nonexistent", 0.0, tx.clone()).await.is_err());
+        assert!(replay("/nonexistent", f64::NAN, tx).await.is_err());
+    }
+}
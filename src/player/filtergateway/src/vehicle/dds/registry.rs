@@ -0,0 +1,197 @@
+/*
+* SPDX-FileCopyrightText: Copyright 2024 LG Electronics Inc.
+* SPDX-License-Identifier: Apache-2.0
+*/
+use super::listener::DdsTopicListener;
+use super::DdsData;
+use std::collections::BTreeMap;
+use tokio::sync::mpsc::Sender;
+
+/// Creates the listener for one topic
+///
+/// # Arguments
+///
+/// * `String` - Topic name
+/// * `Sender<DdsData>` - Channel the listener forwards samples to
+/// * `i32` - DDS domain ID
+pub type ListenerFactory = fn(String, Sender<DdsData>, i32) -> Box<dyn DdsTopicListener>;
+
+/// Runtime registry of the vehicle topics FilterGateway can listen to
+///
+/// Topics generated from the IDL directory register themselves through
+/// `dds_type_registry::register_topics`, so adding a vehicle signal only
+/// needs a new IDL file.
+#[derive(Default)]
+pub struct TopicRegistry {
+    /// Listener factory per topic name
+    topics: BTreeMap<String, ListenerFactory>,
+}
+
+impl TopicRegistry {
+    /// Creates an empty registry
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates a registry holding every topic generated from the IDL files
+    pub fn generated() -> Self {
+        let mut registry = Self::new();
+        super::dds_type_registry::register_topics(&mut registry);
+        registry
+    }
+
+    /// Registers `factory` for `topic_name`, replacing any previous entry
+    pub fn register(&mut self, topic_name: &str, factory: ListenerFactory) {
+        self.topics.insert(topic_name.to_string(), factory);
+    }
+
+    /// Returns `true` if `topic_name` is registered
+    pub fn contains(&self, topic_name: &str) -> bool {
+        self.topics.contains_key(topic_name)
+    }
+
+    /// Registered topic names in alphabetical order
+    pub fn topics(&self) -> Vec<String> {
+        self.topics.keys().cloned().collect()
+    }
+
+    /// Creates the listener for `topic_name`
+    ///
+    /// # Returns
+    ///
+    /// * `Option<Box<dyn DdsTopicListener>>` - `None` if the topic is not registered
+    pub fn create_listener(
+        &self,
+        topic_name: &str,
+        tx: Sender<DdsData>,
+        domain_id: i32,
+    ) -> Option<Box<dyn DdsTopicListener>> {
+        self.topics
+            .get(topic_name)
+            .map(|factory| factory(topic_name.to_string(), tx, domain_id))
+    }
+
+    /// Resolves the topics to enable
+    ///
+    /// # Arguments
+    ///
+    /// * `topics` - Requested topic names, or `None` for every registered topic
+    ///
+    /// # Returns
+    ///
+    /// * `Result<Vec<String>, String>` - Topics to enable, or an error naming
+    ///   the unknown topics and listing the available ones
+    pub fn select(&self, topics: Option<&[String]>) -> Result<Vec<String>, String> {
+        let Some(topics) = topics else {
+            return Ok(self.topics());
+        };
+
+        let unknown: Vec<&str> = topics
+            .iter()
+            .filter(|topic| !self.contains(topic))
+            .map(String::as_str)
+            .collect();
+        if !unknown.is_empty() {
+            return Err(format!(
+                "Unknown DDS topic(s): {}. Available topics: {}",
+                unknown.join(", "),
+                self.topics().join(", ")
+            ));
+        }
+
+        let mut selected: Vec<String> = Vec::new();
+        for topic in topics {
+            if !selected.contains(topic) {
+                selected.push(topic.clone());
+            }
+        }
+        Ok(selected)
+    }
+}
+
+//Unit Test Cases
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::vehicle::dds::listener::DdsTopicListener;
+    use common::Result;
+    use tokio::sync::mpsc;
+
+    struct NoopListener {
+        topic_name: String,
+    }
+
+    #[async_trait::async_trait]
+    impl DdsTopicListener for NoopListener {
+        async fn start(&mut self) -> Result<()> {
+            Ok(())
+        }
+
+        async fn stop(&mut self) -> Result<()> {
+            Ok(())
+        }
+
+        fn is_running(&self) -> bool {
+            false
+        }
+
+        fn get_topic_name(&self) -> &str {
+            &self.topic_name
+        }
+
+        fn is_topic(&self, topic: &str) -> bool {
+            self.topic_name == topic
+        }
+    }
+
+    fn noop_factory(topic_name: String, _: Sender<DdsData>, _: i32) -> Box<dyn DdsTopicListener> {
+        Box::new(NoopListener { topic_name })
+    }
+
+    fn registry() -> TopicRegistry {
+        let mut registry = TopicRegistry::new();
+        registry.register("Speed", noop_factory);
+        registry.register("Gear", noop_factory);
+        registry
+    }
+
+    #[test]
+    fn test_register_and_create_listener() {
+        let registry = registry();
+        let (tx, _rx) = mpsc::channel(1);
+
+        assert_eq!(registry.topics(), vec!["Gear", "Speed"]);
+        let listener = registry.create_listener("Speed", tx.clone(), 0).unwrap();
+        assert_eq!(listener.get_topic_name(), "Speed");
+        assert!(registry.create_listener("Missing", tx, 0).is_none());
+    }
+
+    #[test]
+    fn test_select_all_and_subset() {
+        let registry = registry();
+
+        assert_eq!(registry.select(None).unwrap(), vec!["Gear", "Speed"]);
+        let subset = vec!["Speed".to_string(), "Speed".to_string()];
+        assert_eq!(registry.select(Some(&subset)).unwrap(), vec!["Speed"]);
+    }
+
+    #[test]
+    fn test_select_unknown_topic_lists_available() {
+        let registry = registry();
+        let requested = vec!["Speed".to_string(), "Wiper".to_string()];
+
+        let err = registry.select(Some(&requested)).unwrap_err();
+        assert_eq!(
+            err,
+            "Unknown DDS topic(s): Wiper. Available topics: Gear, Speed"
+        );
+    }
+
+    #[cfg(feature = "dds_type_registry_exists")]
+    #[test]
+    fn test_generated_registry_contains_idl_topics() {
+        let registry = TopicRegistry::generated();
+        assert!(registry.contains("BodyTrunkStatus"));
+        assert!(registry.contains("ADASObstacleDetectionIsWarning"));
+    }
+}
@@ -0,0 +1,380 @@
+/*
+* SPDX-FileCopyrightText: Copyright 2024 LG Electronics Inc.
+* SPDX-License-Identifier: Apache-2.0
+*/
+use common::logd;
+use common::Result;
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+use tokio::sync::watch;
+use tokio::task::JoinHandle;
+use tokio::time::{self, Duration, Instant};
+
+/// Health of a supervised listener
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ListenerHealth {
+    /// Reader is up and polling its topic
+    Running,
+    /// Reader failed and is waiting to be recreated
+    Restarting,
+    /// Listener was stopped or its data channel closed
+    Stopped,
+}
+
+/// Status reported by a supervised listener
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ListenerStatus {
+    /// Topic name
+    pub topic_name: String,
+    /// Current health
+    pub health: ListenerHealth,
+    /// Number of times the reader was recreated
+    pub restarts: u32,
+    /// Reason of the last failure, if any
+    pub last_error: Option<String>,
+}
+
+impl ListenerStatus {
+    /// Status of a listener that has not been started yet
+    pub fn stopped(topic_name: &str) -> Self {
+        Self {
+            topic_name: topic_name.to_string(),
+            health: ListenerHealth::Stopped,
+            restarts: 0,
+            last_error: None,
+        }
+    }
+}
+
+/// Restart policy of a supervised listener
+#[derive(Debug, Clone, PartialEq)]
+pub struct SupervisorConfig {
+    /// Recreate the reader when no sample arrives for this long.
+    /// `None` disables silence detection, for topics that are legitimately quiet.
+    pub heartbeat_timeout: Option<Duration>,
+    /// Delay before the first restart
+    pub initial_backoff: Duration,
+    /// Upper bound of the restart delay, which doubles after each failure
+    pub max_backoff: Duration,
+}
+
+impl Default for SupervisorConfig {
+    fn default() -> Self {
+        Self {
+            heartbeat_timeout: None,
+            initial_backoff: Duration::from_secs(1),
+            max_backoff: Duration::from_secs(30),
+        }
+    }
+}
+
+/// Time of the last sample received by a listener loop
+#[derive(Debug, Clone)]
+pub struct Heartbeat(Arc<Mutex<Instant>>);
+
+impl Heartbeat {
+    /// Creates a heartbeat that was last seen now
+    pub fn new() -> Self {
+        Self(Arc::new(Mutex::new(Instant::now())))
+    }
+
+    /// Records that a sample was received
+    pub fn beat(&self) {
+        if let Ok(mut last) = self.0.lock() {
+            *last = Instant::now();
+        }
+    }
+
+    /// Time since the last sample
+    pub fn elapsed(&self) -> Duration {
+        self.0.lock().map(|last| last.elapsed()).unwrap_or_default()
+    }
+}
+
+impl Default for Heartbeat {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Aborts the wrapped task when dropped, so stopping the supervisor also
+/// stops the reader it owns
+struct AbortOnDrop(JoinHandle<std::result::Result<(), String>>);
+
+impl Drop for AbortOnDrop {
+    fn drop(&mut self) {
+        self.0.abort();
+    }
+}
+
+/// Resolves once `heartbeat` has been silent for `timeout`
+async fn silence(heartbeat: &Heartbeat, timeout: Option<Duration>) {
+    let Some(timeout) = timeout else {
+        return std::future::pending().await;
+    };
+    loop {
+        let elapsed = heartbeat.elapsed();
+        if elapsed >= timeout {
+            return;
+        }
+        time::sleep(timeout - elapsed).await;
+    }
+}
+
+/// Runs a listener loop and recreates it whenever it fails
+///
+/// The loop runs in its own task, so a panic only ends that task. The loop
+/// is recreated with exponential backoff when it returns an error, panics,
+/// is aborted, or stays silent longer than `config.heartbeat_timeout`.
+/// Returning `Ok(())` means the data channel closed and ends supervision.
+///
+/// # Arguments
+///
+/// * `topic_name` - Topic name used in logs and status
+/// * `config` - Restart policy
+/// * `status` - Channel the listener health is published on
+/// * `run` - Creates a new listener loop for the given heartbeat
+pub async fn supervise<F, Fut>(
+    topic_name: String,
+    config: SupervisorConfig,
+    status: Arc<watch::Sender<ListenerStatus>>,
+    run: F,
+) where
+    F: Fn(Heartbeat) -> Fut,
+    Fut: Future<Output = Result<()>> + Send + 'static,
+{
+    let mut restarts = 0;
+    let mut backoff = config.initial_backoff;
+
+    loop {
+        let heartbeat = Heartbeat::new();
+        let listener = run(heartbeat.clone());
+        let mut task = AbortOnDrop(tokio::spawn(async move {
+            listener.await.map_err(|e| e.to_string())
+        }));
+        let started = Instant::now();
+        publish(
+            &status,
+            &topic_name,
+            ListenerHealth::Running,
+            restarts,
+            None,
+        );
+
+        let failure = tokio::select! {
+            joined = &mut task.0 => match joined {
+                Ok(Ok(())) => {
+                    logd!(3, "Listener for '{}' finished", topic_name);
+                    publish(&status, &topic_name, ListenerHealth::Stopped, restarts, None);
+                    return;
+                }
+                Ok(Err(e)) => format!("listener failed: {}", e),
+                Err(e) if e.is_panic() => "listener panicked".to_string(),
+                Err(e) => format!("listener task ended: {}", e),
+            },
+            _ = silence(&heartbeat, config.heartbeat_timeout) => {
+                format!("no sample for {:?}", heartbeat.elapsed())
+            }
+        };
+        drop(task);
+
+        // A listener that stayed up for a while starts over with a short delay
+        if started.elapsed() > config.max_backoff {
+            backoff = config.initial_backoff;
+        }
+        restarts += 1;
+        logd!(
+            5,
+            "Listener for '{}' stopped ({}), resubscribing in {:?}",
+            topic_name,
+            failure,
+            backoff
+        );
+        publish(
+            &status,
+            &topic_name,
+            ListenerHealth::Restarting,
+            restarts,
+            Some(failure),
+        );
+
+        time::sleep(backoff).await;
+        backoff = (backoff * 2).min(config.max_backoff);
+    }
+}
+
+/// Publishes a new status, keeping the last error unless a new one is given
+fn publish(
+    status: &watch::Sender<ListenerStatus>,
+    topic_name: &str,
+    health: ListenerHealth,
+    restarts: u32,
+    error: Option<String>,
+) {
+    status.send_modify(|current| {
+        current.topic_name = topic_name.to_string();
+        current.health = health;
+        current.restarts = restarts;
+        if error.is_some() {
+            current.last_error = error;
+        }
+    });
+}
+
+//Unit Test Cases
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicU32, Ordering};
+
+    fn fast_config(heartbeat_timeout: Option<Duration>) -> SupervisorConfig {
+        SupervisorConfig {
+            heartbeat_timeout,
+            initial_backoff: Duration::from_millis(10),
+            max_backoff: Duration::from_millis(40),
+        }
+    }
+
+    fn status_channel() -> (
+        Arc<watch::Sender<ListenerStatus>>,
+        watch::Receiver<ListenerStatus>,
+    ) {
+        let (tx, rx) = watch::channel(ListenerStatus::stopped("Speed"));
+        (Arc::new(tx), rx)
+    }
+
+    async fn wait_for<P>(rx: &mut watch::Receiver<ListenerStatus>, predicate: P) -> ListenerStatus
+    where
+        P: Fn(&ListenerStatus) -> bool,
+    {
+        time::timeout(Duration::from_secs(5), rx.wait_for(|s| predicate(s)))
+            .await
+            .expect("status not reached")
+            .expect("status channel closed")
+            .clone()
+    }
+
+    #[tokio::test]
+    async fn test_killed_listener_is_resubscribed() {
+        let (status, mut rx) = status_channel();
+        let runs = Arc::new(AtomicU32::new(0));
+
+        let counter = runs.clone();
+        let supervisor = tokio::spawn(supervise(
+            "Speed".to_string(),
+            fast_config(None),
+            status,
+            move |_heartbeat| {
+                let run = counter.fetch_add(1, Ordering::SeqCst);
+                async move {
+                    if run == 0 {
+                        panic!("reader lost");
+                    }
+                    std::future::pending::<()>().await;
+                    Ok(())
+                }
+            },
+        ));
+
+        let current = wait_for(&mut rx, |s| {
+            s.restarts == 1 && s.health == ListenerHealth::Running
+        })
+        .await;
+        assert_eq!(current.last_error.as_deref(), Some("listener panicked"));
+        assert_eq!(runs.load(Ordering::SeqCst), 2);
+
+        supervisor.abort();
+    }
+
+    #[tokio::test]
+    async fn test_failing_listener_is_retried_with_backoff() {
+        let (status, mut rx) = status_channel();
+        let runs = Arc::new(AtomicU32::new(0));
+
+        let counter = runs.clone();
+        let supervisor = tokio::spawn(supervise(
+            "Speed".to_string(),
+            fast_config(None),
+            status,
+            move |_heartbeat| {
+                counter.fetch_add(1, Ordering::SeqCst);
+                async { Err("reader error".into()) }
+            },
+        ));
+
+        let current = wait_for(&mut rx, |s| s.restarts >= 3).await;
+        assert_eq!(
+            current.last_error.as_deref(),
+            Some("listener failed: reader error")
+        );
+        assert!(runs.load(Ordering::SeqCst) >= 3);
+
+        supervisor.abort();
+    }
+
+    #[tokio::test]
+    async fn test_silent_listener_is_recreated() {
+        let (status, mut rx) = status_channel();
+
+        let supervisor = tokio::spawn(supervise(
+            "Speed".to_string(),
+            fast_config(Some(Duration::from_millis(50))),
+            status,
+            |_heartbeat| async {
+                std::future::pending::<()>().await;
+                Ok(())
+            },
+        ));
+
+        let current = wait_for(&mut rx, |s| s.restarts >= 1).await;
+        assert!(current
+            .last_error
+            .as_deref()
+            .is_some_and(|e| e.starts_with("no sample for")));
+
+        supervisor.abort();
+    }
+
+    #[tokio::test]
+    async fn test_beating_listener_is_not_recreated() {
+        let (status, rx) = status_channel();
+
+        let supervisor = tokio::spawn(supervise(
+            "Speed".to_string(),
+            fast_config(Some(Duration::from_millis(100))),
+            status,
+            |heartbeat| async move {
+                loop {
+                    heartbeat.beat();
+                    time::sleep(Duration::from_millis(10)).await;
+                }
+            },
+        ));
+
+        time::sleep(Duration::from_millis(400)).await;
+        assert_eq!(rx.borrow().restarts, 0);
+        assert_eq!(rx.borrow().health, ListenerHealth::Running);
+
+        supervisor.abort();
+    }
+
+    #[tokio::test]
+    async fn test_closed_channel_ends_supervision() {
+        let (status, rx) = status_channel();
+
+        time::timeout(
+            Duration::from_secs(5),
+            supervise(
+                "Speed".to_string(),
+                fast_config(None),
+                status,
+                |_heartbeat| async { Ok(()) },
+            ),
+        )
+        .await
+        .expect("supervisor did not finish");
+
+        assert_eq!(rx.borrow().health, ListenerHealth::Stopped);
+        assert_eq!(rx.borrow().restarts, 0);
+    }
+}
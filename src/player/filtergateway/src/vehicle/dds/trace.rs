@@ -0,0 +1,279 @@
+/*
+* SPDX-FileCopyrightText: Copyright 2024 LG Electronics Inc.
+* SPDX-License-Identifier: Apache-2.0
+*/
+//! Replay of vehicle signal traces over DDS
+//!
+//! Unlike a recording replayed into the manager, a trace is published on
+//! the DDS bus, so FilterGateway receives it through its listeners like
+//! live vehicle data. A trace lists `topic`, `value` and `offset_ms` per
+//! sample, where the offset is the time since the start of the trace:
+//!
+//! * `.csv` - `topic,value,offset_ms` lines, the value as JSON. A header
+//!   line and values quoted with `"` are accepted.
+//! * `.json` - Array of `{"topic": ..., "value": ..., "offset_ms": ...}`.
+//! * `.jsonl` - Recording written by `dds.record_path`, see `recorder`.
+
+use super::recorder::read_recording;
+use super::DdsData;
+use anyhow::anyhow;
+use common::Result;
+use serde::{Deserialize, Serialize};
+use std::future::Future;
+use std::path::Path;
+use std::time::Duration;
+
+/// One sample of a trace
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TraceSample {
+    /// Topic the sample is published on, which is also its type name
+    pub topic: String,
+    /// Sample as JSON, using the IDL field names
+    pub value: serde_json::Value,
+    /// Milliseconds since the start of the trace
+    pub offset_ms: u64,
+}
+
+impl TraceSample {
+    /// Converts a recorded sample received `offset` after the first one
+    pub fn from_recorded(data: &DdsData, offset: Duration) -> Result<Self> {
+        Ok(Self {
+            topic: data.topic.clone(),
+            value: serde_json::from_str(&data.value)
+                .map_err(|e| anyhow!("invalid value on {}: {}", data.topic, e))?,
+            offset_ms: offset.as_millis() as u64,
+        })
+    }
+}
+
+/// Reads a trace, choosing the format by the file extension
+///
+/// # Arguments
+///
+/// * `path` - Trace file, `.csv`, `.json` or `.jsonl`
+///
+/// # Returns
+///
+/// * `Result<Vec<TraceSample>>` - Samples sorted by offset, or an error
+///   naming the first invalid line or sample
+pub fn read_trace(path: impl AsRef<Path>) -> Result<Vec<TraceSample>> {
+    let path = path.as_ref();
+    let mut samples = match path.extension().and_then(|e| e.to_str()) {
+        Some("csv") => parse_csv(&std::fs::read_to_string(path)?)?,
+        Some("json") => serde_json::from_str(&std::fs::read_to_string(path)?)
+            .map_err(|e| anyhow!("invalid trace: {}", e))?,
+        Some("jsonl") => {
+            let recording = read_recording(path)?;
+            let Some(first) = recording.first().map(|d| d.received_at) else {
+                return Ok(Vec::new());
+            };
+            recording
+                .iter()
+                .map(|data| {
+                    let offset = data.received_at.duration_since(first).unwrap_or_default();
+                    TraceSample::from_recorded(data, offset)
+                })
+                .collect::<Result<_>>()?
+        }
+        _ => return Err(anyhow!("unknown trace format: {}", path.display()).into()),
+    };
+    // Stable, so samples at the same offset keep their order
+    samples.sort_by_key(|s| s.offset_ms);
+    Ok(samples)
+}
+
+/// Parses `topic,value,offset_ms` lines
+fn parse_csv(text: &str) -> Result<Vec<TraceSample>> {
+    let mut samples = Vec::new();
+    for (index, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || (index == 0 && line.starts_with("topic,")) {
+            continue;
+        }
+        let invalid = |reason: &str| anyhow!("line {}: {}", index + 1, reason);
+
+        // The value may contain commas, the topic and offset cannot
+        let (topic, rest) = line
+            .split_once(',')
+            .ok_or_else(|| invalid("missing value"))?;
+        let (value, offset) = rest
+            .rsplit_once(',')
+            .ok_or_else(|| invalid("missing offset"))?;
+        let value = value.trim();
+        let value = match value.strip_prefix('"').and_then(|v| v.strip_suffix('"')) {
+            Some(quoted) => quoted.replace("\"\"", "\""),
+            None => value.to_string(),
+        };
+
+        samples.push(TraceSample {
+            topic: topic.trim().to_string(),
+            value: serde_json::from_str(&value)
+                .map_err(|e| invalid(&format!("invalid value: {}", e)))?,
+            offset_ms: offset
+                .trim()
+                .parse()
+                .map_err(|e| invalid(&format!("invalid offset: {}", e)))?,
+        });
+    }
+    Ok(samples)
+}
+
+/// Hands the samples to `publish` at their offsets
+///
+/// Samples are published `speed_factor` times faster than recorded.
+///
+/// # Arguments
+///
+/// * `samples` - Samples sorted by offset
+/// * `speed_factor` - Replay speed, 1.0 for real time
+/// * `publish` - Publishes one sample on its topic
+///
+/// # Returns
+///
+/// * `Result<usize>` - Number of samples published, or the first error of
+///   `publish`
+pub async fn play<F, Fut>(
+    samples: &[TraceSample],
+    speed_factor: f64,
+    mut publish: F,
+) -> Result<usize>
+where
+    F: FnMut(String, serde_json::Value) -> Fut,
+    Fut: Future<Output = Result<()>>,
+{
+    if !(speed_factor > 0.0 && speed_factor.is_finite()) {
+        return Err(anyhow!("speed factor must be positive, got {}", speed_factor).into());
+    }
+
+    let started = tokio::time::Instant::now();
+    for sample in samples {
+        let offset = Duration::from_millis(sample.offset_ms);
+        tokio::time::sleep_until(started + offset.div_f64(speed_factor)).await;
+        publish(sample.topic.clone(), sample.value.clone()).await?;
+    }
+    Ok(samples.len())
+}
+
+/// Publishes a trace on the DDS bus
+///
+/// # Arguments
+///
+/// * `path` - Trace file
+/// * `speed_factor` - Replay speed, 1.0 for real time
+/// * `domain_id` - DDS domain ID
+///
+/// # Returns
+///
+/// * `Result<usize>` - Number of samples published
+pub async fn publish_trace(
+    path: impl AsRef<Path>,
+    speed_factor: f64,
+    domain_id: i32,
+) -> Result<usize> {
+    let samples = read_trace(path)?;
+    play(&samples, speed_factor, |topic, value| async move {
+        super::publisher::publish_raw(&topic, value, domain_id).await
+    })
+    .await
+}
+
+//Unit Test Cases
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::vehicle::dds::recorder::Recorder;
+    use std::sync::{Arc, Mutex};
+    use std::time::SystemTime;
+
+    fn trace_path(name: &str, contents: &str) -> std::path::PathBuf {
+        let path = std::env::temp_dir().join(format!("filtergateway-trace-{}", name));
+        std::fs::write(&path, contents).unwrap();
+        path
+    }
+
+    const CSV_TRACE: &str = "topic,value,offset_ms
+VehicleSpeed,\"{\"\"speed\"\":79}\",0
+BodyTrunk,{\"status\":\"open\",\"locked\":false},20
+VehicleSpeed,{\"speed\":81},10
+";
+
+    #[test]
+    fn test_read_csv_trace() {
+        let samples = read_trace(trace_path("read.csv", CSV_TRACE)).unwrap();
+
+        let offsets: Vec<u64> = samples.iter().map(|s| s.offset_ms).collect();
+        assert_eq!(offsets, vec![0, 10, 20]);
+        assert_eq!(samples[0].value, serde_json::json!({"speed": 79}));
+        assert_eq!(samples[2].topic, "BodyTrunk");
+        assert_eq!(
+            samples[2].value,
+            serde_json::json!({"status": "open", "locked": false})
+        );
+
+        let err =
+            read_trace(trace_path("bad.csv", "VehicleSpeed,{\"speed\":1},soon\n")).unwrap_err();
+        assert!(err.to_string().starts_with("line 1: invalid offset"));
+    }
+
+    #[test]
+    fn test_read_json_trace_and_recording() {
+        let json = r#"[{"topic": "VehicleSpeed", "value": {"speed": 50}, "offset_ms": 5}]"#;
+        let samples = read_trace(trace_path("read.json", json)).unwrap();
+        assert_eq!(samples.len(), 1);
+        assert_eq!(samples[0].offset_ms, 5);
+
+        let path = std::env::temp_dir().join("filtergateway-trace-recording.jsonl");
+        let _ = std::fs::remove_file(&path);
+        let mut recorder = Recorder::open(&path).unwrap();
+        for (i, speed) in [10, 20].iter().enumerate() {
+            let mut data = DdsData::new("VehicleSpeed", format!("{{\"speed\":{}}}", speed));
+            data.received_at = SystemTime::UNIX_EPOCH + Duration::from_millis(1000 + i as u64 * 40);
+            recorder.record(&data).unwrap();
+        }
+
+        let samples = read_trace(&path).unwrap();
+        assert_eq!(samples[1].offset_ms, 40);
+        assert_eq!(samples[1].value, serde_json::json!({"speed": 20}));
+        assert!(read_trace("trace.txt").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_play_publishes_every_sample() {
+        let samples = read_trace(trace_path("play.csv", CSV_TRACE)).unwrap();
+        let published = Arc::new(Mutex::new(Vec::new()));
+
+        let started = tokio::time::Instant::now();
+        let sink = published.clone();
+        let count = play(&samples, 2.0, |topic, value| {
+            sink.lock().unwrap().push((topic, value));
+            async { Ok(()) }
+        })
+        .await
+        .unwrap();
+
+        assert_eq!(count, 3);
+        let published = published.lock().unwrap();
+        let topics: Vec<&str> = published.iter().map(|(t, _)| t.as_str()).collect();
+        assert_eq!(topics, vec!["VehicleSpeed", "VehicleSpeed", "BodyTrunk"]);
+        assert_eq!(published[1].1, serde_json::json!({"speed": 81}));
+        // The last sample, 20ms into the trace, is published after 10ms at double speed
+        assert!(started.elapsed() >= Duration::from_millis(10));
+    }
+
+    #[tokio::test]
+    async fn test_play_stops_on_publish_error() {
+        let samples = read_trace(trace_path("error.csv", CSV_TRACE)).unwrap();
+        let mut calls = 0;
+
+        let err = play(&samples, 100.0, |topic, _| {
+            calls += 1;
+            async move { Err(anyhow!("no writer for {}", topic).into()) }
+        })
+        .await
+        .unwrap_err();
+
+        assert_eq!(calls, 1);
+        assert_eq!(err.to_string(), "no writer for VehicleSpeed");
+        assert!(play(&samples, 0.0, |_, _| async { Ok(()) }).await.is_err());
+    }
+}
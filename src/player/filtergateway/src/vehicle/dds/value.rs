@@ -0,0 +1,234 @@
+/*
+* SPDX-FileCopyrightText: Copyright 2024 LG Electronics Inc.
+* SPDX-License-Identifier: Apache-2.0
+*/
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::cmp::Ordering;
+use std::fmt;
+
+/// Typed value of a DDS sample field
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum DdsValue {
+    /// IDL `boolean`
+    Bool(bool),
+    /// IDL integer types (`short`, `long`, `long long` and unsigned variants)
+    Int64(i64),
+    /// IDL `float` and `double`
+    Float64(f64),
+    /// IDL `string`, and nested values rendered as JSON
+    Text(String),
+}
+
+impl DdsValue {
+    /// Converts a field of a JSON-serialized sample
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - Field value as produced by `serde_json`
+    ///
+    /// # Returns
+    ///
+    /// The typed value. Unsigned values above `i64::MAX` become `Float64`,
+    /// arrays, structs and nulls become `Text` holding their JSON.
+    pub fn from_json(value: &Value) -> Self {
+        match value {
+            Value::Bool(b) => DdsValue::Bool(*b),
+            Value::Number(n) => match n.as_i64() {
+                Some(i) => DdsValue::Int64(i),
+                None => DdsValue::Float64(n.as_f64().unwrap_or(f64::NAN)),
+            },
+            Value::String(s) => DdsValue::Text(s.clone()),
+            other => DdsValue::Text(other.to_string()),
+        }
+    }
+
+    /// Numeric view of the value, parsing `Text` if needed
+    pub fn as_f64(&self) -> Option<f64> {
+        match self {
+            DdsValue::Bool(_) => None,
+            DdsValue::Int64(i) => Some(*i as f64),
+            DdsValue::Float64(f) => Some(*f),
+            DdsValue::Text(s) => s.trim().parse().ok(),
+        }
+    }
+
+    /// Compares the value with a scenario condition target
+    ///
+    /// # Arguments
+    ///
+    /// * `express` - One of `eq`, `lt`, `le`, `ge`, `gt`
+    /// * `target` - Target value as written in the scenario
+    ///
+    /// # Returns
+    ///
+    /// * `Result<bool, String>` - Whether the condition holds, or why the
+    ///   values cannot be compared
+    pub fn compare(&self, express: &str, target: &str) -> Result<bool, String> {
+        match express {
+            "eq" => self.equals(target),
+            "lt" => Ok(self.order(target)? == Ordering::Less),
+            "le" => Ok(self.order(target)? != Ordering::Greater),
+            "ge" => Ok(self.order(target)? != Ordering::Less),
+            "gt" => Ok(self.order(target)? == Ordering::Greater),
+            _ => Err("wrong expression in condition".to_string()),
+        }
+    }
+
+    fn equals(&self, target: &str) -> Result<bool, String> {
+        let target = target.trim();
+        match self {
+            DdsValue::Bool(b) => target
+                .to_lowercase()
+                .parse::<bool>()
+                .map(|t| *b == t)
+                .map_err(|_| "target_value parse error".to_string()),
+            DdsValue::Int64(_) | DdsValue::Float64(_) => Ok(self.order(target)? == Ordering::Equal),
+            DdsValue::Text(s) => Ok(s.to_lowercase() == target.to_lowercase()),
+        }
+    }
+
+    fn order(&self, target: &str) -> Result<Ordering, String> {
+        let target = target.trim();
+        // Integers compare exactly when the target is an integer too
+        if let (DdsValue::Int64(current), Ok(target)) = (self, target.parse::<i64>()) {
+            return Ok(current.cmp(&target));
+        }
+
+        let target = target
+            .parse::<f64>()
+            .map_err(|_| "target_value parse error".to_string())?;
+        let current = self
+            .as_f64()
+            .ok_or_else(|| "field_value parse error".to_string())?;
+        current
+            .partial_cmp(&target)
+            .ok_or_else(|| "field_value parse error".to_string())
+    }
+}
+
+impl fmt::Display for DdsValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DdsValue::Bool(b) => write!(f, "{}", b),
+            DdsValue::Int64(i) => write!(f, "{}", i),
+            DdsValue::Float64(x) => write!(f, "{}", x),
+            DdsValue::Text(s) => write!(f, "{}", s),
+        }
+    }
+}
+
+impl From<bool> for DdsValue {
+    fn from(value: bool) -> Self {
+        DdsValue::Bool(value)
+    }
+}
+
+impl From<i32> for DdsValue {
+    fn from(value: i32) -> Self {
+        DdsValue::Int64(value.into())
+    }
+}
+
+impl From<i64> for DdsValue {
+    fn from(value: i64) -> Self {
+        DdsValue::Int64(value)
+    }
+}
+
+impl From<f64> for DdsValue {
+    fn from(value: f64) -> Self {
+        DdsValue::Float64(value)
+    }
+}
+
+impl From<&str> for DdsValue {
+    fn from(value: &str) -> Self {
+        DdsValue::Text(value.to_string())
+    }
+}
+
+impl From<String> for DdsValue {
+    fn from(value: String) -> Self {
+        DdsValue::Text(value)
+    }
+}
+
+//Unit Test Cases
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_from_json_keeps_types() {
+        assert_eq!(DdsValue::from_json(&json!(true)), DdsValue::Bool(true));
+        assert_eq!(DdsValue::from_json(&json!(-3)), DdsValue::Int64(-3));
+        assert_eq!(DdsValue::from_json(&json!(2.5)), DdsValue::Float64(2.5));
+        assert_eq!(
+            DdsValue::from_json(&json!(u64::MAX)),
+            DdsValue::Float64(u64::MAX as f64)
+        );
+        assert_eq!(DdsValue::from_json(&json!("on")), DdsValue::from("on"));
+        assert_eq!(DdsValue::from_json(&json!([1, 2])), DdsValue::from("[1,2]"));
+    }
+
+    #[test]
+    fn test_display_reads_like_the_raw_value() {
+        assert_eq!(DdsValue::Bool(false).to_string(), "false");
+        assert_eq!(DdsValue::Int64(42).to_string(), "42");
+        assert_eq!(DdsValue::Float64(0.5).to_string(), "0.5");
+        assert_eq!(DdsValue::from("open").to_string(), "open");
+    }
+
+    #[test]
+    fn test_compare_bool() {
+        assert_eq!(DdsValue::Bool(true).compare("eq", "TRUE"), Ok(true));
+        assert_eq!(DdsValue::Bool(true).compare("eq", "false"), Ok(false));
+        assert!(DdsValue::Bool(true).compare("eq", "on").is_err());
+        assert_eq!(
+            DdsValue::Bool(true).compare("gt", "0"),
+            Err("field_value parse error".to_string())
+        );
+    }
+
+    #[test]
+    fn test_compare_numbers() {
+        let progress = DdsValue::Int64(50);
+        assert_eq!(progress.compare("eq", "50"), Ok(true));
+        assert_eq!(progress.compare("eq", "50.0"), Ok(true));
+        assert_eq!(progress.compare("lt", "51"), Ok(true));
+        assert_eq!(progress.compare("le", "50"), Ok(true));
+        assert_eq!(progress.compare("ge", "50.5"), Ok(false));
+        assert_eq!(progress.compare("gt", "49.9"), Ok(true));
+        assert_eq!(
+            progress.compare("gt", "high"),
+            Err("target_value parse error".to_string())
+        );
+
+        let speed = DdsValue::Float64(12.5);
+        assert_eq!(speed.compare("gt", "12"), Ok(true));
+        assert_eq!(speed.compare("eq", "12.5"), Ok(true));
+    }
+
+    #[test]
+    fn test_compare_large_integers_exactly() {
+        let value = DdsValue::Int64(i64::MAX);
+        assert_eq!(value.compare("gt", &(i64::MAX - 1).to_string()), Ok(true));
+    }
+
+    #[test]
+    fn test_compare_text() {
+        assert_eq!(DdsValue::from("Open").compare("eq", "open"), Ok(true));
+        assert_eq!(DdsValue::from("15").compare("gt", "10"), Ok(true));
+        assert_eq!(
+            DdsValue::from("abc").compare("gt", "10"),
+            Err("field_value parse error".to_string())
+        );
+        assert_eq!(
+            DdsValue::from("abc").compare("ne", "abc"),
+            Err("wrong expression in condition".to_string())
+        );
+    }
+}
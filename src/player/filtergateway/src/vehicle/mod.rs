@@ -4,6 +4,9 @@
 */
 pub mod dds;
 
+#[allow(unused_imports)]
+pub use dds::recorder::replay;
+
 use common::logd;
 use common::Result;
 use dds::DdsData;
@@ -78,6 +81,46 @@ impl VehicleManager {
         Ok(())
     }
 
+    /// Starts listeners for registered vehicle topics
+    ///
+    /// # Arguments
+    ///
+    /// * `topics` - Topic names to enable, or `None` for every registered topic
+    ///
+    /// # Returns
+    ///
+    /// * `Result<Vec<String>>` - Topics enabled by this call, or an error
+    ///   listing the available topics if any name is unknown
+    pub async fn run(&mut self, topics: Option<&[String]>) -> Result<Vec<String>> {
+        self.dds_manager.run(topics).await
+    }
+
+    /// Health of every supervised vehicle topic listener
+    ///
+    /// # Returns
+    ///
+    /// * `Vec<dds::ListenerStatus>` - Listener statuses sorted by topic name
+    pub fn listener_statuses(&self) -> Vec<dds::ListenerStatus> {
+        self.dds_manager.listener_statuses()
+    }
+
+    /// Subscribes to health changes of a topic listener
+    ///
+    /// # Arguments
+    ///
+    /// * `topic_name` - Name of the topic
+    ///
+    /// # Returns
+    ///
+    /// * `Option<watch::Receiver<dds::ListenerStatus>>` - Status channel, or `None`
+    ///   if the topic is not being listened to
+    pub fn subscribe_status(
+        &self,
+        topic_name: &str,
+    ) -> Option<tokio::sync::watch::Receiver<dds::ListenerStatus>> {
+        self.dds_manager.subscribe_status(topic_name)
+    }
+
     /// Get list of available DDS types
     pub fn list_available_types(&self) -> Vec<String> {
         self.dds_manager.list_available_types()
@@ -8,7 +8,6 @@ use filtergateway::vehicle::dds::DdsData;
 use filtergateway::vehicle::VehicleManager;
 use filtergateway::FilterGatewaySender;
 use serde_yaml;
-use std::collections::HashMap;
 use std::sync::Arc;
 use std::time::Duration;
 use tokio::sync::{mpsc, Mutex};
@@ -331,24 +330,11 @@ async fn test_subscribe_and_unsubscribe_vehicle_data() {
     let (_tx, rx) = mpsc::channel(10);
     let manager = FilterGatewayManager::new(rx).await;
 
-    let mut fields = HashMap::new();
-    fields.insert("speed".to_string(), "100".to_string());
-
-    let data = DdsData {
-        name: "test_topic".to_string(),
-        value: "TestType".to_string(),
-        fields,
-    };
+    let data = DdsData::new("test_topic", "TestType").with_field("speed", 100);
 
     assert!(manager.subscribe_vehicle_data(data).await.is_ok());
 
-    let mut fields2 = HashMap::new();
-    fields2.insert("speed".to_string(), "100".to_string());
-    let data2 = DdsData {
-        name: "test_topic".to_string(),
-        value: "TestType".to_string(),
-        fields: fields2,
-    };
+    let data2 = DdsData::new("test_topic", "TestType").with_field("speed", 100);
 
     assert!(manager.unsubscribe_vehicle_data(data2).await.is_ok());
 }
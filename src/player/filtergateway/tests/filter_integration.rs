@@ -6,7 +6,6 @@ use common::spec::artifact::Scenario;
 use filtergateway::filter::Filter;
 use filtergateway::grpc::sender::actioncontroller::FilterGatewaySender;
 use filtergateway::vehicle::dds::DdsData;
-use std::collections::HashMap;
 use tokio;
 
 // Helper to build a scenario from an expression and value
@@ -41,13 +40,7 @@ fn build_scenario_from_yaml(yaml: &str) -> Scenario {
 
 // Build DDS data with topic, field key and value
 fn build_dds_data(topic: &str, key: &str, value: &str) -> DdsData {
-    let mut fields = HashMap::new();
-    fields.insert(key.into(), value.into());
-    DdsData {
-        name: topic.into(),
-        value: value.to_string(),
-        fields,
-    }
+    DdsData::new(topic, value).with_field(key, value)
 }
 // === Expression Tests ===
 
@@ -0,0 +1,207 @@
+/*
+* SPDX-FileCopyrightText: Copyright 2024 LG Electronics Inc.
+* SPDX-License-Identifier: Apache-2.0
+*/
+//! Dependency graph between the models of a package
+//!
+//! A model declares the models it depends on with `dependsOn` in its
+//! package. A model may only run once all its dependencies run, and it is
+//! blocked when one of them fails. The graph is kept acyclic: a package
+//! whose dependencies would form a cycle is rejected as a whole.
+
+use common::spec::artifact::Package;
+use std::collections::{HashMap, HashSet};
+
+/// Dependencies between models, by model name
+#[derive(Debug, Default)]
+pub struct DependencyGraph {
+    /// Models each model depends on
+    dependencies: HashMap<String, Vec<String>>,
+    /// Models depending on each model
+    dependents: HashMap<String, Vec<String>>,
+}
+
+impl DependencyGraph {
+    /// Registers the dependencies of every model of `package`
+    ///
+    /// # Arguments
+    /// * `package` - Package whose models declare `dependsOn`
+    ///
+    /// # Returns
+    /// * `Err` naming the models of the cycle, with the graph unchanged
+    pub fn register_package(&mut self, package: &Package) -> Result<(), String> {
+        let models: Vec<(String, Vec<String>)> = package
+            .get_models()
+            .iter()
+            .map(|m| (m.get_name(), m.get_depends_on().clone()))
+            .collect();
+        self.register(&models)
+    }
+
+    /// Registers `models` with their dependencies, replacing what was
+    /// registered for them before
+    ///
+    /// # Returns
+    /// * `Err` naming the models of the cycle, with the graph unchanged
+    pub fn register(&mut self, models: &[(String, Vec<String>)]) -> Result<(), String> {
+        let mut dependencies = self.dependencies.clone();
+        for (model, depends_on) in models {
+            dependencies.insert(model.clone(), depends_on.clone());
+        }
+        if let Some(cycle) = find_cycle(&dependencies) {
+            return Err(format!("dependency cycle: {}", cycle.join(" -> ")));
+        }
+
+        self.dependencies = dependencies;
+        self.dependents.clear();
+        for (model, depends_on) in &self.dependencies {
+            for dependency in depends_on {
+                self.dependents
+                    .entry(dependency.clone())
+                    .or_default()
+                    .push(model.clone());
+            }
+        }
+        Ok(())
+    }
+
+    /// Whether the dependencies of `model` are registered
+    pub fn contains(&self, model: &str) -> bool {
+        self.dependencies.contains_key(model)
+    }
+
+    /// Models `model` directly depends on
+    pub fn dependencies(&self, model: &str) -> &[String] {
+        self.dependencies
+            .get(model)
+            .map(Vec::as_slice)
+            .unwrap_or_default()
+    }
+
+    /// Models depending on `model`, directly or through other models
+    ///
+    /// # Returns
+    /// * `Vec<String>` - Dependents, each after the models it depends on
+    pub fn dependents(&self, model: &str) -> Vec<String> {
+        // Reverse post-order of a walk along the dependents is topological
+        fn visit(
+            graph: &DependencyGraph,
+            model: &str,
+            seen: &mut HashSet<String>,
+            order: &mut Vec<String>,
+        ) {
+            for dependent in graph.dependents.get(model).into_iter().flatten() {
+                if seen.insert(dependent.clone()) {
+                    visit(graph, dependent, seen, order);
+                    order.push(dependent.clone());
+                }
+            }
+        }
+
+        let mut seen = HashSet::new();
+        let mut order = Vec::new();
+        visit(self, model, &mut seen, &mut order);
+        order.reverse();
+        order
+    }
+}
+
+/// First cycle of `dependencies`, as the models along it
+fn find_cycle(dependencies: &HashMap<String, Vec<String>>) -> Option<Vec<String>> {
+    // Models on the current path, and models known to lead to no cycle
+    let mut path: Vec<String> = Vec::new();
+    let mut done: HashSet<String> = HashSet::new();
+
+    fn visit(
+        model: &str,
+        dependencies: &HashMap<String, Vec<String>>,
+        path: &mut Vec<String>,
+        done: &mut HashSet<String>,
+    ) -> Option<Vec<String>> {
+        if let Some(start) = path.iter().position(|m| m == model) {
+            let mut cycle = path[start..].to_vec();
+            cycle.push(model.to_string());
+            return Some(cycle);
+        }
+        if done.contains(model) {
+            return None;
+        }
+        path.push(model.to_string());
+        for dependency in dependencies.get(model).into_iter().flatten() {
+            if let Some(cycle) = visit(dependency, dependencies, path, done) {
+                return Some(cycle);
+            }
+        }
+        path.pop();
+        done.insert(model.to_string());
+        None
+    }
+
+    let mut models: Vec<&String> = dependencies.keys().collect();
+    models.sort();
+    models
+        .into_iter()
+        .find_map(|model| visit(model, dependencies, &mut path, &mut done))
+}
+
+//Unit Test Cases
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn model(name: &str, depends_on: &[&str]) -> (String, Vec<String>) {
+        (
+            name.to_string(),
+            depends_on.iter().map(|d| d.to_string()).collect(),
+        )
+    }
+
+    #[test]
+    fn test_chain_dependents_in_order() {
+        let mut graph = DependencyGraph::default();
+        graph
+            .register(&[model("a", &[]), model("b", &["a"]), model("c", &["b"])])
+            .unwrap();
+
+        assert_eq!(graph.dependencies("c"), ["b".to_string()]);
+        assert_eq!(graph.dependents("a"), vec!["b", "c"]);
+        assert_eq!(graph.dependents("b"), vec!["c"]);
+        assert!(graph.dependents("c").is_empty());
+    }
+
+    #[test]
+    fn test_diamond_dependents_listed_once() {
+        let mut graph = DependencyGraph::default();
+        graph
+            .register(&[
+                model("base", &[]),
+                model("left", &["base"]),
+                model("right", &["base"]),
+                model("top", &["left", "right"]),
+            ])
+            .unwrap();
+
+        let mut dependents = graph.dependents("base");
+        assert_eq!(dependents.pop().as_deref(), Some("top"));
+        dependents.sort();
+        assert_eq!(dependents, vec!["left", "right"]);
+        assert_eq!(graph.dependents("left"), vec!["top"]);
+    }
+
+    #[test]
+    fn test_cycle_rejected_and_graph_unchanged() {
+        let mut graph = DependencyGraph::default();
+        graph
+            .register(&[model("a", &[]), model("b", &["a"])])
+            .unwrap();
+
+        let err = graph
+            .register(&[model("a", &["c"]), model("c", &["b"])])
+            .unwrap_err();
+
+        assert_eq!(err, "dependency cycle: a -> c -> b -> a");
+        assert!(graph.dependencies("a").is_empty());
+        assert!(!graph.contains("c"));
+        assert!(graph.register(&[model("self", &["self"])]).is_err());
+    }
+}
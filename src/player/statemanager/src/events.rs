@@ -0,0 +1,288 @@
+/*
+ * SPDX-FileCopyrightText: Copyright 2024 LG Electronics Inc.
+ * SPDX-License-Identifier: Apache-2.0
+ */
+
+//! State transition events published on DDS
+//!
+//! ECU applications on the DDS bus follow the states Pullpiri commits through
+//! the `PiccoloStateEvent` topic. Publishing is disabled by default. When it
+//! is enabled, events are queued to a background writer and dropped when it
+//! falls behind, so the DDS stack never delays or fails a transition.
+
+use common::logd;
+use common::setting::StateManagerSettings;
+use common::statemanager::{ModelState, PackageState, ResourceType, ScenarioState};
+use dust_dds::{
+    domain::domain_participant_factory::DomainParticipantFactory,
+    infrastructure::{
+        qos::{DataWriterQos, QosKind},
+        qos_policy::{
+            HistoryQosPolicy, HistoryQosPolicyKind, ReliabilityQosPolicy, ReliabilityQosPolicyKind,
+        },
+        status::NO_STATUS,
+        time::{Duration as DdsDuration, DurationKind},
+    },
+    publication::data_writer::DataWriter,
+};
+use dust_dds_derive::DdsType;
+use tokio::sync::mpsc::{self, error::TrySendError};
+use tokio::time::{self, Duration};
+
+/// Events queued for the writer, newer events are dropped beyond it
+const QUEUE_SIZE: usize = 256;
+/// How long the writer waits for a reader before its first event
+const MATCH_TIMEOUT: Duration = Duration::from_secs(10);
+/// Interval between matched-reader checks
+const MATCH_POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Committed state transition of a resource
+#[derive(Debug, Clone, Default, PartialEq, DdsType)]
+pub struct PiccoloStateEvent {
+    /// Type of the resource, e.g. `RESOURCE_TYPE_MODEL`
+    pub resource_type: String,
+    /// Name of the resource
+    pub resource: String,
+    /// State before the transition, e.g. `MODEL_STATE_CREATED`
+    pub from: String,
+    /// State after the transition, e.g. `MODEL_STATE_RUNNING`
+    pub to: String,
+    /// Why the transition happened
+    pub reason: String,
+    /// Time of the transition, in nanoseconds since the epoch
+    pub timestamp_ns: i64,
+}
+
+impl PiccoloStateEvent {
+    /// Event of a transition happening now
+    pub fn new(
+        resource_type: ResourceType,
+        resource: &str,
+        from: &str,
+        to: &str,
+        reason: &str,
+    ) -> Self {
+        Self {
+            resource_type: resource_type.as_str_name().to_string(),
+            resource: resource.to_string(),
+            from: from.to_string(),
+            to: to.to_string(),
+            reason: reason.to_string(),
+            timestamp_ns: chrono::Utc::now().timestamp_nanos_opt().unwrap_or_default(),
+        }
+    }
+}
+
+/// Name of a state of `resource_type`, e.g. `SCENARIO_STATE_ALLOWED`
+pub fn state_name(resource_type: ResourceType, state: i32) -> &'static str {
+    match resource_type {
+        ResourceType::Scenario => ScenarioState::try_from(state).map(|s| s.as_str_name()),
+        ResourceType::Package => PackageState::try_from(state).map(|s| s.as_str_name()),
+        ResourceType::Model => ModelState::try_from(state).map(|s| s.as_str_name()),
+        _ => Ok("UNKNOWN"),
+    }
+    .unwrap_or("UNKNOWN")
+}
+
+/// Publisher of state transition events, doing nothing when disabled
+#[derive(Clone, Default)]
+pub struct StateEventPublisher {
+    /// Queue of the writer task, `None` when disabled
+    tx: Option<mpsc::Sender<PiccoloStateEvent>>,
+}
+
+impl StateEventPublisher {
+    /// Publisher dropping every event
+    pub fn disabled() -> Self {
+        Self::default()
+    }
+
+    /// Publisher set up by the `statemanager` and `dds` settings
+    pub fn from_settings() -> Self {
+        let config = common::setting::get_config();
+        if !config.statemanager.dds_events {
+            return Self::disabled();
+        }
+        Self::start(&config.statemanager, config.dds.domain_id)
+    }
+
+    /// Starts a writer task publishing on the topic of `settings`
+    ///
+    /// # Arguments
+    ///
+    /// * `settings` - Topic and QoS of the events
+    /// * `domain_id` - DDS domain ID
+    pub fn start(settings: &StateManagerSettings, domain_id: i32) -> Self {
+        let (tx, rx) = mpsc::channel(QUEUE_SIZE);
+        let topic_name = settings.dds_events_topic.clone();
+        let qos = writer_qos(settings);
+        tokio::spawn(async move {
+            if let Err(e) = writer_loop(&topic_name, domain_id, qos, rx).await {
+                logd!(4, "State events not published on '{}': {}", topic_name, e);
+            }
+        });
+        Self { tx: Some(tx) }
+    }
+
+    /// Whether events are published
+    pub fn is_enabled(&self) -> bool {
+        self.tx.is_some()
+    }
+
+    /// Queues an event for publishing, without waiting
+    ///
+    /// The event is dropped if the writer is behind or has stopped.
+    pub fn publish(&self, event: PiccoloStateEvent) {
+        let Some(tx) = &self.tx else {
+            return;
+        };
+        match tx.try_send(event) {
+            Ok(()) => {}
+            Err(TrySendError::Full(event)) => {
+                logd!(
+                    4,
+                    "State event queue full, dropped event of {}",
+                    event.resource
+                );
+            }
+            Err(TrySendError::Closed(event)) => {
+                logd!(
+                    2,
+                    "State event writer stopped, dropped event of {}",
+                    event.resource
+                );
+            }
+        }
+    }
+}
+
+/// QoS of the event writer
+fn writer_qos(settings: &StateManagerSettings) -> DataWriterQos {
+    let kind = if settings.dds_events_reliable {
+        ReliabilityQosPolicyKind::Reliable
+    } else {
+        ReliabilityQosPolicyKind::BestEffort
+    };
+    DataWriterQos {
+        reliability: ReliabilityQosPolicy {
+            kind,
+            max_blocking_time: DurationKind::Finite(DdsDuration::new(0, 100_000_000)),
+        },
+        history: HistoryQosPolicy {
+            kind: HistoryQosPolicyKind::KeepLast(settings.dds_events_history_depth.max(1)),
+        },
+        ..Default::default()
+    }
+}
+
+/// Writes every queued event until the queue is closed
+async fn writer_loop(
+    topic_name: &str,
+    domain_id: i32,
+    qos: DataWriterQos,
+    mut rx: mpsc::Receiver<PiccoloStateEvent>,
+) -> Result<(), String> {
+    let writer = create_writer(topic_name, domain_id, qos)?;
+    let mut waited = false;
+
+    while let Some(event) = rx.recv().await {
+        if !waited {
+            wait_for_reader(&writer).await;
+            waited = true;
+        }
+        if let Err(e) = writer.write(&event, None) {
+            logd!(
+                4,
+                "Failed to publish state event of {}: {:?}",
+                event.resource,
+                e
+            );
+        }
+    }
+    Ok(())
+}
+
+/// Creates the data writer of the event topic
+fn create_writer(
+    topic_name: &str,
+    domain_id: i32,
+    qos: DataWriterQos,
+) -> Result<DataWriter<PiccoloStateEvent>, String> {
+    let participant = DomainParticipantFactory::get_instance()
+        .create_participant(domain_id, QosKind::Default, None, NO_STATUS)
+        .map_err(|e| format!("Failed to create domain participant: {:?}", e))?;
+    let publisher = participant
+        .create_publisher(QosKind::Default, None, NO_STATUS)
+        .map_err(|e| format!("Failed to create publisher: {:?}", e))?;
+    let topic = participant
+        .create_topic::<PiccoloStateEvent>(
+            topic_name,
+            "PiccoloStateEvent",
+            QosKind::Default,
+            None,
+            NO_STATUS,
+        )
+        .map_err(|e| format!("Failed to create topic: {:?}", e))?;
+    publisher
+        .create_datawriter::<PiccoloStateEvent>(&topic, QosKind::Specific(qos), None, NO_STATUS)
+        .map_err(|e| format!("Failed to create data writer: {:?}", e))
+}
+
+/// Waits until discovery matches a reader, so the first events are not lost
+async fn wait_for_reader(writer: &DataWriter<PiccoloStateEvent>) {
+    let deadline = time::Instant::now() + MATCH_TIMEOUT;
+    while time::Instant::now() < deadline {
+        if writer
+            .get_matched_subscriptions()
+            .is_ok_and(|readers| !readers.is_empty())
+        {
+            return;
+        }
+        time::sleep(MATCH_POLL_INTERVAL).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_state_name() {
+        assert_eq!(
+            state_name(ResourceType::Scenario, ScenarioState::Allowed as i32),
+            "SCENARIO_STATE_ALLOWED"
+        );
+        assert_eq!(
+            state_name(ResourceType::Model, ModelState::Running as i32),
+            "MODEL_STATE_RUNNING"
+        );
+        assert_eq!(state_name(ResourceType::Package, 999), "UNKNOWN");
+        assert_eq!(state_name(ResourceType::Node, 1), "UNKNOWN");
+    }
+
+    #[test]
+    fn test_disabled_publisher_drops_events() {
+        let events = StateEventPublisher::disabled();
+
+        assert!(!events.is_enabled());
+        events.publish(PiccoloStateEvent::default());
+    }
+
+    #[tokio::test]
+    async fn test_publish_does_not_wait_for_writer() {
+        // A queue nobody reads, as if the writer were stuck
+        let (tx, _rx) = mpsc::channel(1);
+        let events = StateEventPublisher { tx: Some(tx) };
+
+        let started = std::time::Instant::now();
+        for _ in 0..10 {
+            events.publish(PiccoloStateEvent::default());
+        }
+        assert!(started.elapsed() < Duration::from_secs(1));
+
+        // A closed queue drops events too
+        let (tx, rx) = mpsc::channel(1);
+        drop(rx);
+        StateEventPublisher { tx: Some(tx) }.publish(PiccoloStateEvent::default());
+    }
+}
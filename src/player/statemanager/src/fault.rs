@@ -0,0 +1,394 @@
+/*
+ * SPDX-FileCopyrightText: Copyright 2024 LG Electronics Inc.
+ * SPDX-License-Identifier: Apache-2.0
+ */
+
+//! Fault injection through the Timpani FaultService
+//!
+//! A `FaultInfo` with a non-zero `duration_ms` injects a fault: the model
+//! named by `workload_id` is forced into the state matching the fault type
+//! and put back into its previous state once the duration expires. Both
+//! transitions are recorded with the `fault-injected` source and sent to
+//! the engine as `StateChange` events, which it propagates like any other
+//! model state change.
+//!
+//! Models of a scenario with an ASIL level above QM are protected unless
+//! the request sets `override_asil`.
+
+use crate::state_machine::{StateMachine, FAULT_INJECTED_SOURCE};
+use common::external::timpani::{FaultInfo, FaultType};
+use common::logd;
+use common::spec::artifact::policy::{AsilLevel, ASIL_ANNOTATION};
+use common::spec::artifact::Scenario;
+use common::statemanager::{ModelState, ResourceType, StateChange};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::mpsc::Sender;
+use tokio::sync::Mutex;
+use tonic::Status;
+
+/// Applies injected faults to the state machine and reverts them
+#[derive(Clone)]
+pub struct FaultInjector {
+    state_machine: Arc<Mutex<StateMachine>>,
+    /// Engine the forced transitions are sent to
+    events: Option<Sender<StateChange>>,
+}
+
+impl FaultInjector {
+    pub fn new(state_machine: Arc<Mutex<StateMachine>>) -> Self {
+        Self {
+            state_machine,
+            events: None,
+        }
+    }
+
+    /// Send the forced transitions to the engine through `events`
+    pub fn with_events(mut self, events: Sender<StateChange>) -> Self {
+        self.events = Some(events);
+        self
+    }
+
+    /// Inject the fault described by `info`
+    ///
+    /// # Arguments
+    /// * `info` - Fault to inject; `duration_ms` must be non-zero
+    /// * `asil_level` - ASIL level of the targeted model, `None` if untagged
+    ///
+    /// # Returns
+    /// * `Result<String, Status>` - ID of the forced transition, or
+    ///   `InvalidArgument` for a malformed request and `FailedPrecondition`
+    ///   when the ASIL guard rejects it
+    pub async fn inject(
+        &self,
+        info: &FaultInfo,
+        asil_level: Option<AsilLevel>,
+    ) -> Result<String, Status> {
+        let model = info.workload_id.trim();
+        if model.is_empty() {
+            return Err(Status::invalid_argument("workload_id is required"));
+        }
+        if info.duration_ms == 0 {
+            return Err(Status::invalid_argument("duration_ms must be positive"));
+        }
+        let fault_state = fault_state(info.r#type())
+            .ok_or_else(|| Status::invalid_argument("unsupported fault type"))?;
+        check_asil_guard(asil_level, info.override_asil).map_err(Status::failed_precondition)?;
+
+        let transition_id = format!("fault_{}_{}", model, now_ns());
+        let (result, change) = {
+            let mut state_machine = self.state_machine.lock().await;
+            let result = state_machine.inject_fault(
+                ResourceType::Model,
+                model,
+                fault_state as i32,
+                &transition_id,
+            );
+            (result, forced_change(&state_machine, model))
+        };
+        logd!(
+            4,
+            "Injected {:?} fault for {} ms: {}",
+            info.r#type(),
+            info.duration_ms,
+            result.message
+        );
+        self.publish(change).await;
+
+        let injector = self.clone();
+        let model = model.to_string();
+        let duration = Duration::from_millis(info.duration_ms);
+        tokio::spawn(async move {
+            tokio::time::sleep(duration).await;
+            injector.revert(&model).await;
+        });
+
+        Ok(transition_id)
+    }
+
+    /// Put `model` back into the state it had before its fault
+    async fn revert(&self, model: &str) {
+        let transition_id = format!("fault_revert_{}_{}", model, now_ns());
+        let reverted = {
+            let mut state_machine = self.state_machine.lock().await;
+            state_machine
+                .revert_fault(ResourceType::Model, model, &transition_id)
+                .map(|result| (result, forced_change(&state_machine, model)))
+        };
+        if let Some((result, change)) = reverted {
+            logd!(3, "Reverted injected fault: {}", result.message);
+            self.publish(change).await;
+        }
+    }
+
+    /// Send a forced transition to the engine
+    async fn publish(&self, change: Option<StateChange>) {
+        let (Some(events), Some(change)) = (&self.events, change) else {
+            return;
+        };
+        if events.send(change).await.is_err() {
+            logd!(
+                4,
+                "Failed to send {} state change: engine stopped",
+                FAULT_INJECTED_SOURCE
+            );
+        }
+    }
+}
+
+/// State change of the transition just forced on `model`
+fn forced_change(state_machine: &StateMachine, model: &str) -> Option<StateChange> {
+    let history = state_machine.transition_history(ResourceType::Model, model);
+    let record = history.last()?;
+    Some(StateChange {
+        resource_type: ResourceType::Model as i32,
+        resource_name: model.to_string(),
+        current_state: state_machine.state_enum_to_str(record.from_state, ResourceType::Model),
+        target_state: state_machine.state_enum_to_str(record.to_state, ResourceType::Model),
+        transition_id: record.transition_id.clone(),
+        timestamp_ns: record.timestamp_ns,
+        source: record.source.clone(),
+        trace_id: record.trace_id.clone(),
+    })
+}
+
+/// Model state a fault of type `fault_type` forces
+fn fault_state(fault_type: FaultType) -> Option<ModelState> {
+    match fault_type {
+        FaultType::Dmiss => Some(ModelState::Dead),
+        FaultType::Unknown => None,
+    }
+}
+
+/// Reject faults on ASIL-tagged models unless `override_asil` is set
+fn check_asil_guard(asil_level: Option<AsilLevel>, override_asil: bool) -> Result<(), String> {
+    match asil_level {
+        Some(level) if level > AsilLevel::QM && !override_asil => Err(format!(
+            "target is ASIL {:?}; set override_asil to inject faults",
+            level
+        )),
+        _ => Ok(()),
+    }
+}
+
+/// Highest ASIL level among the scenarios deploying `model`
+///
+/// # Returns
+/// * `Result<Option<AsilLevel>, String>` - `None` if no scenario deploying
+///   the model carries the ASIL annotation, or an error if etcd cannot be read
+pub async fn resolve_asil_level(model: &str) -> Result<Option<AsilLevel>, String> {
+    let packages = StateMachine::find_packages_containing_model(model).await?;
+    let scenarios = common::etcd::get_all_with_prefix(common::keys::SCENARIO_PREFIX)
+        .await
+        .map_err(|e| format!("Failed to get scenarios from ETCD: {:?}", e))?;
+
+    Ok(scenarios
+        .iter()
+        .filter_map(|(_, yaml)| serde_yaml::from_str::<Scenario>(yaml).ok())
+        .filter(|scenario| packages.contains(&scenario.get_targets()))
+        .filter_map(|scenario| scenario.get_annotation(ASIL_ANNOTATION)?.parse().ok())
+        .max())
+}
+
+fn now_ns() -> i64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_nanos() as i64
+}
+
+//Unit Test Cases
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use common::monitoringserver::ContainerInfo;
+    use std::collections::HashMap;
+
+    fn injector() -> FaultInjector {
+        FaultInjector::new(Arc::new(Mutex::new(StateMachine::new())))
+    }
+
+    fn fault(model: &str, duration_ms: u64, override_asil: bool) -> FaultInfo {
+        FaultInfo {
+            workload_id: model.to_string(),
+            node_id: "HPC".to_string(),
+            task_name: "task".to_string(),
+            r#type: FaultType::Dmiss.into(),
+            duration_ms,
+            override_asil,
+        }
+    }
+
+    fn running_container(model: &str) -> ContainerInfo {
+        let mut state = HashMap::new();
+        state.insert("Status".to_string(), "running".to_string());
+        state.insert("Running".to_string(), "true".to_string());
+        ContainerInfo {
+            id: "c1".to_string(),
+            names: vec![model.to_string()],
+            state,
+            ..Default::default()
+        }
+    }
+
+    async fn model_state(injector: &FaultInjector, model: &str) -> Option<i32> {
+        injector
+            .state_machine
+            .lock()
+            .await
+            .get_resource_state(model, ResourceType::Model)
+            .map(|rs| rs.current_state)
+    }
+
+    #[tokio::test]
+    async fn test_inject_forces_dead_and_records_source() {
+        let injector = injector();
+        let container = running_container("bms");
+        injector
+            .state_machine
+            .lock()
+            .await
+            .process_model_state_update("bms", &[&container]);
+
+        let transition_id = injector
+            .inject(&fault("bms", 60_000, false), None)
+            .await
+            .unwrap();
+
+        assert_eq!(
+            model_state(&injector, "bms").await,
+            Some(ModelState::Dead as i32)
+        );
+        let state_machine = injector.state_machine.lock().await;
+        let history = state_machine.transition_history(ResourceType::Model, "bms");
+        let last = history.last().unwrap();
+        assert_eq!(last.transition_id, transition_id);
+        assert_eq!(last.source, FAULT_INJECTED_SOURCE);
+        assert_eq!(last.from_state, ModelState::Running as i32);
+        assert_eq!(last.to_state, ModelState::Dead as i32);
+    }
+
+    #[tokio::test]
+    async fn test_fault_holds_state_against_container_reports() {
+        let injector = injector();
+        injector
+            .inject(&fault("bms", 60_000, false), None)
+            .await
+            .unwrap();
+
+        let container = running_container("bms");
+        let result = injector
+            .state_machine
+            .lock()
+            .await
+            .process_model_state_update("bms", &[&container]);
+
+        assert!(result.actions_to_execute.is_empty());
+        assert_eq!(
+            model_state(&injector, "bms").await,
+            Some(ModelState::Dead as i32)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_fault_reverts_after_duration() {
+        let injector = injector();
+        let container = running_container("bms");
+        injector
+            .state_machine
+            .lock()
+            .await
+            .process_model_state_update("bms", &[&container]);
+
+        injector
+            .inject(&fault("bms", 50, false), None)
+            .await
+            .unwrap();
+        tokio::time::sleep(Duration::from_millis(300)).await;
+
+        assert_eq!(
+            model_state(&injector, "bms").await,
+            Some(ModelState::Running as i32)
+        );
+        let state_machine = injector.state_machine.lock().await;
+        assert!(!state_machine.has_injected_fault(ResourceType::Model, "bms"));
+        let sources: Vec<(&str, i32)> = state_machine
+            .transition_history(ResourceType::Model, "bms")
+            .iter()
+            .map(|r| (r.source.as_str(), r.to_state))
+            .collect();
+        assert_eq!(
+            sources,
+            vec![
+                ("container_analysis", ModelState::Running as i32),
+                (FAULT_INJECTED_SOURCE, ModelState::Dead as i32),
+                (FAULT_INJECTED_SOURCE, ModelState::Running as i32),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_forced_transitions_are_sent_to_engine() {
+        let (tx, mut rx) = tokio::sync::mpsc::channel(4);
+        let injector = injector().with_events(tx);
+
+        let transition_id = injector
+            .inject(&fault("bms", 50, false), None)
+            .await
+            .unwrap();
+
+        let injected = rx.recv().await.unwrap();
+        assert_eq!(injected.resource_type, ResourceType::Model as i32);
+        assert_eq!(injected.resource_name, "bms");
+        assert_eq!(injected.current_state, "RUNNING");
+        assert_eq!(injected.target_state, "DEAD");
+        assert_eq!(injected.transition_id, transition_id);
+        assert_eq!(injected.source, FAULT_INJECTED_SOURCE);
+
+        let reverted = rx.recv().await.unwrap();
+        assert_eq!(reverted.current_state, "DEAD");
+        assert_eq!(reverted.target_state, "RUNNING");
+        assert!(reverted.transition_id.starts_with("fault_revert_bms_"));
+    }
+
+    #[tokio::test]
+    async fn test_asil_guard() {
+        let injector = injector();
+
+        let rejected = injector
+            .inject(&fault("brake", 60_000, false), Some(AsilLevel::B))
+            .await
+            .unwrap_err();
+        assert_eq!(rejected.code(), tonic::Code::FailedPrecondition);
+        assert_eq!(model_state(&injector, "brake").await, None);
+
+        // QM is not a safety level
+        assert!(injector
+            .inject(&fault("radio", 60_000, false), Some(AsilLevel::QM))
+            .await
+            .is_ok());
+
+        // The override lets the fault through
+        assert!(injector
+            .inject(&fault("brake", 60_000, true), Some(AsilLevel::D))
+            .await
+            .is_ok());
+        assert_eq!(
+            model_state(&injector, "brake").await,
+            Some(ModelState::Dead as i32)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_inject_rejects_invalid_requests() {
+        let injector = injector();
+
+        let mut unknown = fault("bms", 1000, false);
+        unknown.r#type = FaultType::Unknown.into();
+        for info in [fault("", 1000, false), fault("bms", 0, false), unknown] {
+            let err = injector.inject(&info, None).await.unwrap_err();
+            assert_eq!(err.code(), tonic::Code::InvalidArgument);
+        }
+        assert_eq!(model_state(&injector, "bms").await, None);
+    }
+}
@@ -17,6 +17,8 @@ use common::logd;
 use common::monitoringserver::{ContainerList, SendContainerListResponse};
 use common::statemanager::{
     state_manager_connection_server::StateManagerConnection,
+    Ack,
+    AckResponse,
     Action,
     ErrorCode,
     // // State Query API message types
@@ -62,6 +64,10 @@ pub struct StateManagerReceiver {
     /// Channel sender for StateChange messages from various components.
     /// Used to forward state transition requests to the StateManager's state machine engine.
     pub tx_state_change: mpsc::Sender<StateChange>,
+
+    /// Channel sender for Ack messages from ActionController.
+    /// Used to forward the outcome of reconcile requests to the StateManager's request tracking.
+    pub tx_ack: mpsc::Sender<Ack>,
 }
 
 #[tonic::async_trait]
@@ -169,8 +175,12 @@ impl StateManagerConnection for StateManagerReceiver {
         &self,
         request: Request<StateChange>,
     ) -> Result<tonic::Response<StateChangeResponse>, Status> {
-        let req = request.into_inner();
+        let mut req = request.into_inner();
         let transition_id = req.transition_id.clone();
+        // Keep the trace of the request once the change is queued
+        if req.trace_id.is_empty() {
+            req.trace_id = common::trace::current().unwrap_or_default();
+        }
 
         // 🔍 COMMENT 5: StateManager receiving scenario state change requests
         // This method receives state change requests from multiple components:
@@ -242,6 +252,52 @@ impl StateManagerConnection for StateManagerReceiver {
             }
         }
     }
+
+    /// Handles Ack messages from ActionController.
+    ///
+    /// Forwards the outcome of a reconcile request to the StateManager, which
+    /// stops tracking the request. Acks of unknown or already acknowledged
+    /// requests are accepted as well and ignored by the StateManager.
+    ///
+    /// # Arguments
+    /// * `request` - gRPC request containing an Ack message
+    ///
+    /// # Returns
+    /// * `Result<tonic::Response<AckResponse>, Status>` - Whether the ack was queued
+    async fn send_ack(
+        &self,
+        request: Request<Ack>,
+    ) -> Result<tonic::Response<AckResponse>, Status> {
+        let req = request.into_inner();
+        if req.transaction_id.trim().is_empty() {
+            return Ok(tonic::Response::new(AckResponse {
+                accepted: false,
+                message: "transaction_id cannot be empty".to_string(),
+            }));
+        }
+
+        logd!(
+            1,
+            "Ack received: ID: {}, Success: {}, Source: {}",
+            req.transaction_id,
+            req.success,
+            req.source
+        );
+
+        match self.tx_ack.send(req).await {
+            Ok(_) => Ok(tonic::Response::new(AckResponse {
+                accepted: true,
+                message: "Ack successfully received and queued for processing".to_string(),
+            })),
+            Err(e) => {
+                logd!(5, "Failed to forward Ack to StateManager: {e}");
+                Ok(tonic::Response::new(AckResponse {
+                    accepted: false,
+                    message: format!("Cannot forward Ack to StateManager: {e}"),
+                }))
+            }
+        }
+    }
 }
 
 impl StateManagerReceiver {
@@ -327,6 +383,11 @@ mod tests {
     use common::statemanager::{ErrorCode, ResourceType, StateChange};
     use tonic::Request;
 
+    /// Ack sender for tests that do not exercise acknowledgements
+    fn ack_sender() -> mpsc::Sender<Ack> {
+        mpsc::channel::<Ack>(1).0
+    }
+
     #[test]
     fn test_validate_state_change_and_resource_type_to_string() {
         let (tx, _rx) = mpsc::channel::<ContainerList>(1);
@@ -334,6 +395,7 @@ mod tests {
         let receiver = StateManagerReceiver {
             tx,
             tx_state_change,
+            tx_ack: ack_sender(),
         };
 
         // Valid state change
@@ -345,6 +407,7 @@ mod tests {
             transition_id: "t1".to_string(),
             timestamp_ns: 1,
             source: "unittest".to_string(),
+            trace_id: String::new(),
         };
         assert!(receiver.validate_state_change(&sc).is_ok());
 
@@ -374,6 +437,7 @@ mod tests {
         let receiver = StateManagerReceiver {
             tx: tx.clone(),
             tx_state_change: tx_state_change.clone(),
+            tx_ack: ack_sender(),
         };
 
         let cl = ContainerList {
@@ -389,6 +453,7 @@ mod tests {
         let receiver2 = StateManagerReceiver {
             tx: bad_tx,
             tx_state_change: tx_state_change.clone(),
+            tx_ack: ack_sender(),
         };
         let cl2 = ContainerList {
             node_name: "n2".to_string(),
@@ -407,6 +472,7 @@ mod tests {
         let receiver = StateManagerReceiver {
             tx: tx.clone(),
             tx_state_change: tx_state_change.clone(),
+            tx_ack: ack_sender(),
         };
 
         let cl = ContainerList {
@@ -426,6 +492,7 @@ mod tests {
         let receiver2 = StateManagerReceiver {
             tx: bad_tx,
             tx_state_change,
+            tx_ack: ack_sender(),
         };
         let cl2 = ContainerList {
             node_name: "n2".to_string(),
@@ -450,6 +517,7 @@ mod tests {
         let receiver = StateManagerReceiver {
             tx: tx.clone(),
             tx_state_change: tx_state_change.clone(),
+            tx_ack: ack_sender(),
         };
 
         let sc = StateChange {
@@ -460,6 +528,7 @@ mod tests {
             transition_id: "t2".to_string(),
             timestamp_ns: 1,
             source: "unittest".to_string(),
+            trace_id: String::new(),
         };
 
         let resp = receiver.send_state_change(Request::new(sc.clone())).await;
@@ -477,6 +546,7 @@ mod tests {
         let receiver2 = StateManagerReceiver {
             tx: tx.clone(),
             tx_state_change: bad_tx,
+            tx_ack: ack_sender(),
         };
 
         let sc2 = StateChange {
@@ -498,6 +568,7 @@ mod tests {
         let receiver = StateManagerReceiver {
             tx,
             tx_state_change,
+            tx_ack: ack_sender(),
         };
 
         let action = common::statemanager::Action {
@@ -518,6 +589,7 @@ mod tests {
         let receiver = StateManagerReceiver {
             tx,
             tx_state_change,
+            tx_ack: ack_sender(),
         };
 
         // Build an invalid StateChange (timestamp_ns <= 0)
@@ -529,6 +601,7 @@ mod tests {
             transition_id: "bad-tid".to_string(),
             timestamp_ns: 0,
             source: "unittest".to_string(),
+            trace_id: String::new(),
         };
 
         let resp = receiver.send_state_change(Request::new(sc)).await;
@@ -544,6 +617,7 @@ mod tests {
         let receiver = StateManagerReceiver {
             tx,
             tx_state_change,
+            tx_ack: ack_sender(),
         };
 
         let sc = StateChange {
@@ -554,6 +628,7 @@ mod tests {
             transition_id: "tid-invalid".to_string(),
             timestamp_ns: 1,
             source: "unittest".to_string(),
+            trace_id: String::new(),
         };
 
         let resp = receiver.send_state_change(Request::new(sc)).await;
@@ -569,6 +644,7 @@ mod tests {
         let receiver = StateManagerReceiver {
             tx,
             tx_state_change,
+            tx_ack: ack_sender(),
         };
 
         assert_eq!(
@@ -597,6 +673,48 @@ mod tests {
         );
         assert_eq!(receiver.resource_type_to_string(9999), "Unknown");
     }
+
+    #[tokio::test]
+    async fn test_send_ack_forwards_every_ack() {
+        let (tx, _rx) = mpsc::channel::<ContainerList>(1);
+        let (tx_state_change, _rx2) = mpsc::channel::<StateChange>(1);
+        let (tx_ack, mut rx_ack) = mpsc::channel::<Ack>(2);
+        let receiver = StateManagerReceiver {
+            tx,
+            tx_state_change,
+            tx_ack,
+        };
+
+        let ack = Ack {
+            transaction_id: "reconcile_s1_1".to_string(),
+            success: true,
+            details: "done".to_string(),
+            source: "actioncontroller".to_string(),
+        };
+        // Duplicates are queued as well; the manager ignores them
+        for _ in 0..2 {
+            let resp = receiver
+                .send_ack(Request::new(ack.clone()))
+                .await
+                .unwrap()
+                .into_inner();
+            assert!(resp.accepted);
+        }
+        assert_eq!(rx_ack.recv().await.unwrap(), ack);
+        assert_eq!(rx_ack.recv().await.unwrap(), ack);
+
+        let empty = Ack {
+            transaction_id: " ".to_string(),
+            ..ack
+        };
+        let resp = receiver
+            .send_ack(Request::new(empty))
+            .await
+            .unwrap()
+            .into_inner();
+        assert!(!resp.accepted);
+        assert!(rx_ack.try_recv().is_err());
+    }
 }
 
 // ========================================
@@ -2,12 +2,27 @@
 * SPDX-FileCopyrightText: Copyright 2024 LG Electronics Inc.
 * SPDX-License-Identifier: Apache-2.0
 */
+use crate::fault::{resolve_asil_level, FaultInjector};
 use common::external::timpani::fault_service_server::FaultService;
 use common::external::timpani::{FaultInfo, Response as TimpaniResponse};
 use tonic::{Request, Response, Status};
 
+/// Handler of the Timpani FaultService
+///
+/// Faults with a `duration_ms` are injected through the `FaultInjector`;
+/// other faults are only logged.
 #[derive(Default)]
-pub struct TimpaniReceiver {}
+pub struct TimpaniReceiver {
+    injector: Option<FaultInjector>,
+}
+
+impl TimpaniReceiver {
+    pub fn new(injector: FaultInjector) -> Self {
+        Self {
+            injector: Some(injector),
+        }
+    }
+}
 
 #[tonic::async_trait]
 impl FaultService for TimpaniReceiver {
@@ -18,6 +33,22 @@ impl FaultService for TimpaniReceiver {
         let info = info.into_inner();
         common::logd!(4, "Received fault notification: {:?}", info);
 
+        if info.duration_ms > 0 {
+            let injector = self
+                .injector
+                .as_ref()
+                .ok_or_else(|| Status::unimplemented("fault injection is not enabled"))?;
+            // An ASIL level that cannot be checked must not let the fault through
+            let asil_level = if info.override_asil {
+                None
+            } else {
+                resolve_asil_level(&info.workload_id).await.map_err(|e| {
+                    Status::failed_precondition(format!("cannot check ASIL level: {}", e))
+                })?
+            };
+            injector.inject(&info, asil_level).await?;
+        }
+
         // Process the fault information and generate a response
         let response = TimpaniResponse { status: 0 };
         Ok(Response::new(response))
@@ -67,4 +98,44 @@ mod tests {
             assert_eq!(out.get_ref().status, 0);
         }
     }
+
+    #[tokio::test]
+    async fn test_notify_fault_injection_needs_injector() {
+        let receiver = TimpaniReceiver::default();
+        let info = FaultInfo {
+            workload_id: "bms".to_string(),
+            duration_ms: 1000,
+            ..Default::default()
+        };
+
+        let err = receiver.notify_fault(Request::new(info)).await.unwrap_err();
+        assert_eq!(err.code(), tonic::Code::Unimplemented);
+    }
+
+    #[tokio::test]
+    async fn test_notify_fault_injects_with_override() {
+        use crate::state_machine::StateMachine;
+        use common::statemanager::{ModelState, ResourceType};
+        use std::sync::Arc;
+        use tokio::sync::Mutex;
+
+        let state_machine = Arc::new(Mutex::new(StateMachine::new()));
+        let receiver = TimpaniReceiver::new(FaultInjector::new(state_machine.clone()));
+        let info = FaultInfo {
+            workload_id: "bms".to_string(),
+            r#type: common::external::timpani::FaultType::Dmiss.into(),
+            duration_ms: 60_000,
+            override_asil: true,
+            ..Default::default()
+        };
+
+        let resp = receiver.notify_fault(Request::new(info)).await.unwrap();
+
+        assert_eq!(resp.get_ref().status, 0);
+        let state_machine = state_machine.lock().await;
+        let state = state_machine
+            .get_resource_state("bms", ResourceType::Model)
+            .unwrap();
+        assert_eq!(state.current_state, ModelState::Dead as i32);
+    }
 }
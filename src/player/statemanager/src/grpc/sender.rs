@@ -8,7 +8,7 @@ use common::actioncontroller::{
     ReconcileRequest, ReconcileResponse,
 };
 use std::env;
-use tonic::{Request, Response, Status};
+use tonic::{Response, Status};
 
 pub async fn _send(condition: ReconcileRequest) -> Result<Response<ReconcileResponse>, Status> {
     // Test mode bypass: return a fake successful response when env var is set
@@ -19,10 +19,11 @@ pub async fn _send(condition: ReconcileRequest) -> Result<Response<ReconcileResp
         };
         return Ok(Response::new(resp));
     }
+    // An unreachable ActionController is reported, so the request can be retried
     let mut client = ActionControllerConnectionClient::connect(connect_server())
         .await
-        .unwrap();
-    client.reconcile(Request::new(condition)).await
+        .map_err(|e| Status::unavailable(format!("Failed to connect to ActionController: {e}")))?;
+    client.reconcile(common::trace::request(condition)).await
 }
 
 #[cfg(test)]
@@ -38,6 +39,7 @@ mod tests {
             scenario_name: "s1".to_string(),
             current: 0,
             desired: 0,
+            transaction_id: "reconcile_s1_1".to_string(),
         };
 
         let res = _send(req).await;
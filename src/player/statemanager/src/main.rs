@@ -12,18 +12,32 @@
 //! The StateManager service is a core component of the PICCOLO framework, responsible for managing
 //! resource state transitions, monitoring container health, and ensuring ASIL-compliant operation.
 
-use common::logd;
+use common::channel::bounded;
+use common::external::timpani::fault_service_server::FaultServiceServer;
+use common::health::{etcd_reachable, ServerHealth, PROBE_INTERVAL};
 use common::logd::logger;
 use common::monitoringserver::ContainerList;
+use common::setting::TimpaniSettings;
 use common::statemanager::{
-    state_manager_connection_server::StateManagerConnectionServer, StateChange,
+    state_manager_connection_server::StateManagerConnectionServer, Ack, StateChange,
 };
+use state_machine::StateMachine;
 use std::env;
-use tokio::sync::mpsc::{channel, Receiver, Sender};
+use std::net::SocketAddr;
+use std::sync::Arc;
+use thiserror::Error;
+use tokio::sync::mpsc::{Receiver, Sender};
+use tokio::sync::Mutex;
+use tonic::transport::server::TcpIncoming;
 use tonic::transport::Server;
+use tracing::{error, info, trace, warn};
 
+pub mod dependency;
+pub mod events;
+pub mod fault;
 pub mod grpc;
 pub mod manager;
+pub mod pending;
 pub mod state_machine;
 pub mod types;
 
@@ -36,6 +50,8 @@ pub mod types;
 /// # Arguments
 /// * `rx_container` - Channel receiver for ContainerList messages from nodeagent
 /// * `rx_state_change` - Channel receiver for StateChange messages from various components
+/// * `rx_ack` - Channel receiver for acknowledgements from ActionController
+/// * `state_machine` - State machine shared with the fault injection service
 ///
 /// # Processing Flow
 /// 1. Create StateManagerManager instance with provided channels
@@ -50,49 +66,47 @@ pub mod types;
 async fn launch_manager(
     rx_container: Receiver<ContainerList>,
     rx_state_change: Receiver<StateChange>,
+    rx_ack: Receiver<Ack>,
+    state_machine: Arc<Mutex<StateMachine>>,
 ) {
     // In test mode we short-circuit heavy startup to keep unit tests fast
     // In test builds or when `PULLPIRI_TEST_MODE` is set we short-circuit heavy startup
     if cfg!(test) || env::var("PULLPIRI_TEST_MODE").is_ok() {
-        logd!(1, "Test mode: skipping StateManagerManager startup");
+        trace!("Test mode: skipping StateManagerManager startup");
         return;
     }
-    logd!(3, "=== StateManagerManager Starting ===");
+    info!("=== StateManagerManager Starting ===");
 
     // Create the StateManager engine with async channel receivers
-    let mut manager = manager::StateManagerManager::new(rx_container, rx_state_change).await;
+    let mut manager = manager::StateManagerManager::with_state_machine(
+        rx_container,
+        rx_state_change,
+        rx_ack,
+        state_machine,
+    );
 
     // Initialize the manager with configuration and persistent state
     match manager.initialize().await {
         Ok(_) => {
-            logd!(
-                3,
-                "StateManagerManager initialization completed successfully"
-            );
+            info!("StateManagerManager initialization completed successfully");
 
             // Run the main processing loop
-            logd!(3, "Starting StateManagerManager main processing loop...");
+            info!("Starting StateManagerManager main processing loop...");
             if let Err(e) = manager.run().await {
-                logd!(5, "StateManagerManager stopped with error: {e:?}");
-                logd!(
-                    5,
-                    "This may indicate a critical system failure or shutdown request"
-                );
+                error!("StateManagerManager stopped with error: {e:?}");
+                error!("This may indicate a critical system failure or shutdown request");
             } else {
-                logd!(4, "StateManagerManager stopped gracefully");
+                warn!("StateManagerManager stopped gracefully");
             }
         }
         Err(e) => {
-            logd!(5, "Failed to initialize StateManagerManager: {e:?}");
-            logd!(
-                5,
-                "StateManager service cannot start - check configuration and dependencies"
-            );
+            error!("Failed to initialize StateManagerManager: {e:?}");
+            error!("StateManager service cannot start - check configuration and dependencies");
             // Don't panic - allow graceful shutdown of other components
         }
     }
 
-    logd!(4, "=== StateManagerManager Stopped ===");
+    warn!("=== StateManagerManager Stopped ===");
 }
 
 /// Initializes and runs the StateManager gRPC server.
@@ -104,6 +118,7 @@ async fn launch_manager(
 /// # Arguments
 /// * `tx_container` - Channel sender for ContainerList messages to StateManager engine
 /// * `tx_state_change` - Channel sender for StateChange messages to StateManager engine
+/// * `tx_ack` - Channel sender for acknowledgements to StateManager engine
 ///
 /// # Server Configuration
 /// - Binds to address specified in common::statemanager::open_server()
@@ -119,110 +134,184 @@ async fn launch_manager(
 async fn initialize_grpc_server(
     tx_container: Sender<ContainerList>,
     tx_state_change: Sender<StateChange>,
+    tx_ack: Sender<Ack>,
 ) {
     // Allow tests to opt-out of starting the actual gRPC server
     // Skip starting the real gRPC server when running tests or explicitly requested
     if cfg!(test) || env::var("PULLPIRI_TEST_MODE").is_ok() {
-        logd!(1, "Test mode: skipping gRPC server startup");
+        trace!("Test mode: skipping gRPC server startup");
         return;
     }
-    logd!(3, "=== StateManager gRPC Server Starting ===");
+    info!("=== StateManager gRPC Server Starting ===");
 
     // Create the gRPC service handler with async channels
     let server = grpc::receiver::StateManagerReceiver {
         tx: tx_container,
         tx_state_change,
+        tx_ack,
     };
-    logd!(3, "StateManagerReceiver instance created successfully");
+    info!("StateManagerReceiver instance created successfully");
 
     // Parse the server address from configuration
     let addr = match common::statemanager::open_server().parse() {
         Ok(addr) => {
-            logd!(3, "StateManager gRPC server will bind to: {addr}");
+            info!("StateManager gRPC server will bind to: {addr}");
             addr
         }
         Err(e) => {
-            logd!(5, "Failed to parse StateManager server address: {e:?}");
-            logd!(
-                5,
-                "Check StateManager address configuration in common module"
-            );
+            error!("Failed to parse StateManager server address: {e:?}");
+            error!("Check StateManager address configuration in common module");
             return; // Exit gracefully without panicking
         }
     };
 
+    // Health service reports NOT_SERVING when etcd or the engine channels are gone
+    let (health, health_service) =
+        ServerHealth::new::<StateManagerConnectionServer<grpc::receiver::StateManagerReceiver>>();
+    let probe_container = server.tx.clone();
+    let probe_state_change = server.tx_state_change.clone();
+    let probe_ack = server.tx_ack.clone();
+    health.spawn_probe(PROBE_INTERVAL, move || {
+        let channels_open = !probe_container.is_closed()
+            && !probe_state_change.is_closed()
+            && !probe_ack.is_closed();
+        async move { channels_open && etcd_reachable().await }
+    });
+
     // Start the gRPC server with comprehensive error handling
-    logd!(3, "Starting StateManager gRPC server...");
+    info!("Starting StateManager gRPC server...");
     match Server::builder()
+        .layer(common::trace::TraceLayer)
+        .layer(common::recover::RecoverLayer)
+        .add_service(health_service)
+        .add_service(common::version::service("statemanager"))
         .add_service(StateManagerConnectionServer::new(server))
-        .serve(addr)
+        .serve_with_shutdown(addr, health.shutdown_signal())
         .await
     {
         Ok(_) => {
-            logd!(4, "StateManager gRPC server stopped gracefully");
+            warn!("StateManager gRPC server stopped gracefully");
         }
         Err(e) => {
-            logd!(5, "StateManager gRPC server error: {e:?}");
-            logd!(
-                5,
-                "This may indicate network issues, port conflicts, or configuration problems"
-            );
+            error!("StateManager gRPC server error: {e:?}");
+            error!("This may indicate network issues, port conflicts, or configuration problems");
         }
     }
 
-    logd!(4, "=== StateManager gRPC Server Stopped ===");
+    warn!("=== StateManager gRPC Server Stopped ===");
 }
 
-async fn initialize_timpani_server() {
+/// Timpani FaultService handler configured by the `timpani` settings
+///
+/// Fault injection is only enabled by `timpani.fault_injection`; faults are
+/// then forced into `state_machine` and sent to the engine through
+/// `tx_state_change`.
+///
+/// # Arguments
+/// * `settings` - `timpani` section of `settings.yaml`
+/// * `state_machine` - State machine injected faults are applied to
+/// * `tx_state_change` - Channel sender for StateChange messages to StateManager engine
+fn timpani_service(
+    settings: &TimpaniSettings,
+    state_machine: Arc<Mutex<StateMachine>>,
+    tx_state_change: Sender<StateChange>,
+) -> FaultServiceServer<grpc::receiver::timpani::TimpaniReceiver> {
+    let receiver = if settings.fault_injection {
+        warn!("Fault injection is enabled");
+        let injector = fault::FaultInjector::new(state_machine).with_events(tx_state_change);
+        grpc::receiver::timpani::TimpaniReceiver::new(injector)
+    } else {
+        grpc::receiver::timpani::TimpaniReceiver::default()
+    };
+    FaultServiceServer::new(receiver)
+}
+
+/// Reasons the Timpani FaultService could not be served
+#[derive(Debug, Error)]
+pub enum TimpaniServerError {
+    #[error("invalid Timpani address '{address}': {source}")]
+    InvalidAddress {
+        address: String,
+        source: std::net::AddrParseError,
+    },
+
+    #[error("cannot bind Timpani FaultService to {addr}: {source}")]
+    Bind {
+        addr: SocketAddr,
+        source: std::io::Error,
+    },
+
+    #[error("Timpani FaultService failed: {0}")]
+    Serve(#[from] tonic::transport::Error),
+}
+
+/// Initializes and runs the Timpani FaultService server.
+///
+/// Serves on `timpani.address` of `settings.yaml`.
+///
+/// # Arguments
+/// * `state_machine` - State machine injected faults are applied to
+/// * `tx_state_change` - Channel sender for StateChange messages to StateManager engine
+///
+/// # Returns
+/// * `Result<(), TimpaniServerError>` - Error if the server could not start
+///   or stopped on a failure
+async fn initialize_timpani_server(
+    state_machine: Arc<Mutex<StateMachine>>,
+    tx_state_change: Sender<StateChange>,
+) -> Result<(), TimpaniServerError> {
     // Allow tests to opt-out of starting the timpani server
     // Skip starting the timpani server when running tests or explicitly requested
     if cfg!(test) || env::var("PULLPIRI_TEST_MODE").is_ok() {
-        logd!(1, "Test mode: skipping Timpani server startup");
-        return;
+        trace!("Test mode: skipping Timpani server startup");
+        return Ok(());
     }
-    logd!(3, "=== Timpani gRPC Server Starting ===");
+    info!("=== Timpani gRPC Server Starting ===");
 
     // Create the gRPC service handler for Timpani
-    let timpani_server = grpc::receiver::timpani::TimpaniReceiver::default();
-    logd!(3, "TimpaniReceiver instance created successfully");
+    let settings = &common::setting::get_config().timpani;
+    let timpani_server = timpani_service(settings, state_machine, tx_state_change);
+    info!("TimpaniReceiver instance created successfully");
 
-    // Parse the Timpani server address from configuration
-    let addr = match "127.0.0.1:50053".parse() {
-        Ok(addr) => {
-            logd!(3, "Timpani gRPC server will bind to: {addr}");
-            addr
-        }
-        Err(e) => {
-            logd!(5, "Failed to parse Timpani server address: {e:?}");
-            logd!(5, "Check Timpani address configuration in common module");
-            return; // Exit gracefully without panicking
-        }
-    };
+    serve_timpani(&settings.address, timpani_server).await?;
 
-    // Start the gRPC server for Timpani with comprehensive error handling
-    logd!(3, "Starting Timpani gRPC server...");
-    match Server::builder()
-        .add_service(
-            common::external::timpani::fault_service_server::FaultServiceServer::new(
-                timpani_server,
-            ),
-        )
-        .serve(addr)
+    warn!("=== Timpani gRPC Server Stopped ===");
+    Ok(())
+}
+
+/// Serves `service` on `address` until the server stops
+///
+/// The address is bound before serving, so a port already in use is
+/// reported as [`TimpaniServerError::Bind`].
+async fn serve_timpani(
+    address: &str,
+    service: FaultServiceServer<grpc::receiver::timpani::TimpaniReceiver>,
+) -> Result<(), TimpaniServerError> {
+    let addr: SocketAddr =
+        address
+            .parse()
+            .map_err(|source| TimpaniServerError::InvalidAddress {
+                address: address.to_string(),
+                source,
+            })?;
+    let listener = tokio::net::TcpListener::bind(addr)
         .await
-    {
-        Ok(_) => {
-            logd!(4, "Timpani gRPC server stopped gracefully");
-        }
-        Err(e) => {
-            logd!(5, "Timpani gRPC server error: {e:?}");
-            logd!(
-                5,
-                "This may indicate network issues, port conflicts, or configuration problems"
-            );
+        .map_err(|source| TimpaniServerError::Bind { addr, source })?;
+    let incoming = TcpIncoming::from_listener(listener, true, None).map_err(|source| {
+        TimpaniServerError::Bind {
+            addr,
+            source: std::io::Error::other(source),
         }
-    }
-
-    logd!(4, "=== Timpani gRPC Server Stopped ===");
+    })?;
+    info!("Timpani gRPC server bound to: {addr}");
+
+    Server::builder()
+        .layer(common::trace::TraceLayer)
+        .layer(common::recover::RecoverLayer)
+        .add_service(service)
+        .serve_with_incoming(incoming)
+        .await?;
+    Ok(())
 }
 
 /// Main entry point for the StateManager service.
@@ -240,8 +329,8 @@ async fn initialize_timpani_server() {
 /// - Supports graceful termination handling
 ///
 /// # Channel Configuration
-/// - ContainerList channel: 100 message buffer for nodeagent communication
-/// - StateChange channel: 100 message buffer for component communication
+/// - ContainerList, StateChange and Ack channels: capacities from the
+///   `channels` settings, 100 messages each by default
 /// - Async processing prevents blocking between message types
 ///
 /// # Error Handling
@@ -250,36 +339,62 @@ async fn initialize_timpani_server() {
 /// - Graceful shutdown even if one component fails
 #[tokio::main]
 async fn main() {
+    common::logging::init("statemanager");
     let _ = logger::init_async_logger("statemanager").await;
-    logd!(1, "initiailize statemanager...");
+    trace!("initiailize statemanager...");
 
     // Create async channels for communication between gRPC server and processing engine
-    // Buffer size of 100 provides good throughput while preventing excessive memory usage
-    let (tx_container, rx_container) = channel::<ContainerList>(100);
-    let (tx_state_change, rx_state_change) = channel::<StateChange>(100);
+    // Their capacities are set in the `channels` settings
+    let channels = &common::setting::get_config().channels;
+    let (tx_container, rx_container) =
+        bounded::<ContainerList>("statemanager_container", channels.statemanager_container);
+    let (tx_state_change, rx_state_change) = bounded::<StateChange>(
+        "statemanager_state_change",
+        channels.statemanager_state_change,
+    );
+    let (tx_ack, rx_ack) = bounded::<Ack>("statemanager_ack", channels.statemanager_ack);
+    let state_machine = Arc::new(Mutex::new(StateMachine::new()));
 
     // Launch StateManager processing engine
-    let manager_task = launch_manager(rx_container, rx_state_change);
+    let manager_task = launch_manager(rx_container, rx_state_change, rx_ack, state_machine.clone());
+
+    // Launch gRPC server for timpani deadline miss and fault injection
+    let timpani_task = initialize_timpani_server(state_machine, tx_state_change.clone());
 
     // Launch gRPC server for external communication
-    let grpc_task = initialize_grpc_server(tx_container, tx_state_change);
+    let grpc_task = initialize_grpc_server(tx_container, tx_state_change, tx_ack);
 
-    // Launch gRPC server for timpani deadline miss
-    let timpani_task = initialize_timpani_server();
+    // A fault service that cannot start must not go unnoticed, but the
+    // other components keep running without it
+    let timpani_task = async {
+        if let Err(e) = timpani_task.await {
+            error!("Timpani FaultService is not running: {e}");
+        }
+    };
 
-    // Run both components concurrently until shutdown
-    // tokio::join! ensures both tasks complete before main exits
-    tokio::join!(manager_task, grpc_task, timpani_task);
+    // Run all components concurrently until the gRPC server shuts down
+    tokio::select! {
+        _ = async { tokio::join!(manager_task, timpani_task) } => {}
+        _ = grpc_task => {}
+    }
 
     // Both tasks return (), but we log completion for monitoring
-    logd!(6, "statemanager service stopped");
+    error!("statemanager service stopped");
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use common::external::timpani::fault_service_client::FaultServiceClient;
+    use common::external::timpani::{FaultInfo, FaultType};
+    use common::statemanager::ResourceType;
+    use tokio::sync::mpsc::channel;
     use tokio::time::{timeout, Duration};
 
+    fn state_machine() -> Arc<Mutex<StateMachine>> {
+        Arc::new(Mutex::new(StateMachine::new()))
+    }
+
     #[tokio::test]
     async fn test_launch_manager_skips_in_test_mode() {
         unsafe {
@@ -288,11 +403,12 @@ mod tests {
 
         let (_tx_container, rx_container) = channel::<ContainerList>(10);
         let (_tx_state_change, rx_state_change) = channel::<StateChange>(10);
+        let (_tx_ack, rx_ack) = channel::<Ack>(10);
 
         // Should return quickly because test mode short-circuits startup
         let res = timeout(
             Duration::from_secs(1),
-            launch_manager(rx_container, rx_state_change),
+            launch_manager(rx_container, rx_state_change, rx_ack, state_machine()),
         )
         .await;
         assert!(res.is_ok(), "launch_manager did not return in test mode");
@@ -310,11 +426,12 @@ mod tests {
 
         let (tx_container, _rx_container) = channel::<ContainerList>(10);
         let (tx_state_change, _rx_state_change) = channel::<StateChange>(10);
+        let (tx_ack, _rx_ack) = channel::<Ack>(10);
 
         // Should return quickly because test mode short-circuits server startup
         let res = timeout(
             Duration::from_secs(1),
-            initialize_grpc_server(tx_container, tx_state_change),
+            initialize_grpc_server(tx_container, tx_state_change, tx_ack),
         )
         .await;
         assert!(
@@ -333,9 +450,13 @@ mod tests {
         }
 
         // Should return quickly because test mode short-circuits timpani startup
-        let res = timeout(Duration::from_secs(1), initialize_timpani_server()).await;
+        let res = timeout(
+            Duration::from_secs(1),
+            initialize_timpani_server(state_machine(), channel::<StateChange>(10).0),
+        )
+        .await;
         assert!(
-            res.is_ok(),
+            matches!(res, Ok(Ok(()))),
             "initialize_timpani_server did not return in test mode"
         );
 
@@ -356,12 +477,13 @@ mod tests {
 
         let (tx_container, rx_container) = channel::<ContainerList>(10);
         let (tx_state_change, rx_state_change) = channel::<StateChange>(10);
+        let (tx_ack, rx_ack) = channel::<Ack>(10);
 
         // Both futures should return quickly because cfg!(test) is true
         let fut = async move {
             tokio::join!(
-                launch_manager(rx_container, rx_state_change),
-                initialize_grpc_server(tx_container, tx_state_change),
+                launch_manager(rx_container, rx_state_change, rx_ack, state_machine()),
+                initialize_grpc_server(tx_container, tx_state_change, tx_ack),
             );
         };
 
@@ -378,14 +500,16 @@ mod tests {
 
         let (tx_container, rx_container) = channel::<ContainerList>(10);
         let (tx_state_change, rx_state_change) = channel::<StateChange>(10);
+        let (tx_ack, rx_ack) = channel::<Ack>(10);
 
         // Run manager, grpc server and timpani concurrently and ensure they all return quickly
         let fut = async move {
-            tokio::join!(
-                launch_manager(rx_container, rx_state_change),
-                initialize_grpc_server(tx_container, tx_state_change),
-                initialize_timpani_server(),
+            let (_, _, timpani) = tokio::join!(
+                launch_manager(rx_container, rx_state_change, rx_ack, state_machine()),
+                initialize_grpc_server(tx_container, tx_state_change, tx_ack),
+                initialize_timpani_server(state_machine(), channel::<StateChange>(10).0),
             );
+            assert!(timpani.is_ok());
         };
 
         let res = timeout(Duration::from_secs(1), fut).await;
@@ -399,6 +523,128 @@ mod tests {
         }
     }
 
+    /// Serve the Timpani service configured by `settings` on a free port
+    /// and return a client to it and the engine channel it sends to
+    async fn start_timpani(
+        settings: &TimpaniSettings,
+    ) -> (
+        FaultServiceClient<tonic::transport::Channel>,
+        tokio::sync::mpsc::Receiver<StateChange>,
+    ) {
+        let (tx_state_change, rx_state_change) = channel::<StateChange>(10);
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let incoming = TcpIncoming::from_listener(listener, true, None).unwrap();
+        let service = timpani_service(settings, state_machine(), tx_state_change);
+        tokio::spawn(
+            Server::builder()
+                .add_service(service)
+                .serve_with_incoming(incoming),
+        );
+        let client = FaultServiceClient::connect(format!("http://{}", addr))
+            .await
+            .unwrap();
+        (client, rx_state_change)
+    }
+
+    fn dmiss(model: &str) -> FaultInfo {
+        FaultInfo {
+            workload_id: model.to_string(),
+            r#type: FaultType::Dmiss.into(),
+            duration_ms: 60_000,
+            override_asil: true,
+            ..Default::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_timpani_fault_injection_emits_state_change() {
+        let settings = TimpaniSettings {
+            fault_injection: true,
+            ..Default::default()
+        };
+        let (mut client, mut rx_state_change) = start_timpani(&settings).await;
+
+        let response = client.notify_fault(dmiss("bms")).await.unwrap();
+        assert_eq!(response.get_ref().status, 0);
+
+        let change = timeout(Duration::from_secs(1), rx_state_change.recv())
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(change.resource_type, ResourceType::Model as i32);
+        assert_eq!(change.resource_name, "bms");
+        assert_eq!(change.current_state, "RUNNING");
+        assert_eq!(change.target_state, "DEAD");
+        assert_eq!(change.source, state_machine::FAULT_INJECTED_SOURCE);
+    }
+
+    #[tokio::test]
+    async fn test_timpani_fault_injection_disabled_by_default() {
+        let (mut client, mut rx_state_change) = start_timpani(&TimpaniSettings::default()).await;
+
+        let err = client.notify_fault(dmiss("bms")).await.unwrap_err();
+        assert_eq!(err.code(), tonic::Code::Unimplemented);
+        assert!(rx_state_change.try_recv().is_err());
+
+        // Faults without a duration are still accepted
+        let response = client.notify_fault(FaultInfo::default()).await.unwrap();
+        assert_eq!(response.get_ref().status, 0);
+    }
+
+    fn disabled_timpani_service() -> FaultServiceServer<grpc::receiver::timpani::TimpaniReceiver> {
+        timpani_service(
+            &TimpaniSettings::default(),
+            state_machine(),
+            channel::<StateChange>(1).0,
+        )
+    }
+
+    #[tokio::test]
+    async fn test_timpani_port_conflict_is_reported() {
+        let free = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = free.local_addr().unwrap().to_string();
+        drop(free);
+
+        let first = tokio::spawn({
+            let address = address.clone();
+            async move { serve_timpani(&address, disabled_timpani_service()).await }
+        });
+        // Wait until the first instance accepts connections
+        let started = timeout(Duration::from_secs(1), async {
+            while tokio::net::TcpStream::connect(&address).await.is_err() {
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+        })
+        .await;
+        assert!(started.is_ok(), "first Timpani server did not start");
+
+        let err = serve_timpani(&address, disabled_timpani_service())
+            .await
+            .unwrap_err();
+
+        match &err {
+            TimpaniServerError::Bind { addr, source } => {
+                assert_eq!(addr.to_string(), address);
+                assert_eq!(source.kind(), std::io::ErrorKind::AddrInUse);
+            }
+            other => panic!("expected a bind error, got {other:?}"),
+        }
+        assert!(err
+            .to_string()
+            .starts_with(&format!("cannot bind Timpani FaultService to {address}")));
+        assert!(!first.is_finished());
+        first.abort();
+    }
+
+    #[tokio::test]
+    async fn test_timpani_invalid_address_is_reported() {
+        let err = serve_timpani("localhost:fault", disabled_timpani_service())
+            .await
+            .unwrap_err();
+        assert!(matches!(err, TimpaniServerError::InvalidAddress { .. }));
+    }
+
     // Call the generated `main()` function (synchronous entry created by `#[tokio::main]`)
     // to exercise the startup logging, channel creation and join logic in test builds.
     #[test]
@@ -13,21 +13,22 @@
 //! state transitions, monitoring, reconciliation, and recovery for all resource types
 //! (Scenario, Package, Model, Volume, Network, Node).
 
+use crate::events::{state_name, PiccoloStateEvent, StateEventPublisher};
 use crate::grpc::sender;
-use crate::state_machine::StateMachine;
+use crate::pending::{PendingRequest, PendingTracker, SWEEP_INTERVAL};
+use crate::state_machine::{StateMachine, FAULT_INJECTED_SOURCE};
 use crate::types::{ActionCommand, TransitionResult};
 use common::monitoringserver::ContainerList;
 use common::spec::artifact::Artifact;
 
-use common::statemanager::{
-    ErrorCode, ModelState, PackageState, ResourceType, ScenarioState, StateChange,
-};
+use common::statemanager::{Ack, ErrorCode, ModelState, PackageState, ResourceType, StateChange};
 
-use common::logd;
+use common::keys;
 use common::Result;
 use std::sync::Arc;
 use tokio::sync::{mpsc, Mutex};
 use tokio::task;
+use tracing::{debug, error, info, trace, warn};
 
 /// Core state management engine for the StateManager service.
 ///
@@ -64,6 +65,15 @@ pub struct StateManagerManager {
     /// - FilterGateway: Policy-driven state transitions and filtering decisions
     /// - ActionController: Action execution results and state confirmations
     rx_state_change: Arc<Mutex<mpsc::Receiver<StateChange>>>,
+
+    /// Channel receiver for acknowledgements of reconcile requests from ActionController.
+    rx_ack: Arc<Mutex<mpsc::Receiver<Ack>>>,
+
+    /// Reconcile requests sent to ActionController that are not acknowledged yet.
+    pending: Arc<Mutex<PendingTracker>>,
+
+    /// Publisher of committed transitions to DDS consumers.
+    events: StateEventPublisher,
 }
 
 impl StateManagerManager {
@@ -75,20 +85,56 @@ impl StateManagerManager {
     /// # Arguments
     /// * `rx_container` - Channel receiver for ContainerList messages from nodeagent
     /// * `rx_state_change` - Channel receiver for StateChange messages from components
+    /// * `rx_ack` - Channel receiver for acknowledgements from ActionController
     ///
     /// # Returns
     /// * `Self` - New StateManagerManager instance ready for initialization
     pub async fn new(
         rx_container: mpsc::Receiver<ContainerList>,
         rx_state_change: mpsc::Receiver<StateChange>,
+        rx_ack: mpsc::Receiver<Ack>,
+    ) -> Self {
+        Self::with_state_machine(
+            rx_container,
+            rx_state_change,
+            rx_ack,
+            Arc::new(Mutex::new(StateMachine::new())),
+        )
+    }
+
+    /// Creates a StateManagerManager driving a state machine shared with
+    /// other services, such as fault injection.
+    ///
+    /// # Arguments
+    /// * `rx_container` - Channel receiver for ContainerList messages from nodeagent
+    /// * `rx_state_change` - Channel receiver for StateChange messages from components
+    /// * `rx_ack` - Channel receiver for acknowledgements from ActionController
+    /// * `state_machine` - State machine to process transitions with
+    pub fn with_state_machine(
+        rx_container: mpsc::Receiver<ContainerList>,
+        rx_state_change: mpsc::Receiver<StateChange>,
+        rx_ack: mpsc::Receiver<Ack>,
+        state_machine: Arc<Mutex<StateMachine>>,
     ) -> Self {
         Self {
-            state_machine: Arc::new(Mutex::new(StateMachine::new())),
+            state_machine,
             rx_container: Arc::new(Mutex::new(rx_container)),
             rx_state_change: Arc::new(Mutex::new(rx_state_change)),
+            rx_ack: Arc::new(Mutex::new(rx_ack)),
+            pending: Arc::new(Mutex::new(PendingTracker::default())),
+            events: StateEventPublisher::from_settings(),
         }
     }
 
+    /// Replaces the publisher of state transition events.
+    ///
+    /// # Arguments
+    /// * `events` - Publisher to send committed transitions to
+    pub fn with_state_events(mut self, events: StateEventPublisher) -> Self {
+        self.events = events;
+        self
+    }
+
     /// Initializes the StateManagerManager's internal state and resources.
     ///
     /// Performs startup operations required before beginning message processing:
@@ -106,7 +152,7 @@ impl StateManagerManager {
     /// - Set up dependency tracking and validation systems
     /// - Configure ASIL safety monitoring and alerting
     pub async fn initialize(&mut self) -> Result<()> {
-        logd!(3, "StateManagerManager initializing...");
+        info!("StateManagerManager initializing...");
 
         // Initialize the state machine with async action executor
         let action_receiver = {
@@ -119,11 +165,11 @@ impl StateManagerManager {
             run_action_executor(action_receiver).await;
         });
 
-        logd!(3, "State machine initialized with transition tables for Scenario, Package, and Model resources");
-        logd!(
-            3,
-            "Async action executor started for non-blocking action processing"
-        );
+        // Requests sent before a restart are still waiting for their acknowledgement
+        self.restore_pending_requests().await;
+
+        info!("State machine initialized with transition tables for Scenario, Package, and Model resources");
+        info!("Async action executor started for non-blocking action processing");
 
         // TODO: Add comprehensive initialization logic:
         // - Load persisted resource states from persistent storage
@@ -134,7 +180,7 @@ impl StateManagerManager {
         // - Set up health check systems for all resource types
         // - Configure event streaming and notification systems
 
-        logd!(3, "StateManagerManager initialization completed");
+        info!("StateManagerManager initialization completed");
         Ok(())
     }
 
@@ -185,8 +231,7 @@ impl StateManagerManager {
         let resource_type = match ResourceType::try_from(state_change.resource_type) {
             Ok(rt) => rt,
             Err(_) => {
-                logd!(5,
-                    "VALIDATION ERROR: Invalid resource type '{}' in StateChange request for resource '{}'", 
+                error!("VALIDATION ERROR: Invalid resource type '{}' in StateChange request for resource '{}'", 
                     state_change.resource_type,
                     state_change.resource_name
                 );
@@ -209,28 +254,32 @@ impl StateManagerManager {
         // - Dependency impact analysis and root cause investigation
         // - Security audit trails for state change authorization
         //
-        // TODO: Replace println! with structured logging (tracing crate) for production:
-        // - Use appropriate log levels (info, warn, error)
-        // - Include correlation IDs for distributed tracing
-        // - Add structured fields for metrics aggregation
-        // - Implement log sampling for high-volume scenarios
-        logd!(1, "=== PROCESSING STATE CHANGE ===");
-        logd!(
-            1,
+        // Changes queued by a gRPC request are processed in its trace, so
+        // these lines carry its trace ID.
+        trace!("=== PROCESSING STATE CHANGE ===");
+        trace!(
             "  Resource Type: {:?} (numeric: {})",
             resource_type,
             state_change.resource_type
         );
-        logd!(1, "  Resource Name: {}", state_change.resource_name);
-        logd!(
-            1,
+        trace!("  Resource Name: {}", state_change.resource_name);
+        trace!("  Trace ID: {}", state_change.trace_id);
+        trace!(
             "  State Transition: {} -> {}",
             state_change.current_state,
             state_change.target_state
         );
-        logd!(1, "  Transition ID: {}", state_change.transition_id);
-        logd!(1, "  Source Component: {}", state_change.source);
-        logd!(1, "  Timestamp: {} ns", state_change.timestamp_ns);
+        trace!("  Transition ID: {}", state_change.transition_id);
+        trace!("  Source Component: {}", state_change.source);
+        trace!("  Timestamp: {} ns", state_change.timestamp_ns);
+
+        // Injected faults are already forced into the state machine by the
+        // fault injector; they are only propagated
+        if state_change.source == FAULT_INJECTED_SOURCE {
+            self.propagate_forced_state_change(&state_change, resource_type)
+                .await;
+            return;
+        }
 
         // ========================================
         // COMPREHENSIVE IMPLEMENTATION ROADMAP
@@ -304,11 +353,26 @@ impl StateManagerManager {
         // - Condition evaluation for conditional transitions
         // - Action scheduling for follow-up operations
         // - Error detection and reporting
-        let result = {
+        let (previous, result) = {
             // Acquire exclusive lock on the state machine for this transition
             // Note: This serializes all state transitions to maintain consistency
             let mut state_machine = self.state_machine.lock().await;
-            state_machine.process_state_change(state_change.clone())
+            // New resources start from the state the request reports
+            let previous = state_machine
+                .get_resource_state(&state_change.resource_name, resource_type)
+                .map_or_else(
+                    || {
+                        StateMachine::state_str_to_enum(
+                            &state_change.current_state,
+                            state_change.resource_type,
+                        )
+                    },
+                    |rs| rs.current_state,
+                );
+            (
+                previous,
+                state_machine.process_state_change(state_change.clone()),
+            )
         }; // Lock is automatically released here
 
         // ========================================
@@ -320,49 +384,40 @@ impl StateManagerManager {
             // ========================================
             // SUCCESS PATH: Log positive outcome and queue actions
             // ========================================
-            logd!(1, "  ✓ State transition completed successfully");
-            // Convert new_state to string representation based on resource type only for logs
-            let new_state_str = match resource_type {
-                ResourceType::Scenario => ScenarioState::try_from(result.new_state)
-                    .map(|s| s.as_str_name())
-                    .unwrap_or("UNKNOWN"),
-                ResourceType::Package => PackageState::try_from(result.new_state)
-                    .map(|s| s.as_str_name())
-                    .unwrap_or("UNKNOWN"),
-                ResourceType::Model => ModelState::try_from(result.new_state)
-                    .map(|s| s.as_str_name())
-                    .unwrap_or("UNKNOWN"),
-                _ => "UNKNOWN",
-            };
-            logd!(2, "    Final State: {new_state_str}");
-            logd!(2, "    Success Message: {}", result.message);
-            logd!(1, "    Transition ID: {}", result.transition_id);
+            trace!("  ✓ State transition completed successfully");
+            let new_state_str = state_name(resource_type, result.new_state);
+            self.events.publish(PiccoloStateEvent::new(
+                resource_type,
+                &state_change.resource_name,
+                state_name(resource_type, previous),
+                new_state_str,
+                &format!("{}: {}", state_change.source, result.message),
+            ));
+            debug!("    Final State: {new_state_str}");
+            debug!("    Success Message: {}", result.message);
+            trace!("    Transition ID: {}", result.transition_id);
 
             // 🔍 COMMENT 6: Save scenario state changes to ETCD
             // StateManager receives state change requests from FilterGateway, ActionController, and PolicyManager
             // and saves the scenario state transitions to ETCD for persistence
             if resource_type == ResourceType::Scenario {
-                logd!(
-                    1,
-                    "💾 SCENARIO STATE PERSISTENCE: StateManager ETCD Storage"
-                );
-                logd!(1, "   📋 Scenario: {}", state_change.resource_name);
-                logd!(1, "   🔄 Final State: {}", new_state_str);
-                logd!(1, "   🔍 Reason: Successful state transition completed");
+                trace!("💾 SCENARIO STATE PERSISTENCE: StateManager ETCD Storage");
+                trace!("   📋 Scenario: {}", state_change.resource_name);
+                trace!("   🔄 Final State: {}", new_state_str);
+                trace!("   🔍 Reason: Successful state transition completed");
 
-                let etcd_key = format!("/scenario/{}/state", state_change.resource_name);
+                let etcd_key = keys::scenario_state(&state_change.resource_name);
                 let etcd_value = new_state_str;
 
-                logd!(1, "   📤 Saving to ETCD:");
-                logd!(1, "      • Key: {}", etcd_key);
-                logd!(1, "      • Value: {}", etcd_value);
-                logd!(1, "      • Operation: common::etcd::put()");
+                trace!("   📤 Saving to ETCD:");
+                trace!("      • Key: {}", etcd_key);
+                trace!("      • Value: {}", etcd_value);
+                trace!("      • Operation: common::etcd::put()");
 
                 if let Err(e) = common::etcd::put(&etcd_key, etcd_value).await {
-                    logd!(4, "   ❌ Failed to save scenario state to ETCD: {:?}", e);
+                    warn!("   ❌ Failed to save scenario state to ETCD: {:?}", e);
                 } else {
-                    logd!(
-                        1,
+                    trace!(
                         "   ✅ Successfully saved scenario state to ETCD: {} → {}",
                         etcd_key,
                         etcd_value
@@ -373,52 +428,71 @@ impl StateManagerManager {
             // Log any actions that were queued for asynchronous execution
             // Actions are processed separately to keep state transitions fast
             if !result.actions_to_execute.is_empty() {
-                logd!(1, "    Actions queued for async execution:");
+                trace!("    Actions queued for async execution:");
                 for action in &result.actions_to_execute {
-                    logd!(1, "      - {action}");
+                    trace!("      - {action}");
                 }
-                logd!(
-                    1,
-                    "    Note: Actions will be executed asynchronously by the action executor"
-                );
+                trace!("    Note: Actions will be executed asynchronously by the action executor");
             }
 
-            logd!(
-                1,
-                "  Status: State change processing completed successfully"
-            );
+            trace!("  Status: State change processing completed successfully");
         } else {
             // ========================================
             // FAILURE PATH: Log error details and initiate recovery
             // ========================================
-            logd!(4, "  ✗ State transition failed");
-            // Convert new_state to string representation based on resource type only for logs
-            let new_state_str = match resource_type {
-                ResourceType::Scenario => ScenarioState::try_from(result.new_state)
-                    .map(|s| s.as_str_name())
-                    .unwrap_or("UNKNOWN"),
-                ResourceType::Package => PackageState::try_from(result.new_state)
-                    .map(|s| s.as_str_name())
-                    .unwrap_or("UNKNOWN"),
-                ResourceType::Model => ModelState::try_from(result.new_state)
-                    .map(|s| s.as_str_name())
-                    .unwrap_or("UNKNOWN"),
-                _ => "UNKNOWN",
-            };
-            logd!(4, "    Error Code: {:?}", result.error_code);
-            logd!(4, "    Error Message: {}", result.message);
-            logd!(4, "    Error Details: {}", result.error_details);
-            logd!(4, "    Current State: {new_state_str} (unchanged)");
-            logd!(4, "    Failed Transition ID: {}", result.transition_id);
+            warn!("  ✗ State transition failed");
+            let new_state_str = state_name(resource_type, result.new_state);
+            warn!("    Error Code: {:?}", result.error_code);
+            warn!("    Error Message: {}", result.message);
+            warn!("    Error Details: {}", result.error_details);
+            warn!("    Current State: {new_state_str} (unchanged)");
+            warn!("    Failed Transition ID: {}", result.transition_id);
 
             // Delegate to specialized failure handling logic
             // This method will analyze the failure type and determine appropriate recovery actions
             self.handle_transition_failure(&state_change, &result).await;
 
-            logd!(4, "  Status: State change processing completed with errors");
+            warn!("  Status: State change processing completed with errors");
         }
 
-        logd!(1, "================================");
+        trace!("================================");
+    }
+
+    /// Propagates a model state forced by fault injection
+    ///
+    /// Like a model state change seen in a container report, the new state
+    /// is saved to etcd and the packages of the model are evaluated again.
+    async fn propagate_forced_state_change(
+        &self,
+        state_change: &StateChange,
+        resource_type: ResourceType,
+    ) {
+        let model_state =
+            ModelState::from_str_name(&format!("MODEL_STATE_{}", state_change.target_state));
+        let Some(model_state) = model_state.filter(|_| resource_type == ResourceType::Model) else {
+            warn!(
+                "Ignoring forced state {} of {:?} {}",
+                state_change.target_state, resource_type, state_change.resource_name
+            );
+            return;
+        };
+
+        info!(
+            "Model {} forced from {} to {} by {}",
+            state_change.resource_name,
+            state_change.current_state,
+            state_change.target_state,
+            FAULT_INJECTED_SOURCE
+        );
+        if let Err(e) = self
+            .save_model_state_to_etcd(&state_change.resource_name, model_state)
+            .await
+        {
+            warn!("    Failed to save model state to ETCD: {:?}", e);
+            return;
+        }
+        self.trigger_package_state_evaluation(&state_change.resource_name)
+            .await;
     }
 
     /// Handle state transition failures
@@ -427,34 +501,30 @@ impl StateManagerManager {
         state_change: &StateChange,
         result: &TransitionResult,
     ) {
-        logd!(
-            4,
+        warn!(
             "    Handling transition failure for resource: {}",
             state_change.resource_name
         );
-        logd!(4, "      Error: {}", result.message);
-        logd!(4, "      Error code: {:?}", result.error_code);
-        logd!(4, "      Error details: {}", result.error_details);
+        warn!("      Error: {}", result.message);
+        warn!("      Error code: {:?}", result.error_code);
+        warn!("      Error details: {}", result.error_details);
 
         // Generate appropriate error responses based on error type
         match result.error_code {
             ErrorCode::InvalidStateTransition => {
-                logd!(
-                    4,
-                    "      Invalid state transition - checking state machine rules"
-                );
+                warn!("      Invalid state transition - checking state machine rules");
                 // Would log detailed state machine validation errors
             }
             ErrorCode::PreconditionFailed => {
-                logd!(4, "      Preconditions not met - evaluating retry strategy");
+                warn!("      Preconditions not met - evaluating retry strategy");
                 // Would check if conditions might be met later and schedule retry
             }
             ErrorCode::ResourceNotFound => {
-                logd!(4, "      Resource not found - may need initialization");
+                warn!("      Resource not found - may need initialization");
                 // Would check if resource needs to be created or registered
             }
             _ => {
-                logd!(4, "      General error - applying default error handling");
+                warn!("      General error - applying default error handling");
                 // Would apply general error handling procedures
             }
         }
@@ -480,9 +550,9 @@ impl StateManagerManager {
     /// 3. Evaluate model state based on container states
     /// 4. Update model states in ETCD if transitions occur
     async fn process_container_list(&self, container_list: ContainerList) {
-        logd!(2, "=== PROCESSING CONTAINER LIST ===");
-        logd!(2, "  Node Name: {}", container_list.node_name);
-        logd!(2, "  Container Count: {}", container_list.containers.len());
+        debug!("=== PROCESSING CONTAINER LIST ===");
+        debug!("  Node Name: {}", container_list.node_name);
+        debug!("  Container Count: {}", container_list.containers.len());
 
         // Process containers and group by model
         let model_containers = self
@@ -491,10 +561,14 @@ impl StateManagerManager {
 
         // Process each model's container states
         for (model_name, containers) in model_containers {
-            logd!(2, "  Processing model: {}", model_name);
+            debug!("  Processing model: {}", model_name);
+            self.register_model_dependencies(&model_name).await;
 
             // Process the state evaluation and transition through the state machine
             let mut state_machine = self.state_machine.lock().await;
+            let previous = state_machine
+                .get_resource_state(&model_name, ResourceType::Model)
+                .map_or(ModelState::Created as i32, |rs| rs.current_state);
             let transition_result =
                 state_machine.process_model_state_update(&model_name, &containers);
 
@@ -503,8 +577,7 @@ impl StateManagerManager {
                 let state_changed = !transition_result.actions_to_execute.is_empty();
 
                 if state_changed {
-                    logd!(
-                        1,
+                    trace!(
                         "    State transition successful: {}",
                         transition_result.message
                     );
@@ -516,41 +589,46 @@ impl StateManagerManager {
                         3 => common::statemanager::ModelState::Exited,
                         4 => common::statemanager::ModelState::Dead,
                         5 => common::statemanager::ModelState::Running,
+                        6 => common::statemanager::ModelState::Blocked,
                         _ => common::statemanager::ModelState::Running,
                     };
 
                     // Save the new model state to ETCD
                     drop(state_machine); // Release the lock before async operation
+                    self.events.publish(PiccoloStateEvent::new(
+                        ResourceType::Model,
+                        &model_name,
+                        state_name(ResourceType::Model, previous),
+                        state_name(ResourceType::Model, transition_result.new_state),
+                        &transition_result.message,
+                    ));
                     if let Err(e) = self
                         .save_model_state_to_etcd(&model_name, new_model_state)
                         .await
                     {
-                        logd!(4, "    Failed to save model state to ETCD: {:?}", e);
+                        warn!("    Failed to save model state to ETCD: {:?}", e);
                     } else {
-                        logd!(1, "    Successfully saved model state to ETCD");
+                        trace!("    Successfully saved model state to ETCD");
 
                         // Trigger package state evaluation based on model state change
                         // This implements the chain reaction described in the Korean documentation
                         self.trigger_package_state_evaluation(&model_name).await;
                     }
+
+                    if new_model_state == common::statemanager::ModelState::Dead {
+                        self.block_dependents(&model_name, &transition_result.transition_id)
+                            .await;
+                    }
                 } else {
-                    logd!(
-                        2,
-                        "    Model state unchanged: {}",
-                        transition_result.message
-                    );
+                    debug!("    Model state unchanged: {}", transition_result.message);
                 }
             } else {
-                logd!(
-                    4,
-                    "    State evaluation failed: {}",
-                    transition_result.message
-                );
+                warn!("    State evaluation failed: {}", transition_result.message);
             }
         }
 
-        logd!(2, "  Status: Container list processing completed");
-        logd!(2, "=====================================");
+        debug!("  Status: Container list processing completed");
+        debug!("=====================================");
     }
 
     /// Groups containers by their associated model based on annotations or naming conventions
@@ -604,26 +682,97 @@ impl StateManagerManager {
         None
     }
 
+    /// Registers the dependencies declared by the package of a model
+    ///
+    /// Packages are read from ETCD the first time a model is seen. A package
+    /// whose dependencies form a cycle is rejected and its models are
+    /// treated as independent.
+    async fn register_model_dependencies(&self, model_name: &str) {
+        if self
+            .state_machine
+            .lock()
+            .await
+            .has_dependencies_registered(model_name)
+        {
+            return;
+        }
+
+        let packages = match common::etcd::get_all_with_prefix(keys::PACKAGE_PREFIX).await {
+            Ok(packages) => packages,
+            Err(e) => {
+                warn!("    Failed to get packages from ETCD: {:?}", e);
+                return;
+            }
+        };
+        let package = packages.iter().find_map(|(_, yaml)| {
+            serde_yaml::from_str::<common::spec::artifact::Package>(yaml)
+                .ok()
+                .filter(|p| p.get_models().iter().any(|m| m.get_name() == model_name))
+        });
+
+        let mut state_machine = self.state_machine.lock().await;
+        match package {
+            Some(package) => {
+                if let Err(e) = state_machine.register_dependencies(&package) {
+                    error!(
+                        "    Rejected dependencies of package {}: {}",
+                        package.get_name(),
+                        e
+                    );
+                    for model in package.get_models() {
+                        state_machine.register_independent_model(&model.get_name());
+                    }
+                }
+            }
+            None => state_machine.register_independent_model(model_name),
+        }
+    }
+
+    /// Moves the models depending on a dead model to Blocked
+    async fn block_dependents(&self, model_name: &str, transition_id: &str) {
+        let blocked = self
+            .state_machine
+            .lock()
+            .await
+            .block_dependents(model_name, transition_id);
+
+        for dependent in blocked {
+            warn!(
+                "    Model {} blocked: dependency {} is dead",
+                dependent, model_name
+            );
+            if let Err(e) = self
+                .save_model_state_to_etcd(&dependent, common::statemanager::ModelState::Blocked)
+                .await
+            {
+                warn!("    Failed to save model state to ETCD: {:?}", e);
+                continue;
+            }
+            self.trigger_package_state_evaluation(&dependent).await;
+        }
+    }
+
     /// Saves model state to ETCD using the format specified in the documentation
     async fn save_model_state_to_etcd(
         &self,
         model_name: &str,
         model_state: common::statemanager::ModelState,
     ) -> std::result::Result<(), String> {
-        let key = format!("/model/{}/state", model_name);
+        let key = keys::model_state(model_name);
         let value = match model_state {
             common::statemanager::ModelState::Created => "Created",
             common::statemanager::ModelState::Paused => "Paused",
             common::statemanager::ModelState::Exited => "Exited",
             common::statemanager::ModelState::Dead => "Dead",
             common::statemanager::ModelState::Running => "Running",
+            common::statemanager::ModelState::Blocked => "Blocked",
             _ => "Unknown",
         };
 
-        logd!(1, "    Saving to ETCD - Key: {}, Value: {}", key, value);
+        trace!("    Saving to ETCD - Key: {}, Value: {}", key, value);
 
         if let Err(e) = common::etcd::put(&key, value).await {
-            logd!(5, "    Failed to save model state: {:?}", e);
+            error!("    Failed to save model state: {:?}", e);
             return Err(format!(
                 "Failed to save model state for {}: {:?}",
                 model_name, e
@@ -641,18 +790,17 @@ impl StateManagerManager {
         package_name: &str,
         package_state: common::statemanager::PackageState,
     ) -> std::result::Result<(), String> {
-        let key = format!("/package/{}/state", package_name);
+        let key = keys::package_state(package_name);
         let value = package_state.as_str_name();
 
-        logd!(
-            1,
+        trace!(
             "    Saving package state to ETCD - Key: {}, Value: {}",
             key,
             value
         );
 
         if let Err(e) = common::etcd::put(&key, value).await {
-            logd!(5, "    Failed to save package state: {:?}", e);
+            error!("    Failed to save package state: {:?}", e);
             return Err(format!(
                 "Failed to save package state for {}: {:?}",
                 package_name, e
@@ -668,8 +816,7 @@ impl StateManagerManager {
     /// When a model state changes, it triggers package state evaluation to see if the
     /// package state should also change based on the states of all models in the package.
     async fn trigger_package_state_evaluation(&self, changed_model_name: &str) {
-        logd!(
-            2,
+        debug!(
             "  Triggering package state evaluation for model: {}",
             changed_model_name
         );
@@ -679,11 +826,9 @@ impl StateManagerManager {
         {
             Ok(pkgs) => pkgs,
             Err(e) => {
-                logd!(
-                    4,
+                warn!(
                     "    Failed to find packages for model {}: {:?}",
-                    changed_model_name,
-                    e
+                    changed_model_name, e
                 );
                 return;
             }
@@ -705,7 +850,7 @@ impl StateManagerManager {
                             .save_package_state_to_etcd(&package_name, new_state)
                             .await
                         {
-                            logd!(5, "      Failed to save package state: {:?}", e);
+                            error!("      Failed to save package state: {:?}", e);
                             continue;
                         }
 
@@ -717,16 +862,14 @@ impl StateManagerManager {
                                 .trigger_action_controller_reconcile_internal(&package_name)
                                 .await
                             {
-                                logd!(
-                                    5,
+                                error!(
                                     "      Failed to trigger ActionController reconcile: {:?}",
                                     e
                                 );
                             }
                         }
 
-                        logd!(
-                            1,
+                        trace!(
                             "      Successfully updated package {} state to {}",
                             package_name,
                             new_state.as_str_name()
@@ -734,11 +877,9 @@ impl StateManagerManager {
                     }
                 }
                 Err(e) => {
-                    logd!(
-                        4,
+                    warn!(
                         "    Failed to evaluate package state for {}: {:?}",
-                        package_name,
-                        e
+                        package_name, e
                     );
                 }
             }
@@ -763,8 +904,7 @@ impl StateManagerManager {
         &self,
         package_name: &str,
     ) -> std::result::Result<(), String> {
-        logd!(
-            3,
+        info!(
             "      Triggering ActionController reconcile for package: {}",
             package_name
         );
@@ -773,36 +913,41 @@ impl StateManagerManager {
         let scenario_name = match self.find_scenario_for_package(package_name).await {
             Ok(Some(name)) => name,
             Ok(None) => {
-                logd!(4, "      No scenario found for package: {}", package_name);
+                warn!("      No scenario found for package: {}", package_name);
                 return Err(format!("No scenario found for package: {}", package_name));
             }
             Err(e) => {
-                logd!(
-                    4,
+                warn!(
                     "      Failed to find scenario for package {}: {:?}",
-                    package_name,
-                    e
+                    package_name, e
                 );
                 return Err(format!("Failed to find scenario for package: {}", e));
             }
         };
 
-        // Create reconcile request using the gRPC sender
-        let reconcile_request = common::actioncontroller::ReconcileRequest {
-            scenario_name: scenario_name.clone(),
-            current: common::actioncontroller::PodStatus::Failed.into(),
-            desired: common::actioncontroller::PodStatus::Running.into(),
-        };
+        // Track the request until ActionController acknowledges it
+        let request = PendingRequest::new(&scenario_name, package_name, now_ms());
+        self.persist_pending_request(&request).await;
+        self.pending.lock().await.track(request.clone());
 
-        match sender::_send(reconcile_request).await {
+        self.send_reconcile_request(&request).await
+    }
+
+    /// Sends a tracked reconcile request to ActionController
+    ///
+    /// A failed send is left to the pending request sweep to retry.
+    async fn send_reconcile_request(
+        &self,
+        request: &PendingRequest,
+    ) -> std::result::Result<(), String> {
+        match sender::_send(request.to_reconcile_request()).await {
             Ok(response) => {
-                logd!(
-                    2,
-                    "      Successfully sent reconcile request for scenario: {}",
-                    scenario_name
+                debug!("      Successfully sent reconcile request for scenario: {} (transaction: {}, attempt: {})",
+                    request.scenario_name,
+                    request.transaction_id,
+                    request.attempts
                 );
-                logd!(
-                    1,
+                trace!(
                     "      ActionController response: status={:?}",
                     response.get_ref().status
                 );
@@ -813,19 +958,124 @@ impl StateManagerManager {
                     "Failed to send reconcile request to ActionController: {:?}",
                     e
                 );
-                logd!(5, "      {}", error_msg);
+                error!("      {}", error_msg);
                 Err(error_msg)
             }
         }
     }
 
+    /// Processes an acknowledgement of a reconcile request from ActionController
+    ///
+    /// Acknowledgements of unknown or already acknowledged requests are
+    /// ignored, so a repeated ack has no effect.
+    ///
+    /// # Arguments
+    /// * `ack` - Ack message with the transaction ID and outcome of the request
+    async fn process_ack(&self, ack: Ack) {
+        let acknowledged = self.pending.lock().await.acknowledge(&ack.transaction_id);
+        let Some(request) = acknowledged else {
+            trace!(
+                "  Ignoring ack for unknown or already acknowledged transaction: {}",
+                ack.transaction_id
+            );
+            return;
+        };
+
+        if let Err(e) = common::etcd::delete(&keys::pending(&request.transaction_id)).await {
+            warn!("    Failed to delete pending request: {:?}", e);
+        }
+
+        if ack.success {
+            debug!(
+                "  Reconcile of scenario {} acknowledged by {}: {}",
+                request.scenario_name, ack.source, ack.details
+            );
+        } else {
+            self.escalate_request(&request, &ack.details).await;
+        }
+    }
+
+    /// Sends again the reconcile requests whose acknowledgement timed out
+    /// and escalates those that ran out of attempts
+    async fn sweep_pending_requests(&self) {
+        let sweep = self.pending.lock().await.sweep(now_ms());
+
+        for request in sweep.retry {
+            warn!(
+                "  No ack for transaction {} - sending reconcile request again",
+                request.transaction_id
+            );
+            self.persist_pending_request(&request).await;
+            let _ = self.send_reconcile_request(&request).await;
+        }
+
+        for request in sweep.escalate {
+            if let Err(e) = common::etcd::delete(&keys::pending(&request.transaction_id)).await {
+                warn!("    Failed to delete pending request: {:?}", e);
+            }
+            let reason = format!("no acknowledgement after {} attempts", request.attempts);
+            self.escalate_request(&request, &reason).await;
+        }
+    }
+
+    /// Puts the package of a reconcile request that could not be carried out
+    /// into the error state
+    async fn escalate_request(&self, request: &PendingRequest, reason: &str) {
+        error!(
+            "  Reconcile of scenario {} failed (transaction: {}): {}",
+            request.scenario_name, request.transaction_id, reason
+        );
+        if let Err(e) = self
+            .save_package_state_to_etcd(&request.package_name, PackageState::Error)
+            .await
+        {
+            error!(
+                "    Failed to escalate package {}: {}",
+                request.package_name, e
+            );
+        }
+    }
+
+    /// Stores a pending request under `StateManager/pending/<transaction_id>`
+    async fn persist_pending_request(&self, request: &PendingRequest) {
+        let value = match serde_json::to_string(request) {
+            Ok(value) => value,
+            Err(e) => {
+                warn!("    Failed to serialize pending request: {:?}", e);
+                return;
+            }
+        };
+        if let Err(e) = common::etcd::put(&keys::pending(&request.transaction_id), &value).await {
+            warn!("    Failed to persist pending request: {:?}", e);
+        }
+    }
+
+    /// Loads the pending requests stored before a restart
+    async fn restore_pending_requests(&self) {
+        let stored = match common::etcd::get_all_with_prefix(keys::PENDING_PREFIX).await {
+            Ok(stored) => stored,
+            Err(e) => {
+                warn!("Failed to load pending requests: {:?}", e);
+                return;
+            }
+        };
+
+        let mut pending = self.pending.lock().await;
+        for (key, value) in stored {
+            match serde_json::from_str::<PendingRequest>(&value) {
+                Ok(request) => pending.track(request),
+                Err(e) => warn!("Ignoring malformed pending request {}: {:?}", key, e),
+            }
+        }
+    }
+
     /// Find scenario that contains the given package
     async fn find_scenario_for_package(
         &self,
         package_name: &str,
     ) -> std::result::Result<Option<String>, String> {
         // Get all scenarios from ETCD
-        match common::etcd::get_all_with_prefix("Scenario/").await {
+        match common::etcd::get_all_with_prefix(keys::SCENARIO_PREFIX).await {
             Ok(scenario_entries) => {
                 for kv in scenario_entries {
                     match serde_yaml::from_str::<common::spec::artifact::Scenario>(&kv.1) {
@@ -836,14 +1086,14 @@ impl StateManagerManager {
                             }
                         }
                         Err(e) => {
-                            logd!(4, "      Failed to parse scenario {}: {:?}", kv.0, e);
+                            warn!("      Failed to parse scenario {}: {:?}", kv.0, e);
                         }
                     }
                 }
                 Ok(None) // No scenario found containing this package
             }
             Err(e) => {
-                logd!(4, "      Failed to get scenarios from ETCD: {:?}", e);
+                warn!("      Failed to get scenarios from ETCD: {:?}", e);
                 Err(format!("Failed to get scenarios from ETCD: {:?}", e))
             }
         }
@@ -888,15 +1138,12 @@ impl StateManagerManager {
                         }
                         None => {
                             // Channel closed - graceful shutdown
-                            logd!(
-                                4,
-                                "Container channel closed - shutting down container processing"
-                            );
+                            warn!("Container channel closed - shutting down container processing");
                             break;
                         }
                     }
                 }
-                logd!(4, "ContainerList processing task stopped");
+                warn!("ContainerList processing task stopped");
             })
         };
 
@@ -913,33 +1160,66 @@ impl StateManagerManager {
                         rx.recv().await
                     };
                     match state_change_opt {
-                        Some(state_change) => {
+                        Some(state_change) if state_change.trace_id.is_empty() => {
                             // Process state change with comprehensive PICCOLO compliance
                             state_manager.process_state_change(state_change).await;
                         }
+                        Some(state_change) => {
+                            // Continue the trace of the request that queued the change
+                            let trace_id = state_change.trace_id.clone();
+                            common::trace::scope(
+                                trace_id,
+                                state_manager.process_state_change(state_change),
+                            )
+                            .await;
+                        }
                         None => {
                             // Channel closed - graceful shutdown
-                            logd!(
-                                4,
-                                "StateChange channel closed - shutting down state processing"
-                            );
+                            warn!("StateChange channel closed - shutting down state processing");
                             break;
                         }
                     }
                 }
-                logd!(4, "StateChange processing task stopped");
+                warn!("StateChange processing task stopped");
+            })
+        };
+
+        // ========================================
+        // ACKNOWLEDGEMENT PROCESSING TASK
+        // ========================================
+        // Handles Acks from ActionController and periodically resends or
+        // escalates reconcile requests that were never acknowledged
+        let ack_task = {
+            let state_manager = self.clone_for_task();
+            let rx_ack = Arc::clone(&self.rx_ack);
+            tokio::spawn(async move {
+                let mut sweep = tokio::time::interval(SWEEP_INTERVAL);
+                loop {
+                    tokio::select! {
+                        ack_opt = async { rx_ack.lock().await.recv().await } => match ack_opt {
+                            Some(ack) => state_manager.process_ack(ack).await,
+                            None => {
+                                // Channel closed - graceful shutdown
+                                warn!("Ack channel closed - shutting down ack processing");
+                                break;
+                            }
+                        },
+                        _ = sweep.tick() => state_manager.sweep_pending_requests().await,
+                    }
+                }
+                warn!("Ack processing task stopped");
             })
         };
 
-        // Wait for both tasks to complete (typically on shutdown)
-        let result = tokio::try_join!(container_task, state_change_task);
+        // Wait for all tasks to complete (typically on shutdown)
+        let result = tokio::try_join!(container_task, state_change_task, ack_task);
         match result {
             Ok(_) => {
-                logd!(3, "All processing tasks completed successfully");
+                info!("All processing tasks completed successfully");
                 Ok(())
             }
             Err(e) => {
-                logd!(4, "Error in processing tasks: {e:?}");
+                warn!("Error in processing tasks: {e:?}");
                 Err(e.into())
             }
         }
@@ -957,6 +1237,9 @@ impl StateManagerManager {
             state_machine: Arc::clone(&self.state_machine),
             rx_container: Arc::clone(&self.rx_container),
             rx_state_change: Arc::clone(&self.rx_state_change),
+            rx_ack: Arc::clone(&self.rx_ack),
+            pending: Arc::clone(&self.pending),
+            events: self.events.clone(),
         }
     }
 
@@ -986,7 +1269,7 @@ impl StateManagerManager {
         // Spawn the main gRPC processing task
         let grpc_processor = tokio::spawn(async move {
             if let Err(e) = grpc_manager.process_grpc_requests().await {
-                logd!(5, "Error in gRPC processor: {e:?}");
+                error!("Error in gRPC processor: {e:?}");
             }
         });
 
@@ -994,26 +1277,31 @@ impl StateManagerManager {
         let result = grpc_processor.await;
         match result {
             Ok(_) => {
-                logd!(4, "StateManagerManager stopped gracefully");
+                warn!("StateManagerManager stopped gracefully");
                 Ok(())
             }
             Err(e) => {
-                logd!(5, "StateManagerManager stopped with error: {e:?}");
+                error!("StateManagerManager stopped with error: {e:?}");
                 Err(e.into())
             }
         }
     }
 }
 
+/// Current time in milliseconds since the Unix epoch
+fn now_ms() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis() as u64
+}
+
 /// Async action executor - runs in separate task
 ///
 /// This function handles the execution of actions triggered by state transitions.
 /// Actions are executed asynchronously to ensure state transitions remain fast and non-blocking.
 pub async fn run_action_executor(mut receiver: mpsc::UnboundedReceiver<ActionCommand>) {
-    logd!(
-        3,
-        "Action executor started - processing actions asynchronously"
-    );
+    info!("Action executor started - processing actions asynchronously");
 
     while let Some(action_command) = receiver.recv().await {
         // Execute action asynchronously without blocking state transitions
@@ -1022,199 +1310,170 @@ pub async fn run_action_executor(mut receiver: mpsc::UnboundedReceiver<ActionCom
         });
     }
 
-    logd!(4, "Action executor stopped");
+    warn!("Action executor stopped");
 }
 
 /// Execute individual action asynchronously
 async fn execute_action(command: ActionCommand) {
-    logd!(
-        3,
+    info!(
         " Executing action: {} for resource: {}",
-        command.action,
-        command.resource_key
+        command.action, command.resource_key
     );
 
     match command.action.as_str() {
         "start_condition_evaluation" => {
-            logd!(
-                2,
+            debug!(
                 " Starting condition evaluation for scenario: {}",
                 command.resource_key
             );
             // Would integrate with policy engine or condition evaluator
         }
         "start_policy_verification" => {
-            logd!(
-                2,
+            debug!(
                 " Starting policy verification for scenario: {}",
                 command.resource_key
             );
             // Would integrate with policy manager
         }
         "execute_action_on_target_package" => {
-            logd!(
-                2,
+            debug!(
                 " Executing action on target package for scenario: {}",
                 command.resource_key
             );
             // Would trigger package operations
         }
         "log_denial_generate_alert" => {
-            logd!(
-                2,
+            debug!(
                 " Logging denial and generating alert for scenario: {}",
                 command.resource_key
             );
             // Would integrate with alerting system
         }
         "start_model_creation_allocate_resources" => {
-            logd!(
-                2,
+            debug!(
                 " Starting model creation and resource allocation for package: {}",
                 command.resource_key
             );
             // Would integrate with resource allocation system
         }
         "update_state_announce_availability" => {
-            logd!(
-                2,
+            debug!(
                 " Updating state and announcing availability for: {}",
                 command.resource_key
             );
             // Would update service discovery and announce availability
         }
         "log_warning_activate_partial_functionality" => {
-            logd!(
-                2,
+            debug!(
                 " Logging warning and activating partial functionality for: {}",
                 command.resource_key
             );
             // Would configure degraded mode operation
         }
         "log_error_attempt_recovery" => {
-            logd!(
-                2,
+            debug!(
                 " Logging error and attempting recovery for: {}",
                 command.resource_key
             );
             // Would trigger automated recovery procedures
         }
         "pause_models_preserve_state" => {
-            logd!(
-                2,
+            debug!(
                 " Pausing models and preserving state for: {}",
                 command.resource_key
             );
             // Would pause container execution and save state
         }
         "resume_models_restore_state" => {
-            logd!(
-                2,
+            debug!(
                 " Resuming models and restoring state for: {}",
                 command.resource_key
             );
             // Would resume container execution and restore state
         }
         "start_node_selection_and_allocation" => {
-            logd!(
-                2,
+            debug!(
                 " Starting node selection and allocation for model: {}",
                 command.resource_key
             );
             // Would integrate with scheduler for node allocation
         }
         "pull_container_images_mount_volumes" => {
-            logd!(
-                2,
+            debug!(
                 " Pulling container images and mounting volumes for model: {}",
                 command.resource_key
             );
             // Would trigger container image pulls and volume mounts
         }
         "update_state_start_readiness_checks" => {
-            logd!(
-                2,
+            debug!(
                 " Updating state and starting readiness checks for model: {}",
                 command.resource_key
             );
             // Would start health/readiness checks
         }
         "log_completion_clean_up_resources" => {
-            logd!(
-                2,
+            debug!(
                 " Logging completion and cleaning up resources for model: {}",
                 command.resource_key
             );
             // Would clean up completed job resources
         }
         "set_backoff_timer_collect_logs" => {
-            logd!(
-                2,
+            debug!(
                 " Setting backoff timer and collecting logs for model: {}",
                 command.resource_key
             );
             // Would set exponential backoff and collect diagnostic logs
         }
         "attempt_diagnostics_restore_communication" => {
-            logd!(
-                2,
+            debug!(
                 " Attempting diagnostics and restoring communication for model: {}",
                 command.resource_key
             );
             // Would run diagnostic checks and restore node communication
         }
         "resume_monitoring_reset_counter" => {
-            logd!(
-                2,
+            debug!(
                 " Resuming monitoring and resetting counter for model: {}",
                 command.resource_key
             );
             // Would resume monitoring and reset failure counters
         }
         "log_error_notify_for_manual_intervention" => {
-            logd!(
-                2,
+            debug!(
                 " Logging error and notifying for manual intervention for model: {}",
                 command.resource_key
             );
             // Would log critical error and notify operations team
         }
         "synchronize_state_recover_if_needed" => {
-            logd!(
-                2,
+            debug!(
                 " Synchronizing state and recovering if needed for model: {}",
                 command.resource_key
             );
             // Would synchronize state and trigger recovery if necessary
         }
         "start_model_recreation" => {
-            logd!(
-                2,
-                " Starting model recreation for: {}",
-                command.resource_key
-            );
+            debug!(" Starting model recreation for: {}", command.resource_key);
             // Would start complete model recreation process
         }
         _ => {
-            logd!(
-                4,
+            warn!(
                 " Unknown action: {} for resource: {}",
-                command.action,
-                command.resource_key
+                command.action, command.resource_key
             );
         }
     }
 
     // Print context information if available
     if !command.context.is_empty() {
-        logd!(2, "    Context: {:?}", command.context);
+        debug!("    Context: {:?}", command.context);
     }
 
-    logd!(
-        2,
+    debug!(
         "  ✓ Action '{}' completed for: {}",
-        command.action,
-        command.resource_key
+        command.action, command.resource_key
     );
 }
 
@@ -1278,8 +1537,10 @@ mod integration_tests {
         action_controller_connection_server::{
             ActionControllerConnection, ActionControllerConnectionServer,
         },
-        CompleteNetworkSettingRequest, CompleteNetworkSettingResponse, ReconcileRequest,
-        ReconcileResponse, TriggerActionRequest, TriggerActionResponse,
+        CancelScheduledRequest, CancelScheduledResponse, CompleteNetworkSettingRequest,
+        CompleteNetworkSettingResponse, DrainNodeRequest, DrainNodeResponse, ReconcileRequest,
+        ReconcileResponse, TriggerActionRequest, TriggerActionResponse, UncordonNodeRequest,
+        UncordonNodeResponse,
     };
     use std::sync::Arc;
     use tonic::{transport::Server, Request, Response, Status};
@@ -1341,6 +1602,27 @@ mod integration_tests {
                 acknowledged: true,
             }))
         }
+
+        async fn drain_node(
+            &self,
+            _request: Request<DrainNodeRequest>,
+        ) -> std::result::Result<Response<DrainNodeResponse>, Status> {
+            Ok(Response::new(DrainNodeResponse::default()))
+        }
+
+        async fn uncordon_node(
+            &self,
+            _request: Request<UncordonNodeRequest>,
+        ) -> std::result::Result<Response<UncordonNodeResponse>, Status> {
+            Ok(Response::new(UncordonNodeResponse::default()))
+        }
+
+        async fn cancel_scheduled(
+            &self,
+            _request: Request<CancelScheduledRequest>,
+        ) -> std::result::Result<Response<CancelScheduledResponse>, Status> {
+            Ok(Response::new(CancelScheduledResponse::default()))
+        }
     }
 
     #[tokio::test]
@@ -1408,8 +1690,10 @@ spec:
         // Create StateManager and test the reconcile communication
         let (tx_container, rx_container) = tokio::sync::mpsc::channel(100);
         let (tx_state_change, rx_state_change) = tokio::sync::mpsc::channel(100);
+        let (_tx_ack, rx_ack) = tokio::sync::mpsc::channel(1);
 
-        let mut state_manager = StateManagerManager::new(rx_container, rx_state_change).await;
+        let mut state_manager =
+            StateManagerManager::new(rx_container, rx_state_change, rx_ack).await;
         state_manager
             .initialize()
             .await
@@ -1457,8 +1741,10 @@ spec:
         // Test with package that doesn't have an associated scenario
         let (tx_container, rx_container) = tokio::sync::mpsc::channel(100);
         let (tx_state_change, rx_state_change) = tokio::sync::mpsc::channel(100);
+        let (_tx_ack, rx_ack) = tokio::sync::mpsc::channel(1);
 
-        let mut state_manager = StateManagerManager::new(rx_container, rx_state_change).await;
+        let mut state_manager =
+            StateManagerManager::new(rx_container, rx_state_change, rx_ack).await;
         state_manager
             .initialize()
             .await
@@ -1493,8 +1779,9 @@ mod unit_tests {
         let (tx_container, rx_container) = mpsc::channel::<ContainerList>(1);
         let (tx_state_change, rx_state_change) =
             mpsc::channel::<common::statemanager::StateChange>(1);
+        let (_tx_ack, rx_ack) = tokio::sync::mpsc::channel(1);
 
-        let manager = StateManagerManager::new(rx_container, rx_state_change).await;
+        let manager = StateManagerManager::new(rx_container, rx_state_change, rx_ack).await;
 
         let mut annotation = HashMap::new();
         annotation.insert("model".to_string(), "group-model".to_string());
@@ -1507,6 +1794,7 @@ mod unit_tests {
             config: HashMap::new(),
             annotation: annotation.clone(),
             stats: HashMap::new(),
+            usage: None,
         };
 
         let c2 = common::monitoringserver::ContainerInfo {
@@ -1517,6 +1805,7 @@ mod unit_tests {
             config: HashMap::new(),
             annotation: annotation.clone(),
             stats: HashMap::new(),
+            usage: None,
         };
 
         let containers = vec![c1.clone(), c2.clone()];
@@ -1565,8 +1854,9 @@ mod unit_tests {
         let (tx_container, rx_container) = mpsc::channel::<ContainerList>(1);
         let (tx_state_change, rx_state_change) =
             mpsc::channel::<common::statemanager::StateChange>(1);
+        let (_tx_ack, rx_ack) = tokio::sync::mpsc::channel(1);
 
-        let manager = StateManagerManager::new(rx_container, rx_state_change).await;
+        let manager = StateManagerManager::new(rx_container, rx_state_change, rx_ack).await;
 
         let container = ContainerInfo {
             id: "cnone".to_string(),
@@ -1576,6 +1866,7 @@ mod unit_tests {
             config: HashMap::new(),
             annotation: HashMap::new(),
             stats: HashMap::new(),
+            usage: None,
         };
 
         let extracted = manager.extract_model_name_from_container(&container).await;
@@ -1587,8 +1878,9 @@ mod unit_tests {
         let (tx_container, rx_container) = mpsc::channel::<ContainerList>(1);
         let (tx_state_change, rx_state_change) =
             mpsc::channel::<common::statemanager::StateChange>(1);
+        let (_tx_ack, rx_ack) = tokio::sync::mpsc::channel(1);
 
-        let manager = StateManagerManager::new(rx_container, rx_state_change).await;
+        let manager = StateManagerManager::new(rx_container, rx_state_change, rx_ack).await;
 
         let mut ann1 = HashMap::new();
         ann1.insert("model".to_string(), "m1".to_string());
@@ -1600,6 +1892,7 @@ mod unit_tests {
             config: HashMap::new(),
             annotation: ann1,
             stats: HashMap::new(),
+            usage: None,
         };
 
         let mut ann2 = HashMap::new();
@@ -1612,6 +1905,7 @@ mod unit_tests {
             config: HashMap::new(),
             annotation: ann2,
             stats: HashMap::new(),
+            usage: None,
         };
 
         let containers = vec![c1.clone(), c2.clone()];
@@ -1648,8 +1942,9 @@ mod unit_tests {
         let (tx_container, rx_container) = mpsc::channel::<ContainerList>(1);
         let (tx_state_change, rx_state_change) =
             mpsc::channel::<common::statemanager::StateChange>(1);
+        let (_tx_ack, rx_ack) = tokio::sync::mpsc::channel(1);
 
-        let manager = StateManagerManager::new(rx_container, rx_state_change).await;
+        let manager = StateManagerManager::new(rx_container, rx_state_change, rx_ack).await;
         let cloned = manager.clone_for_task();
 
         // The internal Arcs should point to the same allocation
@@ -1700,8 +1995,9 @@ mod unit_tests {
         let (tx_container, rx_container) = mpsc::channel::<ContainerList>(1);
         let (tx_state_change, rx_state_change) =
             mpsc::channel::<common::statemanager::StateChange>(1);
+        let (_tx_ack, rx_ack) = tokio::sync::mpsc::channel(1);
 
-        let manager = StateManagerManager::new(rx_container, rx_state_change).await;
+        let manager = StateManagerManager::new(rx_container, rx_state_change, rx_ack).await;
         let containers: Vec<common::monitoringserver::ContainerInfo> = vec![];
         let grouped = manager.group_containers_by_model(&containers).await;
         assert!(grouped.is_empty());
@@ -1749,7 +2045,8 @@ mod unit_tests {
         let (tx_container, rx_container) = mpsc::channel::<ContainerList>(1);
         let (tx_state_change, rx_state_change) =
             mpsc::channel::<common::statemanager::StateChange>(1);
-        let manager = StateManagerManager::new(rx_container, rx_state_change).await;
+        let (_tx_ack, rx_ack) = tokio::sync::mpsc::channel(1);
+        let manager = StateManagerManager::new(rx_container, rx_state_change, rx_ack).await;
 
         let dummy_change = StateChange {
             resource_type: common::statemanager::ResourceType::Model as i32,
@@ -1759,6 +2056,7 @@ mod unit_tests {
             transition_id: "tid".to_string(),
             source: "test".to_string(),
             timestamp_ns: 0,
+            trace_id: String::new(),
         };
 
         use common::statemanager::ErrorCode;
@@ -1790,8 +2088,9 @@ mod unit_tests {
         let (tx_container, rx_container) = mpsc::channel::<ContainerList>(1);
         let (tx_state_change, rx_state_change) =
             mpsc::channel::<common::statemanager::StateChange>(1);
+        let (_tx_ack, rx_ack) = tokio::sync::mpsc::channel(1);
 
-        let manager = StateManagerManager::new(rx_container, rx_state_change).await;
+        let manager = StateManagerManager::new(rx_container, rx_state_change, rx_ack).await;
 
         let mut ann = HashMap::new();
         ann.insert("model".to_string(), "mtest".to_string());
@@ -1804,6 +2103,7 @@ mod unit_tests {
             config: HashMap::new(),
             annotation: ann,
             stats: HashMap::new(),
+            usage: None,
         };
 
         let cl = ContainerList {
@@ -1820,8 +2120,9 @@ mod unit_tests {
         let (tx_container, rx_container) = mpsc::channel::<ContainerList>(1);
         let (tx_state_change, rx_state_change) =
             mpsc::channel::<common::statemanager::StateChange>(1);
+        let (_tx_ack, rx_ack) = tokio::sync::mpsc::channel(1);
 
-        let manager = StateManagerManager::new(rx_container, rx_state_change).await;
+        let manager = StateManagerManager::new(rx_container, rx_state_change, rx_ack).await;
 
         // Use an invalid numeric resource type
         let bad = StateChange {
@@ -1832,18 +2133,51 @@ mod unit_tests {
             transition_id: "t".to_string(),
             source: "s".to_string(),
             timestamp_ns: 0,
+            trace_id: String::new(),
         };
 
         manager.process_state_change(bad).await;
     }
 
+    #[tokio::test]
+    async fn test_forced_state_change_is_not_applied_again() {
+        let (_tx_container, rx_container) = mpsc::channel::<ContainerList>(1);
+        let (_tx_state_change, rx_state_change) = mpsc::channel::<StateChange>(1);
+        let (_tx_ack, rx_ack) = mpsc::channel(1);
+        let manager = StateManagerManager::new(rx_container, rx_state_change, rx_ack).await;
+
+        manager
+            .process_state_change(StateChange {
+                resource_type: ResourceType::Model as i32,
+                resource_name: "bms".to_string(),
+                current_state: "RUNNING".to_string(),
+                target_state: "DEAD".to_string(),
+                transition_id: "fault_bms_1".to_string(),
+                source: FAULT_INJECTED_SOURCE.to_string(),
+                timestamp_ns: 1,
+                trace_id: String::new(),
+            })
+            .await;
+
+        // The injector forces the state; the engine must not run the
+        // transition through the state machine a second time
+        let state_machine = manager.state_machine.lock().await;
+        assert!(state_machine
+            .transition_history(ResourceType::Model, "bms")
+            .is_empty());
+        assert!(state_machine
+            .get_resource_state("bms", ResourceType::Model)
+            .is_none());
+    }
+
     #[tokio::test]
     async fn test_save_model_and_package_state_to_etcd_success() {
         let (tx_container, rx_container) = mpsc::channel::<ContainerList>(1);
         let (tx_state_change, rx_state_change) =
             mpsc::channel::<common::statemanager::StateChange>(1);
+        let (_tx_ack, rx_ack) = tokio::sync::mpsc::channel(1);
 
-        let manager = StateManagerManager::new(rx_container, rx_state_change).await;
+        let manager = StateManagerManager::new(rx_container, rx_state_change, rx_ack).await;
 
         // Attempt to save a model state (success path)
         let res = manager
@@ -1871,8 +2205,9 @@ mod unit_tests {
         let (tx_container, rx_container) = mpsc::channel::<ContainerList>(1);
         let (tx_state_change, rx_state_change) =
             mpsc::channel::<common::statemanager::StateChange>(1);
+        let (_tx_ack, rx_ack) = tokio::sync::mpsc::channel(1);
 
-        let manager = StateManagerManager::new(rx_container, rx_state_change).await;
+        let manager = StateManagerManager::new(rx_container, rx_state_change, rx_ack).await;
 
         // Create an excessively long model name to force an ETCD key length validation error
         let long_name = "a".repeat(2000);
@@ -1892,8 +2227,9 @@ mod unit_tests {
         let (tx_container, rx_container) = mpsc::channel::<ContainerList>(1);
         let (tx_state_change, rx_state_change) =
             mpsc::channel::<common::statemanager::StateChange>(1);
+        let (_tx_ack, rx_ack) = tokio::sync::mpsc::channel(1);
 
-        let manager = StateManagerManager::new(rx_container, rx_state_change).await;
+        let manager = StateManagerManager::new(rx_container, rx_state_change, rx_ack).await;
 
         // Create an excessively long package name to force an ETCD key length validation error
         let long_name = "b".repeat(2000);
@@ -1913,8 +2249,9 @@ mod unit_tests {
         let (tx_container, rx_container) = mpsc::channel::<ContainerList>(1);
         let (tx_state_change, rx_state_change) =
             mpsc::channel::<common::statemanager::StateChange>(1);
+        let (_tx_ack, rx_ack) = tokio::sync::mpsc::channel(1);
 
-        let manager = StateManagerManager::new(rx_container, rx_state_change).await;
+        let manager = StateManagerManager::new(rx_container, rx_state_change, rx_ack).await;
 
         // Use a package name unlikely to have a scenario mapping in ETCD
         let res = manager
@@ -1931,8 +2268,9 @@ mod unit_tests {
         let (tx_container, rx_container) = tokio::sync::mpsc::channel::<ContainerList>(10);
         let (tx_state_change, rx_state_change) =
             tokio::sync::mpsc::channel::<common::statemanager::StateChange>(10);
+        let (tx_ack, rx_ack) = tokio::sync::mpsc::channel(1);
 
-        let manager = StateManagerManager::new(rx_container, rx_state_change).await;
+        let manager = StateManagerManager::new(rx_container, rx_state_change, rx_ack).await;
 
         // Spawn the processing loop (map result to unit so the spawned future is Send)
         let mgr = manager.clone_for_task();
@@ -1958,6 +2296,7 @@ mod unit_tests {
             transition_id: "t1".to_string(),
             source: "test".to_string(),
             timestamp_ns: 0,
+            trace_id: String::new(),
         };
 
         tx_state_change
@@ -1968,19 +2307,47 @@ mod unit_tests {
         // Close senders so loop exits
         drop(tx_container);
         drop(tx_state_change);
+        drop(tx_ack);
 
         // Wait for the processing tasks to finish (with timeout)
         let res = tokio::time::timeout(std::time::Duration::from_secs(2), handle).await;
         assert!(res.is_ok(), "process_grpc_requests did not finish in time");
     }
 
+    #[tokio::test]
+    async fn test_duplicate_acks_are_ignored() {
+        let (_tx_container, rx_container) = mpsc::channel::<ContainerList>(1);
+        let (_tx_state_change, rx_state_change) =
+            mpsc::channel::<common::statemanager::StateChange>(1);
+        let (_tx_ack, rx_ack) = mpsc::channel::<Ack>(1);
+
+        let manager = StateManagerManager::new(rx_container, rx_state_change, rx_ack).await;
+        let request = PendingRequest::new("ack-scenario", "ack-package", now_ms());
+        let id = request.transaction_id.clone();
+        manager.pending.lock().await.track(request);
+
+        let ack = Ack {
+            transaction_id: id.clone(),
+            success: true,
+            details: "Reconciliation completed successfully".to_string(),
+            source: "actioncontroller".to_string(),
+        };
+        manager.process_ack(ack.clone()).await;
+        assert!(!manager.pending.lock().await.contains(&id));
+
+        // A repeated ack finds nothing to acknowledge and changes nothing
+        manager.process_ack(ack).await;
+        assert!(!manager.pending.lock().await.contains(&id));
+    }
+
     #[tokio::test]
     async fn test_manager_process_state_change_scenario_saves_etcd() {
         let (tx_container, rx_container) = mpsc::channel::<ContainerList>(1);
         let (tx_state_change, rx_state_change) =
             mpsc::channel::<common::statemanager::StateChange>(1);
+        let (_tx_ack, rx_ack) = tokio::sync::mpsc::channel(1);
 
-        let manager = StateManagerManager::new(rx_container, rx_state_change).await;
+        let manager = StateManagerManager::new(rx_container, rx_state_change, rx_ack).await;
 
         // Build a valid Scenario state change Idle -> Waiting
         let sc = StateChange {
@@ -1991,6 +2358,7 @@ mod unit_tests {
             transition_id: "t-etcd".to_string(),
             timestamp_ns: 1,
             source: "unittest".to_string(),
+            trace_id: String::new(),
         };
 
         manager.process_state_change(sc.clone()).await;
@@ -2008,8 +2376,9 @@ mod unit_tests {
         let (tx_container, rx_container) = mpsc::channel::<ContainerList>(1);
         let (tx_state_change, rx_state_change) =
             mpsc::channel::<common::statemanager::StateChange>(1);
+        let (_tx_ack, rx_ack) = tokio::sync::mpsc::channel(1);
 
-        let manager = StateManagerManager::new(rx_container, rx_state_change).await;
+        let manager = StateManagerManager::new(rx_container, rx_state_change, rx_ack).await;
 
         // Ensure no packages exist for this test model
         let _ = common::etcd::delete("Package/no-packages").await;
@@ -2025,8 +2394,9 @@ mod unit_tests {
         let (tx_container, rx_container) = mpsc::channel::<ContainerList>(1);
         let (tx_state_change, rx_state_change) =
             mpsc::channel::<common::statemanager::StateChange>(1);
+        let (_tx_ack, rx_ack) = tokio::sync::mpsc::channel(1);
 
-        let manager = StateManagerManager::new(rx_container, rx_state_change).await;
+        let manager = StateManagerManager::new(rx_container, rx_state_change, rx_ack).await;
 
         // Create a package with a single model that is Dead -> package should become Error
         let pkg_key = "Package/pkg-update";
@@ -2052,8 +2422,9 @@ mod unit_tests {
         let (tx_container, rx_container) = mpsc::channel::<ContainerList>(1);
         let (tx_state_change, rx_state_change) =
             mpsc::channel::<common::statemanager::StateChange>(1);
+        let (_tx_ack, rx_ack) = tokio::sync::mpsc::channel(1);
 
-        let manager = StateManagerManager::new(rx_container, rx_state_change).await;
+        let manager = StateManagerManager::new(rx_container, rx_state_change, rx_ack).await;
 
         // Ensure no scenarios present
         let _ = common::etcd::delete("Scenario/nonexistent").await;
@@ -2069,10 +2440,128 @@ mod unit_tests {
         let (tx_container, rx_container) = mpsc::channel::<ContainerList>(1);
         let (tx_state_change, rx_state_change) =
             mpsc::channel::<common::statemanager::StateChange>(1);
+        let (_tx_ack, rx_ack) = tokio::sync::mpsc::channel(1);
 
-        let mut manager = StateManagerManager::new(rx_container, rx_state_change).await;
+        let mut manager = StateManagerManager::new(rx_container, rx_state_change, rx_ack).await;
         // initialize should start the async action executor without error
         let res = manager.initialize().await;
         assert!(res.is_ok());
     }
+
+    /// Reliable reader of the state events on `topic_name`
+    fn subscribe_state_events(
+        topic_name: &str,
+        domain_id: i32,
+    ) -> dust_dds::subscription::data_reader::DataReader<PiccoloStateEvent> {
+        use dust_dds::domain::domain_participant_factory::DomainParticipantFactory;
+        use dust_dds::infrastructure::qos::{DataReaderQos, QosKind};
+        use dust_dds::infrastructure::qos_policy::{
+            HistoryQosPolicy, HistoryQosPolicyKind, ReliabilityQosPolicy, ReliabilityQosPolicyKind,
+        };
+        use dust_dds::infrastructure::{status::NO_STATUS, time::DurationKind};
+
+        let participant = DomainParticipantFactory::get_instance()
+            .create_participant(domain_id, QosKind::Default, None, NO_STATUS)
+            .unwrap();
+        let subscriber = participant
+            .create_subscriber(QosKind::Default, None, NO_STATUS)
+            .unwrap();
+        let topic = participant
+            .create_topic::<PiccoloStateEvent>(
+                topic_name,
+                "PiccoloStateEvent",
+                QosKind::Default,
+                None,
+                NO_STATUS,
+            )
+            .unwrap();
+        let qos = DataReaderQos {
+            reliability: ReliabilityQosPolicy {
+                kind: ReliabilityQosPolicyKind::Reliable,
+                max_blocking_time: DurationKind::Infinite,
+            },
+            history: HistoryQosPolicy {
+                kind: HistoryQosPolicyKind::KeepAll,
+            },
+            ..Default::default()
+        };
+        subscriber
+            .create_datareader::<PiccoloStateEvent>(&topic, QosKind::Specific(qos), None, NO_STATUS)
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_committed_transitions_published_on_dds() {
+        use dust_dds::subscription::sample_info::{
+            ANY_INSTANCE_STATE, ANY_SAMPLE_STATE, ANY_VIEW_STATE,
+        };
+
+        // Own domain, so parallel DDS tests do not see each other
+        const DOMAIN: i32 = 171;
+        let topic = "PiccoloStateEventLoopback";
+        let reader = subscribe_state_events(topic, DOMAIN);
+        let settings = common::setting::StateManagerSettings {
+            dds_events: true,
+            dds_events_topic: topic.to_string(),
+            dds_events_reliable: true,
+            dds_events_history_depth: 16,
+        };
+
+        let (_tx_container, rx_container) = mpsc::channel::<ContainerList>(1);
+        let (_tx_state_change, rx_state_change) =
+            mpsc::channel::<common::statemanager::StateChange>(1);
+        let (_tx_ack, rx_ack) = mpsc::channel(1);
+        let manager = StateManagerManager::new(rx_container, rx_state_change, rx_ack)
+            .await
+            .with_state_events(StateEventPublisher::start(&settings, DOMAIN));
+
+        let script = [
+            ("idle", "waiting"),
+            ("waiting", "satisfied"),
+            // Not allowed from satisfied: no event
+            ("satisfied", "completed"),
+            ("satisfied", "allowed"),
+        ];
+        for (i, (current, target)) in script.iter().enumerate() {
+            manager
+                .process_state_change(common::statemanager::StateChange {
+                    resource_type: ResourceType::Scenario as i32,
+                    resource_name: "dds-events".to_string(),
+                    current_state: current.to_string(),
+                    target_state: target.to_string(),
+                    transition_id: format!("dds-events-{}", i),
+                    timestamp_ns: i as i64,
+                    source: "filtergateway".to_string(),
+                    trace_id: String::new(),
+                })
+                .await;
+        }
+
+        let mut received = Vec::new();
+        let deadline = std::time::Instant::now() + Duration::from_secs(20);
+        while received.len() < 3 && std::time::Instant::now() < deadline {
+            if let Ok(samples) =
+                reader.take(10, ANY_SAMPLE_STATE, ANY_VIEW_STATE, ANY_INSTANCE_STATE)
+            {
+                received.extend(samples.iter().filter_map(|sample| sample.data().ok()));
+            }
+            tokio::time::sleep(Duration::from_millis(100)).await;
+        }
+
+        let transitions: Vec<(&str, &str)> = received
+            .iter()
+            .map(|event| (event.from.as_str(), event.to.as_str()))
+            .collect();
+        assert_eq!(
+            transitions,
+            vec![
+                ("SCENARIO_STATE_IDLE", "SCENARIO_STATE_WAITING"),
+                ("SCENARIO_STATE_WAITING", "SCENARIO_STATE_SATISFIED"),
+                ("SCENARIO_STATE_SATISFIED", "SCENARIO_STATE_ALLOWED"),
+            ]
+        );
+        assert!(received.iter().all(|event| event.resource == "dds-events"
+            && event.resource_type == "RESOURCE_TYPE_SCENARIO"
+            && event.reason.starts_with("filtergateway: ")));
+    }
 }
@@ -7,6 +7,7 @@
 //!
 //! This module provides the public interface for the StateManager component
 
+pub mod dependency;
 pub mod grpc;
 pub mod manager;
 pub mod state_machine;
@@ -0,0 +1,231 @@
+/*
+* SPDX-FileCopyrightText: Copyright 2024 LG Electronics Inc.
+* SPDX-License-Identifier: Apache-2.0
+*/
+//! Reconcile requests awaiting acknowledgement from ActionController
+//!
+//! Every reconcile request carries a transaction ID that ActionController
+//! acknowledges with the outcome. A request left unacknowledged is sent
+//! again once the ack timeout passes, and escalated once it was sent
+//! `max_attempts` times.
+
+use common::actioncontroller::{PodStatus, ReconcileRequest};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::time::Duration;
+
+/// Time to wait for an acknowledgement before sending a request again
+pub const ACK_TIMEOUT: Duration = Duration::from_secs(10);
+/// Number of sends after which an unacknowledged request is escalated
+pub const MAX_ATTEMPTS: u32 = 3;
+/// Interval between two sweeps for unacknowledged requests
+pub const SWEEP_INTERVAL: Duration = Duration::from_secs(2);
+
+/// A reconcile request sent to ActionController, stored under
+/// `StateManager/pending/<transaction_id>` until it is acknowledged
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PendingRequest {
+    pub transaction_id: String,
+    pub scenario_name: String,
+    /// Package whose state triggered the request
+    pub package_name: String,
+    /// Number of times the request was sent
+    pub attempts: u32,
+    /// Time of the last send, in milliseconds since the Unix epoch
+    pub sent_at_ms: u64,
+}
+
+impl PendingRequest {
+    /// Create a request that is about to be sent for the first time
+    pub fn new(scenario_name: &str, package_name: &str, now_ms: u64) -> Self {
+        Self {
+            transaction_id: format!("reconcile_{}_{}", scenario_name, now_ms),
+            scenario_name: scenario_name.to_string(),
+            package_name: package_name.to_string(),
+            attempts: 1,
+            sent_at_ms: now_ms,
+        }
+    }
+
+    /// Reconcile request to send to ActionController
+    pub fn to_reconcile_request(&self) -> ReconcileRequest {
+        ReconcileRequest {
+            scenario_name: self.scenario_name.clone(),
+            current: PodStatus::Failed.into(),
+            desired: PodStatus::Running.into(),
+            transaction_id: self.transaction_id.clone(),
+        }
+    }
+}
+
+/// Requests found by a sweep
+#[derive(Debug, Default, PartialEq)]
+pub struct Sweep {
+    /// Requests to send again, with their attempt count already raised
+    pub retry: Vec<PendingRequest>,
+    /// Requests that ran out of attempts and are no longer tracked
+    pub escalate: Vec<PendingRequest>,
+}
+
+/// Pending requests by transaction ID
+#[derive(Debug)]
+pub struct PendingTracker {
+    requests: HashMap<String, PendingRequest>,
+    timeout: Duration,
+    max_attempts: u32,
+}
+
+impl Default for PendingTracker {
+    fn default() -> Self {
+        Self::new(ACK_TIMEOUT, MAX_ATTEMPTS)
+    }
+}
+
+impl PendingTracker {
+    /// Create an empty tracker
+    ///
+    /// # Arguments
+    ///
+    /// * `timeout` - Time to wait for an acknowledgement of each send
+    /// * `max_attempts` - Number of sends before a request is escalated
+    pub fn new(timeout: Duration, max_attempts: u32) -> Self {
+        Self {
+            requests: HashMap::new(),
+            timeout,
+            max_attempts,
+        }
+    }
+
+    /// Start tracking a sent request
+    pub fn track(&mut self, request: PendingRequest) {
+        self.requests
+            .insert(request.transaction_id.clone(), request);
+    }
+
+    /// Stop tracking the request acknowledged under `transaction_id`
+    ///
+    /// # Returns
+    ///
+    /// * `Option<PendingRequest>` - The request, or None if it is unknown or
+    ///   was already acknowledged, so repeated acks have no effect
+    pub fn acknowledge(&mut self, transaction_id: &str) -> Option<PendingRequest> {
+        self.requests.remove(transaction_id)
+    }
+
+    /// Whether a request is pending under `transaction_id`
+    pub fn contains(&self, transaction_id: &str) -> bool {
+        self.requests.contains_key(transaction_id)
+    }
+
+    /// Find the requests whose acknowledgement timed out
+    ///
+    /// Timed out requests with attempts left are marked as sent again at
+    /// `now_ms`. The others are dropped from the tracker.
+    ///
+    /// # Arguments
+    ///
+    /// * `now_ms` - Current time in milliseconds since the Unix epoch
+    pub fn sweep(&mut self, now_ms: u64) -> Sweep {
+        let timeout_ms = self.timeout.as_millis() as u64;
+        let mut timed_out: Vec<String> = self
+            .requests
+            .values()
+            .filter(|r| now_ms.saturating_sub(r.sent_at_ms) >= timeout_ms)
+            .map(|r| r.transaction_id.clone())
+            .collect();
+        timed_out.sort();
+
+        let mut sweep = Sweep::default();
+        for id in timed_out {
+            let Some(request) = self.requests.get_mut(&id) else {
+                continue;
+            };
+            if request.attempts >= self.max_attempts {
+                if let Some(request) = self.requests.remove(&id) {
+                    sweep.escalate.push(request);
+                }
+            } else {
+                request.attempts += 1;
+                request.sent_at_ms = now_ms;
+                sweep.retry.push(request.clone());
+            }
+        }
+        sweep
+    }
+}
+
+//Unit Test Cases
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tracker() -> PendingTracker {
+        PendingTracker::new(Duration::from_millis(100), 3)
+    }
+
+    #[test]
+    fn test_request_carries_transaction_id() {
+        let request = PendingRequest::new("antipinch", "antipinch-pkg", 42);
+        let reconcile = request.to_reconcile_request();
+
+        assert_eq!(request.transaction_id, "reconcile_antipinch_42");
+        assert_eq!(reconcile.transaction_id, request.transaction_id);
+        assert_eq!(reconcile.scenario_name, "antipinch");
+        assert_eq!(request.attempts, 1);
+    }
+
+    #[test]
+    fn test_lost_acks_are_retried_then_escalated() {
+        let mut tracker = tracker();
+        let request = PendingRequest::new("antipinch", "antipinch-pkg", 0);
+        let id = request.transaction_id.clone();
+        tracker.track(request);
+
+        // Still within the ack timeout
+        assert_eq!(tracker.sweep(50), Sweep::default());
+
+        let sweep = tracker.sweep(100);
+        assert_eq!(sweep.retry.len(), 1);
+        assert_eq!(sweep.retry[0].attempts, 2);
+        assert_eq!(sweep.retry[0].sent_at_ms, 100);
+        assert!(sweep.escalate.is_empty());
+
+        // The timeout restarts with every send
+        assert_eq!(tracker.sweep(150), Sweep::default());
+        assert_eq!(tracker.sweep(200).retry[0].attempts, 3);
+
+        let sweep = tracker.sweep(300);
+        assert!(sweep.retry.is_empty());
+        assert_eq!(sweep.escalate.len(), 1);
+        assert_eq!(sweep.escalate[0].transaction_id, id);
+        assert!(!tracker.contains(&id));
+        assert_eq!(tracker.sweep(1000), Sweep::default());
+    }
+
+    #[test]
+    fn test_duplicate_acks_are_idempotent() {
+        let mut tracker = tracker();
+        let request = PendingRequest::new("antipinch", "antipinch-pkg", 0);
+        let id = request.transaction_id.clone();
+        tracker.track(request.clone());
+
+        assert_eq!(tracker.acknowledge(&id), Some(request));
+        assert_eq!(tracker.acknowledge(&id), None);
+        assert_eq!(tracker.acknowledge("unknown"), None);
+        // An acknowledged request is neither retried nor escalated
+        assert_eq!(tracker.sweep(1000), Sweep::default());
+    }
+
+    #[test]
+    fn test_pending_request_round_trips_as_json() {
+        let request = PendingRequest::new("antipinch", "antipinch-pkg", 7);
+        let json = serde_json::to_string(&request).unwrap();
+
+        assert!(json.contains("\"transactionId\":\"reconcile_antipinch_7\""));
+        assert_eq!(
+            serde_json::from_str::<PendingRequest>(&json).unwrap(),
+            request
+        );
+    }
+}
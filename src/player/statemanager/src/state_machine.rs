@@ -26,15 +26,17 @@
 //! let result = state_machine.process_state_change(state_change);
 //! ```
 
+use crate::dependency::DependencyGraph;
 use crate::types::{
-    ActionCommand, ContainerState, HealthStatus, ResourceState, StateTransition, TransitionResult,
+    ActionCommand, ContainerState, HealthStatus, ResourceState, StateTransition, TransitionRecord,
+    TransitionResult,
 };
 use common::logd;
 use common::spec::artifact::Artifact;
 use common::statemanager::{
     ErrorCode, ModelState, PackageState, ResourceType, ScenarioState, StateChange,
 };
-use std::collections::HashMap;
+use std::collections::{HashMap, VecDeque};
 use tokio::sync::mpsc;
 use tokio::time::Instant;
 
@@ -45,6 +47,28 @@ use tokio::time::Instant;
 /// Maximum consecutive failures before marking resource as unhealthy
 const MAX_CONSECUTIVE_FAILURES: u32 = 3;
 
+/// Number of transitions kept in the transition history
+const MAX_TRANSITION_HISTORY: usize = 1000;
+
+/// Source of the transitions forced by an injected fault
+pub const FAULT_INJECTED_SOURCE: &str = "fault-injected";
+
+/// Source of the transitions of models blocked by a failed dependency
+pub const DEPENDENCY_SOURCE: &str = "dependency";
+
+/// Metadata key of the models a model depends on, comma separated
+pub const DEPENDS_ON_METADATA: &str = "depends_on";
+
+/// Metadata key of the reason a model is blocked
+pub const BLOCKED_BY_METADATA: &str = "blocked_by";
+
+fn now_ns() -> i64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_nanos() as i64
+}
+
 impl TransitionResult {
     /// Check if the transition was successful
     pub fn is_success(&self) -> bool {
@@ -101,6 +125,16 @@ pub struct StateMachine {
 
     /// Action command sender for async execution
     action_sender: Option<mpsc::UnboundedSender<ActionCommand>>,
+
+    /// Most recent transitions of all resources, oldest first
+    transition_history: VecDeque<TransitionRecord>,
+
+    /// Resources held in a state by an injected fault, with the state to
+    /// restore once the fault is reverted
+    injected_faults: HashMap<String, i32>,
+
+    /// Dependencies between models declared by their packages
+    dependency_graph: DependencyGraph,
 }
 
 impl StateMachine {
@@ -121,6 +155,9 @@ impl StateMachine {
             transition_tables: HashMap::new(),
             resource_states: HashMap::new(),
             action_sender: None,
+            transition_history: VecDeque::new(),
+            injected_faults: HashMap::new(),
+            dependency_graph: DependencyGraph::default(),
         };
 
         // Initialize transition tables for each resource type
@@ -191,6 +228,13 @@ impl StateMachine {
                 condition: None,
                 action: "finalize_scenario".to_string(),
             },
+            StateTransition {
+                from_state: ScenarioState::Completed as i32,
+                event: "scenario_preempted".to_string(),
+                to_state: ScenarioState::Idle as i32,
+                condition: None,
+                action: "release_preempted_workloads".to_string(),
+            },
         ];
         self.transition_tables
             .insert(ResourceType::Scenario, scenario_transitions);
@@ -428,13 +472,33 @@ impl StateMachine {
         containers: &[&common::monitoringserver::ContainerInfo],
     ) -> TransitionResult {
         let resource_key = self.generate_resource_key(ResourceType::Model, model_name);
-        let timestamp_ns = std::time::SystemTime::now()
-            .duration_since(std::time::UNIX_EPOCH)
-            .unwrap_or_default()
-            .as_nanos() as i64;
+        let timestamp_ns = now_ns();
+
+        // An injected fault holds the model state until it is reverted
+        if let Some(state) = self
+            .injected_faults
+            .contains_key(&resource_key)
+            .then(|| self.resource_states.get(&resource_key))
+            .flatten()
+        {
+            return TransitionResult {
+                new_state: state.current_state,
+                error_code: ErrorCode::Success,
+                message: "Model state held by an injected fault".to_string(),
+                actions_to_execute: vec![],
+                transition_id: format!("model_update_{}_{}", model_name, timestamp_ns),
+                error_details: String::new(),
+            };
+        }
 
         // Evaluate the new model state based on container states
-        let new_model_state = self.evaluate_model_state_from_containers(containers);
+        let mut new_model_state = self.evaluate_model_state_from_containers(containers);
+
+        // A model only runs once all its dependencies run
+        let waiting_for = self.unmet_dependencies(model_name);
+        if new_model_state == ModelState::Running && !waiting_for.is_empty() {
+            new_model_state = ModelState::Blocked;
+        }
 
         // Create a pseudo state change for internal processing
         let state_change = StateChange {
@@ -449,6 +513,7 @@ impl StateMachine {
             transition_id: format!("model_update_{}_{}", model_name, timestamp_ns),
             timestamp_ns,
             source: "container_analysis".to_string(),
+            trace_id: String::new(),
         };
 
         // Get current state from existing resource or default to Created
@@ -479,6 +544,12 @@ impl StateMachine {
             target_state,
             ResourceType::Model,
         );
+        if new_model_state == ModelState::Blocked {
+            self.set_blocked_by(
+                &resource_key,
+                format!("waiting for {}", waiting_for.join(", ")),
+            );
+        }
 
         // Return successful transition result indicating state changed
         TransitionResult {
@@ -617,7 +688,7 @@ impl StateMachine {
         package_name: &str,
     ) -> std::result::Result<Vec<(String, common::statemanager::ModelState)>, String> {
         // Get package definition from ETCD to find its models
-        let package_key = format!("Package/{}", package_name);
+        let package_key = common::keys::package(package_name);
         let package_yaml = match common::etcd::get(&package_key).await {
             Ok(yaml) => yaml,
             Err(e) => {
@@ -640,7 +711,7 @@ impl StateMachine {
         // Get state for each model in the package
         for model_info in package.get_models() {
             let model_name = model_info.get_name();
-            let model_state_key = format!("/model/{}/state", model_name);
+            let model_state_key = common::keys::model_state(&model_name);
 
             match common::etcd::get(&model_state_key).await {
                 Ok(state_str) => {
@@ -650,6 +721,7 @@ impl StateMachine {
                         "Exited" => common::statemanager::ModelState::Exited,
                         "Dead" => common::statemanager::ModelState::Dead,
                         "Running" => common::statemanager::ModelState::Running,
+                        "Blocked" => common::statemanager::ModelState::Blocked,
                         _ => common::statemanager::ModelState::Running, // Default to Running
                     };
                     model_states.push((model_name, model_state));
@@ -671,7 +743,7 @@ impl StateMachine {
         let mut packages = Vec::new();
 
         // Get all packages from ETCD with prefix
-        match common::etcd::get_all_with_prefix("Package/").await {
+        match common::etcd::get_all_with_prefix(common::keys::PACKAGE_PREFIX).await {
             Ok(package_entries) => {
                 for kv in package_entries {
                     match serde_yaml::from_str::<common::spec::artifact::Package>(&kv.1) {
@@ -703,7 +775,7 @@ impl StateMachine {
     pub async fn get_current_package_state(
         package_name: &str,
     ) -> Option<common::statemanager::PackageState> {
-        let key = format!("/package/{}/state", package_name);
+        let key = common::keys::package_state(package_name);
         match common::etcd::get(&key).await {
             Ok(state_str) => match state_str.as_str() {
                 "PACKAGE_STATE_IDLE" | "idle" => Some(common::statemanager::PackageState::Idle),
@@ -751,6 +823,7 @@ impl StateMachine {
                     common::statemanager::ModelState::Exited => ModelState::Exited,
                     common::statemanager::ModelState::Dead => ModelState::Dead,
                     common::statemanager::ModelState::Running => ModelState::Running,
+                    common::statemanager::ModelState::Blocked => ModelState::Blocked,
                     _ => ModelState::Running,
                 };
                 (name.clone(), converted_state)
@@ -835,6 +908,7 @@ impl StateMachine {
             ModelState::Exited => "Exited".to_string(),
             ModelState::Dead => "Dead".to_string(),
             ModelState::Running => "Running".to_string(),
+            ModelState::Blocked => "Blocked".to_string(),
             _ => "Unknown".to_string(),
         }
     }
@@ -1038,6 +1112,11 @@ impl StateMachine {
                 {
                     "scenario_completion".to_string()
                 }
+                (x, y)
+                    if x == ScenarioState::Completed as i32 && y == ScenarioState::Idle as i32 =>
+                {
+                    "scenario_preempted".to_string()
+                }
                 _ => format!("transition_{current_state}_{target_state}"),
             },
             ResourceType::Package => match (current_state, target_state) {
@@ -1214,6 +1293,25 @@ impl StateMachine {
         resource_type: ResourceType,
     ) {
         let now = Instant::now();
+        let from_state = self
+            .resource_states
+            .get(resource_key)
+            .map(|rs| rs.current_state)
+            .unwrap_or_else(|| {
+                Self::state_str_to_enum(
+                    state_change.current_state.as_str(),
+                    state_change.resource_type,
+                )
+            });
+        self.record_transition(TransitionRecord {
+            resource_key: resource_key.to_string(),
+            from_state,
+            to_state: new_state,
+            transition_id: state_change.transition_id.clone(),
+            source: state_change.source.clone(),
+            trace_id: state_change.trace_id.clone(),
+            timestamp_ns: now_ns(),
+        });
 
         let resource_state = self
             .resource_states
@@ -1250,12 +1348,268 @@ impl StateMachine {
         resource_state
             .metadata
             .insert("source".to_string(), state_change.source.clone());
+        resource_state.metadata.remove(BLOCKED_BY_METADATA);
+        if resource_type == ResourceType::Model {
+            let depends_on = self
+                .dependency_graph
+                .dependencies(&state_change.resource_name);
+            if depends_on.is_empty() {
+                resource_state.metadata.remove(DEPENDS_ON_METADATA);
+            } else {
+                resource_state
+                    .metadata
+                    .insert(DEPENDS_ON_METADATA.to_string(), depends_on.join(","));
+            }
+        }
+    }
+
+    /// Append `record` to the history, dropping the oldest entry when full
+    fn record_transition(&mut self, record: TransitionRecord) {
+        if self.transition_history.len() == MAX_TRANSITION_HISTORY {
+            self.transition_history.pop_front();
+        }
+        self.transition_history.push_back(record);
+    }
+
+    // ========================================
+    // FAULT INJECTION
+    // ========================================
+
+    /// Force a resource into `fault_state`, bypassing the transition tables
+    ///
+    /// The resource keeps that state until [`Self::revert_fault`], whatever
+    /// its containers report. Injecting a fault into a resource that already
+    /// has one keeps the state to restore from the first fault.
+    ///
+    /// # Parameters
+    /// - `resource_type`: Type of the faulted resource
+    /// - `resource_name`: Name of the faulted resource
+    /// - `fault_state`: State to force
+    /// - `transition_id`: ID recorded in the transition history
+    ///
+    /// # Returns
+    /// - `TransitionResult`: The forced transition, with an `update_etcd` action
+    pub fn inject_fault(
+        &mut self,
+        resource_type: ResourceType,
+        resource_name: &str,
+        fault_state: i32,
+        transition_id: &str,
+    ) -> TransitionResult {
+        let resource_key = self.generate_resource_key(resource_type, resource_name);
+        let current_state = self
+            .resource_states
+            .get(&resource_key)
+            .map(|rs| rs.current_state)
+            // A model nothing was reported for yet is assumed to be running
+            .unwrap_or(match resource_type {
+                ResourceType::Model => ModelState::Running as i32,
+                _ => 0,
+            });
+        self.injected_faults
+            .entry(resource_key.clone())
+            .or_insert(current_state);
+
+        self.force_state(
+            resource_type,
+            resource_name,
+            current_state,
+            fault_state,
+            transition_id,
+            FAULT_INJECTED_SOURCE,
+        )
+    }
+
+    /// Restore the state a resource had before its injected fault
+    ///
+    /// # Returns
+    /// - `Some(TransitionResult)`: The forced transition back
+    /// - `None`: If the resource has no injected fault
+    pub fn revert_fault(
+        &mut self,
+        resource_type: ResourceType,
+        resource_name: &str,
+        transition_id: &str,
+    ) -> Option<TransitionResult> {
+        let resource_key = self.generate_resource_key(resource_type, resource_name);
+        let restored_state = self.injected_faults.remove(&resource_key)?;
+        let current_state = self
+            .resource_states
+            .get(&resource_key)
+            .map(|rs| rs.current_state)
+            .unwrap_or(restored_state);
+
+        Some(self.force_state(
+            resource_type,
+            resource_name,
+            current_state,
+            restored_state,
+            transition_id,
+            FAULT_INJECTED_SOURCE,
+        ))
+    }
+
+    /// Returns `true` if the resource is held by an injected fault
+    pub fn has_injected_fault(&self, resource_type: ResourceType, resource_name: &str) -> bool {
+        self.injected_faults
+            .contains_key(&self.generate_resource_key(resource_type, resource_name))
+    }
+
+    fn force_state(
+        &mut self,
+        resource_type: ResourceType,
+        resource_name: &str,
+        from_state: i32,
+        to_state: i32,
+        transition_id: &str,
+        source: &str,
+    ) -> TransitionResult {
+        let state_change = StateChange {
+            resource_type: resource_type as i32,
+            resource_name: resource_name.to_string(),
+            current_state: self.state_enum_to_str(from_state, resource_type),
+            target_state: self.state_enum_to_str(to_state, resource_type),
+            transition_id: transition_id.to_string(),
+            timestamp_ns: now_ns(),
+            source: source.to_string(),
+            trace_id: common::trace::current().unwrap_or_default(),
+        };
+        let resource_key = self.generate_resource_key(resource_type, resource_name);
+        self.update_resource_state(&resource_key, &state_change, to_state, resource_type);
+
+        TransitionResult {
+            new_state: to_state,
+            error_code: ErrorCode::Success,
+            message: format!(
+                "Forced {} from {} to {}",
+                resource_name, state_change.current_state, state_change.target_state
+            ),
+            actions_to_execute: vec!["update_etcd".to_string()],
+            transition_id: state_change.transition_id,
+            error_details: String::new(),
+        }
+    }
+
+    // ========================================
+    // MODEL DEPENDENCIES
+    // ========================================
+
+    /// Register the dependencies declared by the models of a package
+    ///
+    /// # Parameters
+    /// - `package`: Package whose models declare `dependsOn`
+    ///
+    /// # Returns
+    /// - `Err`: If the dependencies would form a cycle, in which case none
+    ///   of the package's dependencies are registered
+    pub fn register_dependencies(
+        &mut self,
+        package: &common::spec::artifact::Package,
+    ) -> Result<(), String> {
+        self.dependency_graph.register_package(package)
+    }
+
+    /// Register a model that belongs to no package, so it has no dependencies
+    pub fn register_independent_model(&mut self, model_name: &str) {
+        // A model with no dependencies cannot close a cycle
+        let _ = self
+            .dependency_graph
+            .register(&[(model_name.to_string(), Vec::new())]);
+    }
+
+    /// Returns `true` if the dependencies of the model are registered
+    pub fn has_dependencies_registered(&self, model_name: &str) -> bool {
+        self.dependency_graph.contains(model_name)
+    }
+
+    /// Dependencies of the model that are not running
+    fn unmet_dependencies(&self, model_name: &str) -> Vec<String> {
+        self.dependency_graph
+            .dependencies(model_name)
+            .iter()
+            .filter(|dependency| {
+                let key = self.generate_resource_key(ResourceType::Model, dependency);
+                self.resource_states
+                    .get(&key)
+                    .is_none_or(|rs| rs.current_state != ModelState::Running as i32)
+            })
+            .cloned()
+            .collect()
+    }
+
+    fn set_blocked_by(&mut self, resource_key: &str, cause: String) {
+        if let Some(resource_state) = self.resource_states.get_mut(resource_key) {
+            resource_state
+                .metadata
+                .insert(BLOCKED_BY_METADATA.to_string(), cause);
+        }
+    }
+
+    /// Block the models depending on a failed model
+    ///
+    /// Every model depending on `model_name`, directly or through other
+    /// models, moves to Blocked with the failure recorded as the cause.
+    /// Models already blocked or held by an injected fault are left as is.
+    ///
+    /// # Parameters
+    /// - `model_name`: The model that failed
+    /// - `transition_id`: ID recorded in the transition history
+    ///
+    /// # Returns
+    /// - `Vec<String>`: The models that moved to Blocked, in dependency order
+    pub fn block_dependents(&mut self, model_name: &str, transition_id: &str) -> Vec<String> {
+        let failed_state = self
+            .get_resource_state(model_name, ResourceType::Model)
+            .map(|rs| self.state_enum_to_str(rs.current_state, ResourceType::Model))
+            .unwrap_or_else(|| "Unknown".to_string());
+        let cause = format!("dependency {} is {}", model_name, failed_state);
+
+        let mut blocked = Vec::new();
+        for dependent in self.dependency_graph.dependents(model_name) {
+            let resource_key = self.generate_resource_key(ResourceType::Model, &dependent);
+            if self.injected_faults.contains_key(&resource_key) {
+                continue;
+            }
+            let current_state = self
+                .resource_states
+                .get(&resource_key)
+                .map(|rs| rs.current_state)
+                .unwrap_or(ModelState::Created as i32);
+            if current_state == ModelState::Blocked as i32 {
+                continue;
+            }
+
+            self.force_state(
+                ResourceType::Model,
+                &dependent,
+                current_state,
+                ModelState::Blocked as i32,
+                transition_id,
+                DEPENDENCY_SOURCE,
+            );
+            self.set_blocked_by(&resource_key, cause.clone());
+            blocked.push(dependent);
+        }
+        blocked
     }
 
     // ========================================
     // PUBLIC QUERY METHODS
     // ========================================
 
+    /// Transitions of a resource still in the history, oldest first
+    pub fn transition_history(
+        &self,
+        resource_type: ResourceType,
+        resource_name: &str,
+    ) -> Vec<&TransitionRecord> {
+        let resource_key = self.generate_resource_key(resource_type, resource_name);
+        self.transition_history
+            .iter()
+            .filter(|record| record.resource_key == resource_key)
+            .collect()
+    }
+
     /// Retrieve the current state information for a specific resource
     ///
     /// Provides read-only access to the complete state information for
@@ -1318,7 +1672,7 @@ impl StateMachine {
     }
 
     // Utility: Convert state string to proto enum value
-    fn state_str_to_enum(state: &str, resource_type: i32) -> i32 {
+    pub(crate) fn state_str_to_enum(state: &str, resource_type: i32) -> i32 {
         // Map "idle" -> "SCENARIO_STATE_IDLE", etc.
         let normalized = match ResourceType::try_from(resource_type) {
             Ok(ResourceType::Scenario) => format!(
@@ -1350,7 +1704,7 @@ impl StateMachine {
     }
 
     // Utility: Convert proto enum value to state string
-    fn state_enum_to_str(&self, state: i32, resource_type: ResourceType) -> String {
+    pub(crate) fn state_enum_to_str(&self, state: i32, resource_type: ResourceType) -> String {
         match resource_type {
             ResourceType::Scenario => ScenarioState::try_from(state)
                 .map(|s| {
@@ -1501,6 +1855,7 @@ mod tests {
             config: HashMap::new(),
             annotation: HashMap::new(),
             stats: HashMap::new(),
+            usage: None,
         };
         let result = state_machine.parse_container_state(&container_info);
         assert_eq!(result, ContainerState::Initialized);
@@ -1516,6 +1871,7 @@ mod tests {
             config: HashMap::new(),
             annotation: HashMap::new(),
             stats: HashMap::new(),
+            usage: None,
         };
         let result = state_machine.parse_container_state(&container_info);
         assert_eq!(result, ContainerState::Unknown);
@@ -1531,6 +1887,7 @@ mod tests {
             config: HashMap::new(),
             annotation: HashMap::new(),
             stats: HashMap::new(),
+            usage: None,
         };
         let result = state_machine.parse_container_state(&container_info);
         assert_eq!(result, ContainerState::Unknown);
@@ -1554,6 +1911,7 @@ mod tests {
             transition_id: "t-1".to_string(),
             timestamp_ns: 1,
             source: "unittest".to_string(),
+            trace_id: String::new(),
         };
 
         let result = state_machine.process_state_change(state_change.clone());
@@ -1586,6 +1944,7 @@ mod tests {
             transition_id: "t-2".to_string(),
             timestamp_ns: 2,
             source: "unittest".to_string(),
+            trace_id: String::new(),
         };
 
         let result = state_machine.process_state_change(state_change);
@@ -1662,6 +2021,7 @@ mod tests {
             config: HashMap::new(),
             annotation: HashMap::new(),
             stats: HashMap::new(),
+            usage: None,
         };
         let res = state_machine.evaluate_model_state_from_containers(&[&container_dead]);
         assert_eq!(res, ModelState::Dead);
@@ -1677,6 +2037,7 @@ mod tests {
             config: HashMap::new(),
             annotation: HashMap::new(),
             stats: HashMap::new(),
+            usage: None,
         };
         let mut s2 = HashMap::new();
         s2.insert("Status".to_string(), "paused".to_string());
@@ -1688,6 +2049,7 @@ mod tests {
             config: HashMap::new(),
             annotation: HashMap::new(),
             stats: HashMap::new(),
+            usage: None,
         };
         let res = state_machine.evaluate_model_state_from_containers(&[&c1, &c2]);
         assert_eq!(res, ModelState::Paused);
@@ -1703,6 +2065,7 @@ mod tests {
             config: HashMap::new(),
             annotation: HashMap::new(),
             stats: HashMap::new(),
+            usage: None,
         };
         let mut e2 = HashMap::new();
         e2.insert("Status".to_string(), "exited".to_string());
@@ -1714,6 +2077,7 @@ mod tests {
             config: HashMap::new(),
             annotation: HashMap::new(),
             stats: HashMap::new(),
+            usage: None,
         };
         let res = state_machine.evaluate_model_state_from_containers(&[&ce1, &ce2]);
         assert_eq!(res, ModelState::Exited);
@@ -1729,6 +2093,7 @@ mod tests {
             config: HashMap::new(),
             annotation: HashMap::new(),
             stats: HashMap::new(),
+            usage: None,
         };
         let mut cr2m = HashMap::new();
         cr2m.insert("Status".to_string(), "initialized".to_string());
@@ -1740,6 +2105,7 @@ mod tests {
             config: HashMap::new(),
             annotation: HashMap::new(),
             stats: HashMap::new(),
+            usage: None,
         };
         let res = state_machine.evaluate_model_state_from_containers(&[&cr1, &cr2]);
         assert_eq!(res, ModelState::Running);
@@ -1763,6 +2129,7 @@ mod tests {
             config: HashMap::new(),
             annotation: HashMap::new(),
             stats: HashMap::new(),
+            usage: None,
         };
 
         let result = state_machine.process_model_state_update("model-x", &[&container]);
@@ -1791,6 +2158,7 @@ mod tests {
             config: HashMap::new(),
             annotation: HashMap::new(),
             stats: HashMap::new(),
+            usage: None,
         };
 
         let res = state_machine.parse_container_state(&container);
@@ -1812,6 +2180,7 @@ mod tests {
             transition_id: "lt-1".to_string(),
             timestamp_ns: 1,
             source: "unittest".to_string(),
+            trace_id: String::new(),
         };
 
         let _ = state_machine.process_state_change(state_change);
@@ -1850,6 +2219,7 @@ mod tests {
                 transition_id: "t".to_string(),
                 timestamp_ns: 0,
                 source: "test".to_string(),
+                trace_id: String::new(),
             }
         ));
 
@@ -1864,6 +2234,7 @@ mod tests {
                 transition_id: "t".to_string(),
                 timestamp_ns: 0,
                 source: "test".to_string(),
+                trace_id: String::new(),
             }
         ));
     }
@@ -1879,6 +2250,7 @@ mod tests {
             transition_id: "t".to_string(),
             timestamp_ns: 0,
             source: "test".to_string(),
+            trace_id: String::new(),
         };
         assert!(!sm.evaluate_condition("critical_models_failed", &sc));
         assert!(!sm.evaluate_condition("timeout_or_error", &sc));
@@ -2092,6 +2464,52 @@ mod tests {
         assert_eq!(t.action, "start_condition_evaluation");
     }
 
+    #[test]
+    fn test_preempted_scenario_returns_to_idle() {
+        let sm = StateMachine::new();
+        let evt = sm.infer_event_from_states(
+            ScenarioState::Completed as i32,
+            ScenarioState::Idle as i32,
+            ResourceType::Scenario,
+        );
+        assert_eq!(evt, "scenario_preempted");
+        assert!(sm
+            .find_valid_transition(
+                ResourceType::Scenario,
+                ScenarioState::Completed as i32,
+                &evt,
+                ScenarioState::Idle as i32,
+            )
+            .is_some());
+    }
+
+    #[tokio::test]
+    async fn test_transition_history_keeps_trace_id() {
+        let mut sm = StateMachine::new();
+        let result = sm.process_state_change(StateChange {
+            resource_type: ResourceType::Scenario as i32,
+            resource_name: "helloworld".to_string(),
+            current_state: "idle".to_string(),
+            target_state: "waiting".to_string(),
+            transition_id: "t-1".to_string(),
+            timestamp_ns: 1,
+            source: "filtergateway".to_string(),
+            trace_id: "trace-1".to_string(),
+        });
+        assert_eq!(result.error_code, ErrorCode::Success);
+
+        // Injected faults take the trace of the request injecting them
+        common::trace::scope("trace-2".to_string(), async {
+            sm.inject_fault(ResourceType::Model, "bms", ModelState::Dead as i32, "t-2");
+        })
+        .await;
+
+        let scenario = sm.transition_history(ResourceType::Scenario, "helloworld");
+        assert_eq!(scenario.last().unwrap().trace_id, "trace-1");
+        let model = sm.transition_history(ResourceType::Model, "bms");
+        assert_eq!(model.last().unwrap().trace_id, "trace-2");
+    }
+
     #[tokio::test]
     async fn test_evaluate_and_update_package_state_no_models() {
         let sm = StateMachine::new();
@@ -2104,4 +2522,118 @@ mod tests {
         assert!(!changed);
         assert_eq!(state, common::statemanager::PackageState::Idle);
     }
+
+    fn container_with_status(status: &str) -> common::monitoringserver::ContainerInfo {
+        common::monitoringserver::ContainerInfo {
+            id: status.to_string(),
+            names: vec![status.to_string()],
+            image: "img".to_string(),
+            state: HashMap::from([("Status".to_string(), status.to_string())]),
+            config: HashMap::new(),
+            annotation: HashMap::new(),
+            stats: HashMap::new(),
+            usage: None,
+        }
+    }
+
+    /// Package whose models are `(name, dependsOn)`
+    fn package_with_dependencies(models: &[(&str, &[&str])]) -> common::spec::artifact::Package {
+        let models: Vec<serde_json::Value> = models
+            .iter()
+            .map(|(name, depends_on)| {
+                serde_json::json!({
+                    "name": name,
+                    "node": "n",
+                    "resources": {"volume": "", "network": ""},
+                    "dependsOn": depends_on,
+                })
+            })
+            .collect();
+        let package = serde_json::json!({
+            "apiVersion": "v1",
+            "kind": "Package",
+            "metadata": {"name": "pkg-deps"},
+            "spec": {"pattern": [], "models": models},
+        });
+        serde_yaml::from_str(&package.to_string()).unwrap()
+    }
+
+    #[test]
+    fn test_model_blocked_until_dependencies_run() {
+        let mut state_machine = StateMachine::new();
+        state_machine
+            .register_dependencies(&package_with_dependencies(&[("db", &[]), ("app", &["db"])]))
+            .unwrap();
+        let running = container_with_status("running");
+
+        let result = state_machine.process_model_state_update("app", &[&running]);
+        assert_eq!(result.new_state, ModelState::Blocked as i32);
+        let app = state_machine
+            .get_resource_state("app", ResourceType::Model)
+            .unwrap();
+        assert_eq!(app.metadata[BLOCKED_BY_METADATA], "waiting for db");
+        assert_eq!(app.metadata[DEPENDS_ON_METADATA], "db");
+
+        state_machine.process_model_state_update("db", &[&running]);
+        let result = state_machine.process_model_state_update("app", &[&running]);
+        assert_eq!(result.new_state, ModelState::Running as i32);
+        let app = state_machine
+            .get_resource_state("app", ResourceType::Model)
+            .unwrap();
+        assert!(!app.metadata.contains_key(BLOCKED_BY_METADATA));
+    }
+
+    #[test]
+    fn test_dead_dependency_blocks_dependents() {
+        let mut state_machine = StateMachine::new();
+        state_machine
+            .register_dependencies(&package_with_dependencies(&[
+                ("base", &[]),
+                ("left", &["base"]),
+                ("right", &["base"]),
+                ("top", &["left", "right"]),
+            ]))
+            .unwrap();
+        let running = container_with_status("running");
+        for model in ["base", "left", "right", "top"] {
+            let result = state_machine.process_model_state_update(model, &[&running]);
+            assert_eq!(result.new_state, ModelState::Running as i32);
+        }
+
+        let dead = container_with_status("dead");
+        state_machine.process_model_state_update("base", &[&dead]);
+        let blocked = state_machine.block_dependents("base", "tid-dead");
+
+        assert_eq!(blocked.len(), 3);
+        assert_eq!(blocked.last().map(String::as_str), Some("top"));
+        for model in ["left", "right", "top"] {
+            let rs = state_machine
+                .get_resource_state(model, ResourceType::Model)
+                .unwrap();
+            assert_eq!(rs.current_state, ModelState::Blocked as i32);
+            assert_eq!(rs.metadata[BLOCKED_BY_METADATA], "dependency base is DEAD");
+            let history = state_machine.transition_history(ResourceType::Model, model);
+            assert_eq!(history.last().unwrap().source, DEPENDENCY_SOURCE);
+        }
+        // Models already blocked are not blocked again
+        assert!(state_machine
+            .block_dependents("base", "tid-again")
+            .is_empty());
+    }
+
+    #[test]
+    fn test_register_dependencies_rejects_cycle() {
+        let mut state_machine = StateMachine::new();
+
+        let err = state_machine
+            .register_dependencies(&package_with_dependencies(&[
+                ("a", &["c"]),
+                ("b", &["a"]),
+                ("c", &["b"]),
+            ]))
+            .unwrap_err();
+
+        assert!(err.contains("dependency cycle"));
+        assert!(!state_machine.has_dependencies_registered("a"));
+    }
 }
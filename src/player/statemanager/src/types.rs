@@ -51,6 +51,20 @@ pub struct ResourceState {
     pub health_status: HealthStatus,
 }
 
+/// Entry of the transition history kept by the state machine
+#[derive(Debug, Clone, PartialEq)]
+pub struct TransitionRecord {
+    pub resource_key: String,
+    pub from_state: i32,
+    pub to_state: i32,
+    pub transition_id: String,
+    /// Component that triggered the transition, e.g. `fault-injected`
+    pub source: String,
+    /// Trace ID of the request that caused the transition, if any
+    pub trace_id: String,
+    pub timestamp_ns: i64,
+}
+
 /// Result of a state transition attempt - aligned with proto StateChangeResponse
 #[derive(Debug, Clone)]
 pub struct TransitionResult {
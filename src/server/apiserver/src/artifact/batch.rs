@@ -0,0 +1,235 @@
+/*
+ * SPDX-FileCopyrightText: Copyright 2024 LG Electronics Inc.
+ * SPDX-License-Identifier: Apache-2.0
+ */
+
+//! Split a batch of scenarios into artifacts applied one by one
+
+use super::{parser, KIND_SCENARIO};
+use crate::grpc::sender::filtergateway::Delivery;
+use serde::Serialize;
+use std::future::Future;
+
+/// Outcome of one artifact of a batch
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct BatchResult {
+    /// Position of the artifact in the batch, starting at 0
+    pub index: usize,
+    /// Name of the scenario, if the artifact has one
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    /// `applied`, `pending` or `failed`
+    pub status: &'static str,
+    /// Why the artifact could not be applied
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// Split a batch into artifact bodies
+///
+/// ### Parameters
+/// * `body: &str` - JSON array or multi-document YAML
+/// ### Returns
+/// * `Result<Vec<String>>` - YAML of each artifact
+/// ### Description
+/// An element of a JSON array is an artifact as a YAML string, a document
+/// as an object, or an array of documents. In multi-document YAML, each
+/// `Scenario` document starts a new artifact and the documents after it,
+/// like its package and models, belong to it. Only a body that is not a
+/// JSON array at all is an error, so one broken artifact is reported on its
+/// own when it is applied.
+pub fn split(body: &str) -> common::Result<Vec<String>> {
+    if body.trim_start().starts_with('[') {
+        let elements: Vec<serde_json::Value> =
+            serde_json::from_str(body).map_err(|e| format!("invalid batch: {}", e))?;
+        return elements.iter().map(json_artifact).collect();
+    }
+
+    let mut artifacts: Vec<Vec<String>> = Vec::new();
+    for document in documents(body) {
+        if document.trim().is_empty() {
+            continue;
+        }
+        let kind = serde_yaml::from_str::<serde_yaml::Value>(&document)
+            .ok()
+            .and_then(|v| v.get("kind").and_then(|k| k.as_str()).map(str::to_string));
+        match artifacts.last_mut() {
+            Some(current) if kind.as_deref() != Some(KIND_SCENARIO) => current.push(document),
+            _ => artifacts.push(vec![document]),
+        }
+    }
+    Ok(artifacts.iter().map(|docs| docs.join("---\n")).collect())
+}
+
+/// YAML of one element of a JSON batch
+fn json_artifact(element: &serde_json::Value) -> common::Result<String> {
+    let documents = match element {
+        serde_json::Value::String(yaml) => return Ok(yaml.clone()),
+        serde_json::Value::Array(documents) => documents.iter().collect(),
+        document => vec![document],
+    };
+    let yaml = documents
+        .into_iter()
+        .map(serde_yaml::to_string)
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(yaml.join("---\n"))
+}
+
+/// Text of each document, split at `---` lines
+fn documents(body: &str) -> Vec<String> {
+    let mut documents = vec![String::new()];
+    for line in body.lines() {
+        if line.trim_end() == "---" {
+            documents.push(String::new());
+        } else if let Some(current) = documents.last_mut() {
+            current.push_str(line);
+            current.push('\n');
+        }
+    }
+    documents
+}
+
+/// Name of the first scenario of an artifact
+fn scenario_name(artifact: &str) -> Option<String> {
+    parser::parse(artifact, false)
+        .ok()?
+        .into_iter()
+        .find(|d| d.kind.as_deref() == Some(KIND_SCENARIO))
+        .and_then(|d| d.name)
+}
+
+/// Apply each artifact, carrying on after a failure
+///
+/// ### Parameters
+/// * `artifacts: &[String]` - YAML of each artifact
+/// * `apply: F` - applies one artifact
+/// ### Returns
+/// * `Vec<BatchResult>` - one result per artifact, in batch order
+pub async fn apply_each<F, Fut>(artifacts: &[String], mut apply: F) -> Vec<BatchResult>
+where
+    F: FnMut(String) -> Fut,
+    Fut: Future<Output = common::Result<Delivery>>,
+{
+    let mut results = Vec::new();
+    for (index, artifact) in artifacts.iter().enumerate() {
+        let (status, error) = match apply(artifact.clone()).await {
+            Ok(Delivery::Delivered) => ("applied", None),
+            Ok(Delivery::Pending) => ("pending", None),
+            Err(e) => ("failed", Some(e.to_string())),
+        };
+        results.push(BatchResult {
+            index,
+            name: scenario_name(artifact),
+            status,
+            error,
+        });
+    }
+    results
+}
+
+//UNIT TEST CASES
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const VALID_ARTIFACT: &str = "apiVersion: v1
+kind: Scenario
+metadata:
+  name: batch-valid
+spec:
+  condition:
+  action: update
+  target: batch-valid
+---
+apiVersion: v1
+kind: Package
+metadata:
+  name: batch-valid
+spec:
+  pattern:
+    - type: plain
+  models:
+    - name: batch-valid-core
+      node: HPC
+      resources:
+        volume:
+        network:
+";
+
+    /// Scenario without `action`
+    const INVALID_ARTIFACT: &str = "apiVersion: v1
+kind: Scenario
+metadata:
+  name: batch-invalid
+spec:
+  condition:
+  target: batch-invalid
+---
+apiVersion: v1
+kind: Package
+metadata:
+  name: batch-invalid
+spec:
+  pattern:
+    - type: plain
+  models:
+    - name: batch-invalid-core
+      node: HPC
+      resources:
+        volume:
+        network:
+";
+
+    /// Checks an artifact the way apply does, without storing it
+    async fn validate(artifact: String) -> common::Result<Delivery> {
+        crate::artifact::validate(&artifact)
+            .await
+            .map(|_| Delivery::Delivered)
+    }
+
+    #[test]
+    fn test_split_yaml_starts_artifact_at_each_scenario() {
+        let body = format!("{}---\n{}", VALID_ARTIFACT, INVALID_ARTIFACT);
+
+        let artifacts = split(&body).unwrap();
+
+        assert_eq!(artifacts.len(), 2);
+        assert_eq!(scenario_name(&artifacts[0]).as_deref(), Some("batch-valid"));
+        assert_eq!(
+            scenario_name(&artifacts[1]).as_deref(),
+            Some("batch-invalid")
+        );
+        assert!(artifacts[1].contains("kind: Package"));
+    }
+
+    #[test]
+    fn test_split_json_array() {
+        let scenario: serde_yaml::Value =
+            serde_yaml::from_str(VALID_ARTIFACT.split("---").next().unwrap()).unwrap();
+        let body = serde_json::json!([INVALID_ARTIFACT, scenario, [scenario, scenario]]);
+
+        let artifacts = split(&body.to_string()).unwrap();
+
+        assert_eq!(artifacts.len(), 3);
+        assert_eq!(artifacts[0], INVALID_ARTIFACT);
+        assert_eq!(scenario_name(&artifacts[1]).as_deref(), Some("batch-valid"));
+        assert_eq!(artifacts[2].matches("kind: Scenario").count(), 2);
+        assert!(split("[not json").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_apply_each_reports_mixed_results() {
+        let body = format!("{}---\n{}", VALID_ARTIFACT, INVALID_ARTIFACT);
+        let artifacts = split(&body).unwrap();
+
+        let results = apply_each(&artifacts, validate).await;
+
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[0].status, "applied");
+        assert_eq!(results[0].error, None);
+        assert_eq!(results[1].index, 1);
+        assert_eq!(results[1].name.as_deref(), Some("batch-invalid"));
+        assert_eq!(results[1].status, "failed");
+        assert!(results[1].error.is_some());
+    }
+}
@@ -26,7 +26,7 @@ pub async fn read_from_etcd(artifact_name: &str) -> common::Result<String> {
 /// ### Return
 /// * `Result<Vec<String>>` - `Ok(_)` contains scenario yaml string vector
 pub async fn read_all_scenario_from_etcd() -> common::Result<Vec<String>> {
-    let kv_scenario = common::etcd::get_all_with_prefix("Scenario").await?;
+    let kv_scenario = common::etcd::get_all_with_prefix(common::keys::SCENARIO_PREFIX).await?;
     let values = kv_scenario.into_iter().map(|kv| kv.1).collect();
 
     Ok(values)
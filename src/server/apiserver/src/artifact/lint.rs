@@ -0,0 +1,506 @@
+/*
+ * SPDX-FileCopyrightText: Copyright 2024 LG Electronics Inc.
+ * SPDX-License-Identifier: Apache-2.0
+ */
+
+//! Lint Scenario documents and report problems with line numbers
+
+use super::KIND_SCENARIO;
+use common::spec::artifact::Scenario;
+use std::collections::HashMap;
+
+/// Condition expressions understood by filtergateway
+const KNOWN_EXPRESSIONS: &[&str] = &["eq", "lt", "le", "ge", "gt"];
+/// Operand types filtergateway can subscribe to
+const KNOWN_OPERAND_TYPES: &[&str] = &["DDS"];
+
+/// Severity of a lint diagnostic
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    /// The document cannot be applied
+    Error,
+    /// The document is applied but probably does not do what was intended
+    Warning,
+}
+
+impl std::fmt::Display for Severity {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Severity::Error => write!(f, "error"),
+            Severity::Warning => write!(f, "warning"),
+        }
+    }
+}
+
+/// Problem found in an artifact, located by 1-based line and column
+#[derive(Debug, Clone, PartialEq, serde::Serialize)]
+pub struct Diagnostic {
+    pub severity: Severity,
+    pub line: usize,
+    pub column: usize,
+    pub message: String,
+}
+
+impl std::fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "line {}:{}: {}: {}",
+            self.line, self.column, self.severity, self.message
+        )
+    }
+}
+
+/// Lint every Scenario document of an artifact bundle
+///
+/// ### Parameters
+/// * `body: &str` - artifact bundle, documents separated by `---`
+/// ### Returns
+/// * `Vec<Diagnostic>` - problems found, with lines relative to `body`
+pub fn lint(body: &str) -> Vec<Diagnostic> {
+    let mut diagnostics = Vec::new();
+
+    for (first_line, doc) in split_with_offsets(body) {
+        let value: serde_yaml::Value = match serde_yaml::from_str(&doc) {
+            Ok(value) => value,
+            Err(e) => {
+                diagnostics.push(from_yaml_error(&e, first_line));
+                continue;
+            }
+        };
+
+        if value.get("kind").and_then(|k| k.as_str()) == Some(KIND_SCENARIO) {
+            diagnostics.extend(lint_scenario(&doc, &value, first_line));
+        }
+    }
+
+    diagnostics
+}
+
+/// Split `body` on `---` lines, keeping the line number each document starts at
+fn split_with_offsets(body: &str) -> Vec<(usize, String)> {
+    let mut documents = vec![(1, String::new())];
+    for (i, line) in body.lines().enumerate() {
+        if line.trim_end() == "---" {
+            documents.push((i + 2, String::new()));
+        } else if let Some((_, current)) = documents.last_mut() {
+            current.push_str(line);
+            current.push('\n');
+        }
+    }
+    documents.retain(|(_, doc)| !doc.trim().is_empty());
+    documents
+}
+
+fn from_yaml_error(e: &serde_yaml::Error, first_line: usize) -> Diagnostic {
+    let (line, column) = e
+        .location()
+        .map(|l| (first_line + l.line() - 1, l.column()))
+        .unwrap_or((first_line, 1));
+    Diagnostic {
+        severity: Severity::Error,
+        line,
+        column,
+        message: e.to_string(),
+    }
+}
+
+/// Fields allowed under each checked path of a Scenario
+fn scenario_schema(path: &str) -> Option<&'static [&'static str]> {
+    match path {
+        "" => Some(&["apiVersion", "kind", "metadata", "spec", "status"]),
+        "metadata" => Some(&["name", "labels", "annotations"]),
+        "spec" => Some(&[
+            "condition",
+            "action",
+            "target",
+            "priority",
+            "dependsOn",
+            "delayMs",
+        ]),
+        "spec.condition" => Some(&[
+            "express",
+            "value",
+            "operands",
+            "debounceMs",
+            "exitValue",
+            "window",
+        ]),
+        "spec.condition.window" => Some(&["start", "end", "days"]),
+        "spec.condition.operands" => Some(&["type", "name", "value"]),
+        "status" => Some(&["state"]),
+        _ => None,
+    }
+}
+
+fn lint_scenario(doc: &str, value: &serde_yaml::Value, first_line: usize) -> Vec<Diagnostic> {
+    let keys = scan_keys(doc);
+    let mut diagnostics = Vec::new();
+    let at = |path: &str| -> (usize, usize) {
+        keys.get(path)
+            .map(|(line, column)| (first_line + line - 1, *column))
+            .unwrap_or((first_line, 1))
+    };
+    let mut push = |severity, (line, column): (usize, usize), message: String| {
+        diagnostics.push(Diagnostic {
+            severity,
+            line,
+            column,
+            message,
+        })
+    };
+
+    // Unknown fields, in document order
+    let mut located: Vec<(&String, &(usize, usize))> = keys.iter().collect();
+    located.sort_by_key(|(_, pos)| **pos);
+    for (path, pos) in located {
+        let (parent, field) = match path.rsplit_once('.') {
+            Some((parent, field)) => (parent, field),
+            None => ("", path.as_str()),
+        };
+        let Some(allowed) = scenario_schema(parent) else {
+            continue;
+        };
+        if !allowed.contains(&field) {
+            let hint = closest(field, allowed)
+                .map(|c| format!("; did you mean `{}`?", c))
+                .unwrap_or_default();
+            let location = if parent.is_empty() {
+                "scenario"
+            } else {
+                parent
+            };
+            push(
+                Severity::Warning,
+                (first_line + pos.0 - 1, pos.1),
+                format!("unknown field `{}` in {}{}", field, location, hint),
+            );
+        }
+    }
+
+    let spec = value.get("spec");
+
+    if text(spec.and_then(|s| s.get("action"))).is_empty() {
+        let pos = if keys.contains_key("spec.action") {
+            at("spec.action")
+        } else {
+            at("spec")
+        };
+        push(Severity::Error, pos, "scenario has an empty action".into());
+    }
+    if text(spec.and_then(|s| s.get("target"))).is_empty() {
+        let pos = if keys.contains_key("spec.target") {
+            at("spec.target")
+        } else {
+            at("spec")
+        };
+        push(Severity::Error, pos, "scenario has an empty target".into());
+    }
+
+    if let Some(condition) = spec
+        .and_then(|s| s.get("condition"))
+        .filter(|c| !c.is_null())
+    {
+        let express = text(condition.get("express"));
+        if !KNOWN_EXPRESSIONS.contains(&express) {
+            push(
+                Severity::Error,
+                at("spec.condition.express"),
+                format!(
+                    "unknown condition expression `{}`, expected one of {}",
+                    express,
+                    KNOWN_EXPRESSIONS.join(", ")
+                ),
+            );
+        }
+
+        let operands = condition.get("operands");
+        let topic = text(operands.and_then(|o| o.get("value")));
+        let operand_type = text(operands.and_then(|o| o.get("type")));
+        if operands.is_some() && topic.is_empty() {
+            push(
+                Severity::Error,
+                at("spec.condition.operands"),
+                "condition does not reference a topic".into(),
+            );
+        } else if operands.is_some() && !KNOWN_OPERAND_TYPES.contains(&operand_type) {
+            push(
+                Severity::Warning,
+                at("spec.condition.operands.type"),
+                format!(
+                    "condition references topic `{}` with no known type (`{}`)",
+                    topic, operand_type
+                ),
+            );
+        }
+    }
+
+    // Only report the deserializer error when no clearer error was found
+    if !diagnostics.iter().any(|d| d.severity == Severity::Error) {
+        if let Err(e) = serde_yaml::from_str::<Scenario>(doc) {
+            diagnostics.push(from_yaml_error(&e, first_line));
+        }
+    }
+
+    diagnostics
+}
+
+/// Trimmed string value, empty when missing or not a string
+fn text(value: Option<&serde_yaml::Value>) -> &str {
+    value.and_then(|v| v.as_str()).unwrap_or("").trim()
+}
+
+/// Map each mapping key path (`spec.condition.express`) to its 1-based line and column
+///
+/// This is a line scanner for the block style used by artifacts; keys inside
+/// block scalars and flow collections are not reported.
+fn scan_keys(doc: &str) -> HashMap<String, (usize, usize)> {
+    let mut keys = HashMap::new();
+    let mut stack: Vec<(usize, String)> = Vec::new();
+    let mut block_scalar_indent: Option<usize> = None;
+
+    for (i, raw) in doc.lines().enumerate() {
+        let trimmed = raw.trim_start();
+        if trimmed.is_empty() || trimmed.starts_with('#') {
+            continue;
+        }
+        let mut indent = raw.len() - trimmed.len();
+        if let Some(scalar_indent) = block_scalar_indent {
+            if indent > scalar_indent {
+                continue;
+            }
+            block_scalar_indent = None;
+        }
+
+        // A list item starts a nested mapping two columns further in
+        let mut content = trimmed;
+        while let Some(rest) = content.strip_prefix("- ") {
+            indent += 2;
+            content = rest.trim_start();
+        }
+
+        let Some((key, rest)) = content.split_once(':') else {
+            continue;
+        };
+        if key.is_empty()
+            || !key
+                .chars()
+                .all(|c| c.is_alphanumeric() || "_-./".contains(c))
+        {
+            continue;
+        }
+        if !(rest.is_empty() || rest.starts_with(' ')) {
+            continue;
+        }
+
+        while stack.last().is_some_and(|(level, _)| *level >= indent) {
+            stack.pop();
+        }
+        let path = stack
+            .iter()
+            .map(|(_, k)| k.as_str())
+            .chain(std::iter::once(key))
+            .collect::<Vec<_>>()
+            .join(".");
+        keys.entry(path).or_insert((i + 1, indent + 1));
+        stack.push((indent, key.to_string()));
+
+        let value = rest.trim();
+        if value.starts_with('|') || value.starts_with('>') {
+            block_scalar_indent = Some(indent);
+        }
+    }
+
+    keys
+}
+
+/// Closest allowed field within a few edits, for typo hints
+fn closest<'a>(field: &str, allowed: &[&'a str]) -> Option<&'a str> {
+    allowed
+        .iter()
+        .map(|a| (edit_distance(field, a), *a))
+        .filter(|(d, _)| *d <= 3 && *d * 2 < field.len())
+        .min()
+        .map(|(_, a)| a)
+}
+
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut prev: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut curr = vec![i + 1; b.len() + 1];
+        for (j, cb) in b.iter().enumerate() {
+            let cost = usize::from(ca != *cb);
+            curr[j + 1] = (prev[j] + cost).min(prev[j + 1] + 1).min(curr[j] + 1);
+        }
+        prev = curr;
+    }
+    prev[b.len()]
+}
+
+//UNIT TEST CASES
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const VALID_SCENARIO: &str = r#"apiVersion: v1
+kind: Scenario
+metadata:
+  name: helloworld
+spec:
+  condition:
+    express: eq
+    value: "true"
+    operands:
+      type: DDS
+      name: value
+      value: ADASObstacleDetectionIsWarning
+  action: update
+  target: helloworld
+"#;
+
+    const PACKAGE: &str = r#"apiVersion: v1
+kind: Package
+metadata:
+  name: helloworld
+spec:
+  pattern:
+    - type: plain
+  models:
+    - name: helloworld-core
+      node: HPC
+"#;
+
+    fn errors(diagnostics: &[Diagnostic]) -> Vec<&Diagnostic> {
+        diagnostics
+            .iter()
+            .filter(|d| d.severity == Severity::Error)
+            .collect()
+    }
+
+    #[test]
+    fn test_valid_scenario_has_no_diagnostics() {
+        assert!(lint(VALID_SCENARIO).is_empty());
+        assert!(lint(&format!("{}---\n{}", VALID_SCENARIO, PACKAGE)).is_empty());
+    }
+
+    #[test]
+    fn test_time_window_has_no_diagnostics() {
+        let body = VALID_SCENARIO.replace(
+            "    operands:",
+            "    window:\n      start: \"22:00\"\n      end: \"06:00\"\n      days: [Mon, Fri]\n    operands:",
+        );
+        assert!(lint(&body).is_empty());
+
+        let diagnostics = lint(&body.replace("      end:", "      stop:"));
+        let typo = diagnostics
+            .iter()
+            .find(|d| d.severity == Severity::Warning)
+            .unwrap();
+        assert_eq!((typo.line, typo.column), (11, 7));
+        assert!(typo
+            .message
+            .contains("unknown field `stop` in spec.condition.window"));
+    }
+
+    #[test]
+    fn test_depends_on_has_no_diagnostics() {
+        let body = format!("{}  dependsOn:\n    - base\n", VALID_SCENARIO);
+        assert!(lint(&body).is_empty());
+    }
+
+    #[test]
+    fn test_typo_in_operands_reports_line() {
+        let body = VALID_SCENARIO.replace("    operands:", "    opernad:");
+        let diagnostics = lint(&body);
+
+        let typo = diagnostics
+            .iter()
+            .find(|d| d.severity == Severity::Warning)
+            .unwrap();
+        assert_eq!((typo.line, typo.column), (9, 5));
+        assert!(typo
+            .message
+            .contains("unknown field `opernad` in spec.condition; did you mean `operands`?"));
+        // The missing operands also make the scenario invalid
+        assert!(!errors(&diagnostics).is_empty());
+    }
+
+    #[test]
+    fn test_line_numbers_are_relative_to_bundle() {
+        let scenario = VALID_SCENARIO.replace("  action: update", "  actoin: update");
+        let body = format!("{}---\n{}", PACKAGE, scenario);
+        let diagnostics = lint(&body);
+
+        // Package takes 10 lines, the separator 1, so the scenario starts at 12
+        let typo = diagnostics
+            .iter()
+            .find(|d| d.message.contains("actoin"))
+            .unwrap();
+        assert_eq!(typo.line, 24);
+        assert!(typo.message.contains("did you mean `action`?"));
+
+        let empty = diagnostics
+            .iter()
+            .find(|d| d.message.contains("empty action"))
+            .unwrap();
+        assert_eq!(empty.severity, Severity::Error);
+        assert_eq!(empty.line, 16);
+    }
+
+    #[test]
+    fn test_empty_action_reports_key_line() {
+        let body = VALID_SCENARIO.replace("  action: update", "  action: \"\"");
+        let diagnostics = lint(&body);
+
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].severity, Severity::Error);
+        assert_eq!(diagnostics[0].line, 13);
+        assert_eq!(diagnostics[0].message, "scenario has an empty action");
+    }
+
+    #[test]
+    fn test_unknown_operand_type_is_warning() {
+        let body = VALID_SCENARIO.replace("type: DDS", "type: CAN");
+        let diagnostics = lint(&body);
+
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].severity, Severity::Warning);
+        assert_eq!(diagnostics[0].line, 10);
+        assert!(diagnostics[0]
+            .message
+            .contains("topic `ADASObstacleDetectionIsWarning` with no known type (`CAN`)"));
+    }
+
+    #[test]
+    fn test_unknown_expression_is_error() {
+        let body = VALID_SCENARIO.replace("express: eq", "express: equals");
+        let diagnostics = lint(&body);
+
+        assert_eq!(errors(&diagnostics).len(), 1);
+        assert_eq!(diagnostics[0].line, 7);
+        assert!(diagnostics[0].message.contains("`equals`"));
+    }
+
+    #[test]
+    fn test_syntax_error_has_location() {
+        let body = format!("{}---\nkind: Scenario\nspec: [unclosed\n", PACKAGE);
+        let diagnostics = lint(&body);
+
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].severity, Severity::Error);
+        assert!(diagnostics[0].line >= 12);
+    }
+
+    #[test]
+    fn test_diagnostic_display() {
+        let d = Diagnostic {
+            severity: Severity::Warning,
+            line: 3,
+            column: 5,
+            message: "unknown field `x`".into(),
+        };
+        assert_eq!(d.to_string(), "line 3:5: warning: unknown field `x`");
+    }
+}
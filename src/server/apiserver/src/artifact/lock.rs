@@ -0,0 +1,170 @@
+/*
+ * SPDX-FileCopyrightText: Copyright 2024 LG Electronics Inc.
+ * SPDX-License-Identifier: Apache-2.0
+ */
+
+//! Locks serializing concurrent imports of the same package
+//!
+//! Importing a package writes its documents to etcd, then reads its models
+//! back to build the pods. Two imports of one package running together could
+//! build pods from a mix of both, so the second one waits for the first.
+//! Imports of different packages do not wait on each other.
+
+use std::collections::HashMap;
+use std::sync::{Arc, LazyLock, Mutex, Weak};
+use tokio::sync::{Mutex as AsyncMutex, OwnedMutexGuard};
+
+/// Locks of the packages being imported
+static PACKAGES: LazyLock<KeyedLock> = LazyLock::new(KeyedLock::default);
+
+/// Async locks by key, dropped once no one holds or waits for them
+#[derive(Default)]
+pub struct KeyedLock {
+    locks: Mutex<HashMap<String, Weak<AsyncMutex<()>>>>,
+}
+
+impl KeyedLock {
+    /// Waits until no one else holds the lock of `key`, then holds it
+    /// until the guard is dropped
+    pub async fn lock(&self, key: &str) -> OwnedMutexGuard<()> {
+        let lock = {
+            let mut locks = self.locks.lock().unwrap();
+            locks.retain(|_, lock| lock.strong_count() > 0);
+            match locks.get(key).and_then(Weak::upgrade) {
+                Some(lock) => lock,
+                None => {
+                    let lock = Arc::new(AsyncMutex::new(()));
+                    locks.insert(key.to_string(), Arc::downgrade(&lock));
+                    lock
+                }
+            }
+        };
+        lock.lock_owned().await
+    }
+}
+
+/// Holds the import locks of `packages`, waiting for imports of the same
+/// packages in progress
+///
+/// Locks are taken in name order, so imports sharing several packages
+/// cannot deadlock.
+pub async fn lock_packages(packages: &[String]) -> Vec<OwnedMutexGuard<()>> {
+    let mut names: Vec<&String> = packages.iter().collect();
+    names.sort();
+    names.dedup();
+
+    let mut guards = Vec::with_capacity(names.len());
+    for name in names {
+        guards.push(PACKAGES.lock(name).await);
+    }
+    guards
+}
+
+//UNIT TEST CASES
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::time::Duration;
+
+    /// Import writing each document, then reading them all back
+    async fn import(lock: &KeyedLock, store: &Mutex<Vec<String>>, package: &str, version: usize) {
+        let _guard = lock.lock(package).await;
+        store.lock().unwrap().clear();
+        for document in ["Package", "Model"] {
+            store
+                .lock()
+                .unwrap()
+                .push(format!("{}/{}/v{}", document, package, version));
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        let read_back = store.lock().unwrap().clone();
+        assert_eq!(
+            read_back,
+            vec![
+                format!("Package/{}/v{}", package, version),
+                format!("Model/{}/v{}", package, version)
+            ],
+            "import of v{} saw documents of another import",
+            version
+        );
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn test_imports_of_same_package_serialize() {
+        let lock = Arc::new(KeyedLock::default());
+        let store = Arc::new(Mutex::new(Vec::new()));
+
+        let imports: Vec<_> = (0..8)
+            .map(|version| {
+                let (lock, store) = (lock.clone(), store.clone());
+                tokio::spawn(async move { import(&lock, &store, "helloworld", version).await })
+            })
+            .collect();
+        for import in imports {
+            import.await.unwrap();
+        }
+
+        assert_eq!(store.lock().unwrap().len(), 2);
+        // Locks are dropped once released
+        let locks = lock.locks.lock().unwrap();
+        assert!(locks.values().all(|lock| lock.strong_count() == 0));
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn test_imports_of_different_packages_run_together() {
+        let lock = Arc::new(KeyedLock::default());
+        let inside = Arc::new(AtomicUsize::new(0));
+        let most_inside = Arc::new(AtomicUsize::new(0));
+
+        let imports: Vec<_> = ["helloworld", "antipinch"]
+            .into_iter()
+            .map(|package| {
+                let (lock, inside, most_inside) =
+                    (lock.clone(), inside.clone(), most_inside.clone());
+                tokio::spawn(async move {
+                    let _guard = lock.lock(package).await;
+                    let now = inside.fetch_add(1, Ordering::SeqCst) + 1;
+                    most_inside.fetch_max(now, Ordering::SeqCst);
+                    tokio::time::sleep(Duration::from_millis(100)).await;
+                    inside.fetch_sub(1, Ordering::SeqCst);
+                })
+            })
+            .collect();
+        for import in imports {
+            import.await.unwrap();
+        }
+
+        assert_eq!(most_inside.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn test_second_import_waits_for_first() {
+        let lock = KeyedLock::default();
+        let first = lock.lock("helloworld").await;
+
+        let second = lock.lock("helloworld");
+        tokio::pin!(second);
+        assert!(tokio::time::timeout(Duration::from_millis(50), &mut second)
+            .await
+            .is_err());
+
+        drop(first);
+        tokio::time::timeout(Duration::from_secs(1), second)
+            .await
+            .expect("second import still waiting");
+    }
+
+    #[tokio::test]
+    async fn test_lock_packages_ignores_duplicates() {
+        let packages = vec![
+            "lock-test-b".to_string(),
+            "lock-test-a".to_string(),
+            "lock-test-b".to_string(),
+        ];
+
+        let guards = lock_packages(&packages).await;
+
+        assert_eq!(guards.len(), 2);
+    }
+}
@@ -5,11 +5,21 @@
 
 //! Convert string-type artifacts to struct and access etcd
 
+pub mod batch;
 pub mod data;
+pub mod git;
+pub mod lint;
+pub mod lock;
+pub mod parser;
+pub mod status;
 
+use common::keys;
 use common::logd;
-use common::spec::artifact::{Artifact, Model, Network, Node, Package, Scenario, Schedule, Volume};
+use common::spec::artifact::network::{Subnet, NETWORK_ANNOTATION};
+use common::spec::artifact::scenario::dependency_order;
+use common::spec::artifact::{Artifact, Model, Network, Package, Scenario, Volume};
 use common::spec::k8s::Pod;
+use status::ArtifactStatus;
 
 // Artifact kind constants
 const KIND_SCENARIO: &str = "Scenario";
@@ -19,41 +29,7 @@ const KIND_NETWORK: &str = "Network";
 const KIND_NODE: &str = "Node";
 const KIND_MODEL: &str = "Model";
 const KIND_SCHEDULE: &str = "Schedule";
-
-// YAML document separator
-const YAML_SEPARATOR: &str = "---";
-
-/// Parse artifact kind and name from YAML value
-fn parse_artifact_info(value: &serde_yaml::Value) -> Option<(String, String)> {
-    let kind = value.get("kind")?.as_str()?;
-
-    let name = match kind {
-        KIND_SCENARIO => serde_yaml::from_value::<Scenario>(value.clone())
-            .ok()?
-            .get_name(),
-        KIND_PACKAGE => serde_yaml::from_value::<Package>(value.clone())
-            .ok()?
-            .get_name(),
-        KIND_VOLUME => serde_yaml::from_value::<Volume>(value.clone())
-            .ok()?
-            .get_name(),
-        KIND_NETWORK => serde_yaml::from_value::<Network>(value.clone())
-            .ok()?
-            .get_name(),
-        KIND_NODE => serde_yaml::from_value::<Node>(value.clone())
-            .ok()?
-            .get_name(),
-        KIND_MODEL => serde_yaml::from_value::<Model>(value.clone())
-            .ok()?
-            .get_name(),
-        KIND_SCHEDULE => serde_yaml::from_value::<Schedule>(value.clone())
-            .ok()?
-            .get_name(),
-        _ => return None,
-    };
-
-    Some((kind.to_string(), name))
-}
+const KIND_POLICY: &str = "Policy";
 
 /// Send initial state change notification to StateManager
 async fn notify_scenario_state(scenario_name: &str, target_state: &str) {
@@ -70,6 +46,7 @@ async fn notify_scenario_state(scenario_name: &str, target_state: &str) {
         transition_id: format!("apiserver-scenario-init-{}", timestamp),
         timestamp_ns: timestamp,
         source: "apiserver".to_string(),
+        trace_id: String::new(),
     };
 
     logd!(
@@ -96,28 +73,16 @@ async fn notify_scenario_state(scenario_name: &str, target_state: &str) {
     }
 }
 
-/// Process and store a single artifact document
-async fn process_artifact_document(doc: &str) -> common::Result<Option<(String, String)>> {
+/// Process and store a single validated artifact document
+async fn process_artifact_document(
+    kind: &str,
+    name: &str,
+    value: &serde_yaml::Value,
+) -> common::Result<String> {
     use std::time::Instant;
 
-    let parse_start = Instant::now();
-    let value: serde_yaml::Value = serde_yaml::from_str(doc)?;
-    let artifact_str = serde_yaml::to_string(&value)?;
-    logd!(
-        1,
-        "process_artifact: YAML parse elapsed = {:?}",
-        parse_start.elapsed()
-    );
-
-    let (kind, name) = match parse_artifact_info(&value) {
-        Some(info) => info,
-        None => {
-            logd!(5, "Unknown or invalid artifact");
-            return Ok(None);
-        }
-    };
-
-    let key = format!("{}/{}", kind, name);
+    let artifact_str = serde_yaml::to_string(value)?;
+    let key = keys::artifact(kind, name);
 
     let etcd_start = Instant::now();
     data::write_to_etcd(&key, &artifact_str).await?;
@@ -129,41 +94,105 @@ async fn process_artifact_document(doc: &str) -> common::Result<Option<(String,
     );
 
     if kind == KIND_SCENARIO {
-        notify_scenario_state(&name, "idle").await;
+        notify_scenario_state(name, "idle").await;
     }
 
-    Ok(Some((kind, artifact_str)))
+    Ok(artifact_str)
 }
 
 /// Apply downloaded artifact to etcd
 ///
 /// ### Parametets
 /// * `body: &str` - whole yaml string of piccolo artifact
+/// * `strict: bool` - reject the whole artifact if any document is invalid
 /// ### Returns
 /// * `Result(String, String)` - scenario and package yaml in downloaded artifact
 /// ### Description
-/// Write artifact in etcd
-pub async fn apply(body: &str) -> common::Result<String> {
+/// Write every valid document in etcd. Invalid documents are logged and
+/// skipped unless `strict` is set. Scenario lint errors also reject the
+/// artifact in `strict` mode.
+pub async fn apply(body: &str, strict: bool) -> common::Result<String> {
     use std::time::Instant;
     let total_start = Instant::now();
 
-    let docs: Vec<&str> = body.split(YAML_SEPARATOR).collect();
+    let diagnostics = lint::lint(body);
+    let lint_errors: Vec<String> = diagnostics
+        .iter()
+        .filter(|d| d.severity == lint::Severity::Error)
+        .map(|d| d.to_string())
+        .collect();
+    for diagnostic in &diagnostics {
+        match diagnostic.severity {
+            lint::Severity::Error => logd!(5, "apply: {}", diagnostic),
+            lint::Severity::Warning => logd!(4, "apply: {}", diagnostic),
+        }
+    }
+    if strict && !lint_errors.is_empty() {
+        return Err(format!("strict mode: {}", lint_errors.join("; ")).into());
+    }
+
+    let parse_start = Instant::now();
+    let documents = parser::parse(body, strict)?;
+    logd!(1, "apply: YAML parse elapsed = {:?}", parse_start.elapsed());
+
+    // The artifact is tracked from here, when the name of its scenario is known
+    let scenarios = scenario_names(&documents);
+    status::record(&status::Etcd, &scenarios, ArtifactStatus::Pending, None).await;
+
+    // Imports of the same package wait here for the one in progress
+    let _guards = lock::lock_packages(&package_names(&documents)).await;
+    let stored = store(&documents, &lint_errors)
+        .await
+        .map_err(|e| e.to_string());
+    let stored =
+        status::record_result(&status::Etcd, &scenarios, ArtifactStatus::Stored, stored).await;
+
+    logd!(1, "apply: total elapsed = {:?}", total_start.elapsed());
+    Ok(stored?)
+}
+
+/// Store the documents of an artifact and the pods of its package
+///
+/// ### Returns
+/// * `Result(String)` - scenario yaml of the artifact
+async fn store(
+    documents: &[parser::DocumentResult],
+    lint_errors: &[String],
+) -> common::Result<String> {
+    check_volume_references(documents).await?;
+    check_network_subnets(documents).await?;
+    check_scenario_dependencies(documents).await?;
+
     let mut scenario_str = String::new();
     let mut package_str = String::new();
 
-    for doc in docs {
-        if let Some((kind, artifact_str)) = process_artifact_document(doc).await? {
-            match kind.as_str() {
-                KIND_SCENARIO => scenario_str = artifact_str,
-                KIND_PACKAGE => package_str = artifact_str,
-                _ => continue,
+    for document in documents {
+        let value = match &document.result {
+            Ok(value) => value,
+            Err(_) => {
+                logd!(5, "apply: skipping {}", document);
+                continue;
             }
+        };
+        let (Some(kind), Some(name)) = (&document.kind, &document.name) else {
+            continue;
+        };
+
+        let artifact_str = process_artifact_document(kind, name, value).await?;
+        match kind.as_str() {
+            KIND_SCENARIO => scenario_str = artifact_str,
+            KIND_PACKAGE => package_str = artifact_str,
+            _ => continue,
         }
     }
 
-    logd!(1, "apply: total elapsed = {:?}", total_start.elapsed());
-
-    if scenario_str.is_empty() {
+    if scenario_str.is_empty() && !lint_errors.is_empty() {
+        Err(format!(
+            "There is not any scenario in yaml string: {}",
+            lint_errors.join("; ")
+        )
+        .into())
+    } else if scenario_str.is_empty() {
         Err("There is not any scenario in yaml string".into())
     } else if package_str.is_empty() {
         Err("There is not any package in yaml string".into())
@@ -173,6 +202,238 @@ pub async fn apply(body: &str) -> common::Result<String> {
     }
 }
 
+/// Names of the valid scenarios in `documents`
+fn scenario_names(documents: &[parser::DocumentResult]) -> Vec<String> {
+    documents
+        .iter()
+        .filter(|d| d.is_ok() && d.kind.as_deref() == Some(KIND_SCENARIO))
+        .filter_map(|d| d.name.clone())
+        .collect()
+}
+
+/// Names of the valid packages in `documents`
+fn package_names(documents: &[parser::DocumentResult]) -> Vec<String> {
+    documents
+        .iter()
+        .filter(|d| d.is_ok() && d.kind.as_deref() == Some(KIND_PACKAGE))
+        .filter_map(|d| d.name.clone())
+        .collect()
+}
+
+/// Result of validating an artifact without applying it
+#[derive(Debug)]
+pub struct Validation {
+    /// Parsed documents, in input order
+    pub documents: Vec<parser::DocumentResult>,
+    /// Lint warnings that would not block applying the artifact
+    pub warnings: Vec<String>,
+}
+
+impl Validation {
+    /// Store keys the artifact would be written to, e.g. `Scenario/helloworld`
+    pub fn keys(&self) -> Vec<String> {
+        self.documents
+            .iter()
+            .filter_map(|d| match (&d.kind, &d.name) {
+                (Some(kind), Some(name)) => Some(keys::artifact(kind, name)),
+                _ => None,
+            })
+            .collect()
+    }
+}
+
+/// Validate downloaded artifact without applying it
+///
+/// ### Parametets
+/// * `body: &str` - whole yaml string of piccolo artifact
+/// ### Returns
+/// * `Result(Validation)` - parsed documents and lint warnings
+/// ### Description
+/// Run the same checks as a `strict` apply: lint errors, invalid documents,
+/// a missing scenario or package, dangling volume references and scenario
+/// dependency cycles fail validation. Nothing is written to etcd and no component is notified, so
+/// a package can be checked before it is published.
+pub async fn validate(body: &str) -> common::Result<Validation> {
+    let diagnostics = lint::lint(body);
+    let errors: Vec<String> = diagnostics
+        .iter()
+        .filter(|d| d.severity == lint::Severity::Error)
+        .map(|d| d.to_string())
+        .collect();
+    if !errors.is_empty() {
+        return Err(format!("validation failed: {}", errors.join("; ")).into());
+    }
+    let warnings = diagnostics
+        .iter()
+        .filter(|d| d.severity == lint::Severity::Warning)
+        .map(|d| d.to_string())
+        .collect();
+
+    let documents = parser::parse(body, true)?;
+    let has_kind = |kind: &str| documents.iter().any(|d| d.kind.as_deref() == Some(kind));
+    if !has_kind(KIND_SCENARIO) {
+        return Err("There is not any scenario in yaml string".into());
+    }
+    if !has_kind(KIND_PACKAGE) {
+        return Err("There is not any package in yaml string".into());
+    }
+    check_volume_references(&documents).await?;
+    check_network_subnets(&documents).await?;
+    check_scenario_dependencies(&documents).await?;
+
+    Ok(Validation {
+        documents,
+        warnings,
+    })
+}
+
+/// Volumes referenced by the packages of a bundle but not defined in it
+///
+/// ### Returns
+/// * `Vec<(String, String)>` - package and volume name of each reference
+fn undefined_volumes(documents: &[parser::DocumentResult]) -> Vec<(String, String)> {
+    let defined: std::collections::HashSet<&str> = documents
+        .iter()
+        .filter(|d| d.is_ok() && d.kind.as_deref() == Some(KIND_VOLUME))
+        .filter_map(|d| d.name.as_deref())
+        .collect();
+
+    let mut undefined = Vec::new();
+    for document in documents {
+        let (Ok(value), Some(KIND_PACKAGE)) = (&document.result, document.kind.as_deref()) else {
+            continue;
+        };
+        let Ok(package) = serde_yaml::from_value::<Package>(value.clone()) else {
+            continue;
+        };
+        for model in package.get_models() {
+            if let Some(volume) = model.get_resources().get_volume() {
+                if !volume.is_empty() && !defined.contains(volume.as_str()) {
+                    undefined.push((package.get_name(), volume));
+                }
+            }
+        }
+    }
+    undefined
+}
+
+/// Fail if a package references a volume that is neither in the bundle nor stored
+async fn check_volume_references(documents: &[parser::DocumentResult]) -> common::Result<()> {
+    let mut dangling = Vec::new();
+    for (package, volume) in undefined_volumes(documents) {
+        if common::etcd::get(&keys::volume(&volume)).await.is_err() {
+            dangling.push(format!(
+                "package '{}' references unknown volume '{}'",
+                package, volume
+            ));
+        }
+    }
+
+    if dangling.is_empty() {
+        Ok(())
+    } else {
+        Err(dangling.join("; ").into())
+    }
+}
+
+/// Subnet overlaps between the networks of a bundle and with stored networks
+///
+/// ### Parameters
+/// * `new: &[(String, Subnet)]` - networks being registered
+/// * `stored: &[(String, Subnet)]` - networks already registered
+/// ### Returns
+/// * `Vec<String>` - one message per overlapping pair. A stored network
+///   with the name of a new one is replaced by it and not compared.
+fn subnet_conflicts(new: &[(String, Subnet)], stored: &[(String, Subnet)]) -> Vec<String> {
+    let stored: Vec<&(String, Subnet)> = stored
+        .iter()
+        .filter(|(name, _)| !new.iter().any(|(n, _)| n == name))
+        .collect();
+
+    let mut conflicts = Vec::new();
+    for (i, (name, subnet)) in new.iter().enumerate() {
+        let others = new[i + 1..].iter().chain(stored.iter().copied());
+        for (other_name, other_subnet) in others {
+            if subnet.overlaps(other_subnet) {
+                conflicts.push(format!(
+                    "network '{}' subnet {} overlaps network '{}' subnet {}",
+                    name, subnet, other_name, other_subnet
+                ));
+            }
+        }
+    }
+    conflicts
+}
+
+/// Fail if a network of the bundle has a subnet used by another network
+async fn check_network_subnets(documents: &[parser::DocumentResult]) -> common::Result<()> {
+    let new: Vec<(String, Subnet)> = documents
+        .iter()
+        .filter(|d| d.kind.as_deref() == Some(KIND_NETWORK))
+        .filter_map(|d| d.result.as_ref().ok())
+        .filter_map(|value| serde_yaml::from_value::<Network>(value.clone()).ok())
+        .filter_map(|network| Some((network.get_name(), network.get_subnet()?)))
+        .collect();
+    if new.is_empty() {
+        return Ok(());
+    }
+
+    let stored: Vec<(String, Subnet)> = common::etcd::get_all_with_prefix(keys::NETWORK_PREFIX)
+        .await?
+        .iter()
+        .filter_map(|(_, value)| serde_yaml::from_str::<Network>(value).ok())
+        .filter_map(|network| Some((network.get_name(), network.get_subnet()?)))
+        .collect();
+
+    let conflicts = subnet_conflicts(&new, &stored);
+    if conflicts.is_empty() {
+        Ok(())
+    } else {
+        Err(conflicts.join("; ").into())
+    }
+}
+
+/// Dependency cycle between the scenarios of a bundle and stored scenarios
+///
+/// ### Parameters
+/// * `new: &[Scenario]` - scenarios being registered
+/// * `stored: &[Scenario]` - scenarios already registered
+/// ### Returns
+/// * `Option<String>` - the scenarios along a cycle. A stored scenario
+///   with the name of a new one is replaced by it.
+fn dependency_cycle(new: &[Scenario], stored: &[Scenario]) -> Option<String> {
+    let mut dependencies = std::collections::HashMap::new();
+    for scenario in stored.iter().chain(new) {
+        dependencies.insert(scenario.get_name(), scenario.get_depends_on().clone());
+    }
+    let roots: Vec<String> = new.iter().map(|s| s.get_name()).collect();
+    dependency_order(&dependencies, &roots).err()
+}
+
+/// Fail if a scenario of the bundle closes a dependency cycle
+async fn check_scenario_dependencies(documents: &[parser::DocumentResult]) -> common::Result<()> {
+    let new: Vec<Scenario> = documents
+        .iter()
+        .filter(|d| d.kind.as_deref() == Some(KIND_SCENARIO))
+        .filter_map(|d| d.result.as_ref().ok())
+        .filter_map(|value| serde_yaml::from_value::<Scenario>(value.clone()).ok())
+        .collect();
+    if new.iter().all(|s| s.get_depends_on().is_empty()) {
+        return Ok(());
+    }
+
+    let stored: Vec<Scenario> = common::etcd::get_all_with_prefix(keys::SCENARIO_PREFIX)
+        .await?
+        .iter()
+        .filter_map(|(_, value)| serde_yaml::from_str::<Scenario>(value).ok())
+        .collect();
+
+    match dependency_cycle(&new, &stored) {
+        Some(cycle) => Err(cycle.into()),
+        None => Ok(()),
+    }
+}
+
 /// Delete downloaded artifact to etcd
 ///
 /// ### Parametets
@@ -182,18 +443,15 @@ pub async fn apply(body: &str) -> common::Result<String> {
 /// ### Description
 /// Delete scenario yaml only, because other scenario can use a package with same name
 pub async fn withdraw(body: &str) -> common::Result<String> {
-    let docs: Vec<&str> = body.split(YAML_SEPARATOR).collect();
-
-    for doc in docs {
-        let value: serde_yaml::Value = serde_yaml::from_str(doc)?;
-
-        if let Some((kind, name)) = parse_artifact_info(&value) {
-            if kind == KIND_SCENARIO {
-                let artifact_str = serde_yaml::to_string(&value)?;
-                let key = format!("{}/{}", KIND_SCENARIO, name);
-                data::delete_at_etcd(&key).await?;
-                return Ok(artifact_str);
-            }
+    let documents = parser::parse(body, false)?;
+    for document in documents {
+        if let (Ok(value), Some(KIND_SCENARIO), Some(name)) =
+            (&document.result, document.kind.as_deref(), &document.name)
+        {
+            let artifact_str = serde_yaml::to_string(value)?;
+            let key = keys::scenario(name);
+            data::delete_at_etcd(&key).await?;
+            return Ok(artifact_str);
         }
     }
 
@@ -201,50 +459,51 @@ pub async fn withdraw(body: &str) -> common::Result<String> {
 }
 
 /// Load model with optional volume and network resources
+///
+/// ### Returns
+/// * `Result<(Model, Option<String>)>` - the model and the network its pod joins
 async fn load_model_with_resources(
     model_info: &common::spec::artifact::package::ModelInfo,
-) -> common::Result<Model> {
-    let model_str = common::etcd::get(&format!("{}/{}", KIND_MODEL, model_info.get_name())).await?;
+) -> common::Result<(Model, Option<String>)> {
+    let model_str = common::etcd::get(&keys::model(&model_info.get_name())).await?;
     let mut model: Model = serde_yaml::from_str(&model_str)?;
 
     // Load volume if specified
     if let Some(volume_name) = model_info.get_resources().get_volume() {
-        let volume_str = common::etcd::get(&format!("{}/{}", KIND_VOLUME, volume_name)).await?;
+        let volume_str = common::etcd::get(&keys::volume(&volume_name)).await?;
         let volume: Volume = serde_yaml::from_str(&volume_str)?;
 
-        if let Some(volume_spec) = volume.get_spec() {
-            model
-                .get_podspec_mut()
-                .volumes
-                .clone_from(volume_spec.get_volume());
+        if let Some(volumes) = volume.get_pod_volumes() {
+            model.get_podspec_mut().volumes = Some(volumes);
         }
     }
 
-    // Load network if specified
-    if let Some(network_name) = model_info.get_resources().get_network() {
-        let network_str = common::etcd::get(&format!("{}/{}", KIND_NETWORK, network_name)).await?;
-        let _network: Network = serde_yaml::from_str(&network_str)?;
-        // TODO: Apply network configuration
-    }
+    // Load network if specified, NodeAgent creates it before starting the pod
+    let network = match model_info.get_resources().get_network() {
+        Some(network_name) if !network_name.is_empty() => {
+            let network_str = common::etcd::get(&keys::network(&network_name)).await?;
+            let network: Network = serde_yaml::from_str(&network_str)?;
+            Some(network.get_name())
+        }
+        _ => None,
+    };
 
-    Ok(model)
+    Ok((model, network))
 }
 
 /// Save Pod YAML for all models in a package
 async fn save_pod_yaml_from_package(package_str: &str) -> common::Result<()> {
     let package: Package = serde_yaml::from_str(package_str)?;
-    let mut models = Vec::new();
 
     for model_info in package.get_models() {
-        let model = load_model_with_resources(&model_info).await?;
-        models.push(model);
-    }
-
-    let pods: Vec<Pod> = models.into_iter().map(Pod::from).collect();
+        let (model, network) = load_model_with_resources(model_info).await?;
+        let mut pod = Pod::from(model);
+        if let Some(network) = network {
+            pod.set_annotation(NETWORK_ANNOTATION, &network);
+        }
 
-    for pod in pods {
         let pod_yaml = serde_yaml::to_string(&pod)?;
-        let key = format!("{}/{}", "Pod", pod.get_name());
+        let key = keys::pod(&pod.get_name());
         data::write_to_etcd(&key, &pod_yaml).await?;
     }
 
@@ -327,6 +586,18 @@ spec:
   dummy: value
 "#;
 
+    /// Volume used by the package when its `volume:` is filled in
+    const VOLUME_YAML: &str = r#"
+---
+apiVersion: v1
+kind: Volume
+metadata:
+  name: shared-data
+spec:
+  kind: hostPath
+  accessModes: [ReadWriteMany]
+"#;
+
     /// Invalid YAML — empty string
     const INVALID_YAML_EMPTY: &str = "";
 
@@ -362,7 +633,7 @@ spec:
             .await
             .unwrap();
 
-        let result = apply(VALID_ARTIFACT_YAML).await;
+        let result = apply(VALID_ARTIFACT_YAML, false).await;
 
         // Assert: should succeed because both Scenario + Package present and valid
         assert!(
@@ -382,7 +653,7 @@ spec:
     /// Test apply() with missing `action` field (invalid Scenario)
     #[tokio::test]
     async fn test_apply_invalid_missing_action() {
-        let result = apply(INVALID_YAML_MISSING_ACTION).await;
+        let result = apply(INVALID_YAML_MISSING_ACTION, false).await;
 
         // Assert: should fail because Scenario is invalid (missing required field)
         assert!(
@@ -394,7 +665,7 @@ spec:
     /// Test apply() with unknown artifact (no Scenario, no Package)
     #[tokio::test]
     async fn test_apply_invalid_unknown_artifact() {
-        let result = apply(INVALID_YAML_UNKNOWN_ARTIFACT).await;
+        let result = apply(INVALID_YAML_UNKNOWN_ARTIFACT, false).await;
 
         // Assert: should fail because no Scenario or Package present
         assert!(
@@ -406,7 +677,7 @@ spec:
     /// Test apply() with empty YAML
     #[tokio::test]
     async fn test_apply_invalid_empty_yaml() {
-        let result = apply(INVALID_YAML_EMPTY).await;
+        let result = apply(INVALID_YAML_EMPTY, false).await;
 
         // Assert: should fail because YAML is empty
         assert!(
@@ -415,6 +686,162 @@ spec:
         );
     }
 
+    // -- validate() tests --
+
+    /// Test validate() with a known-good artifact: parsed, but never stored
+    #[tokio::test]
+    async fn test_validate_valid_artifact_writes_nothing() {
+        let body = VALID_ARTIFACT_YAML.replace("helloworld", "validate-only");
+
+        let validation = validate(&body).await.unwrap();
+        assert_eq!(
+            validation.keys(),
+            vec!["Scenario/validate-only", "Package/validate-only"]
+        );
+        assert!(validation.warnings.is_empty());
+
+        // Assert: nothing was written for any document
+        for key in validation.keys() {
+            assert!(
+                data::read_from_etcd(&key).await.is_err(),
+                "validate() wrote {}",
+                key
+            );
+        }
+    }
+
+    /// Test validate() with a known-bad artifact: rejected, and never stored
+    #[tokio::test]
+    async fn test_validate_invalid_artifact_writes_nothing() {
+        let body = INVALID_YAML_MISSING_ACTION.replace("helloworld", "validate-bad");
+
+        let result = validate(&body).await;
+        assert!(
+            result.is_err(),
+            "validate() unexpectedly accepted missing action"
+        );
+
+        assert!(data::read_from_etcd("Scenario/validate-bad").await.is_err());
+        assert!(data::read_from_etcd("Package/validate-bad").await.is_err());
+    }
+
+    /// Test validate() rejects an artifact without a package
+    #[tokio::test]
+    async fn test_validate_requires_package() {
+        let scenario_only = VALID_ARTIFACT_YAML.split("---").next().unwrap();
+
+        let err = validate(scenario_only).await.unwrap_err();
+        assert_eq!(err.to_string(), "There is not any package in yaml string");
+    }
+
+    /// Test validate() accepts a package using a volume of the same bundle
+    #[tokio::test]
+    async fn test_validate_volume_defined_in_bundle() {
+        let body = format!(
+            "{}{}",
+            VALID_ARTIFACT_YAML.replace("volume:\n", "volume: shared-data\n"),
+            VOLUME_YAML
+        );
+
+        let validation = validate(&body).await.unwrap();
+        assert!(validation
+            .keys()
+            .contains(&"Volume/shared-data".to_string()));
+    }
+
+    /// Test validate() rejects a package using a volume defined nowhere
+    #[tokio::test]
+    async fn test_validate_rejects_dangling_volume() {
+        let body = VALID_ARTIFACT_YAML.replace("volume:\n", "volume: no-such-volume\n");
+
+        let err = validate(&body).await.unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "package 'helloworld' references unknown volume 'no-such-volume'"
+        );
+    }
+
+    #[test]
+    fn test_undefined_volumes() {
+        let body = VALID_ARTIFACT_YAML.replace("volume:\n", "volume: shared-data\n");
+        let documents = parser::parse(&body, true).unwrap();
+        assert_eq!(
+            undefined_volumes(&documents),
+            vec![("helloworld".to_string(), "shared-data".to_string())]
+        );
+
+        let documents = parser::parse(&format!("{}{}", body, VOLUME_YAML), true).unwrap();
+        assert!(undefined_volumes(&documents).is_empty());
+
+        // An empty `volume:` is no reference
+        let documents = parser::parse(VALID_ARTIFACT_YAML, true).unwrap();
+        assert!(undefined_volumes(&documents).is_empty());
+    }
+
+    #[test]
+    fn test_subnet_conflicts() {
+        let network = |name: &str, subnet: &str| (name.to_string(), subnet.parse().unwrap());
+        let new = vec![
+            network("bms-net", "10.89.1.0/24"),
+            network("adas-net", "10.89.2.0/24"),
+        ];
+
+        assert!(subnet_conflicts(&new, &[network("infotainment", "10.90.0.0/16")]).is_empty());
+
+        // Re-registering a network replaces its stored version
+        assert!(subnet_conflicts(&new, &[network("bms-net", "10.89.0.0/16")]).is_empty());
+
+        assert_eq!(
+            subnet_conflicts(&new, &[network("legacy", "10.89.0.0/16")]),
+            vec![
+                "network 'bms-net' subnet 10.89.1.0/24 overlaps network 'legacy' subnet 10.89.0.0/16",
+                "network 'adas-net' subnet 10.89.2.0/24 overlaps network 'legacy' subnet 10.89.0.0/16",
+            ]
+        );
+
+        let new = vec![
+            network("bms-net", "10.89.1.0/24"),
+            network("bms-net-2", "10.89.1.128/25"),
+        ];
+        assert_eq!(subnet_conflicts(&new, &[]).len(), 1);
+    }
+
+    #[test]
+    fn test_package_names() {
+        let documents = parser::parse(VALID_ARTIFACT_YAML, false).unwrap();
+        assert_eq!(package_names(&documents), vec!["helloworld"]);
+    }
+
+    #[test]
+    fn test_dependency_cycle() {
+        let scenario = |name: &str, depends_on: &str| -> Scenario {
+            serde_yaml::from_str(&format!(
+                "apiVersion: v1\nkind: Scenario\nmetadata:\n  name: {}\nspec:\n  \
+                 action: launch\n  target: {}\n  dependsOn: {}\n",
+                name, name, depends_on
+            ))
+            .unwrap()
+        };
+        let stored = vec![scenario("camera", "[]"), scenario("radar", "[camera]")];
+
+        // Diamond over stored scenarios
+        let new = vec![
+            scenario("fusion", "[camera, radar]"),
+            scenario("parking", "[fusion, radar]"),
+        ];
+        assert_eq!(dependency_cycle(&new, &stored), None);
+
+        // Re-registering a stored scenario with a dependency back on itself
+        let new = vec![
+            scenario("camera", "[parking]"),
+            scenario("parking", "[radar]"),
+        ];
+        assert_eq!(
+            dependency_cycle(&new, &stored),
+            Some("dependency cycle: camera -> parking -> radar -> camera".to_string())
+        );
+    }
+
     // -- withdraw() tests --
 
     /// Test withdraw() with valid artifact YAML (Scenario present)
@@ -0,0 +1,398 @@
+/*
+ * SPDX-FileCopyrightText: Copyright 2024 LG Electronics Inc.
+ * SPDX-License-Identifier: Apache-2.0
+ */
+
+//! Split multi-document artifact YAML and validate each document
+
+use super::{
+    KIND_MODEL, KIND_NETWORK, KIND_NODE, KIND_PACKAGE, KIND_POLICY, KIND_SCENARIO, KIND_SCHEDULE,
+    KIND_VOLUME,
+};
+use common::spec::artifact::{
+    Artifact, Model, Network, Node, Package, Policy, Scenario, Schedule, Volume,
+};
+use serde::Deserialize;
+
+/// Parse outcome of one document in an artifact bundle
+#[derive(Debug, Clone, PartialEq)]
+pub struct DocumentResult {
+    /// Position of the document in the bundle, starting at 0
+    pub index: usize,
+    /// `kind` field, if the document has one
+    pub kind: Option<String>,
+    /// `metadata.name`, if the document could be read
+    pub name: Option<String>,
+    /// Parsed document, or the reason it was rejected
+    pub result: Result<serde_yaml::Value, String>,
+}
+
+impl DocumentResult {
+    /// Returns `true` if the document is a valid artifact
+    pub fn is_ok(&self) -> bool {
+        self.result.is_ok()
+    }
+}
+
+impl std::fmt::Display for DocumentResult {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "document {} ({}/{}): ",
+            self.index,
+            self.kind.as_deref().unwrap_or("?"),
+            self.name.as_deref().unwrap_or("?")
+        )?;
+        match &self.result {
+            Ok(_) => write!(f, "ok"),
+            Err(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+/// Identify the artifact kind and name of a YAML document
+///
+/// ### Returns
+/// * `Result<(String, String), String>` - kind and name, or why the document is not a valid artifact
+pub fn identify(value: &serde_yaml::Value) -> Result<(String, String), String> {
+    let kind = value
+        .get("kind")
+        .and_then(|k| k.as_str())
+        .ok_or_else(|| "missing `kind` field".to_string())?;
+
+    let name = match kind {
+        KIND_SCENARIO => deserialize::<Scenario>(value)?.get_name(),
+        KIND_PACKAGE => deserialize::<Package>(value)?.get_name(),
+        KIND_VOLUME => {
+            let volume = deserialize::<Volume>(value)?;
+            volume.validate()?;
+            volume.get_name()
+        }
+        KIND_NETWORK => {
+            let network = deserialize::<Network>(value)?;
+            network.validate()?;
+            network.get_name()
+        }
+        KIND_NODE => deserialize::<Node>(value)?.get_name(),
+        KIND_MODEL => deserialize::<Model>(value)?.get_name(),
+        KIND_SCHEDULE => deserialize::<Schedule>(value)?.get_name(),
+        KIND_POLICY => deserialize::<Policy>(value)?.get_name(),
+        _ => return Err(format!("unknown artifact kind `{}`", kind)),
+    };
+
+    Ok((kind.to_string(), name))
+}
+
+fn deserialize<T: serde::de::DeserializeOwned>(value: &serde_yaml::Value) -> Result<T, String> {
+    serde_yaml::from_value::<T>(value.clone()).map_err(|e| e.to_string())
+}
+
+/// Split `body` into YAML documents and validate each one
+///
+/// ### Parameters
+/// * `body: &str` - artifact bundle, documents separated by `---`
+/// * `strict: bool` - fail when any document is invalid
+/// ### Returns
+/// * `Result<Vec<DocumentResult>>` - one entry per non-empty document
+/// ### Description
+/// Without `strict`, an invalid document is reported in its own entry and
+/// the other documents are still returned.
+pub fn parse(body: &str, strict: bool) -> common::Result<Vec<DocumentResult>> {
+    let values = split_documents(body);
+
+    let mut results = Vec::new();
+    for value in values {
+        let index = results.len();
+        let value = match value {
+            Ok(serde_yaml::Value::Null) => continue,
+            Ok(value) => value,
+            Err(e) => {
+                results.push(DocumentResult {
+                    index,
+                    kind: None,
+                    name: None,
+                    result: Err(format!("invalid YAML: {}", e)),
+                });
+                continue;
+            }
+        };
+
+        let kind = value
+            .get("kind")
+            .and_then(|k| k.as_str())
+            .map(str::to_string);
+        let name = value
+            .get("metadata")
+            .and_then(|m| m.get("name"))
+            .and_then(|n| n.as_str())
+            .map(str::to_string);
+
+        let result = identify(&value).map(|_| value);
+        results.push(DocumentResult {
+            index,
+            kind,
+            name,
+            result,
+        });
+    }
+
+    if strict {
+        if let Some(failed) = results.iter().find(|r| !r.is_ok()) {
+            return Err(format!("strict mode: {}", failed).into());
+        }
+    }
+
+    Ok(results)
+}
+
+/// Deserialize every document of a YAML stream
+///
+/// A syntax error aborts the whole stream in the YAML deserializer, so in
+/// that case documents are split on `---` lines and parsed one by one to
+/// keep the valid ones.
+fn split_documents(body: &str) -> Vec<Result<serde_yaml::Value, serde_yaml::Error>> {
+    let mut values = Vec::new();
+    for document in serde_yaml::Deserializer::from_str(body) {
+        match serde_yaml::Value::deserialize(document) {
+            Ok(value) => values.push(Ok(value)),
+            // The stream keeps yielding the same error after a syntax error
+            Err(_) => {
+                values.clear();
+                break;
+            }
+        }
+    }
+    if !values.is_empty() || body.trim().is_empty() {
+        return values;
+    }
+
+    let mut documents = vec![String::new()];
+    for line in body.lines() {
+        if line.trim_end() == "---" {
+            documents.push(String::new());
+        } else if let Some(current) = documents.last_mut() {
+            current.push_str(line);
+            current.push('\n');
+        }
+    }
+
+    documents
+        .iter()
+        .map(|doc| serde_yaml::from_str::<serde_yaml::Value>(doc))
+        .collect()
+}
+
+//UNIT TEST CASES
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SCENARIO: &str = r#"
+apiVersion: v1
+kind: Scenario
+metadata:
+  name: helloworld
+spec:
+  condition:
+  action: update
+  target: helloworld
+"#;
+
+    const PACKAGE: &str = r#"
+apiVersion: v1
+kind: Package
+metadata:
+  label: null
+  name: helloworld
+spec:
+  pattern:
+    - type: plain
+  models:
+    - name: helloworld-core
+      node: HPC
+      resources:
+        volume:
+        network:
+"#;
+
+    /// Scenario without the required `action` field
+    const BAD_SCENARIO: &str = r#"
+apiVersion: v1
+kind: Scenario
+metadata:
+  name: broken
+spec:
+  condition:
+  target: broken
+"#;
+
+    const UNKNOWN_KIND: &str = r#"
+apiVersion: v1
+kind: Unknown
+metadata:
+  name: mystery
+spec:
+  dummy: value
+"#;
+
+    const POLICY: &str = r#"
+apiVersion: v1
+kind: Policy
+metadata:
+  name: night-limit
+spec:
+  action: deny
+  timeWindow:
+    start: "22:00"
+    end: "06:00"
+"#;
+
+    /// Image volume missing its `image` field
+    const IMAGE_VOLUME: &str = r#"
+apiVersion: v1
+kind: Volume
+metadata:
+  name: maps
+spec:
+  kind: image
+"#;
+
+    /// Network whose gateway is outside its subnet
+    const BAD_NETWORK: &str = r#"
+apiVersion: v1
+kind: Network
+metadata:
+  name: bms-net
+spec:
+  subnet: 10.89.1.0/24
+  gateway: 10.89.2.1
+"#;
+
+    fn bundle(docs: &[&str]) -> String {
+        docs.join("---")
+    }
+
+    #[test]
+    fn test_parse_all_valid_documents() {
+        let results = parse(&bundle(&[SCENARIO, PACKAGE]), false).unwrap();
+
+        assert_eq!(results.len(), 2);
+        assert!(results.iter().all(DocumentResult::is_ok));
+        assert_eq!(results[0].kind.as_deref(), Some(KIND_SCENARIO));
+        assert_eq!(results[0].name.as_deref(), Some("helloworld"));
+        assert_eq!(results[1].kind.as_deref(), Some(KIND_PACKAGE));
+        assert_eq!(results[1].index, 1);
+    }
+
+    #[test]
+    fn test_parse_policy_document() {
+        let results = parse(&bundle(&[SCENARIO, PACKAGE, POLICY]), true).unwrap();
+
+        assert!(results[2].is_ok());
+        assert_eq!(results[2].kind.as_deref(), Some(KIND_POLICY));
+        assert_eq!(results[2].name.as_deref(), Some("night-limit"));
+    }
+
+    #[test]
+    fn test_parse_volume_document_is_validated() {
+        let results = parse(&bundle(&[SCENARIO, PACKAGE, IMAGE_VOLUME]), false).unwrap();
+
+        assert_eq!(results[2].kind.as_deref(), Some(KIND_VOLUME));
+        assert_eq!(
+            results[2].result.as_ref().unwrap_err(),
+            "volume 'maps': image volume without `image`"
+        );
+
+        let volume = format!("{}  image: maps:v2\n", IMAGE_VOLUME);
+        let results = parse(&bundle(&[SCENARIO, PACKAGE, &volume]), true).unwrap();
+        assert!(results[2].is_ok());
+    }
+
+    #[test]
+    fn test_parse_network_document_is_validated() {
+        let results = parse(&bundle(&[SCENARIO, PACKAGE, BAD_NETWORK]), false).unwrap();
+
+        assert_eq!(results[2].kind.as_deref(), Some(KIND_NETWORK));
+        assert_eq!(
+            results[2].result.as_ref().unwrap_err(),
+            "network 'bms-net': gateway 10.89.2.1 is outside 10.89.1.0/24"
+        );
+
+        let network = BAD_NETWORK.replace("10.89.2.1", "10.89.1.1");
+        assert!(parse(&bundle(&[SCENARIO, PACKAGE, &network]), true).is_ok());
+    }
+
+    #[test]
+    fn test_parse_mixed_documents_keeps_valid_ones() {
+        let body = bundle(&[BAD_SCENARIO, SCENARIO, UNKNOWN_KIND, PACKAGE]);
+        let results = parse(&body, false).unwrap();
+
+        assert_eq!(results.len(), 4);
+        assert!(!results[0].is_ok());
+        assert_eq!(results[0].name.as_deref(), Some("broken"));
+        assert!(results[1].is_ok());
+        assert!(!results[2].is_ok());
+        assert_eq!(
+            results[2].result.as_ref().unwrap_err(),
+            "unknown artifact kind `Unknown`"
+        );
+        assert!(results[3].is_ok());
+    }
+
+    #[test]
+    fn test_parse_strict_rejects_bundle_with_invalid_document() {
+        let body = bundle(&[SCENARIO, UNKNOWN_KIND, PACKAGE]);
+
+        let err = parse(&body, true).unwrap_err();
+        assert!(err.to_string().contains("document 1 (Unknown/mystery)"));
+
+        assert!(parse(&bundle(&[SCENARIO, PACKAGE]), true).is_ok());
+    }
+
+    #[test]
+    fn test_parse_missing_kind() {
+        let results = parse("apiVersion: v1\nmetadata:\n  name: nokind\n", false).unwrap();
+
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].kind, None);
+        assert_eq!(
+            results[0].result.as_ref().unwrap_err(),
+            "missing `kind` field"
+        );
+    }
+
+    #[test]
+    fn test_parse_skips_empty_documents() {
+        let body = format!("---\n{}---\n---\n{}", SCENARIO, PACKAGE);
+        let results = parse(&body, false).unwrap();
+
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[1].index, 1);
+        assert!(parse("", false).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_parse_separator_inside_value_is_not_split() {
+        let scenario = SCENARIO.replace("target: helloworld", "target: \"a---b\"");
+        let results = parse(&bundle(&[&scenario, PACKAGE]), false).unwrap();
+
+        assert_eq!(results.len(), 2);
+        assert!(results.iter().all(DocumentResult::is_ok));
+    }
+
+    #[test]
+    fn test_parse_syntax_error_keeps_other_documents() {
+        let body = bundle(&[SCENARIO, "\nkind: [unclosed\n", PACKAGE]);
+        let results = parse(&body, false).unwrap();
+
+        assert_eq!(results.len(), 3);
+        assert!(results[0].is_ok());
+        assert!(results[1]
+            .result
+            .as_ref()
+            .unwrap_err()
+            .starts_with("invalid YAML"));
+        assert!(results[2].is_ok());
+        assert!(parse(&body, true).is_err());
+    }
+}
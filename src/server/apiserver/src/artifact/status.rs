@@ -0,0 +1,324 @@
+/*
+ * SPDX-FileCopyrightText: Copyright 2024 LG Electronics Inc.
+ * SPDX-License-Identifier: Apache-2.0
+ */
+
+//! Import status of artifacts
+//!
+//! An artifact is tracked under the name of its scenario. It is `Pending`
+//! once parsed, `Stored` once its documents are in etcd and `Propagated`
+//! once filtergateway received the scenario. A stage that fails leaves it
+//! `Failed` with the error. A scenario queued while filtergateway is
+//! unreachable stays `Stored` until the queue delivers it.
+
+use crate::grpc::sender::filtergateway::Delivery;
+use common::{keys, logd};
+use serde::{Deserialize, Serialize};
+use std::future::Future;
+use tonic::Status;
+
+/// Stage an artifact import reached
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum ArtifactStatus {
+    /// Parsed, waiting to be stored
+    Pending,
+    /// Stored in etcd, not yet received by filtergateway
+    Stored,
+    /// Received by filtergateway
+    Propagated,
+    /// A stage failed, see the error
+    Failed,
+}
+
+/// Status of one artifact, as stored and listed
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StatusRecord {
+    /// Name of the scenario of the artifact
+    pub name: String,
+    pub status: ArtifactStatus,
+    /// Why the artifact is `Failed`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    /// Time of the last update, RFC 3339
+    pub updated_at: String,
+}
+
+/// Where status records are kept
+#[tonic::async_trait]
+pub trait StatusStore: Send + Sync {
+    async fn put(&self, key: &str, value: &str) -> Result<(), String>;
+    async fn get_all(&self, prefix: &str) -> Result<Vec<(String, String)>, String>;
+    async fn delete(&self, key: &str) -> Result<(), String>;
+}
+
+/// Status records kept in etcd, at `keys::artifact_status`
+pub struct Etcd;
+
+#[tonic::async_trait]
+impl StatusStore for Etcd {
+    async fn put(&self, key: &str, value: &str) -> Result<(), String> {
+        common::etcd::put(key, value)
+            .await
+            .map_err(|e| e.to_string())
+    }
+
+    async fn get_all(&self, prefix: &str) -> Result<Vec<(String, String)>, String> {
+        common::etcd::get_all_with_prefix(prefix)
+            .await
+            .map_err(|e| e.to_string())
+    }
+
+    async fn delete(&self, key: &str) -> Result<(), String> {
+        common::etcd::delete(key).await.map_err(|e| e.to_string())
+    }
+}
+
+/// Set the status of the artifacts of `names`
+///
+/// ### Description
+/// A record that cannot be written is logged. Tracking never fails an
+/// import.
+pub async fn record(
+    store: &dyn StatusStore,
+    names: &[String],
+    status: ArtifactStatus,
+    error: Option<&str>,
+) {
+    let updated_at = chrono::Utc::now().to_rfc3339();
+    for name in names {
+        let record = StatusRecord {
+            name: name.clone(),
+            status,
+            error: error.map(str::to_string),
+            updated_at: updated_at.clone(),
+        };
+        let value = match serde_json::to_string(&record) {
+            Ok(value) => value,
+            Err(e) => {
+                logd!(4, "Failed to serialize status of {}: {:?}", name, e);
+                continue;
+            }
+        };
+        if let Err(e) = store.put(&keys::artifact_status(name), &value).await {
+            logd!(4, "Failed to record status {:?} of {}: {}", status, name, e);
+        }
+    }
+}
+
+/// Set the status of the artifacts of `names` from the result of a stage
+///
+/// ### Returns
+/// * the result of the stage, unchanged
+pub async fn record_result<T>(
+    store: &dyn StatusStore,
+    names: &[String],
+    done: ArtifactStatus,
+    result: Result<T, String>,
+) -> Result<T, String> {
+    match &result {
+        Ok(_) => record(store, names, done, None).await,
+        Err(e) => record(store, names, ArtifactStatus::Failed, Some(e)).await,
+    }
+    result
+}
+
+/// Propagate the artifacts of `names` to filtergateway with `notify`
+///
+/// ### Description
+/// A delivered scenario is `Propagated`, a rejected one `Failed`. A
+/// queued one stays `Stored`.
+pub async fn propagate<F>(
+    store: &dyn StatusStore,
+    names: &[String],
+    notify: F,
+) -> Result<Delivery, Status>
+where
+    F: Future<Output = Result<Delivery, Status>>,
+{
+    let result = notify.await;
+    match &result {
+        Ok(Delivery::Delivered) => record(store, names, ArtifactStatus::Propagated, None).await,
+        Ok(Delivery::Pending) => {}
+        Err(status) => {
+            let error = format!("propagation to filtergateway failed: {}", status.message());
+            record(store, names, ArtifactStatus::Failed, Some(&error)).await
+        }
+    }
+    result
+}
+
+/// Remove the status of a withdrawn artifact
+pub async fn forget(store: &dyn StatusStore, name: &str) {
+    if let Err(e) = store.delete(&keys::artifact_status(name)).await {
+        logd!(4, "Failed to delete status of {}: {}", name, e);
+    }
+}
+
+/// Status of every tracked artifact, by name
+pub async fn list(store: &dyn StatusStore) -> Result<Vec<StatusRecord>, String> {
+    let stored = store.get_all(keys::ARTIFACT_STATUS_PREFIX).await?;
+    let mut records: Vec<StatusRecord> = stored
+        .into_iter()
+        .filter_map(|(key, value)| match serde_json::from_str(&value) {
+            Ok(record) => Some(record),
+            Err(e) => {
+                logd!(4, "Ignoring malformed status {}: {:?}", key, e);
+                None
+            }
+        })
+        .collect();
+    records.sort_by(|a, b| a.name.cmp(&b.name));
+    Ok(records)
+}
+
+//UNIT TEST CASES
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::BTreeMap;
+    use std::sync::Mutex;
+
+    #[derive(Default)]
+    struct MemoryStore {
+        values: Mutex<BTreeMap<String, String>>,
+    }
+
+    #[tonic::async_trait]
+    impl StatusStore for MemoryStore {
+        async fn put(&self, key: &str, value: &str) -> Result<(), String> {
+            self.values
+                .lock()
+                .unwrap()
+                .insert(key.to_string(), value.to_string());
+            Ok(())
+        }
+
+        async fn get_all(&self, prefix: &str) -> Result<Vec<(String, String)>, String> {
+            let values = self.values.lock().unwrap();
+            Ok(values
+                .iter()
+                .filter(|(key, _)| key.starts_with(prefix))
+                .map(|(key, value)| (key.clone(), value.clone()))
+                .collect())
+        }
+
+        async fn delete(&self, key: &str) -> Result<(), String> {
+            self.values.lock().unwrap().remove(key);
+            Ok(())
+        }
+    }
+
+    fn names(name: &str) -> Vec<String> {
+        vec![name.to_string()]
+    }
+
+    async fn status_of(store: &MemoryStore, name: &str) -> StatusRecord {
+        list(store)
+            .await
+            .unwrap()
+            .into_iter()
+            .find(|record| record.name == name)
+            .expect("no status recorded")
+    }
+
+    #[tokio::test]
+    async fn test_successful_import_ends_propagated() {
+        let store = MemoryStore::default();
+        let names = names("helloworld");
+
+        record(&store, &names, ArtifactStatus::Pending, None).await;
+        assert_eq!(
+            status_of(&store, "helloworld").await.status,
+            ArtifactStatus::Pending
+        );
+        let stored = record_result(&store, &names, ArtifactStatus::Stored, Ok("scenario")).await;
+        assert!(stored.is_ok());
+        assert_eq!(
+            status_of(&store, "helloworld").await.status,
+            ArtifactStatus::Stored
+        );
+
+        let delivery = propagate(&store, &names, async { Ok(Delivery::Delivered) }).await;
+
+        assert_eq!(delivery.unwrap(), Delivery::Delivered);
+        let record = status_of(&store, "helloworld").await;
+        assert_eq!(record.status, ArtifactStatus::Propagated);
+        assert_eq!(record.error, None);
+    }
+
+    #[tokio::test]
+    async fn test_propagation_failure_ends_failed() {
+        let store = MemoryStore::default();
+        let names = names("helloworld");
+        record(&store, &names, ArtifactStatus::Stored, None).await;
+
+        let delivery = propagate(&store, &names, async {
+            Err(Status::invalid_argument("unknown action"))
+        })
+        .await;
+
+        assert!(delivery.is_err());
+        let record = status_of(&store, "helloworld").await;
+        assert_eq!(record.status, ArtifactStatus::Failed);
+        assert_eq!(
+            record.error.as_deref(),
+            Some("propagation to filtergateway failed: unknown action")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_queued_propagation_stays_stored() {
+        let store = MemoryStore::default();
+        let names = names("helloworld");
+        record(&store, &names, ArtifactStatus::Stored, None).await;
+
+        let delivery = propagate(&store, &names, async { Ok(Delivery::Pending) }).await;
+
+        assert_eq!(delivery.unwrap(), Delivery::Pending);
+        assert_eq!(
+            status_of(&store, "helloworld").await.status,
+            ArtifactStatus::Stored
+        );
+    }
+
+    #[tokio::test]
+    async fn test_store_failure_ends_failed_and_forget_removes() {
+        let store = MemoryStore::default();
+        let names = names("helloworld");
+
+        let stored = record_result::<String>(
+            &store,
+            &names,
+            ArtifactStatus::Stored,
+            Err("package 'helloworld' references unknown volume 'data'".to_string()),
+        )
+        .await;
+
+        assert!(stored.is_err());
+        let record = status_of(&store, "helloworld").await;
+        assert_eq!(record.status, ArtifactStatus::Failed);
+        assert!(record.error.unwrap().contains("unknown volume"));
+
+        forget(&store, "helloworld").await;
+        assert!(list(&store).await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_list_sorts_and_skips_malformed() {
+        let store = MemoryStore::default();
+        record(&store, &names("b"), ArtifactStatus::Stored, None).await;
+        record(&store, &names("a"), ArtifactStatus::Pending, None).await;
+        store
+            .put(&keys::artifact_status("broken"), "not json")
+            .await
+            .unwrap();
+
+        let records = list(&store).await.unwrap();
+
+        let names: Vec<&str> = records.iter().map(|r| r.name.as_str()).collect();
+        assert_eq!(names, vec!["a", "b"]);
+        let json = serde_json::to_string(&records[0]).unwrap();
+        assert!(json.contains("\"status\":\"Pending\""));
+        assert!(!json.contains("error"));
+    }
+}
@@ -0,0 +1,512 @@
+/*
+ * SPDX-FileCopyrightText: Copyright 2024 LG Electronics Inc.
+ * SPDX-License-Identifier: Apache-2.0
+ */
+
+//! Support bundle collected on demand
+//!
+//! A bundle is a tar.gz archive holding one JSON file per section. The
+//! sections are collected concurrently. A section that fails or times out
+//! is written as a `.error` file instead of failing the whole bundle, and
+//! a section larger than the configured limit is cut at the limit.
+//! `manifest.json` lists the outcome of every section.
+
+use crate::node::NodeManager;
+use common::keys;
+use flate2::{write::GzEncoder, Compression};
+use serde::Serialize;
+use serde_json::{json, Map, Value};
+use std::time::Duration;
+
+type Error = Box<dyn std::error::Error + Send + Sync>;
+
+/// Source of one section of a bundle
+#[tonic::async_trait]
+pub trait Collector: Send + Sync {
+    /// Name of the section, used as its file name in the bundle
+    fn name(&self) -> &'static str;
+    /// Gather the content of the section
+    async fn collect(&self) -> Result<Value, String>;
+}
+
+/// Outcome of one section, as listed in the manifest
+#[derive(Debug, Serialize)]
+struct SectionEntry {
+    name: &'static str,
+    file: String,
+    bytes: usize,
+    truncated: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
+/// Collected bundle, ready to be sent
+pub struct Bundle {
+    /// Name of the directory every file of the archive is in
+    pub name: String,
+    /// tar.gz archive
+    pub data: Vec<u8>,
+}
+
+impl Bundle {
+    /// File name to offer for downloading the archive
+    pub fn file_name(&self) -> String {
+        format!("{}.tar.gz", self.name)
+    }
+}
+
+/// Collect every section and pack them into a tar.gz archive
+///
+/// ### Parameters
+/// * `collectors: &[Box<dyn Collector>]` - sources of the sections
+/// * `limit: usize` - largest size of one section file, in bytes
+/// * `timeout: Duration` - time given to each collector
+/// ### Returns
+/// * `Result<Bundle, Error>` - fails only if the archive cannot be written
+pub async fn build(
+    collectors: &[Box<dyn Collector>],
+    limit: usize,
+    timeout: Duration,
+) -> Result<Bundle, Error> {
+    let results = futures::future::join_all(collectors.iter().map(|collector| async move {
+        match tokio::time::timeout(timeout, collector.collect()).await {
+            Ok(result) => result,
+            Err(_) => Err(format!("timed out after {:?}", timeout)),
+        }
+    }))
+    .await;
+
+    let now = chrono::Utc::now();
+    let name = format!("pullpiri-diagnostics-{}", now.format("%Y%m%dT%H%M%SZ"));
+    let mtime = now.timestamp().max(0) as u64;
+    let mut archive = tar::Builder::new(GzEncoder::new(Vec::new(), Compression::default()));
+    let mut sections = Vec::new();
+
+    for (collector, result) in collectors.iter().zip(results) {
+        let entry = match result {
+            Ok(value) => {
+                let mut content = serde_json::to_vec_pretty(&value)?;
+                let truncated = content.len() > limit;
+                content.truncate(limit);
+                let file = format!("{}.json", collector.name());
+                append(&mut archive, &name, &file, &content, mtime)?;
+                SectionEntry {
+                    name: collector.name(),
+                    file,
+                    bytes: content.len(),
+                    truncated,
+                    error: None,
+                }
+            }
+            Err(e) => {
+                let file = format!("{}.error", collector.name());
+                append(&mut archive, &name, &file, e.as_bytes(), mtime)?;
+                SectionEntry {
+                    name: collector.name(),
+                    file,
+                    bytes: e.len(),
+                    truncated: false,
+                    error: Some(e),
+                }
+            }
+        };
+        sections.push(entry);
+    }
+
+    let manifest = json!({
+        "createdAt": now.to_rfc3339(),
+        "host": common::setting::get_config().host.name,
+        "sectionLimitBytes": limit,
+        "sections": sections,
+    });
+    let manifest = serde_json::to_vec_pretty(&manifest)?;
+    append(&mut archive, &name, "manifest.json", &manifest, mtime)?;
+
+    let data = archive.into_inner()?.finish()?;
+    Ok(Bundle { name, data })
+}
+
+fn append(
+    archive: &mut tar::Builder<GzEncoder<Vec<u8>>>,
+    dir: &str,
+    file: &str,
+    content: &[u8],
+    mtime: u64,
+) -> std::io::Result<()> {
+    let mut header = tar::Header::new_gnu();
+    header.set_size(content.len() as u64);
+    header.set_mode(0o644);
+    header.set_mtime(mtime);
+    header.set_cksum();
+    archive.append_data(&mut header, format!("{}/{}", dir, file), content)
+}
+
+/// Collectors of a bundle of this API server
+///
+/// ### Parameters
+/// * `history_limit: usize` - number of most recent decisions to include
+pub fn collectors(history_limit: usize) -> Vec<Box<dyn Collector>> {
+    vec![
+        Box::new(EtcdKeys),
+        Box::new(ComponentHealth),
+        Box::new(StateHistory {
+            limit: history_limit,
+        }),
+        Box::new(Nodes),
+    ]
+}
+
+/// Key prefixes of the Pullpiri artifacts and bookkeeping saved in etcd
+///
+/// Monitoring metrics and logs are left out, they have their own services.
+const ETCD_PREFIXES: &[&str] = &[
+    keys::SCENARIO_PREFIX,
+    keys::PACKAGE_PREFIX,
+    keys::MODEL_PREFIX,
+    keys::VOLUME_PREFIX,
+    keys::NETWORK_PREFIX,
+    keys::SCHEDULE_PREFIX,
+    keys::POLICY_PREFIX,
+    keys::CLUSTER_PREFIX,
+    keys::NODE_ADDRESS_PREFIX,
+    keys::NETWORK_REFS_PREFIX,
+];
+
+/// Stored keys under the Pullpiri prefixes
+struct EtcdKeys;
+
+#[tonic::async_trait]
+impl Collector for EtcdKeys {
+    fn name(&self) -> &'static str {
+        "etcd"
+    }
+
+    async fn collect(&self) -> Result<Value, String> {
+        let mut stored = Map::new();
+        let mut errors = Map::new();
+        for prefix in ETCD_PREFIXES {
+            match common::etcd::get_all_with_prefix(prefix).await {
+                Ok(entries) => {
+                    for (key, value) in entries {
+                        stored.insert(key, json!(value));
+                    }
+                }
+                Err(e) => {
+                    errors.insert(prefix.to_string(), json!(e));
+                }
+            }
+        }
+
+        if stored.is_empty() && errors.len() == ETCD_PREFIXES.len() {
+            return Err(format!("etcd unreachable: {}", json!(errors)));
+        }
+        Ok(json!({ "keys": stored, "errors": errors }))
+    }
+}
+
+/// gRPC servers of the Pullpiri components
+fn components() -> Vec<(&'static str, String)> {
+    vec![
+        ("apiserver", common::apiserver::connect_grpc_server()),
+        (
+            "actioncontroller",
+            common::actioncontroller::connect_server(),
+        ),
+        ("filtergateway", common::filtergateway::connect_server()),
+        (
+            "monitoringserver",
+            common::monitoringserver::connect_server(),
+        ),
+        ("policymanager", common::policymanager::connect_server()),
+        ("statemanager", common::statemanager::connect_server()),
+    ]
+}
+
+/// Health reported by the gRPC server of each component
+struct ComponentHealth;
+
+#[tonic::async_trait]
+impl Collector for ComponentHealth {
+    fn name(&self) -> &'static str {
+        "health"
+    }
+
+    async fn collect(&self) -> Result<Value, String> {
+        let components = components();
+        let statuses =
+            futures::future::join_all(components.iter().map(|(_, url)| common::health::check(url)))
+                .await;
+
+        let mut health = Map::new();
+        for ((component, url), status) in components.into_iter().zip(statuses) {
+            let entry = match status {
+                Ok(status) => json!({ "url": url, "status": status }),
+                Err(e) => json!({ "url": url, "error": e }),
+            };
+            health.insert(component.to_string(), entry);
+        }
+        Ok(Value::Object(health))
+    }
+}
+
+/// Resource states, pending requests and recent decisions of StateManager
+struct StateHistory {
+    limit: usize,
+}
+
+/// The `limit` most recent decisions, newest first
+///
+/// Decision keys end with the time they were taken, in nanoseconds.
+fn recent_decisions(mut decisions: Vec<(String, String)>, limit: usize) -> Vec<(String, String)> {
+    let taken_at = |key: &str| {
+        key.rsplit('/')
+            .next()
+            .and_then(|t| t.parse::<i64>().ok())
+            .unwrap_or_default()
+    };
+    decisions.sort_by_key(|(key, _)| std::cmp::Reverse(taken_at(key)));
+    decisions.truncate(limit);
+    decisions
+}
+
+fn to_object(entries: Vec<(String, String)>) -> Value {
+    Value::Object(entries.into_iter().map(|(k, v)| (k, json!(v))).collect())
+}
+
+#[tonic::async_trait]
+impl Collector for StateHistory {
+    fn name(&self) -> &'static str {
+        "statemanager"
+    }
+
+    async fn collect(&self) -> Result<Value, String> {
+        let mut states = Vec::new();
+        for prefix in [
+            keys::SCENARIO_STATE_PREFIX,
+            keys::PACKAGE_STATE_PREFIX,
+            keys::MODEL_STATE_PREFIX,
+        ] {
+            states.extend(common::etcd::get_all_with_prefix(prefix).await?);
+        }
+        let pending = common::etcd::get_all_with_prefix(keys::PENDING_PREFIX).await?;
+        let decisions = common::etcd::get_all_with_prefix(keys::DECISIONS_PREFIX).await?;
+
+        Ok(json!({
+            "states": to_object(states),
+            "pending": to_object(pending),
+            "decisions": recent_decisions(decisions, self.limit)
+                .into_iter()
+                .map(|(key, value)| json!({ "key": key, "value": value }))
+                .collect::<Vec<_>>(),
+        }))
+    }
+}
+
+/// Registration records of the nodes
+struct Nodes;
+
+#[tonic::async_trait]
+impl Collector for Nodes {
+    fn name(&self) -> &'static str {
+        "nodes"
+    }
+
+    async fn collect(&self) -> Result<Value, String> {
+        let nodes = NodeManager.get_nodes().await.map_err(|e| e.to_string())?;
+        serde_json::to_value(nodes).map_err(|e| e.to_string())
+    }
+}
+
+//UNIT TEST CASES
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+    use std::io::Read;
+
+    /// Collector returning a fixed outcome after `delay`
+    struct Mock {
+        name: &'static str,
+        result: Result<Value, String>,
+        delay: Duration,
+    }
+
+    impl Mock {
+        fn ok(name: &'static str, value: Value) -> Box<dyn Collector> {
+            Box::new(Self {
+                name,
+                result: Ok(value),
+                delay: Duration::ZERO,
+            })
+        }
+
+        fn failing(name: &'static str, error: &str) -> Box<dyn Collector> {
+            Box::new(Self {
+                name,
+                result: Err(error.to_string()),
+                delay: Duration::ZERO,
+            })
+        }
+
+        fn slow(name: &'static str, delay: Duration) -> Box<dyn Collector> {
+            Box::new(Self {
+                name,
+                result: Ok(json!({})),
+                delay,
+            })
+        }
+    }
+
+    #[tonic::async_trait]
+    impl Collector for Mock {
+        fn name(&self) -> &'static str {
+            self.name
+        }
+
+        async fn collect(&self) -> Result<Value, String> {
+            tokio::time::sleep(self.delay).await;
+            self.result.clone()
+        }
+    }
+
+    /// Files of the archive by path, in archive order
+    fn unpack(data: &[u8]) -> Vec<(String, Vec<u8>)> {
+        let mut archive = tar::Archive::new(flate2::read::GzDecoder::new(data));
+        archive
+            .entries()
+            .unwrap()
+            .map(|entry| {
+                let mut entry = entry.unwrap();
+                let path = entry.path().unwrap().to_string_lossy().to_string();
+                let mut content = Vec::new();
+                entry.read_to_end(&mut content).unwrap();
+                (path, content)
+            })
+            .collect()
+    }
+
+    fn files(bundle: &Bundle) -> HashMap<String, Vec<u8>> {
+        let prefix = format!("{}/", bundle.name);
+        unpack(&bundle.data)
+            .into_iter()
+            .map(|(path, content)| {
+                assert!(path.starts_with(&prefix), "{} outside {}", path, prefix);
+                (path[prefix.len()..].to_string(), content)
+            })
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn test_archive_holds_one_file_per_section_and_manifest() {
+        let collectors = vec![
+            Mock::ok("etcd", json!({"keys": {"Scenario/hello": "spec"}})),
+            Mock::ok("nodes", json!([{"node_name": "HPC"}])),
+        ];
+
+        let bundle = build(&collectors, 4096, Duration::from_secs(1))
+            .await
+            .unwrap();
+
+        assert!(bundle.name.starts_with("pullpiri-diagnostics-"));
+        assert_eq!(bundle.file_name(), format!("{}.tar.gz", bundle.name));
+        let paths: Vec<String> = unpack(&bundle.data).into_iter().map(|(p, _)| p).collect();
+        assert_eq!(
+            paths,
+            vec![
+                format!("{}/etcd.json", bundle.name),
+                format!("{}/nodes.json", bundle.name),
+                format!("{}/manifest.json", bundle.name),
+            ]
+        );
+
+        let files = files(&bundle);
+        let etcd: Value = serde_json::from_slice(&files["etcd.json"]).unwrap();
+        assert_eq!(etcd["keys"]["Scenario/hello"], "spec");
+
+        let manifest: Value = serde_json::from_slice(&files["manifest.json"]).unwrap();
+        assert_eq!(manifest["sectionLimitBytes"], 4096);
+        let sections = manifest["sections"].as_array().unwrap();
+        assert_eq!(sections.len(), 2);
+        assert_eq!(sections[1]["name"], "nodes");
+        assert_eq!(sections[1]["file"], "nodes.json");
+        assert_eq!(sections[1]["truncated"], false);
+        assert_eq!(sections[1]["bytes"], files["nodes.json"].len());
+        assert!(sections[1].get("error").is_none());
+    }
+
+    #[tokio::test]
+    async fn test_failed_section_is_recorded_in_bundle() {
+        let collectors = vec![
+            Mock::failing("health", "connection refused"),
+            Mock::ok("nodes", json!([])),
+        ];
+
+        let bundle = build(&collectors, 4096, Duration::from_secs(1))
+            .await
+            .unwrap();
+        let files = files(&bundle);
+
+        assert!(!files.contains_key("health.json"));
+        assert_eq!(files["health.error"], b"connection refused");
+        assert!(files.contains_key("nodes.json"));
+
+        let manifest: Value = serde_json::from_slice(&files["manifest.json"]).unwrap();
+        assert_eq!(manifest["sections"][0]["file"], "health.error");
+        assert_eq!(manifest["sections"][0]["error"], "connection refused");
+    }
+
+    #[tokio::test]
+    async fn test_slow_section_times_out_without_blocking_others() {
+        let collectors = vec![
+            Mock::slow("statemanager", Duration::from_secs(5)),
+            Mock::ok("nodes", json!([])),
+        ];
+
+        let start = std::time::Instant::now();
+        let bundle = build(&collectors, 4096, Duration::from_millis(50))
+            .await
+            .unwrap();
+        assert!(start.elapsed() < Duration::from_secs(5));
+
+        let files = files(&bundle);
+        assert!(String::from_utf8_lossy(&files["statemanager.error"]).starts_with("timed out"));
+        assert!(files.contains_key("nodes.json"));
+    }
+
+    #[tokio::test]
+    async fn test_large_section_is_cut_at_limit() {
+        let collectors = vec![Mock::ok("etcd", json!({"value": "x".repeat(1000)}))];
+
+        let bundle = build(&collectors, 100, Duration::from_secs(1))
+            .await
+            .unwrap();
+        let files = files(&bundle);
+
+        assert_eq!(files["etcd.json"].len(), 100);
+        let manifest: Value = serde_json::from_slice(&files["manifest.json"]).unwrap();
+        assert_eq!(manifest["sections"][0]["truncated"], true);
+        assert_eq!(manifest["sections"][0]["bytes"], 100);
+    }
+
+    #[test]
+    fn test_recent_decisions_keeps_newest() {
+        let decision = |scenario: &str, ts: i64| {
+            (keys::decision(scenario, ts), format!("{}@{}", scenario, ts))
+        };
+        let decisions = vec![
+            decision("parking", 10),
+            decision("bms", 30),
+            decision("parking", 20),
+        ];
+
+        let recent = recent_decisions(decisions, 2);
+
+        assert_eq!(recent, vec![decision("bms", 30), decision("parking", 20)]);
+    }
+
+    #[test]
+    fn test_collectors_cover_every_section() {
+        let names: Vec<&str> = collectors(10).iter().map(|c| c.name()).collect();
+        assert_eq!(names, vec!["etcd", "health", "statemanager", "nodes"]);
+    }
+}
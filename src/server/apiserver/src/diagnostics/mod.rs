@@ -3,13 +3,16 @@
  * SPDX-License-Identifier: Apache-2.0
  */
 
-//! Diagnostic utilities for service connectivity
+//! Diagnostic utilities for service connectivity and support bundles
+
+pub mod bundle;
 
 use common::logd;
 use std::time::Duration;
 use tokio::net::TcpStream;
 
 /// Check if a service is reachable at the given IP and port
+#[allow(dead_code)]
 pub async fn check_service_connectivity(ip: &str, port: u16) -> bool {
     let addr = format!("{}:{}", ip, port);
     logd!(2, "Checking connectivity to {}", addr);
@@ -31,6 +34,7 @@ pub async fn check_service_connectivity(ip: &str, port: u16) -> bool {
 }
 
 /// Check if NodeAgent is reachable at the given IP
+#[allow(dead_code)]
 pub async fn check_node_agent_connectivity(ip: &str) -> bool {
     check_service_connectivity(ip, 47004).await
 }
@@ -3,6 +3,7 @@
  * SPDX-License-Identifier: Apache-2.0
  */
 
+use crate::logs;
 use crate::node::NodeManager;
 use base64::Engine;
 use common::apiserver::api_server_connection_server::ApiServerConnection;
@@ -12,9 +13,11 @@ use common::apiserver::{
     UpdateTopologyResponse,
 };
 use common::etcd;
+use common::keys;
 use common::logd;
 use common::nodeagent::fromapiserver::{
-    NodeRegistrationRequest, NodeRegistrationResponse, NodeStatus,
+    HeartbeatRequest, HeartbeatResponse, NodeRegistrationRequest, NodeRegistrationResponse,
+    NodeStatus, WorkloadLogsRequest,
 };
 use prost::Message;
 use tonic::{Request, Response, Status};
@@ -27,7 +30,7 @@ impl NodeRegistry {
     async fn get_topology(
         &self,
     ) -> Result<ClusterTopology, Box<dyn std::error::Error + Send + Sync>> {
-        let topology_key = "cluster/topology";
+        let topology_key = keys::CLUSTER_TOPOLOGY;
 
         match etcd::get(topology_key).await {
             Ok(encoded) => {
@@ -51,7 +54,7 @@ impl NodeRegistry {
         &self,
         topology: ClusterTopology,
     ) -> Result<ClusterTopology, Box<dyn std::error::Error + Send + Sync>> {
-        let topology_key = "cluster/topology";
+        let topology_key = keys::CLUSTER_TOPOLOGY;
 
         // 인코딩을 제거하고 json string으로 변환
         let topology_json = serde_json::to_string(&topology)?;
@@ -185,12 +188,12 @@ impl ApiServerConnection for ApiServerReceiver {
                 // 두 가지 키로 저장
                 // 1. IP 주소로 빠른 조회용 (json 문자열로 변경)
                 let _ =
-                    common::etcd::put(&format!("nodes/{}", req.ip_address), &req.hostname).await;
+                    common::etcd::put(&keys::node_address(&req.ip_address), &req.hostname).await;
                 logd!(1, "Hostname stored at IP key: nodes/{}", req.ip_address);
 
                 // 2. 호스트 이름으로 빠른 조회용 (ActionController용)
                 let _ =
-                    common::etcd::put(&format!("nodes/{}", req.hostname), &req.ip_address).await;
+                    common::etcd::put(&keys::node_address(&req.hostname), &req.ip_address).await;
                 logd!(1, "Node IP stored at hostname key: nodes/{}", req.hostname);
 
                 // Immediately update the node status to Ready
@@ -232,6 +235,31 @@ impl ApiServerConnection for ApiServerReceiver {
         }
     }
 
+    /// Refresh the heartbeat of a node, so the reaper leaves it alone
+    async fn heartbeat(
+        &self,
+        request: Request<HeartbeatRequest>,
+    ) -> Result<Response<HeartbeatResponse>, Status> {
+        let req = request.into_inner();
+        logd!(1, "Received Heartbeat from node {}", req.node_id);
+
+        match self.node_manager.update_heartbeat(&req.node_id).await {
+            Ok(known) => {
+                if !known {
+                    logd!(4, "Heartbeat from unregistered node {}", req.node_id);
+                }
+                Ok(Response::new(HeartbeatResponse {
+                    ack: known,
+                    updated_config: None,
+                }))
+            }
+            Err(e) => Err(Status::unavailable(format!(
+                "Failed to update heartbeat of node {}: {}",
+                req.node_id, e
+            ))),
+        }
+    }
+
     async fn get_topology(
         &self,
         _request: Request<GetTopologyRequest>,
@@ -277,6 +305,17 @@ impl ApiServerConnection for ApiServerReceiver {
             }))
         }
     }
+
+    type GetWorkloadLogsStream = logs::LogStream;
+
+    /// Stream the logs of a workload from the node running it
+    async fn get_workload_logs(
+        &self,
+        request: Request<WorkloadLogsRequest>,
+    ) -> Result<Response<Self::GetWorkloadLogsStream>, Status> {
+        let stream = logs::open(&logs::NodeAgent, request.into_inner()).await?;
+        Ok(Response::new(stream))
+    }
 }
 
 #[cfg(test)]
@@ -314,6 +353,7 @@ mod tests {
             node_role: NodeRole::Nodeagent.into(),
             resources: Some(create_test_resource_info()),
             metadata,
+            version: None,
         }
     }
 
@@ -428,6 +468,18 @@ mod tests {
         assert!(response.node.is_none());
     }
 
+    #[tokio::test]
+    async fn test_heartbeat_of_unknown_node_is_not_acked() {
+        let receiver = ApiServerReceiver::new();
+        let request = Request::new(HeartbeatRequest {
+            node_id: "non-existent-node".to_string(),
+            timestamp: chrono::Utc::now().timestamp(),
+        });
+
+        let response = receiver.heartbeat(request).await.unwrap().into_inner();
+        assert!(!response.ack);
+    }
+
     #[tokio::test]
     async fn test_register_node_success() {
         let receiver = ApiServerReceiver::new();
@@ -629,6 +681,7 @@ mod tests {
             node_role: NodeRole::Bluechi.into(),
             resources: Some(create_test_resource_info()),
             metadata,
+            version: None,
         };
 
         let request = Request::new(registration_request);
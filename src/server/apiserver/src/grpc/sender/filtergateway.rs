@@ -5,11 +5,28 @@
 
 //! Running gRPC message sending to filtergateway
 
+use crate::artifact::status;
 use common::filtergateway::{
-    connect_server, filter_gateway_connection_client::FilterGatewayConnectionClient,
+    connect_server, filter_gateway_connection_client::FilterGatewayConnectionClient, Action,
     HandleScenarioRequest, HandleScenarioResponse,
 };
-use tonic::{Request, Response, Status};
+use common::{keys, logd};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::{LazyLock, Mutex};
+use std::time::Duration;
+use tokio::sync::Notify;
+use tonic::{Code, Response, Status};
+
+/// Attempts made to deliver a notification before it is dead-lettered
+const SEND_ATTEMPTS: u32 = 3;
+/// Wait before the second attempt, doubled before each further one
+const INITIAL_BACKOFF: Duration = Duration::from_millis(200);
+/// Interval at which undelivered notifications are retried
+const DRAIN_INTERVAL: Duration = Duration::from_secs(10);
+
+static NOTIFIER: LazyLock<Notifier> =
+    LazyLock::new(|| Notifier::new(connect_server(), SEND_ATTEMPTS, INITIAL_BACKOFF));
 
 /// Send scenario information to filtergateway via gRPC
 ///
@@ -18,16 +35,26 @@ use tonic::{Request, Response, Status};
 /// ### Description
 /// This is generated almost automatically by `tonic_build`, so you
 /// don't need to modify it separately.
+#[allow(dead_code)]
 pub async fn send(
     scenario: HandleScenarioRequest,
+) -> Result<Response<HandleScenarioResponse>, Status> {
+    send_to(&connect_server(), scenario).await
+}
+
+async fn send_to(
+    addr: &str,
+    scenario: HandleScenarioRequest,
 ) -> Result<Response<HandleScenarioResponse>, Status> {
     use std::time::Instant;
     let start = Instant::now();
 
-    let mut client = FilterGatewayConnectionClient::connect(connect_server())
+    let mut client = FilterGatewayConnectionClient::connect(addr.to_string())
         .await
         .map_err(|e| Status::unavailable(format!("Failed to connect to FilterGateway: {}", e)))?;
-    let response = client.handle_scenario(Request::new(scenario)).await;
+    let response = client
+        .handle_scenario(common::trace::request(scenario))
+        .await;
 
     let elapsed = start.elapsed();
     common::logd!(1, "send: elapsed = {:?}", elapsed);
@@ -35,6 +62,264 @@ pub async fn send(
     response
 }
 
+/// Send scenario information to filtergateway, queueing it if unreachable
+///
+/// ### Parametets
+/// * `scenario: HandleScenarioRequest` - wrapped scenario information
+/// ### Description
+/// The request is retried with backoff while filtergateway is unreachable.
+/// After the last attempt it is stored under
+/// `ApiServer/deadletter/filtergateway/<scenario>` and delivered later by
+/// [`run_dead_letter_queue`], and `Delivery::Pending` is returned.
+pub async fn notify(scenario: HandleScenarioRequest) -> Result<Delivery, Status> {
+    NOTIFIER.notify(scenario).await
+}
+
+/// Deliver the dead-lettered notifications until the process exits
+///
+/// ### Description
+/// Notifications stored by a previous run are loaded first. The queue is
+/// drained every `DRAIN_INTERVAL` and whenever a notification gets through.
+pub async fn run_dead_letter_queue() {
+    NOTIFIER.run().await
+}
+
+/// Outcome of a scenario notification
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Delivery {
+    /// filtergateway received the scenario
+    Delivered,
+    /// filtergateway was unreachable; the scenario will be delivered later
+    Pending,
+}
+
+/// Notification waiting for filtergateway to come back
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DeadLetter {
+    pub scenario_name: String,
+    pub action: i32,
+    pub scenario: String,
+}
+
+impl DeadLetter {
+    fn request(&self) -> HandleScenarioRequest {
+        HandleScenarioRequest {
+            action: self.action,
+            scenario: self.scenario.clone(),
+        }
+    }
+}
+
+/// Returns `true` if `status` means filtergateway could not be reached,
+/// rather than that it rejected the request
+fn is_unreachable(status: &Status) -> bool {
+    matches!(status.code(), Code::Unavailable | Code::DeadlineExceeded)
+}
+
+/// Delivers scenario notifications to one filtergateway
+///
+/// Only the latest undelivered notification of each scenario is kept, so a
+/// withdraw queued after an apply replaces it.
+struct Notifier {
+    addr: String,
+    attempts: u32,
+    backoff: Duration,
+    queue: Mutex<HashMap<String, DeadLetter>>,
+    wake: Notify,
+}
+
+impl Notifier {
+    fn new(addr: String, attempts: u32, backoff: Duration) -> Self {
+        Self {
+            addr,
+            attempts: attempts.max(1),
+            backoff,
+            queue: Mutex::new(HashMap::new()),
+            wake: Notify::new(),
+        }
+    }
+
+    fn has_dead_letters(&self) -> bool {
+        !self.queue.lock().unwrap().is_empty()
+    }
+
+    async fn notify(&self, scenario: HandleScenarioRequest) -> Result<Delivery, Status> {
+        let mut backoff = self.backoff;
+        let mut attempt = 1;
+        let status = loop {
+            match send_to(&self.addr, scenario.clone()).await {
+                Ok(_) => {
+                    if self.has_dead_letters() {
+                        // An older notification of the same scenario is now stale
+                        if let Some(name) = scenario_name(&scenario.scenario) {
+                            self.forget(&name, None).await;
+                        }
+                        self.wake.notify_one();
+                    }
+                    return Ok(Delivery::Delivered);
+                }
+                Err(status) if is_unreachable(&status) && attempt < self.attempts => {
+                    logd!(
+                        3,
+                        "FilterGateway unreachable (attempt {}/{}): {}",
+                        attempt,
+                        self.attempts,
+                        status.message()
+                    );
+                    tokio::time::sleep(backoff).await;
+                    backoff *= 2;
+                    attempt += 1;
+                }
+                Err(status) => break status,
+            }
+        };
+
+        if !is_unreachable(&status) {
+            return Err(status);
+        }
+        let Some(scenario_name) = scenario_name(&scenario.scenario) else {
+            return Err(status);
+        };
+
+        logd!(
+            4,
+            "FilterGateway unreachable, queueing scenario {}: {}",
+            scenario_name,
+            status.message()
+        );
+        let letter = DeadLetter {
+            scenario_name,
+            action: scenario.action,
+            scenario: scenario.scenario,
+        };
+        self.persist(&letter).await;
+        self.queue
+            .lock()
+            .unwrap()
+            .insert(letter.scenario_name.clone(), letter);
+        Ok(Delivery::Pending)
+    }
+
+    /// Send every dead-lettered notification once
+    ///
+    /// ### Description
+    /// Stops at the first notification filtergateway cannot be reached for.
+    /// A notification filtergateway rejects is dropped, as sending it again
+    /// would not change the outcome.
+    /// ### Returns
+    /// * `usize` - number of notifications delivered
+    async fn drain(&self) -> usize {
+        let letters: Vec<DeadLetter> = self.queue.lock().unwrap().values().cloned().collect();
+        let mut delivered = 0;
+
+        for letter in letters {
+            let result = send_to(&self.addr, letter.request()).await;
+            match &result {
+                Ok(_) => {
+                    logd!(
+                        3,
+                        "Delivered queued scenario {} to FilterGateway",
+                        letter.scenario_name
+                    );
+                    delivered += 1;
+                }
+                Err(status) if is_unreachable(status) => break,
+                Err(status) => logd!(
+                    4,
+                    "FilterGateway rejected queued scenario {}: {}",
+                    letter.scenario_name,
+                    status.message()
+                ),
+            }
+            if letter.action == i32::from(Action::Apply) {
+                // The import of the artifact stayed `Stored` until now
+                let names = [letter.scenario_name.clone()];
+                let outcome = async { result.map(|_| Delivery::Delivered) };
+                let _ = status::propagate(&status::Etcd, &names, outcome).await;
+            }
+            self.forget(&letter.scenario_name, Some(&letter)).await;
+        }
+        delivered
+    }
+
+    /// Remove the dead letter of `scenario_name`
+    ///
+    /// With `sent`, it is only removed if it was not replaced by a newer
+    /// notification in the meantime.
+    async fn forget(&self, scenario_name: &str, sent: Option<&DeadLetter>) {
+        {
+            let mut queue = self.queue.lock().unwrap();
+            match (queue.get(scenario_name), sent) {
+                (None, _) => return,
+                (Some(current), Some(sent)) if current != sent => return,
+                _ => {}
+            }
+            queue.remove(scenario_name);
+        }
+        if let Err(e) = common::etcd::delete(&keys::filtergateway_deadletter(scenario_name)).await {
+            logd!(4, "Failed to delete dead letter {}: {}", scenario_name, e);
+        }
+    }
+
+    /// Store `letter` so it survives a restart of the apiserver
+    async fn persist(&self, letter: &DeadLetter) {
+        let value = match serde_json::to_string(letter) {
+            Ok(value) => value,
+            Err(e) => {
+                logd!(4, "Failed to serialize dead letter: {:?}", e);
+                return;
+            }
+        };
+        let key = keys::filtergateway_deadletter(&letter.scenario_name);
+        if let Err(e) = common::etcd::put(&key, &value).await {
+            logd!(4, "Failed to persist dead letter {}: {}", key, e);
+        }
+    }
+
+    /// Load the dead letters stored before a restart
+    async fn restore(&self) {
+        let stored =
+            match common::etcd::get_all_with_prefix(keys::FILTERGATEWAY_DEADLETTER_PREFIX).await {
+                Ok(stored) => stored,
+                Err(e) => {
+                    logd!(4, "Failed to load dead letters: {}", e);
+                    return;
+                }
+            };
+
+        let mut queue = self.queue.lock().unwrap();
+        for (key, value) in stored {
+            match serde_json::from_str::<DeadLetter>(&value) {
+                Ok(letter) => {
+                    queue.entry(letter.scenario_name.clone()).or_insert(letter);
+                }
+                Err(e) => logd!(4, "Ignoring malformed dead letter {}: {:?}", key, e),
+            }
+        }
+    }
+
+    async fn run(&self) {
+        self.restore().await;
+        loop {
+            if self.has_dead_letters() {
+                self.drain().await;
+            }
+            tokio::select! {
+                _ = self.wake.notified() => {}
+                _ = tokio::time::sleep(DRAIN_INTERVAL) => {}
+            }
+        }
+    }
+}
+
+/// Name of the scenario in `yaml`, if it is a valid scenario
+pub fn scenario_name(yaml: &str) -> Option<String> {
+    use common::spec::artifact::Artifact;
+    serde_yaml::from_str::<common::spec::artifact::Scenario>(yaml)
+        .ok()
+        .map(|scenario| scenario.get_name())
+}
+
 //UNIT TEST CASES
 
 #[cfg(test)]
@@ -107,6 +392,13 @@ spec:
     /// HandleScenario just checks if the scenario string is empty
     #[tonic::async_trait]
     impl FilterGatewayConnection for MockFilterGateway {
+        async fn get_metrics(
+            &self,
+            _request: Request<common::filtergateway::GetMetricsRequest>,
+        ) -> Result<Response<common::filtergateway::GetMetricsResponse>, Status> {
+            Ok(Response::new(Default::default()))
+        }
+
         async fn handle_scenario(
             &self,
             request: Request<HandleScenarioRequest>,
@@ -267,4 +559,162 @@ spec:
         let result = send_mocked(scenario, addr).await;
         assert!(result.is_ok());
     }
+
+    // === Dead letter queue ===
+
+    /// A mock filtergateway that keeps the requests it receives
+    #[derive(Clone, Default)]
+    struct RecordingFilterGateway {
+        received: std::sync::Arc<std::sync::Mutex<Vec<HandleScenarioRequest>>>,
+    }
+
+    #[tonic::async_trait]
+    impl FilterGatewayConnection for RecordingFilterGateway {
+        async fn get_metrics(
+            &self,
+            _request: Request<common::filtergateway::GetMetricsRequest>,
+        ) -> Result<Response<common::filtergateway::GetMetricsResponse>, Status> {
+            Ok(Response::new(Default::default()))
+        }
+
+        async fn handle_scenario(
+            &self,
+            request: Request<HandleScenarioRequest>,
+        ) -> Result<Response<HandleScenarioResponse>, Status> {
+            let req = request.into_inner();
+            if req.scenario.trim().is_empty() {
+                return Err(Status::invalid_argument("Empty scenario"));
+            }
+            self.received.lock().unwrap().push(req);
+            Ok(Response::new(HandleScenarioResponse {
+                status: true,
+                desc: String::new(),
+            }))
+        }
+    }
+
+    /// An address nothing listens on, until a server is started on it
+    async fn unused_addr() -> SocketAddr {
+        TcpListener::bind("127.0.0.1:0")
+            .await
+            .unwrap()
+            .local_addr()
+            .unwrap()
+    }
+
+    async fn start_recording_server(addr: SocketAddr) -> RecordingFilterGateway {
+        let gateway = RecordingFilterGateway::default();
+        let listener = TcpListener::bind(addr).await.unwrap();
+        let service = FilterGatewayConnectionServer::new(gateway.clone());
+        tokio::spawn(async move {
+            tonic::transport::Server::builder()
+                .add_service(service)
+                .serve_with_incoming(TcpListenerStream::new(listener))
+                .await
+                .unwrap();
+        });
+        tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+        gateway
+    }
+
+    fn notifier(addr: SocketAddr) -> Notifier {
+        Notifier::new(
+            format!("http://{}", addr),
+            3,
+            std::time::Duration::from_millis(1),
+        )
+    }
+
+    fn request(action: Action, yaml: &str) -> HandleScenarioRequest {
+        HandleScenarioRequest {
+            action: action.into(),
+            scenario: yaml.to_string(),
+        }
+    }
+
+    /// An unreachable filtergateway gets the scenario once it is back
+    #[tokio::test]
+    async fn test_notify_unreachable_then_recovered() {
+        let addr = unused_addr().await;
+        let notifier = notifier(addr);
+
+        let delivery = notifier
+            .notify(request(Action::Apply, VALID_SCENARIO_YAML))
+            .await
+            .unwrap();
+        assert_eq!(delivery, Delivery::Pending);
+        assert!(notifier.queue.lock().unwrap().contains_key("helloworld"));
+
+        // Still down: the notification stays queued
+        assert_eq!(notifier.drain().await, 0);
+        assert!(notifier.has_dead_letters());
+
+        let gateway = start_recording_server(addr).await;
+        assert_eq!(notifier.drain().await, 1);
+
+        assert!(!notifier.has_dead_letters());
+        let received = gateway.received.lock().unwrap();
+        assert_eq!(received.len(), 1);
+        assert_eq!(received[0].action, i32::from(Action::Apply));
+        assert_eq!(received[0].scenario, VALID_SCENARIO_YAML);
+    }
+
+    /// Only the latest notification of a scenario is kept, and delivering a
+    /// newer one directly discards the queued one
+    #[tokio::test]
+    async fn test_newer_notification_supersedes_dead_letter() {
+        let addr = unused_addr().await;
+        let notifier = notifier(addr);
+
+        notifier
+            .notify(request(Action::Apply, VALID_SCENARIO_YAML))
+            .await
+            .unwrap();
+        notifier
+            .notify(request(Action::Withdraw, VALID_SCENARIO_YAML))
+            .await
+            .unwrap();
+        {
+            let queue = notifier.queue.lock().unwrap();
+            assert_eq!(queue.len(), 1);
+            assert_eq!(queue["helloworld"].action, i32::from(Action::Withdraw));
+        }
+
+        let gateway = start_recording_server(addr).await;
+        let delivery = notifier
+            .notify(request(Action::Apply, VALID_SCENARIO_YAML))
+            .await
+            .unwrap();
+
+        assert_eq!(delivery, Delivery::Delivered);
+        assert!(!notifier.has_dead_letters());
+        assert_eq!(notifier.drain().await, 0);
+        assert_eq!(gateway.received.lock().unwrap().len(), 1);
+    }
+
+    /// A scenario filtergateway rejects is reported, not queued
+    #[tokio::test]
+    async fn test_rejected_notification_is_not_queued() {
+        let addr = unused_addr().await;
+        start_recording_server(addr).await;
+        let notifier = notifier(addr);
+
+        let result = notifier.notify(request(Action::Apply, " ")).await;
+
+        assert_eq!(result.unwrap_err().code(), Code::InvalidArgument);
+        assert!(!notifier.has_dead_letters());
+    }
+
+    /// A request that is not a scenario cannot be keyed, so it is not queued
+    #[tokio::test]
+    async fn test_unreachable_without_scenario_name_fails() {
+        let notifier = notifier(unused_addr().await);
+
+        let result = notifier
+            .notify(request(Action::Apply, INVALID_SCENARIO_YAML_EMPTY))
+            .await;
+
+        assert_eq!(result.unwrap_err().code(), Code::Unavailable);
+        assert!(!notifier.has_dead_letters());
+    }
 }
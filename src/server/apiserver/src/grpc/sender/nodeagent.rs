@@ -3,62 +3,50 @@
 * SPDX-License-Identifier: Apache-2.0
 */
 use common::logd;
-use common::nodeagent::fromapiserver::{HandleYamlRequest, HandleYamlResponse};
+use common::nodeagent::fromapiserver::{
+    EnrollBluechiRequest, EnrollBluechiResponse, HandleYamlRequest, HandleYamlResponse,
+    PrefetchImagesRequest, PrefetchImagesResponse, WorkloadLogLine, WorkloadLogsRequest,
+};
 use common::nodeagent::node_agent_connection_client::NodeAgentConnectionClient;
-use tonic::{Request, Response, Status};
-
-// Send to a specific node using its IP address
-pub async fn send_to_node(
-    action: HandleYamlRequest,
-    node_ip: String,
-) -> Result<Response<HandleYamlResponse>, Status> {
+use common::version::capability;
+use std::time::Duration;
+use tonic::transport::Channel;
+use tonic::{Response, Status, Streaming};
+
+/// Time the NodeAgent gets to apply a yaml, covering the pull of its images
+/// (at most 300 seconds by default)
+const APPLY_TIMEOUT: Duration = Duration::from_secs(310);
+/// Time the NodeAgent gets to enroll with bluechi, covering the restart of
+/// bluechi-agent and the wait for the controller (30 seconds by default)
+const ENROLL_TIMEOUT: Duration = Duration::from_secs(60);
+/// Time the NodeAgent gets to start streaming the logs of a workload
+const LOGS_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// gRPC address of the NodeAgent of the node at `node_ip`
+fn address(node_ip: &str) -> String {
     // Fix 0.0.0.0 to actual host IP for NodeAgent connection
     let fixed_ip = if node_ip == "0.0.0.0" {
-        "127.0.0.1".to_string()
+        "127.0.0.1"
     } else {
-        node_ip.clone()
+        node_ip
     };
-    let addr = format!("http://{}:47004", fixed_ip);
+    format!("http://{}:47004", fixed_ip)
+}
+
+/// Connect to the NodeAgent of the node at `node_ip`
+///
+/// The first connection to a NodeAgent negotiates its version.
+async fn connect(node_ip: &str) -> Result<NodeAgentConnectionClient<Channel>, Status> {
+    let addr = address(node_ip);
 
     logd!(2, "Attempting to connect to NodeAgent at: {}", addr);
 
     // Attempting to connect with a timeout
-    let client_result = tokio::time::timeout(
-        std::time::Duration::from_secs(5),
-        NodeAgentConnectionClient::connect(addr.clone()),
-    )
-    .await;
+    let client_result =
+        tokio::time::timeout(Duration::from_secs(5), common::rpc::connect(addr.clone())).await;
 
     match client_result {
-        Ok(Ok(mut client)) => {
-            logd!(2, "Successfully connected to NodeAgent, sending request...");
-            match tokio::time::timeout(
-                std::time::Duration::from_secs(1),
-                client.handle_yaml(Request::new(action)),
-            )
-            .await
-            {
-                Ok(result) => match result {
-                    Ok(response) => {
-                        logd!(1, "Request to NodeAgent successful");
-                        Ok(response)
-                    }
-                    Err(e) => {
-                        logd!(5, "Error calling NodeAgent handle_yaml: {}", e);
-                        Err(Status::internal(format!(
-                            "Error calling NodeAgent handle_yaml: {}",
-                            e
-                        )))
-                    }
-                },
-                Err(_) => {
-                    logd!(5, "Timeout while waiting for NodeAgent to respond");
-                    Err(Status::deadline_exceeded(
-                        "Timeout while waiting for NodeAgent to respond",
-                    ))
-                }
-            }
-        }
+        Ok(Ok(channel)) => Ok(NodeAgentConnectionClient::new(channel)),
         Ok(Err(e)) => {
             logd!(5, "Error connecting to NodeAgent at {}: {}", addr, e);
             logd!(5, "Connection error details: {:?}", e);
@@ -76,6 +64,156 @@ pub async fn send_to_node(
         }
     }
 }
+
+/// Connect to the NodeAgent of the node at `node_ip`, failing with
+/// `UNIMPLEMENTED` if its version lacks `capability`
+async fn connect_for(
+    node_ip: &str,
+    capability: &str,
+) -> Result<NodeAgentConnectionClient<Channel>, Status> {
+    let client = connect(node_ip).await?;
+    common::version::require(&address(node_ip), capability)?;
+    Ok(client)
+}
+
+// Send to a specific node using its IP address
+pub async fn send_to_node(
+    action: HandleYamlRequest,
+    node_ip: String,
+) -> Result<Response<HandleYamlResponse>, Status> {
+    let mut client = connect(&node_ip).await?;
+    logd!(2, "Successfully connected to NodeAgent, sending request...");
+    match tokio::time::timeout(
+        APPLY_TIMEOUT,
+        client.handle_yaml(common::trace::request(action)),
+    )
+    .await
+    {
+        Ok(result) => match result {
+            Ok(response) => {
+                logd!(1, "Request to NodeAgent successful");
+                Ok(response)
+            }
+            Err(e) => {
+                logd!(5, "Error calling NodeAgent handle_yaml: {}", e);
+                Err(Status::internal(format!(
+                    "Error calling NodeAgent handle_yaml: {}",
+                    e
+                )))
+            }
+        },
+        Err(_) => {
+            logd!(5, "Timeout while waiting for NodeAgent to respond");
+            Err(Status::deadline_exceeded(
+                "Timeout while waiting for NodeAgent to respond",
+            ))
+        }
+    }
+}
+
+/// Have the node at `node_ip` pull `images` ahead of scenario activation
+///
+/// # Arguments
+///
+/// * `images` - Image references, e.g. `nginx:latest` or `nginx@sha256:...`
+/// * `node_ip` - IP address of the node
+///
+/// # Returns
+///
+/// * `Result<Response<PrefetchImagesResponse>, Status>` - Response of the
+///   NodeAgent, or the error of the first image it could not pull
+#[allow(dead_code)]
+pub async fn prefetch_images_on_node(
+    images: Vec<String>,
+    node_ip: String,
+) -> Result<Response<PrefetchImagesResponse>, Status> {
+    let mut client = connect_for(&node_ip, capability::PREFETCH_IMAGES).await?;
+    let request = PrefetchImagesRequest { images };
+    match tokio::time::timeout(
+        APPLY_TIMEOUT,
+        client.prefetch_images(common::trace::request(request)),
+    )
+    .await
+    {
+        Ok(result) => result.inspect_err(|e| {
+            logd!(
+                5,
+                "NodeAgent at {} failed to prefetch images: {}",
+                node_ip,
+                e
+            );
+        }),
+        Err(_) => Err(Status::deadline_exceeded(
+            "Timeout while waiting for NodeAgent to prefetch images",
+        )),
+    }
+}
+
+/// Have the node at `node_ip` enroll with the bluechi controller
+///
+/// # Arguments
+///
+/// * `node_ip` - IP address of the node
+///
+/// # Returns
+///
+/// * `Result<Response<EnrollBluechiResponse>, Status>` - Response of the
+///   NodeAgent, or why the node is not online in the controller
+#[allow(dead_code)]
+pub async fn enroll_bluechi_on_node(
+    node_ip: String,
+) -> Result<Response<EnrollBluechiResponse>, Status> {
+    let mut client = connect_for(&node_ip, capability::ENROLL_BLUECHI).await?;
+    match tokio::time::timeout(
+        ENROLL_TIMEOUT,
+        client.enroll_bluechi(common::trace::request(EnrollBluechiRequest {})),
+    )
+    .await
+    {
+        Ok(result) => result.inspect_err(|e| {
+            logd!(
+                5,
+                "NodeAgent at {} failed to enroll with bluechi: {}",
+                node_ip,
+                e
+            );
+        }),
+        Err(_) => Err(Status::deadline_exceeded(
+            "Timeout while waiting for NodeAgent to enroll with bluechi",
+        )),
+    }
+}
+
+/// Stream the logs of a workload from the node at `node_ip`
+///
+/// # Arguments
+///
+/// * `request` - Workload and the lines to stream
+/// * `node_ip` - IP address of the node running the workload
+///
+/// # Returns
+///
+/// * `Result<Streaming<WorkloadLogLine>, Status>` - Lines as the NodeAgent
+///   reads them. Dropping the stream ends the tail on the node.
+pub async fn get_workload_logs(
+    request: WorkloadLogsRequest,
+    node_ip: String,
+) -> Result<Streaming<WorkloadLogLine>, Status> {
+    let mut client = connect_for(&node_ip, capability::WORKLOAD_LOGS).await?;
+    // Only the start of the stream is bounded, a followed stream lasts
+    match tokio::time::timeout(
+        LOGS_TIMEOUT,
+        client.get_workload_logs(common::trace::request(request)),
+    )
+    .await
+    {
+        Ok(result) => result.map(Response::into_inner),
+        Err(_) => Err(Status::deadline_exceeded(
+            "Timeout while waiting for NodeAgent to stream logs",
+        )),
+    }
+}
+
 #[allow(dead_code)]
 pub async fn send(action: HandleYamlRequest) -> Result<Response<HandleYamlResponse>, Status> {
     // Use the node lookup module to get the node IP
@@ -135,8 +273,12 @@ mod tests {
     use super::*;
     use common::apiserver::NodeInfo;
     use common::nodeagent::fromapiserver::{NodeRole, NodeStatus, NodeType, ResourceInfo};
+    use common::version::version_service_server::{VersionService, VersionServiceServer};
+    use common::version::{GetVersionRequest, VersionInfo};
     use std::collections::HashMap;
     use tokio;
+    use tokio::net::TcpListener;
+    use tokio_stream::wrappers::TcpListenerStream;
     use tonic::Code;
 
     fn create_test_handle_yaml_request() -> HandleYamlRequest {
@@ -215,6 +357,41 @@ spec:
         }
     }
 
+    #[tokio::test]
+    async fn test_prefetch_images_on_node_connection_failure() {
+        let images = vec!["nginx:latest".to_string()];
+        let node_ip = "192.168.1.999".to_string(); // Invalid IP to simulate connection failure
+
+        let error = prefetch_images_on_node(images, node_ip).await.unwrap_err();
+
+        match error.code() {
+            Code::Unavailable => {
+                assert!(error.message().contains("Failed to connect to NodeAgent"));
+            }
+            Code::DeadlineExceeded => {
+                assert!(error.message().contains("Timeout while connecting"));
+            }
+            _ => panic!("Unexpected error code: {:?}", error.code()),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_enroll_bluechi_on_node_connection_failure() {
+        let node_ip = "192.168.1.999".to_string(); // Invalid IP to simulate connection failure
+
+        let error = enroll_bluechi_on_node(node_ip).await.unwrap_err();
+
+        match error.code() {
+            Code::Unavailable => {
+                assert!(error.message().contains("Failed to connect to NodeAgent"));
+            }
+            Code::DeadlineExceeded => {
+                assert!(error.message().contains("Timeout while connecting"));
+            }
+            _ => panic!("Unexpected error code: {:?}", error.code()),
+        }
+    }
+
     #[tokio::test]
     async fn test_send_to_node_with_unreachable_ip() {
         let action = create_test_simple_yaml_request();
@@ -488,4 +665,48 @@ spec:
             let result = send_to_node(action, "127.0.0.1".to_string()).await;
         }
     }
+
+    /// Version service of a NodeAgent built before workload logs
+    struct OldNodeAgentVersion;
+
+    #[tonic::async_trait]
+    impl VersionService for OldNodeAgentVersion {
+        async fn get_version(
+            &self,
+            _request: tonic::Request<GetVersionRequest>,
+        ) -> Result<Response<VersionInfo>, Status> {
+            Ok(Response::new(VersionInfo {
+                component: "nodeagent".to_string(),
+                version: "0.0.9".to_string(),
+                capabilities: vec![capability::PREFETCH_IMAGES.to_string()],
+                ..Default::default()
+            }))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_workload_logs_of_old_nodeagent_are_unimplemented() {
+        // A loopback address of its own, as NodeAgents listen on a fixed port
+        let node_ip = "127.0.0.77";
+        let listener = TcpListener::bind(format!("{}:47004", node_ip))
+            .await
+            .unwrap();
+        tokio::spawn(
+            tonic::transport::Server::builder()
+                .add_service(VersionServiceServer::new(OldNodeAgentVersion))
+                .serve_with_incoming(TcpListenerStream::new(listener)),
+        );
+
+        let request = WorkloadLogsRequest {
+            workload: "helloworld-core".to_string(),
+            follow: false,
+            tail: None,
+        };
+        let error = get_workload_logs(request, node_ip.to_string())
+            .await
+            .unwrap_err();
+
+        assert_eq!(error.code(), Code::Unimplemented);
+        assert!(error.message().contains(capability::WORKLOAD_LOGS));
+    }
 }
@@ -13,11 +13,12 @@
 //! and comprehensive error handling to ensure reliable communication with the
 //! StateManager in the PICCOLO framework.
 
+use common::rpc;
 use common::statemanager::{
     connect_server, state_manager_connection_client::StateManagerConnectionClient, StateChange,
     StateChangeResponse,
 };
-use tonic::{Request, Status};
+use tonic::Status;
 
 /// StateManager gRPC client for ApiServer component.
 ///
@@ -93,9 +94,9 @@ impl StateManagerSender {
     /// - Add connection pooling for high-throughput scenarios
     async fn ensure_connected(&mut self) -> Result<(), Status> {
         if self.client.is_none() {
-            match StateManagerConnectionClient::connect(connect_server()).await {
-                Ok(client) => {
-                    self.client = Some(client);
+            match rpc::connect(connect_server()).await {
+                Ok(channel) => {
+                    self.client = Some(StateManagerConnectionClient::new(channel));
                     Ok(())
                 }
                 Err(e) => Err(Status::unknown(format!(
@@ -154,7 +155,7 @@ impl StateManagerSender {
 
         if let Some(client) = &mut self.client {
             // Send the state change message via gRPC
-            client.send_state_change(Request::new(state_change)).await
+            rpc::call(state_change, |request| client.send_state_change(request)).await
         } else {
             // This should never happen due to ensure_connected, but provide safety fallback
             Err(Status::unknown("Client not connected"))
@@ -5,6 +5,8 @@
 pub mod artifact;
 pub mod diagnostics;
 pub mod grpc;
+pub mod logs;
 pub mod manager;
 pub mod node;
 pub mod route;
+pub mod shutdown;
@@ -0,0 +1,284 @@
+/*
+ * SPDX-FileCopyrightText: Copyright 2024 LG Electronics Inc.
+ * SPDX-License-Identifier: Apache-2.0
+ */
+
+//! Live logs of workloads
+//!
+//! A workload is a model its package places on a node. Its logs are read by
+//! the NodeAgent of that node and relayed to the client as they come.
+//! Dropping the relayed stream, e.g. when the client disconnects, drops the
+//! stream from the NodeAgent, which stops tailing the container.
+
+use crate::grpc::sender::nodeagent;
+use crate::node::node_lookup::find_node_by_hostname;
+use common::nodeagent::fromapiserver::{WorkloadLogLine, WorkloadLogsRequest};
+use common::spec::artifact::Package;
+use common::{etcd, keys, logd};
+use futures::{Stream, StreamExt};
+use std::convert::Infallible;
+use std::pin::Pin;
+use tonic::Status;
+
+/// Log lines of a workload, ending with an error if the node fails
+pub type LogStream = Pin<Box<dyn Stream<Item = Result<WorkloadLogLine, Status>> + Send>>;
+
+/// Where the logs of the workloads of a node are read from
+#[tonic::async_trait]
+pub trait NodeLogs: Send + Sync {
+    async fn open(&self, node_ip: &str, request: WorkloadLogsRequest) -> Result<LogStream, Status>;
+}
+
+/// Logs read from the NodeAgent of the node
+pub struct NodeAgent;
+
+#[tonic::async_trait]
+impl NodeLogs for NodeAgent {
+    async fn open(&self, node_ip: &str, request: WorkloadLogsRequest) -> Result<LogStream, Status> {
+        let stream = nodeagent::get_workload_logs(request, node_ip.to_string()).await?;
+        Ok(Box::pin(stream))
+    }
+}
+
+/// Logs of the workload of `request`, relayed from the node running it
+pub async fn open(nodes: &dyn NodeLogs, request: WorkloadLogsRequest) -> Result<LogStream, Status> {
+    let node_ip = locate(&request.workload).await?;
+    relay(nodes, &node_ip, request).await
+}
+
+/// Logs of the workload of `request`, relayed from the node at `node_ip`
+///
+/// The stream of the node is passed on as it is, so lines reach the client
+/// as soon as the node sends them and dropping the relayed stream ends the
+/// stream of the node.
+pub async fn relay(
+    nodes: &dyn NodeLogs,
+    node_ip: &str,
+    request: WorkloadLogsRequest,
+) -> Result<LogStream, Status> {
+    logd!(
+        2,
+        "Relaying logs of workload {} from {} (follow: {})",
+        request.workload,
+        node_ip,
+        request.follow
+    );
+    nodes.open(node_ip, request).await
+}
+
+/// IP address of the node running `workload`
+///
+/// The node is the one the package of the workload places it on.
+async fn locate(workload: &str) -> Result<String, Status> {
+    let stored = etcd::get_all_with_prefix(keys::PACKAGE_PREFIX)
+        .await
+        .map_err(|e| Status::unavailable(format!("cannot read packages: {}", e)))?;
+    let packages: Vec<Package> = stored
+        .into_iter()
+        .filter_map(|(key, yaml)| match serde_yaml::from_str(&yaml) {
+            Ok(package) => Some(package),
+            Err(e) => {
+                logd!(4, "Skipping unreadable package '{}': {}", key, e);
+                None
+            }
+        })
+        .collect();
+    let node = node_of(&packages, workload)
+        .ok_or_else(|| Status::not_found(format!("workload {} is not in any package", workload)))?;
+
+    if let Some(info) = find_node_by_hostname(&node).await {
+        return Ok(info.ip_address);
+    }
+    etcd::get(&keys::node_address(&node)).await.map_err(|_| {
+        Status::unavailable(format!(
+            "node {} of workload {} is not registered",
+            node, workload
+        ))
+    })
+}
+
+/// Node `packages` place `workload` on
+fn node_of(packages: &[Package], workload: &str) -> Option<String> {
+    packages
+        .iter()
+        .flat_map(|package| package.get_models())
+        .find(|model| model.get_name() == workload)
+        .map(|model| model.get_node())
+}
+
+/// `stream` as newline-delimited JSON
+///
+/// Each line is sent as `{"timestamp":...,"line":"..."}`. An error of the
+/// node is sent as `{"error":"..."}` and ends the stream, as the HTTP
+/// status is already sent by then.
+pub fn ndjson(stream: LogStream) -> impl Stream<Item = Result<String, Infallible>> + Send {
+    futures::stream::unfold(Some(stream), |stream| async move {
+        let mut stream = stream?;
+        let (entry, rest) = match stream.next().await? {
+            Ok(line) => (
+                serde_json::json!({ "timestamp": line.timestamp, "line": line.line }),
+                Some(stream),
+            ),
+            Err(status) => (serde_json::json!({ "error": status.message() }), None),
+        };
+        Some((Ok(format!("{}\n", entry)), rest))
+    })
+}
+
+//UNIT TEST CASES
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+    use std::time::Duration;
+    use tokio::sync::mpsc;
+    use tokio_stream::wrappers::ReceiverStream;
+
+    /// Node streaming the lines pushed to the sender it hands out
+    #[derive(Default)]
+    struct MockNode {
+        opened: Mutex<Vec<(String, WorkloadLogsRequest)>>,
+        senders: Mutex<Vec<mpsc::Sender<Result<WorkloadLogLine, Status>>>>,
+    }
+
+    impl MockNode {
+        fn sender(&self) -> mpsc::Sender<Result<WorkloadLogLine, Status>> {
+            self.senders.lock().unwrap()[0].clone()
+        }
+    }
+
+    #[tonic::async_trait]
+    impl NodeLogs for MockNode {
+        async fn open(
+            &self,
+            node_ip: &str,
+            request: WorkloadLogsRequest,
+        ) -> Result<LogStream, Status> {
+            self.opened
+                .lock()
+                .unwrap()
+                .push((node_ip.to_string(), request));
+            let (tx, rx) = mpsc::channel(8);
+            self.senders.lock().unwrap().push(tx);
+            Ok(Box::pin(ReceiverStream::new(rx)))
+        }
+    }
+
+    fn request(workload: &str) -> WorkloadLogsRequest {
+        WorkloadLogsRequest {
+            workload: workload.to_string(),
+            follow: true,
+            tail: Some(10),
+        }
+    }
+
+    fn line(timestamp: i64, line: &str) -> WorkloadLogLine {
+        WorkloadLogLine {
+            timestamp,
+            line: line.to_string(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_relay_passes_node_lines_on() {
+        let node = MockNode::default();
+        let mut stream = relay(&node, "10.0.0.2", request("helloworld-core"))
+            .await
+            .unwrap();
+
+        let (node_ip, sent) = node.opened.lock().unwrap()[0].clone();
+        assert_eq!(node_ip, "10.0.0.2");
+        assert_eq!(sent, request("helloworld-core"));
+
+        let tx = node.sender();
+        tx.send(Ok(line(1, "started"))).await.unwrap();
+        assert_eq!(stream.next().await.unwrap().unwrap().line, "started");
+        tx.send(Ok(line(2, "serving"))).await.unwrap();
+        assert_eq!(stream.next().await.unwrap().unwrap().timestamp, 2);
+
+        // The node ending the stream ends the relay
+        drop(tx);
+        node.senders.lock().unwrap().clear();
+        assert!(stream.next().await.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_client_disconnect_closes_node_stream() {
+        let node = MockNode::default();
+        let stream = relay(&node, "10.0.0.2", request("helloworld-core"))
+            .await
+            .unwrap();
+        let tx = node.sender();
+
+        drop(stream);
+
+        tokio::time::timeout(Duration::from_secs(1), tx.closed())
+            .await
+            .expect("node stream still open after the client left");
+        assert!(tx.send(Ok(line(3, "late"))).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_ndjson_ends_with_node_error() {
+        let node = MockNode::default();
+        let stream = relay(&node, "10.0.0.2", request("helloworld-core"))
+            .await
+            .unwrap();
+        let tx = node.sender();
+        tx.send(Ok(line(1, "started"))).await.unwrap();
+        tx.send(Err(Status::unavailable("podman is down")))
+            .await
+            .unwrap();
+        // Not relayed, the error ended the stream
+        tx.send(Ok(line(2, "after"))).await.unwrap();
+
+        let body: Vec<serde_json::Value> = ndjson(stream)
+            .map(|chunk| serde_json::from_str(chunk.unwrap().trim_end()).unwrap())
+            .collect()
+            .await;
+
+        assert_eq!(
+            body,
+            vec![
+                serde_json::json!({ "timestamp": 1, "line": "started" }),
+                serde_json::json!({ "error": "podman is down" }),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_node_of_workload() {
+        let package: Package = serde_yaml::from_str(
+            r#"
+apiVersion: v1
+kind: Package
+metadata:
+  label: null
+  name: helloworld
+spec:
+  pattern:
+    - type: plain
+  models:
+    - name: helloworld-core
+      node: HPC
+      resources:
+        volume:
+        network:
+    - name: helloworld-ui
+      node: ZONE
+      resources:
+        volume:
+        network:
+"#,
+        )
+        .unwrap();
+        let packages = vec![package];
+
+        assert_eq!(node_of(&packages, "helloworld-ui").as_deref(), Some("ZONE"));
+        assert_eq!(
+            node_of(&packages, "helloworld-core").as_deref(),
+            Some("HPC")
+        );
+        assert_eq!(node_of(&packages, "other"), None);
+    }
+}
@@ -15,25 +15,29 @@
 //!   that a filter can be created.
 
 mod artifact;
+mod diagnostics;
 mod grpc;
+mod logs;
 mod manager;
 mod node;
 mod route;
+mod shutdown;
 
-use common::logd;
 use common::logd::logger;
+use tracing::trace;
 
 /// Main function of Piccolo API Server
 #[cfg(feature = "tarpaulin_include")]
 fn main() {
     // Dummy main for coverage builds
-    logd!(0, "Tarpaulin coverage build: main function stub.");
+    tracing::info!("Tarpaulin coverage build: main function stub.");
 }
 #[cfg(not(feature = "tarpaulin_include"))]
 #[tokio::main]
 async fn main() {
+    common::logging::init("apiserver");
     let _ = logger::init_async_logger("apiserver").await;
-    logd!(1, "initiailize api server");
+    trace!("initiailize api server");
 
     manager::initialize().await
 }
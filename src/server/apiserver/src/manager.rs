@@ -4,43 +4,91 @@
  */
 
 //! Controls the flow of data between each module.
+use crate::artifact::batch::{self, BatchResult};
+use crate::artifact::git::{self, GitError, GitSource};
+use crate::artifact::status;
+use crate::grpc::sender::filtergateway::{self, Delivery};
 use crate::node::node_lookup::{find_guest_nodes, find_node_by_hostname, get_node_ip};
+use crate::shutdown::Shutdown;
 use common::apiserver::api_server_connection_server::ApiServerConnectionServer;
 use common::filtergateway::{Action, HandleScenarioRequest};
-use common::logd;
+use common::health::{etcd_reachable, Health, HealthServer, ServerHealth, PROBE_INTERVAL};
+use common::keys;
 use common::nodeagent::fromapiserver::HandleYamlRequest;
+use std::time::Duration;
 use tonic::transport::Server;
+use tracing::{debug, error, info, warn};
 
 /// Launch REST API listener, gRPC server, and reload scenario data in etcd
 pub async fn initialize() {
     // 먼저 호스트 노드를 etcd에 등록합니다.
     if let Err(e) = register_host_node().await {
-        logd!(5, "Failed to register host node: {:?}", e);
+        error!("Failed to register host node: {:?}", e);
     } else {
-        logd!(2, "Host node registered successfully");
+        debug!("Host node registered successfully");
     }
 
-    tokio::join!(
-        crate::route::launch_tcp_listener(),
-        start_grpc_server(),
-        reload()
-    );
+    // Ctrl-C or SIGTERM stops both servers once their requests in flight
+    // are done, so no artifact is left half written
+    let (health, health_service) =
+        ServerHealth::new::<ApiServerConnectionServer<crate::grpc::receiver::ApiServerReceiver>>();
+    let (trigger, shutdown) = crate::shutdown::channel();
+    let signal = health.clone();
+    tokio::spawn(async move {
+        signal.shutdown_signal().await;
+        trigger.trigger();
+    });
+
+    tokio::select! {
+        _ = async {
+            tokio::join!(
+                crate::route::launch_tcp_listener(shutdown.clone()),
+                start_grpc_server(health, health_service, shutdown)
+            )
+        } => {
+            info!("ApiServer stopped");
+        }
+        _ = async {
+            tokio::join!(
+                reload(),
+                filtergateway::run_dead_letter_queue(),
+                crate::node::reaper::run()
+            );
+            std::future::pending::<()>().await
+        } => {}
+    }
 }
 
 /// Start gRPC server for node communications
-async fn start_grpc_server() {
+///
+/// Also serves `grpc.health.v1.Health`, reporting `SERVING` while etcd is
+/// reachable and `NOT_SERVING` once a shutdown signal is received. On
+/// `shutdown`, the server waits for the calls in flight before returning.
+async fn start_grpc_server(
+    health: ServerHealth,
+    health_service: HealthServer<impl Health>,
+    shutdown: Shutdown,
+) {
     let addr = common::apiserver::open_grpc_server()
         .parse()
         .expect("Invalid gRPC server address");
 
     let grpc_service = crate::grpc::receiver::ApiServerReceiver::new();
+    health.spawn_probe(PROBE_INTERVAL, etcd_reachable);
 
-    logd!(3, "ApiServer gRPC listening on {}", addr);
+    info!("ApiServer gRPC listening on {}", addr);
 
-    let _ = Server::builder()
+    if let Err(e) = Server::builder()
+        .layer(common::trace::TraceLayer)
+        .layer(common::recover::RecoverLayer)
+        .add_service(health_service)
+        .add_service(common::version::service("apiserver"))
         .add_service(ApiServerConnectionServer::new(grpc_service))
-        .serve(addr)
-        .await;
+        .serve_with_shutdown(addr, shutdown.wait())
+        .await
+    {
+        error!("ApiServer gRPC server error: {}", e);
+    }
 }
 
 /// (under construction) Send request message to piccolo cloud
@@ -84,6 +132,7 @@ async fn register_host_node() -> Result<(), Box<dyn std::error::Error + Send + S
         hostname: hostname.clone(),
         ip_address: ip_address.clone(),
         metadata: std::collections::HashMap::new(),
+        version: None,
         resources: None,
         node_type,
         node_role,
@@ -94,14 +143,12 @@ async fn register_host_node() -> Result<(), Box<dyn std::error::Error + Send + S
     node_manager.register_node(registration_request).await?;
 
     // 추가적으로 nodes/{hostname} 키에도 저장 (ActionController가 이 키를 사용)
-    let hostname_key = format!("nodes/{}", hostname);
+    let hostname_key = common::keys::node_address(&hostname);
     common::etcd::put(&hostname_key, &ip_address).await?;
 
-    logd!(
-        2,
+    debug!(
         "Host node information registered to etcd: {} ({})",
-        hostname,
-        ip_address
+        hostname, ip_address
     );
     Ok(())
 }
@@ -121,12 +168,12 @@ async fn reload() {
                 action: Action::Apply.into(),
                 scenario,
             };
-            if let Err(status) = crate::grpc::sender::filtergateway::send(req).await {
-                logd!(4, "{:#?}", status);
+            if let Err(status) = filtergateway::notify(req).await {
+                warn!("{:#?}", status);
             }
         }
     } else {
-        logd!(2, "{:#?}", scenarios_result);
+        debug!("{:#?}", scenarios_result);
     }
 }
 
@@ -134,19 +181,114 @@ async fn reload() {
 ///
 /// ### Parameters
 /// * `body: &str` - whole yaml string of piccolo artifact
+/// * `strict: bool` - reject the artifact if any document is invalid
 /// ### Description
 /// write artifact in etcd
 /// (optional) make yaml, kube files for Bluechi
 /// send a gRPC message to gateway
-pub async fn apply_artifact(body: &str) -> common::Result<()> {
-    let scenario = crate::artifact::apply(body).await?;
+/// The import status of the artifact is updated as each step completes.
+/// ### Returns
+/// * `Delivery::Pending` if gateway is unreachable and the scenario is
+///   activated once it is back
+pub async fn apply_artifact(body: &str, strict: bool) -> common::Result<Delivery> {
+    let scenario = crate::artifact::apply(body, strict).await?;
+    let names: Vec<String> = filtergateway::scenario_name(&scenario)
+        .into_iter()
+        .collect();
 
     let req: HandleScenarioRequest = HandleScenarioRequest {
         action: Action::Apply.into(),
         scenario,
     };
-    crate::grpc::sender::filtergateway::send(req).await?;
-    Ok(())
+    Ok(status::propagate(&status::Etcd, &names, filtergateway::notify(req)).await?)
+}
+
+/// Apply a batch of artifacts
+///
+/// ### Parameters
+/// * `body: &str` - JSON array or multi-document YAML of artifacts
+/// ### Description
+/// Each artifact is applied like a body of `POST /api/artifact`. One that
+/// fails is reported in its result and the rest of the batch is still
+/// applied.
+/// ### Returns
+/// * `Vec<BatchResult>` - one result per artifact, in batch order
+pub async fn apply_artifact_batch(body: &str) -> common::Result<Vec<BatchResult>> {
+    let artifacts = batch::split(body)?;
+    debug!("apply: batch of {} artifacts", artifacts.len());
+
+    Ok(batch::apply_each(&artifacts, |artifact| async move {
+        apply_artifact(&artifact, false).await
+    })
+    .await)
+}
+
+/// Artifact files applied from one git commit
+pub struct GitApply {
+    /// SHA of the commit the ref resolved to
+    pub commit: String,
+    /// Path of each artifact file and the result of applying it
+    pub files: Vec<(String, Result<Delivery, String>)>,
+}
+
+/// Apply the artifacts of a git repository
+///
+/// ### Parameters
+/// * `source: &GitSource` - repository, ref and path of the artifacts
+/// ### Description
+/// Every artifact file at the path is applied like a body of
+/// `POST /api/artifact`. A file that fails does not stop the others. The
+/// repository, ref and commit are stored with each document of an applied
+/// file, at `keys::artifact_source`.
+/// ### Returns
+/// * `GitError` if the repository, ref or path cannot be read
+pub async fn apply_artifact_from_git(source: &GitSource) -> Result<GitApply, GitError> {
+    let settings = &common::setting::get_config().git;
+    let checkout = git::fetch(
+        source,
+        settings.token.as_deref(),
+        Duration::from_secs(settings.timeout_secs),
+    )
+    .await?;
+    debug!(
+        "apply: {} artifact files from {} at {}",
+        checkout.files.len(),
+        source.reference,
+        checkout.commit
+    );
+
+    let mut files = Vec::new();
+    for (path, yaml) in &checkout.files {
+        let result = apply_artifact(yaml, false).await.map_err(|e| e.to_string());
+        match &result {
+            Ok(_) => record_git_source(&checkout.record(source, path), yaml).await,
+            Err(e) => error!("apply: {} failed: {}", path, e),
+        }
+        files.push((path.clone(), result));
+    }
+    Ok(GitApply {
+        commit: checkout.commit,
+        files,
+    })
+}
+
+/// Store where each document of an applied artifact file came from
+async fn record_git_source(record: &git::SourceRecord, yaml: &str) {
+    let Ok(documents) = crate::artifact::parser::parse(yaml, false) else {
+        return;
+    };
+    let Ok(record_str) = serde_json::to_string(record) else {
+        return;
+    };
+    for document in documents.iter().filter(|d| d.is_ok()) {
+        let (Some(kind), Some(name)) = (&document.kind, &document.name) else {
+            continue;
+        };
+        let key = keys::artifact_source(kind, name);
+        if let Err(e) = crate::artifact::data::write_to_etcd(&key, &record_str).await {
+            warn!("apply: cannot record source of {}: {}", key, e);
+        }
+    }
 }
 
 /// Withdraw downloaded artifact
@@ -157,16 +299,20 @@ pub async fn apply_artifact(body: &str) -> common::Result<()> {
 /// delete artifact in etcd
 /// (optional) delete yaml, kube files for Bluechi
 /// send a gRPC message to gateway
-pub async fn withdraw_artifact(body: &str) -> common::Result<()> {
+/// ### Returns
+/// * `Delivery::Pending` if gateway is unreachable and the scenario is
+///   deactivated once it is back
+pub async fn withdraw_artifact(body: &str) -> common::Result<Delivery> {
     let scenario = crate::artifact::withdraw(body).await?;
+    if let Some(name) = filtergateway::scenario_name(&scenario) {
+        status::forget(&status::Etcd, &name).await;
+    }
 
     let req = HandleScenarioRequest {
         action: Action::Withdraw.into(),
         scenario,
     };
-    crate::grpc::sender::filtergateway::send(req).await?;
-
-    Ok(())
+    Ok(filtergateway::notify(req).await?)
 }
 
 //UNIT Test Cases
@@ -596,6 +742,13 @@ spec:
 
     #[tonic::async_trait]
     impl FilterGatewayConnection for MockFilterGateway {
+        async fn get_metrics(
+            &self,
+            _request: Request<common::filtergateway::GetMetricsRequest>,
+        ) -> Result<Response<common::filtergateway::GetMetricsResponse>, Status> {
+            Ok(Response::new(Default::default()))
+        }
+
         /// Mocks the handle_scenario gRPC method.
         /// Returns error if scenario is empty.
         /// Returns failure status if scenario contains keywords indicating invalid input.
@@ -674,7 +827,7 @@ spec:
         body: &str,
         grpc_addr: SocketAddr,
     ) -> Result<(), Box<dyn std::error::Error>> {
-        let scenario = crate::artifact::apply(body).await?;
+        let scenario = crate::artifact::apply(body, false).await?;
 
         // Prepare the gRPC request with Apply action
         let req = HandleScenarioRequest {
@@ -714,11 +867,11 @@ spec:
                     scenario,
                 };
                 if let Err(status) = mock_send(req, grpc_addr).await {
-                    logd!(5, "{:#?}", status);
+                    error!("{:#?}", status);
                 }
             }
         } else {
-            logd!(3, "{:#?}", scenarios_result);
+            info!("{:#?}", scenarios_result);
         }
     }
 
@@ -805,7 +958,7 @@ spec:
 
         let result = apply_artifact(INVALID_ARTIFACT_YAML_EMPTY, addr).await;
         if let Err(e) = &result {
-            logd!(5, "apply_artifact() failed with error: {:?}", e);
+            error!("apply_artifact() failed with error: {:?}", e);
         }
         assert!(
             result.is_err(),
@@ -820,7 +973,7 @@ spec:
 
         let result = apply_artifact(INVALID_ARTIFACT_YAML_UNKNOWN, addr).await;
         if let Err(e) = &result {
-            logd!(5, "apply_artifact() failed with error: {:?}", e);
+            error!("apply_artifact() failed with error: {:?}", e);
         }
         assert!(
             result.is_err(),
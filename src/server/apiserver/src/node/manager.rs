@@ -7,6 +7,7 @@
 
 use common::apiserver::NodeInfo;
 use common::etcd;
+use common::keys;
 use common::logd;
 use common::nodeagent::fromapiserver::{NodeRegistrationRequest, NodeStatus};
 
@@ -26,7 +27,7 @@ impl NodeManager {
         request: NodeRegistrationRequest,
     ) -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
         // node_id 대신 hostname(node_name)을 키로 사용합니다
-        let node_key = format!("cluster/nodes/{}", request.hostname);
+        let node_key = keys::node(&request.hostname);
 
         // Create node info
         let node_info = NodeInfo {
@@ -47,13 +48,40 @@ impl NodeManager {
         etcd::put(&node_key, &node_json).await?;
 
         // 2. nodes/{ip_address}: hostname(plain string)
-        let ip_key = format!("nodes/{}", request.ip_address);
+        let ip_key = keys::node_address(&request.ip_address);
         etcd::put(&ip_key, &request.hostname).await?;
 
         // 3. nodes/{hostname}: ip 주소(plain string)
-        let hostname_key = format!("nodes/{}", request.hostname);
+        let hostname_key = keys::node_address(&request.hostname);
         etcd::put(&hostname_key, &request.ip_address).await?;
 
+        // A node registering again is no longer offline
+        let _ = etcd::delete(&keys::offline_node(&request.hostname)).await;
+
+        // 4. cluster/versions/{hostname}: version of the NodeAgent, for the fleet inventory
+        match &request.version {
+            Some(version) => {
+                etcd::put(
+                    &keys::node_version(&request.hostname),
+                    &serde_json::to_string(version)?,
+                )
+                .await?;
+                logd!(
+                    2,
+                    "Node {} runs {} {} ({})",
+                    request.hostname,
+                    version.component,
+                    version.version,
+                    version.git_commit
+                );
+            }
+            None => logd!(
+                1,
+                "Node {} did not report a version, it predates version reporting",
+                request.hostname
+            ),
+        }
+
         logd!(2, "Node {} registered successfully", request.node_id);
         Ok(format!("cluster-token-{}", request.node_id))
     }
@@ -62,8 +90,7 @@ impl NodeManager {
     pub async fn get_all_nodes(
         &self,
     ) -> Result<Vec<NodeInfo>, Box<dyn std::error::Error + Send + Sync>> {
-        let prefix = "cluster/nodes/";
-        let kvs = etcd::get_all_with_prefix(prefix).await?;
+        let kvs = etcd::get_all_with_prefix(keys::CLUSTER_NODES_PREFIX).await?;
 
         let mut nodes = Vec::new();
         for kv in kvs {
@@ -91,7 +118,7 @@ impl NodeManager {
         node_id: &str,
     ) -> Result<Option<NodeInfo>, Box<dyn std::error::Error + Send + Sync>> {
         // node_id를 직접 사용 (hostname으로 간주)
-        let node_key = format!("cluster/nodes/{}", node_id);
+        let node_key = keys::node(node_id);
 
         match etcd::get(&node_key).await {
             Ok(json_str) => {
@@ -103,22 +130,27 @@ impl NodeManager {
     }
 
     /// Update node heartbeat
+    ///
+    /// Returns false if the node is not registered, e.g. because the reaper
+    /// removed it while it was unreachable.
     pub async fn update_heartbeat(
         &self,
         node_id: &str,
-    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
-        if let Some(mut node) = self.get_node(node_id).await? {
-            node.last_heartbeat = chrono::Utc::now().timestamp();
-            node.status = NodeStatus::Ready.into();
+    ) -> Result<bool, Box<dyn std::error::Error + Send + Sync>> {
+        let Some(mut node) = self.get_node(node_id).await? else {
+            return Ok(false);
+        };
+        node.last_heartbeat = chrono::Utc::now().timestamp();
+        node.status = NodeStatus::Ready.into();
 
-            // node_name으로 키 생성
-            let node_key = format!("cluster/nodes/{}", node.hostname);
-            let node_json = serde_json::to_string(&node)?;
-            etcd::put(&node_key, &node_json).await?;
+        // node_name으로 키 생성
+        let node_key = keys::node(&node.hostname);
+        let node_json = serde_json::to_string(&node)?;
+        etcd::put(&node_key, &node_json).await?;
+        let _ = etcd::delete(&keys::offline_node(&node.hostname)).await;
 
-            logd!(1, "Updated heartbeat for node {}", node_id);
-        }
-        Ok(())
+        logd!(1, "Updated heartbeat for node {}", node_id);
+        Ok(true)
     }
 
     /// Update node status
@@ -132,7 +164,7 @@ impl NodeManager {
             node.last_heartbeat = chrono::Utc::now().timestamp();
 
             // node.hostname을 사용하여 키 생성 (node_id 대신)
-            let node_key = format!("cluster/nodes/{}", node.hostname);
+            let node_key = keys::node(&node.hostname);
             let node_json = serde_json::to_string(&node)?;
             etcd::put(&node_key, &node_json).await?;
 
@@ -148,8 +180,9 @@ impl NodeManager {
     ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
         // get_node를 사용하여 노드 정보를 얻고 hostname을 추출
         if let Some(node) = self.get_node(node_id).await? {
-            let node_key = format!("cluster/nodes/{}", node.hostname);
+            let node_key = keys::node(&node.hostname);
             etcd::delete(&node_key).await?;
+            let _ = etcd::delete(&keys::node_version(&node.hostname)).await;
 
             logd!(2, "Removed node {} from cluster", node_id);
             return Ok(());
@@ -193,6 +226,7 @@ mod tests {
             node_role: NodeRole::Nodeagent.into(),
             resources: Some(create_test_resource_info()),
             metadata,
+            version: None,
         }
     }
 
@@ -211,6 +245,7 @@ mod tests {
                 os_version: "Ubuntu 22.04".to_string(),
             }),
             metadata: HashMap::new(),
+            version: None,
         }
     }
 
@@ -223,6 +258,7 @@ mod tests {
             node_role: NodeRole::Master.into(), // Use Master instead of BluechiManager
             resources: Some(create_test_resource_info()),
             metadata: HashMap::new(),
+            version: None,
         }
     }
 
@@ -305,6 +341,36 @@ mod tests {
         }
     }
 
+    #[tokio::test]
+    async fn test_register_node_persists_version() {
+        let manager = NodeManager::new().expect("Failed to create NodeManager");
+        let mut request =
+            create_test_registration_request("versioned-node", "versioned-host", "192.168.1.120");
+        request.version = Some(common::version::info("nodeagent"));
+
+        match manager.register_node(request.clone()).await {
+            Ok(_) => {
+                let stored = etcd::get(&keys::node_version("versioned-host"))
+                    .await
+                    .expect("version not stored");
+                let stored: common::version::VersionInfo = serde_json::from_str(&stored).unwrap();
+                assert_eq!(Some(stored), request.version);
+
+                // Registering without a version keeps the last reported one
+                request.version = None;
+                manager.register_node(request).await.unwrap();
+                assert!(etcd::get(&keys::node_version("versioned-host"))
+                    .await
+                    .is_ok());
+                let _ = etcd::delete(&keys::node_version("versioned-host")).await;
+            }
+            Err(e) => {
+                // Expected if etcd is not available during testing
+                logd!(4, "Expected etcd connection error: {}", e);
+            }
+        }
+    }
+
     #[tokio::test]
     async fn test_register_multiple_nodes() {
         let manager = NodeManager::new().expect("Failed to create NodeManager");
@@ -425,7 +491,7 @@ mod tests {
         let manager = NodeManager::new().expect("Failed to create NodeManager");
 
         match manager.update_heartbeat("test-node-001").await {
-            Ok(()) => {
+            Ok(_) => {
                 println!("Heartbeat updated successfully");
             }
             Err(e) => {
@@ -587,6 +653,7 @@ mod tests {
             node_role: NodeRole::Nodeagent.into(),
             resources: None, // Test with no resources
             metadata: HashMap::new(),
+            version: None,
         };
 
         match manager.register_node(edge_case_request).await {
@@ -606,7 +673,7 @@ mod tests {
         }
 
         match manager.update_heartbeat("").await {
-            Ok(()) => println!("Heartbeat update with empty node_id succeeded"),
+            Ok(known) => assert!(!known, "no node has an empty node_id"),
             Err(e) => println!("Heartbeat update with empty node_id failed: {}", e),
         }
 
@@ -699,6 +766,7 @@ mod tests {
             node_role: NodeRole::Master.into(),
             resources: Some(create_test_resource_info()),
             metadata: complex_metadata.clone(),
+            version: None,
         };
 
         assert_eq!(request.metadata.len(), 5);
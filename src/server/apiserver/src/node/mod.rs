@@ -7,6 +7,7 @@
 
 pub mod manager;
 pub mod node_lookup;
+pub mod reaper;
 pub mod registry;
 pub mod status;
 
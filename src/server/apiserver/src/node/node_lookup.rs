@@ -7,6 +7,7 @@
 
 use common::apiserver::NodeInfo;
 use common::etcd;
+use common::keys;
 use common::logd;
 use serde_json;
 use std::error::Error;
@@ -14,13 +15,13 @@ use std::error::Error;
 /// Find a node by IP address from simplified node keys
 pub async fn find_node_by_simple_key() -> Option<String> {
     logd!(1, "Checking simplified node keys in etcd...");
-    match etcd::get_all_with_prefix("nodes/").await {
+    match etcd::get_all_with_prefix(keys::NODE_ADDRESS_PREFIX).await {
         Ok(kvs) => {
             logd!(2, "Found {} simplified node keys", kvs.len());
             // Find first non-empty key
             for kv in kvs {
                 logd!(1, "Node key: {}", kv.0);
-                let ip_address = kv.0.trim_start_matches("nodes/");
+                let ip_address = kv.0.trim_start_matches(keys::NODE_ADDRESS_PREFIX);
                 if !ip_address.is_empty() {
                     logd!(1, "Found node IP directly from key: {}", ip_address);
                     return Some(ip_address.to_string());
@@ -38,7 +39,7 @@ pub async fn find_node_by_simple_key() -> Option<String> {
 /// Find a node directly from etcd using cluster/nodes/ prefix
 pub async fn find_node_from_etcd() -> Option<String> {
     logd!(1, "Checking cluster/nodes/ prefix in etcd...");
-    let kvs = match etcd::get_all_with_prefix("cluster/nodes/").await {
+    let kvs = match etcd::get_all_with_prefix(keys::CLUSTER_NODES_PREFIX).await {
         Ok(kvs) => kvs,
         Err(e) => {
             logd!(5, "Error getting nodes: {}", e);
@@ -112,7 +113,7 @@ pub async fn find_node_from_manager() -> Option<String> {
 /// Find a node by hostname
 pub async fn find_node_by_hostname(hostname: &str) -> Option<common::apiserver::NodeInfo> {
     logd!(1, "Looking for node with hostname: {}", hostname);
-    let kvs = match common::etcd::get_all_with_prefix("cluster/nodes/").await {
+    let kvs = match common::etcd::get_all_with_prefix(keys::CLUSTER_NODES_PREFIX).await {
         Ok(kvs) => kvs,
         Err(e) => {
             logd!(5, "Error searching for hostname {}: {}", hostname, e);
@@ -179,7 +180,7 @@ pub async fn get_node_ip() -> String {
 /// Add a node IP to the simplified keys for quick lookup
 #[allow(dead_code)]
 pub async fn add_node_to_simple_keys(ip_address: &str) -> Result<(), Box<dyn Error + Send + Sync>> {
-    let key = format!("nodes/{}", ip_address);
+    let key = keys::node_address(ip_address);
     etcd::put(&key, ip_address).await?;
     logd!(2, "Added node IP to simple keys: {}", ip_address);
     Ok(())
@@ -188,7 +189,7 @@ pub async fn add_node_to_simple_keys(ip_address: &str) -> Result<(), Box<dyn Err
 /// 게스트 노드 정보를 etcd에서 검색하는 함수
 pub async fn find_guest_nodes() -> Vec<NodeInfo> {
     logd!(1, "Finding guest nodes from etcd...");
-    let kvs = match etcd::get_all_with_prefix("cluster/nodes/").await {
+    let kvs = match etcd::get_all_with_prefix(keys::CLUSTER_NODES_PREFIX).await {
         Ok(kvs) => kvs,
         Err(e) => {
             logd!(5, "Error searching for guest nodes: {}", e);
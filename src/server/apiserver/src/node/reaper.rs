@@ -0,0 +1,255 @@
+/*
+ * SPDX-FileCopyrightText: Copyright 2024 LG Electronics Inc.
+ * SPDX-License-Identifier: Apache-2.0
+ */
+
+//! Cleanup of nodes that stopped sending heartbeats
+//!
+//! A node whose last heartbeat is older than `heartbeat.offline_after_secs`
+//! is set `NotReady` and marked offline under `cluster/offline/<node>`. Once
+//! the heartbeat is older than `heartbeat.remove_after_secs`, the node and its
+//! address mappings are removed. A node registering or sending a heartbeat
+//! again clears the mark.
+
+use common::apiserver::NodeInfo;
+use common::etcd;
+use common::keys;
+use common::logd;
+use common::nodeagent::fromapiserver::NodeStatus;
+use common::setting::HeartbeatSettings;
+use std::collections::HashSet;
+use std::time::Duration;
+
+/// Change to apply to one node record
+#[derive(Debug, PartialEq)]
+pub enum Step {
+    /// Missed its heartbeats: set it `NotReady` and mark it offline
+    MarkOffline(String),
+    /// Offline for too long: remove it and its address mappings
+    Remove(String),
+    /// Sends heartbeats again: clear its offline mark
+    ClearOffline(String),
+}
+
+/// Nodes changed by one pass of the reaper
+#[derive(Debug, Default, PartialEq)]
+pub struct ReapReport {
+    pub offline: Vec<String>,
+    pub removed: Vec<String>,
+}
+
+/// Steps that bring the node records in line with their heartbeats
+///
+/// # Arguments
+///
+/// * `nodes` - Registered nodes
+/// * `offline` - Hostnames currently marked offline
+/// * `host` - Hostname of the node ApiServer runs on, never reaped
+/// * `now` - Current time, in seconds since the epoch
+/// * `settings` - Grace periods
+pub fn plan(
+    nodes: &[NodeInfo],
+    offline: &HashSet<String>,
+    host: &str,
+    now: i64,
+    settings: &HeartbeatSettings,
+) -> Vec<Step> {
+    let mut steps = Vec::new();
+    for node in nodes.iter().filter(|node| node.hostname != host) {
+        let silence = now.saturating_sub(node.last_heartbeat);
+        let marked = offline.contains(&node.hostname);
+        if silence >= settings.remove_after_secs as i64 {
+            steps.push(Step::Remove(node.hostname.clone()));
+        } else if silence >= settings.offline_after_secs as i64 {
+            if !marked {
+                steps.push(Step::MarkOffline(node.hostname.clone()));
+            }
+        } else if marked {
+            steps.push(Step::ClearOffline(node.hostname.clone()));
+        }
+    }
+    steps
+}
+
+/// Marks offline or removes the nodes that missed their heartbeats
+pub async fn reap(
+    now: i64,
+    settings: &HeartbeatSettings,
+) -> Result<ReapReport, Box<dyn std::error::Error + Send + Sync>> {
+    let nodes = crate::node::NodeManager::new()?.get_all_nodes().await?;
+    let offline: HashSet<String> = etcd::get_all_with_prefix(keys::OFFLINE_NODES_PREFIX)
+        .await?
+        .into_iter()
+        .filter_map(|(key, _)| {
+            key.strip_prefix(keys::OFFLINE_NODES_PREFIX)
+                .map(str::to_string)
+        })
+        .collect();
+    let host = &common::setting::get_config().host.name;
+
+    let mut report = ReapReport::default();
+    for step in plan(&nodes, &offline, host, now, settings) {
+        match step {
+            Step::MarkOffline(hostname) => {
+                let Some(mut node) = nodes.iter().find(|n| n.hostname == hostname).cloned() else {
+                    continue;
+                };
+                node.status = NodeStatus::NotReady.into();
+                etcd::put(&keys::node(&hostname), &serde_json::to_string(&node)?).await?;
+                etcd::put(&keys::offline_node(&hostname), &now.to_string()).await?;
+                logd!(
+                    4,
+                    "Node {} missed its heartbeats since {}, marked offline",
+                    hostname,
+                    node.last_heartbeat
+                );
+                report.offline.push(hostname);
+            }
+            Step::Remove(hostname) => {
+                if let Some(node) = nodes.iter().find(|n| n.hostname == hostname) {
+                    let _ = etcd::delete(&keys::node_address(&node.ip_address)).await;
+                }
+                let _ = etcd::delete(&keys::node_address(&hostname)).await;
+                let _ = etcd::delete(&keys::offline_node(&hostname)).await;
+                let _ = etcd::delete(&keys::node_version(&hostname)).await;
+                etcd::delete(&keys::node(&hostname)).await?;
+                logd!(4, "Node {} stayed offline too long, removed", hostname);
+                report.removed.push(hostname);
+            }
+            Step::ClearOffline(hostname) => {
+                etcd::delete(&keys::offline_node(&hostname)).await?;
+                logd!(3, "Node {} is back online", hostname);
+            }
+        }
+    }
+    Ok(report)
+}
+
+/// Runs the reaper every `heartbeat.check_interval_secs` seconds, if set
+pub async fn run() {
+    let settings = &common::setting::get_config().heartbeat;
+    if settings.check_interval_secs == 0 {
+        return;
+    }
+    let mut interval = tokio::time::interval(Duration::from_secs(settings.check_interval_secs));
+    loop {
+        interval.tick().await;
+        if let Err(e) = reap(chrono::Utc::now().timestamp(), settings).await {
+            logd!(4, "Failed to check node heartbeats: {}", e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NOW: i64 = 1_000_000;
+
+    fn settings() -> HeartbeatSettings {
+        HeartbeatSettings {
+            offline_after_secs: 90,
+            remove_after_secs: 3600,
+            check_interval_secs: 30,
+        }
+    }
+
+    fn node(hostname: &str, last_heartbeat: i64) -> NodeInfo {
+        NodeInfo {
+            hostname: hostname.to_string(),
+            ip_address: "192.0.2.10".to_string(),
+            status: NodeStatus::Ready.into(),
+            last_heartbeat,
+            ..Default::default()
+        }
+    }
+
+    fn marked(hostnames: &[&str]) -> HashSet<String> {
+        hostnames.iter().map(|h| h.to_string()).collect()
+    }
+
+    #[test]
+    fn test_plan_keeps_nodes_with_recent_heartbeats() {
+        let nodes = vec![node("HPC", NOW - 10), node("ZONE", NOW - 89)];
+
+        assert!(plan(&nodes, &marked(&[]), "master", NOW, &settings()).is_empty());
+    }
+
+    #[test]
+    fn test_plan_goes_offline_then_removed() {
+        let settings = settings();
+        let mut offline = marked(&[]);
+        let nodes = vec![node("ZONE", NOW - 100)];
+
+        // Missed its heartbeats
+        assert_eq!(
+            plan(&nodes, &offline, "master", NOW, &settings),
+            vec![Step::MarkOffline("ZONE".to_string())]
+        );
+
+        // Already marked, nothing to do until it expires
+        offline.insert("ZONE".to_string());
+        assert!(plan(&nodes, &offline, "master", NOW, &settings).is_empty());
+
+        // Expired
+        assert_eq!(
+            plan(&nodes, &offline, "master", NOW + 3500, &settings),
+            vec![Step::Remove("ZONE".to_string())]
+        );
+    }
+
+    #[test]
+    fn test_plan_removes_long_gone_nodes_directly() {
+        let nodes = vec![node("ZONE", NOW - 7200)];
+
+        assert_eq!(
+            plan(&nodes, &marked(&[]), "master", NOW, &settings()),
+            vec![Step::Remove("ZONE".to_string())]
+        );
+    }
+
+    #[test]
+    fn test_plan_clears_mark_of_reregistered_node() {
+        let nodes = vec![node("ZONE", NOW - 5)];
+
+        assert_eq!(
+            plan(&nodes, &marked(&["ZONE"]), "master", NOW, &settings()),
+            vec![Step::ClearOffline("ZONE".to_string())]
+        );
+    }
+
+    #[test]
+    fn test_plan_never_reaps_host_node() {
+        let nodes = vec![node("master", NOW - 7200)];
+
+        assert!(plan(&nodes, &marked(&[]), "master", NOW, &settings()).is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_reap_marks_stale_node_offline() {
+        let stale = node("reaper-test-node", NOW - 100);
+        if etcd::put(
+            &keys::node(&stale.hostname),
+            &serde_json::to_string(&stale).unwrap(),
+        )
+        .await
+        .is_err()
+        {
+            println!("etcd unavailable, skipping");
+            return;
+        }
+
+        let report = reap(NOW, &settings()).await.unwrap();
+        assert!(report.offline.contains(&stale.hostname));
+        assert!(etcd::get(&keys::offline_node(&stale.hostname))
+            .await
+            .is_ok());
+
+        let report = reap(NOW + 3600, &settings()).await.unwrap();
+        assert!(report.removed.contains(&stale.hostname));
+        assert!(etcd::get(&keys::node(&stale.hostname)).await.is_err());
+        assert!(etcd::get(&keys::offline_node(&stale.hostname))
+            .await
+            .is_err());
+    }
+}
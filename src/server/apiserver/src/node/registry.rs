@@ -8,6 +8,7 @@
 use base64::Engine;
 use common::apiserver::{ClusterTopology, TopologyType};
 use common::etcd;
+use common::keys;
 use common::logd;
 use prost::Message;
 
@@ -21,7 +22,7 @@ impl NodeRegistry {
     pub async fn get_topology(
         &self,
     ) -> Result<ClusterTopology, Box<dyn std::error::Error + Send + Sync>> {
-        let topology_key = "cluster/topology";
+        let topology_key = keys::CLUSTER_TOPOLOGY;
 
         match etcd::get(topology_key).await {
             Ok(encoded) => {
@@ -49,7 +50,7 @@ impl NodeRegistry {
         &self,
         topology: ClusterTopology,
     ) -> Result<ClusterTopology, Box<dyn std::error::Error + Send + Sync>> {
-        let topology_key = "cluster/topology";
+        let topology_key = keys::CLUSTER_TOPOLOGY;
 
         // 인코딩을 제거하고 json string으로 변환
         let topology_json = serde_json::to_string(&topology)?;
@@ -5,11 +5,23 @@
 
 //! Handler functions of Piccolo REST API
 
+use crate::artifact::git::{GitError, GitSource};
+use crate::artifact::lint::{self, Diagnostic};
+use crate::artifact::status;
+use crate::diagnostics::bundle;
+use crate::grpc::sender::filtergateway::Delivery;
+use crate::logs;
+use crate::node::NodeManager;
 use axum::{
-    response::Response,
+    body::Body,
+    extract::{Path, Query},
+    http::{header, StatusCode},
+    response::{IntoResponse, Response},
     routing::{delete, get, post},
-    Router,
+    Json, Router,
 };
+use common::nodeagent::fromapiserver::WorkloadLogsRequest;
+use serde::{Deserialize, Serialize};
 
 /// Make router type for composing handler and Piccolo service
 ///
@@ -20,6 +32,13 @@ pub fn router() -> Router {
         .route("/api/notify", get(notify))
         .route("/api/artifact", post(apply_artifact))
         .route("/api/artifact", delete(withdraw_artifact))
+        .route("/api/v1/artifacts", get(list_artifacts))
+        .route("/api/v1/artifacts/from-git", post(apply_artifact_from_git))
+        .route("/api/v1/scenario/batch", post(apply_artifact_batch))
+        .route("/api/v1/nodes", get(list_nodes))
+        .route("/api/v1/nodes/:name", get(get_node))
+        .route("/api/v1/workloads/:name/logs", get(workload_logs))
+        .route("/api/v1/diagnostics/bundle", post(diagnostics_bundle))
 }
 
 /// Notify of new artifact release in the cloud
@@ -32,14 +51,73 @@ async fn notify(artifact_name: String) -> Response {
     super::status(Ok(()))
 }
 
+/// Query parameters of artifact apply
+#[derive(Debug, Default, Deserialize)]
+struct ApplyParams {
+    /// Reject the whole artifact if any document is invalid
+    #[serde(default)]
+    strict: bool,
+    /// Only validate the artifact, without storing or applying it
+    #[serde(default)]
+    dry_run: bool,
+}
+
+/// Response of a dry-run apply
+#[derive(Debug, Serialize)]
+struct ValidationReport {
+    /// Keys the artifact would be stored at
+    artifacts: Vec<String>,
+    /// Lint warnings that would not block applying the artifact
+    warnings: Vec<String>,
+    /// Lint findings of the scenarios, with their location
+    diagnostics: Vec<Diagnostic>,
+}
+
+/// Response of an apply, or of a dry-run apply that failed
+#[derive(Debug, Serialize)]
+struct ApplyReport {
+    /// Outcome of the apply, or why it failed
+    message: String,
+    /// Lint findings of the scenarios, with their location
+    diagnostics: Vec<Diagnostic>,
+}
+
 /// Apply the new artifacts (scenario, package, etc...)
 ///
 /// ### Parameters
+/// * `params: ApplyParams` - `?strict=true` rejects bundles with invalid documents,
+///   `?dry_run=true` only validates the bundle
 /// * `body: String` - the string in yaml format
-async fn apply_artifact(body: String) -> Response {
-    let result = crate::manager::apply_artifact(&body).await;
+/// ### Description
+/// The lint diagnostics of the scenarios are part of the response in every
+/// mode, whether the artifact was applied or not.
+async fn apply_artifact(Query(params): Query<ApplyParams>, body: String) -> Response {
+    let diagnostics = lint::lint(&body);
+    let result = if params.dry_run {
+        match crate::artifact::validate(&body).await {
+            Ok(validation) => {
+                let report = ValidationReport {
+                    artifacts: validation.keys(),
+                    warnings: validation.warnings,
+                    diagnostics,
+                };
+                return (StatusCode::OK, Json(report)).into_response();
+            }
+            Err(e) => Err(e),
+        }
+    } else {
+        let start = std::time::Instant::now();
+        let result = crate::manager::apply_artifact(&body, params.strict).await;
+        super::metrics::observe_apply(start.elapsed());
+        result
+    };
 
-    super::status(result)
+    let (status, message) = delivery_outcome(result);
+    let report = ApplyReport {
+        message,
+        diagnostics,
+    };
+    (status, Json(report)).into_response()
 }
 
 /// Withdraw the applied scenario
@@ -49,13 +127,238 @@ async fn apply_artifact(body: String) -> Response {
 async fn withdraw_artifact(body: String) -> Response {
     let result = crate::manager::withdraw_artifact(&body).await;
 
-    super::status(result)
+    delivery_status(result)
+}
+
+/// Apply a batch of artifacts, each holding a scenario
+///
+/// ### Parameters
+/// * `body: String` - JSON array or multi-document YAML of artifacts
+/// ### Description
+/// Answers `200 OK` with the result of each artifact, even if some failed,
+/// so a partial failure is reported per artifact. Only a body that cannot
+/// be split into artifacts is rejected as a whole.
+async fn apply_artifact_batch(body: String) -> Response {
+    match crate::manager::apply_artifact_batch(&body).await {
+        Ok(results) => (StatusCode::OK, Json(results)).into_response(),
+        Err(e) => (StatusCode::BAD_REQUEST, Json(e.to_string())).into_response(),
+    }
+}
+
+/// Result of applying one artifact file from git
+#[derive(Debug, Serialize)]
+struct GitFileReport {
+    /// Artifact file, relative to the repository root
+    path: String,
+    /// `applied`, `pending` or `failed`
+    status: &'static str,
+    /// Why the file could not be applied
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
+/// Response of an apply from git
+#[derive(Debug, Serialize)]
+struct GitApplyReport {
+    /// Commit the ref resolved to
+    commit: String,
+    /// Result of each artifact file
+    artifacts: Vec<GitFileReport>,
+}
+
+/// Apply the artifacts of a git repository
+///
+/// ### Parameters
+/// * `source: GitSource` - `{"repo_url": ..., "ref": ..., "path": ...}`
+/// ### Description
+/// Unknown refs and paths answer `422 Unprocessable Entity`, an unreachable
+/// repository `502 Bad Gateway`. Otherwise the result of each artifact file
+/// is reported with `200 OK`.
+async fn apply_artifact_from_git(Json(source): Json<GitSource>) -> Response {
+    let applied = match crate::manager::apply_artifact_from_git(&source).await {
+        Ok(applied) => applied,
+        Err(e @ GitError::Fetch(_)) => {
+            return (StatusCode::BAD_GATEWAY, Json(e.to_string())).into_response()
+        }
+        Err(e) => return (StatusCode::UNPROCESSABLE_ENTITY, Json(e.to_string())).into_response(),
+    };
+
+    let artifacts = applied
+        .files
+        .into_iter()
+        .map(|(path, result)| match result {
+            Ok(delivery) => GitFileReport {
+                path,
+                status: match delivery {
+                    Delivery::Delivered => "applied",
+                    Delivery::Pending => "pending",
+                },
+                error: None,
+            },
+            Err(e) => GitFileReport {
+                path,
+                status: "failed",
+                error: Some(e),
+            },
+        })
+        .collect();
+    let report = GitApplyReport {
+        commit: applied.commit,
+        artifacts,
+    };
+    (StatusCode::OK, Json(report)).into_response()
+}
+
+/// Generate the response of a request that notifies filtergateway
+///
+/// ### Parametets
+/// * `result: Result<Delivery>` - result of API handler logic
+/// ### Description
+/// `202 Accepted` tells the client the artifact is stored but filtergateway
+/// was unreachable, so it will only take effect once filtergateway is back.
+fn delivery_status(result: common::Result<Delivery>) -> Response {
+    let (status, message) = delivery_outcome(result);
+    (status, Json(message)).into_response()
+}
+
+/// Status code and message answering the result of API handler logic
+fn delivery_outcome(result: common::Result<Delivery>) -> (StatusCode, String) {
+    match result {
+        Ok(Delivery::Pending) => (
+            StatusCode::ACCEPTED,
+            String::from("Accepted, activation pending"),
+        ),
+        Ok(Delivery::Delivered) => (StatusCode::OK, String::from("Ok")),
+        Err(e) => (StatusCode::METHOD_NOT_ALLOWED, e.to_string()),
+    }
+}
+
+/// List the import status of the artifacts
+///
+/// ### Parameters
+/// None
+/// ### Description
+/// Each artifact is listed by the name of its scenario, with its status
+/// (`Pending`, `Stored`, `Propagated` or `Failed`) and the error of a
+/// failed import.
+async fn list_artifacts() -> Response {
+    match status::list(&status::Etcd).await {
+        Ok(records) => (StatusCode::OK, Json(records)).into_response(),
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, Json(e)).into_response(),
+    }
+}
+
+/// List the nodes registered with the API server
+///
+/// ### Parameters
+/// None
+async fn list_nodes() -> Response {
+    match NodeManager.get_nodes().await {
+        Ok(nodes) => (StatusCode::OK, Json(nodes)).into_response(),
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, Json(e.to_string())).into_response(),
+    }
+}
+
+/// Get the registration record of one node
+///
+/// ### Parameters
+/// * `name: String` - hostname the node registered with
+async fn get_node(Path(name): Path<String>) -> Response {
+    match NodeManager.get_node(&name).await {
+        Ok(Some(node)) => (StatusCode::OK, Json(node)).into_response(),
+        Ok(None) => (
+            StatusCode::NOT_FOUND,
+            Json(format!("Node {} not found", name)),
+        )
+            .into_response(),
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, Json(e.to_string())).into_response(),
+    }
+}
+
+/// Query parameters of workload logs
+#[derive(Debug, Default, Deserialize)]
+struct LogsParams {
+    /// Keep streaming the lines logged after the request
+    #[serde(default)]
+    follow: bool,
+    /// Only the last lines, all lines when unset
+    tail: Option<u32>,
+}
+
+/// Stream the logs of a workload from the node running it
+///
+/// ### Parameters
+/// * `name: String` - name of the model the workload runs
+/// * `params: LogsParams` - `?follow=true` keeps streaming new lines,
+///   `?tail=N` starts with the last N lines
+/// ### Description
+/// Lines are sent as newline-delimited JSON as the node reads them. An
+/// error of the node after the stream started is sent as a last
+/// `{"error": ...}` line.
+async fn workload_logs(Path(name): Path<String>, Query(params): Query<LogsParams>) -> Response {
+    let request = WorkloadLogsRequest {
+        workload: name,
+        follow: params.follow,
+        tail: params.tail,
+    };
+    match logs::open(&logs::NodeAgent, request).await {
+        Ok(stream) => (
+            StatusCode::OK,
+            [(header::CONTENT_TYPE, "application/x-ndjson")],
+            Body::from_stream(logs::ndjson(stream)),
+        )
+            .into_response(),
+        Err(status) => {
+            let code = match status.code() {
+                tonic::Code::NotFound => StatusCode::NOT_FOUND,
+                tonic::Code::Unavailable | tonic::Code::DeadlineExceeded => {
+                    StatusCode::SERVICE_UNAVAILABLE
+                }
+                // The NodeAgent of the node is too old to stream logs
+                tonic::Code::Unimplemented => StatusCode::NOT_IMPLEMENTED,
+                _ => StatusCode::INTERNAL_SERVER_ERROR,
+            };
+            (code, Json(status.message().to_string())).into_response()
+        }
+    }
+}
+
+/// Collect a support bundle and send it back as a tar.gz archive
+///
+/// ### Parameters
+/// None
+/// ### Description
+/// A section that cannot be collected is recorded in the bundle instead of
+/// failing the request. Section size and history length come from the
+/// `diagnostics` settings.
+async fn diagnostics_bundle() -> Response {
+    let settings = &common::setting::get_config().diagnostics;
+    let collectors = bundle::collectors(settings.history_limit);
+    // A health check may spend one deadline connecting and one waiting for the answer
+    let timeout = common::rpc::default_deadline() * 2;
+
+    match bundle::build(&collectors, settings.section_limit_bytes, timeout).await {
+        Ok(bundle) => (
+            StatusCode::OK,
+            [
+                (header::CONTENT_TYPE, "application/gzip".to_string()),
+                (
+                    header::CONTENT_DISPOSITION,
+                    format!("attachment; filename=\"{}\"", bundle.file_name()),
+                ),
+            ],
+            bundle.data,
+        )
+            .into_response(),
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, Json(e.to_string())).into_response(),
+    }
 }
 
 //UNIT TEST CASES
 #[cfg(test)]
 mod tests {
 
+    use super::{delivery_status, Delivery};
     use crate::route::status;
     use axum::{
         body::Body,
@@ -283,4 +586,222 @@ spec:
         let response = app.oneshot(req).await.unwrap();
         assert_eq!(response.status(), StatusCode::METHOD_NOT_ALLOWED);
     }
+
+    // -------------------
+    // Node Endpoint Tests
+    // -------------------
+
+    /// Negative test: GET /api/v1/nodes/{name} for an unregistered node returns 404
+    #[tokio::test]
+    async fn test_get_node_not_found() {
+        let app = super::router();
+
+        let req = Request::builder()
+            .method("GET")
+            .uri("/api/v1/nodes/no-such-node")
+            .body(Body::empty())
+            .unwrap();
+
+        let response = app.oneshot(req).await.unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    }
+
+    /// Negative test: POST /api/v1/nodes returns 405 Method Not Allowed
+    #[tokio::test]
+    async fn test_list_nodes_invalid_method() {
+        let app = super::router();
+
+        let req = Request::builder()
+            .method("POST")
+            .uri("/api/v1/nodes")
+            .body(Body::empty())
+            .unwrap();
+
+        let response = app.oneshot(req).await.unwrap();
+        assert_eq!(response.status(), StatusCode::METHOD_NOT_ALLOWED);
+    }
+
+    // ----------------------
+    // Dry-run Apply Tests
+    // ----------------------
+
+    /// Positive test: POST /api/artifact?dry_run=true reports the artifacts it would store
+    #[tokio::test]
+    async fn test_apply_artifact_dry_run_valid() {
+        let app = super::router();
+        let body = r#"
+apiVersion: v1
+kind: Scenario
+metadata:
+  name: dry-run
+spec:
+  condition:
+    express: eq
+    value: "true"
+    operands:
+      type: DDS
+      name: value
+      value: ADASObstacleDetectionIsWarning
+  action: update
+  target: dry-run
+---
+apiVersion: v1
+kind: Package
+metadata:
+  name: dry-run
+spec:
+  pattern:
+    - type: plain
+  models:
+    - name: dry-run-core
+      node: HPC
+      resources:
+        volume:
+        network:
+"#;
+
+        let req = Request::builder()
+            .method("POST")
+            .uri("/api/artifact?dry_run=true")
+            .body(Body::from(body))
+            .unwrap();
+
+        let response = app.oneshot(req).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let report: serde_json::Value = serde_json::from_slice(&bytes).unwrap();
+        assert_eq!(
+            report["artifacts"],
+            serde_json::json!(["Scenario/dry-run", "Package/dry-run"])
+        );
+        assert_eq!(report["diagnostics"], serde_json::json!([]));
+    }
+
+    /// Negative test: POST /api/artifact?dry_run=true rejects an invalid artifact
+    #[tokio::test]
+    async fn test_apply_artifact_dry_run_invalid() {
+        let app = super::router();
+
+        let req = Request::builder()
+            .method("POST")
+            .uri("/api/artifact?dry_run=true")
+            .body(Body::from("apiVersion: v1\nkind: Unknown\n"))
+            .unwrap();
+
+        let response = app.oneshot(req).await.unwrap();
+        assert_eq!(response.status(), StatusCode::METHOD_NOT_ALLOWED);
+    }
+
+    // ----------------------
+    // Apply From Git Tests
+    // ----------------------
+
+    /// Negative test: POST /api/v1/artifacts/from-git with an unknown ref returns 422
+    #[tokio::test]
+    async fn test_apply_artifact_from_git_invalid_ref() {
+        let fixture = crate::artifact::git::tests::Fixture::new();
+        let body = serde_json::json!({
+            "repo_url": fixture.url(),
+            "ref": "no-such-branch",
+            "path": "scenarios",
+        });
+
+        let req = Request::builder()
+            .method("POST")
+            .uri("/api/v1/artifacts/from-git")
+            .header("Content-Type", "application/json")
+            .body(Body::from(body.to_string()))
+            .unwrap();
+
+        let response = super::router().oneshot(req).await.unwrap();
+        assert_eq!(response.status(), StatusCode::UNPROCESSABLE_ENTITY);
+    }
+
+    /// Negative test: POST /api/v1/artifacts/from-git with a path outside the repository returns 422
+    #[tokio::test]
+    async fn test_apply_artifact_from_git_invalid_path() {
+        let body = serde_json::json!({
+            "repo_url": "https://example.com/scenarios.git",
+            "ref": "main",
+            "path": "../etc",
+        });
+
+        let req = Request::builder()
+            .method("POST")
+            .uri("/api/v1/artifacts/from-git")
+            .header("Content-Type", "application/json")
+            .body(Body::from(body.to_string()))
+            .unwrap();
+
+        let response = super::router().oneshot(req).await.unwrap();
+        assert_eq!(response.status(), StatusCode::UNPROCESSABLE_ENTITY);
+    }
+
+    // ----------------------
+    // Batch Apply Tests
+    // ----------------------
+
+    /// Negative test: POST /api/v1/scenario/batch with a malformed JSON array returns 400
+    #[tokio::test]
+    async fn test_apply_artifact_batch_malformed() {
+        let req = Request::builder()
+            .method("POST")
+            .uri("/api/v1/scenario/batch")
+            .body(Body::from("[\"unterminated"))
+            .unwrap();
+
+        let response = super::router().oneshot(req).await.unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+
+    /// An artifact filtergateway has not received yet is accepted, not applied
+    #[test]
+    fn test_delivery_status() {
+        assert_eq!(
+            delivery_status(Ok(Delivery::Delivered)).status(),
+            StatusCode::OK
+        );
+        assert_eq!(
+            delivery_status(Ok(Delivery::Pending)).status(),
+            StatusCode::ACCEPTED
+        );
+        let err = Box::new(std::io::Error::other("test error")) as Box<_>;
+        assert_eq!(
+            delivery_status(Err(err)).status(),
+            StatusCode::METHOD_NOT_ALLOWED
+        );
+    }
+
+    /// Every apply mode reports the lint diagnostics with their location
+    #[tokio::test]
+    async fn test_apply_artifact_reports_diagnostics_in_every_mode() {
+        let body = "apiVersion: v1\nkind: Scenario\nmetadata:\n  name: [lint-report\n";
+
+        for query in ["", "?strict=true", "?dry_run=true"] {
+            let req = Request::builder()
+                .method("POST")
+                .uri(format!("/api/artifact{}", query))
+                .body(Body::from(body))
+                .unwrap();
+
+            let response = super::router().oneshot(req).await.unwrap();
+            assert_eq!(
+                response.status(),
+                StatusCode::METHOD_NOT_ALLOWED,
+                "{}",
+                query
+            );
+            let bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
+                .await
+                .unwrap();
+            let report: serde_json::Value = serde_json::from_slice(&bytes).unwrap();
+            assert!(!report["message"].as_str().unwrap().is_empty(), "{}", query);
+            let diagnostic = &report["diagnostics"][0];
+            assert_eq!(diagnostic["severity"], "error", "{}", query);
+            assert_eq!(diagnostic["line"], 5, "{}", query);
+            assert_eq!(diagnostic["column"], 1, "{}", query);
+        }
+    }
 }
@@ -0,0 +1,259 @@
+/*
+ * SPDX-FileCopyrightText: Copyright 2024 LG Electronics Inc.
+ * SPDX-License-Identifier: Apache-2.0
+ */
+
+//! Request metrics of the REST API in Prometheus text format
+
+use axum::{
+    extract::{MatchedPath, Request},
+    http::header,
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::sync::{LazyLock, Mutex};
+use std::time::{Duration, Instant};
+
+/// Upper bounds, in seconds, of the artifact apply duration buckets
+const APPLY_BUCKETS: [f64; 8] = [0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0, 30.0];
+
+/// Route label of requests that matched no route
+const UNMATCHED_ROUTE: &str = "unmatched";
+
+static REGISTRY: LazyLock<Registry> = LazyLock::new(Registry::default);
+
+#[derive(Default)]
+struct Registry {
+    /// Counters keyed by (route, method)
+    routes: Mutex<BTreeMap<(String, String), RouteStats>>,
+    apply: Mutex<Histogram>,
+}
+
+/// Name, help text and value of a per-route counter
+type Counter = (&'static str, &'static str, fn(&RouteStats) -> u64);
+
+#[derive(Default)]
+struct RouteStats {
+    requests: u64,
+    errors: u64,
+    duration_sum: f64,
+}
+
+struct Histogram {
+    /// Observations per bucket of `APPLY_BUCKETS`, not cumulative
+    buckets: [u64; APPLY_BUCKETS.len()],
+    count: u64,
+    sum: f64,
+}
+
+impl Default for Histogram {
+    fn default() -> Self {
+        Self {
+            buckets: [0; APPLY_BUCKETS.len()],
+            count: 0,
+            sum: 0.0,
+        }
+    }
+}
+
+impl Histogram {
+    fn observe(&mut self, seconds: f64) {
+        if let Some(i) = APPLY_BUCKETS.iter().position(|le| seconds <= *le) {
+            self.buckets[i] += 1;
+        }
+        self.count += 1;
+        self.sum += seconds;
+    }
+}
+
+/// Middleware counting requests, errors and latency per route
+///
+/// ### Parametets
+/// * `request: Request` - incoming request
+/// * `next: Next` - rest of the middleware stack
+/// ### Description
+/// Responses with a 4xx or 5xx status count as errors.
+pub async fn track(request: Request, next: Next) -> Response {
+    let route = request
+        .extensions()
+        .get::<MatchedPath>()
+        .map(|path| path.as_str().to_string())
+        .unwrap_or_else(|| UNMATCHED_ROUTE.to_string());
+    let method = request.method().to_string();
+    let start = Instant::now();
+
+    let response = next.run(request).await;
+
+    let status = response.status();
+    let mut routes = REGISTRY.routes.lock().unwrap();
+    let stats = routes.entry((route, method)).or_default();
+    stats.requests += 1;
+    if status.is_client_error() || status.is_server_error() {
+        stats.errors += 1;
+    }
+    stats.duration_sum += start.elapsed().as_secs_f64();
+    response
+}
+
+/// Record how long an artifact apply took
+pub fn observe_apply(duration: Duration) {
+    REGISTRY
+        .apply
+        .lock()
+        .unwrap()
+        .observe(duration.as_secs_f64());
+}
+
+/// Expose the metrics
+///
+/// ### Parametets
+/// None
+pub async fn metrics() -> Response {
+    (
+        [(header::CONTENT_TYPE, "text/plain; version=0.0.4")],
+        render(),
+    )
+        .into_response()
+}
+
+fn render() -> String {
+    let mut out = String::new();
+    {
+        let routes = REGISTRY.routes.lock().unwrap();
+        let counters: [Counter; 2] = [
+            (
+                "apiserver_http_requests_total",
+                "Requests handled, by route and method.",
+                |s| s.requests,
+            ),
+            (
+                "apiserver_http_request_errors_total",
+                "Requests answered with a 4xx or 5xx status.",
+                |s| s.errors,
+            ),
+        ];
+        for (name, help, value) in counters {
+            let _ = writeln!(out, "# HELP {} {}", name, help);
+            let _ = writeln!(out, "# TYPE {} counter", name);
+            for ((route, method), stats) in routes.iter() {
+                let _ = writeln!(
+                    out,
+                    "{}{{route=\"{}\",method=\"{}\"}} {}",
+                    name,
+                    route,
+                    method,
+                    value(stats)
+                );
+            }
+        }
+
+        let name = "apiserver_http_request_duration_seconds";
+        let _ = writeln!(out, "# HELP {} Time spent handling requests.", name);
+        let _ = writeln!(out, "# TYPE {} summary", name);
+        for ((route, method), stats) in routes.iter() {
+            let labels = format!("route=\"{}\",method=\"{}\"", route, method);
+            let _ = writeln!(out, "{}_sum{{{}}} {}", name, labels, stats.duration_sum);
+            let _ = writeln!(out, "{}_count{{{}}} {}", name, labels, stats.requests);
+        }
+    }
+
+    let apply = REGISTRY.apply.lock().unwrap();
+    let name = "apiserver_artifact_apply_duration_seconds";
+    let _ = writeln!(out, "# HELP {} Time taken to apply an artifact.", name);
+    let _ = writeln!(out, "# TYPE {} histogram", name);
+    let mut cumulative = 0;
+    for (le, count) in APPLY_BUCKETS.iter().zip(apply.buckets) {
+        cumulative += count;
+        let _ = writeln!(out, "{}_bucket{{le=\"{}\"}} {}", name, le, cumulative);
+    }
+    let _ = writeln!(out, "{}_bucket{{le=\"+Inf\"}} {}", name, apply.count);
+    let _ = writeln!(out, "{}_sum {}", name, apply.sum);
+    let _ = writeln!(out, "{}_count {}", name, apply.count);
+    out
+}
+
+//UNIT TEST CASES
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{
+        body::Body,
+        http::{Request, StatusCode},
+        middleware,
+        routing::get,
+        Router,
+    };
+    use tower::ServiceExt;
+
+    /// Router with routes only this test uses, so parallel tests do not
+    /// change the counters it reads
+    fn app() -> Router {
+        Router::new()
+            .route(
+                "/test/metrics/ok",
+                get(|| async { StatusCode::OK }).post(|| async { StatusCode::BAD_REQUEST }),
+            )
+            .route("/metrics", get(metrics))
+            .layer(middleware::from_fn(track))
+    }
+
+    async fn send(app: &Router, method: &str, uri: &str) -> Response {
+        let request = Request::builder()
+            .method(method)
+            .uri(uri)
+            .body(Body::empty())
+            .unwrap();
+        app.clone().oneshot(request).await.unwrap()
+    }
+
+    async fn exposition(app: &Router) -> String {
+        let response = send(app, "GET", "/metrics").await;
+        assert_eq!(response.status(), StatusCode::OK);
+        let bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        String::from_utf8(bytes.to_vec()).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_requests_are_counted_per_route() {
+        let app = app();
+        send(&app, "GET", "/test/metrics/ok").await;
+        send(&app, "GET", "/test/metrics/ok").await;
+        send(&app, "POST", "/test/metrics/ok").await;
+
+        let text = exposition(&app).await;
+        assert!(text.contains(
+            "apiserver_http_requests_total{route=\"/test/metrics/ok\",method=\"GET\"} 2"
+        ));
+        assert!(text.contains(
+            "apiserver_http_requests_total{route=\"/test/metrics/ok\",method=\"POST\"} 1"
+        ));
+        assert!(text.contains(
+            "apiserver_http_request_errors_total{route=\"/test/metrics/ok\",method=\"GET\"} 0"
+        ));
+        assert!(text.contains(
+            "apiserver_http_request_errors_total{route=\"/test/metrics/ok\",method=\"POST\"} 1"
+        ));
+        assert!(text.contains(
+            "apiserver_http_request_duration_seconds_count{route=\"/test/metrics/ok\",method=\"GET\"} 2"
+        ));
+        assert!(text.contains("# TYPE apiserver_artifact_apply_duration_seconds histogram"));
+    }
+
+    #[test]
+    fn test_histogram_buckets() {
+        let mut histogram = Histogram::default();
+        histogram.observe(0.05);
+        histogram.observe(0.3);
+        histogram.observe(60.0);
+
+        assert_eq!(histogram.buckets[0], 1);
+        assert_eq!(histogram.buckets[2], 1);
+        assert_eq!(histogram.buckets.iter().sum::<u64>(), 2);
+        assert_eq!(histogram.count, 3);
+        assert!((histogram.sum - 60.35).abs() < 1e-9);
+    }
+}
@@ -6,10 +6,14 @@
 //! Access point of Piccolo REST API
 
 pub mod api;
+pub mod metrics;
 
+use crate::shutdown::Shutdown;
 use axum::{
     http::StatusCode,
+    middleware,
     response::{IntoResponse, Response},
+    routing::get,
     Json, Router,
 };
 use common::logd;
@@ -19,29 +23,47 @@ use tower_http::cors::{Any, CorsLayer};
 /// Serve Piccolo HTTP API service
 ///
 /// ### Parametets
-/// None
+/// * `shutdown: Shutdown` - stops the service once triggered
 /// ### Description
 /// CORS layer needs to be considerd.
-pub async fn launch_tcp_listener() {
+pub async fn launch_tcp_listener(shutdown: Shutdown) {
     let addr = common::apiserver::open_rest_server();
     let listener = TcpListener::bind(addr).await.unwrap();
     let cors = CorsLayer::new()
         .allow_origin(Any)
         .allow_methods(Any)
         .allow_headers(Any);
-    let app = Router::new().merge(api::router()).layer(cors);
+    let app = Router::new()
+        .merge(api::router())
+        .route("/metrics", get(metrics::metrics))
+        .layer(middleware::from_fn(metrics::track))
+        .layer(cors);
 
     logd!(
         2,
         "http api listening on {}",
         listener.local_addr().unwrap()
     );
-    logd!(
-        2,
-        "http api listening on {}",
-        listener.local_addr().unwrap()
-    );
-    axum::serve(listener, app).await.unwrap();
+    serve(listener, app, shutdown).await;
+}
+
+/// Serve `app` on `listener` until `shutdown` is triggered
+///
+/// ### Parametets
+/// * `listener: TcpListener` - bound listener of the service
+/// * `app: Router` - routes of the service
+/// * `shutdown: Shutdown` - stops accepting connections once triggered
+/// ### Description
+/// Requests in flight when the shutdown starts are completed before this
+/// returns, so an artifact import is never cut short.
+pub async fn serve(listener: TcpListener, app: Router, shutdown: Shutdown) {
+    if let Err(e) = axum::serve(listener, app)
+        .with_graceful_shutdown(shutdown.wait())
+        .await
+    {
+        logd!(5, "http api server error: {}", e);
+    }
+    logd!(3, "http api stopped");
 }
 
 /// Generate appropriate API response based on handler execution result
@@ -90,7 +112,8 @@ mod tests {
     #[tokio::test]
     async fn test_launch_tcp_listener_success() {
         let handle = tokio::task::spawn(async {
-            launch_tcp_listener().await;
+            let (_trigger, shutdown) = crate::shutdown::channel();
+            launch_tcp_listener(shutdown).await;
         });
 
         tokio::time::sleep(std::time::Duration::from_millis(500)).await;
@@ -124,6 +147,52 @@ mod tests {
         handle.abort();
     }
 
+    // Shutdown waits for a request in flight instead of dropping it
+    #[tokio::test]
+    async fn test_shutdown_completes_request_in_flight() {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        let (handler_started, started) = tokio::sync::oneshot::channel::<()>();
+        let handler_started = std::sync::Arc::new(std::sync::Mutex::new(Some(handler_started)));
+        let app = Router::new().route(
+            "/slow",
+            post(move || {
+                if let Some(tx) = handler_started.lock().unwrap().take() {
+                    let _ = tx.send(());
+                }
+                async {
+                    tokio::time::sleep(std::time::Duration::from_millis(500)).await;
+                    "imported"
+                }
+            }),
+        );
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let (trigger, shutdown) = crate::shutdown::channel();
+        let server = tokio::spawn(serve(listener, app, shutdown));
+
+        let mut stream = tokio::net::TcpStream::connect(addr).await.unwrap();
+        stream
+            .write_all(
+                b"POST /slow HTTP/1.1\r\nHost: localhost\r\nContent-Length: 0\r\nConnection: close\r\n\r\n",
+            )
+            .await
+            .unwrap();
+        started.await.unwrap();
+        trigger.trigger();
+
+        let mut response = String::new();
+        stream.read_to_string(&mut response).await.unwrap();
+        assert!(response.starts_with("HTTP/1.1 200 OK"), "{}", response);
+        assert!(response.ends_with("imported"), "{}", response);
+
+        tokio::time::timeout(std::time::Duration::from_secs(5), server)
+            .await
+            .expect("server did not stop")
+            .unwrap();
+        assert!(tokio::net::TcpStream::connect(addr).await.is_err());
+    }
+
     // Test router configuration and valid endpoints (Positive)
     #[tokio::test]
     async fn test_router_configuration() {
@@ -0,0 +1,91 @@
+/*
+ * SPDX-FileCopyrightText: Copyright 2024 LG Electronics Inc.
+ * SPDX-License-Identifier: Apache-2.0
+ */
+
+//! Shutdown signal shared by the REST and gRPC servers
+//!
+//! Once triggered, both servers stop accepting connections and wait for the
+//! requests in flight, so an artifact being imported is written to etcd
+//! completely before the process exits.
+
+use tokio::sync::watch;
+
+/// Starts the shutdown of the servers holding the matching [`Shutdown`]
+pub struct ShutdownTrigger {
+    tx: watch::Sender<bool>,
+}
+
+impl ShutdownTrigger {
+    /// Starts the shutdown, once
+    pub fn trigger(&self) {
+        self.tx.send_replace(true);
+    }
+}
+
+/// Completes once the shutdown has been triggered
+#[derive(Clone)]
+pub struct Shutdown {
+    rx: watch::Receiver<bool>,
+}
+
+impl Shutdown {
+    /// Waits until the shutdown is triggered
+    ///
+    /// Never completes if the trigger is dropped without being used.
+    pub async fn wait(mut self) {
+        if self.rx.wait_for(|triggered| *triggered).await.is_err() {
+            std::future::pending::<()>().await;
+        }
+    }
+}
+
+/// Creates a shutdown signal and its trigger
+pub fn channel() -> (ShutdownTrigger, Shutdown) {
+    let (tx, rx) = watch::channel(false);
+    (ShutdownTrigger { tx }, Shutdown { rx })
+}
+
+//UNIT TEST CASES
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[tokio::test]
+    async fn test_wait_completes_for_every_holder() {
+        let (trigger, shutdown) = channel();
+        let waiters: Vec<_> = (0..2)
+            .map(|_| tokio::spawn(shutdown.clone().wait()))
+            .collect();
+        // Not triggered yet
+        assert!(
+            tokio::time::timeout(Duration::from_millis(20), shutdown.clone().wait())
+                .await
+                .is_err()
+        );
+
+        trigger.trigger();
+
+        for waiter in waiters {
+            tokio::time::timeout(Duration::from_secs(1), waiter)
+                .await
+                .expect("shutdown not seen")
+                .unwrap();
+        }
+        // Holders created after the trigger see it too
+        shutdown.clone().wait().await;
+    }
+
+    #[tokio::test]
+    async fn test_dropped_trigger_never_shuts_down() {
+        let (trigger, shutdown) = channel();
+        drop(trigger);
+
+        assert!(
+            tokio::time::timeout(Duration::from_millis(100), shutdown.wait())
+                .await
+                .is_err()
+        );
+    }
+}
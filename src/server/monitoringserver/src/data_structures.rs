@@ -618,6 +618,7 @@ mod tests {
             write_bytes: 4000,
             arch: "x86_64".to_string(),
             os: "linux".to_string(),
+            dropped_container_updates: 0,
         }
     }
 
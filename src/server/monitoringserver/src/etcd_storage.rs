@@ -6,9 +6,23 @@
 //! Store and retrieve monitoring data in etcd
 
 use crate::data_structures::{BoardInfo, SocInfo};
+use crate::restart_tracker::RestartRecord;
+use common::keys::{self, METRICS_PREFIX};
 use common::monitoringserver::{ContainerInfo, NodeInfo}; // Use protobuf types
 use serde::{de::DeserializeOwned, Serialize};
 use serde_json::Value;
+use std::collections::BTreeMap;
+
+/// Record count, size and age bounds of one resource type in etcd
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PrefixStats {
+    pub count: u64,
+    pub total_bytes: u64,
+    /// Unix seconds of the oldest record carrying a timestamp
+    pub oldest: Option<i64>,
+    /// Unix seconds of the newest record carrying a timestamp
+    pub newest: Option<i64>,
+}
 
 /// Generic function to store info in etcd
 async fn store_info<T: Serialize>(
@@ -16,9 +30,18 @@ async fn store_info<T: Serialize>(
     resource_id: &str,
     info: &T,
 ) -> common::Result<()> {
-    let key = format!("/piccolo/metrics/{}/{}", resource_type, resource_id);
-    let json_data = serde_json::to_string(info)
+    let key = keys::metrics(resource_type, resource_id);
+    let mut value = serde_json::to_value(info)
         .map_err(|e| format!("Failed to serialize {}: {}", resource_type, e))?;
+    // Record the write time so storage_stats() can report record ages
+    if let Value::Object(map) = &mut value {
+        let now = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_secs() as i64)
+            .unwrap_or(0);
+        map.insert("stored_at".to_string(), Value::from(now));
+    }
+    let json_data = value.to_string();
 
     common::etcd::put(&key, &json_data).await?;
     println!(
@@ -33,7 +56,7 @@ async fn get_info<T: DeserializeOwned>(
     resource_type: &str,
     resource_id: &str,
 ) -> common::Result<T> {
-    let key = format!("/piccolo/metrics/{}/{}", resource_type, resource_id);
+    let key = keys::metrics(resource_type, resource_id);
     let json_data = common::etcd::get(&key).await?;
 
     let info: T = serde_json::from_str(&json_data)
@@ -44,7 +67,7 @@ async fn get_info<T: DeserializeOwned>(
 
 /// Generic function to delete info from etcd
 async fn delete_info(resource_type: &str, resource_id: &str) -> common::Result<()> {
-    let key = format!("/piccolo/metrics/{}/{}", resource_type, resource_id);
+    let key = keys::metrics(resource_type, resource_id);
     common::etcd::delete(&key).await?;
     println!(
         "[ETCD] Deleted the metrics for {}: {}",
@@ -55,7 +78,7 @@ async fn delete_info(resource_type: &str, resource_id: &str) -> common::Result<(
 
 /// Generic function to get all items of a type from etcd
 async fn get_all_info<T: DeserializeOwned>(resource_type: &str) -> common::Result<Vec<T>> {
-    let prefix = format!("/piccolo/metrics/{}/", resource_type);
+    let prefix = keys::metrics_prefix(resource_type);
     let kv_pairs = common::etcd::get_all_with_prefix(&prefix).await?;
 
     let mut items = Vec::new();
@@ -105,6 +128,11 @@ pub async fn store_container_info(container_info: &ContainerInfo) -> common::Res
     store_info("containers", &container_info.id, &json_value).await
 }
 
+/// Store the restart history of a container in etcd
+pub async fn store_restart_record(record: &RestartRecord) -> common::Result<()> {
+    store_info("restarts", &record.key(), record).await
+}
+
 /// Retrieve NodeInfo from etcd
 pub async fn get_node_info(node_name: &str) -> common::Result<NodeInfo> {
     get_info("nodes", node_name).await
@@ -158,6 +186,7 @@ pub async fn get_container_info(container_id: &str) -> common::Result<ContainerI
             .iter()
             .map(|(k, v)| (k.clone(), v.as_str().unwrap_or_default().to_string()))
             .collect(),
+        usage: serde_json::from_value(json_value["usage"].clone()).ok(),
     };
 
     Ok(container_info)
@@ -178,9 +207,14 @@ pub async fn get_all_boards() -> common::Result<Vec<BoardInfo>> {
     get_all_info("boards").await
 }
 
+/// Get the restart history of every container from etcd
+pub async fn get_all_restart_records() -> common::Result<Vec<RestartRecord>> {
+    get_all_info("restarts").await
+}
+
 /// Get all containers from etcd
 pub async fn get_all_containers() -> common::Result<Vec<ContainerInfo>> {
-    let prefix = "/piccolo/metrics/containers/".to_string();
+    let prefix = keys::metrics_prefix("containers");
     let kv_pairs = common::etcd::get_all_with_prefix(&prefix).await?;
 
     let mut containers = Vec::new();
@@ -220,6 +254,7 @@ pub async fn get_all_containers() -> common::Result<Vec<ContainerInfo>> {
                         .iter()
                         .map(|(k, v)| (k.clone(), v.as_str().unwrap_or_default().to_string()))
                         .collect(),
+                    usage: serde_json::from_value(json_value["usage"].clone()).ok(),
                 };
                 containers.push(container_info);
             }
@@ -235,7 +270,7 @@ pub async fn get_all_containers() -> common::Result<Vec<ContainerInfo>> {
 pub async fn delete_all_containers() -> common::Result<()> {
     use tokio::time::{sleep, Duration};
 
-    let prefix = "/piccolo/metrics/containers/".to_string();
+    let prefix = keys::metrics_prefix("containers");
     const MAX_RETRIES: u32 = 10;
     const RETRY_INTERVAL_SECS: u64 = 1;
 
@@ -340,11 +375,57 @@ pub async fn delete_board_info(board_id: &str) -> common::Result<()> {
     delete_info("boards", board_id).await
 }
 
+/// Delete the restart history of a container from etcd
+pub async fn delete_restart_record(key: &str) -> common::Result<()> {
+    delete_info("restarts", key).await
+}
+
 /// Delete ContainerInfo from etcd
 pub async fn delete_container_info(container_id: &str) -> common::Result<()> {
     delete_info("containers", container_id).await
 }
 
+/// Count the stored records per resource type with their size and age bounds
+pub async fn storage_stats() -> common::Result<BTreeMap<String, PrefixStats>> {
+    let kv_pairs = common::etcd::get_all_with_prefix(METRICS_PREFIX).await?;
+    Ok(compute_storage_stats(&kv_pairs))
+}
+
+/// Group `(key, value)` pairs by the resource type following `METRICS_PREFIX`
+fn compute_storage_stats(kv_pairs: &[(String, String)]) -> BTreeMap<String, PrefixStats> {
+    let mut stats: BTreeMap<String, PrefixStats> = BTreeMap::new();
+
+    for (key, value) in kv_pairs {
+        let Some(rest) = key.strip_prefix(METRICS_PREFIX) else {
+            continue;
+        };
+        let resource_type = rest.split('/').next().unwrap_or_default();
+        let entry = stats.entry(resource_type.to_string()).or_default();
+        entry.count += 1;
+        entry.total_bytes += value.len() as u64;
+
+        if let Some(ts) = record_timestamp(value) {
+            entry.oldest = Some(entry.oldest.map_or(ts, |old| old.min(ts)));
+            entry.newest = Some(entry.newest.map_or(ts, |new| new.max(ts)));
+        }
+    }
+
+    stats
+}
+
+/// Write time of a stored record
+///
+/// Uses `stored_at` added by `store_info`, falling back to the
+/// `last_updated` field of SoC/board records written before it existed.
+fn record_timestamp(value: &str) -> Option<i64> {
+    let v: Value = serde_json::from_str(value).ok()?;
+    v.get("stored_at").and_then(|t| t.as_i64()).or_else(|| {
+        v.get("last_updated")
+            .and_then(|t| t.get("secs_since_epoch"))
+            .and_then(|t| t.as_i64())
+    })
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -368,6 +449,7 @@ mod tests {
             write_bytes: 400,
             arch: "x86_64".to_string(),
             os: "linux".to_string(),
+            dropped_container_updates: 0,
         }
     }
 
@@ -562,4 +644,79 @@ mod tests {
         let result = get_board_info("board4").await;
         assert!(result.is_ok() || result.is_err());
     }
+
+    fn kv(key: &str, value: Value) -> (String, String) {
+        (key.to_string(), value.to_string())
+    }
+
+    #[test]
+    fn test_compute_storage_stats_counts_and_bounds() {
+        let kvs = vec![
+            kv(
+                "/piccolo/metrics/nodes/node1",
+                serde_json::json!({"node_name": "node1", "stored_at": 1_700_000_100}),
+            ),
+            kv(
+                "/piccolo/metrics/nodes/node2",
+                serde_json::json!({"node_name": "node2", "stored_at": 1_700_000_000}),
+            ),
+            kv(
+                "/piccolo/metrics/nodes/node3",
+                serde_json::json!({"node_name": "node3", "stored_at": 1_700_000_050}),
+            ),
+            kv(
+                "/piccolo/metrics/stress/proc/42",
+                serde_json::json!({"pid": 42, "stored_at": 1_700_000_500}),
+            ),
+            kv(
+                "/piccolo/metrics/socs/soc1",
+                serde_json::json!({"soc_id": "soc1", "last_updated": {"secs_since_epoch": 1_600_000_000, "nanos_since_epoch": 0}}),
+            ),
+        ];
+
+        let stats = compute_storage_stats(&kvs);
+
+        assert_eq!(stats.len(), 3);
+        let nodes = &stats["nodes"];
+        assert_eq!(nodes.count, 3);
+        assert_eq!(nodes.oldest, Some(1_700_000_000));
+        assert_eq!(nodes.newest, Some(1_700_000_100));
+        let expected_bytes: u64 = kvs[..3].iter().map(|(_, v)| v.len() as u64).sum();
+        assert_eq!(nodes.total_bytes, expected_bytes);
+
+        let stress = &stats["stress"];
+        assert_eq!(stress.count, 1);
+        assert_eq!(stress.oldest, Some(1_700_000_500));
+        assert_eq!(stress.newest, Some(1_700_000_500));
+
+        assert_eq!(stats["socs"].oldest, Some(1_600_000_000));
+    }
+
+    #[test]
+    fn test_compute_storage_stats_without_timestamps() {
+        let kvs = vec![
+            kv(
+                "/piccolo/metrics/containers/c1",
+                serde_json::json!({"id": "c1"}),
+            ),
+            (
+                "/piccolo/metrics/containers/c2".to_string(),
+                "not json".to_string(),
+            ),
+            kv("/other/prefix/x", serde_json::json!({"stored_at": 1})),
+        ];
+
+        let stats = compute_storage_stats(&kvs);
+
+        assert_eq!(stats.len(), 1);
+        let containers = &stats["containers"];
+        assert_eq!(containers.count, 2);
+        assert_eq!(containers.oldest, None);
+        assert_eq!(containers.newest, None);
+    }
+
+    #[test]
+    fn test_compute_storage_stats_empty() {
+        assert!(compute_storage_stats(&[]).is_empty());
+    }
 }
@@ -4,11 +4,15 @@
 */
 use common::monitoringserver::monitoring_server_connection_server::MonitoringServerConnection;
 use common::monitoringserver::{
-    ContainerList, NodeInfo, SendContainerListResponse, SendNodeInfoResponse,
-    StressMonitoringMetric, StressMonitoringMetricResponse,
+    ContainerList, GetLogsRequest, GetLogsResponse, LogEntry, NodeInfo, PrefixStats,
+    SendContainerListResponse, SendLogsResponse, SendNodeInfoResponse, StorageStatsRequest,
+    StorageStatsResponse, StressMonitoringMetric, StressMonitoringMetricResponse,
 };
+use std::sync::Arc;
 use tokio::sync::mpsc;
-use tonic::{Request, Response, Status};
+use tonic::{Request, Response, Status, Streaming};
+
+use crate::log_store::LogStore;
 
 use serde::Deserialize;
 use serde_json;
@@ -74,6 +78,7 @@ pub struct MonitoringServerReceiver {
     pub tx_container: mpsc::Sender<ContainerList>,
     pub tx_node: mpsc::Sender<NodeInfo>,
     pub tx_stress: mpsc::Sender<String>,
+    pub logs: Arc<LogStore>,
 }
 
 #[tonic::async_trait]
@@ -140,6 +145,82 @@ impl MonitoringServerConnection for MonitoringServerReceiver {
             )),
         }
     }
+
+    /// Report record counts, sizes and age bounds of the monitoring data in etcd
+    ///
+    /// Used to check that eviction keeps up with incoming data.
+    async fn get_storage_stats<'life>(
+        &'life self,
+        _request: Request<StorageStatsRequest>,
+    ) -> Result<Response<StorageStatsResponse>, Status> {
+        let stats = crate::etcd_storage::storage_stats()
+            .await
+            .map_err(|e| Status::unavailable(format!("cannot read storage stats: {}", e)))?;
+
+        Ok(Response::new(to_storage_stats_response(stats)))
+    }
+
+    /// Handle the stream of container log lines from a nodeagent
+    ///
+    /// Lines go straight to the ring buffers, which drop their oldest lines
+    /// instead of waiting, so a busy container never slows the stream down.
+    async fn send_logs<'life>(
+        &'life self,
+        request: Request<Streaming<LogEntry>>,
+    ) -> Result<Response<SendLogsResponse>, Status> {
+        let mut stream = request.into_inner();
+        let mut received = 0;
+
+        while let Some(entry) = stream.message().await? {
+            if entry.node.is_empty() || entry.container.is_empty() {
+                return Err(Status::invalid_argument(
+                    "log entry without node or container",
+                ));
+            }
+            self.logs.push(entry);
+            received += 1;
+        }
+
+        Ok(Response::new(SendLogsResponse { received }))
+    }
+
+    /// Return the most recent log lines of a container
+    async fn get_logs<'life>(
+        &'life self,
+        request: Request<GetLogsRequest>,
+    ) -> Result<Response<GetLogsResponse>, Status> {
+        let req = request.into_inner();
+        if req.node.is_empty() || req.container.is_empty() {
+            return Err(Status::invalid_argument("node and container are required"));
+        }
+
+        let (entries, dropped) =
+            self.logs
+                .query(&req.node, &req.container, req.limit as usize, req.since);
+        Ok(Response::new(GetLogsResponse { entries, dropped }))
+    }
+}
+
+/// Convert per-prefix storage stats into the gRPC response
+fn to_storage_stats_response(
+    stats: std::collections::BTreeMap<String, crate::etcd_storage::PrefixStats>,
+) -> StorageStatsResponse {
+    let prefixes: Vec<PrefixStats> = stats
+        .into_iter()
+        .map(|(prefix, s)| PrefixStats {
+            prefix,
+            count: s.count,
+            total_bytes: s.total_bytes,
+            oldest: s.oldest,
+            newest: s.newest,
+        })
+        .collect();
+
+    StorageStatsResponse {
+        total_count: prefixes.iter().map(|p| p.count).sum(),
+        total_bytes: prefixes.iter().map(|p| p.total_bytes).sum(),
+        prefixes,
+    }
 }
 
 #[cfg(test)]
@@ -166,6 +247,7 @@ mod tests {
             write_bytes: 400,
             arch: "x86_64".to_string(),
             os: "linux".to_string(),
+            dropped_container_updates: 0,
         }
     }
 
@@ -202,6 +284,7 @@ mod tests {
             tx_container: tx,
             tx_node: dummy_tx_node,
             tx_stress: dummy_stress,
+            logs: Arc::new(LogStore::new(10)),
         };
         let req = Request::new(sample_container_list("node1"));
         let resp = receiver.send_container_list(req).await.unwrap();
@@ -222,6 +305,7 @@ mod tests {
             tx_container: tx,
             tx_node: dummy_tx,
             tx_stress: dummy_stress,
+            logs: Arc::new(LogStore::new(10)),
         };
         let req = Request::new(sample_container_list("node1"));
         let resp = receiver.send_container_list(req).await;
@@ -239,6 +323,7 @@ mod tests {
             tx_container: dummy_tx_container,
             tx_node: tx,
             tx_stress: dummy_stress,
+            logs: Arc::new(LogStore::new(10)),
         };
         let req = Request::new(sample_node("node1", "192.168.10.201"));
         let resp = receiver.send_node_info(req).await.unwrap();
@@ -259,6 +344,7 @@ mod tests {
             tx_container: dummy_tx,
             tx_node: tx,
             tx_stress: dummy_stress,
+            logs: Arc::new(LogStore::new(10)),
         };
         let req = Request::new(sample_node("node1", "192.168.10.201"));
         let resp = receiver.send_node_info(req).await;
@@ -276,6 +362,7 @@ mod tests {
             tx_container: dummy_tx_container,
             tx_node: dummy_tx_node,
             tx_stress: tx,
+            logs: Arc::new(LogStore::new(10)),
         };
         let req = Request::new(StressMonitoringMetric {
             json: sample_stress_json(),
@@ -311,6 +398,7 @@ mod tests {
             tx_container: tx_container.clone(),
             tx_node: tx_node.clone(),
             tx_stress: tx_stress.clone(),
+            logs: Arc::new(LogStore::new(10)),
         };
 
         // send the stress metric via gRPC handler (synchronous call)
@@ -353,4 +441,89 @@ mod tests {
         // give manager a moment to finish
         let _ = tokio::time::timeout(Duration::from_secs(1), mgr_handle).await;
     }
+
+    fn receiver_with_logs(logs: Arc<LogStore>) -> MonitoringServerReceiver {
+        MonitoringServerReceiver {
+            tx_container: mpsc::channel(1).0,
+            tx_node: mpsc::channel(1).0,
+            tx_stress: mpsc::channel(1).0,
+            logs,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_get_logs_returns_tail() {
+        let logs = Arc::new(LogStore::new(2));
+        for timestamp in 1..=3 {
+            logs.push(LogEntry {
+                node: "node1".to_string(),
+                container: "app".to_string(),
+                timestamp,
+                line: format!("line {}", timestamp),
+            });
+        }
+        let receiver = receiver_with_logs(logs);
+
+        let resp = receiver
+            .get_logs(Request::new(GetLogsRequest {
+                node: "node1".to_string(),
+                container: "app".to_string(),
+                limit: 0,
+                since: 0,
+            }))
+            .await
+            .unwrap()
+            .into_inner();
+
+        let lines: Vec<&str> = resp.entries.iter().map(|e| e.line.as_str()).collect();
+        assert_eq!(lines, vec!["line 2", "line 3"]);
+        assert_eq!(resp.dropped, 1);
+    }
+
+    #[tokio::test]
+    async fn test_get_logs_requires_container() {
+        let receiver = receiver_with_logs(Arc::new(LogStore::new(2)));
+
+        let status = receiver
+            .get_logs(Request::new(GetLogsRequest {
+                node: "node1".to_string(),
+                ..Default::default()
+            }))
+            .await
+            .unwrap_err();
+
+        assert_eq!(status.code(), Code::InvalidArgument);
+    }
+
+    #[test]
+    fn test_to_storage_stats_response_totals() {
+        let mut stats = std::collections::BTreeMap::new();
+        stats.insert(
+            "nodes".to_string(),
+            crate::etcd_storage::PrefixStats {
+                count: 2,
+                total_bytes: 100,
+                oldest: Some(10),
+                newest: Some(20),
+            },
+        );
+        stats.insert(
+            "containers".to_string(),
+            crate::etcd_storage::PrefixStats {
+                count: 3,
+                total_bytes: 50,
+                oldest: None,
+                newest: None,
+            },
+        );
+
+        let resp = to_storage_stats_response(stats);
+
+        assert_eq!(resp.total_count, 5);
+        assert_eq!(resp.total_bytes, 150);
+        assert_eq!(resp.prefixes.len(), 2);
+        assert_eq!(resp.prefixes[0].prefix, "containers");
+        assert_eq!(resp.prefixes[1].oldest, Some(10));
+        assert_eq!(resp.prefixes[1].newest, Some(20));
+    }
 }
@@ -0,0 +1,236 @@
+/*
+ * SPDX-FileCopyrightText: Copyright 2024 LG Electronics Inc.
+ * SPDX-License-Identifier: Apache-2.0
+ */
+
+//! Recent log lines of the containers, sent by the nodeagents
+//!
+//! Every container has a ring buffer of its last lines. A full buffer
+//! drops its oldest line for each new one, so ingestion never waits for a
+//! reader or for etcd. The last lines of each container can also be saved
+//! to etcd, to be served again after a restart.
+
+use common::keys;
+use common::logd;
+use common::monitoringserver::LogEntry;
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+/// Time between two saves of the changed buffers to etcd
+pub const PERSIST_INTERVAL: Duration = Duration::from_secs(10);
+
+/// Lines of one container
+#[derive(Debug, Default)]
+struct ContainerLog {
+    lines: VecDeque<LogEntry>,
+    /// Lines dropped because the buffer was full
+    dropped: u64,
+    /// Lines were added since the last save
+    changed: bool,
+}
+
+/// Ring buffers of the container logs, by node and container
+#[derive(Debug)]
+pub struct LogStore {
+    containers: Mutex<HashMap<(String, String), ContainerLog>>,
+    capacity: usize,
+}
+
+impl LogStore {
+    /// Store keeping `capacity` lines per container
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            containers: Mutex::new(HashMap::new()),
+            capacity: capacity.max(1),
+        }
+    }
+
+    /// Add a line, dropping the oldest line of the container when full
+    pub fn push(&self, entry: LogEntry) {
+        let mut containers = self.containers.lock().unwrap();
+        let log = containers
+            .entry((entry.node.clone(), entry.container.clone()))
+            .or_default();
+        if log.lines.len() >= self.capacity {
+            log.lines.pop_front();
+            log.dropped += 1;
+        }
+        log.lines.push_back(entry);
+        log.changed = true;
+    }
+
+    /// Most recent lines of a container
+    ///
+    /// # Arguments
+    ///
+    /// * `node` - Node of the container
+    /// * `container` - Name of the container
+    /// * `limit` - Largest number of lines, 0 for every kept line
+    /// * `since` - Only lines after this time, in unix nanoseconds
+    ///
+    /// # Returns
+    ///
+    /// * `(Vec<LogEntry>, u64)` - Lines oldest first, and the number of
+    ///   lines of the container dropped so far
+    pub fn query(
+        &self,
+        node: &str,
+        container: &str,
+        limit: usize,
+        since: i64,
+    ) -> (Vec<LogEntry>, u64) {
+        let containers = self.containers.lock().unwrap();
+        let Some(log) = containers.get(&(node.to_string(), container.to_string())) else {
+            return (Vec::new(), 0);
+        };
+        let limit = if limit == 0 { usize::MAX } else { limit };
+        let mut lines: Vec<LogEntry> = log
+            .lines
+            .iter()
+            .rev()
+            .filter(|entry| entry.timestamp > since)
+            .take(limit)
+            .cloned()
+            .collect();
+        lines.reverse();
+        (lines, log.dropped)
+    }
+
+    /// Last `lines` lines of each container changed since the last call
+    fn take_changed(&self, lines: usize) -> Vec<(String, String, Vec<LogEntry>)> {
+        let mut containers = self.containers.lock().unwrap();
+        containers
+            .iter_mut()
+            .filter(|(_, log)| log.changed)
+            .map(|((node, container), log)| {
+                log.changed = false;
+                let skip = log.lines.len().saturating_sub(lines);
+                let last = log.lines.iter().skip(skip).cloned().collect();
+                (node.clone(), container.clone(), last)
+            })
+            .collect()
+    }
+}
+
+/// Save the last `lines` lines of the changed containers to etcd every
+/// `interval`
+pub async fn persist_periodically(store: Arc<LogStore>, lines: usize, interval: Duration) {
+    let mut ticker = tokio::time::interval(interval);
+    loop {
+        ticker.tick().await;
+        for (node, container, entries) in store.take_changed(lines) {
+            let key = keys::container_logs(&node, &container);
+            let value = match serde_json::to_string(&entries) {
+                Ok(value) => value,
+                Err(e) => {
+                    logd!(
+                        4,
+                        "Failed to serialize logs of {}/{}: {}",
+                        node,
+                        container,
+                        e
+                    );
+                    continue;
+                }
+            };
+            if let Err(e) = common::etcd::put(&key, &value).await {
+                logd!(4, "Failed to save logs of {}/{}: {:?}", node, container, e);
+            }
+        }
+    }
+}
+
+/// Load the lines saved to etcd by `persist_periodically`
+///
+/// # Returns
+///
+/// * `usize` - Number of lines loaded
+pub async fn restore(store: &LogStore) -> common::Result<usize> {
+    let prefix = keys::logs_prefix("containers", "");
+    let mut count = 0;
+    for (key, value) in common::etcd::get_all_with_prefix(&prefix).await? {
+        match serde_json::from_str::<Vec<LogEntry>>(&value) {
+            Ok(entries) => {
+                count += entries.len();
+                entries.into_iter().for_each(|entry| store.push(entry));
+            }
+            Err(e) => logd!(4, "Skipping saved logs {}: {}", key, e),
+        }
+    }
+    // What was just loaded is already saved
+    store.take_changed(0);
+    Ok(count)
+}
+
+//Unit Test Cases
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(container: &str, timestamp: i64) -> LogEntry {
+        LogEntry {
+            node: "HPC".to_string(),
+            container: container.to_string(),
+            timestamp,
+            line: format!("line {}", timestamp),
+        }
+    }
+
+    fn timestamps(entries: &[LogEntry]) -> Vec<i64> {
+        entries.iter().map(|e| e.timestamp).collect()
+    }
+
+    #[test]
+    fn test_ring_buffer_wraps_around() {
+        let store = LogStore::new(3);
+        for timestamp in 1..=5 {
+            store.push(entry("app", timestamp));
+        }
+        store.push(entry("other", 1));
+
+        let (lines, dropped) = store.query("HPC", "app", 0, 0);
+        assert_eq!(timestamps(&lines), vec![3, 4, 5]);
+        assert_eq!(dropped, 2);
+
+        let (lines, dropped) = store.query("HPC", "other", 0, 0);
+        assert_eq!(timestamps(&lines), vec![1]);
+        assert_eq!(dropped, 0);
+        assert!(store.query("ZONE", "app", 0, 0).0.is_empty());
+    }
+
+    #[test]
+    fn test_query_since_and_limit() {
+        let store = LogStore::new(100);
+        for timestamp in 1..=10 {
+            store.push(entry("app", timestamp * 1000));
+        }
+
+        let (lines, _) = store.query("HPC", "app", 0, 7000);
+        assert_eq!(timestamps(&lines), vec![8000, 9000, 10000]);
+
+        let (lines, _) = store.query("HPC", "app", 2, 0);
+        assert_eq!(timestamps(&lines), vec![9000, 10000]);
+
+        let (lines, _) = store.query("HPC", "app", 5, 8500);
+        assert_eq!(timestamps(&lines), vec![9000, 10000]);
+
+        assert!(store.query("HPC", "app", 0, 10000).0.is_empty());
+    }
+
+    #[test]
+    fn test_take_changed_keeps_last_lines() {
+        let store = LogStore::new(10);
+        for timestamp in 1..=4 {
+            store.push(entry("app", timestamp));
+        }
+
+        let changed = store.take_changed(2);
+        assert_eq!(changed.len(), 1);
+        assert_eq!(timestamps(&changed[0].2), vec![3, 4]);
+        assert!(store.take_changed(2).is_empty());
+
+        store.push(entry("app", 5));
+        assert_eq!(timestamps(&store.take_changed(2)[0].2), vec![4, 5]);
+    }
+}
@@ -11,12 +11,17 @@ use common::monitoringserver::{ContainerList, NodeInfo};
 pub mod data_structures;
 pub mod etcd_storage;
 pub mod grpc;
+pub mod log_store;
 pub mod manager;
+pub mod restart_tracker;
 
+use common::channel::bounded;
 use common::logd;
 use common::logd::logger;
 use common::monitoringserver::monitoring_server_connection_server::MonitoringServerConnectionServer;
-use tokio::sync::mpsc::{channel, Receiver, Sender};
+use log_store::LogStore;
+use std::sync::Arc;
+use tokio::sync::mpsc::{Receiver, Sender};
 
 /// Launches the MonitoringServerManager in an asynchronous task.
 ///
@@ -45,17 +50,32 @@ async fn launch_manager(
 /// Initializes the MonitoringServer gRPC server.
 ///
 /// Sets up the gRPC service and starts listening for incoming requests.
+/// The `grpc.health.v1.Health` service reports `SERVING` while etcd is reachable
+/// and the manager channels are open, and `NOT_SERVING` once shutdown starts.
 async fn initialize(
     tx_container: Sender<ContainerList>,
     tx_node: Sender<NodeInfo>,
     tx_stress: Sender<String>,
+    logs: Arc<LogStore>,
 ) {
+    use common::health::{etcd_reachable, ServerHealth, PROBE_INTERVAL};
     use tonic::transport::Server;
 
+    let (health, health_service) = ServerHealth::new::<
+        MonitoringServerConnectionServer<grpc::receiver::MonitoringServerReceiver>,
+    >();
+    let probe_container = tx_container.clone();
+    let probe_node = tx_node.clone();
+    health.spawn_probe(PROBE_INTERVAL, move || {
+        let channels_open = !probe_container.is_closed() && !probe_node.is_closed();
+        async move { channels_open && etcd_reachable().await }
+    });
+
     let server = grpc::receiver::MonitoringServerReceiver {
         tx_container,
         tx_node,
         tx_stress,
+        logs,
     };
 
     let addr = common::monitoringserver::open_server()
@@ -64,8 +84,11 @@ async fn initialize(
     logd!(3, "MonitoringServer listening on {}", addr);
 
     if let Err(e) = Server::builder()
+        .layer(common::trace::TraceLayer)
+        .add_service(health_service)
+        .add_service(common::version::service("monitoringserver"))
         .add_service(MonitoringServerConnectionServer::new(server))
-        .serve(addr)
+        .serve_with_shutdown(addr, health.shutdown_signal())
         .await
     {
         logd!(5, "gRPC server error: {}", e);
@@ -74,19 +97,45 @@ async fn initialize(
 
 #[tokio::main]
 async fn main() {
+    common::logging::init("monitoringserver");
     let _ = logger::init_async_logger("monitoringserver").await;
     logd!(1, "initiailize monitoring server");
 
-    let (tx_container, rx_container) = channel::<ContainerList>(100);
-    let (tx_node, rx_node) = channel::<NodeInfo>(100);
+    let channels = &common::setting::get_config().channels;
+    let (tx_container, rx_container) = bounded::<ContainerList>(
+        "monitoringserver_container",
+        channels.monitoringserver_container,
+    );
+    let (tx_node, rx_node) =
+        bounded::<NodeInfo>("monitoringserver_node", channels.monitoringserver_node);
 
     // Add stress channel and a simple consumer
-    let (tx_stress, rx_stress) = channel::<String>(16);
+    let (tx_stress, rx_stress) =
+        bounded::<String>("monitoringserver_stress", channels.monitoringserver_stress);
+
+    // Recent container logs, kept in memory and optionally saved to etcd
+    let monitoring = &common::setting::get_config().monitoring;
+    let logs = Arc::new(LogStore::new(monitoring.log_buffer_lines));
+    match log_store::restore(&logs).await {
+        Ok(count) => logd!(2, "Restored {} container log lines", count),
+        Err(e) => logd!(4, "Failed to restore container logs: {:?}", e),
+    }
+    if monitoring.log_persist_lines > 0 {
+        tokio::spawn(log_store::persist_periodically(
+            logs.clone(),
+            monitoring.log_persist_lines,
+            log_store::PERSIST_INTERVAL,
+        ));
+    }
 
     let mgr = launch_manager(rx_container, rx_node, rx_stress);
-    let grpc = initialize(tx_container, tx_node, tx_stress);
+    let grpc = initialize(tx_container, tx_node, tx_stress, logs);
 
-    tokio::join!(mgr, grpc);
+    // Stop the process once the gRPC server has shut down
+    tokio::select! {
+        _ = mgr => {}
+        _ = grpc => {}
+    }
 }
 
 #[cfg(test)]
@@ -109,10 +158,15 @@ mod tests {
         let (tx_c, _rx_c) = tokio::sync::mpsc::channel(1);
         let (tx_n, _rx_n) = tokio::sync::mpsc::channel(1);
         let (tx_s, _rx_s) = tokio::sync::mpsc::channel::<String>(1);
+        let logs = Arc::new(LogStore::new(10));
         // Spawn initialize in a background task and cancel after a short delay
         let handle = tokio::spawn(async move {
             // Use a short timeout to avoid hanging on .serve()
-            let _ = timeout(Duration::from_millis(500), initialize(tx_c, tx_n, tx_s)).await;
+            let _ = timeout(
+                Duration::from_millis(500),
+                initialize(tx_c, tx_n, tx_s, logs),
+            )
+            .await;
         });
 
         // Wait for the task to finish or timeout
@@ -8,11 +8,14 @@
 //! a gRPC sender for communicating with the nodeagent or other services.
 //! It is designed to be thread-safe and run in an async context.
 use crate::data_structures::{BoardInfo, DataStore, SocInfo};
+use crate::restart_tracker::{self, RestartEvent, RestartTracker};
+use common::logd;
 use common::monitoringserver::{ContainerList, NodeInfo}; // Use protobuf types
 use common::Result;
 use std::str::FromStr;
 use std::sync::Arc;
 use tokio::sync::{mpsc, Mutex};
+use tracing::{debug, error, info, warn};
 
 /// Main manager struct for MonitoringServer.
 ///
@@ -26,6 +29,8 @@ pub struct MonitoringServerManager {
     rx_stress: Arc<Mutex<mpsc::Receiver<String>>>,
     /// Data store for managing NodeInfo, SocInfo, and BoardInfo
     data_store: Arc<Mutex<DataStore>>,
+    /// Restart counts and flapping state of containers
+    restarts: Arc<Mutex<RestartTracker>>,
 }
 
 impl MonitoringServerManager {
@@ -40,19 +45,23 @@ impl MonitoringServerManager {
             rx_node: Arc::new(Mutex::new(rx_node)),
             rx_stress: Arc::new(Mutex::new(rx_stress)),
             data_store: Arc::new(Mutex::new(DataStore::new())),
+            restarts: Arc::new(Mutex::new(RestartTracker::from_settings())),
         }
     }
 
     /// Initializes the MonitoringServerManager (e.g., loads scenarios, prepares state).
     pub async fn initialize(&mut self) -> Result<()> {
-        println!("MonitoringServerManager init");
+        info!("MonitoringServerManager init");
 
         // Clear stale container data from previous runs
         if let Err(e) = crate::etcd_storage::delete_all_containers().await {
-            eprintln!(
-                "[MonitoringServerManager] Warning: Failed to clear containers: {}",
-                e
-            );
+            warn!("Failed to clear containers: {}", e);
+        }
+
+        // Restart counts are kept across MonitoringServer restarts
+        match crate::etcd_storage::get_all_restart_records().await {
+            Ok(records) => self.restarts.lock().await.load(records),
+            Err(e) => warn!("Failed to load restart records: {}", e),
         }
 
         Ok(())
@@ -61,13 +70,15 @@ impl MonitoringServerManager {
     /// Processes ContainerList messages from nodeagent.
     ///
     /// This function handles the received ContainerList and processes it accordingly.
-    async fn handle_container_list(&self, container_list: ContainerList) {
-        println!(
-            "[MonitoringServer] Received ContainerList from {}: containers count={}",
+    async fn handle_container_list(&self, mut container_list: ContainerList) {
+        debug!(
+            "Received ContainerList from {}: containers count={}",
             container_list.node_name,
             container_list.containers.len()
         );
 
+        self.track_restarts(&mut container_list).await;
+
         let current_container_ids: Vec<String> = container_list
             .containers
             .iter()
@@ -88,32 +99,76 @@ impl MonitoringServerManager {
                 .await
             {
                 Ok(_) => {
-                    println!(
-                        "[MonitoringServer] SUCCESS: Stored container {} on node {}",
+                    debug!(
+                        "Stored container {} on node {}",
                         container.id, container_list.node_name
                     );
                 }
-                Err(e) => eprintln!(
-                    "[MonitoringServer] ERROR: Failed to store container {}: {}",
-                    container.id, e
-                ),
+                Err(e) => error!("Failed to store container {}: {}", container.id, e),
             }
         }
 
         self.print_container_summary(&container_list).await;
     }
 
+    /// Update the restart counts of the containers of a node and copy them
+    /// into each container's state
+    ///
+    /// A container that starts flapping raises a warning.
+    async fn track_restarts(&self, container_list: &mut ContainerList) {
+        let now = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_secs() as i64)
+            .unwrap_or(0);
+        let node = &container_list.node_name;
+
+        let mut tracker = self.restarts.lock().await;
+        let (updates, gone) = tracker.observe(node, &container_list.containers, now);
+
+        for update in &updates {
+            let record = &update.record;
+            match update.event {
+                RestartEvent::Flapping => logd!(
+                    4,
+                    "Container {} on node {} is flapping: {} restarts in the last window",
+                    record.container,
+                    record.node,
+                    record.recent_restarts.len()
+                ),
+                RestartEvent::Recovered => logd!(
+                    3,
+                    "Container {} on node {} stopped flapping",
+                    record.container,
+                    record.node
+                ),
+                RestartEvent::Restarted | RestartEvent::Tracked => {}
+            }
+            if let Err(e) = crate::etcd_storage::store_restart_record(record).await {
+                error!("Failed to store restarts of {}: {}", record.key(), e);
+            }
+        }
+        for key in &gone {
+            if let Err(e) = crate::etcd_storage::delete_restart_record(key).await {
+                error!("Failed to delete restarts of {}: {}", key, e);
+            }
+        }
+
+        for container in &mut container_list.containers {
+            if let Some(record) = tracker.get(node, &restart_tracker::container_name(container)) {
+                restart_tracker::annotate(container, record);
+            }
+        }
+    }
+
     /// Print container summary for a node (line-wise, formatted)
     async fn print_container_summary(&self, container_list: &ContainerList) {
-        println!(
-            "\n┌───────────────────────────── CONTAINER SUMMARY ─────────────────────────────┐"
-        );
-        println!("│ Node: {:<69} │", container_list.node_name);
-        println!(
+        debug!("\n┌───────────────────────────── CONTAINER SUMMARY ─────────────────────────────┐");
+        debug!("│ Node: {:<69} │", container_list.node_name);
+        debug!(
             "│ Total Containers: {:<59} │",
             container_list.containers.len()
         );
-        println!("├─────────────────────────────────────────────────────────────────────────────┤");
+        debug!("├─────────────────────────────────────────────────────────────────────────────┤");
         for (i, container) in container_list.containers.iter().enumerate() {
             let name = container
                 .names
@@ -131,7 +186,7 @@ impl MonitoringServerManager {
                 "paused" => "🟡",
                 _ => "⚪",
             };
-            println!(
+            debug!(
                 "│ {:>2}. {} Name: {:<20} │ Image: {:<20} │ Status: {:<10} │",
                 i + 1,
                 status_icon,
@@ -140,7 +195,7 @@ impl MonitoringServerManager {
                 status
             );
         }
-        println!("└─────────────────────────────────────────────────────────────────────────────┘");
+        debug!("└─────────────────────────────────────────────────────────────────────────────┘");
     }
 
     /// Print comprehensive container overview (line-wise, formatted)
@@ -153,15 +208,13 @@ impl MonitoringServerManager {
             .count();
         let stopped_count = containers.len() - running_count;
 
-        println!(
-            "\n┌───────────────────────── SYSTEM CONTAINER OVERVIEW ─────────────────────────┐"
-        );
-        println!("│ Total Containers: {:<59} │", containers.len());
-        println!(
+        debug!("\n┌───────────────────────── SYSTEM CONTAINER OVERVIEW ─────────────────────────┐");
+        debug!("│ Total Containers: {:<59} │", containers.len());
+        debug!(
             "│ Running: {:<8} │ Stopped: {:<8} │",
             running_count, stopped_count
         );
-        println!("├─────────────────────────────────────────────────────────────────────────────┤");
+        debug!("├─────────────────────────────────────────────────────────────────────────────┤");
         for (i, container) in containers.values().enumerate() {
             let name = container
                 .names
@@ -173,7 +226,7 @@ impl MonitoringServerManager {
                 .get("Status")
                 .cloned()
                 .unwrap_or_else(|| "unknown".to_string());
-            println!(
+            debug!(
                 "│ {:>2}. Name: {:<20} │ Image: {:<20} │ Status: {:<10} │",
                 i + 1,
                 name,
@@ -181,29 +234,25 @@ impl MonitoringServerManager {
                 status
             );
         }
-        println!("└─────────────────────────────────────────────────────────────────────────────┘");
+        debug!("└─────────────────────────────────────────────────────────────────────────────┘");
     }
 
     /// Print all nodes (line-wise, formatted)
     pub async fn print_all_nodes(&self) {
         let data_store = self.data_store.lock().await;
-        println!(
-            "\n┌────────────────────────────────── ALL NODES ───────────────────────────────┐"
-        );
+        debug!("\n┌────────────────────────────────── ALL NODES ───────────────────────────────┐");
         for (i, (_, node)) in data_store.get_all_nodes().iter().enumerate() {
-            println!("│ {:>2}. Node: {:<20} │ IP: {:<15} │ CPU: {:>5.2}% │ Mem: {:>5.2}% │ Containers: {:<3} │",
+            debug!("│ {:>2}. Node: {:<20} │ IP: {:<15} │ CPU: {:>5.2}% │ Mem: {:>5.2}% │ Containers: {:<3} │",
                 i + 1, node.node_name, node.ip, node.cpu_usage, node.mem_usage,
                 data_store.get_containers_by_node(&node.node_name).len());
         }
-        println!("└─────────────────────────────────────────────────────────────────────────────┘");
+        debug!("└─────────────────────────────────────────────────────────────────────────────┘");
     }
 
     /// Print all containers (line-wise, formatted)
     pub async fn print_all_containers(&self) {
         let data_store = self.data_store.lock().await;
-        println!(
-            "\n┌──────────────────────────────── ALL CONTAINERS ─────────────────────────────┐"
-        );
+        debug!("\n┌──────────────────────────────── ALL CONTAINERS ─────────────────────────────┐");
         for (i, (_, container)) in data_store.get_all_containers().iter().enumerate() {
             let name = container
                 .names
@@ -215,7 +264,7 @@ impl MonitoringServerManager {
                 .get("Status")
                 .cloned()
                 .unwrap_or_else(|| "unknown".to_string());
-            println!(
+            debug!(
                 "│ {:>2}. Name: {:<20} │ ID: {:<12} │ Image: {:<20} │ Status: {:<10} │",
                 i + 1,
                 name,
@@ -224,30 +273,26 @@ impl MonitoringServerManager {
                 status
             );
         }
-        println!("└─────────────────────────────────────────────────────────────────────────────┘");
+        debug!("└─────────────────────────────────────────────────────────────────────────────┘");
     }
 
     /// Print all boards (line-wise, formatted)
     pub async fn print_all_boards(&self) {
         let data_store = self.data_store.lock().await;
-        println!(
-            "\n┌────────────────────────────────── ALL BOARDS ───────────────────────────────┐"
-        );
+        debug!("\n┌────────────────────────────────── ALL BOARDS ───────────────────────────────┐");
         for (i, (_, board)) in data_store.get_all_boards().iter().enumerate() {
-            println!("│ {:>2}. Board: {:<20} │ Nodes: {:<3} │ SoCs: {:<3} │ CPU: {:>5.2}% │ Mem: {:>5.2}% │",
+            debug!("│ {:>2}. Board: {:<20} │ Nodes: {:<3} │ SoCs: {:<3} │ CPU: {:>5.2}% │ Mem: {:>5.2}% │",
                 i + 1, board.board_id, board.nodes.len(), board.socs.len(), board.total_cpu_usage, board.total_mem_usage);
         }
-        println!("└─────────────────────────────────────────────────────────────────────────────┘");
+        debug!("└─────────────────────────────────────────────────────────────────────────────┘");
     }
 
     /// Print all SoCs (line-wise, formatted)
     pub async fn print_all_socs(&self) {
         let data_store = self.data_store.lock().await;
-        println!(
-            "\n┌────────────────────────────────── ALL SOCs ────────────────────────────────┐"
-        );
+        debug!("\n┌────────────────────────────────── ALL SOCs ────────────────────────────────┐");
         for (i, (_, soc)) in data_store.get_all_socs().iter().enumerate() {
-            println!(
+            debug!(
                 "│ {:>2}. SoC: {:<20} │ Nodes: {:<3} │ CPU: {:>5.2}% │ Mem: {:>5.2}% │",
                 i + 1,
                 soc.soc_id,
@@ -256,7 +301,7 @@ impl MonitoringServerManager {
                 soc.total_mem_usage
             );
         }
-        println!("└─────────────────────────────────────────────────────────────────────────────┘");
+        debug!("└─────────────────────────────────────────────────────────────────────────────┘");
     }
 
     /// Processes NodeInfo messages from nodeagent.
@@ -271,10 +316,7 @@ impl MonitoringServerManager {
             let mut data_store = self.data_store.lock().await;
             match data_store.store_node_info(node_info.clone()).await {
                 Ok(_) => {
-                    println!(
-                        "[MonitoringServer] SUCCESS: Successfully stored NodeInfo for {}",
-                        node_info.node_name
-                    );
+                    debug!("Successfully stored NodeInfo for {}", node_info.node_name);
 
                     // Print ID generation details
                     self.print_id_generation_details(&node_info.ip);
@@ -289,26 +331,26 @@ impl MonitoringServerManager {
                     self.print_summary_stats(&data_store).await;
                 }
                 Err(e) => {
-                    eprintln!("[MonitoringServer] ERROR: Error storing NodeInfo: {}", e);
+                    error!("Error storing NodeInfo: {}", e);
                 }
             }
         }
 
-        println!("{}", "=".repeat(80));
+        debug!("{}", "=".repeat(80));
     }
 
     /// Print ID generation details for debugging
     fn print_id_generation_details(&self, ip: &str) {
-        println!("\n ID GENERATION DEBUG");
-        println!("┌─────────────────────────────────────────────────────────────────────────────┐");
-        println!("│ Input IP: {:<65} │", ip);
+        debug!("\n ID GENERATION DEBUG");
+        debug!("┌─────────────────────────────────────────────────────────────────────────────┐");
+        debug!("│ Input IP: {:<65} │", ip);
 
         if let Ok(soc_id) = DataStore::generate_soc_id(ip) {
-            println!("│ Generated SoC ID: {:<57} │", soc_id);
+            debug!("│ Generated SoC ID: {:<57} │", soc_id);
         }
 
         if let Ok(board_id) = DataStore::generate_board_id(ip) {
-            println!("│ Generated Board ID: {:<55} │", board_id);
+            debug!("│ Generated Board ID: {:<55} │", board_id);
         }
 
         // Show the logic
@@ -318,21 +360,21 @@ impl MonitoringServerManager {
             let soc_group = (last_octet / 10) * 10;
             let board_group = (last_octet / 100) * 100;
 
-            println!(
+            debug!(
                 "│ Last Octet: {:<3} → SoC Group: {:<3} → Board Group: {:<8}                    │",
                 last_octet, soc_group, board_group
             );
         }
-        println!("└─────────────────────────────────────────────────────────────────────────────┘");
+        debug!("└─────────────────────────────────────────────────────────────────────────────┘");
     }
 
     /// Print detailed SoC mapping for all current data
     async fn print_detailed_soc_mapping(&self, data_store: &DataStore) {
-        println!("\n DETAILED SOC MAPPING");
-        println!("┌─────────────────────────────────────────────────────────────────────────────┐");
+        debug!("\n DETAILED SOC MAPPING");
+        debug!("┌─────────────────────────────────────────────────────────────────────────────┐");
 
         for (soc_id, soc_info) in data_store.get_all_socs() {
-            println!(
+            debug!(
                 "│ SoC: {:<20} │ Nodes: {:<2} │ Nodes List: {:<24}│",
                 soc_id,
                 soc_info.nodes.len(),
@@ -345,10 +387,10 @@ impl MonitoringServerManager {
             );
         }
 
-        println!("├─────────────────────────────────────────────────────────────────────────────┤");
+        debug!("├─────────────────────────────────────────────────────────────────────────────┤");
 
         for (board_id, board_info) in data_store.get_all_boards() {
-            println!(
+            debug!(
                 "│ Board: {:<18} │ Nodes: {:<2} │ SoCs: {:<2} │ SoC List: {:<14} │",
                 board_id,
                 board_info.nodes.len(),
@@ -361,15 +403,15 @@ impl MonitoringServerManager {
                     .join(", ")
             );
         }
-        println!("└─────────────────────────────────────────────────────────────────────────────┘");
+        debug!("└─────────────────────────────────────────────────────────────────────────────┘");
     }
 
     /// Enhanced Board info printing with SoC details
     fn print_board_info(&self, board_info: &BoardInfo) {
-        println!("\nBOARD INFORMATION");
-        println!("┌─────────────────────────────────────────────────────────────────────────────┐");
-        println!("│ Board ID: {:<65} │", board_info.board_id);
-        println!(
+        debug!("\nBOARD INFORMATION");
+        debug!("┌─────────────────────────────────────────────────────────────────────────────┐");
+        debug!("│ Board ID: {:<65} │", board_info.board_id);
+        debug!(
             "│ Nodes Count: {:<6} │ SoCs Count: {:<6} │ Updated: {:<19}     │",
             board_info.nodes.len(),
             board_info.socs.len(),
@@ -378,14 +420,14 @@ impl MonitoringServerManager {
 
         // Show SoCs in this board
         if !board_info.socs.is_empty() {
-            println!(
+            debug!(
                 "├─────────────────────────────────────────────────────────────────────────────┤"
             );
-            println!(
+            debug!(
                 "│ SoCs in this Board:                                                         │"
             );
             for (i, soc) in board_info.socs.iter().enumerate() {
-                println!(
+                debug!(
                     "│  {}. SoC: {:<25} │ Nodes: {:<2} │ Avg CPU: {:<6.2}%           │",
                     i + 1,
                     soc.soc_id,
@@ -395,24 +437,24 @@ impl MonitoringServerManager {
             }
         }
 
-        println!("├─────────────────────────────────────────────────────────────────────────────┤");
-        println!("│ Board-wide Aggregated Metrics:                                              │");
-        println!(
+        debug!("├─────────────────────────────────────────────────────────────────────────────┤");
+        debug!("│ Board-wide Aggregated Metrics:                                              │");
+        debug!(
             "│   CPU: {:<7.2}% │ Total Cores: {:<5} │ GPU Units: {:<3} │ Efficiency: {:<4}    │",
             board_info.total_cpu_usage,
             board_info.total_cpu_count,
             board_info.total_gpu_count,
             self.calculate_efficiency(board_info.total_cpu_usage)
         );
-        println!(
+        debug!(
             "│   Memory: {:<4.2}% │ Used: {:<9} │ Total: {:<9} │ Free: {:<9} │",
             board_info.total_mem_usage,
             self.format_memory(board_info.total_used_memory),
             self.format_memory(board_info.total_memory),
             self.format_memory(board_info.total_memory - board_info.total_used_memory)
         );
-        println!("├─────────────────────────────────────────────────────────────────────────────┤");
-        println!("│ Nodes on this Board (grouped by SoC):                                       │");
+        debug!("├─────────────────────────────────────────────────────────────────────────────┤");
+        debug!("│ Nodes on this Board (grouped by SoC):                                       │");
         for (i, node) in board_info.nodes.iter().enumerate() {
             let status = if node.cpu_usage > 80.0 {
                 "HIGH"
@@ -423,7 +465,7 @@ impl MonitoringServerManager {
             };
             // Show which SoC this node belongs to
             let soc_id = DataStore::generate_soc_id(&node.ip).unwrap_or_default();
-            println!(
+            debug!(
                 "│  {}. {:<25} │ SoC: {:<15} │ CPU: {:<6.2}% {} │",
                 i + 1,
                 node.node_name,
@@ -432,41 +474,41 @@ impl MonitoringServerManager {
                 status
             );
         }
-        println!("└─────────────────────────────────────────────────────────────────────────────┘");
+        debug!("└─────────────────────────────────────────────────────────────────────────────┘");
     }
 
     /// Prints detailed NodeInfo in a formatted way
     fn print_node_info(&self, node_info: &NodeInfo) {
-        println!("\nNODE INFORMATION");
-        println!("┌─────────────────────────────────────────────────────────────────────────────┐");
-        println!("│ Node: {:<69} │", node_info.node_name);
-        println!("│ IP Address: {:<63} │", node_info.ip);
-        println!("├─────────────────────────────────────────────────────────────────────────────┤");
-        println!(
+        debug!("\nNODE INFORMATION");
+        debug!("┌─────────────────────────────────────────────────────────────────────────────┐");
+        debug!("│ Node: {:<69} │", node_info.node_name);
+        debug!("│ IP Address: {:<63} │", node_info.ip);
+        debug!("├─────────────────────────────────────────────────────────────────────────────┤");
+        debug!(
             "│ CPU Usage: {:<6.2}% │ Cores: {:<3} │ GPU Units: {:<3} │ OS: {:<4} │",
             node_info.cpu_usage, node_info.cpu_count, node_info.gpu_count, node_info.os
         );
-        println!(
+        debug!(
             "│ Memory: {:<7.2}% │ Used: {:<8} KB │ Total: {:<8} KB │ Arch: {:<6} │",
             node_info.mem_usage,
             self.format_memory(node_info.used_memory),
             self.format_memory(node_info.total_memory),
             node_info.arch
         );
-        println!("├─────────────────────────────────────────────────────────────────────────────┤");
-        println!(
+        debug!("├─────────────────────────────────────────────────────────────────────────────┤");
+        debug!(
             "│ Network - RX: {:<15} │ TX: {:<15} │ Total: {:<14} │",
             self.format_bytes(node_info.rx_bytes),
             self.format_bytes(node_info.tx_bytes),
             self.format_bytes(node_info.rx_bytes + node_info.tx_bytes)
         );
-        println!(
+        debug!(
             "│ Disk I/O - Read: {:<12} │ Write: {:<12} │ Total: {:<14} │",
             self.format_bytes(node_info.read_bytes),
             self.format_bytes(node_info.write_bytes),
             self.format_bytes(node_info.read_bytes + node_info.write_bytes)
         );
-        println!("└─────────────────────────────────────────────────────────────────────────────┘");
+        debug!("└─────────────────────────────────────────────────────────────────────────────┘");
     }
 
     /// Prints aggregated SoC and Board information
@@ -488,44 +530,44 @@ impl MonitoringServerManager {
 
     /// Prints detailed SoC information
     fn print_soc_info(&self, soc_info: &SocInfo) {
-        println!("\n SOC INFORMATION");
-        println!("┌─────────────────────────────────────────────────────────────────────────────┐");
-        println!("│ SoC ID: {:<67} │", soc_info.soc_id);
-        println!("│ Nodes Count: {:<62} │", soc_info.nodes.len());
-        println!("├─────────────────────────────────────────────────────────────────────────────┤");
-        println!("│ Aggregated Metrics:                                                         │");
-        println!(
+        debug!("\n SOC INFORMATION");
+        debug!("┌─────────────────────────────────────────────────────────────────────────────┐");
+        debug!("│ SoC ID: {:<67} │", soc_info.soc_id);
+        debug!("│ Nodes Count: {:<62} │", soc_info.nodes.len());
+        debug!("├─────────────────────────────────────────────────────────────────────────────┤");
+        debug!("│ Aggregated Metrics:                                                         │");
+        debug!(
             "│   CPU: {:<7.2}%    │ Total Cores: {:<8}  │ GPU Units: {:<8}  │ Updated: {:<8} │",
             soc_info.total_cpu_usage,
             soc_info.total_cpu_count,
             soc_info.total_gpu_count,
             self.format_time_ago(&soc_info.last_updated)
         );
-        println!(
+        debug!(
             "│   Memory: {:<4.2}%   │ Used: {:<11}      │ Total: {:<11}   │ Free: {:<8}  │",
             soc_info.total_mem_usage,
             self.format_memory(soc_info.total_used_memory),
             self.format_memory(soc_info.total_memory),
             self.format_memory(soc_info.total_memory - soc_info.total_used_memory)
         );
-        println!(
+        debug!(
             "│   Network: RX {:<12} │ TX {:<12}         │ Total {:<12} │",
             self.format_bytes(soc_info.total_rx_bytes),
             self.format_bytes(soc_info.total_tx_bytes),
             self.format_bytes(soc_info.total_rx_bytes + soc_info.total_tx_bytes)
         );
-        println!(
+        debug!(
             "│   Disk I/O: Read {:<9} │ Write {:<9}         │ Total {:<9}    │",
             self.format_bytes(soc_info.total_read_bytes),
             self.format_bytes(soc_info.total_write_bytes),
             self.format_bytes(soc_info.total_read_bytes + soc_info.total_write_bytes)
         );
-        println!("├─────────────────────────────────────────────────────────────────────────────┤");
-        println!("│ Nodes in this SoC:                                                          │");
+        debug!("├─────────────────────────────────────────────────────────────────────────────┤");
+        debug!("│ Nodes in this SoC:                                                          │");
         for (i, node) in soc_info.nodes.iter().enumerate() {
-            println!("│  {}. {:<71} │", i + 1, node.node_name);
+            debug!("│  {}. {:<71} │", i + 1, node.node_name);
         }
-        println!("└─────────────────────────────────────────────────────────────────────────────┘");
+        debug!("└─────────────────────────────────────────────────────────────────────────────┘");
     }
 
     /// Prints summary statistics
@@ -534,9 +576,9 @@ impl MonitoringServerManager {
         let total_socs = data_store.get_all_socs().len();
         let total_boards = data_store.get_all_boards().len();
 
-        println!("\n SYSTEM SUMMARY");
-        println!("┌─────────────────────────────────────────────────────────────────────────────┐");
-        println!(
+        debug!("\n SYSTEM SUMMARY");
+        debug!("┌─────────────────────────────────────────────────────────────────────────────┐");
+        debug!(
             "│ Total Nodes: {:<8} │ Total SoCs: {:<8} │ Total Boards: {:<8} │ Status: ✅ │",
             total_nodes, total_socs, total_boards
         );
@@ -545,9 +587,9 @@ impl MonitoringServerManager {
         let (avg_cpu, avg_mem, total_cores, total_gpus) =
             self.calculate_system_averages(data_store);
 
-        println!("│ System Avg CPU: {:<6.2}% │ Avg Memory: {:<6.2}% │ Total Cores: {:<6} │ GPUs: {:<4} │", 
+        debug!("│ System Avg CPU: {:<6.2}% │ Avg Memory: {:<6.2}% │ Total Cores: {:<6} │ GPUs: {:<4} │", 
                  avg_cpu, avg_mem, total_cores, total_gpus);
-        println!("└─────────────────────────────────────────────────────────────────────────────┘");
+        debug!("└─────────────────────────────────────────────────────────────────────────────┘");
     }
 
     /// Helper function to format bytes in human-readable format
@@ -635,14 +677,14 @@ impl MonitoringServerManager {
     pub async fn print_all_data(&self) {
         let data_store = self.data_store.lock().await;
 
-        println!("\n COMPLETE SYSTEM OVERVIEW");
-        println!("{}", "=".repeat(80));
+        debug!("\n COMPLETE SYSTEM OVERVIEW");
+        debug!("{}", "=".repeat(80));
 
         // Print all nodes
-        println!("\n ALL NODES:");
+        debug!("\n ALL NODES:");
         for (i, (_, node)) in data_store.get_all_nodes().iter().enumerate() {
             let node_containers = data_store.get_containers_by_node(&node.node_name);
-            println!(
+            debug!(
                 "{}. {} (IP: {}) - CPU: {:.2}%, Memory: {:.2}%, Containers: {}",
                 i + 1,
                 node.node_name,
@@ -654,9 +696,9 @@ impl MonitoringServerManager {
         }
 
         // Print all SoCs
-        println!("\n ALL SOCs:");
+        debug!("\n ALL SOCs:");
         for (i, (_, soc)) in data_store.get_all_socs().iter().enumerate() {
-            println!(
+            debug!(
                 "{}. {} - {} nodes, Avg CPU: {:.2}%, Avg Memory: {:.2}%",
                 i + 1,
                 soc.soc_id,
@@ -667,9 +709,9 @@ impl MonitoringServerManager {
         }
 
         // Print all Boards
-        println!("\n ALL BOARDS:");
+        debug!("\n ALL BOARDS:");
         for (i, (_, board)) in data_store.get_all_boards().iter().enumerate() {
-            println!(
+            debug!(
                 "{}. {} - {} nodes, {} SoCs, Avg CPU: {:.2}%, Avg Memory: {:.2}%",
                 i + 1,
                 board.board_id,
@@ -681,7 +723,7 @@ impl MonitoringServerManager {
         }
 
         // Print all containers
-        println!("\n ALL CONTAINERS:");
+        debug!("\n ALL CONTAINERS:");
         for (i, (_, container)) in data_store.get_all_containers().iter().enumerate() {
             let name = container
                 .names
@@ -694,7 +736,7 @@ impl MonitoringServerManager {
                 .unwrap_or(&"unknown".to_string())
                 .clone();
 
-            println!(
+            debug!(
                 "{}. {} (ID: {}) - Image: {}, Status: {}",
                 i + 1,
                 name,
@@ -768,22 +810,16 @@ impl MonitoringServerManager {
                         // Persist raw JSON into etcd (uses existing helper)
                         match crate::etcd_storage::store_stress_metric_json(&json).await {
                             Ok(_) => {
-                                println!(
-                                    "[MonitoringServer] SUCCESS: Stored stress metric for process={} pid={}",
-                                    pname, pid
-                                );
+                                debug!("Stored stress metric for process={} pid={}", pname, pid);
                             }
                             Err(e) => {
-                                eprintln!(
-                                    "[MonitoringServer] ERROR: Failed to store stress metric to etcd: {}",
-                                    e
-                                );
+                                error!("Failed to store stress metric to etcd: {}", e);
                             }
                         }
                     }
                     Err(e) => {
-                        eprintln!(
-                            "[MonitoringServer] ERROR: received invalid stress metric JSON: {} -- payload: {}",
+                        warn!(
+                            "received invalid stress metric JSON: {} -- payload: {}",
                             e, json
                         );
                     }
@@ -805,7 +841,7 @@ impl MonitoringServerManager {
         let container_manager = Arc::clone(&arc_self);
         let container_processor = tokio::spawn(async move {
             if let Err(e) = container_manager.process_container_requests().await {
-                eprintln!("Container processor error: {:?}", e);
+                error!("Container processor error: {:?}", e);
             }
         });
 
@@ -813,7 +849,7 @@ impl MonitoringServerManager {
         let node_manager = Arc::clone(&arc_self);
         let node_processor = tokio::spawn(async move {
             if let Err(e) = node_manager.process_node_info_requests().await {
-                eprintln!("Node processor error: {:?}", e);
+                error!("Node processor error: {:?}", e);
             }
         });
 
@@ -821,12 +857,12 @@ impl MonitoringServerManager {
         let stress_manager = Arc::clone(&arc_self);
         let stress_processor = tokio::spawn(async move {
             if let Err(e) = stress_manager.process_stress_requests().await {
-                eprintln!("Stress processor error: {:?}", e);
+                error!("Stress processor error: {:?}", e);
             }
         });
 
         let _ = tokio::try_join!(container_processor, node_processor, stress_processor);
-        println!("MonitoringServerManager stopped");
+        info!("MonitoringServerManager stopped");
         Ok(())
     }
 }
@@ -863,6 +899,7 @@ mod tests {
             write_bytes: 400,
             arch: "x86_64".to_string(),
             os: "linux".to_string(),
+            dropped_container_updates: 0,
         }
     }
 
@@ -903,6 +940,23 @@ mod tests {
         // No assertion: just ensure no panic and output is printed
     }
 
+    #[tokio::test]
+    async fn test_track_restarts_annotates_state() {
+        let mgr = new_mgr().await;
+
+        let mut first =
+            sample_container_list("node1", vec![sample_container("c1", "cont1", "running")]);
+        mgr.track_restarts(&mut first).await;
+        assert_eq!(first.containers[0].state["RestartCount"], "0");
+
+        // Recreated with a new id
+        let mut second =
+            sample_container_list("node1", vec![sample_container("c2", "cont1", "running")]);
+        mgr.track_restarts(&mut second).await;
+        assert_eq!(second.containers[0].state["RestartCount"], "1");
+        assert_eq!(second.containers[0].state["Flapping"], "false");
+    }
+
     #[tokio::test]
     async fn test_print_container_overview() {
         let mgr = new_mgr().await;
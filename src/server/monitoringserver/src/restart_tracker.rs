@@ -0,0 +1,374 @@
+/*
+ * SPDX-FileCopyrightText: Copyright 2024 LG Electronics Inc.
+ * SPDX-License-Identifier: Apache-2.0
+ */
+
+//! Container restart counting and flapping detection
+//!
+//! A container is identified by its node and name across ContainerList
+//! snapshots. It restarted when a later snapshot shows it with another id
+//! (recreated) or another `StartedAt` (restarted in place). A container
+//! restarting more than a threshold number of times within a sliding
+//! window is flapping.
+
+use common::monitoringserver::ContainerInfo;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// State key carrying the restart count of a container
+pub const STATE_RESTART_COUNT: &str = "RestartCount";
+/// State key carrying whether a container is flapping
+pub const STATE_FLAPPING: &str = "Flapping";
+
+/// Restart history of one container, stored in etcd
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct RestartRecord {
+    pub node: String,
+    pub container: String,
+    pub container_id: String,
+    pub started_at: String,
+    /// Restarts since the container was first seen
+    pub restart_count: u64,
+    /// Unix seconds of the restarts within the window
+    pub recent_restarts: Vec<i64>,
+    pub flapping: bool,
+    /// Unix seconds of the last snapshot listing the container
+    pub last_seen: i64,
+}
+
+impl RestartRecord {
+    /// Identifier of the record, unique across nodes
+    pub fn key(&self) -> String {
+        record_key(&self.node, &self.container)
+    }
+}
+
+fn record_key(node: &str, container: &str) -> String {
+    format!("{}/{}", node, container)
+}
+
+/// What a snapshot changed for one container
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum RestartEvent {
+    /// Seen for the first time
+    Tracked,
+    /// Restarted, below the flapping threshold
+    Restarted,
+    /// Crossed the flapping threshold
+    Flapping,
+    /// No longer restarting often enough to be flapping
+    Recovered,
+}
+
+/// Change to a record, to be persisted
+#[derive(Debug, Clone, PartialEq)]
+pub struct RestartUpdate {
+    pub event: RestartEvent,
+    pub record: RestartRecord,
+}
+
+/// Restart records of every container, updated from ContainerList snapshots
+pub struct RestartTracker {
+    records: HashMap<String, RestartRecord>,
+    /// Number of restarts within the window above which a container is flapping
+    threshold: usize,
+    window_secs: i64,
+}
+
+impl RestartTracker {
+    pub fn new(threshold: usize, window_secs: u64) -> Self {
+        Self {
+            records: HashMap::new(),
+            threshold,
+            window_secs: window_secs as i64,
+        }
+    }
+
+    /// Tracker using the thresholds of `settings.yaml`
+    pub fn from_settings() -> Self {
+        let settings = &common::setting::get_config().monitoring;
+        Self::new(settings.flapping_restarts, settings.flapping_window_secs)
+    }
+
+    /// Restore records persisted by a previous run
+    pub fn load(&mut self, records: Vec<RestartRecord>) {
+        for record in records {
+            self.records.insert(record.key(), record);
+        }
+    }
+
+    /// Record of the container `name` on `node`, if it was seen
+    pub fn get(&self, node: &str, name: &str) -> Option<&RestartRecord> {
+        self.records.get(&record_key(node, name))
+    }
+
+    /// Compare a snapshot of the containers of `node` with the known records
+    ///
+    /// # Arguments
+    ///
+    /// * `node` - Node the snapshot comes from
+    /// * `containers` - Containers running on the node
+    /// * `now` - Unix seconds of the snapshot
+    ///
+    /// # Returns
+    ///
+    /// * `(Vec<RestartUpdate>, Vec<String>)` - Records to store, and keys of
+    ///   records to delete because their container has been gone for a
+    ///   whole window
+    pub fn observe(
+        &mut self,
+        node: &str,
+        containers: &[ContainerInfo],
+        now: i64,
+    ) -> (Vec<RestartUpdate>, Vec<String>) {
+        let horizon = now - self.window_secs;
+        let mut updates = Vec::new();
+
+        for container in containers {
+            let name = container_name(container);
+            let started_at = container
+                .state
+                .get("StartedAt")
+                .cloned()
+                .unwrap_or_default();
+
+            let Some(record) = self.records.get_mut(&record_key(node, &name)) else {
+                let record = RestartRecord {
+                    node: node.to_string(),
+                    container: name,
+                    container_id: container.id.clone(),
+                    started_at,
+                    last_seen: now,
+                    ..Default::default()
+                };
+                self.records.insert(record.key(), record.clone());
+                updates.push(RestartUpdate {
+                    event: RestartEvent::Tracked,
+                    record,
+                });
+                continue;
+            };
+
+            record.last_seen = now;
+            let restarted = record.container_id != container.id
+                || (!started_at.is_empty() && record.started_at != started_at);
+            if restarted {
+                record.container_id = container.id.clone();
+                record.started_at = started_at;
+                record.restart_count += 1;
+                record.recent_restarts.push(now);
+            }
+
+            record.recent_restarts.retain(|&t| t >= horizon);
+            let flapping = record.recent_restarts.len() > self.threshold;
+            let event = match (record.flapping, flapping) {
+                (false, true) => Some(RestartEvent::Flapping),
+                (true, false) => Some(RestartEvent::Recovered),
+                _ if restarted => Some(RestartEvent::Restarted),
+                _ => None,
+            };
+            record.flapping = flapping;
+
+            if let Some(event) = event {
+                updates.push(RestartUpdate {
+                    event,
+                    record: record.clone(),
+                });
+            }
+        }
+
+        let gone: Vec<String> = self
+            .records
+            .values()
+            .filter(|r| r.node == node && r.last_seen < horizon)
+            .map(RestartRecord::key)
+            .collect();
+        for key in &gone {
+            self.records.remove(key);
+        }
+
+        (updates, gone)
+    }
+}
+
+/// Name identifying `container` across restarts, its id if it has none
+pub fn container_name(container: &ContainerInfo) -> String {
+    container
+        .names
+        .first()
+        .cloned()
+        .unwrap_or_else(|| container.id.clone())
+}
+
+/// Copy the restart count and flapping state of `record` into the state
+/// map of `container`, so they are stored and served with it
+pub fn annotate(container: &mut ContainerInfo, record: &RestartRecord) {
+    container.state.insert(
+        STATE_RESTART_COUNT.to_string(),
+        record.restart_count.to_string(),
+    );
+    container
+        .state
+        .insert(STATE_FLAPPING.to_string(), record.flapping.to_string());
+}
+
+//Unit Test Cases
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn container(id: &str, name: &str, started_at: &str) -> ContainerInfo {
+        let mut state = HashMap::new();
+        state.insert("Status".to_string(), "running".to_string());
+        state.insert("StartedAt".to_string(), started_at.to_string());
+        ContainerInfo {
+            id: id.to_string(),
+            names: vec![name.to_string()],
+            state,
+            ..Default::default()
+        }
+    }
+
+    fn events(updates: &[RestartUpdate]) -> Vec<RestartEvent> {
+        updates.iter().map(|u| u.event).collect()
+    }
+
+    #[test]
+    fn test_first_snapshot_tracks_containers() {
+        let mut tracker = RestartTracker::new(3, 300);
+
+        let (updates, gone) = tracker.observe(
+            "HPC",
+            &[container("a1", "bms", "t0"), container("b1", "mavd", "t0")],
+            1000,
+        );
+
+        assert_eq!(events(&updates), vec![RestartEvent::Tracked; 2]);
+        assert!(gone.is_empty());
+        assert_eq!(tracker.get("HPC", "bms").unwrap().restart_count, 0);
+
+        // An unchanged snapshot changes nothing
+        let (updates, _) = tracker.observe("HPC", &[container("a1", "bms", "t0")], 1010);
+        assert!(updates.is_empty());
+    }
+
+    #[test]
+    fn test_restart_detected_by_id_or_started_at() {
+        let mut tracker = RestartTracker::new(3, 300);
+        tracker.observe("HPC", &[container("a1", "bms", "t0")], 1000);
+
+        // Restarted in place
+        let (updates, _) = tracker.observe("HPC", &[container("a1", "bms", "t1")], 1010);
+        assert_eq!(events(&updates), vec![RestartEvent::Restarted]);
+
+        // Recreated with a new id
+        let (updates, _) = tracker.observe("HPC", &[container("a2", "bms", "t1")], 1020);
+        assert_eq!(events(&updates), vec![RestartEvent::Restarted]);
+
+        let record = tracker.get("HPC", "bms").unwrap();
+        assert_eq!(record.restart_count, 2);
+        assert_eq!(record.container_id, "a2");
+        assert!(!record.flapping);
+    }
+
+    #[test]
+    fn test_flapping_pattern_and_recovery() {
+        let mut tracker = RestartTracker::new(3, 300);
+        tracker.observe("HPC", &[container("a", "bms", "t0")], 1000);
+
+        let mut seen = Vec::new();
+        for i in 1..=4 {
+            let (updates, _) = tracker.observe(
+                "HPC",
+                &[container("a", "bms", &format!("t{}", i))],
+                1000 + i * 30,
+            );
+            seen.extend(events(&updates));
+        }
+        assert_eq!(
+            seen,
+            vec![
+                RestartEvent::Restarted,
+                RestartEvent::Restarted,
+                RestartEvent::Restarted,
+                RestartEvent::Flapping,
+            ]
+        );
+        assert!(tracker.get("HPC", "bms").unwrap().flapping);
+
+        // A fifth restart while flapping is an ordinary restart
+        let (updates, _) = tracker.observe("HPC", &[container("a", "bms", "t5")], 1150);
+        assert_eq!(events(&updates), vec![RestartEvent::Restarted]);
+
+        // Once the restarts fall out of the window, it recovers
+        let (updates, _) = tracker.observe("HPC", &[container("a", "bms", "t5")], 1400);
+        assert_eq!(events(&updates), vec![RestartEvent::Recovered]);
+        let record = tracker.get("HPC", "bms").unwrap();
+        assert!(!record.flapping);
+        assert_eq!(record.restart_count, 5);
+        assert_eq!(record.recent_restarts, vec![1120, 1150]);
+    }
+
+    #[test]
+    fn test_slow_restarts_are_not_flapping() {
+        let mut tracker = RestartTracker::new(3, 300);
+        tracker.observe("HPC", &[container("a", "bms", "t0")], 0);
+
+        for i in 1..=10 {
+            let (updates, _) =
+                tracker.observe("HPC", &[container("a", "bms", &format!("t{}", i))], i * 120);
+            assert_eq!(events(&updates), vec![RestartEvent::Restarted]);
+        }
+        assert_eq!(tracker.get("HPC", "bms").unwrap().restart_count, 10);
+    }
+
+    #[test]
+    fn test_loaded_records_survive_restart() {
+        let mut tracker = RestartTracker::new(3, 300);
+        tracker.observe("HPC", &[container("a", "bms", "t0")], 1000);
+        tracker.observe("HPC", &[container("a", "bms", "t1")], 1010);
+        let stored = tracker.get("HPC", "bms").unwrap().clone();
+
+        // A new tracker restored from storage keeps counting
+        let mut restored = RestartTracker::new(3, 300);
+        restored.load(vec![stored]);
+        let (updates, _) = restored.observe("HPC", &[container("a", "bms", "t2")], 1020);
+
+        assert_eq!(events(&updates), vec![RestartEvent::Restarted]);
+        assert_eq!(updates[0].record.restart_count, 2);
+        assert_eq!(updates[0].record.recent_restarts, vec![1010, 1020]);
+    }
+
+    #[test]
+    fn test_containers_gone_for_a_window_are_forgotten() {
+        let mut tracker = RestartTracker::new(3, 300);
+        tracker.observe("HPC", &[container("a", "bms", "t0")], 1000);
+        tracker.observe("ZONE", &[container("z", "bms", "t0")], 1000);
+
+        // Missing from a few snapshots is not enough
+        let (_, gone) = tracker.observe("HPC", &[], 1200);
+        assert!(gone.is_empty());
+
+        let (_, gone) = tracker.observe("HPC", &[], 1400);
+        assert_eq!(gone, vec!["HPC/bms".to_string()]);
+        assert!(tracker.get("HPC", "bms").is_none());
+        // Records of other nodes are left alone
+        assert!(tracker.get("ZONE", "bms").is_some());
+    }
+
+    #[test]
+    fn test_annotate_sets_state() {
+        let mut info = container("a", "bms", "t0");
+        let record = RestartRecord {
+            restart_count: 4,
+            flapping: true,
+            ..Default::default()
+        };
+
+        annotate(&mut info, &record);
+
+        assert_eq!(info.state[STATE_RESTART_COUNT], "4");
+        assert_eq!(info.state[STATE_FLAPPING], "true");
+        assert_eq!(info.state["Status"], "running");
+    }
+}
@@ -4,23 +4,79 @@
  */
 
 use crate::grpc::sender::statemanager::StateManagerSender;
+use crate::policy::{self, Decision};
+use crate::preemption;
 use common::policymanager::policy_manager_connection_server::PolicyManagerConnection;
-use common::policymanager::{CheckPolicyRequest, CheckPolicyResponse};
+use common::policymanager::{
+    CheckPolicyRequest, CheckPolicyResponse, DecideRequest, DecideResponse,
+};
+use common::spec::artifact::Policy;
 use common::statemanager::{ResourceType, StateChange};
 use tonic::Response;
-#[allow(dead_code)]
+
 pub struct PolicyManagerGrpcServer {
     /// StateManager sender for scenario state changes
     state_sender: StateManagerSender,
+    /// Policies to evaluate instead of the ones stored in etcd
+    policies: Option<Vec<Policy>>,
+}
+
+impl Default for PolicyManagerGrpcServer {
+    fn default() -> Self {
+        Self::new()
+    }
 }
-#[allow(dead_code)]
+
 impl PolicyManagerGrpcServer {
     /// Creates a new PolicyManagerGrpcServer instance
     pub fn new() -> Self {
         Self {
             state_sender: StateManagerSender::new(),
+            policies: None,
         }
     }
+
+    /// Evaluates `policies` instead of reading them from etcd on every check
+    #[allow(dead_code)]
+    pub fn with_policies(mut self, policies: Vec<Policy>) -> Self {
+        self.policies = Some(policies);
+        self
+    }
+
+    /// Decides whether a scenario may run
+    ///
+    /// Fails closed: when the policies cannot be read the scenario is denied.
+    async fn decide(&self, request: &CheckPolicyRequest) -> Decision {
+        if request.scenario_name.is_empty() {
+            return Decision {
+                allowed: false,
+                reason: "Scenario name cannot be empty".to_string(),
+            };
+        }
+        let loaded;
+        let policies = match &self.policies {
+            Some(policies) => policies,
+            None => match policy::load().await {
+                Ok(stored) => {
+                    loaded = stored;
+                    &loaded
+                }
+                Err(e) => {
+                    return Decision {
+                        allowed: false,
+                        reason: format!("policies could not be read: {}", e),
+                    }
+                }
+            },
+        };
+        let context = request.context.clone().unwrap_or_default();
+        policy::evaluate(
+            policies,
+            &request.scenario_name,
+            &context,
+            policy::minute_of_day(),
+        )
+    }
 }
 
 #[tonic::async_trait]
@@ -30,17 +86,18 @@ impl PolicyManagerConnection for PolicyManagerGrpcServer {
         request: tonic::Request<CheckPolicyRequest>,
     ) -> Result<tonic::Response<CheckPolicyResponse>, tonic::Status> {
         let req = request.into_inner();
-        let scenario_name = req.scenario_name; // Renamed for clarity
+        let decision = self.decide(&req).await;
+        let scenario_name = req.scenario_name;
 
-        // Simulate internal logic
-        let (status, desc) = if scenario_name.is_empty() {
-            (1, "Scenario name cannot be empty".to_string())
-        } else if scenario_name == "test_scenario" {
-            (0, "Policy check passed".to_string())
+        let (status, desc) = if decision.allowed {
+            (0, format!("Policy check passed: {}", decision.reason))
         } else {
             (
                 1,
-                format!("Policy check failed for scenario: {}", scenario_name),
+                format!(
+                    "Policy check failed for scenario {}: {}",
+                    scenario_name, decision.reason
+                ),
             )
         };
 
@@ -64,7 +121,7 @@ impl PolicyManagerConnection for PolicyManagerGrpcServer {
         if status == 0 {
             // Policy satisfied: satisfied -> allowed
             println!("   🔄 State Change: satisfied → allowed");
-            println!("   🔍 Reason: Policy requirements satisfied");
+            println!("   🔍 Reason: {}", decision.reason);
 
             let state_change = StateChange {
                 resource_type: ResourceType::Scenario as i32,
@@ -74,6 +131,7 @@ impl PolicyManagerConnection for PolicyManagerGrpcServer {
                 transition_id: format!("policymanager-policy-allowed-{}", timestamp),
                 timestamp_ns: timestamp,
                 source: "policymanager".to_string(),
+                trace_id: String::new(),
             };
 
             println!("   📤 Sending StateChange to StateManager:");
@@ -100,7 +158,7 @@ impl PolicyManagerConnection for PolicyManagerGrpcServer {
         } else {
             // Policy not satisfied: satisfied -> denied
             println!("   🔄 State Change: satisfied → denied");
-            println!("   🔍 Reason: Policy requirements not satisfied");
+            println!("   🔍 Reason: {}", decision.reason);
 
             let state_change = StateChange {
                 resource_type: ResourceType::Scenario as i32,
@@ -110,6 +168,7 @@ impl PolicyManagerConnection for PolicyManagerGrpcServer {
                 transition_id: format!("policymanager-policy-denied-{}", timestamp),
                 timestamp_ns: timestamp,
                 source: "policymanager".to_string(),
+                trace_id: String::new(),
             };
 
             println!("   📤 Sending StateChange to StateManager:");
@@ -137,6 +196,30 @@ impl PolicyManagerConnection for PolicyManagerGrpcServer {
 
         Ok(Response::new(CheckPolicyResponse { status, desc }))
     }
+
+    async fn decide(
+        &self,
+        request: tonic::Request<DecideRequest>,
+    ) -> Result<tonic::Response<DecideResponse>, tonic::Status> {
+        let req = request.into_inner();
+        if req.scenario_name.is_empty() {
+            return Err(tonic::Status::invalid_argument(
+                "Scenario name cannot be empty",
+            ));
+        }
+
+        let running = preemption::load_running().await.map_err(|e| {
+            tonic::Status::unavailable(format!("running workloads could not be read: {}", e))
+        })?;
+        let response = preemption::decide(&req, &running);
+        println!(
+            "Preemption decision for {}: {:?} ({})",
+            req.scenario_name,
+            response.decision(),
+            response.reason
+        );
+        Ok(Response::new(response))
+    }
 }
 
 #[cfg(test)]
@@ -149,7 +232,11 @@ mod tests {
         println!("🧪 Testing PolicyManager Scenario State Management");
         println!("=================================================");
 
-        let server = PolicyManagerGrpcServer::new();
+        let restricted: Policy = serde_yaml::from_str(
+            "apiVersion: v1\nkind: Policy\nmetadata:\n  name: restricted\nspec:\n  action: deny\n  scenarios: [\"restricted_*\"]\n",
+        )
+        .unwrap();
+        let server = PolicyManagerGrpcServer::new().with_policies(vec![restricted]);
 
         println!("📋 Testing Policy Success Case:");
         println!("   🔄 Expected State Change: satisfied → allowed");
@@ -157,13 +244,17 @@ mod tests {
         // Test policy success (satisfied -> allowed)
         let request = Request::new(CheckPolicyRequest {
             scenario_name: "test_scenario".to_string(),
+            context: None,
         });
 
         let response = server.check_policy(request).await.unwrap();
         let policy_response = response.into_inner();
 
         assert_eq!(policy_response.status, 0);
-        assert_eq!(policy_response.desc, "Policy check passed");
+        assert_eq!(
+            policy_response.desc,
+            "Policy check passed: no policy applies"
+        );
         println!("✅ Policy success state change completed");
         println!("");
 
@@ -173,6 +264,7 @@ mod tests {
         // Test policy failure (satisfied -> denied)
         let request = Request::new(CheckPolicyRequest {
             scenario_name: "restricted_scenario".to_string(),
+            context: None,
         });
 
         let response = server.check_policy(request).await.unwrap();
@@ -180,6 +272,9 @@ mod tests {
 
         assert_eq!(policy_response.status, 1);
         assert!(policy_response.desc.contains("Policy check failed"));
+        assert!(policy_response
+            .desc
+            .contains("denied by policy 'restricted'"));
         println!("✅ Policy failure state change completed");
         println!("");
 
@@ -5,11 +5,12 @@
 
 //! StateManager gRPC client for sending state change messages from PolicyManager.
 
+use common::rpc;
 use common::statemanager::{
     connect_server, state_manager_connection_client::StateManagerConnectionClient, StateChange,
     StateChangeResponse,
 };
-use tonic::{Request, Status};
+use tonic::Status;
 
 /// StateManager gRPC client for PolicyManager component.
 #[derive(Clone)]
@@ -33,9 +34,9 @@ impl StateManagerSender {
     /// Ensures a gRPC connection to the StateManager exists and is ready for use.
     async fn ensure_connected(&mut self) -> Result<(), Status> {
         if self.client.is_none() {
-            match StateManagerConnectionClient::connect(connect_server()).await {
-                Ok(client) => {
-                    self.client = Some(client);
+            match rpc::connect(connect_server()).await {
+                Ok(channel) => {
+                    self.client = Some(StateManagerConnectionClient::new(channel));
                     Ok(())
                 }
                 Err(e) => Err(Status::unknown(format!(
@@ -56,7 +57,7 @@ impl StateManagerSender {
         self.ensure_connected().await?;
 
         if let Some(client) = &mut self.client {
-            client.send_state_change(Request::new(state_change)).await
+            rpc::call(state_change, |request| client.send_state_change(request)).await
         } else {
             Err(Status::unknown("Client not connected"))
         }
@@ -3,10 +3,41 @@
 * SPDX-License-Identifier: Apache-2.0
 */
 pub mod grpc;
+pub mod policy;
+pub mod preemption;
 
-fn main() {
-    println!("Hello, world!");
-    loop {
-        std::thread::sleep(std::time::Duration::from_secs(10));
+use common::health::{etcd_reachable, ServerHealth, PROBE_INTERVAL};
+use common::policymanager::policy_manager_connection_server::PolicyManagerConnectionServer;
+use tonic::transport::Server;
+
+/// Serves policy checks until the gRPC server stops
+///
+/// The server also exposes `grpc.health.v1.Health`, driven by etcd reachability.
+async fn initialize() {
+    let server = grpc::receiver::PolicyManagerGrpcServer::new();
+    let addr = common::policymanager::open_server()
+        .parse()
+        .expect("policymanager address parsing error");
+    println!("PolicyManager listening on {}", addr);
+
+    let (health, health_service) = ServerHealth::new::<
+        PolicyManagerConnectionServer<grpc::receiver::PolicyManagerGrpcServer>,
+    >();
+    health.spawn_probe(PROBE_INTERVAL, etcd_reachable);
+
+    if let Err(e) = Server::builder()
+        .layer(common::trace::TraceLayer)
+        .add_service(health_service)
+        .add_service(common::version::service("policymanager"))
+        .add_service(PolicyManagerConnectionServer::new(server))
+        .serve_with_shutdown(addr, health.shutdown_signal())
+        .await
+    {
+        println!("gRPC server error: {}", e);
     }
 }
+
+#[tokio::main]
+async fn main() {
+    initialize().await;
+}
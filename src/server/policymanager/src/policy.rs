@@ -0,0 +1,274 @@
+/*
+* SPDX-FileCopyrightText: Copyright 2024 LG Electronics Inc.
+* SPDX-License-Identifier: Apache-2.0
+*/
+//! Scenario admission policies
+//!
+//! Policies are stored in etcd under `Policy/<name>`. Every policy whose
+//! conditions match the scenario applies, and the one with the highest
+//! priority decides. Ties are broken by policy name so the outcome does not
+//! depend on etcd ordering. When no policy applies the scenario is allowed.
+
+use common::policymanager::PolicyContext;
+use common::spec::artifact::policy::{AsilLevel, PolicyAction, PolicySpec};
+use common::spec::artifact::{Artifact, Policy};
+
+/// Outcome of a policy check
+#[derive(Debug, Clone, PartialEq)]
+pub struct Decision {
+    pub allowed: bool,
+    pub reason: String,
+}
+
+impl Decision {
+    fn allow(reason: String) -> Self {
+        Self {
+            allowed: true,
+            reason,
+        }
+    }
+
+    fn deny(reason: String) -> Self {
+        Self {
+            allowed: false,
+            reason,
+        }
+    }
+}
+
+/// Evaluates `policies` for a scenario about to be executed
+///
+/// # Arguments
+///
+/// * `policies` - Every stored policy
+/// * `scenario_name` - Scenario being admitted
+/// * `context` - Node types, running scenarios and ASIL level of the scenario
+/// * `minute_of_day` - Current time as minutes since midnight UTC
+///
+/// # Returns
+///
+/// * `Decision` - Whether the scenario may run, and why
+pub fn evaluate(
+    policies: &[Policy],
+    scenario_name: &str,
+    context: &PolicyContext,
+    minute_of_day: u32,
+) -> Decision {
+    let mut applicable: Vec<&Policy> = policies
+        .iter()
+        .filter(|p| applies(p.get_spec(), scenario_name, context, minute_of_day))
+        .collect();
+    applicable.sort_by(|a, b| {
+        b.get_spec()
+            .priority
+            .cmp(&a.get_spec().priority)
+            .then_with(|| a.get_name().cmp(&b.get_name()))
+    });
+
+    let Some(policy) = applicable.first() else {
+        return Decision::allow("no policy applies".to_string());
+    };
+    let name = policy.get_name();
+    let spec = policy.get_spec();
+
+    if spec.action == PolicyAction::Deny {
+        return Decision::deny(format!("denied by policy '{}'", name));
+    }
+    if let Some(max) = spec.max_concurrent {
+        if context.active_scenarios >= max {
+            return Decision::deny(format!(
+                "policy '{}' allows {} concurrent scenarios, {} already running",
+                name, max, context.active_scenarios
+            ));
+        }
+    }
+    if let Some(required) = spec.required_asil {
+        let level = asil_level(&context.asil_level);
+        if level < required {
+            return Decision::deny(format!(
+                "policy '{}' requires ASIL {:?}, scenario is {:?}",
+                name, required, level
+            ));
+        }
+    }
+    Decision::allow(format!("allowed by policy '{}'", name))
+}
+
+/// Whether every condition set in `spec` matches
+///
+/// A time window that cannot be parsed never matches.
+fn applies(spec: &PolicySpec, scenario_name: &str, context: &PolicyContext, minute: u32) -> bool {
+    let scenario_matches = spec.scenarios.is_empty()
+        || spec
+            .scenarios
+            .iter()
+            .any(|pattern| match pattern.strip_suffix('*') {
+                Some(prefix) => scenario_name.starts_with(prefix),
+                None => pattern == scenario_name,
+            });
+    let node_matches = spec.node_types.is_empty()
+        || context
+            .node_types
+            .iter()
+            .any(|node_type| spec.node_types.contains(node_type));
+    let time_matches = spec
+        .time_window
+        .as_ref()
+        .is_none_or(|window| window.contains(minute).unwrap_or(false));
+
+    scenario_matches && node_matches && time_matches
+}
+
+/// ASIL level reported in the context, QM when it is missing or unknown
+fn asil_level(level: &str) -> AsilLevel {
+    level.parse().unwrap_or(AsilLevel::QM)
+}
+
+/// Current time as minutes since midnight UTC
+pub fn minute_of_day() -> u32 {
+    let secs = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default();
+    ((secs % 86_400) / 60) as u32
+}
+
+/// Reads every policy stored in etcd
+///
+/// Entries that do not parse are skipped so one bad policy does not block
+/// every scenario.
+pub async fn load() -> common::Result<Vec<Policy>> {
+    let entries = common::etcd::get_all_with_prefix(common::keys::POLICY_PREFIX).await?;
+    Ok(entries
+        .into_iter()
+        .filter_map(
+            |(key, value)| match serde_yaml::from_str::<Policy>(&value) {
+                Ok(policy) => Some(policy),
+                Err(e) => {
+                    println!("Skipping invalid policy {}: {}", key, e);
+                    None
+                }
+            },
+        )
+        .collect())
+}
+
+//Unit Test Cases
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn policy(name: &str, spec: &str) -> Policy {
+        let yaml = format!(
+            "apiVersion: v1\nkind: Policy\nmetadata:\n  name: {}\nspec:\n{}",
+            name, spec
+        );
+        serde_yaml::from_str(&yaml).unwrap()
+    }
+
+    fn context(node_types: &[&str], active_scenarios: u32, asil_level: &str) -> PolicyContext {
+        PolicyContext {
+            node_types: node_types.iter().map(|t| t.to_string()).collect(),
+            active_scenarios,
+            asil_level: asil_level.to_string(),
+        }
+    }
+
+    const NOON: u32 = 12 * 60;
+
+    #[test]
+    fn test_no_policy_allows() {
+        let decision = evaluate(&[], "any", &context(&[], 0, ""), NOON);
+        assert!(decision.allowed);
+        assert_eq!(decision.reason, "no policy applies");
+    }
+
+    #[test]
+    fn test_scenario_name_rule() {
+        let policies = [policy(
+            "no-infotainment",
+            "  action: deny\n  scenarios: [\"infotainment-*\", \"radio\"]\n",
+        )];
+        let ctx = context(&[], 0, "");
+
+        assert!(!evaluate(&policies, "infotainment-video", &ctx, NOON).allowed);
+        assert!(!evaluate(&policies, "radio", &ctx, NOON).allowed);
+        assert!(evaluate(&policies, "radio-fm", &ctx, NOON).allowed);
+        assert!(evaluate(&policies, "antipinch", &ctx, NOON).allowed);
+    }
+
+    #[test]
+    fn test_node_type_rule() {
+        let policies = [policy(
+            "no-bluechi",
+            "  action: deny\n  nodeTypes: [bluechi]\n",
+        )];
+
+        let decision = evaluate(&policies, "s", &context(&["bluechi"], 0, ""), NOON);
+        assert!(!decision.allowed);
+        assert!(decision.reason.contains("no-bluechi"));
+        assert!(evaluate(&policies, "s", &context(&["nodeagent"], 0, ""), NOON).allowed);
+    }
+
+    #[test]
+    fn test_time_window_rule() {
+        let policies = [policy(
+            "quiet-night",
+            "  action: deny\n  timeWindow:\n    start: \"22:00\"\n    end: \"06:00\"\n",
+        )];
+        let ctx = context(&[], 0, "");
+
+        assert!(!evaluate(&policies, "s", &ctx, 23 * 60).allowed);
+        assert!(!evaluate(&policies, "s", &ctx, 60).allowed);
+        assert!(evaluate(&policies, "s", &ctx, NOON).allowed);
+    }
+
+    #[test]
+    fn test_max_concurrent_rule() {
+        let policies = [policy("limit", "  maxConcurrent: 2\n")];
+
+        assert!(evaluate(&policies, "s", &context(&[], 1, ""), NOON).allowed);
+        let decision = evaluate(&policies, "s", &context(&[], 2, ""), NOON);
+        assert!(!decision.allowed);
+        assert!(decision.reason.contains("2 already running"));
+    }
+
+    #[test]
+    fn test_required_asil_rule() {
+        let policies = [policy("safety", "  requiredAsil: B\n")];
+
+        assert!(evaluate(&policies, "s", &context(&[], 0, "ASIL-B"), NOON).allowed);
+        assert!(evaluate(&policies, "s", &context(&[], 0, "D"), NOON).allowed);
+        assert!(!evaluate(&policies, "s", &context(&[], 0, "A"), NOON).allowed);
+        // A scenario without an ASIL level counts as QM
+        assert!(!evaluate(&policies, "s", &context(&[], 0, ""), NOON).allowed);
+    }
+
+    #[test]
+    fn test_highest_priority_decides() {
+        let policies = [
+            policy("deny-all", "  priority: 1\n  action: deny\n"),
+            policy(
+                "allow-antipinch",
+                "  priority: 10\n  scenarios: [antipinch]\n",
+            ),
+        ];
+        let ctx = context(&[], 0, "");
+
+        let decision = evaluate(&policies, "antipinch", &ctx, NOON);
+        assert!(decision.allowed);
+        assert_eq!(decision.reason, "allowed by policy 'allow-antipinch'");
+        assert!(!evaluate(&policies, "other", &ctx, NOON).allowed);
+    }
+
+    #[test]
+    fn test_equal_priority_is_decided_by_name() {
+        let policies = [
+            policy("b-allow", "  action: allow\n"),
+            policy("a-deny", "  action: deny\n"),
+        ];
+        let decision = evaluate(&policies, "s", &context(&[], 0, ""), NOON);
+        assert!(!decision.allowed);
+        assert_eq!(decision.reason, "denied by policy 'a-deny'");
+    }
+}
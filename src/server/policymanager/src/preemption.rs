@@ -0,0 +1,221 @@
+/*
+* SPDX-FileCopyrightText: Copyright 2024 LG Electronics Inc.
+* SPDX-License-Identifier: Apache-2.0
+*/
+//! Arbitration between scenarios competing for the same nodes
+//!
+//! A running workload competes with a new scenario when it runs on one of
+//! the nodes the scenario deploys to and both scenarios have a priority.
+//! The new scenario preempts competitors whose priority is strictly lower.
+//! A competitor with an equal or higher priority queues it instead.
+
+use common::policymanager::{DecideRequest, DecideResponse, Decision, Workload};
+use common::spec::artifact::{Package, Scenario};
+use common::statemanager::ScenarioState;
+
+/// Decides whether a scenario may start next to the running workloads
+///
+/// # Arguments
+///
+/// * `request` - Scenario about to start, with its priority and workloads
+/// * `running` - Workloads of the scenarios currently running
+///
+/// # Returns
+///
+/// * `DecideResponse` - Allow, Queue, or Preempt with the workloads to stop first
+pub fn decide(request: &DecideRequest, running: &[Workload]) -> DecideResponse {
+    let Some(priority) = request.priority else {
+        return allow("scenario has no priority");
+    };
+
+    let mut competitors: Vec<&Workload> = running
+        .iter()
+        .filter(|w| w.scenario_name != request.scenario_name)
+        .filter(|w| request.workloads.iter().any(|own| own.node == w.node))
+        .collect();
+    if competitors.is_empty() {
+        return allow("no competing workloads");
+    }
+
+    competitors.sort_by_key(|w| std::cmp::Reverse(w.priority));
+    let strongest = competitors[0];
+    if strongest.priority >= priority {
+        return DecideResponse {
+            decision: Decision::Queue as i32,
+            preempt: Vec::new(),
+            reason: format!(
+                "scenario '{}' (priority {}) holds node '{}'",
+                strongest.scenario_name, strongest.priority, strongest.node
+            ),
+        };
+    }
+
+    let mut preempt: Vec<Workload> = competitors.into_iter().cloned().collect();
+    preempt
+        .sort_by(|a, b| (&a.scenario_name, &a.model_name).cmp(&(&b.scenario_name, &b.model_name)));
+    preempt.dedup();
+    DecideResponse {
+        decision: Decision::Preempt as i32,
+        reason: format!(
+            "priority {} preempts {} lower priority workload(s)",
+            priority,
+            preempt.len()
+        ),
+        preempt,
+    }
+}
+
+fn allow(reason: &str) -> DecideResponse {
+    DecideResponse {
+        decision: Decision::Allow as i32,
+        preempt: Vec::new(),
+        reason: reason.to_string(),
+    }
+}
+
+/// Reads the workloads of running scenarios from etcd
+///
+/// A scenario is running when StateManager recorded it as completed and its
+/// action launched workloads. Scenarios without a priority are left out
+/// since they never compete.
+pub async fn load_running() -> common::Result<Vec<Workload>> {
+    let completed = ScenarioState::Completed.as_str_name();
+    let states = common::etcd::get_all_with_prefix(common::keys::SCENARIO_STATE_PREFIX).await?;
+
+    let mut running = Vec::new();
+    for (key, state) in states {
+        if state != completed {
+            continue;
+        }
+        let Some(name) = key
+            .strip_prefix(common::keys::SCENARIO_STATE_PREFIX)
+            .and_then(|rest| rest.strip_suffix("/state"))
+        else {
+            continue;
+        };
+        match scenario_workloads(name).await {
+            Ok(workloads) => running.extend(workloads),
+            Err(e) => println!("Skipping running scenario {}: {}", name, e),
+        }
+    }
+    Ok(running)
+}
+
+async fn scenario_workloads(name: &str) -> common::Result<Vec<Workload>> {
+    let scenario: Scenario =
+        serde_yaml::from_str(&common::etcd::get(&common::keys::scenario(name)).await?)?;
+    let Some(priority) = scenario.get_priority() else {
+        return Ok(Vec::new());
+    };
+    if scenario.get_actions() != "launch" {
+        return Ok(Vec::new());
+    }
+    let package: Package = serde_yaml::from_str(
+        &common::etcd::get(&common::keys::package(&scenario.get_targets())).await?,
+    )?;
+
+    Ok(package
+        .get_models()
+        .iter()
+        .map(|model| Workload {
+            scenario_name: name.to_string(),
+            model_name: model.get_name(),
+            node: model.get_node(),
+            priority,
+        })
+        .collect())
+}
+
+//Unit Test Cases
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn workload(scenario: &str, model: &str, node: &str, priority: i32) -> Workload {
+        Workload {
+            scenario_name: scenario.to_string(),
+            model_name: model.to_string(),
+            node: node.to_string(),
+            priority,
+        }
+    }
+
+    fn request(priority: Option<i32>, nodes: &[&str]) -> DecideRequest {
+        DecideRequest {
+            scenario_name: "parking-assist".to_string(),
+            priority,
+            workloads: nodes
+                .iter()
+                .map(|node| workload("parking-assist", "assist", node, priority.unwrap_or(0)))
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn test_no_priority_is_allowed() {
+        let running = [workload("vision", "detector", "HPC", 5)];
+        let response = decide(&request(None, &["HPC"]), &running);
+        assert_eq!(response.decision, Decision::Allow as i32);
+    }
+
+    #[test]
+    fn test_other_nodes_do_not_compete() {
+        let running = [workload("vision", "detector", "ZONE", 5)];
+        let response = decide(&request(Some(1), &["HPC"]), &running);
+        assert_eq!(response.decision, Decision::Allow as i32);
+        assert!(response.preempt.is_empty());
+    }
+
+    #[test]
+    fn test_equal_priority_queues_without_preemption() {
+        let running = [workload("vision", "detector", "HPC", 5)];
+        let response = decide(&request(Some(5), &["HPC"]), &running);
+        assert_eq!(response.decision, Decision::Queue as i32);
+        assert!(response.preempt.is_empty());
+        assert!(response.reason.contains("vision"));
+    }
+
+    #[test]
+    fn test_higher_priority_running_queues() {
+        let running = [
+            workload("infotainment", "player", "HPC", 1),
+            workload("vision", "detector", "HPC", 9),
+        ];
+        let response = decide(&request(Some(5), &["HPC"]), &running);
+        assert_eq!(response.decision, Decision::Queue as i32);
+        assert!(response.reason.contains("'vision' (priority 9)"));
+    }
+
+    #[test]
+    fn test_strictly_higher_priority_preempts_all_competitors() {
+        let running = [
+            workload("vision", "tracker", "HPC", 3),
+            workload("vision", "detector", "HPC", 3),
+            workload("infotainment", "player", "HPC", 1),
+            workload("navigation", "map", "ZONE", 1),
+        ];
+        let response = decide(&request(Some(5), &["HPC"]), &running);
+
+        assert_eq!(response.decision, Decision::Preempt as i32);
+        let preempted: Vec<(&str, &str)> = response
+            .preempt
+            .iter()
+            .map(|w| (w.scenario_name.as_str(), w.model_name.as_str()))
+            .collect();
+        assert_eq!(
+            preempted,
+            vec![
+                ("infotainment", "player"),
+                ("vision", "detector"),
+                ("vision", "tracker")
+            ]
+        );
+    }
+
+    #[test]
+    fn test_own_workloads_do_not_compete() {
+        let running = [workload("parking-assist", "assist", "HPC", 1)];
+        let response = decide(&request(Some(5), &["HPC"]), &running);
+        assert_eq!(response.decision, Decision::Allow as i32);
+    }
+}
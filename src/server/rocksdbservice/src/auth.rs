@@ -0,0 +1,302 @@
+/*
+ * SPDX-FileCopyrightText: Copyright 2024 LG Electronics Inc.
+ * SPDX-License-Identifier: Apache-2.0
+ */
+
+//! Token authentication and per-token key-prefix ACLs
+//!
+//! Each client sends its token as `authorization: Bearer <token>`. A token
+//! is configured as `TOKEN=RULE[,RULE...]`, where a rule is `read:PREFIX` or
+//! `write:PREFIX` and `*` stands for every key, e.g.
+//! `monitoring=write:/metrics/,write:/logs/,read:*`. Writes cover puts,
+//! batch puts and deletes. Without any configured token every request is
+//! allowed.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+use tonic::service::Interceptor;
+use tonic::{Request, Status};
+
+/// Kind of access an operation needs
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Access {
+    Read,
+    Write,
+}
+
+impl Access {
+    fn as_str(&self) -> &'static str {
+        match self {
+            Access::Read => "read",
+            Access::Write => "write",
+        }
+    }
+}
+
+/// Key prefixes a token may read and write
+#[derive(Debug, Default, PartialEq)]
+pub struct Acl {
+    read: Vec<String>,
+    write: Vec<String>,
+}
+
+impl Acl {
+    /// Parse the rules of a token, e.g. `write:/metrics/,read:*`
+    pub fn parse(rules: &str) -> Result<Self, String> {
+        let mut acl = Acl::default();
+        for rule in rules.split(',').map(str::trim).filter(|r| !r.is_empty()) {
+            let (access, prefix) = rule
+                .split_once(':')
+                .ok_or_else(|| format!("invalid rule '{}', expected ACCESS:PREFIX", rule))?;
+            let prefix = if prefix == "*" { "" } else { prefix }.to_string();
+            match access {
+                "read" => acl.read.push(prefix),
+                "write" => acl.write.push(prefix),
+                _ => {
+                    return Err(format!(
+                        "invalid access '{}' in rule '{}', expected read or write",
+                        access, rule
+                    ))
+                }
+            }
+        }
+        Ok(acl)
+    }
+
+    fn prefixes(&self, access: Access) -> &[String] {
+        match access {
+            Access::Read => &self.read,
+            Access::Write => &self.write,
+        }
+    }
+
+    /// Whether `key`, or every key starting with it, is under an allowed prefix
+    fn allows(&self, access: Access, key: &str) -> bool {
+        self.prefixes(access)
+            .iter()
+            .any(|prefix| key.starts_with(prefix.as_str()))
+    }
+
+    /// Checks `access` to `key`
+    ///
+    /// # Returns
+    ///
+    /// * `Result<(), Status>` - `PERMISSION_DENIED` naming the key and the
+    ///   prefixes the token is limited to
+    pub fn check(&self, access: Access, key: &str) -> Result<(), Status> {
+        if self.allows(access, key) {
+            return Ok(());
+        }
+        let allowed: Vec<&str> = self
+            .prefixes(access)
+            .iter()
+            .map(|p| if p.is_empty() { "*" } else { p.as_str() })
+            .collect();
+        Err(Status::permission_denied(format!(
+            "{} access to '{}' denied, token may only {} under [{}]",
+            access.as_str(),
+            key,
+            access.as_str(),
+            allowed.join(", ")
+        )))
+    }
+}
+
+/// Checks `access` to `key` with the ACL the interceptor attached to
+/// `request`, allowing everything when authentication is disabled
+pub fn authorize<T>(request: &Request<T>, access: Access, key: &str) -> Result<(), Status> {
+    match request.extensions().get::<Arc<Acl>>() {
+        Some(acl) => acl.check(access, key),
+        None => Ok(()),
+    }
+}
+
+/// Configured tokens and their ACLs
+#[derive(Debug, Default)]
+pub struct Tokens {
+    acls: HashMap<String, Arc<Acl>>,
+}
+
+impl Tokens {
+    /// Parse tokens given as `TOKEN=RULE[,RULE...]`
+    pub fn parse<'a>(entries: impl IntoIterator<Item = &'a str>) -> Result<Self, String> {
+        let mut tokens = Tokens::default();
+        for entry in entries {
+            let (token, rules) = entry
+                .split_once('=')
+                .ok_or_else(|| "invalid token entry, expected TOKEN=RULE[,RULE...]".to_string())?;
+            let token = token.trim();
+            if token.is_empty() {
+                return Err("invalid token entry, the token is empty".to_string());
+            }
+            let acl = Acl::parse(rules)?;
+            tokens.acls.insert(token.to_string(), Arc::new(acl));
+        }
+        Ok(tokens)
+    }
+
+    /// Tokens of the file at `path`, one entry per line, and of `args`
+    ///
+    /// Blank lines and lines starting with `#` are skipped.
+    pub fn load(path: Option<&str>, args: &[String]) -> Result<Self, String> {
+        let file = match path {
+            Some(path) => std::fs::read_to_string(path)
+                .map_err(|e| format!("cannot read tokens file {}: {}", path, e))?,
+            None => String::new(),
+        };
+        let lines = file
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty() && !line.starts_with('#'));
+        Self::parse(lines.chain(args.iter().map(String::as_str)))
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.acls.is_empty()
+    }
+
+    pub fn len(&self) -> usize {
+        self.acls.len()
+    }
+}
+
+/// Interceptor rejecting requests without a known token
+///
+/// The ACL of the token is attached to the request for the handlers.
+#[derive(Clone)]
+pub struct Authenticator {
+    tokens: Arc<Tokens>,
+}
+
+impl Authenticator {
+    /// Authenticator over `tokens`, `None` when there are none
+    pub fn new(tokens: Tokens) -> Option<Self> {
+        (!tokens.is_empty()).then(|| Self {
+            tokens: Arc::new(tokens),
+        })
+    }
+}
+
+impl Interceptor for Authenticator {
+    fn call(&mut self, mut request: Request<()>) -> Result<Request<()>, Status> {
+        let token = request
+            .metadata()
+            .get("authorization")
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.strip_prefix("Bearer "))
+            .ok_or_else(|| Status::unauthenticated("missing bearer token"))?;
+        let acl = self
+            .tokens
+            .acls
+            .get(token.trim())
+            .cloned()
+            .ok_or_else(|| Status::unauthenticated("unknown token"))?;
+        request.extensions_mut().insert(acl);
+        Ok(request)
+    }
+}
+
+//UNIT TEST CASES
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tonic::Code;
+
+    fn bearer(token: &str) -> Request<()> {
+        let mut request = Request::new(());
+        request.metadata_mut().insert(
+            "authorization",
+            format!("Bearer {}", token).parse().unwrap(),
+        );
+        request
+    }
+
+    #[test]
+    fn test_parse_rules() {
+        let acl = Acl::parse("write:/metrics/, write:/logs/,read:*").unwrap();
+        assert_eq!(
+            acl,
+            Acl {
+                read: vec![String::new()],
+                write: vec!["/metrics/".to_string(), "/logs/".to_string()],
+            }
+        );
+
+        assert!(Acl::parse("/metrics/").is_err());
+        let err = Acl::parse("delete:/metrics/").unwrap_err();
+        assert!(err.contains("invalid access 'delete'"));
+    }
+
+    #[test]
+    fn test_check_prefixes() {
+        let acl = Acl::parse("write:/metrics/,write:/logs/,read:*").unwrap();
+
+        assert!(acl.check(Access::Write, "/metrics/node1/cpu").is_ok());
+        assert!(acl.check(Access::Read, "Scenario/helloworld").is_ok());
+
+        let status = acl.check(Access::Write, "Scenario/helloworld").unwrap_err();
+        assert_eq!(status.code(), Code::PermissionDenied);
+        assert_eq!(
+            status.message(),
+            "write access to 'Scenario/helloworld' denied, token may only write under [/metrics/, /logs/]"
+        );
+
+        // No rule for an access denies it entirely
+        let status = Acl::parse("read:/metrics/")
+            .unwrap()
+            .check(Access::Write, "/metrics/x")
+            .unwrap_err();
+        assert!(status.message().ends_with("token may only write under []"));
+    }
+
+    #[test]
+    fn test_load_tokens_from_file_and_args() {
+        let path = std::env::temp_dir().join(format!(
+            "pullpiri_rocksdbservice_tokens_{}",
+            std::process::id()
+        ));
+        std::fs::write(
+            &path,
+            "# monitoring server\nmon-token=write:/metrics/,write:/logs/\n\n",
+        )
+        .unwrap();
+
+        let tokens = Tokens::load(
+            Some(&path.to_string_lossy()),
+            &["api-token=write:*,read:*".to_string()],
+        )
+        .unwrap();
+        assert_eq!(tokens.len(), 2);
+
+        assert!(Tokens::load(Some("/nonexistent/tokens"), &[]).is_err());
+        assert!(Tokens::parse(["no-rules"]).is_err());
+        assert!(Tokens::parse(["=read:*"]).is_err());
+        assert!(Tokens::load(None, &[]).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_interceptor_attaches_acl_of_token() {
+        let tokens = Tokens::parse(["mon-token=write:/metrics/"]).unwrap();
+        let mut authenticator = Authenticator::new(tokens).unwrap();
+
+        let request = authenticator.call(bearer("mon-token")).unwrap();
+        assert!(authorize(&request, Access::Write, "/metrics/cpu").is_ok());
+        assert_eq!(
+            authorize(&request, Access::Write, "Scenario/x")
+                .unwrap_err()
+                .code(),
+            Code::PermissionDenied
+        );
+
+        let status = authenticator.call(bearer("stolen")).unwrap_err();
+        assert_eq!(status.code(), Code::Unauthenticated);
+        let status = authenticator.call(Request::new(())).unwrap_err();
+        assert_eq!(status.code(), Code::Unauthenticated);
+    }
+
+    #[test]
+    fn test_no_tokens_disables_authentication() {
+        assert!(Authenticator::new(Tokens::default()).is_none());
+        assert!(authorize(&Request::new(()), Access::Write, "Scenario/x").is_ok());
+    }
+}
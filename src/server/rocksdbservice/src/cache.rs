@@ -0,0 +1,128 @@
+/*
+ * SPDX-FileCopyrightText: Copyright 2024 LG Electronics Inc.
+ * SPDX-License-Identifier: Apache-2.0
+ */
+
+//! Bounded LRU read cache placed in front of RocksDB `get` calls.
+//!
+//! The cache itself is not aware of the database lock. Callers must only
+//! populate or invalidate entries while holding the RocksDB lock so that a
+//! concurrent write can never be overtaken by a stale fill.
+
+use lru::LruCache;
+use std::num::NonZeroUsize;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+
+/// LRU cache of `key -> value` pairs with hit/miss counters.
+///
+/// A capacity of `0` disables caching entirely.
+pub struct ReadCache {
+    entries: Option<Mutex<LruCache<String, String>>>,
+    hits: AtomicU64,
+    misses: AtomicU64,
+}
+
+impl ReadCache {
+    /// Create a cache holding at most `capacity` entries.
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            entries: NonZeroUsize::new(capacity).map(|cap| Mutex::new(LruCache::new(cap))),
+            hits: AtomicU64::new(0),
+            misses: AtomicU64::new(0),
+        }
+    }
+
+    /// Return the cached value for `key`, updating its recency on hit.
+    pub fn get(&self, key: &str) -> Option<String> {
+        let entries = self.entries.as_ref()?;
+        let value = match entries.lock() {
+            Ok(mut cache) => cache.get(key).cloned(),
+            Err(_) => None,
+        };
+
+        let counter = if value.is_some() {
+            &self.hits
+        } else {
+            &self.misses
+        };
+        counter.fetch_add(1, Ordering::Relaxed);
+        value
+    }
+
+    /// Store `value` for `key`, evicting the least recently used entry if full.
+    pub fn insert(&self, key: &str, value: &str) {
+        if let Some(entries) = &self.entries {
+            if let Ok(mut cache) = entries.lock() {
+                cache.put(key.to_string(), value.to_string());
+            }
+        }
+    }
+
+    /// Drop the cached entry for `key`, if any.
+    pub fn invalidate(&self, key: &str) {
+        if let Some(entries) = &self.entries {
+            if let Ok(mut cache) = entries.lock() {
+                cache.pop(key);
+            }
+        }
+    }
+
+    /// Returns `(hits, misses)` observed since the cache was created.
+    pub fn stats(&self) -> (u64, u64) {
+        (
+            self.hits.load(Ordering::Relaxed),
+            self.misses.load(Ordering::Relaxed),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_miss_then_hit() {
+        let cache = ReadCache::new(4);
+
+        assert_eq!(cache.get("key1"), None);
+        cache.insert("key1", "value1");
+        assert_eq!(cache.get("key1"), Some("value1".to_string()));
+        assert_eq!(cache.stats(), (1, 1));
+    }
+
+    #[test]
+    fn test_invalidate_removes_cached_value() {
+        let cache = ReadCache::new(4);
+        cache.insert("key1", "old");
+
+        cache.invalidate("key1");
+
+        assert_eq!(cache.get("key1"), None);
+        cache.insert("key1", "new");
+        assert_eq!(cache.get("key1"), Some("new".to_string()));
+    }
+
+    #[test]
+    fn test_capacity_evicts_least_recently_used() {
+        let cache = ReadCache::new(2);
+        cache.insert("a", "1");
+        cache.insert("b", "2");
+        // Touch "a" so "b" becomes least recently used
+        assert!(cache.get("a").is_some());
+        cache.insert("c", "3");
+
+        assert_eq!(cache.get("b"), None);
+        assert_eq!(cache.get("a"), Some("1".to_string()));
+        assert_eq!(cache.get("c"), Some("3".to_string()));
+    }
+
+    #[test]
+    fn test_zero_capacity_disables_cache() {
+        let cache = ReadCache::new(0);
+        cache.insert("key1", "value1");
+
+        assert_eq!(cache.get("key1"), None);
+        assert_eq!(cache.stats(), (0, 0));
+    }
+}
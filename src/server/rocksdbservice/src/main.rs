@@ -3,12 +3,18 @@
  * SPDX-License-Identifier: Apache-2.0
  */
 
+mod auth;
+mod cache;
+
+use auth::{Access, Authenticator, Tokens};
+use cache::ReadCache;
 use clap::Parser;
+use common::health::{ServerHealth, PROBE_INTERVAL};
 use rocksdb::{IteratorMode, Options, WriteBatch, DB};
 use std::sync::{Arc, OnceLock};
 use tokio::sync::Mutex;
 use tonic::{transport::Server, Request, Response, Status};
-use tracing::{error, info};
+use tracing::{error, info, warn};
 
 // Import protobuf definitions
 use common::rocksdbservice::{
@@ -36,6 +42,18 @@ struct Args {
     /// Bind address
     #[arg(short, long, default_value = "0.0.0.0")]
     addr: String,
+
+    /// Maximum number of entries kept in the read cache (0 disables caching)
+    #[arg(long, default_value = "4096")]
+    cache_capacity: usize,
+
+    /// File of access tokens, one `TOKEN=RULE[,RULE...]` per line
+    #[arg(long)]
+    tokens_file: Option<String>,
+
+    /// Access token as `TOKEN=RULE[,RULE...]`, e.g. `secret=write:/metrics/,read:*`
+    #[arg(long = "token")]
+    tokens: Vec<String>,
 }
 
 // Initialize RocksDB
@@ -76,7 +94,18 @@ fn get_db() -> Result<Arc<Mutex<DB>>, Status> {
 }
 
 // gRPC service implementation
-pub struct RocksDbServiceImpl;
+pub struct RocksDbServiceImpl {
+    cache: ReadCache,
+}
+
+impl RocksDbServiceImpl {
+    /// Create the service with a read cache of `cache_capacity` entries.
+    pub fn new(cache_capacity: usize) -> Self {
+        Self {
+            cache: ReadCache::new(cache_capacity),
+        }
+    }
+}
 
 #[tonic::async_trait]
 impl RocksDbService for RocksDbServiceImpl {
@@ -99,6 +128,9 @@ impl RocksDbService for RocksDbServiceImpl {
             "error".to_string()
         };
 
+        let (hits, misses) = self.cache.stats();
+        info!("Read cache stats: {} hits, {} misses", hits, misses);
+
         let response = HealthResponse {
             status,
             version: "1.0.0".to_string(),
@@ -109,6 +141,7 @@ impl RocksDbService for RocksDbServiceImpl {
     }
 
     async fn put(&self, request: Request<PutRequest>) -> Result<Response<PutResponse>, Status> {
+        auth::authorize(&request, Access::Write, &request.get_ref().key)?;
         let req = request.into_inner();
 
         // Validate key
@@ -131,6 +164,9 @@ impl RocksDbService for RocksDbServiceImpl {
         let db = get_db()?;
         let db_lock = db.lock().await;
 
+        // Invalidate while holding the DB lock so no reader can refill a stale value
+        self.cache.invalidate(&req.key);
+
         match db_lock.put(req.key.as_bytes(), req.value.as_bytes()) {
             Ok(()) => {
                 info!("Successfully stored key: '{}'", req.key);
@@ -147,12 +183,21 @@ impl RocksDbService for RocksDbServiceImpl {
     }
 
     async fn get(&self, request: Request<GetRequest>) -> Result<Response<GetResponse>, Status> {
+        auth::authorize(&request, Access::Read, &request.get_ref().key)?;
         let req = request.into_inner();
 
         if req.key.is_empty() {
             return Err(Status::invalid_argument("Key cannot be empty"));
         }
 
+        if let Some(value) = self.cache.get(&req.key) {
+            return Ok(Response::new(GetResponse {
+                success: true,
+                value,
+                message: "Key found".to_string(),
+            }));
+        }
+
         let db = get_db()?;
         let db_lock = db.lock().await;
 
@@ -160,6 +205,9 @@ impl RocksDbService for RocksDbServiceImpl {
             Ok(Some(value)) => match String::from_utf8(value) {
                 Ok(value_str) => {
                     info!("Successfully retrieved key: '{}'", req.key);
+                    // Populate while still holding the DB lock so a concurrent
+                    // write cannot slip in between the read and the fill
+                    self.cache.insert(&req.key, &value_str);
                     Ok(Response::new(GetResponse {
                         success: true,
                         value: value_str,
@@ -190,6 +238,7 @@ impl RocksDbService for RocksDbServiceImpl {
         &self,
         request: Request<DeleteRequest>,
     ) -> Result<Response<DeleteResponse>, Status> {
+        auth::authorize(&request, Access::Write, &request.get_ref().key)?;
         let req = request.into_inner();
 
         if req.key.is_empty() {
@@ -199,6 +248,8 @@ impl RocksDbService for RocksDbServiceImpl {
         let db = get_db()?;
         let db_lock = db.lock().await;
 
+        self.cache.invalidate(&req.key);
+
         match db_lock.delete(req.key.as_bytes()) {
             Ok(()) => {
                 info!("Successfully deleted key: '{}'", req.key);
@@ -218,6 +269,12 @@ impl RocksDbService for RocksDbServiceImpl {
         &self,
         request: Request<BatchPutRequest>,
     ) -> Result<Response<BatchPutResponse>, Status> {
+        // Every pair is checked, a batch with one denied pair writes nothing
+        for (index, item) in request.get_ref().pairs.iter().enumerate() {
+            auth::authorize(&request, Access::Write, &item.key).map_err(|status| {
+                Status::permission_denied(format!("pair {}: {}", index, status.message()))
+            })?;
+        }
         let req = request.into_inner();
 
         if req.pairs.is_empty() {
@@ -247,6 +304,7 @@ impl RocksDbService for RocksDbServiceImpl {
 
         for item in &req.pairs {
             batch.put(item.key.as_bytes(), item.value.as_bytes());
+            self.cache.invalidate(&item.key);
         }
 
         match db_lock.write(batch) {
@@ -272,6 +330,7 @@ impl RocksDbService for RocksDbServiceImpl {
         &self,
         request: Request<GetByPrefixRequest>,
     ) -> Result<Response<GetByPrefixResponse>, Status> {
+        auth::authorize(&request, Access::Read, &request.get_ref().prefix)?;
         let req = request.into_inner();
 
         if req.prefix.is_empty() {
@@ -315,6 +374,7 @@ impl RocksDbService for RocksDbServiceImpl {
         &self,
         request: Request<ListKeysRequest>,
     ) -> Result<Response<ListKeysResponse>, Status> {
+        auth::authorize(&request, Access::Read, &request.get_ref().prefix)?;
         let req = request.into_inner();
 
         let db = get_db()?;
@@ -364,21 +424,221 @@ async fn main() -> anyhow::Result<()> {
 
     let args = Args::parse();
 
+    let tokens =
+        Tokens::load(args.tokens_file.as_deref(), &args.tokens).map_err(|e| anyhow::anyhow!(e))?;
+
     // Initialize RocksDB
     init_db(&args.path)?;
 
     let bind_addr = format!("{}:{}", args.addr, args.port).parse()?;
-    let rocksdb_service = RocksDbServiceImpl;
+    let rocksdb_service = RocksDbServiceImpl::new(args.cache_capacity);
 
     info!("🚀 RocksDB gRPC Service starting on {}", bind_addr);
     info!("📁 Database path: {}", args.path);
+    info!("🗃️ Read cache capacity: {}", args.cache_capacity);
     info!("🔗 gRPC endpoint: grpc://{}", bind_addr);
+    if tokens.is_empty() {
+        warn!("No access tokens configured, every client may read and write any key");
+    } else {
+        info!("🔒 {} access tokens configured", tokens.len());
+    }
+
+    // Standard grpc.health.v1 service, SERVING while the database is open
+    let (health, health_service) = ServerHealth::new::<RocksDbServiceServer<RocksDbServiceImpl>>();
+    health.spawn_probe(PROBE_INTERVAL, || async { DB_INSTANCE.get().is_some() });
 
     // Start the gRPC server
-    Server::builder()
-        .add_service(RocksDbServiceServer::new(rocksdb_service))
-        .serve(bind_addr)
+    let router = Server::builder()
+        .layer(common::trace::TraceLayer)
+        .add_service(health_service)
+        .add_service(common::version::service("rocksdbservice"));
+    let router = match Authenticator::new(tokens) {
+        Some(authenticator) => router.add_service(RocksDbServiceServer::with_interceptor(
+            rocksdb_service,
+            authenticator,
+        )),
+        None => router.add_service(RocksDbServiceServer::new(rocksdb_service)),
+    };
+    router
+        .serve_with_shutdown(bind_addr, health.shutdown_signal())
         .await?;
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // The DB instance is process-global, so every test shares one database
+    fn ensure_db() {
+        if DB_INSTANCE.get().is_none() {
+            let path = std::env::temp_dir().join(format!(
+                "pullpiri_rocksdbservice_test_{}",
+                std::process::id()
+            ));
+            let _ = init_db(&path.to_string_lossy());
+        }
+    }
+
+    async fn get_value(service: &RocksDbServiceImpl, key: &str) -> String {
+        let response = service
+            .get(Request::new(GetRequest {
+                key: key.to_string(),
+            }))
+            .await;
+        match response {
+            Ok(resp) => resp.into_inner().value,
+            Err(status) => panic!("get failed: {}", status),
+        }
+    }
+
+    async fn put_value(service: &RocksDbServiceImpl, key: &str, value: &str) {
+        let response = service
+            .put(Request::new(PutRequest {
+                key: key.to_string(),
+                value: value.to_string(),
+            }))
+            .await;
+        assert!(response.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_get_populates_cache_on_miss_and_serves_hit() {
+        ensure_db();
+        let service = RocksDbServiceImpl::new(16);
+        put_value(&service, "cache_test/hit", "value1").await;
+
+        assert_eq!(get_value(&service, "cache_test/hit").await, "value1");
+        assert_eq!(get_value(&service, "cache_test/hit").await, "value1");
+
+        assert_eq!(service.cache.stats(), (1, 1));
+    }
+
+    #[tokio::test]
+    async fn test_put_invalidates_cached_value() {
+        ensure_db();
+        let service = RocksDbServiceImpl::new(16);
+        put_value(&service, "cache_test/put", "old").await;
+        assert_eq!(get_value(&service, "cache_test/put").await, "old");
+
+        put_value(&service, "cache_test/put", "new").await;
+
+        assert_eq!(get_value(&service, "cache_test/put").await, "new");
+    }
+
+    #[tokio::test]
+    async fn test_batch_put_and_delete_invalidate_cached_values() {
+        ensure_db();
+        let service = RocksDbServiceImpl::new(16);
+        put_value(&service, "cache_test/batch", "old").await;
+        assert_eq!(get_value(&service, "cache_test/batch").await, "old");
+
+        let batch = service
+            .batch_put(Request::new(BatchPutRequest {
+                pairs: vec![KeyValue {
+                    key: "cache_test/batch".to_string(),
+                    value: "new".to_string(),
+                }],
+            }))
+            .await;
+        assert!(batch.is_ok());
+        assert_eq!(get_value(&service, "cache_test/batch").await, "new");
+
+        let deleted = service
+            .delete(Request::new(DeleteRequest {
+                key: "cache_test/batch".to_string(),
+            }))
+            .await;
+        assert!(deleted.is_ok());
+        assert!(service.cache.get("cache_test/batch").is_none());
+    }
+
+    /// Request carrying the ACL the interceptor attaches for a monitoring token
+    fn as_monitoring<T>(message: T) -> Request<T> {
+        let mut request = Request::new(message);
+        request.extensions_mut().insert(Arc::new(
+            auth::Acl::parse("write:/metrics/,write:/logs/,read:*").unwrap(),
+        ));
+        request
+    }
+
+    #[tokio::test]
+    async fn test_acl_allows_write_under_prefix() {
+        ensure_db();
+        let service = RocksDbServiceImpl::new(16);
+
+        let response = service
+            .put(as_monitoring(PutRequest {
+                key: "/metrics/acl_test/cpu".to_string(),
+                value: "42".to_string(),
+            }))
+            .await;
+
+        assert!(response.is_ok());
+        assert_eq!(get_value(&service, "/metrics/acl_test/cpu").await, "42");
+    }
+
+    #[tokio::test]
+    async fn test_acl_denies_write_outside_prefix() {
+        ensure_db();
+        let service = RocksDbServiceImpl::new(16);
+        put_value(&service, "acl_test/Scenario/helloworld", "kept").await;
+
+        let status = service
+            .delete(as_monitoring(DeleteRequest {
+                key: "acl_test/Scenario/helloworld".to_string(),
+            }))
+            .await
+            .unwrap_err();
+
+        assert_eq!(status.code(), tonic::Code::PermissionDenied);
+        assert!(status.message().contains("[/metrics/, /logs/]"));
+        assert_eq!(
+            get_value(&service, "acl_test/Scenario/helloworld").await,
+            "kept"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_acl_denies_mixed_batch_entirely() {
+        ensure_db();
+        let service = RocksDbServiceImpl::new(16);
+        let pair = |key: &str| KeyValue {
+            key: key.to_string(),
+            value: "batch".to_string(),
+        };
+
+        let status = service
+            .batch_put(as_monitoring(BatchPutRequest {
+                pairs: vec![
+                    pair("/metrics/acl_test/mixed"),
+                    pair("acl_test/Model/mixed"),
+                    pair("/logs/acl_test/mixed"),
+                ],
+            }))
+            .await
+            .unwrap_err();
+
+        assert_eq!(status.code(), tonic::Code::PermissionDenied);
+        assert!(status
+            .message()
+            .starts_with("pair 1: write access to 'acl_test/Model/mixed' denied"));
+        // The allowed pairs of a denied batch are not written either
+        let response = service
+            .get(Request::new(GetRequest {
+                key: "/metrics/acl_test/mixed".to_string(),
+            }))
+            .await
+            .unwrap();
+        assert!(!response.into_inner().success);
+
+        let allowed = service
+            .batch_put(as_monitoring(BatchPutRequest {
+                pairs: vec![pair("/metrics/acl_test/all"), pair("/logs/acl_test/all")],
+            }))
+            .await;
+        assert!(allowed.is_ok());
+        assert_eq!(get_value(&service, "/logs/acl_test/all").await, "batch");
+    }
+}
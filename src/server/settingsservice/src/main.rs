@@ -27,6 +27,7 @@ mod settings_monitoring;
 mod settings_storage;
 mod settings_utils;
 use settings_core::CoreManager;
+use settings_storage::{BackendKind, StorageConfig};
 use settings_utils::logging::init_logging;
 
 /// Settings Service command line arguments
@@ -42,6 +43,16 @@ struct Args {
     #[arg(long, default_value = "localhost:2379")]
     etcd_endpoints: String,
 
+    /// Storage backend for settings data, overriding `storage.backend` of the
+    /// configuration file (default: etcd)
+    #[arg(long, value_enum, env = "SETTINGS_STORAGE_BACKEND")]
+    storage_backend: Option<BackendKind>,
+
+    /// Comma-separated RocksDB service URLs, overriding `storage.rocksdb_url`
+    /// of the configuration file (default: http://localhost:47007)
+    #[arg(long, env = "ROCKSDB_SERVICE_URL")]
+    rocksdb_url: Option<String>,
+
     /// HTTP server bind address
     #[arg(long, default_value = "0.0.0.0")]
     bind_address: String,
@@ -83,8 +94,13 @@ async fn run_server_mode(args: Args) -> Result<()> {
         .map(|s| s.trim().to_string())
         .collect();
 
+    // Select the storage backend, the command line overriding the file
+    let storage_config = storage_config(&args)?;
+    info!("Storage backend: {:?}", storage_config.backend);
+
     // Initialize core manager
-    let mut core_manager = CoreManager::new(
+    let mut core_manager = CoreManager::from_config(
+        &storage_config,
         etcd_endpoints,
         args.bind_address.clone(),
         args.bind_port,
@@ -115,6 +131,18 @@ async fn run_server_mode(args: Args) -> Result<()> {
     Ok(())
 }
 
+/// `storage` section of the configuration file with the command line overrides applied
+fn storage_config(args: &Args) -> Result<StorageConfig> {
+    let mut storage_config = StorageConfig::load(&args.config)?;
+    if let Some(backend) = args.storage_backend {
+        storage_config.backend = backend;
+    }
+    if let Some(rocksdb_url) = &args.rocksdb_url {
+        storage_config.rocksdb_url = rocksdb_url.clone();
+    }
+    Ok(storage_config)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -131,6 +159,51 @@ mod tests {
         assert_eq!(args.bind_address, "0.0.0.0");
         assert_eq!(args.bind_port, 8080);
         assert_eq!(args.log_level, "info");
+        assert_eq!(args.storage_backend, None);
+        assert_eq!(args.rocksdb_url, None);
+    }
+
+    #[test]
+    fn test_args_storage_backend() {
+        let args = Args::parse_from([
+            "settingsservice",
+            "--storage-backend",
+            "rocksdb",
+            "--rocksdb-url",
+            "http://rocksdb:47007",
+        ]);
+
+        assert_eq!(args.storage_backend, Some(BackendKind::Rocksdb));
+        assert_eq!(args.rocksdb_url.as_deref(), Some("http://rocksdb:47007"));
+        assert!(Args::try_parse_from(["settingsservice", "--storage-backend", "redis"]).is_err());
+    }
+
+    #[test]
+    fn test_storage_config_command_line_overrides_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let config = dir.path().join("settings.yaml");
+        std::fs::write(
+            &config,
+            "storage:\n  backend: rocksdb\n  rocksdb_url: http://rocksdb:47007\n",
+        )
+        .unwrap();
+        let config = config.to_str().unwrap();
+
+        let args = Args::parse_from(["settingsservice", "--config", config]);
+        let storage = storage_config(&args).unwrap();
+        assert_eq!(storage.backend, BackendKind::Rocksdb);
+        assert_eq!(storage.rocksdb_url, "http://rocksdb:47007");
+
+        let args = Args::parse_from([
+            "settingsservice",
+            "--config",
+            config,
+            "--storage-backend",
+            "etcd",
+        ]);
+        let storage = storage_config(&args).unwrap();
+        assert_eq!(storage.backend, BackendKind::Etcd);
+        assert_eq!(storage.rocksdb_url, "http://rocksdb:47007");
     }
 
     #[test]
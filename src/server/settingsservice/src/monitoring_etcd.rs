@@ -4,6 +4,7 @@
 //! Integration with monitoring server's etcd storage
 
 use crate::monitoring_types::{BoardInfo, NodeInfo, SocInfo, StressMetrics};
+use common::keys;
 use common::monitoringserver::ContainerInfo;
 use serde::{de::DeserializeOwned, Serialize};
 use thiserror::Error;
@@ -28,7 +29,7 @@ type Result<T> = std::result::Result<T, MonitoringEtcdError>;
 
 /// Generic function to store info in etcd
 async fn store_info<T: Serialize>(resource_type: &str, resource_id: &str, info: &T) -> Result<()> {
-    let key = format!("/piccolo/metrics/{}/{}", resource_type, resource_id);
+    let key = keys::metrics(resource_type, resource_id);
     let json_data = serde_json::to_string(info)?;
 
     common::etcd::put(&key, &json_data)
@@ -41,7 +42,7 @@ async fn store_info<T: Serialize>(resource_type: &str, resource_id: &str, info:
 
 /// Generic function to retrieve info from etcd
 async fn get_info<T: DeserializeOwned>(resource_type: &str, resource_id: &str) -> Result<T> {
-    let key = format!("/piccolo/metrics/{}/{}", resource_type, resource_id);
+    let key = keys::metrics(resource_type, resource_id);
 
     let json_data = common::etcd::get(&key)
         .await
@@ -55,7 +56,7 @@ async fn get_info<T: DeserializeOwned>(resource_type: &str, resource_id: &str) -
 
 /// Generic function to get all items of a type from etcd
 async fn get_all_info<T: DeserializeOwned>(resource_type: &str) -> Result<Vec<T>> {
-    let prefix = format!("/piccolo/metrics/{}/", resource_type);
+    let prefix = keys::metrics_prefix(resource_type);
     let kv_pairs = common::etcd::get_all_with_prefix(&prefix)
         .await
         .map_err(|e| MonitoringEtcdError::EtcdOperation(e.to_string()))?;
@@ -78,7 +79,7 @@ async fn get_all_info<T: DeserializeOwned>(resource_type: &str) -> Result<Vec<T>
 
 /// Generic function to delete info from etcd
 async fn delete_info(resource_type: &str, resource_id: &str) -> Result<()> {
-    let key = format!("/piccolo/metrics/{}/{}", resource_type, resource_id);
+    let key = keys::metrics(resource_type, resource_id);
 
     common::etcd::delete(&key)
         .await
@@ -202,7 +203,7 @@ pub async fn get_container_logs(container_id: &str) -> Result<Vec<String>> {
 
 /// Generic function to get logs from etcd
 async fn get_logs(resource_type: &str, resource_id: &str) -> Result<Vec<String>> {
-    let prefix = format!("/piccolo/logs/{}/{}", resource_type, resource_id);
+    let prefix = keys::logs_prefix(resource_type, resource_id);
     let kv_pairs = common::etcd::get_all_with_prefix(&prefix)
         .await
         .map_err(|e| MonitoringEtcdError::EtcdOperation(e.to_string()))?;
@@ -250,7 +251,7 @@ async fn store_metadata(
     resource_id: &str,
     metadata: &serde_json::Value,
 ) -> Result<()> {
-    let key = format!("/piccolo/metadata/{}/{}", resource_type, resource_id);
+    let key = keys::metadata(resource_type, resource_id);
     let value = serde_json::to_string(metadata)?;
 
     common::etcd::put(&key, &value)
@@ -264,6 +265,7 @@ async fn store_metadata(
 #[cfg(test)]
 mod tests {
     use super::*;
+    use common::keys;
     use common::monitoringserver::ContainerInfo;
     use serde_json::json;
     use std::collections::HashMap;
@@ -384,6 +386,7 @@ mod tests {
             os: "Linux".to_string(),
             arch: "x86_64".to_string(),
             ip: "192.168.1.100".to_string(),
+            dropped_container_updates: 0,
         }
     }
 
@@ -403,6 +406,7 @@ mod tests {
             os: "Linux".to_string(),
             arch: "x86_64".to_string(),
             ip: "192.168.1.100".to_string(),
+            dropped_container_updates: 0,
         };
 
         SocInfo {
@@ -418,6 +422,7 @@ mod tests {
             total_tx_bytes: 2048,
             total_read_bytes: 4096,
             total_write_bytes: 8192,
+            temperature: None,
             last_updated: std::time::SystemTime::now(),
         }
     }
@@ -438,6 +443,7 @@ mod tests {
             os: "Linux".to_string(),
             arch: "x86_64".to_string(),
             ip: "192.168.1.100".to_string(),
+            dropped_container_updates: 0,
         };
 
         let test_soc = SocInfo {
@@ -453,6 +459,7 @@ mod tests {
             total_tx_bytes: 2048,
             total_read_bytes: 4096,
             total_write_bytes: 8192,
+            temperature: None,
             last_updated: std::time::SystemTime::now(),
         };
 
@@ -495,6 +502,7 @@ mod tests {
             config,
             annotation,
             stats,
+            usage: None,
         }
     }
 
@@ -558,23 +566,23 @@ mod tests {
         // Test the key format patterns used in the functions
         let resource_type = "nodes";
         let resource_id = "test-node";
-        let expected_key = format!("/piccolo/metrics/{}/{}", resource_type, resource_id);
+        let expected_key = keys::metrics(resource_type, resource_id);
         assert_eq!(expected_key, "/piccolo/metrics/nodes/test-node");
 
-        let logs_key = format!("/piccolo/logs/{}/{}", resource_type, resource_id);
+        let logs_key = keys::logs_prefix(resource_type, resource_id);
         assert_eq!(logs_key, "/piccolo/logs/nodes/test-node");
 
-        let metadata_key = format!("/piccolo/metadata/{}/{}", resource_type, resource_id);
+        let metadata_key = keys::metadata(resource_type, resource_id);
         assert_eq!(metadata_key, "/piccolo/metadata/nodes/test-node");
     }
 
     #[test]
     fn test_prefix_format_generation() {
         let resource_type = "containers";
-        let prefix = format!("/piccolo/metrics/{}/", resource_type);
+        let prefix = keys::metrics_prefix(resource_type);
         assert_eq!(prefix, "/piccolo/metrics/containers/");
 
-        let logs_prefix = format!("/piccolo/logs/{}/{}", resource_type, "container-id");
+        let logs_prefix = keys::logs_prefix(resource_type, "container-id");
         assert_eq!(logs_prefix, "/piccolo/logs/containers/container-id");
     }
 
@@ -23,6 +23,10 @@ pub struct NodeInfo {
     pub os: String,
     pub arch: String,
     pub ip: String,
+    /// Container lists the NodeAgent dropped because MonitoringServer was
+    /// not keeping up, since the NodeAgent started
+    #[serde(default)]
+    pub dropped_container_updates: u64,
 }
 
 /// JSON types for StressMonitoringMetric payload
@@ -57,6 +61,9 @@ pub struct SocInfo {
     pub total_tx_bytes: u64,
     pub total_read_bytes: u64,
     pub total_write_bytes: u64,
+    /// Temperature in degrees Celsius, when the SoC reports one
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub temperature: Option<f64>,
     pub last_updated: std::time::SystemTime,
 }
 
@@ -96,6 +103,7 @@ impl From<common::monitoringserver::NodeInfo> for NodeInfo {
             os: proto_node.os,
             arch: proto_node.arch,
             ip: proto_node.ip,
+            dropped_container_updates: proto_node.dropped_container_updates,
         }
     }
 }
@@ -117,6 +125,7 @@ impl From<NodeInfo> for common::monitoringserver::NodeInfo {
             os: val.os,
             arch: val.arch,
             ip: val.ip,
+            dropped_container_updates: val.dropped_container_updates,
         }
     }
 }
@@ -143,6 +152,7 @@ mod tests {
             os: "Linux".to_string(),
             arch: "x86_64".to_string(),
             ip: "192.168.1.100".to_string(),
+            dropped_container_updates: 0,
         }
     }
 
@@ -165,6 +175,7 @@ mod tests {
             total_tx_bytes: 4096000,
             total_read_bytes: 8192000,
             total_write_bytes: 16384000,
+            temperature: None,
             last_updated: SystemTime::now(),
         }
     }
@@ -394,6 +405,7 @@ mod tests {
             os: "Ubuntu".to_string(),
             arch: "arm64".to_string(),
             ip: "10.0.0.1".to_string(),
+            dropped_container_updates: 0,
         };
 
         let node_info: NodeInfo = proto_node.into();
@@ -431,6 +443,7 @@ mod tests {
             os: "CentOS".to_string(),
             arch: "x86_64".to_string(),
             ip: "172.16.0.1".to_string(),
+            dropped_container_updates: 0,
         };
 
         let proto_node: common::monitoringserver::NodeInfo = node_info.into();
@@ -468,6 +481,7 @@ mod tests {
             os: "RHEL".to_string(),
             arch: "aarch64".to_string(),
             ip: "192.168.100.1".to_string(),
+            dropped_container_updates: 7,
         };
 
         // Proto -> NodeInfo -> Proto
@@ -480,6 +494,17 @@ mod tests {
         assert_eq!(original_proto.os, converted_proto.os);
         assert_eq!(original_proto.arch, converted_proto.arch);
         assert_eq!(original_proto.ip, converted_proto.ip);
+        assert_eq!(converted_proto.dropped_container_updates, 7);
+    }
+
+    #[test]
+    fn test_node_info_without_dropped_count() {
+        // Stored by a NodeAgent that did not report dropped container lists
+        let json = r#"{"node_name":"old-node","cpu_usage":1.0,"cpu_count":2,"gpu_count":0,
+            "used_memory":1,"total_memory":2,"mem_usage":50.0,"rx_bytes":0,"tx_bytes":0,
+            "read_bytes":0,"write_bytes":0,"os":"linux","arch":"x86_64","ip":"10.0.0.1"}"#;
+        let node: NodeInfo = serde_json::from_str(json).unwrap();
+        assert_eq!(node.dropped_container_updates, 0);
     }
 
     #[test]
@@ -497,6 +522,7 @@ mod tests {
             total_tx_bytes: 0,
             total_read_bytes: 0,
             total_write_bytes: 0,
+            temperature: None,
             last_updated: SystemTime::now(),
         };
 
@@ -543,6 +569,7 @@ mod tests {
             os: "".to_string(),        // Empty string
             arch: "".to_string(),      // Empty string
             ip: "0.0.0.0".to_string(), // Minimum IP
+            dropped_container_updates: u64::MAX,
         };
 
         // Test serialization with extreme values
@@ -575,6 +602,7 @@ mod tests {
             total_tx_bytes: 0,
             total_read_bytes: 0,
             total_write_bytes: 0,
+            temperature: None,
             last_updated: now,
         };
 
@@ -0,0 +1,159 @@
+// SPDX-FileCopyrightText: Copyright 2024 LG Electronics Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Response encoding negotiation
+//!
+//! Handlers answer in JSON. A client sending `Accept: application/cbor`
+//! gets the same document encoded as CBOR instead, which is much smaller
+//! on constrained in-vehicle links.
+
+use crate::settings_utils::error::SettingsError;
+use axum::{
+    body::{to_bytes, Body},
+    extract::Request,
+    http::{header, HeaderMap, HeaderValue},
+    middleware::Next,
+    response::Response,
+};
+use serde::Serialize;
+use serde_json::Value;
+use tracing::warn;
+
+/// Media type of CBOR documents
+pub const CBOR: &str = "application/cbor";
+
+/// Whether the `Accept` header asks for CBOR
+///
+/// The q-values are not ranked: listing `application/cbor` with a non-zero
+/// quality is enough. Anything else keeps the default JSON.
+pub fn accepts_cbor(headers: &HeaderMap) -> bool {
+    headers
+        .get_all(header::ACCEPT)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .any(|range| {
+            let mut params = range.split(';').map(str::trim);
+            let media_type = params.next().unwrap_or_default();
+            let rejected = params.any(|param| {
+                param
+                    .strip_prefix("q=")
+                    .and_then(|q| q.parse::<f32>().ok())
+                    .is_some_and(|q| q == 0.0)
+            });
+            media_type.eq_ignore_ascii_case(CBOR) && !rejected
+        })
+}
+
+/// Encode `value` as CBOR
+pub fn to_cbor<T: Serialize>(value: &T) -> Result<Vec<u8>, SettingsError> {
+    let mut bytes = Vec::new();
+    ciborium::into_writer(value, &mut bytes)
+        .map_err(|e| SettingsError::Api(format!("CBOR encoding failed: {}", e)))?;
+    Ok(bytes)
+}
+
+/// Decode a CBOR document
+#[cfg(test)]
+pub fn from_cbor<T: serde::de::DeserializeOwned>(bytes: &[u8]) -> Result<T, SettingsError> {
+    ciborium::from_reader(bytes)
+        .map_err(|e| SettingsError::Api(format!("CBOR decoding failed: {}", e)))
+}
+
+/// Middleware re-encoding JSON responses as CBOR for clients asking for it
+///
+/// Responses that are not JSON, e.g. empty ones, are passed through.
+pub async fn negotiate(request: Request, next: Next) -> Response {
+    let wants_cbor = accepts_cbor(request.headers());
+    let mut response = next.run(request).await;
+    response
+        .headers_mut()
+        .append(header::VARY, HeaderValue::from_static("accept"));
+
+    let is_json = response
+        .headers()
+        .get(header::CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .is_some_and(|value| value.starts_with("application/json"));
+    if !wants_cbor || !is_json {
+        return response;
+    }
+
+    let (mut parts, body) = response.into_parts();
+    let bytes = match to_bytes(body, usize::MAX).await {
+        Ok(bytes) => bytes,
+        Err(e) => {
+            warn!("Failed to read response body for CBOR encoding: {}", e);
+            return Response::from_parts(parts, Body::empty());
+        }
+    };
+    let cbor = serde_json::from_slice::<Value>(&bytes)
+        .map_err(|e| SettingsError::Api(e.to_string()))
+        .and_then(|value| to_cbor(&value));
+    match cbor {
+        Ok(cbor) => {
+            parts
+                .headers
+                .insert(header::CONTENT_TYPE, HeaderValue::from_static(CBOR));
+            parts.headers.remove(header::CONTENT_LENGTH);
+            Response::from_parts(parts, Body::from(cbor))
+        }
+        Err(e) => {
+            warn!("Answering in JSON, CBOR encoding failed: {}", e);
+            Response::from_parts(parts, Body::from(bytes))
+        }
+    }
+}
+
+//Unit Test Cases
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::settings_config::{Config, ConfigMetadata};
+    use chrono::Utc;
+    use serde_json::json;
+
+    fn accept(value: &str) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert(header::ACCEPT, value.parse().unwrap());
+        headers
+    }
+
+    #[test]
+    fn test_accepts_cbor() {
+        assert!(accepts_cbor(&accept("application/cbor")));
+        assert!(accepts_cbor(&accept(
+            "application/json;q=0.5, Application/CBOR"
+        )));
+        assert!(!accepts_cbor(&accept("application/cbor;q=0")));
+        assert!(!accepts_cbor(&accept("application/json")));
+        assert!(!accepts_cbor(&accept("*/*")));
+        assert!(!accepts_cbor(&HeaderMap::new()));
+    }
+
+    #[test]
+    fn test_cbor_round_trip() {
+        let now = Utc::now();
+        let config = Config {
+            path: "vehicle/speed".to_string(),
+            content: json!({"limit": 90, "unit": "km/h", "zones": [1, 2], "strict": true}),
+            metadata: ConfigMetadata {
+                version: 3,
+                created_at: now,
+                modified_at: now,
+                author: "operator".to_string(),
+                comment: None,
+                schema_type: "json".to_string(),
+            },
+        };
+
+        let bytes = to_cbor(&config).unwrap();
+        let decoded: Config = from_cbor(&bytes).unwrap();
+
+        assert_eq!(
+            serde_json::to_value(&decoded).unwrap(),
+            serde_json::to_value(&config).unwrap()
+        );
+        assert!(bytes.len() < serde_json::to_vec(&config).unwrap().len());
+    }
+}
@@ -2,10 +2,14 @@
 // SPDX-License-Identifier: Apache-2.0
 
 //! REST API server module
+
+pub mod encoding;
+
 use crate::monitoring_etcd;
 use crate::monitoring_types::{BoardInfo, NodeInfo, SocInfo}; //, StressMetrics};
 use crate::settings_config::{Config, ConfigManager, ConfigSummary, ValidationResult};
-use crate::settings_history::{HistoryEntry, HistoryManager};
+use crate::settings_history::{HistoryEntry, HistoryFilter, HistoryManager, HistoryPage};
+use crate::settings_monitoring::summary::MetricsSummary;
 use crate::settings_monitoring::{
     BoardListResponse, FilterSummary, Metric, MetricsFilter, MonitoringManager, NodeListResponse,
     SocListResponse,
@@ -13,9 +17,9 @@ use crate::settings_monitoring::{
 use crate::settings_utils::error::SettingsError;
 use axum::{
     extract::{Path, Query, State},
-    http::StatusCode,
+    http::{header, HeaderMap, StatusCode},
     response::Json,
-    routing::{delete, get, post},
+    routing::{delete, get, post, put},
     Router,
 };
 use chrono::Utc;
@@ -50,9 +54,28 @@ pub struct MetricsQuery {
 }
 
 /// Query parameters for history API
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Default, Deserialize)]
 pub struct HistoryQuery {
     pub limit: Option<usize>,
+    pub offset: Option<usize>,
+    /// Config path prefix of the entries
+    pub key: Option<String>,
+    /// RFC 3339 time of the oldest entry
+    pub since: Option<chrono::DateTime<Utc>>,
+    /// RFC 3339 time of the newest entry
+    pub until: Option<chrono::DateTime<Utc>>,
+}
+
+impl From<HistoryQuery> for HistoryFilter {
+    fn from(query: HistoryQuery) -> Self {
+        HistoryFilter {
+            key: query.key,
+            since: query.since,
+            until: query.until,
+            offset: query.offset.unwrap_or(0),
+            limit: query.limit,
+        }
+    }
 }
 
 /// Query parameters for diff API
@@ -85,6 +108,14 @@ pub struct ConfigRequest {
     pub comment: Option<String>,
 }
 
+/// Request body for a conditional config update
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ConfigUpdateRequest {
+    pub content: Value,
+    pub author: String,
+    pub comment: Option<String>,
+}
+
 /// Response for successful operations
 #[derive(Debug, Serialize)]
 pub struct SuccessResponse {
@@ -200,10 +231,12 @@ impl ApiServer {
             .route("/api/v1/settings", get(list_configs))
             .route("/api/v1/settings/:path", get(get_config))
             .route("/api/v1/settings/:path", post(create_config))
+            .route("/api/v1/settings/:path", put(update_config))
             .route("/api/v1/settings/:path", delete(delete_config))
             .route("/api/v1/settings/validate", post(validate_config))
             .route("/api/v1/settings/schemas/:schema_type", get(get_schema))
             // History endpoints
+            .route("/api/v1/history", get(list_history))
             .route("/api/v1/history/:path", get(get_history))
             .route("/api/v1/history/:path/version/:version", get(get_version))
             .route(
@@ -241,6 +274,7 @@ impl ApiServer {
             .route("/api/v1/metrics/containers", get(get_all_container_metrics))
             .route("/api/v1/metrics/socs", get(get_all_soc_metrics))
             .route("/api/v1/metrics/boards", get(get_all_board_metrics))
+            .route("/api/v1/metrics/summary", get(get_metrics_summary))
             .route("/api/v1/metrics/nodes/:name", get(get_node_metric_by_name))
             // Stress metrics endpoints
             .route("/api/v1/metrics/stressmonitor", get(get_all_stress_metrics))
@@ -253,6 +287,7 @@ impl ApiServer {
                 get(get_container_metric_by_id),
             )
             .with_state(self.state.clone())
+            .layer(axum::middleware::from_fn(encoding::negotiate))
             .layer(CorsLayer::permissive())
     }
 }
@@ -417,16 +452,19 @@ async fn list_configs(
     }
 }
 
+/// Config with its entity tag in the `ETag` header
+type ConfigWithEtag = ([(header::HeaderName, String); 1], Json<Config>);
+
 async fn get_config(
     Path(path): Path<String>,
     State(state): State<ApiState>,
-) -> Result<Json<Config>, (StatusCode, Json<ErrorResponse>)> {
+) -> Result<ConfigWithEtag, (StatusCode, Json<ErrorResponse>)> {
     debug!("GET /api/v1/settings/{}", path);
 
     let mut config_manager = state.config_manager.write().await;
 
     match config_manager.load_config(&path).await {
-        Ok(config) => Ok(Json(config)),
+        Ok(config) => Ok(([(header::ETAG, config.etag())], Json(config))),
         Err(_) => Err(not_found_error("Configuration not found")),
     }
 }
@@ -435,7 +473,7 @@ async fn create_config(
     Path(path): Path<String>,
     State(state): State<ApiState>,
     Json(request): Json<ConfigRequest>,
-) -> Result<Json<Config>, (StatusCode, Json<ErrorResponse>)> {
+) -> Result<ConfigWithEtag, (StatusCode, Json<ErrorResponse>)> {
     debug!("POST /api/v1/settings/{}", path);
 
     let mut config_manager = state.config_manager.write().await;
@@ -452,7 +490,7 @@ async fn create_config(
         )
         .await
     {
-        Ok(config) => Ok(Json(config)),
+        Ok(config) => Ok(([(header::ETAG, config.etag())], Json(config))),
         Err(e) => Err(bad_request_error(&format!(
             "Failed to create config: {}",
             e
@@ -460,6 +498,60 @@ async fn create_config(
     }
 }
 
+/// Update a config the client has read before
+///
+/// `If-Match` must carry the `ETag` of the `GET` the update is based on.
+/// The update is rejected with 412 if the config changed since then, and
+/// with 428 if the header is missing, so concurrent edits never silently
+/// overwrite each other.
+async fn update_config(
+    Path(path): Path<String>,
+    State(state): State<ApiState>,
+    headers: HeaderMap,
+    Json(request): Json<ConfigUpdateRequest>,
+) -> Result<ConfigWithEtag, (StatusCode, Json<ErrorResponse>)> {
+    debug!("PUT /api/v1/settings/{}", path);
+
+    let Some(if_match) = headers
+        .get(header::IF_MATCH)
+        .and_then(|value| value.to_str().ok())
+    else {
+        return Err((
+            StatusCode::PRECONDITION_REQUIRED,
+            Json(ErrorResponse {
+                error: "If-Match header is required".to_string(),
+                details: None,
+            }),
+        ));
+    };
+
+    let mut config_manager = state.config_manager.write().await;
+    let mut history_manager = state.history_manager.write().await;
+
+    match config_manager
+        .update_config_if_match(
+            &path,
+            if_match,
+            request.content,
+            &request.author,
+            request.comment,
+            Some(&mut *history_manager),
+        )
+        .await
+    {
+        Ok(config) => Ok(([(header::ETAG, config.etag())], Json(config))),
+        Err(SettingsError::PreconditionFailed(e)) => Err((
+            StatusCode::PRECONDITION_FAILED,
+            Json(ErrorResponse {
+                error: "Configuration changed since it was read".to_string(),
+                details: Some(serde_json::json!({ "details": e })),
+            }),
+        )),
+        Err(SettingsError::Config(_)) => Err(not_found_error("Configuration not found")),
+        Err(e) => Err(internal_error(&format!("Failed to update config: {}", e))),
+    }
+}
+
 async fn delete_config(
     Path(path): Path<String>,
     State(state): State<ApiState>,
@@ -508,6 +600,20 @@ async fn get_schema(
 
 // History API handlers
 
+async fn list_history(
+    Query(query): Query<HistoryQuery>,
+    State(state): State<ApiState>,
+) -> Result<Json<HistoryPage>, (StatusCode, Json<ErrorResponse>)> {
+    debug!("GET /api/v1/history {:?}", query);
+
+    let mut history_manager = state.history_manager.write().await;
+
+    match history_manager.query_history(None, &query.into()).await {
+        Ok(page) => Ok(Json(page)),
+        Err(e) => Err(internal_error(&format!("Failed to get history: {}", e))),
+    }
+}
+
 async fn get_history(
     Path(path): Path<String>,
     Query(query): Query<HistoryQuery>,
@@ -517,8 +623,11 @@ async fn get_history(
 
     let mut history_manager = state.history_manager.write().await;
 
-    match history_manager.list_history(&path, query.limit).await {
-        Ok(history) => Ok(Json(history)),
+    match history_manager
+        .query_history(Some(&path), &query.into())
+        .await
+    {
+        Ok(page) => Ok(Json(page.entries)),
         Err(e) => Err(internal_error(&format!("Failed to get history: {}", e))),
     }
 }
@@ -1247,6 +1356,25 @@ async fn get_all_board_metrics(
     }
 }
 
+async fn get_metrics_summary(
+    State(state): State<ApiState>,
+) -> Result<Json<MetricsSummary>, (StatusCode, Json<ErrorResponse>)> {
+    debug!("GET /api/v1/metrics/summary");
+
+    let monitoring_manager = state.monitoring_manager.read().await;
+
+    match monitoring_manager.get_metrics_summary().await {
+        Ok(summary) => Ok(Json(summary)),
+        Err(e) => {
+            error!("Failed to get metrics summary: {}", e);
+            Err(internal_error(&format!(
+                "Failed to get metrics summary: {}",
+                e
+            )))
+        }
+    }
+}
+
 async fn get_all_stress_metrics(
     Query(query): Query<MetricsQuery>,
     State(_state): State<ApiState>,
@@ -1401,7 +1529,7 @@ mod tests {
     use crate::settings_utils::error::SettingsError;
     use crate::settings_utils::error::StorageError;
     use async_trait::async_trait;
-    use axum::http::StatusCode;
+    use axum::http::{HeaderValue, StatusCode};
     use axum::Router;
     use axum_test::TestServer;
     use chrono::Utc;
@@ -1640,6 +1768,18 @@ mod tests {
 
         let query = query.unwrap();
         assert_eq!(query.limit, Some(50));
+
+        let query: HistoryQuery =
+            serde_urlencoded::from_str("limit=10&offset=20&key=system/&since=2024-01-01T00:00:00Z")
+                .unwrap();
+        let filter = HistoryFilter::from(query);
+        assert_eq!(filter.offset, 20);
+        assert_eq!(filter.key.as_deref(), Some("system/"));
+        assert_eq!(
+            filter.since.unwrap().to_rfc3339(),
+            "2024-01-01T00:00:00+00:00"
+        );
+        assert!(filter.until.is_none());
     }
 
     #[tokio::test]
@@ -1912,11 +2052,15 @@ mod tests {
     async fn test_history_query_edge_cases() {
         let query = HistoryQuery {
             limit: Some(usize::MAX),
+            ..Default::default()
         };
 
         assert_eq!(query.limit.unwrap(), usize::MAX);
 
-        let zero_query = HistoryQuery { limit: Some(0) };
+        let zero_query = HistoryQuery {
+            limit: Some(0),
+            ..Default::default()
+        };
         assert_eq!(zero_query.limit.unwrap(), 0);
     }
 
@@ -2069,7 +2213,10 @@ mod tests {
         assert!(debug_str.contains("MetricsQuery"));
         assert!(debug_str.contains("test"));
 
-        let history_query = HistoryQuery { limit: Some(50) };
+        let history_query = HistoryQuery {
+            limit: Some(50),
+            ..Default::default()
+        };
         let debug_str = format!("{:?}", history_query);
         assert!(debug_str.contains("HistoryQuery"));
 
@@ -2495,6 +2642,95 @@ mod tests {
         );
     }
 
+    #[tokio::test]
+    async fn test_update_config_handler_if_match() {
+        let server = create_test_server().await;
+        let config_request = ConfigRequest {
+            content: json!({"key": "value"}),
+            schema_type: "json".to_string(),
+            author: "test_user".to_string(),
+            comment: None,
+        };
+        server
+            .post("/api/v1/settings/etag-config")
+            .json(&config_request)
+            .await
+            .assert_status_ok();
+
+        let response = server.get("/api/v1/settings/etag-config").await;
+        let etag = response.header(header::ETAG).to_str().unwrap().to_string();
+        let update = ConfigUpdateRequest {
+            content: json!({"key": "new value"}),
+            author: "operator_a".to_string(),
+            comment: None,
+        };
+
+        let response = server
+            .put("/api/v1/settings/etag-config")
+            .add_header(header::IF_MATCH, etag.parse::<HeaderValue>().unwrap())
+            .json(&update)
+            .await;
+        response.assert_status_ok();
+        assert_ne!(response.header(header::ETAG).to_str().unwrap(), etag);
+        assert_eq!(response.json::<Config>().metadata.version, 2);
+
+        // A second operator still holding the first ETag must not overwrite it
+        let response = server
+            .put("/api/v1/settings/etag-config")
+            .add_header(header::IF_MATCH, etag.parse::<HeaderValue>().unwrap())
+            .json(&update)
+            .await;
+        assert_eq!(response.status_code(), StatusCode::PRECONDITION_FAILED);
+
+        let response = server
+            .put("/api/v1/settings/etag-config")
+            .json(&update)
+            .await;
+        assert_eq!(response.status_code(), StatusCode::PRECONDITION_REQUIRED);
+
+        let response = server
+            .put("/api/v1/settings/missing-config")
+            .add_header(header::IF_MATCH, "*".parse::<HeaderValue>().unwrap())
+            .json(&update)
+            .await;
+        assert_eq!(response.status_code(), StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn test_get_config_handler_cbor() {
+        let server = create_test_server().await;
+        let config_request = ConfigRequest {
+            content: json!({"key": "value", "nested": {"n": 1}}),
+            schema_type: "json".to_string(),
+            author: "test_user".to_string(),
+            comment: None,
+        };
+        server
+            .post("/api/v1/settings/cbor-config")
+            .json(&config_request)
+            .await
+            .assert_status_ok();
+
+        let response = server
+            .get("/api/v1/settings/cbor-config")
+            .add_header(
+                header::ACCEPT,
+                encoding::CBOR.parse::<HeaderValue>().unwrap(),
+            )
+            .await;
+        response.assert_status_ok();
+        assert_eq!(
+            response.header(header::CONTENT_TYPE).to_str().unwrap(),
+            encoding::CBOR
+        );
+        let config: Config = encoding::from_cbor(response.as_bytes()).unwrap();
+        assert_eq!(config.content, config_request.content);
+
+        // JSON stays the default
+        let response = server.get("/api/v1/settings/cbor-config").await;
+        assert_eq!(response.json::<Config>().content, config_request.content);
+    }
+
     #[tokio::test]
     async fn test_delete_config_handler() {
         let server = create_test_server().await;
@@ -2559,6 +2795,16 @@ mod tests {
         let response = server.get("/api/v1/history/test-config?limit=10").await;
         // Should succeed with empty result
         assert!(response.status_code().is_success() || response.status_code().is_server_error());
+
+        // Test paged history of all configurations
+        let response = server
+            .get("/api/v1/history?limit=10&offset=10&key=test&since=2024-01-01T00:00:00Z")
+            .await;
+        assert!(response.status_code().is_success() || response.status_code().is_server_error());
+
+        // An invalid time is rejected
+        let response = server.get("/api/v1/history?since=yesterday").await;
+        assert!(response.status_code().is_client_error());
     }
 
     #[tokio::test]
@@ -2706,7 +2952,10 @@ mod tests {
         let state = create_test_state().await;
 
         // Test get_history with limit
-        let query = HistoryQuery { limit: Some(5) };
+        let query = HistoryQuery {
+            limit: Some(5),
+            ..Default::default()
+        };
         let result = get_history(
             axum::extract::Path("test-config".to_string()),
             axum::extract::Query(query),
@@ -32,6 +32,29 @@ pub struct Config {
     pub metadata: ConfigMetadata,
 }
 
+impl Config {
+    /// Entity tag of the stored configuration, for `ETag` and `If-Match`
+    ///
+    /// Changes with every update, and also when a configuration is deleted
+    /// and created again, since the modification time is part of it.
+    pub fn etag(&self) -> String {
+        format!(
+            "\"{}-{}\"",
+            self.metadata.version,
+            self.metadata.modified_at.timestamp_micros()
+        )
+    }
+
+    /// Whether an `If-Match` header value matches this configuration
+    pub fn matches(&self, if_match: &str) -> bool {
+        let etag = self.etag();
+        if_match
+            .split(',')
+            .map(str::trim)
+            .any(|tag| tag == "*" || tag == etag || tag.strip_prefix("W/") == Some(&etag))
+    }
+}
+
 /// Configuration summary for listing
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ConfigSummary {
@@ -239,7 +262,47 @@ impl ConfigManager {
         history_manager: Option<&mut HistoryManager>,
     ) -> Result<Config, SettingsError> {
         let old_config = self.load_config(path).await?;
+        self.apply_update(old_config, content, author, comment, history_manager)
+            .await
+    }
+
+    /// Update configuration unless it changed since the client read it
+    ///
+    /// `if_match` is compared with the `etag` of the stored configuration
+    /// right before writing. Callers serialize updates through the manager,
+    /// so no other write can happen between the check and the write.
+    pub async fn update_config_if_match(
+        &mut self,
+        path: &str,
+        if_match: &str,
+        content: Value,
+        author: &str,
+        comment: Option<String>,
+        history_manager: Option<&mut HistoryManager>,
+    ) -> Result<Config, SettingsError> {
+        let old_config = self.load_config(path).await?;
+        if !old_config.matches(if_match) {
+            return Err(SettingsError::PreconditionFailed(format!(
+                "{} is at {}, not {}",
+                path,
+                old_config.etag(),
+                if_match
+            )));
+        }
+        self.apply_update(old_config, content, author, comment, history_manager)
+            .await
+    }
 
+    /// Store the next version of `old_config` with new content
+    async fn apply_update(
+        &mut self,
+        old_config: Config,
+        content: Value,
+        author: &str,
+        comment: Option<String>,
+        history_manager: Option<&mut HistoryManager>,
+    ) -> Result<Config, SettingsError> {
+        let path = old_config.path.clone();
         let mut config = old_config.clone();
         config.content = content;
         config.metadata.version += 1;
@@ -252,7 +315,7 @@ impl ConfigManager {
         // Record history
         if let Some(history_manager) = history_manager {
             history_manager
-                .record_change(path, Some(&old_config), &config, ChangeAction::Update)
+                .record_change(&path, Some(&old_config), &config, ChangeAction::Update)
                 .await?;
         }
 
@@ -403,12 +466,10 @@ impl ConfigManager {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::settings_history::HistoryManager;
     use crate::settings_utils::error::{SettingsError, StorageError};
     use async_trait::async_trait;
     use serde_json::json;
     use std::collections::HashMap;
-    use tokio;
 
     /// Mock storage implementation for testing
     #[derive(Default)]
@@ -850,6 +911,37 @@ mod tests {
         );
     }
 
+    #[tokio::test]
+    async fn test_config_manager_update_config_if_match() {
+        let mut storage = MockStorage::new();
+        let config = create_test_config();
+        let config_json = serde_json::to_value(&config).unwrap();
+        storage.set_get_json_result(config_key(&config.path), Some(config_json));
+        let mut manager = ConfigManager::new(Box::new(storage));
+
+        let stale = "\"0-0\"";
+        let result = manager
+            .update_config_if_match(&config.path, stale, json!({}), "author", None, None)
+            .await;
+        assert!(matches!(result, Err(SettingsError::PreconditionFailed(_))));
+
+        let updated = manager
+            .update_config_if_match(
+                &config.path,
+                &config.etag(),
+                json!({"name": "Jane Doe"}),
+                "author",
+                None,
+                None,
+            )
+            .await
+            .unwrap();
+        assert_eq!(updated.metadata.version, config.metadata.version + 1);
+        assert_ne!(updated.etag(), config.etag());
+        assert!(updated.matches("*"));
+        assert!(updated.matches(&format!("{}, W/{}", stale, updated.etag())));
+    }
+
     #[tokio::test]
     async fn test_config_manager_delete_config() {
         let mut storage = MockStorage::new();
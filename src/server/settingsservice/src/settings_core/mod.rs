@@ -6,13 +6,18 @@
 use crate::settings_api::ApiServer;
 use crate::settings_config::ConfigManager;
 use crate::settings_history::HistoryManager;
+use crate::settings_monitoring::summary::SummaryConfig;
 use crate::settings_monitoring::MonitoringManager;
-use crate::settings_storage::EtcdClient;
+use crate::settings_storage::{Storage, StorageBackend, StorageConfig};
 use crate::settings_utils::error::SettingsError;
 use std::path::PathBuf;
 use std::sync::Arc;
+use std::time::Duration;
 use tokio::sync::RwLock;
-use tracing::{debug, error, info};
+use tracing::{debug, error, info, warn};
+
+/// Time between two checks of the stored metrics filters for changes
+const FILTER_WATCH_INTERVAL: Duration = Duration::from_secs(1);
 
 /// System status information
 #[derive(Debug, Clone)]
@@ -32,9 +37,15 @@ pub enum ComponentStatus {
     Failed(String),
 }
 
+/// Give a component its own handle to the shared backend
+fn component_storage(storage: &dyn StorageBackend) -> Box<dyn Storage> {
+    Box::new(storage.boxed_clone())
+}
+
 /// Core manager coordinates all service components
 #[allow(dead_code)]
 pub struct CoreManager {
+    storage: Box<dyn StorageBackend>,
     config_manager: Arc<RwLock<ConfigManager>>,
     history_manager: Arc<RwLock<HistoryManager>>,
     monitoring_manager: Arc<RwLock<MonitoringManager>>,
@@ -44,34 +55,33 @@ pub struct CoreManager {
 #[allow(dead_code)]
 impl CoreManager {
     /// Create a new core manager
+    ///
+    /// Every component stores its data in a clone of `storage`.
     pub async fn new(
-        etcd_endpoints: Vec<String>,
+        storage: Box<dyn StorageBackend>,
         bind_address: String,
         bind_port: u16,
-        _config_file: PathBuf,
+        config_file: PathBuf,
     ) -> Result<Self, SettingsError> {
-        info!("Initializing Settings Service core manager");
-
-        // Initialize ETCD clients for each component
-        let storage_config = EtcdClient::new(etcd_endpoints.clone()).await.map_err(|e| {
-            SettingsError::System(format!("Failed to create config storage: {}", e))
-        })?;
-
-        let storage_history = EtcdClient::new(etcd_endpoints.clone()).await.map_err(|e| {
-            SettingsError::System(format!("Failed to create history storage: {}", e))
-        })?;
-
-        let storage_monitoring = EtcdClient::new(etcd_endpoints).await.map_err(|e| {
-            SettingsError::System(format!("Failed to create monitoring storage: {}", e))
-        })?;
+        info!(
+            "Initializing Settings Service core manager with {} storage",
+            storage.name()
+        );
 
         // Initialize managers
-        let config_manager = Arc::new(RwLock::new(ConfigManager::new(Box::new(storage_config))));
-        let history_manager = Arc::new(RwLock::new(HistoryManager::new(Box::new(storage_history))));
-        let monitoring_manager = Arc::new(RwLock::new(MonitoringManager::new(
-            Box::new(storage_monitoring),
-            1, // 1 seconds cache TTL
-        )));
+        let config_manager = Arc::new(RwLock::new(ConfigManager::new(component_storage(
+            storage.as_ref(),
+        ))));
+        let history_manager = Arc::new(RwLock::new(HistoryManager::new(component_storage(
+            storage.as_ref(),
+        ))));
+        let monitoring_manager = Arc::new(RwLock::new(
+            MonitoringManager::new(
+                component_storage(storage.as_ref()),
+                1, // 1 seconds cache TTL
+            )
+            .with_summary_config(SummaryConfig::load(&config_file)?),
+        ));
 
         // Initialize API server
         let api_server = ApiServer::new(
@@ -84,6 +94,7 @@ impl CoreManager {
         .await?;
 
         Ok(Self {
+            storage,
             config_manager,
             history_manager,
             monitoring_manager,
@@ -92,6 +103,28 @@ impl CoreManager {
         })
     }
 
+    /// Create a core manager on the backend selected by `storage_config`
+    ///
+    /// # Arguments
+    ///
+    /// * `storage_config` - `storage` section of the configuration file
+    /// * `etcd_endpoints` - Endpoints of the `etcd` backend
+    pub async fn from_config(
+        storage_config: &StorageConfig,
+        etcd_endpoints: Vec<String>,
+        bind_address: String,
+        bind_port: u16,
+        config_file: PathBuf,
+    ) -> Result<Self, SettingsError> {
+        let storage = storage_config.create_backend(etcd_endpoints).await?;
+        Self::new(storage, bind_address, bind_port, config_file).await
+    }
+
+    /// Name of the storage backend in use
+    pub fn storage_backend(&self) -> &'static str {
+        self.storage.name()
+    }
+
     /// Start all services
     pub async fn start_services(&mut self) -> Result<(), SettingsError> {
         info!("Starting Settings Service components");
@@ -99,6 +132,17 @@ impl CoreManager {
         // Load default schemas
         self.load_default_schemas().await?;
 
+        // Keep the metrics cache in step with filters changed by other instances
+        if let Err(e) = MonitoringManager::watch_filters(
+            self.monitoring_manager.clone(),
+            self.storage.as_ref(),
+            FILTER_WATCH_INTERVAL,
+        )
+        .await
+        {
+            warn!("Cannot watch metrics filters: {}", e);
+        }
+
         // Start API server
         if let Some(api_server) = self.api_server.take() {
             tokio::spawn(async move {
@@ -319,12 +363,11 @@ impl CoreManager {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::settings_storage::Storage;
+    use crate::settings_storage::MemoryStorage;
     use crate::settings_utils::error::StorageError;
     use async_trait::async_trait;
     use serde_json::Value;
     use std::collections::HashMap;
-    use std::time::Duration;
     use tokio;
 
     /// Mock storage implementation for testing
@@ -431,6 +474,7 @@ mod tests {
         )));
 
         CoreManager {
+            storage: Box::new(MemoryStorage::default()),
             config_manager,
             history_manager,
             monitoring_manager,
@@ -552,6 +596,35 @@ mod tests {
         assert!(elapsed < Duration::from_secs(60));
     }
 
+    #[tokio::test]
+    async fn test_core_manager_from_config() {
+        let storage_config = StorageConfig {
+            backend: crate::settings_storage::BackendKind::Rocksdb,
+            rocksdb_url: "http://localhost:47007".to_string(),
+        };
+        let core_manager = CoreManager::from_config(
+            &storage_config,
+            vec![],
+            "127.0.0.1".to_string(),
+            0,
+            PathBuf::from("/nonexistent/settings.yaml"),
+        )
+        .await
+        .unwrap();
+        assert_eq!(core_manager.storage_backend(), "rocksdb");
+
+        let core_manager = CoreManager::from_config(
+            &StorageConfig::default(),
+            vec![],
+            "127.0.0.1".to_string(),
+            0,
+            PathBuf::from("/nonexistent/settings.yaml"),
+        )
+        .await
+        .unwrap();
+        assert_eq!(core_manager.storage_backend(), "etcd");
+    }
+
     #[tokio::test]
     async fn test_core_manager_get_system_status() {
         let core_manager = create_test_core_manager().await;
@@ -600,6 +673,7 @@ mod tests {
         )));
 
         let core_manager = CoreManager {
+            storage: Box::new(MemoryStorage::default()),
             config_manager,
             history_manager,
             monitoring_manager,
@@ -689,6 +763,7 @@ mod tests {
         )));
 
         let core_manager = CoreManager {
+            storage: Box::new(MemoryStorage::default()),
             config_manager,
             history_manager,
             monitoring_manager,
@@ -720,6 +795,7 @@ mod tests {
         )));
 
         let core_manager = CoreManager {
+            storage: Box::new(MemoryStorage::default()),
             config_manager,
             history_manager,
             monitoring_manager,
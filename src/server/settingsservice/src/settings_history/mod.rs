@@ -9,6 +9,8 @@ use crate::settings_utils::error::SettingsError;
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
+use std::cmp::{Ordering, Reverse};
+use std::collections::BinaryHeap;
 use tracing::{debug, info, warn};
 
 /// History entry for configuration changes
@@ -49,6 +51,70 @@ pub enum DiffOperation {
     Change,
 }
 
+/// Filter and page of a history query
+#[derive(Debug, Clone, Default)]
+pub struct HistoryFilter {
+    /// Only entries whose config path starts with this key
+    pub key: Option<String>,
+    /// Only entries recorded at or after this time
+    pub since: Option<DateTime<Utc>>,
+    /// Only entries recorded at or before this time
+    pub until: Option<DateTime<Utc>>,
+    /// Number of matching entries to skip, newest first
+    pub offset: usize,
+    /// Maximum number of entries to return
+    pub limit: Option<usize>,
+}
+
+impl HistoryFilter {
+    fn matches(&self, entry: &HistoryEntry) -> bool {
+        self.key
+            .as_deref()
+            .is_none_or(|key| entry.config_path.starts_with(key))
+            && self.since.is_none_or(|since| entry.timestamp >= since)
+            && self.until.is_none_or(|until| entry.timestamp <= until)
+    }
+}
+
+/// Page of history entries, newest first
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HistoryPage {
+    /// Number of entries matching the filter, across all pages
+    pub total: usize,
+    pub offset: usize,
+    pub limit: Option<usize>,
+    pub entries: Vec<HistoryEntry>,
+}
+
+/// History entry ordered by recency
+struct Recent(HistoryEntry);
+
+impl Recent {
+    fn key(&self) -> (DateTime<Utc>, u64, &str) {
+        (self.0.timestamp, self.0.version, &self.0.config_path)
+    }
+}
+
+impl PartialEq for Recent {
+    fn eq(&self, other: &Self) -> bool {
+        self.key() == other.key()
+    }
+}
+
+impl Eq for Recent {}
+
+impl PartialOrd for Recent {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Recent {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.key().cmp(&other.key())
+    }
+}
+
 /// History manager for tracking configuration changes
 pub struct HistoryManager {
     storage: Box<dyn Storage>,
@@ -110,40 +176,89 @@ impl HistoryManager {
         config_path: &str,
         limit: Option<usize>,
     ) -> Result<Vec<HistoryEntry>, SettingsError> {
-        debug!("Listing history for: {}", config_path);
+        let filter = HistoryFilter {
+            limit,
+            ..Default::default()
+        };
+        Ok(self
+            .query_history(Some(config_path), &filter)
+            .await?
+            .entries)
+    }
 
-        let prefix = format!(
-            "{}{}/",
-            crate::settings_storage::KeyPrefixes::HISTORY,
-            config_path
-        );
+    /// Get one page of history entries, newest first
+    ///
+    /// Only the newest `offset + limit` matching entries are kept while the
+    /// stored history is scanned, so a small page of a long history stays
+    /// cheap. The total still counts every matching entry.
+    pub async fn query_history(
+        &mut self,
+        config_path: Option<&str>,
+        filter: &HistoryFilter,
+    ) -> Result<HistoryPage, SettingsError> {
+        debug!("Querying history for: {:?} {:?}", config_path, filter);
+
+        let prefix = match config_path {
+            Some(path) => format!("{}{}/", crate::settings_storage::KeyPrefixes::HISTORY, path),
+            None => crate::settings_storage::KeyPrefixes::HISTORY.to_string(),
+        };
 
         let entries = self.storage.list(&prefix).await?;
-        let mut history_entries = Vec::new();
+        let bound = filter
+            .limit
+            .map(|limit| filter.offset.saturating_add(limit));
+        let mut newest = BinaryHeap::new();
+        let mut total = 0;
 
         for (key, value) in entries {
-            match serde_json::from_str::<serde_json::Value>(&value) {
-                Ok(data) => {
-                    if let Some(entry_data) = data.get("entry") {
-                        match serde_json::from_value::<HistoryEntry>(entry_data.clone()) {
-                            Ok(entry) => history_entries.push(entry),
-                            Err(e) => warn!("Failed to parse history entry from {}: {}", key, e),
-                        }
-                    }
-                }
-                Err(e) => warn!("Failed to parse history data from {}: {}", key, e),
+            let Some(entry) = Self::parse_entry(&key, &value) else {
+                continue;
+            };
+            if !filter.matches(&entry) {
+                continue;
+            }
+            total += 1;
+            newest.push(Reverse(Recent(entry)));
+            if bound.is_some_and(|bound| newest.len() > bound) {
+                newest.pop();
             }
         }
 
-        // Sort by version (descending)
-        history_entries.sort_by(|a, b| b.version.cmp(&a.version));
+        // Sorting the reversed entries puts the newest first
+        let entries = newest
+            .into_sorted_vec()
+            .into_iter()
+            .map(|Reverse(Recent(entry))| entry)
+            .skip(filter.offset)
+            .take(filter.limit.unwrap_or(usize::MAX))
+            .collect();
+
+        Ok(HistoryPage {
+            total,
+            offset: filter.offset,
+            limit: filter.limit,
+            entries,
+        })
+    }
 
-        // Apply limit if specified
-        if let Some(limit) = limit {
-            history_entries.truncate(limit);
+    /// Parse the entry stored under a history key, if it is valid
+    fn parse_entry(key: &str, value: &str) -> Option<HistoryEntry> {
+        match serde_json::from_str::<serde_json::Value>(value) {
+            Ok(data) => {
+                let entry_data = data.get("entry")?;
+                match serde_json::from_value::<HistoryEntry>(entry_data.clone()) {
+                    Ok(entry) => Some(entry),
+                    Err(e) => {
+                        warn!("Failed to parse history entry from {}: {}", key, e);
+                        None
+                    }
+                }
+            }
+            Err(e) => {
+                warn!("Failed to parse history data from {}: {}", key, e);
+                None
+            }
         }
-
-        Ok(history_entries)
     }
 
     /// Get configuration at a specific version
@@ -1136,4 +1251,112 @@ mod tests {
             }
         }
     }
+
+    /// Storage with `count` versions of each config, one minute apart
+    async fn seed_history(configs: &[&str], count: u64) -> (MockStorage, DateTime<Utc>) {
+        let mut storage = MockStorage::new();
+        let start = Utc::now() - chrono::Duration::days(1);
+        for version in 1..=count {
+            for (i, config_path) in configs.iter().enumerate() {
+                let entry = HistoryEntry {
+                    config_path: config_path.to_string(),
+                    version,
+                    timestamp: start
+                        + chrono::Duration::minutes(
+                            (version * configs.len() as u64 + i as u64) as i64,
+                        ),
+                    author: "seeder".to_string(),
+                    comment: None,
+                    action: ChangeAction::Update,
+                    change_summary: "1 change".to_string(),
+                };
+                let history_data = json!({"entry": entry, "config": create_test_config()});
+                storage
+                    .put_json(&history_key(config_path, version), &history_data)
+                    .await
+                    .unwrap();
+            }
+        }
+        (storage, start)
+    }
+
+    #[tokio::test]
+    async fn test_query_history_pages_newest_first() {
+        let (storage, _) = seed_history(&["app/a"], 25).await;
+        let mut manager = HistoryManager::new(Box::new(storage));
+
+        let filter = HistoryFilter {
+            offset: 10,
+            limit: Some(10),
+            ..Default::default()
+        };
+        let page = manager.query_history(Some("app/a"), &filter).await.unwrap();
+        assert_eq!(page.total, 25);
+        let versions: Vec<u64> = page.entries.iter().map(|e| e.version).collect();
+        assert_eq!(versions, (6..=15).rev().collect::<Vec<u64>>());
+
+        let filter = HistoryFilter {
+            offset: 20,
+            limit: Some(10),
+            ..Default::default()
+        };
+        let page = manager.query_history(Some("app/a"), &filter).await.unwrap();
+        assert_eq!(page.total, 25);
+        assert_eq!(page.entries.len(), 5);
+        assert_eq!(page.entries.last().unwrap().version, 1);
+
+        let filter = HistoryFilter {
+            offset: 30,
+            ..Default::default()
+        };
+        let page = manager.query_history(Some("app/a"), &filter).await.unwrap();
+        assert_eq!(page.total, 25);
+        assert!(page.entries.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_query_history_filters_by_key_and_time() {
+        let (storage, start) = seed_history(&["app/a", "app/b", "sys/c"], 20).await;
+        let mut manager = HistoryManager::new(Box::new(storage));
+
+        let filter = HistoryFilter {
+            key: Some("app/".to_string()),
+            limit: Some(4),
+            ..Default::default()
+        };
+        let page = manager.query_history(None, &filter).await.unwrap();
+        assert_eq!(page.total, 40);
+        let newest: Vec<(&str, u64)> = page
+            .entries
+            .iter()
+            .map(|e| (e.config_path.as_str(), e.version))
+            .collect();
+        assert_eq!(
+            newest,
+            vec![("app/b", 20), ("app/a", 20), ("app/b", 19), ("app/a", 19)]
+        );
+
+        // Versions 5 to 7 of each config were recorded in this range
+        let filter = HistoryFilter {
+            since: Some(start + chrono::Duration::minutes(15)),
+            until: Some(start + chrono::Duration::minutes(23)),
+            ..Default::default()
+        };
+        let page = manager.query_history(None, &filter).await.unwrap();
+        assert_eq!(page.total, 9);
+        assert!(page.entries.iter().all(|e| (5..=7).contains(&e.version)));
+        assert_eq!(page.entries[0].config_path, "sys/c");
+
+        let filter = HistoryFilter {
+            key: Some("sys/".to_string()),
+            since: Some(start + chrono::Duration::minutes(15)),
+            until: Some(start + chrono::Duration::minutes(23)),
+            limit: Some(2),
+            ..Default::default()
+        };
+        let page = manager.query_history(None, &filter).await.unwrap();
+        assert_eq!(page.total, 3);
+        let versions: Vec<u64> = page.entries.iter().map(|e| e.version).collect();
+        assert_eq!(versions, vec![7, 6]);
+    }
 }
@@ -2,16 +2,19 @@
 // SPDX-License-Identifier: Apache-2.0
 
 //! Monitoring and metrics management module
+pub mod summary;
+
 use crate::monitoring_types::{BoardInfo, NodeInfo, SocInfo, StressMetrics};
 use crate::settings_storage::filter_key;
-use crate::settings_storage::Storage;
-use crate::settings_utils::error::SettingsError;
+use crate::settings_storage::{KeyPrefixes, Storage, StorageBackend};
+use crate::settings_utils::error::{SettingsError, StorageError};
 use chrono::{DateTime, Utc};
 use common::monitoringserver::ContainerInfo;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::sync::RwLock;
-use std::time::{Duration, Instant};
+use std::time::{Duration, Instant, SystemTime};
+use summary::{MetricsSummary, SummaryConfig};
 use tracing::{debug, info, warn};
 use uuid::Uuid;
 
@@ -132,6 +135,8 @@ pub struct MonitoringManager {
     storage: Box<dyn Storage>, // Only used for filters, not metrics
     cache: RwLock<HashMap<String, CacheEntry<Vec<Metric>>>>,
     cache_ttl: Duration,
+    summary_config: SummaryConfig,
+    summary_cache: RwLock<Option<CacheEntry<MetricsSummary>>>,
 }
 #[allow(dead_code)]
 impl MonitoringManager {
@@ -140,9 +145,80 @@ impl MonitoringManager {
             storage,
             cache: RwLock::new(HashMap::new()),
             cache_ttl: Duration::from_secs(cache_ttl_seconds),
+            summary_config: SummaryConfig::default(),
+            summary_cache: RwLock::new(None),
         }
     }
 
+    /// Drop the cached metrics of every filter that changes in `storage`
+    ///
+    /// Filters changed through this manager are invalidated right away; the
+    /// watch also catches changes made by other instances sharing the backend.
+    ///
+    /// # Arguments
+    ///
+    /// * `manager` - Manager whose cache is invalidated
+    /// * `storage` - Backend the filters are stored in
+    /// * `interval` - Time between two checks of the filters
+    pub async fn watch_filters(
+        manager: std::sync::Arc<tokio::sync::RwLock<MonitoringManager>>,
+        storage: &dyn StorageBackend,
+        interval: Duration,
+    ) -> Result<(), StorageError> {
+        let mut events = storage.watch(KeyPrefixes::FILTERS, interval).await?;
+        tokio::spawn(async move {
+            while let Some(event) = events.recv().await {
+                let id = event.key().trim_start_matches(KeyPrefixes::FILTERS);
+                debug!("Filter {} changed in storage", id);
+                manager
+                    .read()
+                    .await
+                    .invalidate_cache(&format!("filter:{}", id));
+            }
+        });
+        Ok(())
+    }
+
+    /// Use `config` for the cluster summary instead of the defaults
+    pub fn with_summary_config(mut self, config: SummaryConfig) -> Self {
+        self.summary_config = config;
+        self
+    }
+
+    /// Get the cluster-wide summary of the node, SoC and board metrics
+    ///
+    /// The summary is computed from etcd at most once every
+    /// `cache_ttl_secs`, however many dashboards ask for it.
+    pub async fn get_metrics_summary(&self) -> Result<MetricsSummary, SettingsError> {
+        if let Some(cached) = self.get_cached_summary() {
+            debug!("Returning cached metrics summary");
+            return Ok(cached);
+        }
+
+        let fetch = |what: &str, e: crate::monitoring_etcd::MonitoringEtcdError| {
+            SettingsError::Metrics(format!("Failed to get {} for summary: {}", what, e))
+        };
+        let nodes = crate::monitoring_etcd::get_all_nodes()
+            .await
+            .map_err(|e| fetch("nodes", e))?;
+        let socs = crate::monitoring_etcd::get_all_socs()
+            .await
+            .map_err(|e| fetch("socs", e))?;
+        let boards = crate::monitoring_etcd::get_all_boards()
+            .await
+            .map_err(|e| fetch("boards", e))?;
+
+        let summary = summary::summarize(
+            &nodes,
+            &socs,
+            &boards,
+            SystemTime::now(),
+            &self.summary_config,
+        );
+        self.set_cached_summary(summary.clone());
+        Ok(summary)
+    }
+
     /// Get metrics with optional filtering
     pub async fn get_metrics(
         &mut self,
@@ -812,6 +888,26 @@ impl MonitoringManager {
         }
     }
 
+    fn get_cached_summary(&self) -> Option<MetricsSummary> {
+        let cache = self.summary_cache.read().ok()?;
+        let entry = cache.as_ref()?;
+
+        if entry.expiry > Instant::now() {
+            Some(entry.data.clone())
+        } else {
+            None
+        }
+    }
+
+    fn set_cached_summary(&self, summary: MetricsSummary) {
+        if let Ok(mut cache) = self.summary_cache.write() {
+            *cache = Some(CacheEntry {
+                data: summary,
+                expiry: Instant::now() + self.summary_config.cache_ttl(),
+            });
+        }
+    }
+
     fn invalidate_cache(&self, key: &str) {
         if let Ok(mut cache) = self.cache.write() {
             cache.remove(key);
@@ -823,6 +919,9 @@ impl MonitoringManager {
         if let Ok(mut cache) = self.cache.write() {
             cache.clear();
         }
+        if let Ok(mut cache) = self.summary_cache.write() {
+            *cache = None;
+        }
         debug!("Cleared metrics cache");
     }
 
@@ -1186,6 +1285,7 @@ mod tests {
             os: "Linux".to_string(),
             arch: "x86_64".to_string(),
             ip: "192.168.1.100".to_string(),
+            dropped_container_updates: 0,
         }
     }
 
@@ -1208,6 +1308,7 @@ mod tests {
             config,
             annotation,
             stats: HashMap::new(),
+            usage: None,
         }
     }
 
@@ -1515,13 +1616,32 @@ mod tests {
         assert!(manager.get_cached("expiring-key").is_none());
     }
 
+    #[tokio::test]
+    async fn test_summary_cache_expiration() {
+        let summary =
+            summary::summarize(&[], &[], &[], SystemTime::now(), &SummaryConfig::default());
+
+        let manager = create_test_monitoring_manager().await;
+        assert!(manager.get_cached_summary().is_none());
+        manager.set_cached_summary(summary.clone());
+        let cached = manager.get_cached_summary().unwrap();
+        assert_eq!(cached.generated_at, summary.generated_at);
+        manager.clear_cache();
+        assert!(manager.get_cached_summary().is_none());
+
+        let manager = create_test_monitoring_manager()
+            .await
+            .with_summary_config(SummaryConfig {
+                cache_ttl_secs: 0,
+                ..Default::default()
+            });
+        manager.set_cached_summary(summary);
+        assert!(manager.get_cached_summary().is_none());
+    }
+
     #[test]
     fn test_simple_wildcard_matching() {
-        let manager = MonitoringManager {
-            storage: Box::new(MockStorage::new()),
-            cache: RwLock::new(HashMap::new()),
-            cache_ttl: Duration::from_secs(300),
-        };
+        let manager = MonitoringManager::new(Box::new(MockStorage::new()), 300);
 
         // Test exact match
         assert!(manager.simple_wildcard_match("test", "test"));
@@ -1541,11 +1661,7 @@ mod tests {
 
     #[test]
     fn test_metric_matches_filter() {
-        let manager = MonitoringManager {
-            storage: Box::new(MockStorage::new()),
-            cache: RwLock::new(HashMap::new()),
-            cache_ttl: Duration::from_secs(300),
-        };
+        let manager = MonitoringManager::new(Box::new(MockStorage::new()), 300);
 
         let mut labels = HashMap::new();
         labels.insert("node_name".to_string(), "test-node".to_string());
@@ -1617,11 +1733,7 @@ mod tests {
 
     #[test]
     fn test_metric_matches_filter_with_labels() {
-        let manager = MonitoringManager {
-            storage: Box::new(MockStorage::new()),
-            cache: RwLock::new(HashMap::new()),
-            cache_ttl: Duration::from_secs(300),
-        };
+        let manager = MonitoringManager::new(Box::new(MockStorage::new()), 300);
 
         let mut labels = HashMap::new();
         labels.insert("node_name".to_string(), "test-node-001".to_string());
@@ -1679,11 +1791,7 @@ mod tests {
 
     #[test]
     fn test_metric_matches_filter_with_time_range() {
-        let manager = MonitoringManager {
-            storage: Box::new(MockStorage::new()),
-            cache: RwLock::new(HashMap::new()),
-            cache_ttl: Duration::from_secs(300),
-        };
+        let manager = MonitoringManager::new(Box::new(MockStorage::new()), 300);
 
         let now = Utc::now();
         let metric = Metric {
@@ -1742,11 +1850,7 @@ mod tests {
 
     #[test]
     fn test_container_belongs_to_node() {
-        let manager = MonitoringManager {
-            storage: Box::new(MockStorage::new()),
-            cache: RwLock::new(HashMap::new()),
-            cache_ttl: Duration::from_secs(300),
-        };
+        let manager = MonitoringManager::new(Box::new(MockStorage::new()), 300);
 
         // Test container with hostname in config
         let mut config_container = create_test_container_info();
@@ -1780,11 +1884,7 @@ mod tests {
 
     #[test]
     fn test_ensure_node_hostname_in_container() {
-        let manager = MonitoringManager {
-            storage: Box::new(MockStorage::new()),
-            cache: RwLock::new(HashMap::new()),
-            cache_ttl: Duration::from_secs(300),
-        };
+        let manager = MonitoringManager::new(Box::new(MockStorage::new()), 300);
 
         // Test container with only hostname
         let mut container_with_hostname = create_test_container_info();
@@ -1981,11 +2081,7 @@ mod tests {
 
     #[test]
     fn test_error_scenarios() {
-        let mut manager = MonitoringManager {
-            storage: Box::new(MockStorage::new()),
-            cache: RwLock::new(HashMap::new()),
-            cache_ttl: Duration::from_secs(300),
-        };
+        let manager = MonitoringManager::new(Box::new(MockStorage::new()), 300);
 
         // Test invalid cache operations
         let invalid_cached = manager.get_cached("non-existent-key");
@@ -1999,13 +2095,42 @@ mod tests {
         assert!(manager.get_cached("test-key").is_none());
     }
 
+    #[tokio::test]
+    async fn test_watch_filters_invalidates_changed_filter() {
+        use crate::settings_storage::MemoryStorage;
+
+        let backend = MemoryStorage::default();
+        let manager = std::sync::Arc::new(tokio::sync::RwLock::new(MonitoringManager::new(
+            Box::new(MockStorage::new()),
+            300,
+        )));
+        MonitoringManager::watch_filters(manager.clone(), &backend, TokioDuration::from_millis(5))
+            .await
+            .unwrap();
+        {
+            let manager = manager.read().await;
+            manager.set_cached("filter:changed", vec![]);
+            manager.set_cached("filter:untouched", vec![]);
+        }
+
+        backend.put(&filter_key("changed"), "{}").await.unwrap();
+        tokio::time::timeout(TokioDuration::from_secs(5), async {
+            while manager.read().await.get_cached("filter:changed").is_some() {
+                sleep(TokioDuration::from_millis(5)).await;
+            }
+        })
+        .await
+        .unwrap();
+        assert!(manager
+            .read()
+            .await
+            .get_cached("filter:untouched")
+            .is_some());
+    }
+
     #[tokio::test]
     async fn test_complex_filtering_scenarios() {
-        let manager = MonitoringManager {
-            storage: Box::new(MockStorage::new()),
-            cache: RwLock::new(HashMap::new()),
-            cache_ttl: Duration::from_secs(300),
-        };
+        let manager = MonitoringManager::new(Box::new(MockStorage::new()), 300);
 
         // Create metric with multiple labels
         let mut labels = HashMap::new();
@@ -0,0 +1,530 @@
+// SPDX-FileCopyrightText: Copyright 2024 LG Electronics Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Cluster-wide summary of the node, SoC and board metrics
+//!
+//! Boards and SoCs older than `stale_after_secs` are left out of the
+//! summary and only counted. Nodes carry no timestamp, so a node is stale
+//! when every SoC and board listing it is stale. A node no SoC or board
+//! lists is taken as current.
+
+use crate::monitoring_types::{BoardInfo, NodeInfo, SocInfo};
+use crate::settings_utils::error::SettingsError;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+use std::collections::HashSet;
+use std::path::Path;
+use std::time::{Duration, SystemTime};
+use tracing::debug;
+
+/// Boards listed as the busiest
+const BUSIEST_BOARDS: usize = 5;
+
+/// `metrics_summary` section of the service configuration file
+///
+/// ```yaml
+/// metrics_summary:
+///   cache_ttl_secs: 5
+///   stale_after_secs: 120
+///   overload_percent: 90.0
+///   soc_temperature_limit: 85.0
+/// ```
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct SummaryConfig {
+    /// How long a computed summary is served before reading etcd again
+    #[serde(default = "default_cache_ttl_secs")]
+    pub cache_ttl_secs: u64,
+    /// Age past which a board or SoC is stale
+    #[serde(default = "default_stale_after_secs")]
+    pub stale_after_secs: u64,
+    /// CPU or memory usage, in percent, from which a node is overloaded
+    #[serde(default = "default_overload_percent")]
+    pub overload_percent: f64,
+    /// Temperature, in degrees Celsius, above which a SoC is reported
+    #[serde(default = "default_soc_temperature_limit")]
+    pub soc_temperature_limit: f64,
+}
+
+fn default_cache_ttl_secs() -> u64 {
+    5
+}
+
+fn default_stale_after_secs() -> u64 {
+    120
+}
+
+fn default_overload_percent() -> f64 {
+    90.0
+}
+
+fn default_soc_temperature_limit() -> f64 {
+    85.0
+}
+
+impl Default for SummaryConfig {
+    fn default() -> Self {
+        Self {
+            cache_ttl_secs: default_cache_ttl_secs(),
+            stale_after_secs: default_stale_after_secs(),
+            overload_percent: default_overload_percent(),
+            soc_temperature_limit: default_soc_temperature_limit(),
+        }
+    }
+}
+
+impl SummaryConfig {
+    /// Read the `metrics_summary` section of the YAML file at `path`
+    ///
+    /// A missing file or section gives the defaults.
+    pub fn load(path: &Path) -> Result<Self, SettingsError> {
+        #[derive(Deserialize)]
+        struct File {
+            #[serde(default)]
+            metrics_summary: SummaryConfig,
+        }
+
+        let text = match std::fs::read_to_string(path) {
+            Ok(text) => text,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                debug!("No configuration file at {:?}, using default summary", path);
+                return Ok(Self::default());
+            }
+            Err(e) => {
+                return Err(SettingsError::Config(format!(
+                    "Cannot read {:?}: {}",
+                    path, e
+                )))
+            }
+        };
+        if text.trim().is_empty() {
+            return Ok(Self::default());
+        }
+        serde_yaml::from_str::<File>(&text)
+            .map(|file| file.metrics_summary)
+            .map_err(|e| SettingsError::Config(format!("Invalid {:?}: {}", path, e)))
+    }
+
+    pub fn cache_ttl(&self) -> Duration {
+        Duration::from_secs(self.cache_ttl_secs)
+    }
+
+    pub fn stale_after(&self) -> Duration {
+        Duration::from_secs(self.stale_after_secs)
+    }
+}
+
+/// Summary of the whole cluster, served by `GET /api/v1/metrics/summary`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MetricsSummary {
+    pub generated_at: DateTime<Utc>,
+    pub nodes: NodeSummary,
+    pub utilization: Utilization,
+    pub socs: SocSummary,
+    pub boards: BoardSummary,
+}
+
+/// Node counts by status
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct NodeSummary {
+    /// Current nodes, healthy or overloaded
+    pub total: usize,
+    pub healthy: usize,
+    /// Nodes using at least `overload_percent` of their CPU or memory
+    pub overloaded: usize,
+    /// Nodes left out of the summary
+    pub stale: usize,
+}
+
+/// Resource usage of the current nodes together
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Utilization {
+    /// CPU usage weighted by the CPU count of each node, in percent
+    pub cpu_percent: f64,
+    /// Used memory over total memory, in percent
+    pub memory_percent: f64,
+    pub cpu_count: u64,
+    pub used_memory: u64,
+    pub total_memory: u64,
+}
+
+/// SoC counts and the SoCs running hot
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct SocSummary {
+    /// Current SoCs
+    pub total: usize,
+    /// IDs of the current SoCs above `soc_temperature_limit`
+    pub over_temperature: Vec<String>,
+    /// Current SoCs reporting no temperature
+    pub temperature_unknown: usize,
+    /// SoCs left out of the summary
+    pub stale: usize,
+}
+
+/// Board counts and the busiest boards
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct BoardSummary {
+    /// Current boards
+    pub total: usize,
+    /// Current boards by CPU usage, busiest first
+    pub busiest: Vec<BoardLoad>,
+    /// Boards left out of the summary
+    pub stale: usize,
+}
+
+/// Load of one board
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BoardLoad {
+    pub board_id: String,
+    pub cpu_usage: f64,
+    pub mem_usage: f64,
+    pub node_count: usize,
+}
+
+/// Whether a record updated at `last_updated` is stale at `now`
+///
+/// A time in the future is taken as current.
+fn is_stale(last_updated: SystemTime, now: SystemTime, stale_after: Duration) -> bool {
+    now.duration_since(last_updated)
+        .is_ok_and(|age| age > stale_after)
+}
+
+/// Percentage of `part` in `whole`, 0 when `whole` is 0
+fn percent(part: f64, whole: f64) -> f64 {
+    if whole > 0.0 {
+        part / whole * 100.0
+    } else {
+        0.0
+    }
+}
+
+/// Summarize the records read from etcd
+///
+/// # Arguments
+///
+/// * `nodes`, `socs`, `boards` - Every stored record
+/// * `now` - Time the records are judged stale against
+/// * `config` - Thresholds of the summary
+pub fn summarize(
+    nodes: &[NodeInfo],
+    socs: &[SocInfo],
+    boards: &[BoardInfo],
+    now: SystemTime,
+    config: &SummaryConfig,
+) -> MetricsSummary {
+    let stale_after = config.stale_after();
+    let (current_socs, stale_socs): (Vec<&SocInfo>, Vec<&SocInfo>) = socs
+        .iter()
+        .partition(|soc| !is_stale(soc.last_updated, now, stale_after));
+    let (current_boards, stale_boards): (Vec<&BoardInfo>, Vec<&BoardInfo>) = boards
+        .iter()
+        .partition(|board| !is_stale(board.last_updated, now, stale_after));
+
+    // Names of the nodes listed by a current SoC or board, and by a stale one
+    let mut listed_current = HashSet::new();
+    let mut listed_stale = HashSet::new();
+    let listings = socs.iter().map(|soc| (&soc.nodes, soc.last_updated)).chain(
+        boards
+            .iter()
+            .map(|board| (&board.nodes, board.last_updated)),
+    );
+    for (listed, last_updated) in listings {
+        let names = listed.iter().map(|node| node.node_name.as_str());
+        if is_stale(last_updated, now, stale_after) {
+            listed_stale.extend(names);
+        } else {
+            listed_current.extend(names);
+        }
+    }
+    let (current_nodes, stale_nodes): (Vec<&NodeInfo>, Vec<&NodeInfo>) =
+        nodes.iter().partition(|node| {
+            let name = node.node_name.as_str();
+            listed_current.contains(name) || !listed_stale.contains(name)
+        });
+
+    MetricsSummary {
+        generated_at: DateTime::<Utc>::from(now),
+        nodes: summarize_nodes(&current_nodes, stale_nodes.len(), config),
+        utilization: utilization(&current_nodes),
+        socs: summarize_socs(&current_socs, stale_socs.len(), config),
+        boards: summarize_boards(&current_boards, stale_boards.len()),
+    }
+}
+
+fn summarize_nodes(nodes: &[&NodeInfo], stale: usize, config: &SummaryConfig) -> NodeSummary {
+    let overloaded = nodes
+        .iter()
+        .filter(|node| {
+            node.cpu_usage >= config.overload_percent || node.mem_usage >= config.overload_percent
+        })
+        .count();
+    NodeSummary {
+        total: nodes.len(),
+        healthy: nodes.len() - overloaded,
+        overloaded,
+        stale,
+    }
+}
+
+fn utilization(nodes: &[&NodeInfo]) -> Utilization {
+    let cpu_count: u64 = nodes.iter().map(|node| node.cpu_count).sum();
+    let busy_cpus: f64 = nodes
+        .iter()
+        .map(|node| node.cpu_usage * node.cpu_count as f64)
+        .sum();
+    let used_memory: u64 = nodes.iter().map(|node| node.used_memory).sum();
+    let total_memory: u64 = nodes.iter().map(|node| node.total_memory).sum();
+
+    Utilization {
+        cpu_percent: busy_cpus / cpu_count.max(1) as f64,
+        memory_percent: percent(used_memory as f64, total_memory as f64),
+        cpu_count,
+        used_memory,
+        total_memory,
+    }
+}
+
+fn summarize_socs(socs: &[&SocInfo], stale: usize, config: &SummaryConfig) -> SocSummary {
+    let mut over_temperature: Vec<String> = socs
+        .iter()
+        .filter(|soc| {
+            soc.temperature
+                .is_some_and(|temperature| temperature > config.soc_temperature_limit)
+        })
+        .map(|soc| soc.soc_id.clone())
+        .collect();
+    over_temperature.sort();
+
+    SocSummary {
+        total: socs.len(),
+        over_temperature,
+        temperature_unknown: socs.iter().filter(|soc| soc.temperature.is_none()).count(),
+        stale,
+    }
+}
+
+fn summarize_boards(boards: &[&BoardInfo], stale: usize) -> BoardSummary {
+    let mut busiest: Vec<BoardLoad> = boards
+        .iter()
+        .map(|board| BoardLoad {
+            board_id: board.board_id.clone(),
+            cpu_usage: board.total_cpu_usage,
+            mem_usage: board.total_mem_usage,
+            node_count: board.nodes.len(),
+        })
+        .collect();
+    busiest.sort_by(|a, b| {
+        b.cpu_usage
+            .partial_cmp(&a.cpu_usage)
+            .unwrap_or(Ordering::Equal)
+            .then_with(|| a.board_id.cmp(&b.board_id))
+    });
+    busiest.truncate(BUSIEST_BOARDS);
+
+    BoardSummary {
+        total: boards.len(),
+        busiest,
+        stale,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn node(name: &str, cpu_usage: f64, cpu_count: u64, used: u64, total: u64) -> NodeInfo {
+        NodeInfo {
+            node_name: name.to_string(),
+            cpu_usage,
+            cpu_count,
+            gpu_count: 0,
+            used_memory: used,
+            total_memory: total,
+            mem_usage: percent(used as f64, total as f64),
+            rx_bytes: 0,
+            tx_bytes: 0,
+            read_bytes: 0,
+            write_bytes: 0,
+            os: "Linux".to_string(),
+            arch: "aarch64".to_string(),
+            ip: String::new(),
+            dropped_container_updates: 0,
+        }
+    }
+
+    fn soc(id: &str, nodes: Vec<NodeInfo>, temperature: Option<f64>, age: u64) -> SocInfo {
+        SocInfo {
+            soc_id: id.to_string(),
+            nodes,
+            total_cpu_usage: 0.0,
+            total_cpu_count: 0,
+            total_gpu_count: 0,
+            total_used_memory: 0,
+            total_memory: 0,
+            total_mem_usage: 0.0,
+            total_rx_bytes: 0,
+            total_tx_bytes: 0,
+            total_read_bytes: 0,
+            total_write_bytes: 0,
+            temperature,
+            last_updated: now() - Duration::from_secs(age),
+        }
+    }
+
+    fn board(id: &str, nodes: Vec<NodeInfo>, cpu_usage: f64, age: u64) -> BoardInfo {
+        BoardInfo {
+            board_id: id.to_string(),
+            nodes,
+            socs: Vec::new(),
+            total_cpu_usage: cpu_usage,
+            total_cpu_count: 0,
+            total_gpu_count: 0,
+            total_used_memory: 0,
+            total_memory: 0,
+            total_mem_usage: cpu_usage / 2.0,
+            total_rx_bytes: 0,
+            total_tx_bytes: 0,
+            total_read_bytes: 0,
+            total_write_bytes: 0,
+            last_updated: now() - Duration::from_secs(age),
+        }
+    }
+
+    fn now() -> SystemTime {
+        SystemTime::UNIX_EPOCH + Duration::from_secs(1_700_000_000)
+    }
+
+    #[test]
+    fn test_utilization_and_node_status() {
+        let nodes = vec![
+            node("hpc", 50.0, 8, 4096, 16384),
+            node("zone1", 95.0, 2, 1024, 4096),
+            node("zone2", 10.0, 2, 3900, 4096),
+        ];
+
+        let summary = summarize(&nodes, &[], &[], now(), &SummaryConfig::default());
+
+        assert_eq!(
+            summary.nodes,
+            NodeSummary {
+                total: 3,
+                healthy: 1,
+                overloaded: 2,
+                stale: 0,
+            }
+        );
+        // (50 * 8 + 95 * 2 + 10 * 2) / 12
+        assert!((summary.utilization.cpu_percent - 610.0 / 12.0).abs() < 1e-9);
+        // 9020 / 24576
+        assert!((summary.utilization.memory_percent - 9020.0 / 24576.0 * 100.0).abs() < 1e-9);
+        assert_eq!(summary.utilization.cpu_count, 12);
+        assert_eq!(summary.utilization.used_memory, 9020);
+        assert_eq!(summary.utilization.total_memory, 24576);
+    }
+
+    #[test]
+    fn test_stale_entities_are_counted_not_summarized() {
+        let current = node("current", 20.0, 4, 1024, 4096);
+        let gone = node("gone", 80.0, 4, 4096, 4096);
+        let socs = vec![
+            soc("soc-a", vec![current.clone()], Some(60.0), 10),
+            soc("soc-b", vec![gone.clone()], Some(99.0), 600),
+        ];
+        let boards = vec![
+            board("board-a", vec![current.clone()], 20.0, 10),
+            board("board-b", vec![gone.clone()], 80.0, 600),
+        ];
+
+        let summary = summarize(
+            &[current, gone],
+            &socs,
+            &boards,
+            now(),
+            &SummaryConfig::default(),
+        );
+
+        assert_eq!(summary.nodes.total, 1);
+        assert_eq!(summary.nodes.stale, 1);
+        assert_eq!(summary.utilization.cpu_percent, 20.0);
+        assert_eq!(summary.utilization.memory_percent, 25.0);
+        assert_eq!(summary.socs.total, 1);
+        assert_eq!(summary.socs.stale, 1);
+        assert!(summary.socs.over_temperature.is_empty());
+        assert_eq!(summary.boards.total, 1);
+        assert_eq!(summary.boards.stale, 1);
+        assert_eq!(summary.boards.busiest[0].board_id, "board-a");
+    }
+
+    #[test]
+    fn test_socs_over_temperature() {
+        let socs = vec![
+            soc("soc-c", Vec::new(), Some(91.5), 0),
+            soc("soc-a", Vec::new(), Some(85.0), 0),
+            soc("soc-b", Vec::new(), Some(86.0), 0),
+            soc("soc-d", Vec::new(), None, 0),
+        ];
+
+        let summary = summarize(&[], &socs, &[], now(), &SummaryConfig::default());
+
+        assert_eq!(summary.socs.total, 4);
+        assert_eq!(summary.socs.over_temperature, vec!["soc-b", "soc-c"]);
+        assert_eq!(summary.socs.temperature_unknown, 1);
+    }
+
+    #[test]
+    fn test_top_five_busiest_boards() {
+        let boards: Vec<BoardInfo> = [30.0, 90.0, 10.0, 70.0, 50.0, 90.0, 20.0]
+            .iter()
+            .enumerate()
+            .map(|(i, cpu)| board(&format!("board-{}", i), Vec::new(), *cpu, 0))
+            .collect();
+
+        let summary = summarize(&[], &[], &boards, now(), &SummaryConfig::default());
+
+        let busiest: Vec<&str> = summary
+            .boards
+            .busiest
+            .iter()
+            .map(|b| b.board_id.as_str())
+            .collect();
+        assert_eq!(
+            busiest,
+            vec!["board-1", "board-5", "board-3", "board-4", "board-0"]
+        );
+        assert_eq!(summary.boards.total, 7);
+        assert_eq!(summary.boards.busiest[0].mem_usage, 45.0);
+    }
+
+    #[test]
+    fn test_empty_cluster() {
+        let summary = summarize(&[], &[], &[], now(), &SummaryConfig::default());
+
+        assert_eq!(summary.utilization, Utilization::default());
+        assert_eq!(summary.nodes, NodeSummary::default());
+        assert!(summary.boards.busiest.is_empty());
+    }
+
+    #[test]
+    fn test_load_config() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("settings.yaml");
+
+        assert_eq!(
+            SummaryConfig::load(&path).unwrap(),
+            SummaryConfig::default()
+        );
+
+        std::fs::write(
+            &path,
+            "metrics_summary:\n  cache_ttl_secs: 30\n  soc_temperature_limit: 70.5\n",
+        )
+        .unwrap();
+        let config = SummaryConfig::load(&path).unwrap();
+        assert_eq!(config.cache_ttl(), Duration::from_secs(30));
+        assert_eq!(config.soc_temperature_limit, 70.5);
+        assert_eq!(config.stale_after_secs, 120);
+
+        std::fs::write(&path, "metrics_summary: [1, 2]\n").unwrap();
+        assert!(SummaryConfig::load(&path).is_err());
+    }
+}
@@ -1,41 +1,248 @@
 // SPDX-FileCopyrightText: Copyright 2024 LG Electronics Inc.
 // SPDX-License-Identifier: Apache-2.0
 
-//! ETCD storage client module
+//! Storage backends for settings data
 
 use crate::settings_utils::error::StorageError;
 use async_trait::async_trait;
+use common::etcd::EndpointPool;
+use common::rocksdbservice::{DeleteRequest, GetByPrefixRequest, GetRequest, PutRequest};
+use serde::Deserialize;
 use serde_json::Value;
-use tracing::debug;
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::mpsc;
+use tracing::{debug, warn};
+
+/// Storage backend the service persists settings in
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, clap::ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum BackendKind {
+    /// Shared key-value store accessed through `common::etcd`
+    #[default]
+    Etcd,
+    /// RocksDB gRPC service at an explicit URL
+    Rocksdb,
+}
+
+/// `storage` section of the service configuration file
+///
+/// ```yaml
+/// storage:
+///   backend: rocksdb
+///   rocksdb_url: http://rocksdb-1:47007,http://rocksdb-2:47007
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct StorageConfig {
+    #[serde(default)]
+    pub backend: BackendKind,
+    /// Comma-separated RocksDB service URLs, used by the `rocksdb` backend
+    #[serde(default = "default_rocksdb_url")]
+    pub rocksdb_url: String,
+}
+
+fn default_rocksdb_url() -> String {
+    "http://localhost:47007".to_string()
+}
+
+impl Default for StorageConfig {
+    fn default() -> Self {
+        Self {
+            backend: BackendKind::default(),
+            rocksdb_url: default_rocksdb_url(),
+        }
+    }
+}
 
-/// ETCD client wrapper for Settings Service (now using common::etcd)
-pub struct EtcdClient {
-    // No longer need direct etcd client - use common::etcd interface
+impl StorageConfig {
+    /// Read the `storage` section of the YAML file at `path`
+    ///
+    /// A missing file or section gives the default etcd backend.
+    pub fn load(path: &Path) -> Result<Self, StorageError> {
+        #[derive(Deserialize)]
+        struct File {
+            #[serde(default)]
+            storage: StorageConfig,
+        }
+
+        let text = match std::fs::read_to_string(path) {
+            Ok(text) => text,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                debug!("No configuration file at {:?}, using default storage", path);
+                return Ok(Self::default());
+            }
+            Err(e) => {
+                return Err(StorageError::ConnectionFailed(format!(
+                    "Cannot read {:?}: {}",
+                    path, e
+                )))
+            }
+        };
+        if text.trim().is_empty() {
+            return Ok(Self::default());
+        }
+        serde_yaml::from_str::<File>(&text)
+            .map(|file| file.storage)
+            .map_err(|e| StorageError::SerializationError(format!("Invalid {:?}: {}", path, e)))
+    }
+
+    /// Create the backend this configuration selects
+    pub async fn create_backend(
+        &self,
+        etcd_endpoints: Vec<String>,
+    ) -> Result<Box<dyn StorageBackend>, StorageError> {
+        create_backend(self.backend, etcd_endpoints, &self.rocksdb_url).await
+    }
 }
 
-impl EtcdClient {
-    /// Create a new ETCD client (now using common::etcd)
+/// Change of a watched key
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum WatchEvent {
+    /// The key was created or got a new value
+    Put { key: String, value: String },
+    /// The key was deleted
+    Delete { key: String },
+}
+
+impl WatchEvent {
+    /// Key that changed
+    pub fn key(&self) -> &str {
+        match self {
+            WatchEvent::Put { key, .. } | WatchEvent::Delete { key } => key,
+        }
+    }
+}
+
+/// Key-value operations a storage backend must provide
+///
+/// Backends are cheap handles to an external store, so clones share the
+/// same data. `CoreManager` keeps one backend and gives each manager a clone.
+#[async_trait]
+pub trait StorageBackend: Send + Sync {
+    /// Short backend name for logs and status output
+    fn name(&self) -> &'static str;
+    /// Clone the backend handle into a new box
+    fn boxed_clone(&self) -> Box<dyn StorageBackend>;
+    async fn get(&self, key: &str) -> Result<Option<String>, StorageError>;
+    async fn put(&self, key: &str, value: &str) -> Result<(), StorageError>;
+    async fn delete(&self, key: &str) -> Result<bool, StorageError>;
+    async fn list(&self, prefix: &str) -> Result<Vec<(String, String)>, StorageError>;
+
+    /// Watch the keys under `prefix`
+    ///
+    /// Neither store pushes changes, so the keys are listed every `interval`
+    /// and the differences to the previous listing are sent as events.
+    /// Changes made before this returns are not reported. Watching stops
+    /// when the receiver is dropped.
+    async fn watch(
+        &self,
+        prefix: &str,
+        interval: Duration,
+    ) -> Result<mpsc::Receiver<WatchEvent>, StorageError> {
+        let mut known: HashMap<String, String> = self.list(prefix).await?.into_iter().collect();
+        let (tx, rx) = mpsc::channel(100);
+        let backend = self.boxed_clone();
+        let prefix = prefix.to_string();
+
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            ticker.tick().await;
+            while !tx.is_closed() {
+                ticker.tick().await;
+                let current: HashMap<String, String> = match backend.as_ref().list(&prefix).await {
+                    Ok(pairs) => pairs.into_iter().collect(),
+                    Err(e) => {
+                        warn!("Failed to watch {} in {}: {}", prefix, backend.name(), e);
+                        continue;
+                    }
+                };
+
+                let mut events: Vec<WatchEvent> = current
+                    .iter()
+                    .filter(|(key, value)| known.get(*key) != Some(*value))
+                    .map(|(key, value)| WatchEvent::Put {
+                        key: key.clone(),
+                        value: value.clone(),
+                    })
+                    .chain(
+                        known
+                            .keys()
+                            .filter(|key| !current.contains_key(*key))
+                            .map(|key| WatchEvent::Delete { key: key.clone() }),
+                    )
+                    .collect();
+                events.sort_by(|a, b| a.key().cmp(b.key()));
+                for event in events {
+                    if tx.send(event).await.is_err() {
+                        return;
+                    }
+                }
+                known = current;
+            }
+        });
+        Ok(rx)
+    }
+}
+
+/// Create the backend selected by `kind`
+pub async fn create_backend(
+    kind: BackendKind,
+    etcd_endpoints: Vec<String>,
+    rocksdb_url: &str,
+) -> Result<Box<dyn StorageBackend>, StorageError> {
+    match kind {
+        BackendKind::Etcd => Ok(Box::new(EtcdStorage::new(etcd_endpoints).await?)),
+        BackendKind::Rocksdb => Ok(Box::new(RocksDbStorage::new(rocksdb_url)?)),
+    }
+}
+
+/// ETCD storage for Settings Service (now using common::etcd)
+#[derive(Clone)]
+pub struct EtcdStorage {
+    /// Endpoints shared with every other user of `common::etcd`
+    pool: &'static EndpointPool,
+}
+
+impl Default for EtcdStorage {
+    fn default() -> Self {
+        Self {
+            pool: common::etcd::pool(),
+        }
+    }
+}
+
+impl EtcdStorage {
+    /// Create a new ETCD storage (now using common::etcd)
     pub async fn new(_endpoints: Vec<String>) -> Result<Self, StorageError> {
         debug!("Using common::etcd interface for RocksDB storage");
         // No need to connect to etcd endpoints - common::etcd handles RocksDB initialization
-        Ok(Self {})
+        Ok(Self::default())
+    }
+
+    /// Storage on `pool` instead of the endpoints of `common::etcd`
+    #[cfg(test)]
+    fn with_pool(pool: &'static EndpointPool) -> Self {
+        Self { pool }
     }
 
     /// Get a value by key
-    pub async fn get(&mut self, key: &str) -> Result<Option<String>, StorageError> {
+    pub async fn get(&self, key: &str) -> Result<Option<String>, StorageError> {
         debug!("Getting key: {}", key);
 
-        match common::etcd::get(key).await {
+        match self.pool.get(key).await {
             Ok(value) => Ok(Some(value)),
             Err(_) => Ok(None), // Key not found
         }
     }
 
     /// Put a key-value pair
-    pub async fn put(&mut self, key: &str, value: &str) -> Result<(), StorageError> {
+    pub async fn put(&self, key: &str, value: &str) -> Result<(), StorageError> {
         debug!("Putting key: {}, value length: {}", key, value.len());
 
-        common::etcd::put(key, value)
+        self.pool
+            .put(key, value)
             .await
             .map_err(|e| StorageError::OperationFailed(format!("Put operation failed: {}", e)))?;
 
@@ -43,22 +250,23 @@ impl EtcdClient {
     }
 
     /// Delete a key
-    pub async fn delete(&mut self, key: &str) -> Result<bool, StorageError> {
+    pub async fn delete(&self, key: &str) -> Result<bool, StorageError> {
         debug!("Deleting key: {}", key);
 
-        match common::etcd::delete(key).await {
+        match self.pool.delete(key).await {
             Ok(()) => Ok(true),
             Err(_) => Ok(false), // Key didn't exist
         }
     }
 
     /// List keys with a prefix
-    pub async fn list(&mut self, prefix: &str) -> Result<Vec<(String, String)>, StorageError> {
+    pub async fn list(&self, prefix: &str) -> Result<Vec<(String, String)>, StorageError> {
         debug!("Listing keys with prefix: {}", prefix);
 
-        let kvs = common::etcd::get_all_with_prefix(prefix)
-            .await
-            .map_err(|e| StorageError::OperationFailed(format!("List operation failed: {}", e)))?;
+        let kvs =
+            self.pool.get_all_with_prefix(prefix).await.map_err(|e| {
+                StorageError::OperationFailed(format!("List operation failed: {}", e))
+            })?;
 
         let results: Vec<(String, String)> = kvs.into_iter().map(|kv| (kv.0, kv.1)).collect();
 
@@ -66,7 +274,7 @@ impl EtcdClient {
     }
 
     /// Get JSON value by key
-    pub async fn get_json(&mut self, key: &str) -> Result<Option<Value>, StorageError> {
+    pub async fn get_json(&self, key: &str) -> Result<Option<Value>, StorageError> {
         if let Some(value_str) = self.get(key).await? {
             let value: Value = serde_json::from_str(&value_str).map_err(|e| {
                 StorageError::SerializationError(format!("JSON parse error: {}", e))
@@ -78,7 +286,7 @@ impl EtcdClient {
     }
 
     /// Put JSON value by key
-    pub async fn put_json(&mut self, key: &str, value: &Value) -> Result<(), StorageError> {
+    pub async fn put_json(&self, key: &str, value: &Value) -> Result<(), StorageError> {
         let value_str = serde_json::to_string(value).map_err(|e| {
             StorageError::SerializationError(format!("JSON serialize error: {}", e))
         })?;
@@ -87,6 +295,167 @@ impl EtcdClient {
     }
 }
 
+#[async_trait]
+impl StorageBackend for EtcdStorage {
+    fn name(&self) -> &'static str {
+        "etcd"
+    }
+
+    fn boxed_clone(&self) -> Box<dyn StorageBackend> {
+        Box::new(self.clone())
+    }
+
+    async fn get(&self, key: &str) -> Result<Option<String>, StorageError> {
+        EtcdStorage::get(self, key).await
+    }
+
+    async fn put(&self, key: &str, value: &str) -> Result<(), StorageError> {
+        EtcdStorage::put(self, key, value).await
+    }
+
+    async fn delete(&self, key: &str) -> Result<bool, StorageError> {
+        EtcdStorage::delete(self, key).await
+    }
+
+    async fn list(&self, prefix: &str) -> Result<Vec<(String, String)>, StorageError> {
+        EtcdStorage::list(self, prefix).await
+    }
+}
+
+/// Storage talking directly to a RocksDB gRPC service
+///
+/// Operations fail over across every endpoint in the configured list.
+#[derive(Clone)]
+pub struct RocksDbStorage {
+    pool: Arc<EndpointPool>,
+}
+
+impl RocksDbStorage {
+    /// Create a storage for the comma-separated RocksDB service `urls`
+    pub fn new(urls: &str) -> Result<Self, StorageError> {
+        let pool = EndpointPool::from_list(urls);
+        if pool.endpoints().is_empty() {
+            return Err(StorageError::ConnectionFailed(
+                "No RocksDB service endpoint configured".to_string(),
+            ));
+        }
+        debug!("Using RocksDB service at {:?}", pool.endpoints());
+        Ok(Self {
+            pool: Arc::new(pool),
+        })
+    }
+}
+
+#[async_trait]
+impl StorageBackend for RocksDbStorage {
+    fn name(&self) -> &'static str {
+        "rocksdb"
+    }
+
+    fn boxed_clone(&self) -> Box<dyn StorageBackend> {
+        Box::new(self.clone())
+    }
+
+    async fn get(&self, key: &str) -> Result<Option<String>, StorageError> {
+        debug!("Getting key: {}", key);
+
+        let response = self
+            .pool
+            .call(|mut client| async move {
+                client
+                    .get(GetRequest {
+                        key: key.to_string(),
+                    })
+                    .await
+                    .map(|response| response.into_inner())
+            })
+            .await
+            .map_err(|e| StorageError::OperationFailed(format!("Get operation failed: {}", e)))?;
+
+        // The service reports a missing key as an unsuccessful get
+        Ok(response.success.then_some(response.value))
+    }
+
+    async fn put(&self, key: &str, value: &str) -> Result<(), StorageError> {
+        debug!("Putting key: {}, value length: {}", key, value.len());
+
+        let response = self
+            .pool
+            .call(|mut client| async move {
+                client
+                    .put(PutRequest {
+                        key: key.to_string(),
+                        value: value.to_string(),
+                    })
+                    .await
+                    .map(|response| response.into_inner())
+            })
+            .await
+            .map_err(|e| StorageError::OperationFailed(format!("Put operation failed: {}", e)))?;
+
+        if response.success {
+            Ok(())
+        } else {
+            Err(StorageError::OperationFailed(format!(
+                "Put operation failed: {}",
+                response.error
+            )))
+        }
+    }
+
+    async fn delete(&self, key: &str) -> Result<bool, StorageError> {
+        debug!("Deleting key: {}", key);
+
+        let response = self
+            .pool
+            .call(|mut client| async move {
+                client
+                    .delete(DeleteRequest {
+                        key: key.to_string(),
+                    })
+                    .await
+                    .map(|response| response.into_inner())
+            })
+            .await
+            .map_err(|e| {
+                StorageError::OperationFailed(format!("Delete operation failed: {}", e))
+            })?;
+
+        Ok(response.success)
+    }
+
+    async fn list(&self, prefix: &str) -> Result<Vec<(String, String)>, StorageError> {
+        debug!("Listing keys with prefix: {}", prefix);
+
+        let response = self
+            .pool
+            .call(|mut client| async move {
+                client
+                    .get_by_prefix(GetByPrefixRequest {
+                        prefix: prefix.to_string(),
+                        limit: 0, // 0 means no limit
+                    })
+                    .await
+                    .map(|response| response.into_inner())
+            })
+            .await
+            .map_err(|e| StorageError::OperationFailed(format!("List operation failed: {}", e)))?;
+
+        if !response.error.is_empty() {
+            return Err(StorageError::OperationFailed(format!(
+                "List operation failed: {}",
+                response.error
+            )));
+        }
+
+        Ok(response
+            .pairs
+            .into_iter()
+            .map(|kv| (kv.key, kv.value))
+            .collect())
+    }
+}
+
 /// Storage interface trait for dependency injection and testing
 #[async_trait]
 #[allow(dead_code)]
@@ -100,29 +469,111 @@ pub trait Storage: Send + Sync {
 }
 
 #[async_trait]
-impl Storage for EtcdClient {
+impl Storage for EtcdStorage {
+    async fn get(&mut self, key: &str) -> Result<Option<String>, StorageError> {
+        EtcdStorage::get(self, key).await
+    }
+
+    async fn put(&mut self, key: &str, value: &str) -> Result<(), StorageError> {
+        EtcdStorage::put(self, key, value).await
+    }
+
+    async fn delete(&mut self, key: &str) -> Result<bool, StorageError> {
+        EtcdStorage::delete(self, key).await
+    }
+
+    async fn list(&mut self, prefix: &str) -> Result<Vec<(String, String)>, StorageError> {
+        EtcdStorage::list(self, prefix).await
+    }
+
+    async fn get_json(&mut self, key: &str) -> Result<Option<Value>, StorageError> {
+        EtcdStorage::get_json(self, key).await
+    }
+
+    async fn put_json(&mut self, key: &str, value: &Value) -> Result<(), StorageError> {
+        EtcdStorage::put_json(self, key, value).await
+    }
+}
+
+/// Any backend can be handed to a manager as its `Storage`
+#[async_trait]
+impl Storage for Box<dyn StorageBackend> {
     async fn get(&mut self, key: &str) -> Result<Option<String>, StorageError> {
-        self.get(key).await
+        self.as_ref().get(key).await
     }
 
     async fn put(&mut self, key: &str, value: &str) -> Result<(), StorageError> {
-        self.put(key, value).await
+        self.as_ref().put(key, value).await
     }
 
     async fn delete(&mut self, key: &str) -> Result<bool, StorageError> {
-        self.delete(key).await
+        self.as_ref().delete(key).await
     }
 
     async fn list(&mut self, prefix: &str) -> Result<Vec<(String, String)>, StorageError> {
-        self.list(prefix).await
+        self.as_ref().list(prefix).await
     }
 
     async fn get_json(&mut self, key: &str) -> Result<Option<Value>, StorageError> {
-        self.get_json(key).await
+        match self.as_ref().get(key).await? {
+            Some(value_str) => serde_json::from_str(&value_str)
+                .map(Some)
+                .map_err(|e| StorageError::SerializationError(format!("JSON parse error: {}", e))),
+            None => Ok(None),
+        }
     }
 
     async fn put_json(&mut self, key: &str, value: &Value) -> Result<(), StorageError> {
-        self.put_json(key, value).await
+        let value_str = serde_json::to_string(value).map_err(|e| {
+            StorageError::SerializationError(format!("JSON serialize error: {}", e))
+        })?;
+        self.as_ref().put(key, &value_str).await
+    }
+}
+
+/// In-memory backend for tests
+#[cfg(test)]
+#[derive(Debug, Clone, Default)]
+pub struct MemoryStorage {
+    data: std::sync::Arc<std::sync::Mutex<std::collections::BTreeMap<String, String>>>,
+}
+
+#[cfg(test)]
+#[async_trait]
+impl StorageBackend for MemoryStorage {
+    fn name(&self) -> &'static str {
+        "memory"
+    }
+
+    fn boxed_clone(&self) -> Box<dyn StorageBackend> {
+        Box::new(self.clone())
+    }
+
+    async fn get(&self, key: &str) -> Result<Option<String>, StorageError> {
+        Ok(self.data.lock().unwrap().get(key).cloned())
+    }
+
+    async fn put(&self, key: &str, value: &str) -> Result<(), StorageError> {
+        self.data
+            .lock()
+            .unwrap()
+            .insert(key.to_string(), value.to_string());
+        Ok(())
+    }
+
+    async fn delete(&self, key: &str) -> Result<bool, StorageError> {
+        Ok(self.data.lock().unwrap().remove(key).is_some())
+    }
+
+    async fn list(&self, prefix: &str) -> Result<Vec<(String, String)>, StorageError> {
+        Ok(self
+            .data
+            .lock()
+            .unwrap()
+            .range(prefix.to_string()..)
+            .take_while(|(k, _)| k.starts_with(prefix))
+            .map(|(k, v)| (k.clone(), v.clone()))
+            .collect())
     }
 }
 
@@ -132,7 +583,7 @@ pub struct KeyPrefixes;
 impl KeyPrefixes {
     pub const CONFIG: &'static str = "/piccolo/settings/configs/";
     pub const HISTORY: &'static str = "/piccolo/settings/history/";
-    pub const METRICS: &'static str = "/piccolo/metrics/";
+    pub const METRICS: &'static str = common::keys::METRICS_PREFIX;
     pub const FILTERS: &'static str = "/piccolo/settings/filters/";
     pub const SCHEMAS: &'static str = "/piccolo/settings/schemas/";
 }
@@ -147,7 +598,7 @@ pub fn history_key(config_path: &str, version: u64) -> String {
 }
 #[allow(dead_code)]
 pub fn metrics_key(resource_type: &str, resource_id: &str) -> String {
-    format!("{}{}/{}", KeyPrefixes::METRICS, resource_type, resource_id)
+    common::keys::metrics(resource_type, resource_id)
 }
 
 pub fn filter_key(filter_id: &str) -> String {
@@ -161,9 +612,16 @@ pub fn schema_key(schema_type: &str) -> String {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use common::rocksdbservice::rocks_db_service_server::{RocksDbService, RocksDbServiceServer};
+    use common::rocksdbservice::{
+        BatchPutRequest, BatchPutResponse, DeleteResponse, GetByPrefixResponse, GetResponse,
+        HealthRequest, HealthResponse, KeyValue, ListKeysRequest, ListKeysResponse, PutResponse,
+    };
     use serde_json::json;
     use std::collections::HashMap;
     use tokio;
+    use tokio_stream::wrappers::TcpListenerStream;
+    use tonic::{Request, Response, Status};
 
     /// Mock storage implementation for testing
     #[derive(Default)]
@@ -751,4 +1209,366 @@ mod tests {
         assert_eq!(result[0]["name"], "Item 1");
         assert_eq!(result[2]["id"], 3);
     }
+
+    #[tokio::test]
+    async fn test_backend_crud() {
+        let backend = MemoryStorage::default();
+
+        backend.put("/a/1", "one").await.unwrap();
+        backend.put("/a/2", "two").await.unwrap();
+        backend.put("/b/1", "three").await.unwrap();
+        assert_eq!(backend.get("/a/1").await.unwrap(), Some("one".to_string()));
+        assert_eq!(backend.get("/missing").await.unwrap(), None);
+
+        backend.put("/a/1", "uno").await.unwrap();
+        assert_eq!(backend.get("/a/1").await.unwrap(), Some("uno".to_string()));
+
+        assert_eq!(
+            backend.list("/a/").await.unwrap(),
+            vec![
+                ("/a/1".to_string(), "uno".to_string()),
+                ("/a/2".to_string(), "two".to_string())
+            ]
+        );
+        assert!(backend.list("/c/").await.unwrap().is_empty());
+
+        assert!(backend.delete("/a/1").await.unwrap());
+        assert!(!backend.delete("/a/1").await.unwrap());
+        assert_eq!(backend.get("/a/1").await.unwrap(), None);
+    }
+
+    #[tokio::test]
+    async fn test_backend_clones_share_data() {
+        let backend: Box<dyn StorageBackend> = Box::new(MemoryStorage::default());
+        let clone = backend.boxed_clone();
+
+        backend.as_ref().put("key", "value").await.unwrap();
+        assert_eq!(
+            clone.as_ref().get("key").await.unwrap(),
+            Some("value".to_string())
+        );
+        assert_eq!(clone.name(), "memory");
+    }
+
+    #[tokio::test]
+    async fn test_boxed_backend_as_storage() {
+        let mut storage: Box<dyn StorageBackend> = Box::new(MemoryStorage::default());
+        let value = json!({"level": "info", "output": ["console"]});
+
+        Storage::put_json(&mut storage, &config_key("logging"), &value)
+            .await
+            .unwrap();
+        assert_eq!(
+            Storage::get_json(&mut storage, &config_key("logging"))
+                .await
+                .unwrap(),
+            Some(value)
+        );
+        assert_eq!(
+            Storage::list(&mut storage, KeyPrefixes::CONFIG)
+                .await
+                .unwrap()
+                .len(),
+            1
+        );
+        assert!(Storage::delete(&mut storage, &config_key("logging"))
+            .await
+            .unwrap());
+        assert_eq!(
+            Storage::get(&mut storage, &config_key("logging"))
+                .await
+                .unwrap(),
+            None
+        );
+
+        Storage::put(&mut storage, "bad", "{not json")
+            .await
+            .unwrap();
+        assert!(matches!(
+            Storage::get_json(&mut storage, "bad").await,
+            Err(StorageError::SerializationError(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_create_backend_selects_kind() {
+        let etcd = create_backend(BackendKind::Etcd, vec![], "").await.unwrap();
+        assert_eq!(etcd.name(), "etcd");
+
+        let rocksdb = create_backend(BackendKind::Rocksdb, vec![], "http://localhost:47007")
+            .await
+            .unwrap();
+        assert_eq!(rocksdb.name(), "rocksdb");
+
+        assert_eq!(BackendKind::default(), BackendKind::Etcd);
+    }
+
+    #[test]
+    fn test_rocksdb_storage_endpoint_list() {
+        assert!(matches!(
+            RocksDbStorage::new(" , "),
+            Err(StorageError::ConnectionFailed(_))
+        ));
+        let storage = RocksDbStorage::new("rocksdb-1:47007, http://rocksdb-2:47007").unwrap();
+        assert_eq!(
+            storage.pool.endpoints(),
+            &["http://rocksdb-1:47007", "http://rocksdb-2:47007"]
+        );
+    }
+
+    /// In-memory RocksDB service, answering like the real one
+    #[derive(Default)]
+    struct MockRocksDb {
+        data: std::sync::Mutex<std::collections::BTreeMap<String, String>>,
+    }
+
+    #[tonic::async_trait]
+    impl RocksDbService for MockRocksDb {
+        async fn health(
+            &self,
+            _request: Request<HealthRequest>,
+        ) -> Result<Response<HealthResponse>, Status> {
+            Ok(Response::new(HealthResponse {
+                status: "healthy".to_string(),
+                ..Default::default()
+            }))
+        }
+
+        async fn put(&self, request: Request<PutRequest>) -> Result<Response<PutResponse>, Status> {
+            let req = request.into_inner();
+            self.data.lock().unwrap().insert(req.key, req.value);
+            Ok(Response::new(PutResponse {
+                success: true,
+                error: String::new(),
+            }))
+        }
+
+        async fn get(&self, request: Request<GetRequest>) -> Result<Response<GetResponse>, Status> {
+            let value = self
+                .data
+                .lock()
+                .unwrap()
+                .get(&request.into_inner().key)
+                .cloned();
+            Ok(Response::new(GetResponse {
+                success: value.is_some(),
+                value: value.unwrap_or_default(),
+                message: String::new(),
+            }))
+        }
+
+        async fn delete(
+            &self,
+            request: Request<DeleteRequest>,
+        ) -> Result<Response<DeleteResponse>, Status> {
+            // Deleting a missing key succeeds, as in RocksDB
+            self.data.lock().unwrap().remove(&request.into_inner().key);
+            Ok(Response::new(DeleteResponse {
+                success: true,
+                error: String::new(),
+            }))
+        }
+
+        async fn batch_put(
+            &self,
+            _request: Request<BatchPutRequest>,
+        ) -> Result<Response<BatchPutResponse>, Status> {
+            Err(Status::unimplemented("batch_put"))
+        }
+
+        async fn get_by_prefix(
+            &self,
+            request: Request<GetByPrefixRequest>,
+        ) -> Result<Response<GetByPrefixResponse>, Status> {
+            let prefix = request.into_inner().prefix;
+            let pairs: Vec<KeyValue> = self
+                .data
+                .lock()
+                .unwrap()
+                .iter()
+                .filter(|(k, _)| k.starts_with(&prefix))
+                .map(|(k, v)| KeyValue {
+                    key: k.clone(),
+                    value: v.clone(),
+                })
+                .collect();
+            Ok(Response::new(GetByPrefixResponse {
+                total_count: pairs.len() as i32,
+                pairs,
+                error: String::new(),
+            }))
+        }
+
+        async fn list_keys(
+            &self,
+            _request: Request<ListKeysRequest>,
+        ) -> Result<Response<ListKeysResponse>, Status> {
+            Err(Status::unimplemented("list_keys"))
+        }
+    }
+
+    /// Start an in-process RocksDB service and return its URL
+    async fn start_rocksdb() -> String {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(
+            tonic::transport::Server::builder()
+                .add_service(RocksDbServiceServer::new(MockRocksDb::default()))
+                .serve_with_incoming(TcpListenerStream::new(listener)),
+        );
+        format!("http://{}", addr)
+    }
+
+    /// Behavior every backend must share, so the managers work on any of them
+    async fn check_backend_contract(backend: &dyn StorageBackend) {
+        let name = backend.name();
+        let prefix = "/piccolo/settings/contract/";
+        let key = |k: &str| format!("{}{}", prefix, k);
+
+        backend.put(&key("a"), "one").await.unwrap();
+        backend.put(&key("b"), "two").await.unwrap();
+        backend
+            .put("/piccolo/settings/other", "three")
+            .await
+            .unwrap();
+        assert_eq!(
+            backend.get(&key("a")).await.unwrap(),
+            Some("one".to_string()),
+            "{}",
+            name
+        );
+        assert_eq!(
+            backend.get(&key("missing")).await.unwrap(),
+            None,
+            "{}",
+            name
+        );
+
+        backend.put(&key("a"), "uno").await.unwrap();
+        let mut listed = backend.list(prefix).await.unwrap();
+        listed.sort();
+        assert_eq!(
+            listed,
+            vec![(key("a"), "uno".to_string()), (key("b"), "two".to_string())],
+            "{}",
+            name
+        );
+        assert!(backend.list("/piccolo/none/").await.unwrap().is_empty());
+
+        backend.delete(&key("b")).await.unwrap();
+        assert_eq!(backend.get(&key("b")).await.unwrap(), None, "{}", name);
+        backend.delete(&key("b")).await.unwrap();
+
+        // Changes through another handle are seen by the watcher
+        let mut events = backend
+            .watch(prefix, Duration::from_millis(20))
+            .await
+            .unwrap();
+        let other = backend.boxed_clone();
+        let other = other.as_ref();
+        other.put(&key("c"), "drei").await.unwrap();
+        other.delete(&key("a")).await.unwrap();
+        other
+            .put("/piccolo/settings/other", "ignored")
+            .await
+            .unwrap();
+
+        let mut seen = Vec::new();
+        while seen.len() < 2 {
+            let event = tokio::time::timeout(Duration::from_secs(5), events.recv())
+                .await
+                .unwrap()
+                .unwrap();
+            seen.push(event);
+        }
+        // The changes may span two polls
+        seen.sort_by(|a, b| a.key().cmp(b.key()));
+        assert_eq!(
+            seen,
+            vec![
+                WatchEvent::Delete { key: key("a") },
+                WatchEvent::Put {
+                    key: key("c"),
+                    value: "drei".to_string()
+                },
+            ],
+            "{}",
+            name
+        );
+    }
+
+    #[tokio::test]
+    async fn test_memory_backend_contract() {
+        check_backend_contract(&MemoryStorage::default()).await;
+    }
+
+    #[tokio::test]
+    async fn test_rocksdb_backend_contract() {
+        let url = start_rocksdb().await;
+        check_backend_contract(&RocksDbStorage::new(&url).unwrap()).await;
+    }
+
+    #[tokio::test]
+    async fn test_etcd_backend_contract() {
+        let url = start_rocksdb().await;
+        let pool = Box::leak(Box::new(EndpointPool::from_list(&url)));
+        check_backend_contract(&EtcdStorage::with_pool(pool)).await;
+    }
+
+    #[tokio::test]
+    async fn test_watch_stops_when_receiver_dropped() {
+        let backend = MemoryStorage::default();
+        let events = backend
+            .watch("/w/", Duration::from_millis(5))
+            .await
+            .unwrap();
+        drop(events);
+        backend.put("/w/key", "value").await.unwrap();
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        assert_eq!(
+            backend.get("/w/key").await.unwrap(),
+            Some("value".to_string())
+        );
+    }
+
+    #[test]
+    fn test_storage_config_load() {
+        let dir = tempfile::tempdir().unwrap();
+
+        let missing = StorageConfig::load(&dir.path().join("missing.yaml")).unwrap();
+        assert_eq!(missing, StorageConfig::default());
+        assert_eq!(missing.backend, BackendKind::Etcd);
+
+        let path = dir.path().join("settings.yaml");
+        std::fs::write(
+            &path,
+            "host:\n  name: HPC\nstorage:\n  backend: rocksdb\n  rocksdb_url: http://rocksdb:47007\n",
+        )
+        .unwrap();
+        let config = StorageConfig::load(&path).unwrap();
+        assert_eq!(config.backend, BackendKind::Rocksdb);
+        assert_eq!(config.rocksdb_url, "http://rocksdb:47007");
+
+        std::fs::write(&path, "host:\n  name: HPC\n").unwrap();
+        assert_eq!(
+            StorageConfig::load(&path).unwrap(),
+            StorageConfig::default()
+        );
+
+        std::fs::write(&path, "storage:\n  backend: redis\n").unwrap();
+        assert!(matches!(
+            StorageConfig::load(&path),
+            Err(StorageError::SerializationError(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_rocksdb_storage_unreachable_service() {
+        // Port 9 (discard) on localhost is never served in the test environment
+        let backend = RocksDbStorage::new("http://127.0.0.1:9").unwrap();
+        assert!(matches!(
+            backend.get("key").await,
+            Err(StorageError::OperationFailed(_))
+        ));
+    }
 }
@@ -15,6 +15,9 @@ pub enum SettingsError {
     #[error("Validation error: {0}")]
     Validation(String),
 
+    #[error("Precondition failed: {0}")]
+    PreconditionFailed(String),
+
     #[error("History error: {0}")]
     History(String),
 
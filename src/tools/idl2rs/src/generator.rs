@@ -0,0 +1,365 @@
+/*
+* SPDX-FileCopyrightText: Copyright 2024 LG Electronics Inc.
+* SPDX-License-Identifier: Apache-2.0
+*/
+//! Conversion of IDL files into Rust modules with topic metadata
+//!
+//! Every struct in an IDL file gets a `Piccoloable` impl. Its topic comes
+//! from a `// @topic <name>` comment right before the struct, or else from
+//! the topic prefix followed by the struct name.
+
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// Comment marking the topic of the next struct
+const TOPIC_ANNOTATION: &str = "@topic";
+
+/// Trait written to the generated `mod.rs` and implemented by every struct
+const PICCOLOABLE_TRAIT: &str = "/// DDS topic metadata of a generated type
+pub trait Piccoloable {
+    /// Topic the type is published on
+    fn topic_name() -> &'static str;
+    /// IDL type name
+    fn type_name() -> &'static str;
+}
+";
+
+/// A struct declared in an IDL file
+#[derive(Debug, Clone, PartialEq)]
+pub struct IdlStruct {
+    /// Enclosing IDL modules, outermost first
+    pub modules: Vec<String>,
+    pub name: String,
+    /// Topic given by a `// @topic` annotation
+    pub topic: Option<String>,
+}
+
+impl IdlStruct {
+    /// Rust path of the generated type, relative to its file module
+    pub fn path(&self) -> String {
+        let mut parts = self.modules.clone();
+        parts.push(self.name.clone());
+        parts.join("::")
+    }
+
+    /// IDL scoped name, e.g. `vehicle::GearState`
+    pub fn type_name(&self) -> String {
+        self.path()
+    }
+}
+
+/// Outcome of converting a directory
+#[derive(Debug, Default)]
+pub struct Report {
+    /// Generated module names, in the order they were written
+    pub modules: Vec<String>,
+    /// IDL files that could not be converted, with the reason
+    pub failures: Vec<(PathBuf, String)>,
+}
+
+/// Finds the structs of an IDL file and their topic annotations
+pub fn parse_structs(idl: &str) -> Vec<IdlStruct> {
+    let mut structs = Vec::new();
+    // Open scopes: Some(name) for a module, None for any other brace
+    let mut scopes: Vec<Option<String>> = Vec::new();
+    let mut topic: Option<String> = None;
+
+    for line in idl.lines() {
+        let line = line.trim();
+        if let Some(comment) = line.strip_prefix("//") {
+            if let Some(name) = comment.trim().strip_prefix(TOPIC_ANNOTATION) {
+                topic = Some(name.trim().to_string());
+            }
+            continue;
+        }
+
+        let mut words = line.split(|c: char| c.is_whitespace() || c == '{');
+        match (words.next(), words.next()) {
+            (Some("module"), Some(name)) if !name.is_empty() => {
+                scopes.push(Some(name.to_string()));
+            }
+            (Some("struct"), Some(name)) if !name.is_empty() => {
+                structs.push(IdlStruct {
+                    modules: scopes.iter().flatten().cloned().collect(),
+                    name: name.to_string(),
+                    topic: topic.take(),
+                });
+                scopes.push(None);
+            }
+            _ => {
+                for _ in 0..line.matches('{').count() {
+                    scopes.push(None);
+                }
+            }
+        }
+        for _ in 0..line.matches('}').count() {
+            scopes.pop();
+        }
+    }
+    structs
+}
+
+/// Generates the `Piccoloable` impl of a struct
+pub fn piccoloable_impl(idl_struct: &IdlStruct, topic_prefix: &str) -> String {
+    let topic = idl_struct
+        .topic
+        .clone()
+        .unwrap_or_else(|| format!("{}{}", topic_prefix, idl_struct.name));
+    format!(
+        "impl Piccoloable for {} {{
+    fn topic_name() -> &'static str {{
+        {:?}
+    }}
+
+    fn type_name() -> &'static str {{
+        {:?}
+    }}
+}}
+",
+        idl_struct.path(),
+        topic,
+        idl_struct.type_name()
+    )
+}
+
+/// Generates the Rust module of one IDL file
+///
+/// # Arguments
+///
+/// * `file_name` - IDL file name, written in the module header
+/// * `idl` - IDL source
+/// * `topic_prefix` - Topic prefix of structs without a `@topic` annotation
+/// * `compile` - Converts IDL into Rust type definitions
+pub fn generate_module<F>(
+    file_name: &str,
+    idl: &str,
+    topic_prefix: &str,
+    compile: F,
+) -> Result<String, String>
+where
+    F: Fn(&str) -> Result<String, String>,
+{
+    let types = compile(idl)?;
+    let mut module = format!(
+        "// Generated by idl2rs from {}. Do not edit.\n\nuse super::Piccoloable;\n\n{}\n",
+        file_name,
+        types.trim_end()
+    );
+    for idl_struct in parse_structs(idl) {
+        module.push('\n');
+        module.push_str(&piccoloable_impl(&idl_struct, topic_prefix));
+    }
+    Ok(module)
+}
+
+/// Generates `mod.rs`, declaring the `Piccoloable` trait and every module
+pub fn generate_mod_rs(modules: &[String]) -> String {
+    let mut mod_rs = format!(
+        "// Generated by idl2rs. Do not edit.\n\n{}\n",
+        PICCOLOABLE_TRAIT
+    );
+    for module in modules {
+        mod_rs.push_str(&format!("pub mod {};\n", module));
+    }
+    mod_rs
+}
+
+/// Rust module name of an IDL file, e.g. `CabinDoor.idl` becomes `cabin_door`
+pub fn module_name(path: &Path) -> String {
+    let stem = path
+        .file_stem()
+        .map(|s| s.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let mut name = String::new();
+    let mut prev_lower = false;
+    for c in stem.chars() {
+        if c.is_ascii_uppercase() {
+            if prev_lower {
+                name.push('_');
+            }
+            name.push(c.to_ascii_lowercase());
+            prev_lower = false;
+        } else if c.is_ascii_alphanumeric() {
+            name.push(c);
+            prev_lower = true;
+        } else {
+            name.push('_');
+            prev_lower = false;
+        }
+    }
+    name
+}
+
+/// Converts every IDL file under `src` into a module in `dst`
+///
+/// Files that fail to convert are recorded in the report and skipped.
+/// `mod.rs` lists the modules that were generated.
+///
+/// # Errors
+///
+/// Returns an error if `src` cannot be read or `dst` cannot be written.
+pub fn convert_dir<F>(src: &Path, dst: &Path, topic_prefix: &str, compile: F) -> io::Result<Report>
+where
+    F: Fn(&str) -> Result<String, String>,
+{
+    let mut files = Vec::new();
+    collect_idl_files(src, &mut files)?;
+    files.sort();
+    fs::create_dir_all(dst)?;
+
+    let mut report = Report::default();
+    for path in files {
+        let module = module_name(&path);
+        if report.modules.contains(&module) {
+            report
+                .failures
+                .push((path, format!("module `{}` is generated twice", module)));
+            continue;
+        }
+
+        let file_name = path
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_default();
+        let generated = fs::read_to_string(&path)
+            .map_err(|e| e.to_string())
+            .and_then(|idl| generate_module(&file_name, &idl, topic_prefix, &compile));
+        match generated {
+            Ok(contents) => {
+                fs::write(dst.join(format!("{}.rs", module)), contents)?;
+                report.modules.push(module);
+            }
+            Err(e) => report.failures.push((path, e)),
+        }
+    }
+
+    fs::write(dst.join("mod.rs"), generate_mod_rs(&report.modules))?;
+    Ok(report)
+}
+
+fn collect_idl_files(dir: &Path, files: &mut Vec<PathBuf>) -> io::Result<()> {
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.is_dir() {
+            collect_idl_files(&path, files)?;
+        } else if path.extension().is_some_and(|ext| ext == "idl") {
+            files.push(path);
+        }
+    }
+    Ok(())
+}
+
+//Unit Test Cases
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FIXTURES: &str = "./tests/fixtures";
+
+    fn fixture(name: &str) -> String {
+        fs::read_to_string(Path::new(FIXTURES).join(name)).unwrap()
+    }
+
+    /// Stands in for the IDL compiler, rejecting files containing `invalid`
+    fn fake_compile(idl: &str) -> Result<String, String> {
+        if idl.contains("invalid") {
+            Err("unexpected token".to_string())
+        } else {
+            Ok("pub struct Generated;\n".to_string())
+        }
+    }
+
+    #[test]
+    fn test_parse_annotated_structs() {
+        let structs = parse_structs(&fixture("GearState.idl"));
+
+        assert_eq!(
+            structs,
+            vec![
+                IdlStruct {
+                    modules: vec!["powertrain".to_string()],
+                    name: "GearState".to_string(),
+                    topic: Some("/rt/piccolo/Gear_State".to_string()),
+                },
+                IdlStruct {
+                    modules: vec!["powertrain".to_string()],
+                    name: "GearCommand".to_string(),
+                    topic: None,
+                },
+                IdlStruct {
+                    modules: vec![],
+                    name: "Heartbeat".to_string(),
+                    topic: Some("/rt/piccolo/Heartbeat".to_string()),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_piccoloable_impl_with_annotation() {
+        let structs = parse_structs(&fixture("GearState.idl"));
+
+        assert_eq!(
+            piccoloable_impl(&structs[0], "/rt/default/"),
+            r#"impl Piccoloable for powertrain::GearState {
+    fn topic_name() -> &'static str {
+        "/rt/piccolo/Gear_State"
+    }
+
+    fn type_name() -> &'static str {
+        "powertrain::GearState"
+    }
+}
+"#
+        );
+    }
+
+    #[test]
+    fn test_piccoloable_impl_uses_topic_prefix() {
+        let structs = parse_structs(&fixture("GearState.idl"));
+
+        assert_eq!(
+            piccoloable_impl(&structs[1], "/rt/default/"),
+            r#"impl Piccoloable for powertrain::GearCommand {
+    fn topic_name() -> &'static str {
+        "/rt/default/GearCommand"
+    }
+
+    fn type_name() -> &'static str {
+        "powertrain::GearCommand"
+    }
+}
+"#
+        );
+    }
+
+    #[test]
+    fn test_module_name() {
+        assert_eq!(module_name(Path::new("a/CabinDoor.idl")), "cabin_door");
+        assert_eq!(module_name(Path::new("Exterior.idl")), "exterior");
+        assert_eq!(module_name(Path::new("gear-state.idl")), "gear_state");
+    }
+
+    #[test]
+    fn test_convert_dir_skips_invalid_files() {
+        let dst = std::env::temp_dir().join("idl2rs-convert-dir");
+        let _ = fs::remove_dir_all(&dst);
+
+        let report = convert_dir(Path::new(FIXTURES), &dst, "/rt/piccolo/", fake_compile).unwrap();
+
+        assert_eq!(report.modules, vec!["gear_state"]);
+        assert_eq!(report.failures.len(), 1);
+        assert!(report.failures[0].0.ends_with("Invalid.idl"));
+        assert_eq!(report.failures[0].1, "unexpected token");
+
+        let module = fs::read_to_string(dst.join("gear_state.rs")).unwrap();
+        assert!(module.starts_with("// Generated by idl2rs from GearState.idl."));
+        assert!(module.contains("use super::Piccoloable;"));
+        assert!(module.contains("impl Piccoloable for Heartbeat {"));
+
+        let mod_rs = fs::read_to_string(dst.join("mod.rs")).unwrap();
+        assert!(mod_rs.contains("pub trait Piccoloable {"));
+        assert!(mod_rs.ends_with("pub mod gear_state;\n"));
+    }
+}
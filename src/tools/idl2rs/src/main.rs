@@ -2,51 +2,65 @@
 * SPDX-FileCopyrightText: Copyright 2024 LG Electronics Inc.
 * SPDX-License-Identifier: Apache-2.0
 */
-fn main() {
-    println!("run cargo test in tools/idl2rs folder");
-}
+//! idl2rs - converts DDS IDL files into Rust modules
+//!
+//! Every IDL file under `--src` becomes a module in `--dst`, next to a
+//! `mod.rs` declaring them and the `Piccoloable` trait.
 
-#[cfg(test)]
-mod tests {
-    use std::fs;
-    use std::io;
-    use std::path::Path;
+mod generator;
 
-    #[test]
-    pub fn idl2rs() {
-        let src = Path::new("./src/idl");
-        let dst = Path::new("./src");
+use clap::Parser;
+use std::path::PathBuf;
+use std::process::ExitCode;
 
-        assert!(copy_dir(src, dst).is_ok());
-    }
+#[derive(Parser)]
+#[command(name = "idl2rs")]
+#[command(about = "Convert DDS IDL files into Rust modules")]
+#[command(version)]
+struct Cli {
+    /// Directory searched recursively for `.idl` files
+    #[arg(long, default_value = "./src/idl")]
+    src: PathBuf,
 
-    pub fn copy_dir(src: &Path, dst: &Path) -> io::Result<()> {
-        if !dst.exists() {
-            fs::create_dir_all(dst)?;
-        }
+    /// Directory the Rust modules and `mod.rs` are written to
+    #[arg(long, default_value = "./generated")]
+    dst: PathBuf,
+
+    /// Topic prefix of structs without a `// @topic` annotation
+    #[arg(long, default_value = "/rt/piccolo/")]
+    topic_prefix: String,
+}
+
+fn main() -> ExitCode {
+    let cli = Cli::parse();
 
-        for entry in fs::read_dir(src)? {
-            let entry = entry?;
-            let entry_path = entry.path();
-            let file_name = entry_path.file_name().unwrap();
-            let mut dest_path = dst.join(file_name);
-
-            if entry_path.is_dir() {
-                copy_dir(&entry_path, &dest_path)?;
-            } else if entry_path.extension().unwrap() == "idl" {
-                println!("{:?}  {:?}", entry_path, dest_path);
-                let contents = get_contents_string(&entry_path);
-                dest_path.set_extension("rs");
-                fs::write(dest_path, contents)?;
-            }
+    let compile = |idl: &str| dust_dds_gen::compile_idl(idl).map_err(|e| e.to_string());
+    let report = match generator::convert_dir(&cli.src, &cli.dst, &cli.topic_prefix, compile) {
+        Ok(report) => report,
+        Err(e) => {
+            eprintln!(
+                "Failed to convert {} into {}: {}",
+                cli.src.display(),
+                cli.dst.display(),
+                e
+            );
+            return ExitCode::FAILURE;
         }
-        Ok(())
+    };
+
+    for module in &report.modules {
+        println!(
+            "generated {}",
+            cli.dst.join(format!("{}.rs", module)).display()
+        );
+    }
+    for (path, reason) in &report.failures {
+        eprintln!("{}: {}", path.display(), reason);
     }
 
-    pub fn get_contents_string(src: &Path) -> String {
-        let idl_src = fs::read_to_string(src).unwrap();
-        let result = dust_dds_gen::compile_idl(&idl_src).unwrap();
-        println!("{result}");
-        result
+    if report.failures.is_empty() {
+        ExitCode::SUCCESS
+    } else {
+        ExitCode::FAILURE
     }
 }
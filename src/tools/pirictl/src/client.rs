@@ -5,16 +5,27 @@
 //! REST API client for SettingsService
 
 use crate::error::{CliError, Result};
-use reqwest::Client;
+use reqwest::header::{ACCEPT, CONTENT_TYPE};
+use reqwest::{Client, RequestBuilder, Response};
 use serde_json::Value;
+use std::sync::Mutex;
 use std::time::Duration;
 
 /// HTTP client for communicating with SettingsService
 pub struct SettingsClient {
     client: Client,
     base_url: String,
+    /// Successful responses received so far, reported by `--output json`
+    responses: Mutex<Vec<Value>>,
+    /// Ask for CBOR responses instead of JSON
+    cbor: bool,
+    /// Request timeout, only bounding the connection of streamed requests
+    timeout: Duration,
 }
 
+/// Media type of CBOR documents
+pub const CBOR: &str = "application/cbor";
+
 impl SettingsClient {
     /// Create a new SettingsClient
     ///
@@ -30,31 +41,122 @@ impl SettingsClient {
         Ok(Self {
             client,
             base_url: base_url.trim_end_matches('/').to_string(),
+            responses: Mutex::new(Vec::new()),
+            cbor: false,
+            timeout: Duration::from_secs(timeout),
         })
     }
 
+    /// Ask the service for CBOR responses, which are smaller than JSON
+    ///
+    /// Responses are still returned as JSON values. A service answering in
+    /// JSON anyway is understood as well.
+    pub fn with_cbor(mut self, cbor: bool) -> Self {
+        self.cbor = cbor;
+        self
+    }
+
+    /// Add the `Accept` header of the selected encoding
+    fn accept(&self, request: RequestBuilder) -> RequestBuilder {
+        if self.cbor {
+            request.header(ACCEPT, CBOR)
+        } else {
+            request
+        }
+    }
+
+    /// Decode a response body according to its `Content-Type`
+    async fn decode(response: Response) -> Result<Value> {
+        let is_cbor = response
+            .headers()
+            .get(CONTENT_TYPE)
+            .and_then(|value| value.to_str().ok())
+            .is_some_and(|value| value.starts_with(CBOR));
+        let bytes = response.bytes().await?;
+        if bytes.is_empty() {
+            Ok(Value::Null)
+        } else if is_cbor {
+            decode_cbor(&bytes)
+        } else {
+            Ok(serde_json::from_slice(&bytes)?)
+        }
+    }
+
+    /// Take every response body received since the last call
+    pub fn take_responses(&self) -> Vec<Value> {
+        match self.responses.lock() {
+            Ok(mut responses) => std::mem::take(&mut *responses),
+            Err(_) => Vec::new(),
+        }
+    }
+
+    /// Report `value` instead of the responses received so far
+    ///
+    /// Lets a command that post-processes server data, e.g. by filtering it,
+    /// put its own result in the `--output json` document.
+    pub fn replace_responses(&self, value: Value) {
+        if let Ok(mut responses) = self.responses.lock() {
+            *responses = vec![value];
+        }
+    }
+
+    /// Remember a successful response body for structured output
+    fn record(&self, value: Value) -> Value {
+        if let Ok(mut responses) = self.responses.lock() {
+            responses.push(value.clone());
+        }
+        value
+    }
+
     /// Make a GET request to the specified endpoint
     ///
     /// # Arguments
     /// * `endpoint` - API endpoint (e.g., "/api/v1/metrics")
     pub async fn get(&self, endpoint: &str) -> Result<Value> {
         let url = format!("{}{}", self.base_url, endpoint);
-        let response = self.client.get(&url).send().await?;
+        let response = self.accept(self.client.get(&url)).send().await?;
 
         if !response.status().is_success() {
-            return Err(CliError::Custom(format!(
-                "Request failed with status: {}",
-                response.status()
-            )));
+            return Err(CliError::Server {
+                status: response.status().as_u16(),
+                message: format!("Request failed with status: {}", response.status()),
+            });
         }
 
-        let bytes = response.bytes().await?;
-        let json = if bytes.is_empty() {
-            Value::Null
-        } else {
-            serde_json::from_slice(&bytes)?
-        };
-        Ok(json)
+        let json = Self::decode(response).await?;
+        Ok(self.record(json))
+    }
+
+    /// Make a GET request whose response body is streamed
+    ///
+    /// Unlike the other requests, only connecting is bounded by the
+    /// timeout, so the body may keep coming for as long as the server
+    /// sends it. Dropping the response closes the connection.
+    ///
+    /// # Arguments
+    /// * `endpoint` - API endpoint (e.g., "/api/v1/workloads/nginx/logs")
+    /// * `query` - Query parameters
+    pub async fn get_stream(&self, endpoint: &str, query: &[(&str, String)]) -> Result<Response> {
+        let client = Client::builder()
+            .connect_timeout(self.timeout)
+            .build()
+            .map_err(CliError::Http)?;
+        let url = format!("{}{}", self.base_url, endpoint);
+        let response = client.get(&url).query(query).send().await?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let error_text = response
+                .text()
+                .await
+                .unwrap_or_else(|_| "Unknown error".to_string());
+            return Err(CliError::Server {
+                status: status.as_u16(),
+                message: format!("Request failed with status: {} - {}", status, error_text),
+            });
+        }
+
+        Ok(response)
     }
 
     /// Make a POST request to the specified endpoint
@@ -64,17 +166,20 @@ impl SettingsClient {
     /// * `body` - Request body as JSON
     pub async fn post(&self, endpoint: &str, body: &Value) -> Result<Value> {
         let url = format!("{}{}", self.base_url, endpoint);
-        let response = self.client.post(&url).json(body).send().await?;
+        let response = self
+            .accept(self.client.post(&url).json(body))
+            .send()
+            .await?;
 
         if !response.status().is_success() {
-            return Err(CliError::Custom(format!(
-                "Request failed with status: {}",
-                response.status()
-            )));
+            return Err(CliError::Server {
+                status: response.status().as_u16(),
+                message: format!("Request failed with status: {}", response.status()),
+            });
         }
 
-        let json: Value = response.json().await?;
-        Ok(json)
+        let json = Self::decode(response).await?;
+        Ok(self.record(json))
     }
 
     /// Make a PUT request to the specified endpoint
@@ -84,17 +189,17 @@ impl SettingsClient {
     /// * `body` - Request body as JSON
     pub async fn put(&self, endpoint: &str, body: &Value) -> Result<Value> {
         let url = format!("{}{}", self.base_url, endpoint);
-        let response = self.client.put(&url).json(body).send().await?;
+        let response = self.accept(self.client.put(&url).json(body)).send().await?;
 
         if !response.status().is_success() {
-            return Err(CliError::Custom(format!(
-                "Request failed with status: {}",
-                response.status()
-            )));
+            return Err(CliError::Server {
+                status: response.status().as_u16(),
+                message: format!("Request failed with status: {}", response.status()),
+            });
         }
 
-        let json: Value = response.json().await?;
-        Ok(json)
+        let json = Self::decode(response).await?;
+        Ok(self.record(json))
     }
 
     /// Make a DELETE request to the specified endpoint
@@ -103,17 +208,17 @@ impl SettingsClient {
     /// * `endpoint` - API endpoint
     pub async fn delete(&self, endpoint: &str) -> Result<Value> {
         let url = format!("{}{}", self.base_url, endpoint);
-        let response = self.client.delete(&url).send().await?;
+        let response = self.accept(self.client.delete(&url)).send().await?;
 
         if !response.status().is_success() {
-            return Err(CliError::Custom(format!(
-                "Request failed with status: {}",
-                response.status()
-            )));
+            return Err(CliError::Server {
+                status: response.status().as_u16(),
+                message: format!("Request failed with status: {}", response.status()),
+            });
         }
 
-        let json: Value = response.json().await?;
-        Ok(json)
+        let json = Self::decode(response).await?;
+        Ok(self.record(json))
     }
 
     /// Check if the SettingsService is reachable
@@ -151,14 +256,14 @@ impl SettingsClient {
                 .text()
                 .await
                 .unwrap_or_else(|_| "Unknown error".to_string());
-            return Err(CliError::Custom(format!(
-                "Request failed with status: {} - {}",
-                status, error_text
-            )));
+            return Err(CliError::Server {
+                status: status.as_u16(),
+                message: format!("Request failed with status: {} - {}", status, error_text),
+            });
         }
 
         let json: Value = response.json().await?;
-        Ok(json)
+        Ok(self.record(json))
     }
 
     /// Withdraw YAML artifact (DELETE with text/plain content)
@@ -182,17 +287,22 @@ impl SettingsClient {
                 .text()
                 .await
                 .unwrap_or_else(|_| "Unknown error".to_string());
-            return Err(CliError::Custom(format!(
-                "Request failed with status: {} - {}",
-                status, error_text
-            )));
+            return Err(CliError::Server {
+                status: status.as_u16(),
+                message: format!("Request failed with status: {} - {}", status, error_text),
+            });
         }
 
         let json: Value = response.json().await?;
-        Ok(json)
+        Ok(self.record(json))
     }
 }
 
+/// Decode a CBOR document into the JSON value it represents
+pub fn decode_cbor(bytes: &[u8]) -> Result<Value> {
+    ciborium::from_reader(bytes).map_err(|e| CliError::Custom(format!("invalid CBOR: {}", e)))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -256,6 +366,27 @@ mod tests {
         assert_eq!(result.unwrap(), Value::Null);
     }
 
+    #[tokio::test]
+    async fn test_get_cbor_round_trip() {
+        let document = json!({"nodes": [{"name": "HPC", "cpu": 42.5}], "total": 1});
+        let mut body = Vec::new();
+        ciborium::into_writer(&document, &mut body).unwrap();
+
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/api/v1/nodes"))
+            .and(header("accept", CBOR))
+            .respond_with(ResponseTemplate::new(200).set_body_raw(body, CBOR))
+            .mount(&server)
+            .await;
+        let client = SettingsClient::new(&server.uri(), 5)
+            .unwrap()
+            .with_cbor(true);
+
+        assert_eq!(client.get("/api/v1/nodes").await.unwrap(), document);
+        assert!(decode_cbor(b"\xff").is_err());
+    }
+
     #[tokio::test]
     async fn test_get_not_found() {
         let server = MockServer::start().await;
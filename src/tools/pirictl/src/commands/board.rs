@@ -52,7 +52,7 @@ async fn get_boards(client: &SettingsClient) -> Result<()> {
             // Look for "boards" array in the response
             if let Some(boards_array) = boards.get("boards").and_then(|b| b.as_array()) {
                 if boards_array.is_empty() {
-                    println!("No boards found.");
+                    outln!("No boards found.");
                 } else {
                     // Print each board
                     for board in boards_array.iter() {
@@ -71,7 +71,7 @@ async fn get_boards(client: &SettingsClient) -> Result<()> {
                             .map(|arr| arr.len())
                             .unwrap_or(0);
 
-                        println!("{:<24} {:<10} {:<10}", id, node_count, soc_count);
+                        outln!("{:<24} {:<10} {:<10}", id, node_count, soc_count);
                     }
                 }
             } else if let Some(id) = boards.get("board_id") {
@@ -86,17 +86,17 @@ async fn get_boards(client: &SettingsClient) -> Result<()> {
                     .and_then(|s| s.as_array())
                     .map(|arr| arr.len())
                     .unwrap_or(0);
-                println!(
+                outln!(
                     "{:<24} {:<10} {:<10}",
                     id.as_str().unwrap_or("Unknown"),
                     node_count,
                     soc_count
                 );
             } else {
-                println!("No boards found.");
+                outln!("No boards found.");
             }
 
-            println!();
+            outln!();
             print_success("Boards list retrieved successfully");
         }
         Err(e) => {
@@ -120,20 +120,20 @@ async fn describe_board(client: &SettingsClient, board_id: &str) -> Result<()> {
                 .get("board_id")
                 .and_then(|id| id.as_str())
                 .unwrap_or(board_id);
-            println!("\n{:<24}{}", format!("{}:", "Name".bold()), board_name);
+            outln!("\n{:<24}{}", format!("{}:", "Name".bold()), board_name);
 
             // Aggregated Resources
-            println!("{}", "Aggregated Resources:".bold());
+            outln!("{}", "Aggregated Resources:".bold());
 
             if let (Some(cpu_count), Some(cpu_usage)) = (
                 board.get("total_cpu_count").and_then(|c| c.as_u64()),
                 board.get("total_cpu_usage").and_then(|u| u.as_f64()),
             ) {
-                println!("  {:<22}{} ({:.2}% used)", "cpu:", cpu_count, cpu_usage);
+                outln!("  {:<22}{} ({:.2}% used)", "cpu:", cpu_count, cpu_usage);
             }
 
             if let Some(gpu_count) = board.get("total_gpu_count").and_then(|g| g.as_u64()) {
-                println!("  {:<22}{}", "gpu:", gpu_count);
+                outln!("  {:<22}{}", "gpu:", gpu_count);
             }
 
             if let (Some(_total_mem), Some(used_mem), Some(mem_usage)) = (
@@ -141,7 +141,7 @@ async fn describe_board(client: &SettingsClient, board_id: &str) -> Result<()> {
                 board.get("total_used_memory").and_then(|m| m.as_u64()),
                 board.get("total_mem_usage").and_then(|u| u.as_f64()),
             ) {
-                println!(
+                outln!(
                     "  {:<22}{} ({:.2}% used)",
                     "memory:",
                     format_memory(used_mem),
@@ -150,27 +150,27 @@ async fn describe_board(client: &SettingsClient, board_id: &str) -> Result<()> {
             }
 
             // Network I/O
-            println!("{}", "Network I/O:".bold());
+            outln!("{}", "Network I/O:".bold());
             if let Some(rx_bytes) = board.get("total_rx_bytes").and_then(|r| r.as_u64()) {
-                println!("  {:<22}{}", "RX:", format_bytes(rx_bytes));
+                outln!("  {:<22}{}", "RX:", format_bytes(rx_bytes));
             }
             if let Some(tx_bytes) = board.get("total_tx_bytes").and_then(|t| t.as_u64()) {
-                println!("  {:<22}{}", "TX:", format_bytes(tx_bytes));
+                outln!("  {:<22}{}", "TX:", format_bytes(tx_bytes));
             }
 
             // Disk I/O
-            println!("{}", "Disk I/O:".bold());
+            outln!("{}", "Disk I/O:".bold());
             if let Some(read_bytes) = board.get("total_read_bytes").and_then(|r| r.as_u64()) {
-                println!("  {:<22}{}", "Read:", format_bytes(read_bytes));
+                outln!("  {:<22}{}", "Read:", format_bytes(read_bytes));
             }
             if let Some(write_bytes) = board.get("total_write_bytes").and_then(|w| w.as_u64()) {
-                println!("  {:<22}{}", "Write:", format_bytes(write_bytes));
+                outln!("  {:<22}{}", "Write:", format_bytes(write_bytes));
             }
 
             // SoCs
             if let Some(socs) = board.get("socs").and_then(|s| s.as_array()) {
                 let soc_count = socs.len();
-                println!("{} ({})", "SoCs:".bold(), soc_count);
+                outln!("{} ({})", "SoCs:".bold(), soc_count);
                 for soc in socs.iter() {
                     let soc_id = soc
                         .get("soc_id")
@@ -181,7 +181,7 @@ async fn describe_board(client: &SettingsClient, board_id: &str) -> Result<()> {
                         .and_then(|n| n.as_array())
                         .map(|n| n.len())
                         .unwrap_or(0);
-                    println!(
+                    outln!(
                         "  {:<22}({} node{})",
                         soc_id,
                         node_count,
@@ -193,14 +193,14 @@ async fn describe_board(client: &SettingsClient, board_id: &str) -> Result<()> {
             // Nodes
             if let Some(nodes) = board.get("nodes").and_then(|n| n.as_array()) {
                 let node_count = nodes.len();
-                println!("{} ({})", "Nodes:".bold(), node_count);
+                outln!("{} ({})", "Nodes:".bold(), node_count);
                 for node in nodes.iter() {
                     let node_name = node
                         .get("node_name")
                         .and_then(|n| n.as_str())
                         .unwrap_or("Unknown");
                     let node_ip = node.get("ip").and_then(|ip| ip.as_str()).unwrap_or("N/A");
-                    println!("  {:<22}{}", node_name, node_ip);
+                    outln!("  {:<22}{}", node_name, node_ip);
                 }
             }
 
@@ -215,7 +215,7 @@ async fn describe_board(client: &SettingsClient, board_id: &str) -> Result<()> {
                         .unwrap()
                         .as_secs();
                     let elapsed = now.saturating_sub(secs);
-                    println!(
+                    outln!(
                         "{:<24}{}",
                         format!("{}:", "Last Updated".bold()),
                         format_duration_ago(elapsed)
@@ -41,13 +41,13 @@ async fn get_containers(client: &SettingsClient) -> Result<()> {
             } else if let Some(array) = containers.get("containers").and_then(|c| c.as_array()) {
                 array
             } else {
-                println!("No containers found.");
+                outln!("No containers found.");
                 print_success("Containers list retrieved successfully");
                 return Ok(());
             };
 
             if containers_array.is_empty() {
-                println!("No containers found.");
+                outln!("No containers found.");
             } else {
                 print_table_header(
                     "Containers",
@@ -81,11 +81,11 @@ async fn get_containers(client: &SettingsClient) -> Result<()> {
                         .and_then(|t| calculate_age(t).ok())
                         .unwrap_or_else(|| "N/A".to_string());
 
-                    println!("{:<32} {:<12} {:<66} {:<8}", name, status, id, age);
+                    outln!("{:<32} {:<12} {:<66} {:<8}", name, status, id, age);
                 }
             }
 
-            println!();
+            outln!();
             print_success("Containers list retrieved successfully");
         }
         Err(e) => {
@@ -115,7 +115,7 @@ async fn describe_container(client: &SettingsClient, container_id: &str) -> Resu
                 .and_then(|n| n.as_str())
                 .unwrap_or(container_id);
 
-            println!("\n{:<24}{}", format!("{}:", "Name".bold()), container_name);
+            outln!("\n{:<24}{}", format!("{}:", "Name".bold()), container_name);
 
             // Node (from config.Hostname)
             if let Some(hostname) = container
@@ -123,7 +123,7 @@ async fn describe_container(client: &SettingsClient, container_id: &str) -> Resu
                 .and_then(|c| c.get("Hostname"))
                 .and_then(|h| h.as_str())
             {
-                println!("{:<24}{}", format!("{}:", "Node".bold()), hostname);
+                outln!("{:<24}{}", format!("{}:", "Node".bold()), hostname);
             }
 
             // Status
@@ -140,23 +140,23 @@ async fn describe_container(client: &SettingsClient, container_id: &str) -> Resu
                 .map(|r| r == "true")
                 .unwrap_or(false);
 
-            println!(
+            outln!(
                 "{:<24}{}",
                 format!("{}:", "Status".bold()),
                 if is_running { "Running" } else { status }
             );
 
             // Container section
-            println!("{}", "Container:".bold());
+            outln!("{}", "Container:".bold());
 
             // ID
             if let Some(id) = container.get("id").and_then(|i| i.as_str()) {
-                println!("  {:<22}{}", "ID:", id);
+                outln!("  {:<22}{}", "ID:", id);
             }
 
             // Image
             if let Some(image) = container.get("image").and_then(|i| i.as_str()) {
-                println!("  {:<22}{}", "Image:", image);
+                outln!("  {:<22}{}", "Image:", image);
             }
 
             // State information
@@ -165,25 +165,25 @@ async fn describe_container(client: &SettingsClient, container_id: &str) -> Resu
                     .get("Status")
                     .and_then(|s| s.as_str())
                     .unwrap_or("Unknown");
-                println!("  {:<22}{}", "State:", capitalize(state_status));
+                outln!("  {:<22}{}", "State:", capitalize(state_status));
 
                 if is_running {
                     // Running container
                     if let Some(started_at) = state.get("StartedAt").and_then(|s| s.as_str()) {
                         if let Ok(formatted) = format_timestamp(started_at) {
-                            println!("  {:<22}{}", "Started:", formatted);
+                            outln!("  {:<22}{}", "Started:", formatted);
                         }
                         // Calculate uptime
                         if let Ok(uptime) = calculate_uptime(started_at) {
-                            println!("  {:<22}{}", "Uptime:", uptime);
+                            outln!("  {:<22}{}", "Uptime:", uptime);
                         }
                     }
 
                     if let Some(pid) = state.get("Pid").and_then(|p| p.as_str()) {
-                        println!("  {:<22}{}", "PID:", pid);
+                        outln!("  {:<22}{}", "PID:", pid);
                     }
 
-                    println!(
+                    outln!(
                         "  {:<22}{}",
                         "Ready:",
                         if is_running { "True" } else { "False" }
@@ -199,18 +199,18 @@ async fn describe_container(client: &SettingsClient, container_id: &str) -> Resu
                     } else {
                         "Error"
                     };
-                    println!("  {:<22}{}", "Reason:", reason);
-                    println!("  {:<22}{}", "Exit Code:", exit_code);
+                    outln!("  {:<22}{}", "Reason:", reason);
+                    outln!("  {:<22}{}", "Exit Code:", exit_code);
 
                     if let Some(started_at) = state.get("StartedAt").and_then(|s| s.as_str()) {
                         if let Ok(formatted) = format_timestamp(started_at) {
-                            println!("  {:<22}{}", "Started:", formatted);
+                            outln!("  {:<22}{}", "Started:", formatted);
                         }
                     }
 
                     if let Some(finished_at) = state.get("FinishedAt").and_then(|f| f.as_str()) {
                         if let Ok(formatted) = format_timestamp(finished_at) {
-                            println!("  {:<22}{}", "Finished:", formatted);
+                            outln!("  {:<22}{}", "Finished:", formatted);
                         }
 
                         // Calculate runtime
@@ -219,7 +219,7 @@ async fn describe_container(client: &SettingsClient, container_id: &str) -> Resu
                             state.get("FinishedAt").and_then(|f| f.as_str()),
                         ) {
                             if let Ok(runtime) = calculate_runtime(started, finished) {
-                                println!("  {:<22}{}", "Runtime:", runtime);
+                                outln!("  {:<22}{}", "Runtime:", runtime);
                             }
                         }
                     }
@@ -228,19 +228,19 @@ async fn describe_container(client: &SettingsClient, container_id: &str) -> Resu
                         .get("OOMKilled")
                         .and_then(|o| o.as_str())
                         .unwrap_or("false");
-                    println!("  {:<22}{}", "OOMKilled:", oom_killed);
-                    println!("  {:<22}False", "Ready:");
+                    outln!("  {:<22}{}", "OOMKilled:", oom_killed);
+                    outln!("  {:<22}False", "Ready:");
                 }
             }
 
             // Resource Usage
-            println!("{}", "Resource Usage:".bold());
+            outln!("{}", "Resource Usage:".bold());
 
             if is_running {
                 if let Some(stats) = container.get("stats") {
                     // Check if stats are available
                     if stats.get("Status").and_then(|s| s.as_str()) == Some("StatsUnavailable") {
-                        println!("  {:<22}N/A (stats unavailable)", "");
+                        outln!("  {:<22}N/A (stats unavailable)", "");
                     } else {
                         // CPU Usage
                         if let (Some(total_cpu), Some(kernel_cpu), Some(user_cpu)) = (
@@ -260,9 +260,12 @@ async fn describe_container(client: &SettingsClient, container_id: &str) -> Resu
                             let total_secs = total_cpu / 1_000_000_000.0;
                             let kernel_secs = kernel_cpu / 1_000_000_000.0;
                             let user_secs = user_cpu / 1_000_000_000.0;
-                            println!(
+                            outln!(
                                 "  {:<22}{:.2}s ({:.2}s kernel, {:.2}s user)",
-                                "CPU:", total_secs, kernel_secs, user_secs
+                                "CPU:",
+                                total_secs,
+                                kernel_secs,
+                                user_secs
                             );
                         }
 
@@ -282,7 +285,7 @@ async fn describe_container(client: &SettingsClient, container_id: &str) -> Resu
                             } else {
                                 0.0
                             };
-                            println!(
+                            outln!(
                                 "  {:<22}{} / {} ({:.2}%)",
                                 "Memory:",
                                 format_bytes(mem_usage),
@@ -295,7 +298,7 @@ async fn describe_container(client: &SettingsClient, container_id: &str) -> Resu
                         if let Some(networks) = stats.get("Networks").and_then(|n| n.as_str()) {
                             let rx_bytes = extract_network_value(networks, "rx_bytes");
                             let tx_bytes = extract_network_value(networks, "tx_bytes");
-                            println!(
+                            outln!(
                                 "  {:<22}RX: {}, TX: {}",
                                 "Network:",
                                 format_bytes(rx_bytes),
@@ -304,10 +307,10 @@ async fn describe_container(client: &SettingsClient, container_id: &str) -> Resu
                         }
                     }
                 } else {
-                    println!("  {:<22}N/A (stats unavailable)", "");
+                    outln!("  {:<22}N/A (stats unavailable)", "");
                 }
             } else {
-                println!("  {:<22}N/A (container exited)", "");
+                outln!("  {:<22}N/A (container exited)", "");
             }
 
             print_success("Container description retrieved successfully");
@@ -330,7 +333,7 @@ async fn raw_containers(client: &SettingsClient) -> Result<()> {
 
     match client.get("/api/v1/containers").await {
         Ok(containers) => {
-            println!("{}", serde_json::to_string_pretty(&containers)?);
+            outln!("{}", serde_json::to_string_pretty(&containers)?);
             print_success("Raw containers data retrieved successfully");
         }
         Err(e) => {
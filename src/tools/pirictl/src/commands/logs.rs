@@ -0,0 +1,169 @@
+/*
+* SPDX-FileCopyrightText: Copyright 2024 LG Electronics Inc.
+* SPDX-License-Identifier: Apache-2.0
+*/
+//! Logs of workloads, streamed through the API server
+//!
+//! The API server relays the logs from the node running the workload as
+//! newline-delimited JSON, one `{"timestamp", "line"}` object per line and
+//! a last `{"error"}` object if the node fails.
+
+use crate::error::CliError;
+use crate::{Result, SettingsClient};
+use serde_json::Value;
+
+/// Print the logs of `workload`
+///
+/// # Arguments
+/// * `client` - API server client
+/// * `workload` - Name of the model the workload runs
+/// * `follow` - Keep printing new lines until interrupted
+/// * `tail` - Only print the last lines, all of them when `None`
+pub async fn handle(
+    client: &SettingsClient,
+    workload: &str,
+    follow: bool,
+    tail: Option<u32>,
+) -> Result<()> {
+    let mut query = vec![("follow", follow.to_string())];
+    if let Some(tail) = tail {
+        query.push(("tail", tail.to_string()));
+    }
+    let endpoint = format!("/api/v1/workloads/{}/logs", workload);
+    let mut response = client.get_stream(&endpoint, &query).await?;
+
+    let mut lines = LineBuffer::default();
+    while let Some(chunk) = response.chunk().await? {
+        for line in lines.push(&chunk) {
+            print_entry(&line)?;
+        }
+    }
+    if let Some(line) = lines.finish() {
+        print_entry(&line)?;
+    }
+    Ok(())
+}
+
+/// Print one entry of the stream, failing on an error entry
+fn print_entry(entry: &str) -> Result<()> {
+    if entry.trim().is_empty() {
+        return Ok(());
+    }
+    let entry: Value = serde_json::from_str(entry)?;
+    if let Some(error) = entry.get("error").and_then(Value::as_str) {
+        return Err(CliError::Custom(format!("log stream ended: {}", error)));
+    }
+    outln!(
+        "{}",
+        entry.get("line").and_then(Value::as_str).unwrap_or("")
+    );
+    Ok(())
+}
+
+/// Splits streamed chunks into complete lines
+#[derive(Default)]
+struct LineBuffer {
+    pending: Vec<u8>,
+}
+
+impl LineBuffer {
+    /// Lines completed by `chunk`, without their newline
+    fn push(&mut self, chunk: &[u8]) -> Vec<String> {
+        self.pending.extend_from_slice(chunk);
+        let mut lines = Vec::new();
+        while let Some(end) = self.pending.iter().position(|b| *b == b'\n') {
+            let line: Vec<u8> = self.pending.drain(..=end).collect();
+            lines.push(String::from_utf8_lossy(&line[..end]).into_owned());
+        }
+        lines
+    }
+
+    /// Last line, if the stream did not end with a newline
+    fn finish(self) -> Option<String> {
+        (!self.pending.is_empty()).then(|| String::from_utf8_lossy(&self.pending).into_owned())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wiremock::matchers::{method, path, query_param};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    #[test]
+    fn test_line_buffer_joins_split_lines() {
+        let mut lines = LineBuffer::default();
+        assert!(lines.push(b"{\"line\":\"he").is_empty());
+        assert_eq!(
+            lines.push(b"llo\"}\n{\"line\":\"a\"}\n{\"li"),
+            vec!["{\"line\":\"hello\"}", "{\"line\":\"a\"}"]
+        );
+        assert_eq!(lines.finish().as_deref(), Some("{\"li"));
+        assert_eq!(LineBuffer::default().finish(), None);
+    }
+
+    #[test]
+    fn test_print_entry() {
+        assert!(print_entry("{\"timestamp\":1,\"line\":\"started\"}").is_ok());
+        assert!(print_entry("").is_ok());
+        let err = print_entry("{\"error\":\"podman is down\"}").unwrap_err();
+        assert_eq!(err.to_string(), "Error: log stream ended: podman is down");
+        assert!(print_entry("not json").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_logs_streams_lines() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/api/v1/workloads/nginx/logs"))
+            .and(query_param("follow", "true"))
+            .and(query_param("tail", "10"))
+            .respond_with(ResponseTemplate::new(200).set_body_raw(
+                "{\"timestamp\":1,\"line\":\"a\"}\n{\"timestamp\":2,\"line\":\"b\"}\n",
+                "application/x-ndjson",
+            ))
+            .mount(&server)
+            .await;
+        let client = SettingsClient::new(&server.uri(), 5).unwrap();
+
+        assert!(handle(&client, "nginx", true, Some(10)).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_logs_node_error_fails() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/api/v1/workloads/nginx/logs"))
+            .respond_with(ResponseTemplate::new(200).set_body_raw(
+                "{\"timestamp\":1,\"line\":\"a\"}\n{\"error\":\"podman is down\"}\n",
+                "application/x-ndjson",
+            ))
+            .mount(&server)
+            .await;
+        let client = SettingsClient::new(&server.uri(), 5).unwrap();
+
+        let err = handle(&client, "nginx", false, None).await.unwrap_err();
+        assert!(err.to_string().contains("podman is down"));
+    }
+
+    #[tokio::test]
+    async fn test_logs_unknown_workload() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/api/v1/workloads/ghost/logs"))
+            .respond_with(
+                ResponseTemplate::new(404).set_body_json("workload ghost is not in any package"),
+            )
+            .mount(&server)
+            .await;
+        let client = SettingsClient::new(&server.uri(), 5).unwrap();
+
+        match handle(&client, "ghost", false, None).await.unwrap_err() {
+            CliError::Server { status, message } => {
+                assert_eq!(status, 404);
+                assert!(message.contains("not in any package"));
+            }
+            other => panic!("unexpected error: {}", other),
+        }
+    }
+}
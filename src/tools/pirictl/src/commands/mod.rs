@@ -7,6 +7,7 @@
 pub mod board;
 pub mod container;
 pub mod format;
+pub mod logs;
 pub mod metrics;
 pub mod node;
 pub mod soc;
@@ -20,23 +21,23 @@ use serde_json::Value;
 /// Helper function to pretty print JSON output
 pub fn print_json(value: &Value) -> Result<()> {
     let pretty = serde_json::to_string_pretty(value)?;
-    println!("{}", pretty);
+    outln!("{}", pretty);
     Ok(())
 }
 
 /// Helper function to print success messages
 pub fn print_success(message: &str) {
-    println!("{} {}", "✓".green().bold(), message);
+    outln!("{} {}", "✓".green().bold(), message);
 }
 
 /// Helper function to print error messages
 pub fn print_error(message: &str) {
-    println!("{} {}", "✗".red().bold(), message);
+    outln!("{} {}", "✗".red().bold(), message);
 }
 
 /// Helper function to print info messages
 pub fn print_info(message: &str) {
-    println!("{} {}", "ℹ".blue().bold(), message);
+    outln!("{} {}", "ℹ".blue().bold(), message);
 }
 
 /// Print table header with title and columns
@@ -46,11 +47,11 @@ pub fn print_info(message: &str) {
 /// * `columns` - Array of (column_name, width) tuples
 pub fn print_table_header(_title: &str, columns: &[(&str, usize)]) {
     // Print column headers only (kubectl style - simple and clean)
-    println!();
+    outln!();
     for (name, width) in columns {
-        print!("{:<width$} ", name, width = width);
+        out!("{:<width$} ", name, width = width);
     }
-    println!();
+    outln!();
 }
 
 #[cfg(test)]
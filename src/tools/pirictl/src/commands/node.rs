@@ -4,11 +4,21 @@
 */
 //! Node command implementation
 
-use crate::commands::format::{format_bytes, format_memory};
+use crate::commands::format::{format_bytes, format_duration_ago, format_memory};
 use crate::commands::{print_error, print_info, print_json, print_success, print_table_header};
+use crate::error::CliError;
 use crate::{Result, SettingsClient};
-use clap::Subcommand;
+use clap::{Subcommand, ValueEnum};
 use colored::Colorize;
+use serde::Serialize;
+use serde_json::Value;
+
+/// Heartbeat age after which a node is reported as stale
+const HEARTBEAT_STALE_SECS: u64 = 30;
+/// Heartbeat age after which a node is reported as unreachable
+const HEARTBEAT_LOST_SECS: u64 = 120;
+/// Maximum edit distance for "did you mean" suggestions
+const SUGGESTION_DISTANCE: usize = 3;
 
 #[derive(Subcommand)]
 pub enum NodeAction {
@@ -26,6 +36,129 @@ pub enum NodeAction {
     },
 }
 
+/// Node status commands combining API server and SettingsService data
+#[derive(Subcommand)]
+pub enum NodeCommand {
+    /// List registered nodes with their health
+    List {
+        /// Only list nodes with this role
+        #[arg(long, value_enum)]
+        role: Option<NodeRole>,
+    },
+    /// Show registration, heartbeat, health and workloads of a node
+    Status {
+        /// Node name (hostname used at registration)
+        name: String,
+    },
+}
+
+/// Role a node registered with
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum NodeRole {
+    Master,
+    Nodeagent,
+    Bluechi,
+}
+
+impl NodeRole {
+    /// `node_role` value of the API server registration record
+    fn code(self) -> i64 {
+        match self {
+            NodeRole::Master => 1,
+            NodeRole::Nodeagent => 2,
+            NodeRole::Bluechi => 3,
+        }
+    }
+}
+
+/// Health classification of a registered node
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum NodeHealth {
+    /// Ready and heartbeating
+    Healthy,
+    /// Heartbeating but not in the Ready state
+    NotReady,
+    /// Heartbeat older than `HEARTBEAT_STALE_SECS`
+    Stale,
+    /// Heartbeat older than `HEARTBEAT_LOST_SECS`, or never received
+    Unreachable,
+}
+
+impl NodeHealth {
+    /// Classify a node from its registration status and heartbeat age
+    pub fn classify(status: i64, heartbeat_age: Option<u64>) -> Self {
+        match heartbeat_age {
+            None => NodeHealth::Unreachable,
+            Some(age) if age >= HEARTBEAT_LOST_SECS => NodeHealth::Unreachable,
+            Some(age) if age >= HEARTBEAT_STALE_SECS => NodeHealth::Stale,
+            // NODE_STATUS_READY
+            Some(_) if status == 3 => NodeHealth::Healthy,
+            Some(_) => NodeHealth::NotReady,
+        }
+    }
+
+    fn label(&self) -> String {
+        match self {
+            NodeHealth::Healthy => "Healthy".green().to_string(),
+            NodeHealth::NotReady => "NotReady".yellow().to_string(),
+            NodeHealth::Stale => "Stale".yellow().to_string(),
+            NodeHealth::Unreachable => "Unreachable".red().to_string(),
+        }
+    }
+}
+
+/// One row of `node list`, also reported by `--output json`
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct NodeSummary {
+    pub name: String,
+    pub ip_address: String,
+    pub role: String,
+    pub status: String,
+    pub health: NodeHealth,
+    /// Seconds since the last heartbeat, `None` if the node never sent one
+    pub heartbeat_age_secs: Option<u64>,
+}
+
+impl NodeSummary {
+    /// Summarize an API server registration record
+    fn from_node(node: &Value, now: u64) -> Self {
+        let status = node.get("status").and_then(|s| s.as_i64()).unwrap_or(0);
+        let age = heartbeat_age(node, now);
+        Self {
+            name: str_field(node, "hostname").to_string(),
+            ip_address: str_field(node, "ip_address").to_string(),
+            role: node_role_name(node_role(node)).to_string(),
+            status: node_status_name(status).to_string(),
+            health: NodeHealth::classify(status, age),
+            heartbeat_age_secs: age,
+        }
+    }
+}
+
+/// Summarize registration records, keeping only nodes with `role` if given
+fn summarize_nodes(nodes: &[Value], role: Option<NodeRole>, now: u64) -> Vec<NodeSummary> {
+    nodes
+        .iter()
+        .filter(|node| role.is_none_or(|role| node_role(node) == role.code()))
+        .map(|node| NodeSummary::from_node(node, now))
+        .collect()
+}
+
+/// Handle node status commands
+///
+/// Registration and heartbeat data come from the API server, workloads from
+/// the SettingsService.
+pub async fn handle_command(
+    api_client: &SettingsClient,
+    settings_client: &SettingsClient,
+    command: NodeCommand,
+) -> Result<()> {
+    match command {
+        NodeCommand::List { role } => list_node_status(api_client, role).await,
+        NodeCommand::Status { name } => node_status(api_client, settings_client, &name).await,
+    }
+}
+
 /// Handle node commands
 pub async fn handle(client: &SettingsClient, action: NodeAction) -> Result<()> {
     match action {
@@ -55,7 +188,7 @@ async fn get_nodes(client: &SettingsClient) -> Result<()> {
             // Look for "nodes" array in the response
             if let Some(nodes_array) = nodes.get("nodes").and_then(|n| n.as_array()) {
                 if nodes_array.is_empty() {
-                    println!("No nodes found.");
+                    outln!("No nodes found.");
                 } else {
                     // Print each node
                     for node in nodes_array.iter() {
@@ -70,7 +203,7 @@ async fn get_nodes(client: &SettingsClient) -> Result<()> {
                             .and_then(|a| a.as_str())
                             .unwrap_or("Unknown");
 
-                        println!("{:<24} {:<18} {:<22} {:<10}", name, ip, os, arch);
+                        outln!("{:<24} {:<18} {:<22} {:<10}", name, ip, os, arch);
                     }
                 }
             } else if let Some(name) = nodes.get("node_name") {
@@ -84,7 +217,7 @@ async fn get_nodes(client: &SettingsClient) -> Result<()> {
                     .get("arch")
                     .and_then(|a| a.as_str())
                     .unwrap_or("Unknown");
-                println!(
+                outln!(
                     "{:<24} {:<18} {:<22} {:<10}",
                     name.as_str().unwrap_or("Unknown"),
                     ip,
@@ -92,10 +225,10 @@ async fn get_nodes(client: &SettingsClient) -> Result<()> {
                     arch
                 );
             } else {
-                println!("No nodes found.");
+                outln!("No nodes found.");
             }
 
-            println!();
+            outln!();
             print_success("Nodes list retrieved successfully");
         }
         Err(e) => {
@@ -119,48 +252,48 @@ async fn describe_node(client: &SettingsClient, node_id: &str) -> Result<()> {
                 .get("node_name")
                 .and_then(|n| n.as_str())
                 .unwrap_or(node_id);
-            println!("\n{:<24}{}", format!("{}:", "Name".bold()), node_name);
+            outln!("\n{:<24}{}", format!("{}:", "Name".bold()), node_name);
 
             // System Info
-            println!("{}", "System Info:".bold());
+            outln!("{}", "System Info:".bold());
             if let Some(os) = node.get("os").and_then(|o| o.as_str()) {
-                println!("  {:<22}{}", "OS Image:", os);
+                outln!("  {:<22}{}", "OS Image:", os);
             }
             if let Some(arch) = node.get("arch").and_then(|a| a.as_str()) {
-                println!("  {:<22}{}", "Architecture:", arch);
+                outln!("  {:<22}{}", "Architecture:", arch);
             }
             // Container runtime is not provided by API, using default
-            println!("  {:<22}Podman", "Container Runtime:");
+            outln!("  {:<22}Podman", "Container Runtime:");
             if let Some(ip) = node.get("ip").and_then(|i| i.as_str()) {
-                println!("  {:<22}{}", "Internal IP:", ip);
+                outln!("  {:<22}{}", "Internal IP:", ip);
             }
 
             // Capacity
-            println!("{}", "Capacity:".bold());
+            outln!("{}", "Capacity:".bold());
             if let Some(cpu_count) = node.get("cpu_count").and_then(|c| c.as_u64()) {
-                println!("  {:<22}{}", "cpu:", cpu_count);
+                outln!("  {:<22}{}", "cpu:", cpu_count);
             }
             if let Some(gpu_count) = node.get("gpu_count").and_then(|g| g.as_u64()) {
-                println!("  {:<22}{}", "gpu:", gpu_count);
+                outln!("  {:<22}{}", "gpu:", gpu_count);
             }
             if let Some(total_memory) = node.get("total_memory").and_then(|m| m.as_u64()) {
-                println!("  {:<22}{}", "memory:", format_memory(total_memory));
+                outln!("  {:<22}{}", "memory:", format_memory(total_memory));
             }
 
             // Allocatable (Current Usage)
-            println!("{}", "Allocatable:".bold());
+            outln!("{}", "Allocatable:".bold());
             if let (Some(cpu_usage), Some(cpu_count)) = (
                 node.get("cpu_usage").and_then(|u| u.as_f64()),
                 node.get("cpu_count").and_then(|c| c.as_u64()),
             ) {
-                println!("  {:<22}{} ({:.2}% used)", "cpu:", cpu_count, cpu_usage);
+                outln!("  {:<22}{} ({:.2}% used)", "cpu:", cpu_count, cpu_usage);
             }
             if let (Some(_used_memory), Some(total_memory), Some(mem_usage)) = (
                 node.get("used_memory").and_then(|m| m.as_u64()),
                 node.get("total_memory").and_then(|m| m.as_u64()),
                 node.get("mem_usage").and_then(|u| u.as_f64()),
             ) {
-                println!(
+                outln!(
                     "  {:<22}{} ({:.2}% used)",
                     "memory:",
                     format_memory(total_memory),
@@ -169,21 +302,21 @@ async fn describe_node(client: &SettingsClient, node_id: &str) -> Result<()> {
             }
 
             // Network I/O
-            println!("{}", "Network I/O:".bold());
+            outln!("{}", "Network I/O:".bold());
             if let Some(rx_bytes) = node.get("rx_bytes").and_then(|r| r.as_u64()) {
-                println!("  {:<22}{}", "RX:", format_bytes(rx_bytes));
+                outln!("  {:<22}{}", "RX:", format_bytes(rx_bytes));
             }
             if let Some(tx_bytes) = node.get("tx_bytes").and_then(|t| t.as_u64()) {
-                println!("  {:<22}{}", "TX:", format_bytes(tx_bytes));
+                outln!("  {:<22}{}", "TX:", format_bytes(tx_bytes));
             }
 
             // Disk I/O
-            println!("{}", "Disk I/O:".bold());
+            outln!("{}", "Disk I/O:".bold());
             if let Some(read_bytes) = node.get("read_bytes").and_then(|r| r.as_u64()) {
-                println!("  {:<22}{}", "Read:", format_bytes(read_bytes));
+                outln!("  {:<22}{}", "Read:", format_bytes(read_bytes));
             }
             if let Some(write_bytes) = node.get("write_bytes").and_then(|w| w.as_u64()) {
-                println!("  {:<22}{}", "Write:", format_bytes(write_bytes));
+                outln!("  {:<22}{}", "Write:", format_bytes(write_bytes));
             }
 
             print_success("Node information retrieved successfully");
@@ -237,6 +370,257 @@ async fn get_node_raw(client: &SettingsClient, node_id: &str) -> Result<()> {
     Ok(())
 }
 
+/// List registered nodes with role, heartbeat age and health
+///
+/// With `--output json` the result holds the listed `NodeSummary` rows
+/// instead of the raw registration records.
+async fn list_node_status(api_client: &SettingsClient, role: Option<NodeRole>) -> Result<()> {
+    print_info("Fetching registered nodes...");
+
+    let nodes = match api_client.get("/api/v1/nodes").await {
+        Ok(nodes) => nodes,
+        Err(e) => {
+            print_error(&format!("Failed to fetch registered nodes: {}", e));
+            return Err(e);
+        }
+    };
+
+    let nodes = nodes.as_array().cloned().unwrap_or_default();
+    let summaries = summarize_nodes(&nodes, role, now_secs());
+    api_client.replace_responses(serde_json::to_value(&summaries)?);
+    if summaries.is_empty() {
+        match role {
+            Some(_) if !nodes.is_empty() => outln!("No nodes registered with that role."),
+            _ => outln!("No nodes registered."),
+        }
+        return Ok(());
+    }
+
+    print_table_header(
+        "Nodes",
+        &[
+            ("NAME", 24),
+            ("IP", 18),
+            ("ROLE", 10),
+            ("STATUS", 14),
+            ("HEALTH", 12),
+            ("LAST HEARTBEAT", 20),
+        ],
+    );
+    for node in &summaries {
+        outln!(
+            "{:<24} {:<18} {:<10} {:<14} {:<12} {:<20}",
+            node.name,
+            node.ip_address,
+            node.role,
+            node.status,
+            node.health.label(),
+            node.heartbeat_age_secs
+                .map(format_duration_ago)
+                .unwrap_or_else(|| "never".to_string())
+        );
+    }
+    outln!();
+    print_success("Node status list retrieved successfully");
+    Ok(())
+}
+
+/// Show registration info, heartbeat age, health and workloads of one node
+async fn node_status(
+    api_client: &SettingsClient,
+    settings_client: &SettingsClient,
+    name: &str,
+) -> Result<()> {
+    print_info(&format!("Fetching status of node {}...", name));
+
+    let node = match api_client.get(&format!("/api/v1/nodes/{}", name)).await {
+        Ok(node) => node,
+        Err(CliError::Server { status: 404, .. }) => {
+            return Err(unknown_node(api_client, name).await);
+        }
+        Err(e) => {
+            print_error(&format!("Failed to fetch node {}: {}", name, e));
+            return Err(e);
+        }
+    };
+
+    let status = node.get("status").and_then(|s| s.as_i64()).unwrap_or(0);
+    let age = heartbeat_age(&node, now_secs());
+
+    outln!(
+        "\n{:<24}{}",
+        format!("{}:", "Name".bold()),
+        str_field(&node, "hostname")
+    );
+    outln!("{}", "Registration:".bold());
+    outln!("  {:<22}{}", "Node ID:", str_field(&node, "node_id"));
+    outln!("  {:<22}{}", "IP Address:", str_field(&node, "ip_address"));
+    outln!("  {:<22}{}", "Role:", node_role_name(node_role(&node)));
+    outln!(
+        "  {:<22}{}",
+        "Type:",
+        node_type_name(node.get("node_type").and_then(|t| t.as_i64()).unwrap_or(0))
+    );
+    if let Some(created_at) = node.get("created_at").and_then(|c| c.as_i64()) {
+        let registered = now_secs().saturating_sub(created_at.max(0) as u64);
+        outln!("  {:<22}{}", "Registered:", format_duration_ago(registered));
+    }
+
+    outln!("{}", "Health:".bold());
+    outln!("  {:<22}{}", "Status:", node_status_name(status));
+    outln!(
+        "  {:<22}{}",
+        "Last Heartbeat:",
+        age.map(format_duration_ago)
+            .unwrap_or_else(|| "never".to_string())
+    );
+    outln!(
+        "  {:<22}{}",
+        "Health:",
+        NodeHealth::classify(status, age).label()
+    );
+
+    outln!("{}", "Workloads:".bold());
+    match settings_client
+        .get(&format!("/api/v1/nodes/{}/containers", name))
+        .await
+    {
+        Ok(containers) => {
+            let containers = containers.as_array().cloned().unwrap_or_default();
+            if containers.is_empty() {
+                outln!("  No workloads running.");
+            } else {
+                for container in &containers {
+                    let workload = container
+                        .get("names")
+                        .and_then(|n| n.as_array())
+                        .and_then(|arr| arr.first())
+                        .and_then(|n| n.as_str())
+                        .unwrap_or("Unknown");
+                    let state = container
+                        .get("state")
+                        .and_then(|s| s.get("Status"))
+                        .and_then(|s| s.as_str())
+                        .unwrap_or("Unknown");
+                    outln!("  {:<32}{}", workload, state);
+                }
+            }
+        }
+        // Registration info is still useful without workload data
+        Err(e) => outln!("  Workload information unavailable: {}", e),
+    }
+
+    print_success("Node status retrieved successfully");
+    Ok(())
+}
+
+/// Build the error for an unknown node, suggesting close registered names
+async fn unknown_node(api_client: &SettingsClient, name: &str) -> CliError {
+    let known: Vec<String> = match api_client.get("/api/v1/nodes").await {
+        Ok(nodes) => nodes
+            .as_array()
+            .map(|arr| {
+                arr.iter()
+                    .filter_map(|n| n.get("hostname").and_then(|h| h.as_str()))
+                    .map(str::to_string)
+                    .collect()
+            })
+            .unwrap_or_default(),
+        Err(_) => Vec::new(),
+    };
+
+    let suggestions = suggest_names(name, &known);
+    let message = if suggestions.is_empty() {
+        format!("Node '{}' not found", name)
+    } else {
+        format!(
+            "Node '{}' not found. Did you mean: {}?",
+            name,
+            suggestions.join(", ")
+        )
+    };
+    print_error(&message);
+    CliError::Server {
+        status: 404,
+        message,
+    }
+}
+
+/// Registered names within `SUGGESTION_DISTANCE` edits of `name`, closest first
+fn suggest_names(name: &str, known: &[String]) -> Vec<String> {
+    let mut candidates: Vec<(usize, &String)> = known
+        .iter()
+        .map(|k| (edit_distance(name, k), k))
+        .filter(|(d, k)| *d <= SUGGESTION_DISTANCE || k.contains(name) || name.contains(k.as_str()))
+        .collect();
+    candidates.sort();
+    candidates.into_iter().map(|(_, k)| k.clone()).collect()
+}
+
+/// Levenshtein distance between two strings
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut prev: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut curr = vec![i + 1; b.len() + 1];
+        for (j, cb) in b.iter().enumerate() {
+            let cost = if ca == *cb { 0 } else { 1 };
+            curr[j + 1] = (prev[j] + cost).min(prev[j + 1] + 1).min(curr[j] + 1);
+        }
+        prev = curr;
+    }
+    prev[b.len()]
+}
+
+fn now_secs() -> u64 {
+    chrono::Utc::now().timestamp().max(0) as u64
+}
+
+/// Seconds since the last heartbeat, `None` if the node never sent one
+fn heartbeat_age(node: &Value, now: u64) -> Option<u64> {
+    node.get("last_heartbeat")
+        .and_then(|h| h.as_i64())
+        .filter(|h| *h > 0)
+        .map(|h| now.saturating_sub(h as u64))
+}
+
+fn str_field<'a>(node: &'a Value, key: &str) -> &'a str {
+    node.get(key).and_then(|v| v.as_str()).unwrap_or("N/A")
+}
+
+fn node_status_name(status: i64) -> &'static str {
+    match status {
+        1 => "Pending",
+        2 => "Initializing",
+        3 => "Ready",
+        4 => "NotReady",
+        5 => "Maintenance",
+        6 => "Terminating",
+        _ => "Unknown",
+    }
+}
+
+fn node_role(node: &Value) -> i64 {
+    node.get("node_role").and_then(|r| r.as_i64()).unwrap_or(0)
+}
+
+fn node_role_name(role: i64) -> &'static str {
+    match role {
+        1 => "master",
+        2 => "nodeagent",
+        3 => "bluechi",
+        _ => "unknown",
+    }
+}
+
+fn node_type_name(node_type: i64) -> &'static str {
+    match node_type {
+        1 => "cloud",
+        2 => "vehicle",
+        _ => "unknown",
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -522,4 +906,275 @@ mod tests {
         .await
         .is_err());
     }
+
+    // ── node status / list ───────────────────────────────────────────────────
+
+    fn registered_node(name: &str, status: i64, heartbeat_age: i64) -> serde_json::Value {
+        let now = chrono::Utc::now().timestamp();
+        json!({
+            "node_id": name,
+            "hostname": name,
+            "ip_address": "10.0.0.2",
+            "node_type": 2,
+            "node_role": 2,
+            "status": status,
+            "resources": null,
+            "last_heartbeat": now - heartbeat_age,
+            "created_at": now - 3600,
+            "metadata": {}
+        })
+    }
+
+    #[test]
+    fn test_classify_health() {
+        assert_eq!(NodeHealth::classify(3, Some(2)), NodeHealth::Healthy);
+        assert_eq!(NodeHealth::classify(1, Some(2)), NodeHealth::NotReady);
+        assert_eq!(NodeHealth::classify(3, Some(45)), NodeHealth::Stale);
+        assert_eq!(NodeHealth::classify(3, Some(600)), NodeHealth::Unreachable);
+        assert_eq!(NodeHealth::classify(3, None), NodeHealth::Unreachable);
+    }
+
+    #[test]
+    fn test_suggest_names_orders_by_distance() {
+        let known = vec![
+            "worker-1".to_string(),
+            "worker-12".to_string(),
+            "master".to_string(),
+        ];
+        assert_eq!(
+            suggest_names("worker-2", &known),
+            vec!["worker-1".to_string(), "worker-12".to_string()]
+        );
+        assert!(suggest_names("gateway", &known).is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_node_status_success() {
+        let api = MockServer::start().await;
+        let settings = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/api/v1/nodes/worker-1"))
+            .respond_with(
+                ResponseTemplate::new(200).set_body_json(registered_node("worker-1", 3, 2)),
+            )
+            .mount(&api)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/api/v1/nodes/worker-1/containers"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!([
+                {"id": "abc", "names": ["helloworld"], "state": {"Status": "running"}}
+            ])))
+            .expect(1)
+            .mount(&settings)
+            .await;
+        let api_client = make_client(&api.uri()).await;
+        let settings_client = make_client(&settings.uri()).await;
+
+        let result = handle_command(
+            &api_client,
+            &settings_client,
+            NodeCommand::Status {
+                name: "worker-1".into(),
+            },
+        )
+        .await;
+        assert!(result.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_node_status_without_workload_data() {
+        let api = MockServer::start().await;
+        let settings = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/api/v1/nodes/worker-1"))
+            .respond_with(
+                ResponseTemplate::new(200).set_body_json(registered_node("worker-1", 3, 500)),
+            )
+            .mount(&api)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/api/v1/nodes/worker-1/containers"))
+            .respond_with(ResponseTemplate::new(500))
+            .mount(&settings)
+            .await;
+        let api_client = make_client(&api.uri()).await;
+        let settings_client = make_client(&settings.uri()).await;
+
+        let result = handle_command(
+            &api_client,
+            &settings_client,
+            NodeCommand::Status {
+                name: "worker-1".into(),
+            },
+        )
+        .await;
+        assert!(result.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_node_status_unknown_node_suggests_close_matches() {
+        let api = MockServer::start().await;
+        let settings = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/api/v1/nodes/workr-1"))
+            .respond_with(ResponseTemplate::new(404).set_body_json(json!("Node workr-1 not found")))
+            .mount(&api)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/api/v1/nodes"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!([
+                registered_node("worker-1", 3, 2),
+                registered_node("master", 3, 2)
+            ])))
+            .mount(&api)
+            .await;
+        let api_client = make_client(&api.uri()).await;
+        let settings_client = make_client(&settings.uri()).await;
+
+        let err = handle_command(
+            &api_client,
+            &settings_client,
+            NodeCommand::Status {
+                name: "workr-1".into(),
+            },
+        )
+        .await
+        .unwrap_err();
+        match &err {
+            CliError::Server { status, message } => {
+                assert_eq!(*status, 404);
+                assert!(message.contains("Did you mean: worker-1?"));
+            }
+            other => panic!("unexpected error: {}", other),
+        }
+        assert_ne!(crate::output::exit_code(&err), crate::output::EXIT_SUCCESS);
+    }
+
+    #[tokio::test]
+    async fn test_node_list() {
+        let api = MockServer::start().await;
+        let settings = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/api/v1/nodes"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!([
+                registered_node("worker-1", 3, 2),
+                registered_node("worker-2", 4, 60)
+            ])))
+            .mount(&api)
+            .await;
+        let api_client = make_client(&api.uri()).await;
+        let settings_client = make_client(&settings.uri()).await;
+
+        assert!(handle_command(
+            &api_client,
+            &settings_client,
+            NodeCommand::List { role: None }
+        )
+        .await
+        .is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_node_list_server_error() {
+        let api = MockServer::start().await;
+        let settings = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/api/v1/nodes"))
+            .respond_with(ResponseTemplate::new(500))
+            .mount(&api)
+            .await;
+        let api_client = make_client(&api.uri()).await;
+        let settings_client = make_client(&settings.uri()).await;
+
+        assert!(handle_command(
+            &api_client,
+            &settings_client,
+            NodeCommand::List { role: None }
+        )
+        .await
+        .is_err());
+    }
+
+    #[test]
+    fn test_summarize_nodes_by_role() {
+        let now = chrono::Utc::now().timestamp() as u64;
+        let mut master = registered_node("master-1", 3, 2);
+        master["node_role"] = json!(1);
+        let mut bluechi = registered_node("bluechi-1", 3, 2);
+        bluechi["node_role"] = json!(3);
+        bluechi["last_heartbeat"] = json!(0);
+        let nodes = vec![
+            master,
+            registered_node("worker-1", 3, 2),
+            bluechi,
+            registered_node("worker-2", 4, 60),
+        ];
+
+        let workers = summarize_nodes(&nodes, Some(NodeRole::Nodeagent), now);
+        assert_eq!(
+            serde_json::to_value(&workers).unwrap(),
+            json!([
+                {
+                    "name": "worker-1",
+                    "ip_address": "10.0.0.2",
+                    "role": "nodeagent",
+                    "status": "Ready",
+                    "health": "Healthy",
+                    "heartbeat_age_secs": 2
+                },
+                {
+                    "name": "worker-2",
+                    "ip_address": "10.0.0.2",
+                    "role": "nodeagent",
+                    "status": "NotReady",
+                    "health": "Stale",
+                    "heartbeat_age_secs": 60
+                }
+            ])
+        );
+
+        let bluechi = summarize_nodes(&nodes, Some(NodeRole::Bluechi), now);
+        assert_eq!(bluechi.len(), 1);
+        assert_eq!(bluechi[0].health, NodeHealth::Unreachable);
+        assert_eq!(bluechi[0].heartbeat_age_secs, None);
+        assert_eq!(summarize_nodes(&nodes, None, now).len(), 4);
+    }
+
+    #[tokio::test]
+    async fn test_node_list_reports_filtered_summaries() {
+        let api = MockServer::start().await;
+        let settings = MockServer::start().await;
+        let mut master = registered_node("master-1", 3, 2);
+        master["node_role"] = json!(1);
+        Mock::given(method("GET"))
+            .and(path("/api/v1/nodes"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_json(json!([master, registered_node("worker-1", 3, 2)])),
+            )
+            .mount(&api)
+            .await;
+        let api_client = make_client(&api.uri()).await;
+        let settings_client = make_client(&settings.uri()).await;
+
+        handle_command(
+            &api_client,
+            &settings_client,
+            NodeCommand::List {
+                role: Some(NodeRole::Master),
+            },
+        )
+        .await
+        .unwrap();
+
+        let responses = api_client.take_responses();
+        assert_eq!(responses.len(), 1);
+        let names: Vec<&str> = responses[0]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|n| n["name"].as_str().unwrap())
+            .collect();
+        assert_eq!(names, vec!["master-1"]);
+    }
 }
@@ -52,7 +52,7 @@ async fn get_socs(client: &SettingsClient) -> Result<()> {
             // Look for "socs" array in the response
             if let Some(socs_array) = socs.get("socs").and_then(|s| s.as_array()) {
                 if socs_array.is_empty() {
-                    println!("No SoCs found.");
+                    outln!("No SoCs found.");
                 } else {
                     // Print each SoC
                     for soc in socs_array.iter() {
@@ -66,7 +66,7 @@ async fn get_socs(client: &SettingsClient) -> Result<()> {
                             .map(|arr| arr.len())
                             .unwrap_or(0);
 
-                        println!("{:<24} {:<10}", id, node_count);
+                        outln!("{:<24} {:<10}", id, node_count);
                     }
                 }
             } else if let Some(id) = socs.get("soc_id") {
@@ -76,16 +76,16 @@ async fn get_socs(client: &SettingsClient) -> Result<()> {
                     .and_then(|n| n.as_array())
                     .map(|arr| arr.len())
                     .unwrap_or(0);
-                println!(
+                outln!(
                     "{:<24} {:<10}",
                     id.as_str().unwrap_or("Unknown"),
                     node_count
                 );
             } else {
-                println!("No SoCs found.");
+                outln!("No SoCs found.");
             }
 
-            println!();
+            outln!();
             print_success("SoCs list retrieved successfully");
         }
         Err(e) => {
@@ -109,34 +109,34 @@ async fn describe_soc(client: &SettingsClient, soc_id: &str) -> Result<()> {
                 .get("soc_id")
                 .and_then(|id| id.as_str())
                 .unwrap_or(soc_id);
-            println!("\n{:<24}{}", format!("{}:", "Name".bold()), soc_name);
+            outln!("\n{:<24}{}", format!("{}:", "Name".bold()), soc_name);
 
             // Status (default to Active if not provided)
             let status = soc
                 .get("status")
                 .and_then(|s| s.as_str())
                 .unwrap_or("Active");
-            println!("{:<24}{}", format!("{}:", "Status".bold()), status);
+            outln!("{:<24}{}", format!("{}:", "Status".bold()), status);
 
             // Aggregated Resources
-            println!("{}", "Aggregated Resources:".bold());
+            outln!("{}", "Aggregated Resources:".bold());
 
             if let (Some(cpu_count), Some(cpu_usage)) = (
                 soc.get("total_cpu_count").and_then(|c| c.as_u64()),
                 soc.get("total_cpu_usage").and_then(|u| u.as_f64()),
             ) {
-                println!("  {:<22}{} ({:.2}% used)", "cpu:", cpu_count, cpu_usage);
+                outln!("  {:<22}{} ({:.2}% used)", "cpu:", cpu_count, cpu_usage);
             }
 
             if let Some(gpu_count) = soc.get("total_gpu_count").and_then(|g| g.as_u64()) {
-                println!("  {:<22}{}", "gpu:", gpu_count);
+                outln!("  {:<22}{}", "gpu:", gpu_count);
             }
 
             if let (Some(used_memory), Some(mem_usage)) = (
                 soc.get("total_used_memory").and_then(|m| m.as_u64()),
                 soc.get("total_mem_usage").and_then(|u| u.as_f64()),
             ) {
-                println!(
+                outln!(
                     "  {:<22}{} ({:.2}% used)",
                     "memory:",
                     format_memory(used_memory),
@@ -145,34 +145,34 @@ async fn describe_soc(client: &SettingsClient, soc_id: &str) -> Result<()> {
             }
 
             // Network I/O
-            println!("{}", "Network I/O:".bold());
+            outln!("{}", "Network I/O:".bold());
             if let Some(rx_bytes) = soc.get("total_rx_bytes").and_then(|r| r.as_u64()) {
-                println!("  {:<22}{}", "RX:", format_bytes(rx_bytes));
+                outln!("  {:<22}{}", "RX:", format_bytes(rx_bytes));
             }
             if let Some(tx_bytes) = soc.get("total_tx_bytes").and_then(|t| t.as_u64()) {
-                println!("  {:<22}{}", "TX:", format_bytes(tx_bytes));
+                outln!("  {:<22}{}", "TX:", format_bytes(tx_bytes));
             }
 
             // Disk I/O
-            println!("{}", "Disk I/O:".bold());
+            outln!("{}", "Disk I/O:".bold());
             if let Some(read_bytes) = soc.get("total_read_bytes").and_then(|r| r.as_u64()) {
-                println!("  {:<22}{}", "Read:", format_bytes(read_bytes));
+                outln!("  {:<22}{}", "Read:", format_bytes(read_bytes));
             }
             if let Some(write_bytes) = soc.get("total_write_bytes").and_then(|w| w.as_u64()) {
-                println!("  {:<22}{}", "Write:", format_bytes(write_bytes));
+                outln!("  {:<22}{}", "Write:", format_bytes(write_bytes));
             }
 
             // Nodes
             if let Some(nodes) = soc.get("nodes").and_then(|n| n.as_array()) {
                 let node_count = nodes.len();
-                println!("{} ({})", "Nodes:".bold(), node_count);
+                outln!("{} ({})", "Nodes:".bold(), node_count);
                 for node in nodes.iter() {
                     let node_name = node
                         .get("node_name")
                         .and_then(|n| n.as_str())
                         .unwrap_or("Unknown");
                     let node_ip = node.get("ip").and_then(|ip| ip.as_str()).unwrap_or("N/A");
-                    println!("  {:<22}{}", node_name, node_ip);
+                    outln!("  {:<22}{}", node_name, node_ip);
                 }
             }
 
@@ -187,7 +187,7 @@ async fn describe_soc(client: &SettingsClient, soc_id: &str) -> Result<()> {
                         .unwrap()
                         .as_secs();
                     let elapsed = now.saturating_sub(secs);
-                    println!(
+                    outln!(
                         "{:<24}{}",
                         format!("{}:", "Last Updated".bold()),
                         format_duration_ago(elapsed)
@@ -196,8 +196,8 @@ async fn describe_soc(client: &SettingsClient, soc_id: &str) -> Result<()> {
             }
 
             // Hint
-            println!("\n{}", "For more details:".dimmed());
-            println!("  {}", "pirictl describe node <node_name>".dimmed());
+            outln!("\n{}", "For more details:".dimmed());
+            outln!("  {}", "pirictl describe node <node_name>".dimmed());
 
             print_success("SoC information retrieved successfully");
         }
@@ -68,8 +68,8 @@ async fn top_metrics(client: &SettingsClient) -> Result<()> {
     }
 
     // Print table header
-    println!();
-    println!(
+    outln!();
+    outln!(
         "{:<6} {:<15} {:<3} {:<5} {:<19} {:<5} {:<3} {:<7} {:<17} {:<21}",
         "LEVEL".bold(),
         "NAME/ID".bold(),
@@ -85,7 +85,7 @@ async fn top_metrics(client: &SettingsClient) -> Result<()> {
 
     // Print each metric row
     for metric in metrics_data {
-        println!(
+        outln!(
             "{:<6} {:<15} {:<3} {:<5} {:<19} {:<5} {:<3} {:<7} {:<17} {:<21}",
             metric.level,
             metric.name,
@@ -100,7 +100,7 @@ async fn top_metrics(client: &SettingsClient) -> Result<()> {
         );
     }
 
-    println!();
+    outln!();
     Ok(())
 }
 
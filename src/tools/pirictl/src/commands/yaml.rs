@@ -15,19 +15,72 @@ pub enum YamlAction {
     Apply {
         /// Path to YAML file or '-' for stdin
         file: String,
+        /// Validate the artifact and show what would be applied without sending it
+        #[arg(long)]
+        dry_run: bool,
     },
     /// Withdraw (delete) YAML artifact from the system
     Withdraw {
         /// Path to YAML file or '-' for stdin
         file: String,
+        /// Validate the artifact and show what would be withdrawn without sending it
+        #[arg(long)]
+        dry_run: bool,
     },
 }
 
 pub async fn handle(client: &SettingsClient, action: YamlAction) -> Result<()> {
     match action {
-        YamlAction::Apply { file } => apply_yaml(client, &file).await,
-        YamlAction::Withdraw { file } => withdraw_yaml(client, &file).await,
+        YamlAction::Apply { file, dry_run } if dry_run => dry_run_yaml(client, &file, "apply"),
+        YamlAction::Apply { file, .. } => apply_yaml(client, &file).await,
+        YamlAction::Withdraw { file, dry_run } if dry_run => {
+            dry_run_yaml(client, &file, "withdraw")
+        }
+        YamlAction::Withdraw { file, .. } => withdraw_yaml(client, &file).await,
+    }
+}
+
+/// Validate a YAML artifact and list its resources without contacting the server
+///
+/// # Arguments
+/// * `client` - Client whose `--output json` report receives the plan
+/// * `file_path` - Path to YAML file or '-' for stdin
+/// * `operation` - "apply" or "withdraw", used in the report
+fn dry_run_yaml(client: &SettingsClient, file_path: &str, operation: &str) -> Result<()> {
+    print_info(&format!(
+        "Dry run: checking what {} would do with: {}",
+        operation, file_path
+    ));
+
+    let yaml_content = read_yaml_content(file_path)?;
+    validate_yaml_artifact(&yaml_content)?;
+    let resources = artifact_resources(&yaml_content)?;
+
+    let verb = if operation == "apply" {
+        "applied"
+    } else {
+        "withdrawn"
+    };
+    outln!("\nResources that would be {}:", verb);
+    for (i, (kind, name)) in resources.iter().enumerate() {
+        outln!("  {}. {} - {}", i + 1, kind, name);
     }
+
+    client.replace_responses(serde_json::json!({
+        "dryRun": true,
+        "operation": operation,
+        "resources": resources
+            .iter()
+            .map(|(kind, name)| serde_json::json!({"kind": kind, "name": name}))
+            .collect::<Vec<_>>(),
+    }));
+
+    print_success(&format!(
+        "Dry run complete: {} resource(s) would be {}, nothing was sent",
+        resources.len(),
+        verb
+    ));
+    Ok(())
 }
 
 /// Apply YAML artifact
@@ -42,16 +95,16 @@ async fn apply_yaml(client: &SettingsClient, file_path: &str) -> Result<()> {
     match client.post_yaml("/api/artifact", &yaml_content).await {
         Ok(response) => {
             if let Some(message) = response.get("message") {
-                println!("{}", message.as_str().unwrap_or("Applied successfully"));
+                outln!("{}", message.as_str().unwrap_or("Applied successfully"));
             }
 
             if let Some(applied) = response.get("applied") {
                 if let Some(array) = applied.as_array() {
-                    println!("\nApplied resources:");
+                    outln!("\nApplied resources:");
                     for (i, resource) in array.iter().enumerate() {
                         if let Some(kind) = resource.get("kind") {
                             if let Some(name) = resource.get("name") {
-                                println!(
+                                outln!(
                                     "  {}. {} - {}",
                                     i + 1,
                                     kind.as_str().unwrap_or("Unknown"),
@@ -86,16 +139,16 @@ async fn withdraw_yaml(client: &SettingsClient, file_path: &str) -> Result<()> {
     match client.delete_yaml("/api/artifact", &yaml_content).await {
         Ok(response) => {
             if let Some(message) = response.get("message") {
-                println!("{}", message.as_str().unwrap_or("Withdrawn successfully"));
+                outln!("{}", message.as_str().unwrap_or("Withdrawn successfully"));
             }
 
             if let Some(withdrawn) = response.get("withdrawn") {
                 if let Some(array) = withdrawn.as_array() {
-                    println!("\nWithdrawn resources:");
+                    outln!("\nWithdrawn resources:");
                     for (i, resource) in array.iter().enumerate() {
                         if let Some(kind) = resource.get("kind") {
                             if let Some(name) = resource.get("name") {
-                                println!(
+                                outln!(
                                     "  {}. {} - {}",
                                     i + 1,
                                     kind.as_str().unwrap_or("Unknown"),
@@ -134,6 +187,65 @@ fn read_yaml_content(file_path: &str) -> Result<String> {
     }
 }
 
+/// Kind and `metadata.name` of every document in a YAML artifact
+///
+/// # Returns
+/// * `Result<Vec<(String, String)>>` - One (kind, name) pair per non-empty
+///   document, or an error naming the first document missing either field
+fn artifact_resources(yaml_content: &str) -> Result<Vec<(String, String)>> {
+    let mut resources = Vec::new();
+
+    let documents = yaml_content
+        .split("---")
+        .map(str::trim)
+        .filter(|doc| !doc.is_empty());
+    for (index, doc) in documents.enumerate() {
+        let mut kind = None;
+        let mut name = None;
+        let mut in_metadata = false;
+
+        for line in doc.lines() {
+            let indented = line.starts_with(' ') || line.starts_with('\t');
+            let line = line.trim();
+            if !indented {
+                in_metadata = line == "metadata:";
+                if let Some(value) = line.strip_prefix("kind:") {
+                    kind = Some(value.trim().to_string());
+                }
+            } else if in_metadata && name.is_none() {
+                if let Some(value) = line.strip_prefix("name:") {
+                    name = Some(value.trim().trim_matches(['"', '\'']).to_string());
+                }
+            }
+        }
+
+        match (kind, name) {
+            (Some(kind), Some(name)) if !kind.is_empty() && !name.is_empty() => {
+                resources.push((kind, name))
+            }
+            (None, _) => {
+                return Err(crate::CliError::Custom(format!(
+                    "Document {} has no kind",
+                    index + 1
+                )))
+            }
+            _ => {
+                return Err(crate::CliError::Custom(format!(
+                    "Document {} has no metadata.name",
+                    index + 1
+                )))
+            }
+        }
+    }
+
+    if resources.is_empty() {
+        return Err(crate::CliError::Custom(
+            "YAML artifact has no documents".to_string(),
+        ));
+    }
+    Ok(resources)
+}
+
 /// Validate YAML artifact structure
 fn validate_yaml_artifact(yaml_content: &str) -> Result<()> {
     // Check if it contains required document separators
@@ -173,12 +285,12 @@ fn validate_yaml_artifact(yaml_content: &str) -> Result<()> {
         .collect();
 
     if !missing_kinds.is_empty() {
-        println!(
+        outln!(
             "{} Warning: Missing recommended kinds: {}",
             "⚠".yellow().bold(),
             missing_kinds.join(", ")
         );
-        println!(
+        outln!(
             "   The API Server expects Scenario, Package, and Model kinds for proper operation."
         );
     }
@@ -221,6 +333,7 @@ mod tests {
         let client = make_client(&server.uri()).await;
         let action = YamlAction::Apply {
             file: tmp.path().to_str().unwrap().to_string(),
+            dry_run: false,
         };
         assert!(handle(&client, action).await.is_ok());
     }
@@ -240,6 +353,7 @@ mod tests {
         let client = make_client(&server.uri()).await;
         let action = YamlAction::Withdraw {
             file: tmp.path().to_str().unwrap().to_string(),
+            dry_run: false,
         };
         assert!(handle(&client, action).await.is_ok());
     }
@@ -265,6 +379,7 @@ mod tests {
         let client = make_client(&server.uri()).await;
         let action = YamlAction::Apply {
             file: tmp.path().to_str().unwrap().to_string(),
+            dry_run: false,
         };
         assert!(handle(&client, action).await.is_ok());
     }
@@ -282,6 +397,7 @@ mod tests {
         let client = make_client(&server.uri()).await;
         let action = YamlAction::Apply {
             file: tmp.path().to_str().unwrap().to_string(),
+            dry_run: false,
         };
         assert!(handle(&client, action).await.is_ok());
     }
@@ -299,6 +415,7 @@ mod tests {
         let client = make_client(&server.uri()).await;
         let action = YamlAction::Apply {
             file: tmp.path().to_str().unwrap().to_string(),
+            dry_run: false,
         };
         assert!(handle(&client, action).await.is_err());
     }
@@ -309,6 +426,7 @@ mod tests {
         let client = make_client(&server.uri()).await;
         let action = YamlAction::Apply {
             file: "/nonexistent/missing.yaml".to_string(),
+            dry_run: false,
         };
         assert!(handle(&client, action).await.is_err());
     }
@@ -334,6 +452,7 @@ mod tests {
         let client = make_client(&server.uri()).await;
         let action = YamlAction::Withdraw {
             file: tmp.path().to_str().unwrap().to_string(),
+            dry_run: false,
         };
         assert!(handle(&client, action).await.is_ok());
     }
@@ -350,6 +469,7 @@ mod tests {
         let client = make_client(&server.uri()).await;
         let action = YamlAction::Withdraw {
             file: tmp.path().to_str().unwrap().to_string(),
+            dry_run: false,
         };
         assert!(handle(&client, action).await.is_ok());
     }
@@ -367,6 +487,7 @@ mod tests {
         let client = make_client(&server.uri()).await;
         let action = YamlAction::Withdraw {
             file: tmp.path().to_str().unwrap().to_string(),
+            dry_run: false,
         };
         assert!(handle(&client, action).await.is_err());
     }
@@ -377,6 +498,7 @@ mod tests {
         let client = make_client(&server.uri()).await;
         let action = YamlAction::Withdraw {
             file: "/nonexistent/missing.yaml".to_string(),
+            dry_run: false,
         };
         assert!(handle(&client, action).await.is_err());
     }
@@ -477,4 +599,87 @@ metadata:
         let result = validate_yaml_artifact(yaml);
         assert!(result.is_ok());
     }
+
+    // ── dry run ───────────────────────────────────────────────────────────────
+
+    const ARTIFACT: &str = "---\napiVersion: v1\nkind: Scenario\nmetadata:\n  name: helloworld\nspec:\n  action: update\n---\napiVersion: v1\nkind: Package\nmetadata:\n  label: null\n  name: helloworld\nspec:\n  models:\n    - name: helloworld-core\n";
+
+    #[tokio::test]
+    async fn test_apply_dry_run_sends_nothing() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({})))
+            .expect(0)
+            .mount(&server)
+            .await;
+        let tmp = write_temp_yaml(ARTIFACT);
+        let client = make_client(&server.uri()).await;
+        let action = YamlAction::Apply {
+            file: tmp.path().to_str().unwrap().to_string(),
+            dry_run: true,
+        };
+        assert!(handle(&client, action).await.is_ok());
+
+        let report = client.take_responses();
+        assert_eq!(report[0]["dryRun"], json!(true));
+        assert_eq!(report[0]["operation"], json!("apply"));
+        assert_eq!(
+            report[0]["resources"],
+            json!([
+                {"kind": "Scenario", "name": "helloworld"},
+                {"kind": "Package", "name": "helloworld"}
+            ])
+        );
+        assert!(server.received_requests().await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_withdraw_dry_run_sends_nothing() {
+        let server = MockServer::start().await;
+        Mock::given(method("DELETE"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({})))
+            .expect(0)
+            .mount(&server)
+            .await;
+        let tmp = write_temp_yaml(ARTIFACT);
+        let client = make_client(&server.uri()).await;
+        let action = YamlAction::Withdraw {
+            file: tmp.path().to_str().unwrap().to_string(),
+            dry_run: true,
+        };
+        assert!(handle(&client, action).await.is_ok());
+        assert_eq!(client.take_responses()[0]["operation"], json!("withdraw"));
+        assert!(server.received_requests().await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_dry_run_rejects_invalid_artifact() {
+        let server = MockServer::start().await;
+        let tmp = write_temp_yaml("---\nkind: Scenario\nspec: {}\n");
+        let client = make_client(&server.uri()).await;
+        let action = YamlAction::Apply {
+            file: tmp.path().to_str().unwrap().to_string(),
+            dry_run: true,
+        };
+        assert!(handle(&client, action).await.is_err());
+        assert!(server.received_requests().await.unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_artifact_resources() {
+        assert_eq!(
+            artifact_resources(ARTIFACT).unwrap(),
+            vec![
+                ("Scenario".to_string(), "helloworld".to_string()),
+                ("Package".to_string(), "helloworld".to_string())
+            ]
+        );
+        let quoted = "kind: Model\nmetadata:\n  name: \"hello-core\"\n";
+        assert_eq!(
+            artifact_resources(quoted).unwrap(),
+            vec![("Model".to_string(), "hello-core".to_string())]
+        );
+        assert!(artifact_resources("metadata:\n  name: x\n").is_err());
+        assert!(artifact_resources("---\n---\n").is_err());
+    }
 }
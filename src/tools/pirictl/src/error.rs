@@ -15,6 +15,13 @@ pub enum CliError {
     Json(serde_json::Error),
     /// IO errors
     Io(std::io::Error),
+    /// Service answered with a non-success HTTP status
+    Server {
+        /// HTTP status code returned by the service
+        status: u16,
+        /// Human-readable description including the status line
+        message: String,
+    },
     /// Custom error messages
     Custom(String),
 }
@@ -25,6 +32,7 @@ impl fmt::Display for CliError {
             CliError::Http(e) => write!(f, "HTTP error: {}", e),
             CliError::Json(e) => write!(f, "JSON error: {}", e),
             CliError::Io(e) => write!(f, "IO error: {}", e),
+            CliError::Server { message, .. } => write!(f, "Error: {}", message),
             CliError::Custom(msg) => write!(f, "Error: {}", msg),
         }
     }
@@ -100,6 +108,18 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_cli_error_display_server() {
+        let err = CliError::Server {
+            status: 404,
+            message: "Request failed with status: 404 Not Found".to_string(),
+        };
+        assert_eq!(
+            format!("{}", err),
+            "Error: Request failed with status: 404 Not Found"
+        );
+    }
+
     #[test]
     fn test_cli_error_debug() {
         let err = CliError::Custom("debug test".to_string());
@@ -7,9 +7,39 @@
 //! This library provides the core functionality for the pirictl tool,
 //! which communicates with the Pullpiri SettingsService via REST APIs.
 
+/// Print human-readable output, followed by a newline.
+///
+/// Writes to stdout normally and to stderr when `--output json` is active,
+/// so that stdout only carries the JSON result document.
+#[macro_export]
+macro_rules! outln {
+    ($($arg:tt)*) => {
+        if $crate::output::is_json() {
+            eprintln!($($arg)*)
+        } else {
+            println!($($arg)*)
+        }
+    };
+}
+
+/// Print human-readable output without a trailing newline.
+///
+/// See [`outln!`] for where the text ends up.
+#[macro_export]
+macro_rules! out {
+    ($($arg:tt)*) => {
+        if $crate::output::is_json() {
+            eprint!($($arg)*)
+        } else {
+            print!($($arg)*)
+        }
+    };
+}
+
 pub mod client;
 pub mod commands;
 pub mod error;
+pub mod output;
 
 pub use client::SettingsClient;
 pub use error::{CliError, Result};
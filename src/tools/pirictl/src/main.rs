@@ -9,8 +9,9 @@
 
 use clap::{Parser, Subcommand};
 use colored::Colorize;
-use pirictl::commands::{board, container, metrics, node, soc, top, yaml};
-use pirictl::{Result, SettingsClient};
+use pirictl::commands::{board, container, logs, metrics, node, soc, top, yaml};
+use pirictl::output::{self, OutputFormat};
+use pirictl::{outln, Result, SettingsClient};
 use url::Url;
 
 #[derive(Parser)]
@@ -39,6 +40,14 @@ struct Cli {
     #[arg(short, long)]
     verbose: bool,
 
+    /// Ask SettingsService for CBOR instead of JSON responses
+    #[arg(long, env = "PICCOLO_CBOR")]
+    cbor: bool,
+
+    /// Output format; `json` prints a single result document to stdout
+    #[arg(short, long, global = true, value_enum, default_value = "text")]
+    output: OutputFormat,
+
     #[command(subcommand)]
     command: Commands,
 }
@@ -70,12 +79,34 @@ enum Commands {
         /// Path to YAML file
         #[arg(short = 'f', long = "file")]
         file: String,
+        /// Validate the file and show what would be applied without sending it
+        #[arg(long)]
+        dry_run: bool,
     },
     /// Delete YAML artifact from the system
     Delete {
         /// Path to YAML file
         #[arg(short = 'f', long = "file")]
         file: String,
+        /// Validate the file and show what would be deleted without sending it
+        #[arg(long)]
+        dry_run: bool,
+    },
+    /// Print the logs of a workload, read from the node running it
+    Logs {
+        /// Name of the model the workload runs
+        workload: String,
+        /// Keep printing new lines until interrupted
+        #[arg(short, long)]
+        follow: bool,
+        /// Only print the last N lines
+        #[arg(long, value_name = "N")]
+        tail: Option<u32>,
+    },
+    /// Show registered nodes and their health
+    Node {
+        #[command(subcommand)]
+        command: node::NodeCommand,
     },
     /// Test connection to SettingsService
     Health,
@@ -143,6 +174,7 @@ enum ResourceTypeRaw {
 #[tokio::main]
 async fn main() -> std::result::Result<(), Box<dyn std::error::Error>> {
     let cli = Cli::parse();
+    output::set_format(cli.output);
 
     // Use the `url` crate to parse the base URL, replace the port, and build the final URLs
     let settings_url = build_url_with_port(&cli.url, cli.settings_port).unwrap_or_else(|e| {
@@ -155,17 +187,17 @@ async fn main() -> std::result::Result<(), Box<dyn std::error::Error>> {
     });
 
     if cli.verbose {
-        println!(
+        outln!(
             "{} SettingsService URL: {}",
             "ℹ".blue().bold(),
             settings_url
         );
-        println!("{} API Server URL: {}", "ℹ".blue().bold(), api_url);
+        outln!("{} API Server URL: {}", "ℹ".blue().bold(), api_url);
     }
 
     // Create two clients: one for SettingsService, one for API Server
     let settings_client = match SettingsClient::new(&settings_url, cli.timeout) {
-        Ok(client) => client,
+        Ok(client) => client.with_cbor(cli.cbor),
         Err(e) => {
             eprintln!(
                 "{} Failed to create settings client: {}",
@@ -230,25 +262,29 @@ async fn main() -> std::result::Result<(), Box<dyn std::error::Error>> {
             }
         },
         Commands::Top { resource } => top::handle(&settings_client, resource).await,
-        Commands::Apply { file } => {
-            yaml::handle(&api_client, yaml::YamlAction::Apply { file }).await
+        Commands::Apply { file, dry_run } => {
+            yaml::handle(&api_client, yaml::YamlAction::Apply { file, dry_run }).await
         }
-        Commands::Delete { file } => {
-            yaml::handle(&api_client, yaml::YamlAction::Withdraw { file }).await
+        Commands::Delete { file, dry_run } => {
+            yaml::handle(&api_client, yaml::YamlAction::Withdraw { file, dry_run }).await
+        }
+        Commands::Logs {
+            workload,
+            follow,
+            tail,
+        } => logs::handle(&api_client, &workload, follow, tail).await,
+        Commands::Node { command } => {
+            node::handle_command(&api_client, &settings_client, command).await
         }
         Commands::Health => health_check(&settings_client).await,
     };
 
-    match result {
-        Ok(_) => {
-            if cli.verbose {
-                println!("{} Command completed successfully", "✓".green().bold());
-            }
-        }
-        Err(e) => {
-            eprintln!("{} Command failed: {}", "✗".red().bold(), e);
-            std::process::exit(1);
-        }
+    let mut responses = settings_client.take_responses();
+    responses.extend(api_client.take_responses());
+
+    let code = output::finish(result, responses, cli.verbose);
+    if code != output::EXIT_SUCCESS {
+        std::process::exit(code);
     }
 
     Ok(())
@@ -268,23 +304,23 @@ fn build_url_with_port(base_url: &str, port: u16) -> std::result::Result<String,
 
 /// Perform a health check on the SettingsService
 async fn health_check(client: &SettingsClient) -> Result<()> {
-    println!("{} Checking SettingsService health...", "ℹ".blue().bold());
+    outln!("{} Checking SettingsService health...", "ℹ".blue().bold());
 
     match client.health_check().await {
         Ok(true) => {
-            println!(
+            outln!(
                 "{} SettingsService is healthy and reachable",
                 "✓".green().bold()
             );
         }
         Ok(false) => {
-            println!("{} SettingsService is not reachable", "✗".red().bold());
+            outln!("{} SettingsService is not reachable", "✗".red().bold());
             return Err(pirictl::error::CliError::Custom(
                 "Health check failed".to_string(),
             ));
         }
         Err(e) => {
-            println!("{} Health check failed: {}", "✗".red().bold(), e);
+            outln!("{} Health check failed: {}", "✗".red().bold(), e);
             return Err(e);
         }
     }
@@ -0,0 +1,246 @@
+/*
+* SPDX-FileCopyrightText: Copyright 2024 LG Electronics Inc.
+* SPDX-License-Identifier: Apache-2.0
+*/
+//! Output format selection and command result handling
+//!
+//! With `--output json` the only thing written to stdout is a single JSON
+//! document describing the command result. Human-readable text is moved to
+//! stderr so scripts can parse stdout directly.
+
+use crate::error::CliError;
+use serde_json::{json, Value};
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// Exit code for a successful command
+pub const EXIT_SUCCESS: i32 = 0;
+/// Exit code for local failures (bad input file, invalid YAML, I/O errors)
+pub const EXIT_FAILURE: i32 = 1;
+/// Exit code when the target service could not be reached
+pub const EXIT_CONNECTION_ERROR: i32 = 3;
+/// Exit code when the service was reached but rejected or failed the command
+pub const EXIT_SERVER_ERROR: i32 = 4;
+
+static JSON_OUTPUT: AtomicBool = AtomicBool::new(false);
+
+/// Output format selected with the global `--output` flag
+#[derive(Clone, Copy, Debug, PartialEq, Eq, clap::ValueEnum)]
+pub enum OutputFormat {
+    /// Human-readable text on stdout
+    Text,
+    /// Machine-readable JSON document on stdout
+    Json,
+}
+
+/// Select the process-wide output format
+pub fn set_format(format: OutputFormat) {
+    JSON_OUTPUT.store(format == OutputFormat::Json, Ordering::Relaxed);
+}
+
+/// Returns `true` when `--output json` is active
+pub fn is_json() -> bool {
+    JSON_OUTPUT.load(Ordering::Relaxed)
+}
+
+/// Map a command error to the process exit code
+pub fn exit_code(error: &CliError) -> i32 {
+    match error {
+        CliError::Http(e) if e.is_connect() || e.is_timeout() => EXIT_CONNECTION_ERROR,
+        CliError::Http(e) if e.status().is_some() => EXIT_SERVER_ERROR,
+        CliError::Http(_) => EXIT_CONNECTION_ERROR,
+        CliError::Server { .. } => EXIT_SERVER_ERROR,
+        CliError::Json(_) | CliError::Io(_) | CliError::Custom(_) => EXIT_FAILURE,
+    }
+}
+
+/// Short machine-readable name of the error category
+fn error_kind(error: &CliError) -> &'static str {
+    match exit_code(error) {
+        EXIT_CONNECTION_ERROR => "connection",
+        EXIT_SERVER_ERROR => "server",
+        _ => "client",
+    }
+}
+
+/// Build the JSON document emitted for `--output json`
+///
+/// `resp` holds the server responses received while running the command.
+pub fn to_json(result: &crate::Result<()>, resp: Vec<Value>) -> Value {
+    let resp = match resp.len() {
+        0 => Value::Null,
+        1 => resp.into_iter().next().unwrap_or(Value::Null),
+        _ => Value::Array(resp),
+    };
+
+    match result {
+        Ok(()) => json!({
+            "success": true,
+            "resp": resp,
+            "error": Value::Null,
+        }),
+        Err(e) => {
+            let status = match e {
+                CliError::Server { status, .. } => json!(status),
+                CliError::Http(err) => err
+                    .status()
+                    .map(|s| json!(s.as_u16()))
+                    .unwrap_or(Value::Null),
+                _ => Value::Null,
+            };
+            json!({
+                "success": false,
+                "resp": resp,
+                "error": {
+                    "kind": error_kind(e),
+                    "message": e.to_string(),
+                    "status": status,
+                    "exit_code": exit_code(e),
+                },
+            })
+        }
+    }
+}
+
+/// Report the command result and return the process exit code
+///
+/// In JSON mode the result document is printed to stdout; otherwise failures
+/// are printed to stderr and nothing else is written.
+pub fn finish(result: crate::Result<()>, resp: Vec<Value>, verbose: bool) -> i32 {
+    let code = match &result {
+        Ok(()) => EXIT_SUCCESS,
+        Err(e) => exit_code(e),
+    };
+
+    if is_json() {
+        let document = to_json(&result, resp);
+        match serde_json::to_string_pretty(&document) {
+            Ok(text) => println!("{}", text),
+            Err(e) => eprintln!("Failed to serialize result: {}", e),
+        }
+        return code;
+    }
+
+    use colored::Colorize;
+    match result {
+        Ok(()) => {
+            if verbose {
+                println!("{} Command completed successfully", "✓".green().bold());
+            }
+        }
+        Err(e) => eprintln!("{} Command failed: {}", "✗".red().bold(), e),
+    }
+    code
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn connection_error() -> CliError {
+        // Port 9 (discard) on localhost is never served in the test environment
+        let err = reqwest::blocking::Client::new()
+            .get("http://127.0.0.1:9/")
+            .send()
+            .unwrap_err();
+        CliError::Http(err)
+    }
+
+    #[test]
+    fn test_exit_code_connection_error() {
+        assert_eq!(exit_code(&connection_error()), EXIT_CONNECTION_ERROR);
+    }
+
+    #[test]
+    fn test_exit_code_server_error() {
+        let err = CliError::Server {
+            status: 500,
+            message: "Request failed with status: 500".to_string(),
+        };
+        assert_eq!(exit_code(&err), EXIT_SERVER_ERROR);
+    }
+
+    #[test]
+    fn test_exit_code_local_errors() {
+        let io = CliError::Io(std::io::Error::new(std::io::ErrorKind::NotFound, "x"));
+        let json = CliError::Json(serde_json::from_str::<Value>("{").unwrap_err());
+        let custom = CliError::Custom("File not found: a.yaml".to_string());
+
+        assert_eq!(exit_code(&io), EXIT_FAILURE);
+        assert_eq!(exit_code(&json), EXIT_FAILURE);
+        assert_eq!(exit_code(&custom), EXIT_FAILURE);
+    }
+
+    #[test]
+    fn test_exit_codes_are_distinct() {
+        let codes = [
+            EXIT_SUCCESS,
+            EXIT_FAILURE,
+            EXIT_CONNECTION_ERROR,
+            EXIT_SERVER_ERROR,
+        ];
+        for (i, a) in codes.iter().enumerate() {
+            for b in codes.iter().skip(i + 1) {
+                assert_ne!(a, b);
+            }
+        }
+        // clap reserves 2 for usage errors
+        assert!(!codes.contains(&2));
+    }
+
+    #[test]
+    fn test_finish_returns_success_code() {
+        assert_eq!(finish(Ok(()), vec![], false), EXIT_SUCCESS);
+    }
+
+    #[test]
+    fn test_finish_returns_error_code() {
+        let err = CliError::Server {
+            status: 404,
+            message: "Request failed with status: 404 Not Found".to_string(),
+        };
+        assert_eq!(finish(Err(err), vec![], false), EXIT_SERVER_ERROR);
+        assert_eq!(
+            finish(Err(connection_error()), vec![], false),
+            EXIT_CONNECTION_ERROR
+        );
+    }
+
+    #[test]
+    fn test_to_json_success_single_response() {
+        let doc = to_json(&Ok(()), vec![json!({"message": "applied"})]);
+        assert_eq!(doc["success"], true);
+        assert_eq!(doc["resp"]["message"], "applied");
+        assert!(doc["error"].is_null());
+    }
+
+    #[test]
+    fn test_to_json_multiple_responses_become_array() {
+        let doc = to_json(&Ok(()), vec![json!(1), json!(2)]);
+        assert_eq!(doc["resp"], json!([1, 2]));
+    }
+
+    #[test]
+    fn test_to_json_server_error_details() {
+        let err = CliError::Server {
+            status: 409,
+            message: "Request failed with status: 409 Conflict - exists".to_string(),
+        };
+        let doc = to_json(&Err(err), vec![]);
+        assert_eq!(doc["success"], false);
+        assert!(doc["resp"].is_null());
+        assert_eq!(doc["error"]["kind"], "server");
+        assert_eq!(doc["error"]["status"], 409);
+        assert_eq!(doc["error"]["exit_code"], EXIT_SERVER_ERROR);
+        assert!(doc["error"]["message"]
+            .as_str()
+            .unwrap()
+            .contains("409 Conflict"));
+    }
+
+    #[test]
+    fn test_to_json_connection_error_kind() {
+        let doc = to_json(&Err(connection_error()), vec![]);
+        assert_eq!(doc["error"]["kind"], "connection");
+        assert!(doc["error"]["status"].is_null());
+    }
+}
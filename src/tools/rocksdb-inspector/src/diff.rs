@@ -0,0 +1,224 @@
+/*
+ * SPDX-FileCopyrightText: Copyright 2024 LG Electronics Inc.
+ * SPDX-License-Identifier: Apache-2.0
+ */
+
+//! Comparison of two RocksDB databases, e.g. before and after a migration
+
+use rocksdb::{Direction, IteratorMode, DB};
+use std::cmp::Ordering;
+
+/// Differences between two databases
+#[derive(Debug, Default, PartialEq)]
+pub struct DbDiff {
+    /// Keys present only in the first database
+    pub only_left: Vec<String>,
+    /// Keys present only in the second database
+    pub only_right: Vec<String>,
+    /// Keys present in both databases with different values
+    pub changed: Vec<String>,
+    /// Number of keys with the same value in both databases
+    pub identical: usize,
+}
+
+impl DbDiff {
+    /// Returns `true` if both databases hold the same data
+    pub fn is_equivalent(&self) -> bool {
+        self.only_left.is_empty() && self.only_right.is_empty() && self.changed.is_empty()
+    }
+}
+
+type Entry = (Box<[u8]>, Box<[u8]>);
+
+/// Entries of `db` in key order, limited to keys starting with `prefix`
+pub fn scan<'a>(
+    db: &'a DB,
+    prefix: &'a str,
+) -> impl Iterator<Item = Result<Entry, rocksdb::Error>> + 'a {
+    db.iterator(IteratorMode::From(prefix.as_bytes(), Direction::Forward))
+        .take_while(move |item| match item {
+            Ok((key, _)) => key.starts_with(prefix.as_bytes()),
+            Err(_) => true,
+        })
+}
+
+/// Compare every key of `left` and `right`
+///
+/// # Arguments
+///
+/// * `left` - First database
+/// * `right` - Second database
+/// * `prefix` - Only compare keys starting with this prefix
+///
+/// Both databases are walked once in key order, so neither is loaded in
+/// memory as a whole.
+pub fn diff(left: &DB, right: &DB, prefix: Option<&str>) -> Result<DbDiff, rocksdb::Error> {
+    let prefix = prefix.unwrap_or("");
+    let mut left = scan(left, prefix).peekable();
+    let mut right = scan(right, prefix).peekable();
+    let mut result = DbDiff::default();
+
+    loop {
+        let order = match (left.peek(), right.peek()) {
+            (None, None) => break,
+            (Some(_), None) => Ordering::Less,
+            (None, Some(_)) => Ordering::Greater,
+            (Some(Ok((l, _))), Some(Ok((r, _)))) => l.cmp(r),
+            // Let the error surface through the `?` below
+            (Some(Err(_)), _) => Ordering::Less,
+            (_, Some(Err(_))) => Ordering::Greater,
+        };
+
+        match order {
+            Ordering::Less => {
+                if let Some(item) = left.next() {
+                    let (key, _) = item?;
+                    result.only_left.push(display_key(&key));
+                }
+            }
+            Ordering::Greater => {
+                if let Some(item) = right.next() {
+                    let (key, _) = item?;
+                    result.only_right.push(display_key(&key));
+                }
+            }
+            Ordering::Equal => {
+                if let (Some(l), Some(r)) = (left.next(), right.next()) {
+                    let ((key, left_value), (_, right_value)) = (l?, r?);
+                    if left_value == right_value {
+                        result.identical += 1;
+                    } else {
+                        result.changed.push(display_key(&key));
+                    }
+                }
+            }
+        }
+    }
+
+    Ok(result)
+}
+
+fn display_key(key: &[u8]) -> String {
+    String::from_utf8_lossy(key).to_string()
+}
+
+/// Print the counts of `diff`, and every differing key if `verbose`
+pub fn print_diff(diff: &DbDiff, left_path: &str, right_path: &str, verbose: bool) {
+    println!("🔀 Diff: {} ⇄ {}", left_path, right_path);
+    println!("   ✅ Identical: {}", diff.identical);
+    println!("   ⬅️  Only in {}: {}", left_path, diff.only_left.len());
+    println!("   ➡️  Only in {}: {}", right_path, diff.only_right.len());
+    println!("   ✏️  Different values: {}", diff.changed.len());
+
+    if verbose {
+        for (title, keys) in [
+            (format!("Only in {}", left_path), &diff.only_left),
+            (format!("Only in {}", right_path), &diff.only_right),
+            ("Different values".to_string(), &diff.changed),
+        ] {
+            if !keys.is_empty() {
+                println!("\n{}:", title);
+                for key in keys {
+                    println!("   📄 {}", key);
+                }
+            }
+        }
+    }
+
+    if diff.is_equivalent() {
+        println!("\n🎉 Databases are equivalent");
+    } else {
+        println!("\n⚠️  Databases differ");
+    }
+}
+
+//Unit Test Cases
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rocksdb::Options;
+    use std::path::PathBuf;
+
+    /// Create a database holding `entries` and return its path
+    fn create_db(name: &str, entries: &[(&str, &str)]) -> PathBuf {
+        let path = std::env::temp_dir().join(format!("rocksdb-inspector-diff-{}", name));
+        let _ = std::fs::remove_dir_all(&path);
+
+        let mut opts = Options::default();
+        opts.create_if_missing(true);
+        let db = DB::open(&opts, &path).unwrap();
+        for (key, value) in entries {
+            db.put(key, value).unwrap();
+        }
+        path
+    }
+
+    fn open(path: &PathBuf) -> DB {
+        DB::open_for_read_only(&Options::default(), path, false).unwrap()
+    }
+
+    fn databases(name: &str) -> (DB, DB) {
+        let left = create_db(
+            &format!("{}-left", name),
+            &[
+                ("Model/helloworld", "model"),
+                ("Package/helloworld", "package-v1"),
+                ("Scenario/helloworld", "scenario"),
+                ("cluster/nodes/HPC", "hpc"),
+            ],
+        );
+        let right = create_db(
+            &format!("{}-right", name),
+            &[
+                ("Package/helloworld", "package-v2"),
+                ("Scenario/helloworld", "scenario"),
+                ("Scenario/parking", "scenario"),
+                ("cluster/nodes/HPC", "hpc"),
+            ],
+        );
+        (open(&left), open(&right))
+    }
+
+    #[test]
+    fn test_diff_categorizes_keys() {
+        let (left, right) = databases("all");
+
+        let result = diff(&left, &right, None).unwrap();
+
+        assert_eq!(result.only_left, vec!["Model/helloworld"]);
+        assert_eq!(result.only_right, vec!["Scenario/parking"]);
+        assert_eq!(result.changed, vec!["Package/helloworld"]);
+        assert_eq!(result.identical, 2);
+        assert!(!result.is_equivalent());
+    }
+
+    #[test]
+    fn test_diff_with_prefix_scope() {
+        let (left, right) = databases("prefix");
+
+        let scenarios = diff(&left, &right, Some("Scenario/")).unwrap();
+        assert_eq!(
+            scenarios,
+            DbDiff {
+                only_left: vec![],
+                only_right: vec!["Scenario/parking".to_string()],
+                changed: vec![],
+                identical: 1,
+            }
+        );
+
+        let cluster = diff(&left, &right, Some("cluster/")).unwrap();
+        assert!(cluster.is_equivalent());
+        assert_eq!(cluster.identical, 1);
+    }
+
+    #[test]
+    fn test_diff_of_same_database_is_equivalent() {
+        let (left, _) = databases("same");
+
+        let result = diff(&left, &left, None).unwrap();
+
+        assert!(result.is_equivalent());
+        assert_eq!(result.identical, 4);
+    }
+}
@@ -0,0 +1,172 @@
+/*
+ * SPDX-FileCopyrightText: Copyright 2024 LG Electronics Inc.
+ * SPDX-License-Identifier: Apache-2.0
+ */
+
+//! JSON dumps of a RocksDB database, to seed a fresh database for testing
+//!
+//! A dump is a JSON array of `{"key": ..., "value": ...}` objects with
+//! string keys and values, in key order.
+
+use crate::diff::scan;
+use rocksdb::{WriteBatch, DB};
+use serde_json::{json, Value};
+
+/// Write the entries of `db` as a JSON dump
+///
+/// # Arguments
+///
+/// * `db` - Database to export
+/// * `prefix` - Only export keys starting with this prefix
+pub fn export(db: &DB, prefix: Option<&str>) -> Result<String, Box<dyn std::error::Error>> {
+    let mut entries = Vec::new();
+    for item in scan(db, prefix.unwrap_or("")) {
+        let (key, value) = item?;
+        let key = String::from_utf8(key.to_vec())?;
+        let value = String::from_utf8(value.to_vec())
+            .map_err(|_| format!("value of '{}' is not valid UTF-8", key))?;
+        entries.push(json!({ "key": key, "value": value }));
+    }
+    Ok(serde_json::to_string_pretty(&entries)?)
+}
+
+/// Read the entries of a JSON dump
+///
+/// # Returns
+///
+/// * `Result<Vec<(String, String)>, String>` - Key and value of every
+///   entry, or what is wrong with the dump
+pub fn parse(dump: &str) -> Result<Vec<(String, String)>, String> {
+    let value: Value = serde_json::from_str(dump).map_err(|e| format!("invalid JSON: {}", e))?;
+    let Value::Array(items) = value else {
+        return Err("expected a JSON array of {key, value} objects".to_string());
+    };
+
+    items
+        .iter()
+        .enumerate()
+        .map(|(index, item)| {
+            let field = |name: &str| {
+                item.get(name)
+                    .and_then(Value::as_str)
+                    .map(str::to_string)
+                    .ok_or_else(|| format!("entry {}: missing string `{}`", index, name))
+            };
+            Ok((field("key")?, field("value")?))
+        })
+        .collect()
+}
+
+/// Write `entries` to `db` in one batch
+///
+/// With `clear_first`, every key already in `db` is deleted in the same
+/// batch, so the database ends up holding exactly the dump.
+///
+/// # Returns
+///
+/// * `Result<usize, rocksdb::Error>` - Number of entries written
+pub fn import(
+    db: &DB,
+    entries: &[(String, String)],
+    clear_first: bool,
+) -> Result<usize, rocksdb::Error> {
+    let mut batch = WriteBatch::default();
+    if clear_first {
+        for item in scan(db, "") {
+            let (key, _) = item?;
+            batch.delete(key);
+        }
+    }
+    for (key, value) in entries {
+        batch.put(key.as_bytes(), value.as_bytes());
+    }
+    db.write(batch)?;
+    Ok(entries.len())
+}
+
+//Unit Test Cases
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rocksdb::Options;
+
+    /// Open a new, empty database for one test
+    fn open_db(name: &str) -> DB {
+        let path = std::env::temp_dir().join(format!("rocksdb-inspector-dump-{}", name));
+        let _ = std::fs::remove_dir_all(&path);
+        let mut opts = Options::default();
+        opts.create_if_missing(true);
+        DB::open(&opts, &path).unwrap()
+    }
+
+    fn entries(pairs: &[(&str, &str)]) -> Vec<(String, String)> {
+        pairs
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn test_export_then_import_into_empty_db() {
+        let source = open_db("source");
+        source.put("Scenario/helloworld", "scenario").unwrap();
+        source.put("Package/helloworld", "package").unwrap();
+        source
+            .put("cluster/nodes/HPC", "{\"ip\": \"10.0.0.1\"}")
+            .unwrap();
+
+        let dump = export(&source, None).unwrap();
+        let target = open_db("target");
+        let written = import(&target, &parse(&dump).unwrap(), false).unwrap();
+
+        assert_eq!(written, 3);
+        let diff = crate::diff::diff(&source, &target, None).unwrap();
+        assert!(diff.is_equivalent(), "{:?}", diff);
+        assert_eq!(diff.identical, 3);
+    }
+
+    #[test]
+    fn test_export_with_prefix() {
+        let db = open_db("prefix");
+        db.put("Scenario/a", "1").unwrap();
+        db.put("Package/a", "2").unwrap();
+
+        let dump = export(&db, Some("Scenario/")).unwrap();
+
+        assert_eq!(parse(&dump).unwrap(), entries(&[("Scenario/a", "1")]));
+    }
+
+    #[test]
+    fn test_import_clear_first() {
+        let db = open_db("clear");
+        db.put("Scenario/stale", "old").unwrap();
+        db.put("Scenario/kept", "old").unwrap();
+
+        import(&db, &entries(&[("Scenario/kept", "new")]), true).unwrap();
+
+        assert_eq!(db.get("Scenario/stale").unwrap(), None);
+        assert_eq!(db.get("Scenario/kept").unwrap(), Some(b"new".to_vec()));
+
+        // Without clearing, existing keys are kept
+        import(&db, &entries(&[("Scenario/other", "x")]), false).unwrap();
+        assert_eq!(db.get("Scenario/kept").unwrap(), Some(b"new".to_vec()));
+    }
+
+    #[test]
+    fn test_parse_rejects_bad_shape() {
+        assert!(parse("not json").unwrap_err().starts_with("invalid JSON"));
+        assert_eq!(
+            parse(r#"{"key": "a", "value": "b"}"#).unwrap_err(),
+            "expected a JSON array of {key, value} objects"
+        );
+        assert_eq!(
+            parse(r#"[{"key": "a", "value": "b"}, {"key": "c"}]"#).unwrap_err(),
+            "entry 1: missing string `value`"
+        );
+        assert_eq!(
+            parse(r#"[{"key": 1, "value": "b"}]"#).unwrap_err(),
+            "entry 0: missing string `key`"
+        );
+        assert!(parse("[]").unwrap().is_empty());
+    }
+}
@@ -3,7 +3,11 @@
  * SPDX-License-Identifier: Apache-2.0
  */
 
+mod diff;
+mod dump;
+
 use clap::{Arg, Command};
+use common::keys;
 use rocksdb::{IteratorMode, Options, DB};
 use std::collections::HashMap;
 
@@ -41,6 +45,40 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
                 .help("Show database statistics")
                 .action(clap::ArgAction::SetTrue),
         )
+        .arg(
+            Arg::new("diff")
+                .short('d')
+                .long("diff")
+                .value_name("OTHER_PATH")
+                .help("Compare with the RocksDB at OTHER_PATH, limited to --prefix if given"),
+        )
+        .arg(
+            Arg::new("verbose")
+                .short('v')
+                .long("verbose")
+                .help("List every differing key in --diff mode")
+                .action(clap::ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new("export")
+                .short('e')
+                .long("export")
+                .value_name("FILE")
+                .help("Write the data, limited to --prefix if given, to FILE as a JSON dump"),
+        )
+        .arg(
+            Arg::new("import")
+                .short('i')
+                .long("import")
+                .value_name("FILE")
+                .help("Write the entries of the JSON dump FILE, creating the database if needed"),
+        )
+        .arg(
+            Arg::new("clear-first")
+                .long("clear-first")
+                .help("Delete every existing key before --import")
+                .action(clap::ArgAction::SetTrue),
+        )
         .arg(
             Arg::new("test")
                 .short('t')
@@ -56,6 +94,22 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     println!("📁 Database path: {}", db_path);
     println!("━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━");
 
+    if let Some(file) = matches.get_one::<String>("import") {
+        let entries = match dump::parse(&std::fs::read_to_string(file)?) {
+            Ok(entries) => entries,
+            Err(e) => {
+                println!("❌ Invalid dump {}: {}", file, e);
+                std::process::exit(2);
+            }
+        };
+        let mut opts = Options::default();
+        opts.create_if_missing(true);
+        let db = DB::open(&opts, db_path)?;
+        let written = dump::import(&db, &entries, matches.get_flag("clear-first"))?;
+        println!("✅ Imported {} entries from {}", written, file);
+        return Ok(());
+    }
+
     // Open RocksDB in read-only mode to avoid lock conflicts
     let mut opts = Options::default();
     opts.create_if_missing(false); // Don't create if doesn't exist
@@ -69,6 +123,31 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         }
     };
 
+    if let Some(other_path) = matches.get_one::<String>("diff") {
+        let other = match DB::open_for_read_only(&opts, other_path, false) {
+            Ok(db) => db,
+            Err(e) => {
+                println!("❌ Failed to open RocksDB at {}: {}", other_path, e);
+                std::process::exit(2);
+            }
+        };
+        let prefix = matches.get_one::<String>("prefix").map(String::as_str);
+        let result = diff::diff(&db, &other, prefix)?;
+        diff::print_diff(&result, db_path, other_path, matches.get_flag("verbose"));
+        // Let migration scripts check the outcome
+        if !result.is_equivalent() {
+            std::process::exit(1);
+        }
+        return Ok(());
+    }
+
+    if let Some(file) = matches.get_one::<String>("export") {
+        let prefix = matches.get_one::<String>("prefix").map(String::as_str);
+        std::fs::write(file, dump::export(&db, prefix)?)?;
+        println!("✅ Exported to {}", file);
+        return Ok(());
+    }
+
     if matches.get_flag("stats") {
         show_database_stats(&db)?;
         return Ok(());
@@ -184,19 +263,19 @@ fn show_all_data(db: &DB) -> Result<(), Box<dyn std::error::Error>> {
         let key = String::from_utf8_lossy(&key_bytes);
         let value = String::from_utf8_lossy(&value_bytes);
 
-        let category = if key.starts_with("cluster/") {
+        let category = if key.starts_with(keys::CLUSTER_PREFIX) {
             "🏗️  Cluster"
-        } else if key.starts_with("nodes/") {
+        } else if key.starts_with(keys::NODE_ADDRESS_PREFIX) {
             "🖥️  Nodes"
-        } else if key.starts_with("Scenario/") {
+        } else if key.starts_with(keys::SCENARIO_PREFIX) {
             "📋 Scenarios"
-        } else if key.starts_with("Package/") {
+        } else if key.starts_with(keys::PACKAGE_PREFIX) {
             "📦 Packages"
-        } else if key.starts_with("Model/") {
+        } else if key.starts_with(keys::MODEL_PREFIX) {
             "🎯 Models"
-        } else if key.starts_with("/metrics/") {
+        } else if key.starts_with(keys::METRICS_PREFIX) {
             "📊 Metrics"
-        } else if key.starts_with("/logs/") {
+        } else if key.starts_with(keys::LOGS_PREFIX) {
             "📝 Logs"
         } else {
             "❓ Other"
@@ -235,7 +314,7 @@ async fn run_helloworld_test(db: &DB) -> Result<(), Box<dyn std::error::Error>>
     let mut test_results = Vec::new();
 
     // Test 1: Check if node registration exists
-    let node_keys = ["cluster/nodes/yh", "nodes/yh"];
+    let node_keys = [keys::node("yh"), keys::node_address("yh")];
     for key in &node_keys {
         match db.get(key.as_bytes())? {
             Some(value) => {
@@ -253,7 +332,7 @@ async fn run_helloworld_test(db: &DB) -> Result<(), Box<dyn std::error::Error>>
     }
 
     // Test 2: Check if helloworld scenario exists
-    let scenario_key = "Scenario/helloworld";
+    let scenario_key = keys::scenario("helloworld");
     match db.get(scenario_key.as_bytes())? {
         Some(value) => {
             let value_str = String::from_utf8_lossy(&value);
@@ -271,7 +350,7 @@ async fn run_helloworld_test(db: &DB) -> Result<(), Box<dyn std::error::Error>>
     }
 
     // Test 3: Check if package information exists
-    let package_key = "Package/helloworld";
+    let package_key = keys::package("helloworld");
     match db.get(package_key.as_bytes())? {
         Some(value) => {
             let value_str = String::from_utf8_lossy(&value);
@@ -288,7 +367,7 @@ async fn run_helloworld_test(db: &DB) -> Result<(), Box<dyn std::error::Error>>
     }
 
     // Test 4: Check if model information exists
-    let model_key = "Model/helloworld";
+    let model_key = keys::model("helloworld");
     match db.get(model_key.as_bytes())? {
         Some(value) => {
             let value_str = String::from_utf8_lossy(&value);